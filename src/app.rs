@@ -1,50 +1,252 @@
 use crate::{
     event::{AppEvent, Event, EventHandler},
-    preview::{
-        MAX_PARQUET_PREVIEW_BYTES, MAX_PARQUET_TABLE_PREVIEW_BYTES, MAX_PREVIEW_BYTES,
-        ParquetSchemaPreview, PreviewData, PreviewFileType, TablePreview, parse_parquet_schema,
-        parse_parquet_table, parse_preview,
-    },
+    help,
+    menu::{Menu, MenuItem, MenuOutcome},
+    motion::{Motion, MotionKey, PendingMotion},
+    mouse::{self, LastClick, ListLayout},
+    palette::{self, Action},
+    preview_pane::PreviewPane,
+    preview_search::{PreviewSearch, PreviewViewport},
+    state::PersistedState,
+    tabs::{Tab, Tabs},
     terminal_icons::{IconSet, detect_terminal_icons},
 };
 use arboard::Clipboard;
-use base64::{Engine as _, engine::general_purpose};
-use chrono::Utc;
-use futures::stream::StreamExt;
-use hmac::{Hmac, Mac};
+use blobrs::{
+    archive::ArchiveKind,
+    audit::{AuditAction, AuditLog, Outcome},
+    azure::{
+        AzureRestClient, BlobSnapshot, ContainerProperties, MAX_BLOB_TAGS, container_store,
+        immutability_hint, tag_filter_expression, validate_container_name, validate_tag,
+        versions_as_of,
+    },
+    cache::BlobCache,
+    commands::{CommandStyle, CommandTarget, download_command},
+    config::Config,
+    credentials::{Credential, UnsupportedCredential},
+    diff::{self, CompareSide, Comparison, DiffLine},
+    edit::{self, EditSession, MAX_EDIT_BYTES},
+    error::BlobrsError,
+    export::{self, ExportRequest, ListingEntry},
+    fetch,
+    filter::{Filter, FilterMode, extension, extensions_in, with_extensions},
+    listing::{EntryKind, FileItem, FolderListing},
+    open,
+    pick::PickOutput,
+    pipe::{self, PipeRequest},
+    preview::{
+        Compression, MAX_COMPRESSED_PREVIEW_BYTES, MAX_PARQUET_TABLE_PREVIEW_BYTES,
+        MAX_PREVIEW_BYTES, ParquetSchemaPreview, PreviewCopy, PreviewData, PreviewFileType,
+        TablePreview, parquet_rows_range, parse_compressed_preview, parse_parquet_rows,
+        parse_parquet_schema, parse_parquet_table,
+    },
+    preview_pager::PreviewPager,
+    search::{
+        FolderSearch, SearchHit, SearchResults, SearchScope, SearchSource, search_accounts,
+        search_tags,
+    },
+    secret::{self, Secret},
+    sync::{self, LocalEntry, RemoteEntry, SyncAction, SyncMode, SyncPlan},
+    tail::TailView,
+    transfer::{
+        FileStatus, FileTransfer, TransferJob, TransferSource, TransferStats, delete_batched,
+        upload_file_with_progress,
+    },
+    usage::UsageScanner,
+    watch::{FolderWatch, WatchDiff},
+};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use futures::stream::{StreamExt, TryStreamExt};
 use object_store::{
-    ObjectStore, ObjectStoreExt, azure::MicrosoftAzureBuilder, path::Path as ObjectPath,
+    ListResult, ObjectStore, ObjectStoreExt, PutMode, PutOptions,
+    azure::MicrosoftAzure,
+    list::{PaginatedListOptions, PaginatedListStore},
+    path::Path as ObjectPath,
 };
 use ratatui::{
-    DefaultTerminal,
-    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    Terminal,
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{
+            DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyModifiers, MouseButton,
+            MouseEvent, MouseEventKind,
+        },
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+    layout::Position,
 };
-use regex::Regex;
-use reqwest;
-use sha2::Sha256;
-use std::fmt::Write as _;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
+use std::io::Write as _;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SortCriteria {
+    #[default]
     Name,
     DateModified,
     DateCreated,
     Size,
 }
 
+/// How blobs are sorted: by what, and whether that order is reversed. Folders always come
+/// first either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SortOrder {
+    pub criteria: SortCriteria,
+    /// Oldest, smallest or Z–A first instead.
+    #[serde(default)]
+    pub reversed: bool,
+}
+
+impl SortOrder {
+    #[must_use]
+    pub const fn new(criteria: SortCriteria) -> Self {
+        Self {
+            criteria,
+            reversed: false,
+        }
+    }
+
+    /// The order picking `criteria` in the sort picker leads to: picking the current
+    /// criteria again reverses it.
+    #[must_use]
+    pub fn then(self, criteria: SortCriteria) -> Self {
+        if criteria == self.criteria {
+            Self {
+                criteria,
+                reversed: !self.reversed,
+            }
+        } else {
+            Self::new(criteria)
+        }
+    }
+
+    /// Which way round the order is, e.g. `newest first`.
+    #[must_use]
+    pub const fn direction(self) -> &'static str {
+        match (self.criteria, self.reversed) {
+            (SortCriteria::Name, false) => "A–Z",
+            (SortCriteria::Name, true) => "Z–A",
+            (SortCriteria::DateModified | SortCriteria::DateCreated, false) => "newest first",
+            (SortCriteria::DateModified | SortCriteria::DateCreated, true) => "oldest first",
+            (SortCriteria::Size, false) => "largest first",
+            (SortCriteria::Size, true) => "smallest first",
+        }
+    }
+}
+
+/// A change to a blob's lease, picked from the lease menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaseAction {
+    /// Acquire for this many seconds, or forever.
+    Acquire {
+        seconds: Option<u32>,
+    },
+    Break,
+    Release,
+}
+
 #[derive(Debug, Clone)]
 pub struct BrowsingState {
     pub object_store: Arc<dyn ObjectStore>,
+    pub container_name: String,
     pub current_path: String,
     pub files: Vec<String>,
     pub file_items: Vec<FileItem>,
     pub selected_index: usize,
+    /// When set, the listing shows the blob versions that were current at this time.
+    pub as_of: Option<chrono::DateTime<chrono::Utc>>,
+    /// How the current folder is listed.
+    pub paging: Paging,
+    /// Entries marked for a batch operation, by full path (folders end with `/`).
+    pub marked: BTreeMap<String, FileItem>,
+}
+
+/// Position within a folder that is listed one page at a time. The first request of a page
+/// is shown up front and the rest of the page is listed in the background.
+#[derive(Clone, Default)]
+pub struct Paging {
+    /// Lists pages server-side; `None` lists the whole folder at once.
+    pub store: Option<Arc<dyn PaginatedListStore>>,
+    /// Continuation tokens of the pages up to the current one; empty on the first page.
+    pub tokens: Vec<String>,
+    /// Continuation token of the next page, if the folder has more entries.
+    pub next_token: Option<String>,
+    /// The rest of the current page, while it is still being listed.
+    pub more: Option<FolderListing>,
+}
+
+impl std::fmt::Debug for Paging {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Paging")
+            .field("paginated", &self.store.is_some())
+            .field("tokens", &self.tokens)
+            .field("next_token", &self.next_token)
+            .field("more", &self.more)
+            .finish()
+    }
+}
+
+impl Paging {
+    /// Page through listings with `store`.
+    #[must_use]
+    pub fn new(store: Arc<dyn PaginatedListStore>) -> Self {
+        Self {
+            store: Some(store),
+            ..Self::default()
+        }
+    }
+
+    /// One-based number of the current page.
+    #[must_use]
+    pub const fn page_number(&self) -> usize {
+        self.tokens.len() + 1
+    }
+
+    /// Whether the folder has more than one page.
+    #[must_use]
+    pub const fn is_paged(&self) -> bool {
+        !self.tokens.is_empty() || self.next_token.is_some()
+    }
+
+    /// Move to the next page, if there is one.
+    pub fn advance(&mut self) -> bool {
+        match self.next_token.take() {
+            Some(token) => {
+                self.tokens.push(token);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move to the previous page, if there is one.
+    pub fn go_back(&mut self) -> bool {
+        self.tokens.pop().is_some()
+    }
+
+    /// Stop listing the current page, before it is listed again.
+    pub fn stop(&mut self) {
+        self.next_token = None;
+        self.more = None;
+    }
+
+    /// Return to the first page, e.g. after changing folder.
+    pub fn reset(&mut self) {
+        self.tokens.clear();
+        self.stop();
+    }
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)] // there is only ever one session
 pub enum Session {
     Selecting,
     Browsing(BrowsingState),
@@ -53,39 +255,46 @@ pub enum Session {
 #[derive(Debug, Clone)]
 pub struct ContainerInfo {
     pub name: String,
+    pub last_modified: Option<chrono::DateTime<chrono::Utc>>,
+    /// Opened recently; listed first.
+    pub recent: bool,
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)] // one value at a time, held by the info popup
 pub enum BlobInfo {
     File {
         name: String,
         size: u64,
-        last_modified: String,
+        last_modified: chrono::DateTime<Utc>,
         etag: Option<String>,
+        /// Blob index tags; `None` when they could not be read.
+        tags: Option<BTreeMap<String, String>>,
+        /// `available`, `leased`, `breaking`, ...; `None` when it could not be read.
+        lease_state: Option<String>,
+        /// `infinite` or `fixed` while leased.
+        lease_duration: Option<String>,
+        created: Option<chrono::DateTime<Utc>>,
+        /// `BlockBlob`, `PageBlob` or `AppendBlob`.
+        blob_type: Option<String>,
+        content_type: Option<String>,
+        content_encoding: Option<String>,
+        /// Base64 `Content-MD5`, when the uploader set one.
+        content_md5: Option<String>,
+        /// Bytes in written page ranges, for page blobs.
+        page_used: Option<u64>,
+        /// Immutability policy expiry and mode, e.g. `2025-01-01 00:00:00 UTC (locked)`.
+        immutable_until: Option<String>,
+        legal_hold: bool,
     },
+    /// Counted in the background by [`App::usage`], so the popup opens straight away.
     Folder {
         name: String,
-        blob_count: usize,
-        total_size: u64,
+        /// Full path of the folder, ending in `/`.
+        path: String,
     },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum EntryKind {
-    File,
-    Folder,
-}
-
-#[derive(Debug, Clone)]
-pub struct FileItem {
-    pub display_name: String, // What to show in the UI (with icon)
-    pub actual_name: String,  // The actual file/folder name
-    pub kind: EntryKind,
-    pub size: Option<u64>,
-    pub last_modified: Option<chrono::DateTime<chrono::Utc>>,
-    pub created: Option<chrono::DateTime<chrono::Utc>>,
-}
-
 #[derive(Debug, Clone)]
 pub enum Modal {
     None,
@@ -94,28 +303,261 @@ pub enum Modal {
     },
     DownloadPicker {
         destination: Option<PathBuf>,
+        /// Recent destinations, then `None` for "Browse".
+        menu: Menu<Option<PathBuf>>,
+        /// Download straight to the configured default directory on Enter.
+        use_default_dir: bool,
+        /// Download this snapshot of the selected blob instead of the selection.
+        snapshot: Option<String>,
+    },
+    SortPicker {
+        menu: Menu<SortCriteria>,
+    },
+    /// Pick the tool to copy an equivalent download command for.
+    CopyCommand {
+        target: CommandTarget,
+        menu: Menu<CommandStyle>,
     },
-    SortPicker,
     Clone {
         input: String,
         original_path: String,
         is_folder: bool,
     },
+    Move {
+        input: String,
+        original_path: String,
+        is_folder: bool,
+    },
     DeleteConfirm {
         input: String,
         target_path: String,
         target_name: String,
         is_folder: bool,
+        /// Size of the blob, unknown for folders.
+        size: Option<u64>,
+        /// Every blob under a folder, listed up front so the totals can be confirmed.
+        plan: Option<FolderPlan>,
+    },
+    DeleteMarked {
+        input: String,
+        /// Every blob of the marked entries.
+        plan: FolderPlan,
+    },
+    TimeTravel {
+        input: String,
+    },
+    NewFolder {
+        input: String,
+    },
+    NewContainer {
+        input: String,
+    },
+    /// Properties of the selected container.
+    ContainerInfo {
+        properties: ContainerProperties,
+        /// Account-wide blob soft delete, `None` when it could not be read.
+        soft_delete: Option<bool>,
+        /// Versioning as seen on the container's first blob, `None` when unknown.
+        versioning: Option<bool>,
+    },
+    /// Confirm deleting an empty container by typing its name.
+    DeleteContainer {
+        name: String,
+        input: String,
+    },
+    /// Lease actions for a blob.
+    LeaseMenu {
+        /// Full blob path.
+        blob_path: String,
+        menu: Menu<LeaseAction>,
+    },
+    /// Snapshots of a blob, newest first.
+    Snapshots {
+        /// Full blob path.
+        blob_path: String,
+        snapshots: Vec<BlobSnapshot>,
+        selected: usize,
+        /// Waiting for `y` to restore the selected snapshot over the blob.
+        confirm_restore: bool,
+    },
+    /// Index tags of a blob, edited locally until saved.
+    EditTags {
+        /// Full blob path.
+        blob_path: String,
+        tags: BTreeMap<String, String>,
+        selected: usize,
+        /// `key=value` being typed for a new tag.
+        input: Option<String>,
+    },
+    /// File types in the folder, to pick which are shown.
+    Extensions {
+        /// Each extension with how many files have it, most common first.
+        counts: Vec<(String, usize)>,
+        shown: BTreeSet<String>,
+        selected: usize,
+    },
+    SyncPreview {
+        local_dir: PathBuf,
+        /// Prefix being synced, ending in `/` (or empty for the container root).
+        prefix: String,
+        mode: SyncMode,
+        local: Vec<LocalEntry>,
+        remote: Vec<RemoteEntry>,
+        plan: SyncPlan,
+        scroll: usize,
+    },
+    EditNote {
+        /// Key of the container or folder the note is attached to.
+        key: String,
+        input: String,
+    },
+    LargeOperationWarning {
+        operation: PendingOperation,
+        plan: FolderPlan,
+    },
+    GlobalSearch {
+        input: String,
+        /// `None` while the query is being typed.
+        results: Option<SearchResults>,
+        selected: usize,
+        scope: SearchScope,
+        /// A folder search still streaming in results.
+        running: Option<FolderSearch>,
+    },
+    /// A blob followed like `tail -f`.
+    Tail {
+        view: TailView,
+        /// Lines scrolled up from the end; 0 keeps following new lines.
+        back: usize,
+    },
+    Jobs {
+        job_index: usize,
+        /// Selected file when a job is expanded to show its files.
+        file_index: Option<usize>,
+    },
+    /// A command typed after `:`, with the highlighted suggestion.
+    Command {
+        input: String,
+        selected: usize,
+    },
+    /// The start of a name typed after `'`, to jump to the first entry starting with it.
+    Jump {
+        prefix: String,
+    },
+    /// Every keybinding, scrolled down `scroll` lines.
+    Help {
+        scroll: usize,
+    },
+    /// Sizes of what is inside a folder, largest first, counted in the background.
+    Usage {
+        /// Folder broken down, ending in `/` (or empty for the container root).
+        folder: String,
+        selected: usize,
+    },
+    /// Two blobs, or a blob and a local file, compared.
+    Diff {
+        /// What was compared with what.
+        title: String,
+        comparison: Comparison,
+        /// Lines scrolled down.
+        scroll: usize,
+    },
+    /// Changes made to a blob in an editor, waiting to be uploaded.
+    EditUpload {
+        session: EditSession,
+        /// Unified diff from the downloaded text to the edited one.
+        diff: Vec<DiffLine>,
     },
+    /// Shown at startup when more than one credential was found.
+    CredentialPicker {
+        credentials: Vec<Credential>,
+        /// Listed below the credentials, but cannot be picked.
+        unsupported: Vec<UnsupportedCredential>,
+        selected: usize,
+    },
+}
+
+/// A folder operation waiting for confirmation because its plan exceeds the configured limits,
+/// or a sync that would delete files.
+#[derive(Debug, Clone)]
+pub enum PendingOperation {
+    Download {
+        folder_name: String,
+        destination: PathBuf,
+    },
+    Delete {
+        target_path: String,
+    },
+    DownloadMarked {
+        destination: PathBuf,
+    },
+    DeleteMarked,
+    Move {
+        source: String,
+        destination: String,
+        /// Every blob to move, with where it goes.
+        moves: Vec<(ObjectPath, ObjectPath)>,
+        /// The other pane's tab, for moves between panes.
+        other_tab: Option<usize>,
+    },
+    Sync {
+        local_dir: PathBuf,
+        prefix: String,
+        plan: SyncPlan,
+    },
+}
+
+/// A blob that a folder operation will touch.
+#[derive(Debug, Clone)]
+pub struct PlannedBlob {
+    pub location: ObjectPath,
+    pub version_id: Option<String>,
+    pub size: u64,
+    pub e_tag: Option<String>,
+}
+
+/// Blobs under a folder, listed before an operation starts so its totals are known up front.
+#[derive(Debug, Clone, Default)]
+pub struct FolderPlan {
+    pub blobs: Vec<PlannedBlob>,
+    /// Listing errors; these entries are reported during the operation.
+    pub errors: Vec<String>,
+}
+
+impl FolderPlan {
+    #[must_use]
+    pub fn total_bytes(&self) -> u64 {
+        self.blobs.iter().map(|blob| blob.size).sum()
+    }
 }
 
+/// Most entries asked for in one listing request, so the start of a page shows quickly.
+const LIST_REQUEST_SIZE: usize = 1000;
+
+/// How many server-side copies run at the same time when moving a folder.
+const MOVE_CONCURRENCY: usize = 16;
+
+/// How many page blobs have their used ranges looked up at the same time.
+const PAGE_USAGE_CONCURRENCY: usize = 8;
+
+/// How long an error or success message stays up: five seconds at 30 ticks a second.
+const MESSAGE_TICKS: usize = 150;
+
+/// Zero-byte blob that keeps an otherwise empty virtual folder in the listing.
+pub const FOLDER_PLACEHOLDER: &str = ".keep";
+
+/// Word to type to confirm deleting the marked entries.
+pub const DELETE_MARKED_CONFIRMATION: &str = "delete";
+
 #[derive(Debug, Clone)]
 pub enum AsyncOp {
     None,
     LoadingContainers,
     LoadingFiles,
     Downloading(DownloadProgress),
+    Uploading(UploadProgress),
     Cloning(CloneProgress),
+    Moving(CloneProgress),
     Deleting(DeleteProgress),
 }
 
@@ -137,6 +579,20 @@ pub enum Search {
 pub struct UiToggles {
     pub show_preview: bool,
     pub is_loading_preview: bool,
+    /// Include soft-deleted blobs in listings.
+    pub show_deleted: bool,
+    /// Show blob counts and total sizes on the container list.
+    pub show_container_usage: bool,
+    /// Show the history of operations below the current view.
+    pub show_audit_log: bool,
+    /// Show the neighbouring tab beside the shown one, as the other of two panes.
+    pub show_two_panes: bool,
+    /// List every blob under the current folder by its path from there, without folders.
+    pub flatten: bool,
+    /// Show a preview of the selected blob beside the list, following the selection.
+    pub preview_pane: bool,
+    /// How search queries are read: fuzzily, as a glob or as a regex.
+    pub filter_mode: FilterMode,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -153,10 +609,12 @@ pub struct App {
     pub events: EventHandler,
     /// Current application session.
     pub session: Session,
+    /// Other open locations, shown one at a time.
+    pub tabs: Tabs,
     /// Azure Storage Account name.
     pub storage_account: String,
     /// Azure Storage Access Key.
-    pub access_key: String,
+    pub access_key: Secret,
     /// List of available containers (may be filtered during search).
     pub containers: Vec<ContainerInfo>,
     /// Full list of all containers from Azure (never filtered).
@@ -177,8 +635,10 @@ pub struct App {
     pub modal: Modal,
     /// Independent UI toggles.
     pub ui: UiToggles,
-    /// Current sort criteria for blobs.
-    pub sort_criteria: SortCriteria,
+    /// Current sort order for blobs.
+    pub sort: SortOrder,
+    /// Order of the container list: by name or by date modified.
+    pub container_sort: SortCriteria,
     /// Preview data for the current file.
     pub preview_data: Option<PreviewData>,
     /// Preview file type.
@@ -195,9 +655,75 @@ pub struct App {
     pub parquet_table_data: Option<TablePreview>,
     /// Cached parquet metadata preview data for fast toggling.
     pub parquet_schema_data: Option<ParquetSchemaPreview>,
+    /// Search within the preview, started with `/`.
+    pub preview_search: Option<PreviewSearch>,
+    /// Where the preview was last drawn.
+    pub preview_viewport: RefCell<PreviewViewport>,
+    /// What the preview pane beside the list shows.
+    pub preview_pane: PreviewPane,
+    /// Fetches more of a long text preview as it is scrolled.
+    pub preview_pager: Option<PreviewPager>,
+    /// Lists the open folder again every few seconds while it is watched.
+    pub watch: Option<FolderWatch>,
+    /// State persisted between runs.
+    pub state: PersistedState,
+    /// User configuration.
+    pub config: Config,
+    /// On-disk cache of blob contents, if enabled.
+    pub cache: Option<BlobCache>,
+    /// Background folder transfers.
+    pub jobs: Vec<TransferJob>,
+    /// Blob or folder delete running in the background, shown by [`AsyncOp::Deleting`].
+    delete_task: Option<DeleteTask>,
+    /// File upload running in the background, shown by [`AsyncOp::Uploading`].
+    upload_task: Option<UploadTask>,
+    /// Move running in the background, shown by [`AsyncOp::Moving`].
+    move_task: Option<MoveTask>,
+    /// Throughput of every transfer job, shown in the status bar.
+    pub transfer_stats: TransferStats,
+    /// Blob or folder marked with `Y`, to be copied by `P`.
+    pub yanked: Option<Yank>,
+    /// Lease ids acquired this session, by `container/blob path`, so they can be released.
+    pub leases: BTreeMap<String, String>,
+    /// Blob counts and sizes of containers and folders, scanned on demand.
+    pub usage: UsageScanner,
+    /// Operations that changed blobs or transferred data this session.
+    pub audit: AuditLog,
+    /// Ticks since startup, which drive the spinner.
+    pub ticks: usize,
+    /// The message shown in the status bar and the tick it first appeared, so it can expire.
+    pub message_shown: Option<(String, usize)>,
+    /// Count or `g` typed ahead of a list motion.
+    pub pending_motion: PendingMotion,
+    /// Where the container or blob list was last drawn, for paging and mouse clicks.
+    pub list_layout: RefCell<ListLayout>,
+    /// The last click on a list entry, for telling double clicks apart.
+    pub last_click: Option<LastClick>,
+    /// A blob downloaded to be opened in an editor once the terminal is free.
+    pub pending_edit: Option<EditSession>,
+    /// A blob to pipe into a shell command once the terminal is free.
+    pub pending_pipe: Option<PipeRequest>,
+    /// The file last downloaded on its own, for `o` to open.
+    pub last_download: Option<PathBuf>,
+    /// Set by `--pick`: Enter on a blob quits with it picked, printed like this.
+    pub pick: Option<PickOutput>,
+    /// What was picked, for printing once the terminal is restored.
+    pub picked: Vec<String>,
 }
 
+/// Blobs and folders marked for a server-side copy.
 #[derive(Debug, Clone)]
+pub struct Yank {
+    /// Client for the container the items live in.
+    pub object_store: Arc<dyn ObjectStore>,
+    pub container: String,
+    /// Folder the items were yanked from; empty for the container root.
+    pub folder: String,
+    /// Full paths; folders end with `/`.
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct DeleteProgress {
     pub current_file: String,
     pub files_completed: usize,
@@ -205,6 +731,66 @@ pub struct DeleteProgress {
     pub error_message: Option<String>,
 }
 
+impl DeleteProgress {
+    /// Count one blob as deleted, or note why it was not.
+    fn record(&mut self, location: &str, error: Option<String>) {
+        self.current_file = location.to_string();
+        match error {
+            None => self.files_completed += 1,
+            // Continue with other files even if one fails
+            Some(e) => {
+                let reason = immutability_hint(&e).map_or(e.as_str(), |hint| hint);
+                self.error_message = Some(format!("Failed to delete {location}: {reason}"));
+            }
+        }
+    }
+}
+
+/// A delete running in the background; its progress is copied to [`AsyncOp::Deleting`] on
+/// every tick, and the listing is refreshed once it is done.
+#[derive(Debug)]
+struct DeleteTask {
+    /// Blob path, folder path ending with `/`, or a description of the marked entries.
+    target_path: String,
+    /// Cursor position to return to after the refresh.
+    selected_index: usize,
+    progress: Arc<Mutex<DeleteProgress>>,
+    handle: tokio::task::JoinHandle<color_eyre::Result<()>>,
+}
+
+/// An upload running in the background; its progress is copied to [`AsyncOp::Uploading`] on
+/// every tick, and the listing is refreshed once it is done.
+#[derive(Debug)]
+struct UploadTask {
+    local_path: PathBuf,
+    blob_path: String,
+    progress: Arc<Mutex<UploadProgress>>,
+    handle: tokio::task::JoinHandle<Result<(), BlobrsError>>,
+}
+
+/// A move running in the background; its progress is copied to [`AsyncOp::Moving`] on every
+/// tick, and the listing is refreshed once it is done.
+#[derive(Debug)]
+struct MoveTask {
+    /// What is moved: a blob or folder path, or a description of the marked entries.
+    source: String,
+    destination: String,
+    /// The other pane's tab, refreshed as well when the move went there.
+    other_tab: Option<usize>,
+    progress: Arc<Mutex<CloneProgress>>,
+    handle: tokio::task::JoinHandle<MoveOutcome>,
+}
+
+/// How a background move ended.
+#[derive(Debug)]
+enum MoveOutcome {
+    Moved,
+    /// A copy failed, so nothing was moved; says what was rolled back.
+    Aborted(String),
+    /// Everything was copied, but these originals could not be deleted.
+    OriginalsLeft(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct CloneProgress {
     pub current_file: String,
@@ -223,6 +809,16 @@ pub struct DownloadProgress {
     pub error_message: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct UploadProgress {
+    pub current_file: String,
+    pub files_completed: usize,
+    pub total_files: usize,
+    pub bytes_uploaded: u64,
+    pub total_bytes: Option<u64>,
+    pub error_message: Option<String>,
+}
+
 impl std::fmt::Debug for App {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("App")
@@ -239,11 +835,12 @@ impl std::fmt::Debug for App {
             .field("icons", &self.icons)
             .field("modal", &self.modal)
             .field("ui", &self.ui)
-            .field("sort_criteria", &self.sort_criteria)
+            .field("sort", &self.sort)
             .field("preview_file_type", &self.preview_file_type)
             .field("preview_scroll", &self.preview_scroll)
             .field("preview_error", &self.preview_error)
             .field("preview_selected_row", &self.preview_selected_row)
+            .field("preview_search", &self.preview_search)
             .field("parquet_preview_mode", &self.parquet_preview_mode)
             .finish_non_exhaustive()
     }
@@ -252,14 +849,38 @@ impl std::fmt::Debug for App {
 impl App {
     /// Constructs a new instance of [`App`].
     ///
+    /// `credentials` are those found by [`blobrs::credentials::resolve`]. When there is more than one,
+    /// the user picks before any containers are listed, with the `unsupported` ones listed as
+    /// such. `config_error` and `credential_errors` are shown as warnings.
+    ///
     /// # Errors
     ///
     /// Returns an error if loading containers from Azure Storage fails.
-    pub async fn new(storage_account: String, access_key: String) -> color_eyre::Result<Self> {
+    pub async fn new(
+        config: Config,
+        config_error: Option<BlobrsError>,
+        credentials: Vec<Credential>,
+        unsupported: Vec<UnsupportedCredential>,
+        credential_errors: Vec<String>,
+    ) -> color_eyre::Result<Self> {
+        let (storage_account, access_key) = match credentials.as_slice() {
+            [only] => (only.account.clone(), only.access_key.clone()),
+            _ => (String::new(), Secret::default()),
+        };
+
+        let cache = BlobCache::from_config(&config.cache);
+        let audit = AuditLog::new(config.audit.file());
+        let container_sort = if config.browse.sort_containers_by_modified {
+            SortCriteria::DateModified
+        } else {
+            SortCriteria::Name
+        };
+
         let mut app = Self {
             running: true,
             events: EventHandler::new(),
             session: Session::Selecting,
+            tabs: Tabs::default(),
             storage_account,
             access_key,
             containers: Vec::new(),
@@ -274,8 +895,16 @@ impl App {
             ui: UiToggles {
                 show_preview: false,
                 is_loading_preview: false,
+                show_deleted: false,
+                show_container_usage: false,
+                show_audit_log: false,
+                show_two_panes: false,
+                flatten: false,
+                preview_pane: false,
+                filter_mode: FilterMode::default(),
             },
-            sort_criteria: SortCriteria::Name,
+            sort: SortOrder::default(),
+            container_sort,
             preview_data: None,
             preview_file_type: None,
             preview_scroll: (0, 0),
@@ -284,24 +913,90 @@ impl App {
             parquet_preview_mode: None,
             parquet_table_data: None,
             parquet_schema_data: None,
+            state: PersistedState::load(),
+            config,
+            cache,
+            jobs: Vec::new(),
+            delete_task: None,
+            upload_task: None,
+            move_task: None,
+            transfer_stats: TransferStats::default(),
+            yanked: None,
+            leases: BTreeMap::new(),
+            usage: UsageScanner::default(),
+            audit,
+            ticks: 0,
+            message_shown: None,
+            preview_search: None,
+            preview_viewport: RefCell::default(),
+            preview_pane: PreviewPane::default(),
+            watch: None,
+            preview_pager: None,
+            pending_motion: PendingMotion::default(),
+            list_layout: RefCell::default(),
+            last_click: None,
+            pending_edit: None,
+            pending_pipe: None,
+            last_download: None,
+            pick: None,
+            picked: Vec::new(),
         };
 
-        // Load container list
-        app.load_containers().await?;
+        if credentials.len() > 1 {
+            app.modal = Modal::CredentialPicker {
+                credentials,
+                unsupported,
+                selected: 0,
+            };
+        } else {
+            // Load container list
+            app.load_containers().await?;
+        }
+
+        // Keep a failed container listing visible over these warnings
+        if app.error_message.is_none() {
+            if let Some(e) = config_error {
+                app.error_message = Some(format!("{e} (using defaults)"));
+            } else if !credential_errors.is_empty() {
+                app.error_message = Some(format!(
+                    "Skipped credentials: {}",
+                    credential_errors.join("; ")
+                ));
+            }
+        }
         Ok(app)
     }
 
-    /// Run the application's main loop.
+    /// Run the application's main loop in `terminal`, drawn on stdout or, when picking, on
+    /// stderr. Returns what was picked in `--pick` mode.
     ///
     /// # Errors
     ///
     /// Returns an error if terminal drawing or event handling fails.
-    pub async fn run(mut self, terminal: &mut DefaultTerminal) -> color_eyre::Result<()> {
+    pub async fn run<W: std::io::Write>(
+        mut self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+    ) -> color_eyre::Result<Vec<String>> {
+        let mut logged_error = None;
         while self.running {
             terminal.draw(|frame| frame.render_widget(&self, frame.area()))?;
             self.handle_events().await?;
+            if let Some(session) = self.pending_edit.take() {
+                self.edit_in_terminal(terminal, session).await?;
+            }
+            if let Some(request) = self.pending_pipe.take() {
+                self.pipe_in_terminal(terminal, request).await?;
+            }
+            if self.error_message != logged_error {
+                // Service and client errors can echo signed URLs or keys
+                if let Some(error) = &mut self.error_message {
+                    *error = secret::redact(error);
+                    tracing::error!("{error}");
+                }
+                logged_error.clone_from(&self.error_message);
+            }
         }
-        Ok(())
+        Ok(self.picked)
     }
 
     /// Handle incoming events from the terminal.
@@ -311,12 +1006,21 @@ impl App {
     /// Returns an error if event reception or key handling fails.
     pub async fn handle_events(&mut self) -> color_eyre::Result<()> {
         match self.events.next()? {
-            Event::Tick => self.tick(),
-            Event::Crossterm(event) => {
-                if let ratatui::crossterm::event::Event::Key(key_event) = event {
+            Event::Tick => {
+                self.tick();
+                self.finish_delete().await;
+                self.finish_upload().await;
+                self.finish_move().await;
+            }
+            Event::Crossterm(event) => match event {
+                ratatui::crossterm::event::Event::Key(key_event) => {
                     self.handle_key_event(key_event).await?;
                 }
-            }
+                ratatui::crossterm::event::Event::Mouse(mouse_event) => {
+                    self.handle_mouse_event(mouse_event).await;
+                }
+                _ => {}
+            },
             Event::App(app_event) => match app_event {
                 AppEvent::Quit => self.quit(),
             },
@@ -324,13 +1028,89 @@ impl App {
         Ok(())
     }
 
+    /// Handle a mouse event in the container or blob list: the wheel scrolls, a click selects,
+    /// a double click opens, and a click on a folder in the path goes to it.
+    pub async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        if !matches!(self.modal, Modal::None) || self.ui.show_preview || self.blocks_input() {
+            return;
+        }
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+            MouseEventKind::ScrollDown => {
+                self.move_selection(Motion::Down, Some(mouse::SCROLL_ROWS))
+            }
+            MouseEventKind::ScrollUp => self.move_selection(Motion::Up, Some(mouse::SCROLL_ROWS)),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let (entry, crumb) = {
+                    let layout = self.list_layout.borrow();
+                    (
+                        layout.entry_at(position),
+                        layout.crumb_at(position).map(str::to_string),
+                    )
+                };
+                if let Some(prefix) = crumb
+                    && self.is_browsing()
+                {
+                    self.last_click = None;
+                    if let Err(e) = self.open_prefix(prefix).await {
+                        self.error_message =
+                            Some(format!("Open folder failed: {}", describe_error(&e)));
+                    }
+                    return;
+                }
+                let Some(entry) = entry else {
+                    return;
+                };
+                let double = self.last_click.is_some_and(|click| click.is_double(entry));
+                self.last_click = (!double).then(|| LastClick::new(entry));
+                self.move_selection(Motion::First, Some(entry + 1));
+                if !double {
+                    return;
+                }
+                if self.is_selecting() {
+                    if let Err(e) = self.select_container().await {
+                        self.error_message = Some(format!(
+                            "Failed to select container: {}",
+                            describe_error(&e)
+                        ));
+                    }
+                } else if let Err(e) = self.enter_directory().await {
+                    self.error_message =
+                        Some(format!("Enter directory failed: {}", describe_error(&e)));
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// Handles the key events and updates the state of [`App`].
     ///
     /// # Errors
     ///
     /// Returns an error if an async operation triggered by a key event fails.
     #[allow(clippy::too_many_lines)]
-    pub async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<()> {
+    pub async fn handle_key_event(&mut self, mut key_event: KeyEvent) -> color_eyre::Result<()> {
+        // Nothing else works until a credential is picked
+        if self.is_modal_credential_picker() {
+            return self.handle_credential_picker_key_event(key_event).await;
+        }
+
+        // Commands that stand for a key carry on as that key
+        if self.is_modal_command() {
+            match self.handle_command_key_event(key_event).await {
+                Some(key) => key_event = key,
+                None => return Ok(()),
+            }
+        }
+
+        // Keys other than the name being typed end a jump and carry on as themselves
+        if self.is_modal_jump() {
+            match self.handle_jump_key_event(key_event) {
+                Some(key) => key_event = key,
+                None => return Ok(()),
+            }
+        }
+
         // Handle delete dialog separately
         if self.is_modal_delete_dialog() {
             return self.handle_delete_dialog_key_event(key_event).await;
@@ -341,6 +1121,134 @@ impl App {
             return self.handle_clone_dialog_key_event(key_event).await;
         }
 
+        // Handle move dialog separately
+        if self.is_modal_move_dialog() {
+            return self.handle_move_dialog_key_event(key_event).await;
+        }
+
+        // Handle time-travel prompt separately
+        if self.is_modal_time_travel() {
+            return self.handle_time_travel_key_event(key_event).await;
+        }
+
+        // Handle new folder prompt separately
+        if self.is_modal_new_folder() {
+            return self.handle_new_folder_key_event(key_event).await;
+        }
+
+        // Handle container prompts separately
+        if self.is_modal_container_info() {
+            if matches!(
+                key_event.code,
+                KeyCode::Esc | KeyCode::Left | KeyCode::Enter | KeyCode::Char('h' | 'i')
+            ) {
+                self.close_modal();
+            }
+            return Ok(());
+        }
+        if self.is_modal_new_container() {
+            self.handle_new_container_key_event(key_event).await;
+            return Ok(());
+        }
+        if self.is_modal_delete_container() {
+            self.handle_delete_container_key_event(key_event).await;
+            return Ok(());
+        }
+
+        // Handle menus separately
+        if self.is_modal_sort_picker() {
+            self.handle_sort_picker_key_event(key_event).await;
+            return Ok(());
+        }
+        if self.is_modal_copy_command() {
+            self.handle_copy_command_key_event(key_event);
+            return Ok(());
+        }
+        if self.is_modal_download_picker() {
+            return self.handle_download_picker_key_event(key_event).await;
+        }
+
+        // Handle sync preview separately
+        if self.is_modal_sync_preview() {
+            return self.handle_sync_preview_key_event(key_event).await;
+        }
+
+        // Handle note editor separately
+        if self.is_modal_edit_note() {
+            self.handle_edit_note_key_event(key_event);
+            return Ok(());
+        }
+
+        // Handle lease menu separately
+        if self.is_modal_lease_menu() {
+            self.handle_lease_menu_key_event(key_event).await;
+            return Ok(());
+        }
+
+        // Handle snapshots view separately
+        if self.is_modal_snapshots() {
+            self.handle_snapshots_key_event(key_event).await;
+            return Ok(());
+        }
+
+        // Handle tag editor separately
+        if self.is_modal_edit_tags() {
+            self.handle_edit_tags_key_event(key_event).await;
+            return Ok(());
+        }
+
+        // Handle the file type picker separately
+        if self.is_modal_extensions() {
+            self.handle_extensions_key_event(key_event);
+            return Ok(());
+        }
+
+        // Handle tail view separately
+        if self.is_modal_tail() {
+            self.handle_tail_key_event(key_event);
+            return Ok(());
+        }
+
+        // Handle jobs panel separately
+        if self.is_modal_jobs() {
+            self.handle_jobs_key_event(key_event);
+            return Ok(());
+        }
+
+        // Handle help overlay separately
+        if self.is_modal_help() {
+            self.handle_help_key_event(key_event);
+            return Ok(());
+        }
+
+        // Handle the size breakdown separately
+        if self.is_modal_usage() {
+            self.handle_usage_key_event(key_event);
+            return Ok(());
+        }
+
+        // Handle the comparison popup separately
+        if self.is_modal_diff() {
+            self.handle_diff_key_event(key_event);
+            return Ok(());
+        }
+
+        // Handle cross-account search separately
+        if self.is_modal_global_search() {
+            return self.handle_global_search_key_event(key_event).await;
+        }
+
+        // Handle the edit upload prompt separately
+        if self.is_modal_edit_upload() {
+            self.handle_edit_upload_key_event(key_event).await;
+            return Ok(());
+        }
+
+        // Handle large operation warning separately
+        if self.is_modal_large_operation_warning() {
+            return self.handle_large_operation_key_event(key_event).await;
+        }
+
         // Handle search mode separately
         if self.is_searching_containers() && self.is_selecting() {
             return self.handle_container_search_key_event(key_event);
@@ -355,7 +1263,27 @@ impl App {
             return Ok(());
         }
 
-        // Global keys
+        // Paging and searching within the preview
+        if matches!(self.modal, Modal::None)
+            && self.ui.show_preview
+            && self.handle_preview_key_event(key_event)
+        {
+            return Ok(());
+        }
+
+        // Motions through the container or blob list
+        if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+            match self.pending_motion.handle(key_event) {
+                MotionKey::Pending => return Ok(()),
+                MotionKey::Move(motion, count) => {
+                    self.move_selection(motion, count);
+                    return Ok(());
+                }
+                MotionKey::Other => {}
+            }
+        }
+
+        // Global keys
         match key_event.code {
             KeyCode::Char('q') => {
                 self.events.send(AppEvent::Quit);
@@ -365,6 +1293,93 @@ impl App {
                 self.events.send(AppEvent::Quit);
                 return Ok(());
             }
+            KeyCode::Char('F') if matches!(self.modal, Modal::None) && !self.ui.show_preview => {
+                self.open_global_search(SearchScope::Accounts);
+                return Ok(());
+            }
+            // Terminals send Ctrl-/ as Ctrl-7 unless they report keys unambiguously
+            KeyCode::Char('/' | '7')
+                if key_event.modifiers == KeyModifiers::CONTROL
+                    && matches!(self.modal, Modal::None)
+                    && !self.ui.show_preview =>
+            {
+                self.open_global_search(SearchScope::Folder);
+                return Ok(());
+            }
+            KeyCode::Char(':') if matches!(self.modal, Modal::None) && !self.ui.show_preview => {
+                self.open_command_mode();
+                return Ok(());
+            }
+            KeyCode::Char('p')
+                if key_event.modifiers == KeyModifiers::CONTROL
+                    && matches!(self.modal, Modal::None)
+                    && !self.ui.show_preview =>
+            {
+                self.open_command_mode();
+                return Ok(());
+            }
+            KeyCode::Char('\'') if matches!(self.modal, Modal::None) && !self.ui.show_preview => {
+                self.modal = Modal::Jump {
+                    prefix: String::new(),
+                };
+                return Ok(());
+            }
+            KeyCode::Char('?') if matches!(self.modal, Modal::None) => {
+                self.modal = Modal::Help { scroll: 0 };
+                return Ok(());
+            }
+            KeyCode::Char('J') if matches!(self.modal, Modal::None) && !self.ui.show_preview => {
+                self.open_jobs_panel();
+                return Ok(());
+            }
+            KeyCode::Char('H') if matches!(self.modal, Modal::None) && !self.ui.show_preview => {
+                self.ui.show_audit_log = !self.ui.show_audit_log;
+                return Ok(());
+            }
+            KeyCode::Char('t')
+                if key_event.modifiers == KeyModifiers::CONTROL
+                    && matches!(self.modal, Modal::None)
+                    && !self.ui.show_preview =>
+            {
+                self.new_tab();
+                return Ok(());
+            }
+            KeyCode::Char('w')
+                if key_event.modifiers == KeyModifiers::CONTROL
+                    && matches!(self.modal, Modal::None)
+                    && !self.ui.show_preview =>
+            {
+                self.close_tab();
+                return Ok(());
+            }
+            KeyCode::Tab if matches!(self.modal, Modal::None) && !self.ui.show_preview => {
+                self.next_tab();
+                return Ok(());
+            }
+            KeyCode::Char('|') if matches!(self.modal, Modal::None) && !self.ui.show_preview => {
+                self.toggle_two_panes();
+                return Ok(());
+            }
+            KeyCode::F(5)
+                if self.shows_two_panes()
+                    && self.is_browsing()
+                    && matches!(self.modal, Modal::None) =>
+            {
+                if let Err(e) = self.copy_to_other_pane().await {
+                    self.error_message = Some(format!("Copy failed: {}", describe_error(&e)));
+                }
+                return Ok(());
+            }
+            KeyCode::F(6)
+                if self.shows_two_panes()
+                    && self.is_browsing()
+                    && matches!(self.modal, Modal::None) =>
+            {
+                if let Err(e) = self.move_to_other_pane().await {
+                    self.error_message = Some(format!("Move failed: {}", describe_error(&e)));
+                }
+                return Ok(());
+            }
             _ => {}
         }
 
@@ -379,16 +1394,27 @@ impl App {
                 KeyCode::Char('/') => {
                     self.enter_container_search_mode();
                 }
+                KeyCode::Char('N') => self.open_note_editor(),
+                KeyCode::Char('i') => self.open_container_info().await,
+                KeyCode::Char('s') => self.toggle_container_usage(),
+                KeyCode::Char('o') => self.open_container_sort_picker(),
+                KeyCode::Char('S') => self.rescan_selected_container(),
+                KeyCode::Char('n') => self.open_new_container_dialog(),
+                KeyCode::Char('x') | KeyCode::Delete => self.open_delete_container_dialog().await,
                 KeyCode::Up | KeyCode::Char('k') => self.move_container_up(),
                 KeyCode::Down | KeyCode::Char('j') => self.move_container_down(),
                 KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
                     if let Err(e) = self.select_container().await {
-                        self.error_message = Some(format!("Failed to select container: {e}"));
+                        self.error_message = Some(format!(
+                            "Failed to select container: {}",
+                            describe_error(&e)
+                        ));
                     }
                 }
                 KeyCode::Char('r') | KeyCode::F(5) => {
                     if let Err(e) = self.load_containers().await {
-                        self.error_message = Some(format!("Refresh failed: {e}"));
+                        self.error_message =
+                            Some(format!("Refresh failed: {}", describe_error(&e)));
                     }
                 }
                 _ => {
@@ -404,10 +1430,11 @@ impl App {
                     }
                 }
                 KeyCode::Char('r') | KeyCode::F(5) => {
-                    if !self.is_modal_blob_info()
-                        && let Err(e) = self.refresh_files().await
-                    {
-                        self.error_message = Some(format!("Refresh failed: {e}"));
+                    if self.is_modal_blob_info() {
+                        self.recount_folder_info();
+                    } else if let Err(e) = self.refresh_files().await {
+                        self.error_message =
+                            Some(format!("Refresh failed: {}", describe_error(&e)));
                     }
                 }
                 KeyCode::Char('i') => {
@@ -415,14 +1442,13 @@ impl App {
                         && !self.ui.show_preview
                         && let Err(e) = self.show_blob_info().await
                     {
-                        self.error_message = Some(format!("Failed to get blob info: {e}"));
+                        self.error_message =
+                            Some(format!("Failed to get blob info: {}", describe_error(&e)));
                     }
                 }
                 KeyCode::Char('p') => {
                     if !self.is_modal_blob_info()
-                        && !self.is_modal_download_picker()
                         && !self.is_downloading()
-                        && !self.is_modal_sort_picker()
                         && !self.is_modal_clone_dialog()
                         && !self.is_cloning()
                         && !self.is_modal_delete_dialog()
@@ -434,7 +1460,8 @@ impl App {
                         } else {
                             // Toggle on - load preview
                             if let Err(e) = self.load_preview().await {
-                                self.error_message = Some(format!("Preview failed: {e}"));
+                                self.error_message =
+                                    Some(format!("Preview failed: {}", describe_error(&e)));
                             }
                         }
                     }
@@ -445,58 +1472,37 @@ impl App {
                     }
                 }
                 KeyCode::Char('d') => {
-                    if !self.is_modal_blob_info()
-                        && !self.is_modal_download_picker()
-                        && !self.is_downloading()
-                        && !self.is_modal_sort_picker()
-                    {
+                    if !self.is_modal_blob_info() && !self.is_downloading() {
                         self.show_download_picker();
                     }
                 }
-                KeyCode::Char('s') => {
-                    if !self.is_modal_blob_info()
-                        && !self.is_modal_download_picker()
-                        && !self.is_downloading()
-                        && !self.is_modal_sort_picker()
-                    {
-                        self.modal = Modal::SortPicker;
-                    } else if self.is_modal_sort_picker() {
-                        // Handle sort selection
-                        if let Err(e) = self.apply_sort(SortCriteria::Size) {
-                            self.error_message = Some(format!("Failed to sort: {e}"));
-                        }
-                        self.close_modal();
-                    }
-                }
-                KeyCode::Char('n') => {
-                    if self.is_modal_sort_picker() {
-                        if let Err(e) = self.apply_sort(SortCriteria::Name) {
-                            self.error_message = Some(format!("Failed to sort: {e}"));
+                KeyCode::Char('D') => {
+                    if !self.is_modal_blob_info() && !self.is_downloading() {
+                        // Choose a destination even if a default download directory is set
+                        self.show_download_picker();
+                        if let Modal::DownloadPicker {
+                            use_default_dir, ..
+                        } = &mut self.modal
+                        {
+                            *use_default_dir = false;
                         }
-                        self.close_modal();
                     }
                 }
-                KeyCode::Char('m') => {
-                    if self.is_modal_sort_picker() {
-                        if let Err(e) = self.apply_sort(SortCriteria::DateModified) {
-                            self.error_message = Some(format!("Failed to sort: {e}"));
-                        }
-                        self.close_modal();
+                KeyCode::Char('s') => {
+                    if self.is_modal_blob_info() {
+                        self.open_snapshots().await;
+                    } else if !self.is_downloading() {
+                        self.open_sort_picker();
                     }
                 }
-                KeyCode::Char('t') => {
-                    if self.is_modal_sort_picker() {
-                        if let Err(e) = self.apply_sort(SortCriteria::DateCreated) {
-                            self.error_message = Some(format!("Failed to sort: {e}"));
-                        }
-                        self.close_modal();
+                KeyCode::Char('n') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_new_folder_dialog();
                     }
                 }
                 KeyCode::Char('y') => {
                     if !self.is_modal_blob_info()
-                        && !self.is_modal_download_picker()
                         && !self.is_downloading()
-                        && !self.is_modal_sort_picker()
                         && !self.is_modal_clone_dialog()
                         && !self.is_cloning()
                     {
@@ -506,11 +1512,48 @@ impl App {
                         }
                     }
                 }
+                KeyCode::Char('Y') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.yank_selected();
+                    }
+                }
+                KeyCode::Char('C') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_copy_command_picker();
+                    }
+                }
+                KeyCode::Char(' ') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.toggle_mark();
+                    }
+                }
+                KeyCode::Char('a') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.mark_all();
+                    }
+                }
+                KeyCode::Char('A') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.invert_marks();
+                    }
+                }
+                KeyCode::Char('P') => {
+                    if matches!(self.modal, Modal::None)
+                        && !self.ui.show_preview
+                        && let Err(e) = self.paste_yanked().await
+                    {
+                        self.async_op = AsyncOp::None;
+                        self.error_message = Some(format!("Paste failed: {e}"));
+                    }
+                }
+                KeyCode::F(2) | KeyCode::Char('R') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_move_dialog();
+                    }
+                }
                 KeyCode::Char('c') => {
                     if !self.is_modal_blob_info()
-                        && !self.is_modal_download_picker()
                         && !self.is_downloading()
-                        && !self.is_modal_sort_picker()
                         && !self.is_modal_clone_dialog()
                         && !self.is_cloning()
                         && !self.is_modal_delete_dialog()
@@ -522,82 +1565,217 @@ impl App {
                 }
                 KeyCode::Char('x') | KeyCode::Delete => {
                     if !self.is_modal_blob_info()
-                        && !self.is_modal_download_picker()
                         && !self.is_downloading()
-                        && !self.is_modal_sort_picker()
                         && !self.is_modal_clone_dialog()
                         && !self.is_cloning()
                         && !self.is_modal_delete_dialog()
                         && !self.is_deleting()
                     {
                         // Open delete dialog
-                        self.open_delete_dialog();
+                        self.open_delete_dialog().await?;
+                    }
+                }
+                KeyCode::Char('u') => {
+                    if !self.is_modal_blob_info() && !self.is_downloading() && !self.ui.show_preview
+                    {
+                        if self.selected_file_item().is_some_and(|item| item.deleted) {
+                            self.undelete_selected().await;
+                        } else if let Err(e) = self.pick_and_upload_file().await {
+                            self.async_op = AsyncOp::None;
+                            self.error_message = Some(format!("Upload failed: {e}"));
+                        }
+                    }
+                }
+                KeyCode::Char('.') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.toggle_show_deleted().await;
+                    }
+                }
+                KeyCode::Char('f') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_tail();
+                    }
+                }
+                KeyCode::Char('E') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_editor().await;
+                    }
+                }
+                KeyCode::Char('!') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_command_mode_with("pipe ");
+                    }
+                }
+                KeyCode::Char('=') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.compare_with_marked().await;
+                    }
+                }
+                KeyCode::Char('B') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_usage_breakdown();
+                    }
+                }
+                KeyCode::Char('W') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.toggle_watch();
+                    }
+                }
+                KeyCode::Char(']') => {
+                    if matches!(self.modal, Modal::None)
+                        && !self.ui.show_preview
+                        && let Err(e) = self.next_page().await
+                    {
+                        self.error_message =
+                            Some(format!("Failed to load next page: {}", describe_error(&e)));
+                    }
+                }
+                KeyCode::Char('[') => {
+                    if matches!(self.modal, Modal::None)
+                        && !self.ui.show_preview
+                        && let Err(e) = self.previous_page().await
+                    {
+                        self.error_message = Some(format!(
+                            "Failed to load previous page: {}",
+                            describe_error(&e)
+                        ));
+                    }
+                }
+                KeyCode::Char('o') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_last_download();
+                    }
+                }
+                KeyCode::Char('e') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.open_extension_picker();
+                    }
+                }
+                KeyCode::Char('v') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.toggle_flatten().await;
+                    }
+                }
+                KeyCode::Char('w') => {
+                    if matches!(self.modal, Modal::None) && !self.ui.show_preview {
+                        self.toggle_preview_pane();
+                    }
+                }
+                KeyCode::Char('U') => {
+                    if !self.is_modal_blob_info()
+                        && !self.is_downloading()
+                        && !self.ui.show_preview
+                        && let Err(e) = self.upload_from_clipboard().await
+                    {
+                        self.async_op = AsyncOp::None;
+                        self.error_message = Some(format!("Upload failed: {e}"));
+                    }
+                }
+                KeyCode::Char('S') => {
+                    if !self.is_modal_blob_info()
+                        && !self.is_downloading()
+                        && !self.ui.show_preview
+                        && let Err(e) = self.pick_sync_directory().await
+                    {
+                        self.error_message = Some(format!("Sync failed: {e}"));
+                    }
+                }
+                KeyCode::Char('T') => {
+                    if !self.is_modal_blob_info() && !self.ui.show_preview {
+                        self.open_time_travel_dialog();
+                    }
+                }
+                KeyCode::Char('N') => {
+                    if !self.is_modal_blob_info() && !self.ui.show_preview {
+                        self.open_note_editor();
+                    }
+                }
+                KeyCode::Char('t') => {
+                    if self.is_modal_blob_info() {
+                        self.open_tag_editor();
+                    }
+                }
+                KeyCode::Char('L') => {
+                    if self.is_modal_blob_info() {
+                        self.open_lease_menu();
                     }
                 }
                 KeyCode::Up | KeyCode::Char('k') => {
                     if self.ui.show_preview {
                         self.preview_scroll_up();
-                    } else if !self.is_modal_blob_info() && !self.is_modal_sort_picker() {
+                    } else if !self.is_modal_blob_info() {
                         self.move_up();
                     }
                 }
                 KeyCode::Down | KeyCode::Char('j') => {
                     if self.ui.show_preview {
                         self.preview_scroll_down();
-                    } else if !self.is_modal_blob_info() && !self.is_modal_sort_picker() {
+                    } else if !self.is_modal_blob_info() {
                         self.move_down();
                     }
                 }
+                // When picking, files are picked instead
+                KeyCode::Enter
+                    if self.pick.is_some()
+                        && !self.ui.show_preview
+                        && matches!(self.modal, Modal::None)
+                        && (self
+                            .browsing()
+                            .is_some_and(|state| !state.marked.is_empty())
+                            || self.selected_file_item().is_some_and(|item| {
+                                item.kind == EntryKind::File && !item.deleted
+                            })) =>
+                {
+                    self.pick_selection();
+                }
+                // Files open in the preview, as with `p`
+                KeyCode::Enter
+                    if !self.ui.show_preview
+                        && matches!(self.modal, Modal::None)
+                        && self
+                            .selected_file_item()
+                            .is_some_and(|item| item.kind == EntryKind::File) =>
+                {
+                    if let Err(e) = self.load_preview().await {
+                        self.error_message =
+                            Some(format!("Preview failed: {}", describe_error(&e)));
+                    }
+                }
                 KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
                     if self.ui.show_preview {
                         self.preview_scroll_right();
-                    } else if self.is_modal_download_picker() {
-                        if let Err(e) = self.confirm_download().await {
-                            self.error_message = Some(format!("Download failed: {e}"));
-                        }
                     } else if !self.is_modal_blob_info()
-                        && !self.is_modal_sort_picker()
                         && let Err(e) = self.enter_directory().await
                     {
-                        self.error_message = Some(format!("Enter directory failed: {e}"));
+                        self.error_message =
+                            Some(format!("Enter directory failed: {}", describe_error(&e)));
                     }
                 }
                 KeyCode::Left | KeyCode::Char('h') => {
                     if self.ui.show_preview {
                         self.preview_scroll_left();
-                    } else if self.is_modal_download_picker() {
-                        // Close download picker
-                        self.close_modal();
                     } else if self.is_modal_blob_info() {
                         // Close popup
                         self.close_modal();
-                    } else if self.is_modal_sort_picker() {
-                        // Close sort popup
-                        self.close_modal();
                     } else if let Err(e) = self.go_up_directory().await {
-                        self.error_message = Some(format!("Go up failed: {e}"));
+                        self.error_message = Some(format!("Go up failed: {}", describe_error(&e)));
                     }
                 }
                 KeyCode::Esc => {
                     if self.ui.show_preview {
                         // Close preview panel
                         self.close_preview();
-                    } else if self.is_modal_download_picker() {
-                        // Close download picker
-                        self.close_modal();
                     } else if self.is_modal_blob_info() {
                         // Close popup
                         self.close_modal();
-                    } else if self.is_modal_sort_picker() {
-                        // Close sort popup
-                        self.close_modal();
                     } else if self
                         .browsing()
                         .is_some_and(|state| !state.current_path.is_empty())
                     {
                         // Go up one directory level if not at container root
                         if let Err(e) = self.go_up_directory().await {
-                            self.error_message = Some(format!("Go up failed: {e}"));
+                            self.error_message =
+                                Some(format!("Go up failed: {}", describe_error(&e)));
                         }
                     } else {
                         // At container root, go back to container selection
@@ -609,15 +1787,9 @@ impl App {
                     }
                 }
                 KeyCode::Backspace => {
-                    if self.is_modal_download_picker() {
-                        // Close download picker
-                        self.close_modal();
-                    } else if self.is_modal_blob_info() {
+                    if self.is_modal_blob_info() {
                         // Close popup
                         self.close_modal();
-                    } else if self.is_modal_sort_picker() {
-                        // Close sort popup
-                        self.close_modal();
                     } else {
                         // Go back to container selection
                         self.session = Session::Selecting;
@@ -640,7 +1812,100 @@ impl App {
     ///
     /// The tick event is where you can update the state of your application with any logic that
     /// needs to be updated at a fixed frame rate. E.g. polling a server, updating an animation.
-    pub fn tick(&self) {}
+    pub fn tick(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+        self.audit.update_jobs();
+        self.take_listed_pages();
+        self.update_watch();
+        self.take_folder_search_hits();
+        self.update_delete_progress();
+        self.update_upload_progress();
+        self.update_move_progress();
+        self.update_preview_pane();
+        self.update_preview_pager();
+        self.expire_messages();
+    }
+
+    /// Add a fetched page to a long text preview, and fetch the next once it is scrolled
+    /// near the end of what has been read.
+    fn update_preview_pager(&mut self) {
+        let (Some(pager), Some(PreviewData::Text(text))) =
+            (&mut self.preview_pager, &mut self.preview_data)
+        else {
+            return;
+        };
+        match pager.poll() {
+            Some(Ok(page)) => pager.add_page(&page, text),
+            Some(Err(e)) => {
+                self.error_message = Some(format!("Failed to load more of the preview: {e}"));
+                self.preview_pager = None;
+                return;
+            }
+            None => {}
+        }
+        let rows = self.preview_viewport.borrow().rows;
+        pager.fetch_near(self.preview_selected_row, rows, text.total_lines);
+    }
+
+    /// Keep the preview pane on the selected blob, fetching it once the selection rests.
+    fn update_preview_pane(&mut self) {
+        if !self.ui.preview_pane {
+            return;
+        }
+        let target = self
+            .browsing()
+            .zip(self.selected_file_item())
+            .filter(|(_, item)| item.kind == EntryKind::File && !item.deleted)
+            .map(|(state, item)| {
+                let path = Self::join_blob_path(&state.current_path, &item.actual_name);
+                (state, path, item.version_id.clone())
+            });
+        let key = target.as_ref().map(|(state, path, version)| {
+            format!(
+                "{}/{path}?{}",
+                state.container_name,
+                version.as_deref().unwrap_or_default()
+            )
+        });
+        let fetch = target.map(|(state, path, version)| {
+            Self::fetch_pane_preview(state.object_store.clone(), path, version)
+        });
+        if self.preview_pane.follow(key)
+            && let Some(fetch) = fetch
+        {
+            self.preview_pane.start(fetch);
+        }
+        self.preview_pane.poll();
+    }
+
+    /// Show or hide the preview pane.
+    pub fn toggle_preview_pane(&mut self) {
+        self.ui.preview_pane = !self.ui.preview_pane;
+        self.preview_pane.clear();
+    }
+
+    /// Clear the error or success message once it has been up for [`MESSAGE_TICKS`]; a new
+    /// message starts the clock again.
+    fn expire_messages(&mut self) {
+        let Some(message) = self
+            .error_message
+            .as_ref()
+            .or(self.success_message.as_ref())
+        else {
+            self.message_shown = None;
+            return;
+        };
+        match &self.message_shown {
+            Some((shown, since)) if shown == message => {
+                if self.ticks.wrapping_sub(*since) >= MESSAGE_TICKS {
+                    self.error_message = None;
+                    self.success_message = None;
+                    self.message_shown = None;
+                }
+            }
+            _ => self.message_shown = Some((message.clone(), self.ticks)),
+        }
+    }
 
     fn is_selecting(&self) -> bool {
         matches!(self.session, Session::Selecting)
@@ -664,11 +1929,148 @@ impl App {
         }
     }
 
-    fn selected_file_item(&self) -> Option<&FileItem> {
+    pub(crate) fn selected_file_item(&self) -> Option<&FileItem> {
         let state = self.browsing()?;
         state.file_items.get(state.selected_index)
     }
 
+    /// Record an operation on the current account in the audit log.
+    fn audit(&mut self, action: AuditAction, target: impl Into<String>, outcome: Outcome) {
+        self.audit
+            .record(&self.storage_account, action, target, outcome);
+    }
+
+    /// `container/path` for a path in the open container, as shown in the audit log.
+    fn audit_target(&self, path: &str) -> String {
+        match self.browsing() {
+            Some(state) => format!("{}/{path}", state.container_name),
+            None => path.to_string(),
+        }
+    }
+
+    /// Full path of a listed entry; folders end with `/`.
+    fn item_path(current_path: &str, item: &FileItem) -> String {
+        if item.kind == EntryKind::Folder {
+            Self::join_folder_path(current_path, &item.actual_name)
+        } else {
+            Self::join_blob_path(current_path, &item.actual_name)
+        }
+    }
+
+    /// Pick the marked blobs, or else the selected one, and quit so they can be printed.
+    /// Marked folders are left out, as a script expects blobs.
+    fn pick_selection(&mut self) {
+        let (Some(output), Some(state)) = (self.pick, self.browsing()) else {
+            return;
+        };
+        let paths: Vec<String> = if state.marked.is_empty() {
+            self.selected_file_item()
+                .map(|item| Self::join_blob_path(&state.current_path, &item.actual_name))
+                .into_iter()
+                .collect()
+        } else {
+            state
+                .marked
+                .keys()
+                .filter(|path| !path.ends_with('/'))
+                .cloned()
+                .collect()
+        };
+        if paths.is_empty() {
+            self.error_message = Some("Mark blobs to pick, not only folders".to_string());
+            return;
+        }
+        self.picked = paths
+            .iter()
+            .map(|path| output.line(&self.storage_account, &state.container_name, path))
+            .collect();
+        self.quit();
+    }
+
+    /// Mark or unmark the highlighted entry, then move to the next one.
+    pub fn toggle_mark(&mut self) {
+        let Some(state) = self.browsing_mut() else {
+            return;
+        };
+        let Some(item) = state.file_items.get(state.selected_index) else {
+            return;
+        };
+        let path = Self::item_path(&state.current_path, item);
+        if state.marked.remove(&path).is_none() {
+            state.marked.insert(path, item.clone());
+        }
+        self.move_down();
+    }
+
+    /// Mark every listed entry.
+    pub fn mark_all(&mut self) {
+        let Some(state) = self.browsing_mut() else {
+            return;
+        };
+        for item in &state.file_items {
+            let path = Self::item_path(&state.current_path, item);
+            state.marked.insert(path, item.clone());
+        }
+    }
+
+    /// Mark the listed entries that are not marked and unmark the ones that are.
+    pub fn invert_marks(&mut self) {
+        let Some(state) = self.browsing_mut() else {
+            return;
+        };
+        for item in &state.file_items {
+            let path = Self::item_path(&state.current_path, item);
+            if state.marked.remove(&path).is_none() {
+                state.marked.insert(path, item.clone());
+            }
+        }
+    }
+
+    /// Whether `item` in the current folder is marked.
+    pub(crate) fn is_marked(&self, item: &FileItem) -> bool {
+        self.browsing().is_some_and(|state| {
+            state
+                .marked
+                .contains_key(&Self::item_path(&state.current_path, item))
+        })
+    }
+
+    /// Number of marked entries and the total size of the marked blobs.
+    ///
+    /// Folders count as entries but not towards the size, which would need a full listing.
+    pub(crate) fn marked_summary(&self) -> Option<(usize, u64)> {
+        let state = self.browsing()?;
+        if state.marked.is_empty() {
+            return None;
+        }
+        let bytes = state.marked.values().filter_map(|item| item.size).sum();
+        Some((state.marked.len(), bytes))
+    }
+
+    /// List every blob of the marked entries, expanding folders.
+    async fn plan_marked(&self) -> color_eyre::Result<FolderPlan> {
+        let marked = self
+            .browsing()
+            .map(|state| state.marked.clone())
+            .unwrap_or_default();
+        let mut plan = FolderPlan::default();
+        for (path, item) in marked {
+            if item.kind == EntryKind::Folder {
+                let folder = self.plan_folder(&path).await?;
+                plan.blobs.extend(folder.blobs);
+                plan.errors.extend(folder.errors);
+            } else {
+                plan.blobs.push(PlannedBlob {
+                    location: ObjectPath::from(path.as_str()),
+                    version_id: item.version_id,
+                    size: item.size.unwrap_or_default(),
+                    e_tag: None,
+                });
+            }
+        }
+        Ok(plan)
+    }
+
     fn join_blob_path(current_path: &str, name: &str) -> String {
         if current_path.is_empty() {
             name.to_string()
@@ -698,46 +2100,186 @@ impl App {
     }
 
     fn is_modal_sort_picker(&self) -> bool {
-        matches!(self.modal, Modal::SortPicker)
+        matches!(self.modal, Modal::SortPicker { .. })
+    }
+
+    fn is_modal_copy_command(&self) -> bool {
+        matches!(self.modal, Modal::CopyCommand { .. })
     }
 
     fn is_modal_clone_dialog(&self) -> bool {
         matches!(self.modal, Modal::Clone { .. })
     }
 
+    fn is_modal_move_dialog(&self) -> bool {
+        matches!(self.modal, Modal::Move { .. })
+    }
+
     fn is_modal_delete_dialog(&self) -> bool {
-        matches!(self.modal, Modal::DeleteConfirm { .. })
+        matches!(
+            self.modal,
+            Modal::DeleteConfirm { .. } | Modal::DeleteMarked { .. }
+        )
     }
 
-    fn close_modal(&mut self) {
-        self.modal = Modal::None;
+    fn is_modal_time_travel(&self) -> bool {
+        matches!(self.modal, Modal::TimeTravel { .. })
     }
 
-    pub(crate) fn is_loading_containers(&self) -> bool {
-        matches!(self.async_op, AsyncOp::LoadingContainers)
+    fn is_modal_new_folder(&self) -> bool {
+        matches!(self.modal, Modal::NewFolder { .. })
     }
 
-    pub(crate) fn is_loading_files(&self) -> bool {
-        matches!(self.async_op, AsyncOp::LoadingFiles)
+    fn is_modal_container_info(&self) -> bool {
+        matches!(self.modal, Modal::ContainerInfo { .. })
     }
 
-    pub(crate) fn is_searching_containers(&self) -> bool {
-        matches!(self.search, Search::Containers { .. })
+    fn is_modal_new_container(&self) -> bool {
+        matches!(self.modal, Modal::NewContainer { .. })
     }
 
-    pub(crate) fn is_searching_files(&self) -> bool {
-        matches!(self.search, Search::Files { .. })
+    fn is_modal_delete_container(&self) -> bool {
+        matches!(self.modal, Modal::DeleteContainer { .. })
     }
 
-    pub(crate) fn container_search_query(&self) -> Option<&str> {
-        match &self.search {
-            Search::Containers { query, .. } => Some(query.as_str()),
-            _ => None,
-        }
+    fn is_modal_sync_preview(&self) -> bool {
+        matches!(self.modal, Modal::SyncPreview { .. })
     }
 
-    pub(crate) fn file_search_query(&self) -> Option<&str> {
-        match &self.search {
+    fn is_modal_edit_note(&self) -> bool {
+        matches!(self.modal, Modal::EditNote { .. })
+    }
+
+    fn is_modal_tail(&self) -> bool {
+        matches!(self.modal, Modal::Tail { .. })
+    }
+
+    fn is_modal_jobs(&self) -> bool {
+        matches!(self.modal, Modal::Jobs { .. })
+    }
+
+    fn is_modal_command(&self) -> bool {
+        matches!(self.modal, Modal::Command { .. })
+    }
+
+    fn is_modal_jump(&self) -> bool {
+        matches!(self.modal, Modal::Jump { .. })
+    }
+
+    fn is_modal_help(&self) -> bool {
+        matches!(self.modal, Modal::Help { .. })
+    }
+
+    fn is_modal_usage(&self) -> bool {
+        matches!(self.modal, Modal::Usage { .. })
+    }
+
+    fn is_modal_diff(&self) -> bool {
+        matches!(self.modal, Modal::Diff { .. })
+    }
+
+    fn is_modal_credential_picker(&self) -> bool {
+        matches!(self.modal, Modal::CredentialPicker { .. })
+    }
+
+    fn is_modal_lease_menu(&self) -> bool {
+        matches!(self.modal, Modal::LeaseMenu { .. })
+    }
+
+    fn is_modal_snapshots(&self) -> bool {
+        matches!(self.modal, Modal::Snapshots { .. })
+    }
+
+    fn is_modal_edit_tags(&self) -> bool {
+        matches!(self.modal, Modal::EditTags { .. })
+    }
+
+    fn is_modal_extensions(&self) -> bool {
+        matches!(self.modal, Modal::Extensions { .. })
+    }
+
+    fn is_modal_global_search(&self) -> bool {
+        matches!(self.modal, Modal::GlobalSearch { .. })
+    }
+
+    fn is_modal_edit_upload(&self) -> bool {
+        matches!(self.modal, Modal::EditUpload { .. })
+    }
+
+    fn is_modal_large_operation_warning(&self) -> bool {
+        matches!(self.modal, Modal::LargeOperationWarning { .. })
+    }
+
+    fn is_time_travel(&self) -> bool {
+        self.browsing().is_some_and(|state| state.as_of.is_some())
+    }
+
+    /// Report an error and return `true` if blobs must not be changed right now, either
+    /// because read-only mode is on or because a time-travel view is open.
+    fn refuse_if_read_only(&mut self) -> bool {
+        let reason = if self.config.read_only {
+            "Read-only mode is on"
+        } else if self.is_time_travel() {
+            "Time-travel view is read-only"
+        } else {
+            return false;
+        };
+        self.error_message = Some(reason.to_string());
+        true
+    }
+
+    /// Refuse to overwrite or delete a blob under a legal hold or an unexpired immutability
+    /// policy. When its properties cannot be read the service has the final say.
+    async fn refuse_if_immutable(&mut self, blob_path: &str) -> bool {
+        let Some(container) = self.browsing().map(|state| state.container_name.clone()) else {
+            return false;
+        };
+        let Ok(client) = self.rest_client() else {
+            return false;
+        };
+        let Ok(properties) = client.blob_properties(&container, blob_path).await else {
+            return false;
+        };
+        match properties.protection(Utc::now()) {
+            Some(reason) => {
+                self.error_message = Some(format!(
+                    "{blob_path} is {reason}; it cannot be changed or deleted"
+                ));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn close_modal(&mut self) {
+        self.modal = Modal::None;
+    }
+
+    pub(crate) fn is_loading_containers(&self) -> bool {
+        matches!(self.async_op, AsyncOp::LoadingContainers)
+    }
+
+    pub(crate) fn is_loading_files(&self) -> bool {
+        matches!(self.async_op, AsyncOp::LoadingFiles)
+    }
+
+    pub(crate) fn is_searching_containers(&self) -> bool {
+        matches!(self.search, Search::Containers { .. })
+    }
+
+    pub(crate) fn is_searching_files(&self) -> bool {
+        matches!(self.search, Search::Files { .. })
+    }
+
+    pub(crate) fn container_search_query(&self) -> Option<&str> {
+        match &self.search {
+            Search::Containers { query, .. } => Some(query.as_str()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn file_search_query(&self) -> Option<&str> {
+        match &self.search {
             Search::Files { query, .. } => Some(query.as_str()),
             _ => None,
         }
@@ -760,7 +2302,9 @@ impl App {
             self.async_op,
             AsyncOp::LoadingContainers
                 | AsyncOp::LoadingFiles
+                | AsyncOp::Uploading(_)
                 | AsyncOp::Cloning(_)
+                | AsyncOp::Moving(_)
                 | AsyncOp::Deleting(_)
         )
     }
@@ -770,20 +2314,107 @@ impl App {
         self.running = false;
     }
 
-    /// List blobs and prefixes with metadata for sorting.
-    async fn list_file_items(&self, prefix: &str) -> color_eyre::Result<Vec<FileItem>> {
-        let object_store = self
+    /// List blobs and prefixes with metadata for sorting: the whole folder, or the first
+    /// request of the current page with the continuation token and number of entries listed.
+    async fn list_file_items(
+        &self,
+        prefix: &str,
+    ) -> color_eyre::Result<(Vec<FileItem>, Option<String>, usize)> {
+        let browsing = self
             .browsing()
-            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?
-            .object_store
-            .clone();
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        if let Some(as_of) = browsing.as_of {
+            let items = self
+                .list_file_items_as_of(&browsing.container_name, prefix, as_of)
+                .await?;
+            let listed = items.len();
+            return Ok((items, None, listed));
+        }
 
-        let result = if prefix.is_empty() {
-            object_store.list_with_delimiter(None).await?
+        let (mut items, next_token, listed) = if let Some(pager) = &browsing.paging.store {
+            let options = PaginatedListOptions {
+                delimiter: self.listing_delimiter().map(Into::into),
+                max_keys: Some(self.config.browse.page_size.clamp(1, LIST_REQUEST_SIZE)),
+                page_token: browsing.paging.tokens.last().cloned(),
+                ..PaginatedListOptions::default()
+            };
+            let page = pager
+                .list_paginated((!prefix.is_empty()).then_some(prefix), options)
+                .await?;
+            let listed = page.result.common_prefixes.len() + page.result.objects.len();
+            (
+                self.file_items_from_listing(page.result, prefix),
+                page.page_token,
+                listed,
+            )
         } else {
-            let object_path = ObjectPath::from(prefix);
-            object_store.list_with_delimiter(Some(&object_path)).await?
+            let object_store = browsing.object_store.clone();
+            let object_path = (!prefix.is_empty()).then(|| ObjectPath::from(prefix));
+            let result = if self.ui.flatten {
+                ListResult {
+                    common_prefixes: Vec::new(),
+                    objects: object_store
+                        .list(object_path.as_ref())
+                        .try_collect()
+                        .await?,
+                }
+            } else {
+                object_store
+                    .list_with_delimiter(object_path.as_ref())
+                    .await?
+            };
+            let listed = result.common_prefixes.len() + result.objects.len();
+            (self.file_items_from_listing(result, prefix), None, listed)
         };
+
+        // Deleted blobs are listed once, alongside the first page
+        if self.ui.show_deleted && browsing.paging.tokens.is_empty() {
+            let deleted = self
+                .rest_client()?
+                .list_deleted_blobs(&browsing.container_name, prefix, self.listing_delimiter())
+                .await?;
+            for blob in deleted {
+                let Some(last_part) = self.listed_name(&blob.name, prefix) else {
+                    continue;
+                };
+                // A blob deleted and then written again is listed as live
+                if items.iter().any(|item| item.actual_name == last_part) {
+                    continue;
+                }
+                items.push(FileItem {
+                    display_name: format!("{trash} {last_part}", trash = self.icons.trash),
+                    actual_name: last_part.to_string(),
+                    kind: EntryKind::File,
+                    size: Some(blob.size),
+                    last_modified: blob.deleted_time,
+                    created: None,
+                    version_id: None,
+                    deleted: true,
+                    used_size: None,
+                });
+            }
+        }
+
+        Ok((items, next_token, listed))
+    }
+
+    /// `/` to list one folder level at a time, or nothing in the flattened view.
+    fn listing_delimiter(&self) -> Option<&'static str> {
+        (!self.ui.flatten).then_some("/")
+    }
+
+    /// The name a blob at `location` under `prefix` is listed by: its path from there in the
+    /// flattened view, otherwise its last part.
+    fn listed_name<'a>(&self, location: &'a str, prefix: &str) -> Option<&'a str> {
+        if self.ui.flatten {
+            location.strip_prefix(prefix)
+        } else {
+            location.split('/').next_back()
+        }
+    }
+
+    /// Turn one delimiter level of a listing under `prefix` into folder and file items.
+    fn file_items_from_listing(&self, result: ListResult, prefix: &str) -> Vec<FileItem> {
         let mut items = Vec::new();
 
         // Add "directories" (common prefixes)
@@ -797,6 +2428,9 @@ impl App {
                     size: None,
                     last_modified: None,
                     created: None,
+                    version_id: None,
+                    deleted: false,
+                    used_size: None,
                 });
             }
         }
@@ -804,14 +2438,75 @@ impl App {
         // Add files (objects) with metadata
         for meta in result.objects {
             let name = meta.location.as_ref();
-            if let Some(last_part) = name.split('/').next_back() {
+            if let Some(last_part) = self.listed_name(name, prefix) {
                 items.push(FileItem {
-                    display_name: format!("{file} {last_part}", file = self.icons.file),
+                    display_name: format!(
+                        "{file} {last_part}",
+                        file = self.icons.file_icon(last_part)
+                    ),
                     actual_name: last_part.to_string(),
                     kind: EntryKind::File,
                     size: Some(meta.size),
                     last_modified: Some(meta.last_modified),
                     created: None, // Azure Blob Storage doesn't provide creation time in list operation
+                    version_id: None,
+                    deleted: false,
+                    used_size: None,
+                });
+            }
+        }
+
+        items
+    }
+
+    /// List the blobs that were current at `as_of`, using the blob versions API.
+    ///
+    /// Virtual folders are listed as they exist today, since a prefix has no version history of
+    /// its own.
+    async fn list_file_items_as_of(
+        &self,
+        container: &str,
+        prefix: &str,
+        as_of: chrono::DateTime<Utc>,
+    ) -> color_eyre::Result<Vec<FileItem>> {
+        let listing = self
+            .rest_client()?
+            .list_blob_versions(container, prefix, self.listing_delimiter())
+            .await?;
+        let mut items = Vec::new();
+
+        for prefix in listing.prefixes {
+            let name = prefix.trim_end_matches('/');
+            if let Some(last_part) = name.split('/').next_back() {
+                items.push(FileItem {
+                    display_name: format!("{folder} {last_part}", folder = self.icons.folder),
+                    actual_name: last_part.to_string(),
+                    kind: EntryKind::Folder,
+                    size: None,
+                    last_modified: None,
+                    created: None,
+                    version_id: None,
+                    deleted: false,
+                    used_size: None,
+                });
+            }
+        }
+
+        for version in versions_as_of(&listing.versions, as_of) {
+            if let Some(last_part) = self.listed_name(&version.name, prefix) {
+                items.push(FileItem {
+                    display_name: format!(
+                        "{file} {last_part}",
+                        file = self.icons.file_icon(last_part)
+                    ),
+                    actual_name: last_part.to_string(),
+                    kind: EntryKind::File,
+                    size: Some(version.size),
+                    last_modified: version.last_modified,
+                    created: None,
+                    version_id: version.version_id,
+                    deleted: false,
+                    used_size: None,
                 });
             }
         }
@@ -819,18 +2514,76 @@ impl App {
         Ok(items)
     }
 
+    /// Build a signed REST client for the current storage account.
+    fn rest_client(&self) -> Result<AzureRestClient, BlobrsError> {
+        AzureRestClient::new(&self.storage_account, self.access_key.expose())?
+            .with_retry(self.config.retry)
+            .with_timeouts(self.config.timeouts)
+    }
+
+    /// Fetch blob bytes through the on-disk cache when it is enabled.
+    ///
+    /// Cache entries are keyed by blob path, ETag and range, so a changed blob is fetched again.
+    /// Without a known ETag one is looked up first.
+    async fn fetch_bytes_cached(
+        &self,
+        object_store: &Arc<dyn ObjectStore>,
+        path: &ObjectPath,
+        range: Option<std::ops::Range<u64>>,
+        version: Option<&str>,
+        e_tag: Option<&str>,
+    ) -> object_store::Result<bytes::Bytes> {
+        let Some(cache) = &self.cache else {
+            return fetch::bytes(object_store, path, range, version).await;
+        };
+
+        let e_tag = match e_tag {
+            Some(e_tag) => Some(e_tag.to_string()),
+            None => fetch::meta(object_store, path, version)
+                .await
+                .ok()
+                .and_then(|meta| meta.e_tag),
+        };
+        let Some(e_tag) = e_tag else {
+            return fetch::bytes(object_store, path, range, version).await;
+        };
+
+        let container = self
+            .browsing()
+            .map_or("", |state| state.container_name.as_str());
+        let range_key = range
+            .as_ref()
+            .map(|r| format!("{}-{}", r.start, r.end))
+            .unwrap_or_default();
+        let key = BlobCache::key(&[
+            &self.storage_account,
+            container,
+            path.as_ref(),
+            &e_tag,
+            &range_key,
+        ]);
+
+        if let Some(bytes) = cache.get(&key) {
+            return Ok(bytes);
+        }
+        let bytes = fetch::bytes(object_store, path, range, version).await?;
+        // A failed cache write only costs a refetch later
+        let _ = cache.put(&key, &bytes);
+        Ok(bytes)
+    }
+
     /// Apply sorting to the current file list.
     ///
     /// # Errors
     ///
     /// This function currently does not return errors but uses `Result` for API consistency.
-    pub fn apply_sort(&mut self, criteria: SortCriteria) -> color_eyre::Result<()> {
-        self.sort_criteria = criteria;
+    pub fn apply_sort(&mut self, order: SortOrder) -> color_eyre::Result<()> {
+        self.sort = order;
 
         if let Some(state) = self.browsing_mut()
             && !state.file_items.is_empty()
         {
-            Self::sort_file_items_static(&mut state.file_items, criteria);
+            Self::sort_file_items_static(&mut state.file_items, order);
             // Update the display list
             state.files = state
                 .file_items
@@ -847,7 +2600,7 @@ impl App {
         } = &mut self.search
             && !all_file_items.is_empty()
         {
-            Self::sort_file_items_static(all_file_items, criteria);
+            Self::sort_file_items_static(all_file_items, order);
             *all_files = all_file_items
                 .iter()
                 .map(|item| item.display_name.clone())
@@ -857,8 +2610,73 @@ impl App {
         Ok(())
     }
 
+    /// Bytes in use by the page blobs among `items`, by name. Sparse VHDs provision far more
+    /// than they use. Anything that cannot be looked up is left out.
+    async fn page_blob_usage(&self, prefix: &str, items: &[FileItem]) -> BTreeMap<String, u64> {
+        let (Some(state), Ok(client)) = (self.browsing(), self.rest_client()) else {
+            return BTreeMap::new();
+        };
+        if state.as_of.is_some() {
+            return BTreeMap::new();
+        }
+        let container = &state.container_name;
+        let Ok(page_blobs) = client.list_page_blobs(container, prefix).await else {
+            return BTreeMap::new();
+        };
+        let client = &client;
+        futures::stream::iter(page_blobs)
+            .filter_map(|path| async move {
+                let name = path.rsplit('/').next()?.to_string();
+                items
+                    .iter()
+                    .any(|item| {
+                        item.kind == EntryKind::File && !item.deleted && item.actual_name == name
+                    })
+                    .then_some((path, name))
+            })
+            .map(|(path, name)| async move {
+                let used = client.page_blob_used_bytes(container, &path).await.ok()?;
+                Some((name, used))
+            })
+            .buffer_unordered(PAGE_USAGE_CONCURRENCY)
+            .filter_map(|usage| async move { usage })
+            .collect()
+            .await
+    }
+
+    /// Record looked-up page blob usage on the matching live file items.
+    fn set_used_sizes(items: &mut [FileItem], usage: &BTreeMap<String, u64>) {
+        for item in items.iter_mut().filter(|item| !item.deleted) {
+            if let Some(used) = usage.get(&item.actual_name) {
+                item.used_size = Some(*used);
+            }
+        }
+    }
+
+    /// Look up page blob usage for the folder being shown, before sorting it by size.
+    async fn load_listed_page_blob_usage(&mut self) {
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let prefix = state.current_path.clone();
+        let items = match &self.search {
+            Search::Files { all_file_items, .. } => all_file_items.clone(),
+            _ => state.file_items.clone(),
+        };
+        let usage = self.page_blob_usage(&prefix, &items).await;
+        if usage.is_empty() {
+            return;
+        }
+        if let Search::Files { all_file_items, .. } = &mut self.search {
+            Self::set_used_sizes(all_file_items, &usage);
+        }
+        if let Some(state) = self.browsing_mut() {
+            Self::set_used_sizes(&mut state.file_items, &usage);
+        }
+    }
+
     /// Sort file items based on the given criteria.
-    fn sort_file_items_static(items: &mut [FileItem], criteria: SortCriteria) {
+    fn sort_file_items_static(items: &mut [FileItem], order: SortOrder) {
         items.sort_by(|a, b| {
             // Always put folders first
             match (a.kind, b.kind) {
@@ -866,7 +2684,7 @@ impl App {
                 (EntryKind::File, EntryKind::Folder) => std::cmp::Ordering::Greater,
                 _ => {
                     // Both are folders or both are files, sort by criteria
-                    match criteria {
+                    let ordering = match order.criteria {
                         SortCriteria::Name => a.actual_name.cmp(&b.actual_name),
                         SortCriteria::DateModified => {
                             match (a.last_modified, b.last_modified) {
@@ -887,13 +2705,19 @@ impl App {
                             }
                         }
                         SortCriteria::Size => {
-                            match (a.size, b.size) {
+                            // Sparse page blobs sort by what they use, not what they provision
+                            match (a.used_size.or(a.size), b.used_size.or(b.size)) {
                                 (Some(a_size), Some(b_size)) => b_size.cmp(&a_size), // Largest first
                                 (Some(_), None) => std::cmp::Ordering::Less,
                                 (None, Some(_)) => std::cmp::Ordering::Greater,
                                 (None, None) => a.actual_name.cmp(&b.actual_name), // Fallback to name
                             }
                         }
+                    };
+                    if order.reversed {
+                        ordering.reverse()
+                    } else {
+                        ordering
                     }
                 }
             }
@@ -914,11 +2738,38 @@ impl App {
         self.async_op = AsyncOp::LoadingFiles;
         self.error_message = None;
         self.success_message = None;
+        if let Some(state) = self.browsing_mut() {
+            state.paging.stop();
+        }
 
         match self.list_file_items(&current_path).await {
-            Ok(mut file_items) => {
+            Ok((mut file_items, next_token, listed)) => {
+                let page_size = self.config.browse.page_size.max(1);
+                let delimiter = self.listing_delimiter();
+                if let Some(state) = self.browsing_mut()
+                    && let (Some(store), Some(token)) = (&state.paging.store, next_token)
+                {
+                    // The rest of the page loads in the background; past it, `]` goes on
+                    if listed < page_size {
+                        state.paging.more = Some(FolderListing::start(
+                            store.clone(),
+                            current_path.clone(),
+                            delimiter,
+                            LIST_REQUEST_SIZE,
+                            page_size - listed,
+                            token,
+                        ));
+                    } else {
+                        state.paging.next_token = Some(token);
+                    }
+                }
+
                 // Apply current sorting
-                Self::sort_file_items_static(&mut file_items, self.sort_criteria);
+                if self.sort.criteria == SortCriteria::Size {
+                    let usage = self.page_blob_usage(&current_path, &file_items).await;
+                    Self::set_used_sizes(&mut file_items, &usage);
+                }
+                Self::sort_file_items_static(&mut file_items, self.sort);
 
                 // Create display strings
                 let files: Vec<String> = file_items
@@ -958,7 +2809,7 @@ impl App {
                 }
             }
             Err(e) => {
-                self.error_message = Some(format!("Failed to list blobs: {e}"));
+                self.error_message = Some(format!("Failed to list blobs: {}", describe_error(&e)));
             }
         }
 
@@ -966,6 +2817,80 @@ impl App {
         Ok(())
     }
 
+    /// Show or hide soft-deleted blobs. They can only be shown when the account keeps them.
+    pub async fn toggle_show_deleted(&mut self) {
+        if !self.ui.show_deleted {
+            let enabled = match self.rest_client() {
+                Ok(client) => client.blob_soft_delete_enabled().await,
+                Err(e) => Err(e),
+            };
+            match enabled {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.error_message = Some(format!(
+                        "Soft delete is not enabled for account {}",
+                        self.storage_account
+                    ));
+                    return;
+                }
+                Err(e) => {
+                    self.error_message = Some(format!("Failed to check soft delete: {e}"));
+                    return;
+                }
+            }
+        }
+
+        self.ui.show_deleted = !self.ui.show_deleted;
+        if let Some(state) = self.browsing_mut() {
+            state.paging.reset();
+        }
+        if let Err(e) = self.refresh_files().await {
+            self.error_message = Some(format!("Refresh failed: {}", describe_error(&e)));
+        }
+    }
+
+    /// Switch between listing one folder level and every blob under the current folder.
+    pub async fn toggle_flatten(&mut self) {
+        self.ui.flatten = !self.ui.flatten;
+        if let Some(state) = self.browsing_mut() {
+            state.paging.reset();
+        }
+        if let Err(e) = self.refresh_files().await {
+            self.error_message = Some(format!("Refresh failed: {}", describe_error(&e)));
+        }
+    }
+
+    /// Restore the highlighted soft-deleted blob.
+    async fn undelete_selected(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let (Some(state), Some(item)) = (self.browsing(), self.selected_file_item()) else {
+            return;
+        };
+        let container = state.container_name.clone();
+        let blob_path = Self::join_blob_path(&state.current_path, &item.actual_name);
+
+        let restored = match self.rest_client() {
+            Ok(client) => client.undelete_blob(&container, &blob_path).await,
+            Err(e) => Err(e),
+        };
+        self.audit(
+            AuditAction::Restore,
+            format!("{container}/{blob_path}"),
+            Outcome::of(&restored),
+        );
+        match restored {
+            Ok(()) => {
+                if let Err(e) = self.refresh_files().await {
+                    self.error_message = Some(format!("Refresh failed: {}", describe_error(&e)));
+                }
+                self.success_message = Some(format!("Restored {blob_path}"));
+            }
+            Err(e) => self.error_message = Some(format!("Failed to restore {blob_path}: {e}")),
+        }
+    }
+
     /// Move the selection up.
     pub fn move_up(&mut self) {
         if let Some(state) = self.browsing_mut()
@@ -975,6 +2900,22 @@ impl App {
         }
     }
 
+    /// Move the container or blob selection by `motion`, `count` times.
+    pub fn move_selection(&mut self, motion: Motion, count: Option<usize>) {
+        let page = usize::from(self.list_layout.borrow().rows.height);
+        if self.is_selecting() {
+            self.selected_container_index = motion.target(
+                count,
+                self.selected_container_index,
+                self.containers.len(),
+                page,
+            );
+        } else if let Some(state) = self.browsing_mut() {
+            state.selected_index =
+                motion.target(count, state.selected_index, state.files.len(), page);
+        }
+    }
+
     /// Move the selection down.
     pub fn move_down(&mut self) {
         if let Some(state) = self.browsing_mut()
@@ -1007,6 +2948,8 @@ impl App {
             let new_path = Self::join_folder_path(&current_path, &selected_item.actual_name);
             if let Some(state) = self.browsing_mut() {
                 state.current_path = new_path;
+                state.paging.reset();
+                state.marked.clear();
             }
             // Exit search mode when navigating
             if self.is_searching_files() {
@@ -1017,43 +2960,276 @@ impl App {
         Ok(())
     }
 
-    /// Go up one directory level.
+    /// Show the next page of a folder that is too large to list at once.
     ///
     /// # Errors
     ///
     /// Returns an error if refreshing the file list fails.
-    pub async fn go_up_directory(&mut self) -> color_eyre::Result<()> {
-        let current_path = match self.browsing() {
-            Some(state) => state.current_path.clone(),
-            None => return Ok(()),
-        };
+    pub async fn next_page(&mut self) -> color_eyre::Result<()> {
+        if self
+            .browsing_mut()
+            .is_some_and(|state| state.paging.advance())
+        {
+            self.refresh_files().await?;
+        }
+        Ok(())
+    }
 
-        if current_path.is_empty() {
-            return Ok(()); // Already at root
+    /// Show the previous page of a paged folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if refreshing the file list fails.
+    pub async fn previous_page(&mut self) -> color_eyre::Result<()> {
+        if self
+            .browsing_mut()
+            .is_some_and(|state| state.paging.go_back())
+        {
+            self.refresh_files().await?;
         }
+        Ok(())
+    }
 
-        // Remove trailing slash and go up one level
-        let trimmed = current_path.trim_end_matches('/');
-        if let Some(last_slash) = trimmed.rfind('/') {
+    /// Show the entries of the current page listed in the background since the last tick.
+    fn take_listed_pages(&mut self) {
+        let Some((listing, prefix)) = self
+            .browsing()
+            .and_then(|state| Some((state.paging.more.clone()?, state.current_path.clone())))
+        else {
+            return;
+        };
+        // Checked first so a page arriving in between is not lost
+        let finished = listing.is_finished();
+        let items: Vec<FileItem> = listing
+            .take_pages()
+            .into_iter()
+            .flat_map(|page| self.file_items_from_listing(page, &prefix))
+            .collect();
+        if !items.is_empty() {
+            self.append_file_items(items);
+        }
+        if finished {
+            if let Some(e) = listing.error() {
+                self.error_message = Some(format!("Failed to list the rest of the page: {e}"));
+            }
             if let Some(state) = self.browsing_mut() {
-                let new_path = &trimmed[..last_slash];
-                state.current_path = format!("{new_path}/");
+                state.paging.more = None;
+                state.paging.next_token = listing.next_token();
             }
-        } else if let Some(state) = self.browsing_mut() {
-            state.current_path = String::new(); // Go to root
         }
+    }
 
-        // Exit search mode when navigating
-        if self.is_searching_files() {
-            self.search = Search::Inactive;
+    /// Start or stop watching the open folder.
+    pub fn toggle_watch(&mut self) {
+        if self.watch.take().is_some() {
+            self.success_message = Some("Stopped watching".to_string());
+            return;
         }
-        self.refresh_files().await?;
-        Ok(())
+        if self.browsing().is_some_and(|state| state.as_of.is_some()) {
+            self.error_message = Some("A folder as of a point in time does not change".to_string());
+            return;
+        }
+        let secs = self.config.browse.watch_secs.max(1);
+        self.watch = Some(FolderWatch::new(std::time::Duration::from_secs(secs)));
+        self.success_message = Some(format!(
+            "Watching the open folder every {secs} s; new and changed blobs are highlighted"
+        ));
     }
 
-    /// Enter search mode.
-    pub fn enter_search_mode(&mut self) {
-        let (files, file_items) = match self.browsing() {
+    /// List the watched folder again when it is due, and take in what changed once the
+    /// listing is back.
+    fn update_watch(&mut self) {
+        let Some(mut watch) = self.watch.take() else {
+            return;
+        };
+        let Some(state) = self.browsing() else {
+            self.watch = Some(watch);
+            return;
+        };
+        let folder = format!("{}/{}", state.container_name, state.current_path);
+        // A folder still loading its first pages is compared once it is all there
+        let idle = state.as_of.is_none()
+            && state.paging.more.is_none()
+            && matches!(self.async_op, AsyncOp::None);
+
+        if let Some((listed, result)) = watch.poll()
+            && listed == folder
+            && idle
+        {
+            match result {
+                Ok(listing) => {
+                    let prefix = state.current_path.clone();
+                    let items = self.file_items_from_listing(listing, &prefix);
+                    let before = match &self.search {
+                        Search::Files { all_file_items, .. } => all_file_items.as_slice(),
+                        _ => self.browsing().map_or(&[][..], |state| &state.file_items),
+                    };
+                    let diff = WatchDiff::new(before, &items);
+                    if !diff.is_empty() {
+                        self.success_message = Some(format!("Watch: {}", diff.summary()));
+                        self.replace_file_items(items);
+                    }
+                    watch.record(&folder, &diff, std::time::Instant::now());
+                }
+                Err(e) => self.error_message = Some(format!("Watch failed to list: {e}")),
+            }
+        }
+        if idle
+            && watch.is_due(std::time::Instant::now())
+            && let Some(state) = self.browsing()
+        {
+            watch.start(
+                folder,
+                state.object_store.clone(),
+                &state.current_path,
+                self.ui.flatten,
+            );
+        }
+        self.watch = Some(watch);
+    }
+
+    /// Add entries from a later page of the listing, keeping the sort order, the search
+    /// filter and the selected entry.
+    ///
+    /// Page blob usage is not looked up for them, so with size sorting they sort by their
+    /// provisioned size until the folder is sorted again.
+    fn append_file_items(&mut self, items: Vec<FileItem>) {
+        self.merge_listed_items(items, false);
+    }
+
+    /// Swap the live entries for a fresh listing of the folder, keeping soft-deleted ones
+    /// (listed separately), the sort order, the search filter and the selected entry.
+    fn replace_file_items(&mut self, items: Vec<FileItem>) {
+        self.merge_listed_items(items, true);
+    }
+
+    fn merge_listed_items(&mut self, mut items: Vec<FileItem>, replace: bool) {
+        let order = self.sort;
+        let selected = self
+            .selected_file_item()
+            .map(|item| (item.kind, item.actual_name.clone()));
+
+        let filter_mode = self.ui.filter_mode;
+        if let Search::Files {
+            query,
+            all_files,
+            all_file_items,
+        } = &mut self.search
+        {
+            if replace {
+                all_file_items.retain(|item| item.deleted);
+            }
+            Self::merge_file_items(all_file_items, items.clone(), order);
+            *all_files = all_file_items
+                .iter()
+                .map(|item| item.display_name.clone())
+                .collect();
+            let filter = Filter::lenient(filter_mode, query);
+            items.retain(|item| filter.is_match(&item.actual_name, item.size, item.last_modified));
+        }
+
+        let Some(state) = self.browsing_mut() else {
+            return;
+        };
+        if replace {
+            state.file_items.retain(|item| item.deleted);
+        }
+        Self::merge_file_items(&mut state.file_items, items, order);
+        state.files = state
+            .file_items
+            .iter()
+            .map(|item| item.display_name.clone())
+            .collect();
+        if let Some((kind, name)) = selected
+            && let Some(index) = state
+                .file_items
+                .iter()
+                .position(|item| item.kind == kind && item.actual_name == name)
+        {
+            state.selected_index = index;
+        } else {
+            // The selected entry is gone from a fresh listing
+            state.selected_index = state
+                .selected_index
+                .min(state.file_items.len().saturating_sub(1));
+        }
+    }
+
+    /// Sort `items` into `existing`. A blob that turns out to be live replaces its deleted
+    /// entry, as it does within one page.
+    fn merge_file_items(existing: &mut Vec<FileItem>, items: Vec<FileItem>, order: SortOrder) {
+        if existing.iter().any(|item| item.deleted) {
+            let live: BTreeSet<&str> = items.iter().map(|item| item.actual_name.as_str()).collect();
+            existing.retain(|item| !item.deleted || !live.contains(item.actual_name.as_str()));
+        }
+        existing.extend(items);
+        Self::sort_file_items_static(existing, order);
+    }
+
+    /// Go up one directory level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if refreshing the file list fails.
+    pub async fn go_up_directory(&mut self) -> color_eyre::Result<()> {
+        let current_path = match self.browsing() {
+            Some(state) => state.current_path.clone(),
+            None => return Ok(()),
+        };
+
+        if current_path.is_empty() {
+            return Ok(()); // Already at root
+        }
+
+        // Remove trailing slash and go up one level
+        let trimmed = current_path.trim_end_matches('/');
+        let parent = match trimmed.rfind('/') {
+            Some(last_slash) => format!("{}/", &trimmed[..last_slash]),
+            None => String::new(), // Go to root
+        };
+        self.open_prefix(parent).await
+    }
+
+    /// Show the folder `prefix` of the open container; empty for its root. Going up selects
+    /// the folder that was come from.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if refreshing the file list fails.
+    pub async fn open_prefix(&mut self, prefix: String) -> color_eyre::Result<()> {
+        let Some(state) = self.browsing_mut() else {
+            return Ok(());
+        };
+        let came_from = state
+            .current_path
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split('/').next())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+        state.current_path = prefix;
+        state.paging.reset();
+        state.marked.clear();
+
+        // Exit search mode when navigating
+        if self.is_searching_files() {
+            self.search = Search::Inactive;
+        }
+        self.refresh_files().await?;
+        if let Some(name) = came_from
+            && let Some(state) = self.browsing_mut()
+            && let Some(index) = state
+                .file_items
+                .iter()
+                .position(|item| item.kind == EntryKind::Folder && item.actual_name == name)
+        {
+            state.selected_index = index;
+        }
+        Ok(())
+    }
+
+    /// Enter search mode.
+    pub fn enter_search_mode(&mut self) {
+        let (files, file_items) = match self.browsing() {
             Some(state) => (state.files.clone(), state.file_items.clone()),
             None => return,
         };
@@ -1092,6 +3268,10 @@ impl App {
     ///
     /// This function currently does not return errors but uses `Result` for API consistency.
     pub fn handle_search_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<()> {
+        if let Some(motion) = Motion::of_key(key_event) {
+            self.move_selection(motion, None);
+            return Ok(());
+        }
         let Search::Files { query, .. } = &mut self.search else {
             return Ok(());
         };
@@ -1115,10 +3295,26 @@ impl App {
             KeyCode::Down if key_event.modifiers == KeyModifiers::CONTROL => {
                 self.move_down();
             }
+            KeyCode::Tab => {
+                let current = query.clone();
+                self.ui.filter_mode = self.ui.filter_mode.next();
+                self.apply_file_search(&current);
+            }
+            KeyCode::Char('e') if key_event.modifiers == KeyModifiers::CONTROL => {
+                self.open_extension_picker();
+            }
             KeyCode::Char(c) => {
                 query.push(c);
                 let current = query.clone();
-                self.apply_file_search(&current);
+                if self.ui.filter_mode.narrows()
+                    && Filter::new(self.ui.filter_mode, &current)
+                        .is_ok_and(|filter| !filter.has_conditions())
+                {
+                    // Everything matching the longer query is already shown
+                    self.narrow_file_search(&current);
+                } else {
+                    self.apply_file_search(&current);
+                }
             }
             _ => {}
         }
@@ -1147,6 +3343,7 @@ impl App {
             KeyCode::Esc => {
                 self.close_modal();
             }
+            #[allow(clippy::collapsible_match)] // the confirm check reads better inside the arm
             KeyCode::Enter => {
                 // Only allow confirm if name is different from original
                 if input != original_path && !input.is_empty() {
@@ -1169,6 +3366,9 @@ impl App {
 
     /// Open the clone dialog for the selected item.
     pub fn open_clone_dialog(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
         let (selected_item, current_path) = match self.browsing() {
             Some(state) => {
                 let Some(item) = self.selected_file_item() else {
@@ -1232,6 +3432,15 @@ impl App {
         };
 
         self.async_op = AsyncOp::None;
+        self.audit(
+            AuditAction::Copy,
+            format!(
+                "{} → {}",
+                self.audit_target(&original_path),
+                new_path.trim_end_matches('/')
+            ),
+            Outcome::of(&result),
+        );
 
         if result.is_ok() {
             let orig = original_path.trim_end_matches('/');
@@ -1341,17 +3550,36 @@ impl App {
         Ok(())
     }
 
-    /// Handle key events when in delete dialog mode.
+    /// Open the move/rename dialog for the selected item.
+    pub fn open_move_dialog(&mut self) {
+        self.open_clone_dialog();
+        if let Modal::Clone {
+            input,
+            original_path,
+            is_folder,
+        } = std::mem::replace(&mut self.modal, Modal::None)
+        {
+            self.modal = Modal::Move {
+                input,
+                original_path,
+                is_folder,
+            };
+        }
+    }
+
+    /// Handle key events when in move dialog mode.
     ///
     /// # Errors
     ///
-    /// Returns an error if the delete operation fails.
-    pub async fn handle_delete_dialog_key_event(
+    /// Returns an error if refreshing the listing after the move fails.
+    pub async fn handle_move_dialog_key_event(
         &mut self,
         key_event: KeyEvent,
     ) -> color_eyre::Result<()> {
-        let Modal::DeleteConfirm {
-            input, target_name, ..
+        let Modal::Move {
+            input,
+            original_path,
+            is_folder,
         } = &mut self.modal
         else {
             return Ok(());
@@ -1361,14 +3589,24 @@ impl App {
             KeyCode::Esc => {
                 self.close_modal();
             }
-            KeyCode::Enter => {
-                // Only allow confirm if the typed name matches the target name
-                if input == target_name {
-                    if let Err(e) = self.execute_delete().await {
-                        self.error_message = Some(format!("Delete failed: {e}"));
-                    }
-                    self.close_modal();
+            // Only allow confirm if name is different from original
+            KeyCode::Enter if input != original_path && !input.is_empty() => {
+                let mut destination = input.trim_start_matches('/').to_string();
+                let source = original_path.clone();
+                let is_folder = *is_folder;
+                // Ensure folder paths end with /
+                if is_folder && !destination.ends_with('/') {
+                    destination.push('/');
+                }
+                self.close_modal();
+                if is_folder && destination.starts_with(&source) {
+                    self.error_message = Some("Cannot move a folder into itself".to_string());
+                    return Ok(());
+                }
+                if !is_folder && self.refuse_if_immutable(&source).await {
+                    return Ok(());
                 }
+                self.execute_move(&source, &destination, is_folder).await?;
             }
             KeyCode::Backspace => {
                 input.pop();
@@ -1381,1346 +3619,7704 @@ impl App {
         Ok(())
     }
 
-    /// Open the delete confirmation dialog for the selected item.
-    pub fn open_delete_dialog(&mut self) {
-        let (selected_item, current_path) = match self.browsing() {
-            Some(state) => {
-                let Some(item) = self.selected_file_item() else {
-                    return;
-                };
-                (item.clone(), state.current_path.clone())
-            }
-            None => return,
-        };
-
-        let (item_name, is_folder) = if selected_item.kind == EntryKind::Folder {
-            (selected_item.actual_name, true)
-        } else {
-            (selected_item.actual_name, false)
+    /// Move a blob or every blob under a folder: copy everything, then delete the originals.
+    ///
+    /// Folders are listed first; moves above the configured limits wait for confirmation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no container is open.
+    pub async fn execute_move(
+        &mut self,
+        source: &str,
+        destination: &str,
+        is_folder: bool,
+    ) -> color_eyre::Result<()> {
+        if self.browsing().is_none() {
+            return Ok(());
+        }
+        let Some(plan) = self.plan_move(source, is_folder).await else {
+            return Ok(());
         };
+        let moves = move_pairs(source, destination, &plan);
+        self.start_move_or_warn(
+            source.to_string(),
+            destination.to_string(),
+            moves,
+            &plan,
+            None,
+        );
+        Ok(())
+    }
 
-        let full_path = if is_folder {
-            Self::join_folder_path(&current_path, &item_name)
-        } else {
-            Self::join_blob_path(&current_path, &item_name)
+    /// The blobs a move of `source` touches, or `None` with the reason shown when the folder
+    /// cannot be listed.
+    async fn plan_move(&mut self, source: &str, is_folder: bool) -> Option<FolderPlan> {
+        if !is_folder {
+            return Some(FolderPlan {
+                blobs: vec![PlannedBlob {
+                    location: ObjectPath::from(source),
+                    version_id: None,
+                    size: 0,
+                    e_tag: None,
+                }],
+                errors: Vec::new(),
+            });
+        }
+        let error = match self.plan_folder(source).await {
+            Ok(plan) => match plan.errors.first() {
+                Some(e) => e.clone(),
+                None => return Some(plan),
+            },
+            Err(e) => e.to_string(),
         };
+        self.error_message = Some(format!("Move failed: could not list {source}: {error}"));
+        None
+    }
 
-        self.modal = Modal::DeleteConfirm {
-            input: String::new(),
-            target_path: full_path,
-            target_name: item_name,
-            is_folder,
-        };
+    /// Start a move, or ask for confirmation first when `plan` exceeds the configured limits.
+    fn start_move_or_warn(
+        &mut self,
+        source: String,
+        destination: String,
+        moves: Vec<(ObjectPath, ObjectPath)>,
+        plan: &FolderPlan,
+        other_tab: Option<usize>,
+    ) {
+        if self
+            .config
+            .limits
+            .exceeded_by(plan.blobs.len(), plan.total_bytes())
+        {
+            self.modal = Modal::LargeOperationWarning {
+                operation: PendingOperation::Move {
+                    source,
+                    destination,
+                    moves,
+                    other_tab,
+                },
+                plan: plan.clone(),
+            };
+            return;
+        }
+        self.start_move(source, destination, moves, other_tab);
     }
 
-    /// Execute the delete operation.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the delete or subsequent file refresh fails.
-    pub async fn execute_delete(&mut self) -> color_eyre::Result<()> {
-        let (target_path, is_folder) = match &self.modal {
-            Modal::DeleteConfirm {
-                target_path,
-                is_folder,
-                ..
-            } => (target_path.clone(), *is_folder),
-            _ => return Ok(()),
+    /// Start moving every pair in the background.
+    fn start_move(
+        &mut self,
+        source: String,
+        destination: String,
+        moves: Vec<(ObjectPath, ObjectPath)>,
+        other_tab: Option<usize>,
+    ) {
+        let Some(browsing) = self.browsing() else {
+            return;
         };
+        let object_store = browsing.object_store.clone();
+        let container = browsing.container_name.clone();
+        // Without a REST client the originals are deleted one request at a time
+        let client = self.rest_client().ok();
 
-        self.async_op = AsyncOp::Deleting(DeleteProgress {
+        let progress = CloneProgress {
             current_file: String::new(),
             files_completed: 0,
-            total_files: 0,
+            total_files: moves.len(),
             error_message: None,
+        };
+        self.async_op = AsyncOp::Moving(progress.clone());
+        let progress = Arc::new(Mutex::new(progress));
+        let handle = {
+            let progress = progress.clone();
+            tokio::spawn(async move {
+                move_blobs(object_store, client, &container, moves, &progress).await
+            })
+        };
+        self.move_task = Some(MoveTask {
+            source,
+            destination,
+            other_tab,
+            progress,
+            handle,
         });
+    }
 
-        let result = if is_folder {
-            self.delete_folder(&target_path).await
-        } else {
-            self.delete_blob(&target_path).await
-        };
+    /// Show how far the background move has got.
+    fn update_move_progress(&mut self) {
+        if let (Some(task), AsyncOp::Moving(progress)) = (&self.move_task, &mut self.async_op) {
+            progress.clone_from(&lock_progress(&task.progress));
+        }
+    }
 
+    /// Once the background move is done, record it and refresh the listings.
+    async fn finish_move(&mut self) {
+        if !self
+            .move_task
+            .as_ref()
+            .is_some_and(|task| task.handle.is_finished())
+        {
+            return;
+        }
+        let Some(task) = self.move_task.take() else {
+            return;
+        };
+        let outcome = task
+            .handle
+            .await
+            .unwrap_or_else(|e| MoveOutcome::Aborted(format!("Move stopped: {e}")));
         self.async_op = AsyncOp::None;
 
-        if result.is_ok() {
-            let name = target_path.trim_end_matches('/');
-            self.success_message = Some(format!("Successfully deleted {name}"));
-            // Refresh the file list
+        let (from, to) = (
+            task.source.trim_end_matches('/'),
+            task.destination.trim_end_matches('/'),
+        );
+        let target = format!("{} → {to}", self.audit_target(from));
+        let audited = match &outcome {
+            MoveOutcome::Moved => Outcome::Succeeded,
+            MoveOutcome::Aborted(problem) | MoveOutcome::OriginalsLeft(problem) => {
+                Outcome::Failed(problem.clone())
+            }
+        };
+        self.audit(AuditAction::Move, target, audited);
+
+        if let Err(e) = self.refresh_files().await {
+            self.error_message = Some(format!("Refresh failed after move: {e}"));
+        }
+        if let Some(other) = task.other_tab {
+            let here = self.tabs.active();
+            self.switch_to_tab(other);
             if let Err(e) = self.refresh_files().await {
-                self.error_message = Some(format!("Refresh failed after delete: {e}"));
+                self.error_message = Some(format!("Refresh failed after move: {e}"));
+            }
+            self.switch_to_tab(here);
+        }
+        match outcome {
+            MoveOutcome::Moved => self.success_message = Some(format!("Moved {from} to {to}")),
+            MoveOutcome::Aborted(message) => self.error_message = Some(message),
+            MoveOutcome::OriginalsLeft(problem) => {
+                self.error_message = Some(format!("Copied {from} to {to}, but {problem}"));
             }
         }
-
-        result
     }
 
-    /// Delete a single blob.
-    async fn delete_blob(&mut self, path: &str) -> color_eyre::Result<()> {
-        let object_store = self
-            .browsing()
-            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?
-            .object_store
-            .clone();
+    /// Mark the selected blob or folder to be copied with [`Self::paste_yanked`].
+    pub fn yank_selected(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let Some(browsing) = self.browsing() else {
+            return;
+        };
+        let paths: Vec<String> = if browsing.marked.is_empty() {
+            match self.selected_file_item() {
+                Some(item) if !item.actual_name.is_empty() => {
+                    vec![Self::item_path(&browsing.current_path, item)]
+                }
+                _ => return,
+            }
+        } else {
+            browsing.marked.keys().cloned().collect()
+        };
 
-        let object_path = ObjectPath::from(path);
+        let yank = Yank {
+            object_store: browsing.object_store.clone(),
+            container: browsing.container_name.clone(),
+            folder: browsing.current_path.clone(),
+            paths,
+        };
+        let what = match yank.paths.as_slice() {
+            [path] => format!("{}/{}", yank.container, path.trim_end_matches('/')),
+            paths => format!("{} items from {}", paths.len(), yank.container),
+        };
+        self.success_message = Some(format!(
+            "Yanked {what} - press P in the destination folder to paste"
+        ));
+        self.error_message = None;
+        self.yanked = Some(yank);
+    }
 
-        // Update progress
-        if let AsyncOp::Deleting(progress) = &mut self.async_op {
-            progress.current_file = path.to_string();
-            progress.total_files = 1;
+    /// Copy the yanked blobs and folders into the current folder, server-side.
+    ///
+    /// Copies within a container use the object store; copies from another container use the
+    /// Copy Blob API so the data stays inside the storage account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the copy fails.
+    pub async fn paste_yanked(&mut self) -> color_eyre::Result<()> {
+        if self.refuse_if_read_only() {
+            return Ok(());
+        }
+        let Some(yank) = self.yanked.clone() else {
+            self.error_message =
+                Some("Nothing yanked - press Y on a blob or folder first".to_string());
+            return Ok(());
+        };
+        let Some(browsing) = self.browsing() else {
+            return Ok(());
+        };
+        let destination_store = browsing.object_store.clone();
+        let destination_container = browsing.container_name.clone();
+        let destination = browsing.current_path.clone();
+        let same_container = destination_container == yank.container;
+        if same_container && destination == yank.folder {
+            self.error_message =
+                Some("Cannot paste onto itself - open another folder first".to_string());
+            return Ok(());
         }
 
-        object_store.delete(&object_path).await?;
-
-        // Update progress
-        if let AsyncOp::Deleting(progress) = &mut self.async_op {
-            progress.files_completed = 1;
+        // Pair every source blob with its destination path
+        let mut sources: Vec<ObjectPath> = Vec::new();
+        for path in &yank.paths {
+            if path.ends_with('/') {
+                let listed: Vec<ObjectPath> = yank
+                    .object_store
+                    .list(Some(&ObjectPath::from(path.as_str())))
+                    .map(|result| result.map(|meta| meta.location))
+                    .collect::<Vec<_>>()
+                    .await
+                    .into_iter()
+                    .collect::<Result<_, _>>()?;
+                sources.extend(listed);
+            } else {
+                sources.push(ObjectPath::from(path.as_str()));
+            }
         }
 
-        Ok(())
-    }
+        self.async_op = AsyncOp::Cloning(CloneProgress {
+            current_file: String::new(),
+            files_completed: 0,
+            total_files: sources.len(),
+            error_message: None,
+        });
+        let rest_client = if same_container {
+            None
+        } else {
+            Some(self.rest_client()?)
+        };
 
-    /// Delete all blobs in a folder (prefix).
-    async fn delete_folder(&mut self, prefix: &str) -> color_eyre::Result<()> {
-        let object_store = self
-            .browsing()
-            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?
-            .object_store
-            .clone();
-
-        let prefix_path = ObjectPath::from(prefix);
-
-        // List all files in the folder
-        let stream = object_store.list(Some(&prefix_path));
-        let objects: Vec<_> = stream.collect().await;
-
-        let total_files = objects.len();
-
-        // Update progress
-        if let AsyncOp::Deleting(progress) = &mut self.async_op {
-            progress.total_files = total_files;
-        }
-
-        let mut files_completed = 0;
-
-        for result in objects {
-            match result {
-                Ok(meta) => {
-                    let file_path = meta.location.as_ref();
-
-                    // Update progress
-                    if let AsyncOp::Deleting(progress) = &mut self.async_op {
-                        progress.current_file = file_path.to_string();
-                    }
-
-                    // Delete the file
-                    if let Err(e) = object_store.delete(&meta.location).await {
-                        if let AsyncOp::Deleting(progress) = &mut self.async_op {
-                            progress.error_message =
-                                Some(format!("Failed to delete {file_path}: {e}"));
-                        }
-                        // Continue with other files even if one fails
-                    } else {
-                        files_completed += 1;
+        let mut errors = Vec::new();
+        for source in &sources {
+            let relative = source
+                .as_ref()
+                .strip_prefix(yank.folder.as_str())
+                .unwrap_or(source.as_ref());
+            let target = format!("{destination}{relative}");
+            if let AsyncOp::Cloning(progress) = &mut self.async_op {
+                progress.current_file = source.to_string();
+            }
 
-                        // Update progress
-                        if let AsyncOp::Deleting(progress) = &mut self.async_op {
-                            progress.files_completed = files_completed;
-                        }
-                    }
+            let result = match &rest_client {
+                None => destination_store
+                    .copy(source, &ObjectPath::from(target.as_str()))
+                    .await
+                    .map_err(BlobrsError::from),
+                Some(client) => {
+                    client
+                        .copy_blob(
+                            &yank.container,
+                            source.as_ref(),
+                            &destination_container,
+                            &target,
+                        )
+                        .await
                 }
-                Err(e) => {
-                    if let AsyncOp::Deleting(progress) = &mut self.async_op {
-                        progress.error_message = Some(format!("Failed to list file: {e}"));
+            };
+            match result {
+                Ok(()) => {
+                    if let AsyncOp::Cloning(progress) = &mut self.async_op {
+                        progress.files_completed += 1;
                     }
                 }
+                Err(e) => errors.push(format!("{source}: {e}")),
             }
         }
 
-        Ok(())
-    }
-
-    /// Filter files based on search query.
-    pub fn filter_files(&mut self) {
-        let query = match &self.search {
-            Search::Files { query, .. } => query.clone(),
-            _ => return,
+        self.async_op = AsyncOp::None;
+        let what = match yank.paths.as_slice() {
+            [path] => format!("{}/{}", yank.container, path.trim_end_matches('/')),
+            paths => format!(
+                "{} items from {}/{}",
+                paths.len(),
+                yank.container,
+                yank.folder.trim_end_matches('/')
+            ),
         };
-        self.apply_file_search(&query);
-        if let Some(state) = self.browsing_mut() {
-            state.selected_index = 0;
+        let to = format!(
+            "{destination_container}/{}",
+            destination.trim_end_matches('/')
+        );
+        let failure = (!errors.is_empty()).then(|| {
+            format!(
+                "{} of {} copies failed: {}",
+                errors.len(),
+                sources.len(),
+                errors.join("; ")
+            )
+        });
+        self.audit(
+            AuditAction::Copy,
+            format!("{what} → {to}"),
+            failure.clone().map_or(Outcome::Succeeded, Outcome::Failed),
+        );
+        self.refresh_files().await?;
+        match failure {
+            None => self.success_message = Some(format!("Copied {what} to {to}")),
+            Some(failure) => self.error_message = Some(failure),
         }
+        Ok(())
     }
 
-    fn apply_file_search(&mut self, query: &str) {
-        let (all_files, all_file_items) = match &self.search {
-            Search::Files {
-                all_files,
-                all_file_items,
-                ..
-            } => (all_files.clone(), all_file_items.clone()),
-            _ => return,
+    /// Handle key events when in delete dialog mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the delete operation fails.
+    pub async fn handle_delete_dialog_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> color_eyre::Result<()> {
+        let (input, confirmation) = match &mut self.modal {
+            Modal::DeleteConfirm {
+                input, target_name, ..
+            } => (input, target_name.as_str()),
+            Modal::DeleteMarked { input, .. } => (input, DELETE_MARKED_CONFIRMATION),
+            _ => return Ok(()),
         };
 
-        if let Some(state) = self.browsing_mut() {
-            if query.is_empty() {
-                state.files = all_files;
-                state.file_items = all_file_items;
-            } else {
-                let filtered_items: Vec<FileItem> = all_file_items
-                    .iter()
-                    .filter(|item| {
-                        item.actual_name
-                            .to_lowercase()
-                            .contains(&query.to_lowercase())
-                    })
-                    .cloned()
-                    .collect();
-
-                state.file_items.clone_from(&filtered_items);
-                state.files = filtered_items
-                    .iter()
-                    .map(|item| item.display_name.clone())
-                    .collect();
+        match key_event.code {
+            KeyCode::Esc => {
+                self.close_modal();
             }
-            state.selected_index = 0;
-        }
-    }
-
-    fn apply_container_search(&mut self, query: &str) {
-        let all_containers = match &self.search {
-            Search::Containers { all_containers, .. } => all_containers.clone(),
-            _ => return,
-        };
-
-        if query.is_empty() {
-            self.containers = all_containers;
-        } else {
-            self.containers = all_containers
-                .iter()
-                .filter(|container| {
-                    container
-                        .name
-                        .to_lowercase()
-                        .contains(&query.to_lowercase())
-                })
-                .cloned()
-                .collect();
-        }
-        self.selected_container_index = 0;
-    }
-
-    /// Load the list of containers from Azure Storage.
-    async fn load_containers(&mut self) -> color_eyre::Result<()> {
-        self.async_op = AsyncOp::LoadingContainers;
-        self.error_message = None;
-        self.success_message = None;
-
-        match self.list_containers().await {
-            Ok(containers) => {
-                // Always store the full list
-                self.all_containers.clone_from(&containers);
-
-                let search_query = match &self.search {
-                    Search::Containers { query, .. } => Some(query.clone()),
-                    _ => None,
-                };
-
-                if let Search::Containers { all_containers, .. } = &mut self.search {
-                    all_containers.clone_from(&containers);
-                }
-
-                if let Some(query) = search_query {
-                    if query.is_empty() {
-                        self.containers = containers;
-                        self.selected_container_index = 0;
-                    } else {
-                        self.apply_container_search(&query);
-                    }
-                } else {
-                    self.containers = containers;
-                    self.selected_container_index = 0;
+            KeyCode::Enter => {
+                // Only allow confirm if the typed name matches the target name
+                if input == confirmation
+                    && let Err(e) = self.execute_delete().await
+                {
+                    self.error_message = Some(format!("Delete failed: {e}"));
                 }
             }
-            Err(e) => {
-                self.error_message = Some(format!("Failed to list containers: {e}"));
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
             }
+            _ => {}
         }
-
-        self.async_op = AsyncOp::None;
         Ok(())
     }
 
-    /// List all containers in the storage account with pagination support.
-    async fn list_containers(&mut self) -> Result<Vec<ContainerInfo>, String> {
-        let account_name = &self.storage_account;
-        let access_key = &self.access_key;
-
-        // Decode the base64 access key
-        let key = general_purpose::STANDARD
-            .decode(access_key)
-            .map_err(|e| format!("Failed to decode access key: {e}"))?;
-
-        let mut all_containers = Vec::new();
-        let mut next_marker: Option<String> = None;
-
-        // Continue fetching pages until no more results
-        loop {
-            // Create the request URL with pagination support
-            let mut url =
-                format!("https://{account_name}.blob.core.windows.net/?comp=list&maxresults=5000");
-            if let Some(ref marker) = next_marker {
-                let _ = write!(url, "&marker={}", urlencoding::encode(marker));
-            }
-
-            // Create timestamp in RFC 1123 format
-            let now = Utc::now();
-            let date = now.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
-
-            // Construct the string to sign for Azure Storage API
-            // Format: VERB + "\n" + Content-Encoding + "\n" + Content-Language + "\n" + Content-Length + "\n" +
-            //         Content-MD5 + "\n" + Content-Type + "\n" + Date + "\n" + If-Modified-Since + "\n" +
-            //         If-Match + "\n" + If-None-Match + "\n" + If-Unmodified-Since + "\n" + Range + "\n" +
-            //         CanonicalizedHeaders + CanonicalizedResource
-            let canonicalized_resource = if let Some(ref marker) = next_marker {
-                format!("/{account_name}/\ncomp:list\nmarker:{marker}\nmaxresults:5000")
-            } else {
-                format!("/{account_name}/\ncomp:list\nmaxresults:5000")
+    /// Open the delete confirmation dialog for the marked entries, or else the selected item.
+    ///
+    /// Folders are listed first so the dialog can show how many blobs and bytes will go.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if listing the folder fails.
+    pub async fn open_delete_dialog(&mut self) -> color_eyre::Result<()> {
+        if self.refuse_if_read_only() {
+            return Ok(());
+        }
+        if self.marked_summary().is_some() {
+            let plan = self.plan_marked().await?;
+            self.modal = Modal::DeleteMarked {
+                input: String::new(),
+                plan,
             };
-
-            let string_to_sign = format!(
-                "GET\n\n\n\n\n\n\n\n\n\n\n\nx-ms-date:{date}\nx-ms-version:2020-08-04\n{canonicalized_resource}"
-            );
-
-            // Generate HMAC-SHA256 signature
-            let mut mac = Hmac::<Sha256>::new_from_slice(&key)
-                .map_err(|e| format!("Failed to create HMAC: {e}"))?;
-            mac.update(string_to_sign.as_bytes());
-            let signature = general_purpose::STANDARD.encode(mac.finalize().into_bytes());
-
-            // Create authorization header
-            let authorization = format!("SharedKey {account_name}:{signature}");
-
-            // Make the HTTP request
-            let client = reqwest::Client::new();
-            let response = client
-                .get(&url)
-                .header("x-ms-date", &date)
-                .header("x-ms-version", "2020-08-04")
-                .header("Authorization", &authorization)
-                .send()
-                .await
-                .map_err(|e| format!("HTTP request failed: {e}"))?;
-
-            let status = response.status();
-            let response_text = response
-                .text()
-                .await
-                .map_err(|e| format!("Failed to read response: {e}"))?;
-
-            if !status.is_success() {
-                return Err(format!(
-                    "HTTP {} {} - {}",
-                    status.as_u16(),
-                    status.canonical_reason().unwrap_or(""),
-                    response_text
-                ));
+            return Ok(());
+        }
+        let (selected_item, current_path) = match self.browsing() {
+            Some(state) => {
+                let Some(item) = self.selected_file_item() else {
+                    return Ok(());
+                };
+                (item.clone(), state.current_path.clone())
             }
+            None => return Ok(()),
+        };
 
-            // Parse XML response and extract containers and next marker
-            let (mut containers, next_marker_option) =
-                Self::parse_containers_xml_with_marker(&response_text)
-                    .map_err(|e| format!("Failed to parse XML response: {e}"))?;
+        let (item_name, is_folder) = if selected_item.kind == EntryKind::Folder {
+            (selected_item.actual_name, true)
+        } else {
+            (selected_item.actual_name, false)
+        };
 
-            // Add containers from this page to our collection
-            all_containers.append(&mut containers);
+        let full_path = if is_folder {
+            Self::join_folder_path(&current_path, &item_name)
+        } else {
+            Self::join_blob_path(&current_path, &item_name)
+        };
 
-            // Check if there are more pages
-            if let Some(marker) = next_marker_option {
-                next_marker = Some(marker);
-            } else {
-                // No more pages, break the loop
-                break;
+        let plan = if is_folder {
+            Some(self.plan_folder(&full_path).await?)
+        } else {
+            if self.refuse_if_immutable(&full_path).await {
+                return Ok(());
             }
-        }
+            None
+        };
 
-        Ok(all_containers)
+        self.modal = Modal::DeleteConfirm {
+            input: String::new(),
+            target_path: full_path,
+            target_name: item_name,
+            is_folder,
+            size: selected_item.size,
+            plan,
+        };
+        Ok(())
     }
 
-    /// Parse XML response from Azure Storage list containers API with pagination marker support.
-    fn parse_containers_xml_with_marker(
-        xml: &str,
-    ) -> color_eyre::Result<(Vec<ContainerInfo>, Option<String>)> {
-        let mut containers = Vec::new();
-
-        // Use regex to find all container names
-        // Pattern: <Container>...<Name>container_name</Name>...</Container>
-        let container_regex = Regex::new(r"<Container>.*?<Name>(.*?)</Name>.*?</Container>")?;
-
-        for cap in container_regex.captures_iter(xml) {
-            if let Some(name_match) = cap.get(1) {
-                let name = name_match.as_str().to_string();
-                if !name.is_empty() {
-                    containers.push(ContainerInfo { name });
+    /// Start the confirmed delete in the background.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folder cannot be listed or no container is open.
+    pub async fn execute_delete(&mut self) -> color_eyre::Result<()> {
+        let (target_path, is_folder, plan) = match &mut self.modal {
+            Modal::DeleteConfirm {
+                target_path,
+                is_folder,
+                plan,
+                ..
+            } => (target_path.clone(), *is_folder, plan.take()),
+            Modal::DeleteMarked { plan, .. } => {
+                let plan = std::mem::take(plan);
+                self.close_modal();
+                if self
+                    .config
+                    .limits
+                    .exceeded_by(plan.blobs.len(), plan.total_bytes())
+                {
+                    self.modal = Modal::LargeOperationWarning {
+                        operation: PendingOperation::DeleteMarked,
+                        plan,
+                    };
+                    return Ok(());
                 }
+                self.start_delete_marked(plan);
+                return Ok(());
             }
-        }
+            _ => return Ok(()),
+        };
+        self.close_modal();
 
-        // Look for NextMarker to determine if there are more pages
-        // Pattern: <NextMarker>marker_value</NextMarker>
-        let next_marker_regex = Regex::new(r"<NextMarker>(.*?)</NextMarker>")?;
-        let next_marker = next_marker_regex
-            .captures(xml)
-            .and_then(|cap| cap.get(1))
-            .map(|m| m.as_str().to_string())
-            .filter(|s| !s.is_empty());
+        let plan = if is_folder {
+            let plan = match plan {
+                Some(plan) => plan,
+                None => self.plan_folder(&target_path).await?,
+            };
+            if self
+                .config
+                .limits
+                .exceeded_by(plan.blobs.len(), plan.total_bytes())
+            {
+                self.modal = Modal::LargeOperationWarning {
+                    operation: PendingOperation::Delete { target_path },
+                    plan,
+                };
+                return Ok(());
+            }
+            Some(plan)
+        } else {
+            None
+        };
 
-        Ok((containers, next_marker))
+        self.start_delete(&target_path, plan)
     }
 
-    /// Select a container and initialize the object store.
-    async fn select_container(&mut self) -> color_eyre::Result<()> {
-        if self.containers.is_empty() {
-            return Ok(());
-        }
-
-        let selected_container = &self.containers[self.selected_container_index];
-
-        let azure_client = MicrosoftAzureBuilder::new()
-            .with_account(&self.storage_account)
-            .with_container_name(&selected_container.name)
-            .with_access_key(&self.access_key)
-            .build()?;
-
-        self.session = Session::Browsing(BrowsingState {
-            object_store: Arc::new(azure_client),
-            current_path: String::new(),
-            files: Vec::new(),
-            file_items: Vec::new(),
-            selected_index: 0,
+    /// Start deleting a blob, or every blob in a folder plan, in the background.
+    fn start_delete(
+        &mut self,
+        target_path: &str,
+        plan: Option<FolderPlan>,
+    ) -> color_eyre::Result<()> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let object_store = browsing.object_store.clone();
+        let container = browsing.container_name.clone();
+        let selected_index = browsing.selected_index;
+        // Without a REST client every blob is deleted one request at a time
+        let client = self.rest_client().ok();
+
+        let progress = Arc::new(Mutex::new(DeleteProgress::default()));
+        self.async_op = AsyncOp::Deleting(DeleteProgress::default());
+        let handle = {
+            let progress = progress.clone();
+            let path = target_path.to_string();
+            tokio::spawn(async move {
+                match plan {
+                    Some(plan) => {
+                        delete_folder(object_store, client, &container, plan, &progress).await;
+                        Ok(())
+                    }
+                    None => delete_blob(object_store, &path, &progress).await,
+                }
+            })
+        };
+        self.delete_task = Some(DeleteTask {
+            target_path: target_path.to_string(),
+            selected_index,
+            progress,
+            handle,
         });
-        self.search = Search::Inactive;
-
-        // Load initial file list
-        self.refresh_files().await?;
         Ok(())
     }
 
-    /// Move container selection up.
-    fn move_container_up(&mut self) {
-        if !self.containers.is_empty() && self.selected_container_index > 0 {
-            self.selected_container_index -= 1;
+    /// Start deleting every blob of the marked entries and clear the marks.
+    fn start_delete_marked(&mut self, plan: FolderPlan) {
+        let count = self.browsing().map_or(0, |state| state.marked.len());
+        if let Some(state) = self.browsing_mut() {
+            state.marked.clear();
+        }
+        if let Err(e) = self.start_delete(&format!("{count} marked items"), Some(plan)) {
+            self.error_message = Some(format!("Delete failed: {e}"));
         }
     }
 
-    /// Move container selection down.
-    fn move_container_down(&mut self) {
-        if !self.containers.is_empty() && self.selected_container_index < self.containers.len() - 1
-        {
-            self.selected_container_index += 1;
+    /// Show how far the background delete has got.
+    fn update_delete_progress(&mut self) {
+        if let (Some(task), AsyncOp::Deleting(progress)) = (&self.delete_task, &mut self.async_op) {
+            progress.clone_from(&lock_progress(&task.progress));
         }
     }
 
-    /// Enter container search mode.
-    pub fn enter_container_search_mode(&mut self) {
-        // Always start search from the full container list
-        self.containers = self.all_containers.clone();
-        self.selected_container_index = 0;
-        self.search = Search::Containers {
-            query: String::new(),
-            all_containers: self.all_containers.clone(),
+    /// Once the background delete is done, record it and refresh the listing.
+    async fn finish_delete(&mut self) {
+        if !self
+            .delete_task
+            .as_ref()
+            .is_some_and(|task| task.handle.is_finished())
+        {
+            return;
+        }
+        let Some(task) = self.delete_task.take() else {
+            return;
+        };
+        let result = match task.handle.await {
+            Ok(result) => result,
+            Err(e) => Err(color_eyre::eyre::eyre!("Delete stopped: {e}")),
         };
-        self.error_message = None;
-        self.success_message = None;
-    }
 
-    /// Exit container search mode and restore original container list.
-    pub fn exit_container_search_mode(&mut self) {
-        if let Search::Containers { all_containers, .. } = &self.search {
-            self.containers = all_containers.clone();
-            self.selected_container_index = 0;
+        // Folder deletes carry on past single failures, reporting them on the progress
+        let problem = lock_progress(&task.progress).error_message.clone();
+        let outcome = match (&result, problem) {
+            (Err(e), _) => Outcome::Failed(e.to_string()),
+            (Ok(()), Some(problem)) => Outcome::Failed(problem),
+            (Ok(()), None) => Outcome::Succeeded,
+        };
+        let target_path = task.target_path.as_str();
+        self.audit(
+            AuditAction::Delete,
+            self.audit_target(target_path.trim_end_matches('/')),
+            outcome,
+        );
+        self.async_op = AsyncOp::None;
+
+        match result {
+            Ok(()) => {
+                let name = target_path.trim_end_matches('/');
+                self.success_message = Some(format!("Successfully deleted {name}"));
+                // Refresh the file list
+                if let Err(e) = self.refresh_files().await {
+                    self.error_message = Some(format!("Refresh failed after delete: {e}"));
+                }
+                // Keep the cursor where it was, on the entry that followed the deleted one
+                if let Some(state) = self.browsing_mut() {
+                    state.selected_index =
+                        task.selected_index.min(state.files.len().saturating_sub(1));
+                    state.marked.remove(target_path);
+                }
+            }
+            Err(e) => self.error_message = Some(format!("Delete failed: {e}")),
         }
-        self.search = Search::Inactive;
     }
 
-    /// Handle key events when in container search mode.
+    /// Handle a key press in the startup credential picker.
     ///
     /// # Errors
     ///
-    /// This function currently does not return errors but uses `Result` for API consistency.
-    pub fn handle_container_search_key_event(
+    /// Returns an error if listing the containers fails.
+    pub async fn handle_credential_picker_key_event(
         &mut self,
         key_event: KeyEvent,
     ) -> color_eyre::Result<()> {
-        let Search::Containers { query, .. } = &mut self.search else {
+        let Modal::CredentialPicker {
+            credentials,
+            selected,
+            ..
+        } = &mut self.modal
+        else {
             return Ok(());
         };
 
         match key_event.code {
-            KeyCode::Esc => {
-                self.exit_container_search_mode();
-            }
-            KeyCode::Enter => {
-                // Exit search mode but keep the filtered results
-                self.search = Search::Inactive;
+            KeyCode::Up | KeyCode::Char('k') => {
+                *selected = selected.saturating_sub(1);
             }
-            KeyCode::Backspace => {
-                query.pop();
-                let current = query.clone();
-                self.apply_container_search(&current);
+            KeyCode::Down | KeyCode::Char('j') if *selected + 1 < credentials.len() => {
+                *selected += 1;
             }
-            KeyCode::Up if key_event.modifiers == KeyModifiers::CONTROL => {
-                self.move_container_up();
+            KeyCode::Enter => {
+                let credential = credentials.swap_remove(*selected);
+                self.close_modal();
+                self.storage_account = credential.account;
+                self.access_key = credential.access_key;
+                self.load_containers().await?;
             }
-            KeyCode::Down if key_event.modifiers == KeyModifiers::CONTROL => {
-                self.move_container_down();
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.events.send(AppEvent::Quit);
             }
-            KeyCode::Char(c) => {
-                query.push(c);
-                let current = query.clone();
-                self.apply_container_search(&current);
+            KeyCode::Char('c' | 'C') if key_event.modifiers == KeyModifiers::CONTROL => {
+                self.events.send(AppEvent::Quit);
             }
             _ => {}
         }
         Ok(())
     }
 
-    /// Filter containers based on search query.
-    pub fn filter_containers(&mut self) {
-        let query = match &self.search {
-            Search::Containers { query, .. } => query.clone(),
-            _ => return,
+    /// Follow the selected blob, showing new lines as they are appended.
+    pub fn open_tail(&mut self) {
+        let Some(state) = self.browsing() else {
+            return;
         };
-        self.apply_container_search(&query);
+        let Some(item) = self.selected_file_item() else {
+            return;
+        };
+        if item.kind != EntryKind::File || item.deleted || item.version_id.is_some() {
+            return;
+        }
+        let blob_path = Self::join_blob_path(&state.current_path, &item.actual_name);
+        let view = TailView::start(state.object_store.clone(), blob_path);
+        self.modal = Modal::Tail { view, back: 0 };
     }
 
-    /// Show information about the currently selected blob or folder.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if fetching blob/folder metadata fails.
-    pub async fn show_blob_info(&mut self) -> color_eyre::Result<()> {
-        let Some(selected_item) = self.selected_file_item().cloned() else {
-            return Ok(());
+    /// Download the selected blob to be opened in `$EDITOR` once the key has been handled.
+    pub async fn open_editor(&mut self) {
+        let Some(state) = self.browsing() else {
+            return;
         };
-
-        let info = if selected_item.kind == EntryKind::Folder {
-            // Get folder information (blob count and total size)
-            self.get_folder_info(&selected_item.actual_name).await?
-        } else {
-            // Get individual blob information
-            self.get_blob_info(&selected_item.actual_name).await?
+        let Some(item) = self.selected_file_item() else {
+            return;
         };
+        if item.kind != EntryKind::File || item.deleted || item.version_id.is_some() {
+            return;
+        }
+        let blob_path = Self::join_blob_path(&state.current_path, &item.actual_name);
+        let object_store = state.object_store.clone();
+        if item.size.is_some_and(|size| size > MAX_EDIT_BYTES) {
+            self.error_message = Some(format!(
+                "{} is too large to edit (over {} MiB)",
+                item.actual_name,
+                MAX_EDIT_BYTES / (1024 * 1024)
+            ));
+            return;
+        }
+        if self.refuse_if_read_only() || self.refuse_if_immutable(&blob_path).await {
+            return;
+        }
+        match EditSession::start(object_store, &blob_path).await {
+            Ok(session) => self.pending_edit = Some(session),
+            Err(e) => {
+                self.error_message = Some(format!(
+                    "Could not open {blob_path} for editing: {}",
+                    describe_error(&e)
+                ));
+            }
+        }
+    }
 
-        self.modal = Modal::BlobInfo { info };
+    /// Suspend the interface while `session`'s file is open in the editor, then show what
+    /// changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the terminal cannot be taken back from the editor.
+    async fn edit_in_terminal<W: std::io::Write>(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+        session: EditSession,
+    ) -> color_eyre::Result<()> {
+        let command = edit::editor_command();
+        self.suspend(terminal)?;
+        let status = edit::run_editor(&command, &session.local_path);
+        self.resume(terminal)?;
+
+        match status {
+            Ok(status) if status.success() => self.review_edit(session).await,
+            Ok(status) => {
+                session.discard();
+                self.error_message = Some(format!(
+                    "`{command}` exited with {status}; nothing was uploaded"
+                ));
+            }
+            Err(e) => {
+                session.discard();
+                self.error_message = Some(format!(
+                    "Could not run `{command}`: {e}. Set $EDITOR to the editor to use"
+                ));
+            }
+        }
         Ok(())
     }
 
-    /// Get information about a folder (blob count and total size).
-    async fn get_folder_info(&self, folder_name: &str) -> color_eyre::Result<BlobInfo> {
-        let browsing = self
-            .browsing()
-            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
-        let object_store = browsing.object_store.clone();
-
-        let folder_path = Self::join_folder_path(&browsing.current_path, folder_name);
-
-        let object_path = ObjectPath::from(folder_path.as_str());
-
-        // List all objects in this folder (recursively)
-        let mut blob_count = 0;
-        let mut total_size: u64 = 0; // Explicitly type as u64
-
-        let stream = object_store.list(Some(&object_path));
-        let objects: Vec<_> = stream.collect().await;
-
-        for meta in objects.into_iter().flatten() {
-            blob_count += 1;
-            total_size += meta.size;
+    /// Hand the terminal over to another program: stop reading keys and leave the alternate
+    /// screen.
+    fn suspend<W: std::io::Write>(
+        &self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+    ) -> color_eyre::Result<()> {
+        self.events.pause();
+        if self.config.browse.mouse {
+            execute!(terminal.backend_mut(), DisableMouseCapture)?;
         }
-
-        Ok(BlobInfo::Folder {
-            name: folder_name.to_string(),
-            blob_count,
-            total_size,
-        })
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
+        Ok(())
     }
 
-    /// Get information about a specific blob.
-    async fn get_blob_info(&self, blob_name: &str) -> color_eyre::Result<BlobInfo> {
-        let browsing = self
-            .browsing()
-            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
-        let object_store = browsing.object_store.clone();
-
-        let blob_path = Self::join_blob_path(&browsing.current_path, blob_name);
-
-        let object_path = ObjectPath::from(blob_path.as_str());
-
-        match object_store.head(&object_path).await {
-            Ok(meta) => Ok(BlobInfo::File {
-                name: blob_name.to_string(),
-                size: meta.size,
-                last_modified: meta
-                    .last_modified
-                    .format("%Y-%m-%d %H:%M:%S UTC")
-                    .to_string(),
-                etag: meta.e_tag.clone(),
-            }),
-            Err(e) => Err(color_eyre::eyre::eyre!("Failed to get blob metadata: {e}",)),
+    /// Take the terminal back after [`Self::suspend`], redrawing everything.
+    fn resume<W: std::io::Write>(
+        &self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+    ) -> color_eyre::Result<()> {
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+        if self.config.browse.mouse {
+            execute!(terminal.backend_mut(), EnableMouseCapture)?;
         }
+        terminal.clear()?;
+        self.events.resume();
+        Ok(())
     }
 
-    /// Copy the full blob path to clipboard.
+    /// Pipe the selected blob into the shell `command`, or `$PAGER` when it is empty, once
+    /// the terminal is free.
+    fn pipe_selected(&mut self, command: &str) {
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let Some(item) = self
+            .selected_file_item()
+            .filter(|item| item.kind == EntryKind::File && !item.deleted)
+        else {
+            self.error_message = Some("Select a blob to pipe".to_string());
+            return;
+        };
+        let command = if command.is_empty() {
+            pipe::default_command()
+        } else {
+            command.to_string()
+        };
+        self.pending_pipe = Some(PipeRequest {
+            object_store: state.object_store.clone(),
+            path: ObjectPath::from(Self::join_blob_path(&state.current_path, &item.actual_name)),
+            command,
+        });
+    }
+
+    /// Suspend the interface while `request`'s command reads the blob, then wait for Enter so
+    /// its output can be read before the interface covers it.
     ///
     /// # Errors
     ///
-    /// Returns an error if clipboard access fails.
-    pub fn copy_blob_path_to_clipboard(&mut self) -> color_eyre::Result<()> {
-        let (selected_item, current_path) = match self.browsing() {
-            Some(state) => {
-                let Some(item) = self.selected_file_item() else {
-                    return Ok(());
-                };
-                (item.clone(), state.current_path.clone())
+    /// Returns an error if the terminal cannot be taken back from the command.
+    async fn pipe_in_terminal<W: std::io::Write>(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+        request: PipeRequest,
+    ) -> color_eyre::Result<()> {
+        self.suspend(terminal)?;
+        let result = pipe::pipe_to(
+            request.object_store.as_ref(),
+            &request.path,
+            &request.command,
+        )
+        .await;
+        eprint!("\n[Press Enter to return to blobrs]");
+        let _ = std::io::stdin().read_line(&mut String::new());
+        self.resume(terminal)?;
+
+        match result {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                self.error_message = Some(format!("`{}` exited with {status}", request.command));
             }
-            None => return Ok(()),
-        };
-
-        if selected_item.actual_name.is_empty() {
-            return Ok(());
+            Err(e) => self.error_message = Some(format!("Pipe failed: {e}")),
         }
-        let is_folder = selected_item.kind == EntryKind::Folder;
-        let full_path = if is_folder {
-            Self::join_folder_path(&current_path, &selected_item.actual_name)
-        } else {
-            Self::join_blob_path(&current_path, &selected_item.actual_name)
-        };
-
-        // Copy to clipboard
-        let mut clipboard = Clipboard::new()?;
-        clipboard.set_text(full_path.clone())?;
-
-        // Set success message
-        let item_type = if is_folder { "folder" } else { "file" };
-        self.success_message = Some(format!("Copied {item_type} path to clipboard: {full_path}"));
-        self.error_message = None; // Clear any existing error message
-
         Ok(())
     }
 
-    /// Show the download destination picker.
-    pub fn show_download_picker(&mut self) {
-        if let Some(state) = self.browsing() {
-            if state.files.is_empty() {
+    /// Ask whether to upload the file left by the editor, unless it is unchanged.
+    async fn review_edit(&mut self, session: EditSession) {
+        let edited = match session.edited().await {
+            Ok(edited) => edited,
+            Err(e) => {
+                self.error_message = Some(format!("Could not read the edited file: {e}"));
                 return;
             }
-        } else {
+        };
+        if edited == session.original {
+            session.discard();
+            self.success_message = Some(format!("No changes to {}", session.blob_path));
             return;
         }
-
-        self.modal = Modal::DownloadPicker { destination: None };
+        let diff = diff::unified_diff(
+            &String::from_utf8_lossy(&session.original),
+            &String::from_utf8_lossy(&edited),
+            &session.blob_path,
+            &session.blob_path,
+        );
+        self.modal = Modal::EditUpload { session, diff };
     }
 
-    /// Start the download process for the selected file or folder.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the download operation fails.
-    pub async fn start_download(&mut self) -> color_eyre::Result<()> {
-        let destination = match &self.modal {
-            Modal::DownloadPicker { destination } => destination.clone(),
-            _ => None,
+    /// Handle a key press while an edit waits to be uploaded: `y` uploads it, `e` opens the
+    /// editor again, and `n` throws it away.
+    async fn handle_edit_upload_key_event(&mut self, key_event: KeyEvent) {
+        if !matches!(
+            key_event.code,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('y' | 'e' | 'n')
+        ) {
+            return;
+        }
+        let Modal::EditUpload { session, .. } = std::mem::replace(&mut self.modal, Modal::None)
+        else {
+            return;
         };
+        match key_event.code {
+            KeyCode::Enter | KeyCode::Char('y') => self.upload_edit(session).await,
+            KeyCode::Char('e') => self.pending_edit = Some(session),
+            _ => {
+                session.discard();
+                self.success_message = Some(format!("Discarded the edit of {}", session.blob_path));
+            }
+        }
+    }
 
-        let Some(selected_item) = self.selected_file_item().cloned() else {
-            return Ok(());
+    /// Upload an edit over its blob, keeping the edited file if that fails.
+    async fn upload_edit(&mut self, session: EditSession) {
+        let uploaded = match session.edited().await {
+            Ok(edited) => session.upload(edited).await,
+            Err(e) => {
+                self.error_message = Some(format!("Could not read the edited file: {e}"));
+                return;
+            }
         };
+        self.audit(
+            AuditAction::Edit,
+            self.audit_target(&session.blob_path),
+            Outcome::of(&uploaded),
+        );
+        match uploaded {
+            Ok(_) => {
+                session.discard();
+                if let Err(e) = self.refresh_files().await {
+                    self.error_message = Some(format!("Refresh failed: {}", describe_error(&e)));
+                }
+                self.success_message = Some(format!("Uploaded the edit of {}", session.blob_path));
+            }
+            Err(object_store::Error::Precondition { .. }) => {
+                self.error_message = Some(format!(
+                    "{} changed since it was opened, so the edit was not uploaded; it is kept at {}",
+                    session.blob_path,
+                    session.local_path.display()
+                ));
+            }
+            Err(e) => {
+                self.error_message = Some(format!(
+                    "Upload failed: {e}; the edit is kept at {}",
+                    session.local_path.display()
+                ));
+            }
+        }
+    }
 
-        let Some(destination) = destination else {
-            return Ok(());
+    /// Handle a key press in the tail view. Scrolling up pauses following until `G` or End.
+    pub fn handle_tail_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::Tail { view, back } = &mut self.modal else {
+            return;
         };
-        let is_folder = selected_item.kind == EntryKind::Folder;
-        let name = selected_item.actual_name;
-
-        self.async_op = AsyncOp::Downloading(DownloadProgress {
-            current_file: String::new(),
-            files_completed: 0,
-            total_files: 0,
-            bytes_downloaded: 0,
-            total_bytes: None,
-            error_message: None,
-        });
-        self.close_modal();
+        let line_count = view.lines().len();
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q' | 'f') => self.close_modal(),
+            KeyCode::Up | KeyCode::Char('k') => *back = (*back + 1).min(line_count),
+            KeyCode::Down | KeyCode::Char('j') => *back = back.saturating_sub(1),
+            KeyCode::PageUp => *back = (*back + 20).min(line_count),
+            KeyCode::PageDown => *back = back.saturating_sub(20),
+            KeyCode::Home | KeyCode::Char('g') => *back = line_count,
+            KeyCode::End | KeyCode::Char('G') => *back = 0,
+            _ => {}
+        }
+    }
 
-        if is_folder {
-            self.download_folder(&name, &destination).await?;
-        } else {
-            self.download_file(&name, &destination).await?;
+    /// Open the background jobs panel, selecting the most recent job.
+    pub fn open_jobs_panel(&mut self) {
+        if self.jobs.is_empty() {
+            self.success_message = Some("No background jobs".to_string());
+            return;
         }
+        self.modal = Modal::Jobs {
+            job_index: self.jobs.len() - 1,
+            file_index: None,
+        };
+    }
 
-        self.async_op = AsyncOp::None;
-        Ok(())
+    /// The first container or entry whose name starts with `prefix`, ignoring case.
+    pub(crate) fn jump_target(&self, prefix: &str) -> Option<usize> {
+        let prefix = prefix.to_lowercase();
+        let starts = |name: &str| name.to_lowercase().starts_with(&prefix);
+        match self.browsing() {
+            Some(state) => state
+                .file_items
+                .iter()
+                .position(|item| starts(&item.actual_name)),
+            None => self
+                .containers
+                .iter()
+                .position(|container| starts(&container.name)),
+        }
     }
 
-    /// Download a single file.
-    async fn download_file(
-        &mut self,
-        file_name: &str,
-        destination: &Path,
-    ) -> color_eyre::Result<()> {
-        let browsing = self
-            .browsing()
-            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
-        let object_store = browsing.object_store.clone();
+    /// Handle a key press while typing a name to jump to. Letters extend the name and move the
+    /// selection to the first entry starting with it; any other key ends the jump and is
+    /// returned to be handled as usual, so `Enter` opens what was jumped to.
+    pub fn handle_jump_key_event(&mut self, key_event: KeyEvent) -> Option<KeyEvent> {
+        let Modal::Jump { prefix } = &mut self.modal else {
+            return Some(key_event);
+        };
+        match key_event.code {
+            KeyCode::Esc => {
+                self.close_modal();
+                return None;
+            }
+            KeyCode::Backspace if prefix.is_empty() => {
+                self.close_modal();
+                return None;
+            }
+            KeyCode::Backspace => {
+                prefix.pop();
+            }
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                prefix.push(c);
+            }
+            _ => {
+                self.close_modal();
+                return Some(key_event);
+            }
+        }
+        let prefix = prefix.clone();
+        if let Some(index) = self.jump_target(&prefix) {
+            if let Some(state) = self.browsing_mut() {
+                state.selected_index = index;
+            } else {
+                self.selected_container_index = index;
+            }
+        }
+        None
+    }
 
-        let blob_path = Self::join_blob_path(&browsing.current_path, file_name);
+    /// Open command mode with an empty command.
+    pub fn open_command_mode(&mut self) {
+        self.open_command_mode_with("");
+    }
 
-        let object_path = ObjectPath::from(blob_path.as_str());
+    /// Open command mode with `input` already typed.
+    pub fn open_command_mode_with(&mut self, input: &str) {
+        self.modal = Modal::Command {
+            input: input.to_string(),
+            selected: 0,
+        };
+    }
 
-        // Initialize progress
-        self.async_op = AsyncOp::Downloading(DownloadProgress {
-            current_file: file_name.to_string(),
-            files_completed: 0,
-            total_files: 1,
-            bytes_downloaded: 0,
-            total_bytes: None,
-            error_message: None,
-        });
+    /// Handle a key press in command mode. Returns the key to handle next when the command
+    /// run stands for one.
+    pub async fn handle_command_key_event(&mut self, key_event: KeyEvent) -> Option<KeyEvent> {
+        let selecting = self.is_selecting();
+        let Modal::Command { input, selected } = &mut self.modal else {
+            return None;
+        };
+        let suggestions = palette::matching(input, selecting);
+        match key_event.code {
+            KeyCode::Esc => self.close_modal(),
+            KeyCode::Enter => {
+                let input = std::mem::take(input);
+                let selected = *selected;
+                self.close_modal();
+                return self.run_command(&input, selected).await;
+            }
+            KeyCode::Tab => {
+                if let Some(command) = suggestions.get(*selected) {
+                    let args = input.split_once(' ').map_or("", |(_, args)| args).trim();
+                    *input = format!("{} {args}", command.name);
+                    *selected = 0;
+                }
+            }
+            KeyCode::Up => *selected = selected.saturating_sub(1),
+            KeyCode::Char('p') if key_event.modifiers == KeyModifiers::CONTROL => {
+                *selected = selected.saturating_sub(1);
+            }
+            KeyCode::Down if *selected + 1 < suggestions.len() => *selected += 1,
+            KeyCode::Char('n')
+                if key_event.modifiers == KeyModifiers::CONTROL
+                    && *selected + 1 < suggestions.len() =>
+            {
+                *selected += 1;
+            }
+            // Deleting past the start leaves command mode, as in vim
+            KeyCode::Backspace if input.is_empty() => self.close_modal(),
+            KeyCode::Backspace => {
+                input.pop();
+                *selected = 0;
+            }
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.push(c);
+                *selected = 0;
+            }
+            _ => {}
+        }
+        None
+    }
 
-        // Get file metadata for total size
-        if let Ok(meta) = object_store.head(&object_path).await
-            && let AsyncOp::Downloading(progress) = &mut self.async_op
-        {
-            progress.total_bytes = Some(meta.size);
+    /// Run the command typed as `input`, or the highlighted suggestion when no command has
+    /// that exact name. Returns the key the command stands for, if it stands for one.
+    async fn run_command(&mut self, input: &str, selected: usize) -> Option<KeyEvent> {
+        let selecting = self.is_selecting();
+        let input = input.trim();
+        let (word, args) = input.split_once(' ').unwrap_or((input, ""));
+        let args = args.trim();
+        if word.is_empty() {
+            return None;
+        }
+        let Some(command) = palette::COMMANDS
+            .iter()
+            .find(|command| command.name == word)
+            .or_else(|| palette::matching(input, selecting).get(selected).copied())
+        else {
+            self.error_message = Some(format!("Unknown command `:{word}` (`?` lists the keys)"));
+            return None;
+        };
+        if !command.runs_in(selecting) {
+            self.error_message = Some(if selecting {
+                format!("`:{}` works inside a container", command.name)
+            } else {
+                format!("`:{}` works on the container list", command.name)
+            });
+            return None;
         }
 
-        // Create destination file path
-        let file_destination = destination.join(file_name);
+        match command.action {
+            Action::Key(..) => return command.key_event(),
+            Action::Sort => {
+                let words: Vec<&str> = args.split_whitespace().collect();
+                match palette::sort_order(&words) {
+                    Ok(order) if selecting => {
+                        if order.reversed
+                            || !matches!(
+                                order.criteria,
+                                SortCriteria::Name | SortCriteria::DateModified
+                            )
+                        {
+                            self.error_message =
+                                Some("Containers sort by name or modified".to_string());
+                        } else {
+                            self.apply_container_sort(order.criteria);
+                        }
+                    }
+                    Ok(order) => self.choose_sort(order).await,
+                    Err(e) => self.error_message = Some(e.to_string()),
+                }
+            }
+            Action::Goto => {
+                if let Err(e) = self.goto(args).await {
+                    self.error_message = Some(format!("Go to failed: {}", describe_error(&e)));
+                }
+            }
+            Action::Tier => match palette::access_tier(args.split_whitespace().next()) {
+                Ok(tier) => self.set_access_tier(tier).await,
+                Err(e) => self.error_message = Some(e.to_string()),
+            },
+            Action::Export => self.export_listing(args).await,
+            Action::Pipe => self.pipe_selected(args),
+            Action::Diff => self.compare_with_local(args).await,
+        }
+        None
+    }
 
-        // Ensure parent directory exists
-        if let Some(parent) = file_destination.parent() {
-            fs::create_dir_all(parent)?;
+    /// Open the folder `path` of the open container, or from the container list the container
+    /// and folder in `container/path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if opening the container or listing the folder fails.
+    pub async fn goto(&mut self, path: &str) -> color_eyre::Result<()> {
+        let mut path = path.trim_start_matches('/');
+        if self.is_selecting() {
+            let (container, rest) = path.split_once('/').unwrap_or((path, ""));
+            let Some(index) = self.containers.iter().position(|c| c.name == container) else {
+                self.error_message = Some(format!("No container named `{container}`"));
+                return Ok(());
+            };
+            self.selected_container_index = index;
+            self.select_container().await?;
+            path = rest;
         }
+        let prefix = if path.is_empty() || path.ends_with('/') {
+            path.to_string()
+        } else {
+            format!("{path}/")
+        };
+        self.open_prefix(prefix).await
+    }
 
-        // Download the file
-        match object_store.get(&object_path).await {
-            Ok(get_result) => {
-                let bytes = get_result.bytes().await?;
-                fs::write(&file_destination, &bytes)?;
+    /// Set the access tier of the marked blobs, or else the selected one. Folders are left
+    /// alone.
+    async fn set_access_tier(&mut self, tier: &str) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let container = state.container_name.clone();
+        let paths: Vec<String> = if state.marked.is_empty() {
+            self.selected_file_item()
+                .filter(|item| item.kind == EntryKind::File && !item.deleted)
+                .map(|item| Self::join_blob_path(&state.current_path, &item.actual_name))
+                .into_iter()
+                .collect()
+        } else {
+            state
+                .marked
+                .keys()
+                .filter(|path| !path.ends_with('/'))
+                .cloned()
+                .collect()
+        };
+        if paths.is_empty() {
+            self.error_message = Some("Select or mark the blobs to set the tier of".to_string());
+            return;
+        }
+        let client = match self.rest_client() {
+            Ok(client) => client,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to set tier: {e}"));
+                return;
+            }
+        };
 
-                if let AsyncOp::Downloading(progress) = &mut self.async_op {
-                    progress.bytes_downloaded = bytes.len() as u64;
-                    progress.files_completed = 1;
+        for path in &paths {
+            let result = client.set_blob_tier(&container, path, tier).await;
+            self.audit(
+                AuditAction::Tier,
+                format!("{container}/{path} → {tier}"),
+                Outcome::of(&result),
+            );
+            if let Err(e) = result {
+                self.error_message = Some(format!("Failed to set the tier of {path}: {e}"));
+                return;
+            }
+        }
+        self.success_message = Some(match paths.as_slice() {
+            [path] => format!("Set {path} to {tier}"),
+            _ => format!("Set {} blobs to {tier}", paths.len()),
+        });
+    }
+
+    /// Write the listing on screen, or with `all` every blob under the open folder, to the
+    /// file `args` ends with, as the format given before it or else the one its extension
+    /// suggests.
+    async fn export_listing(&mut self, args: &str) {
+        let request = match ExportRequest::parse(args) {
+            Ok(request) => request,
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        };
+        let (entries, missing) = match self.export_entries(request.all).await {
+            Ok(exported) => exported,
+            Err(e) => {
+                self.error_message = Some(format!("Export failed: {}", describe_error(&e)));
+                return;
+            }
+        };
+
+        let path = blobrs::config::expand_tilde(Path::new(&request.path));
+        let written = std::fs::File::create(&path).and_then(|file| {
+            let mut out = std::io::BufWriter::new(file);
+            export::write_listing(&mut out, &entries, request.format)?;
+            out.flush()
+        });
+        match written {
+            Ok(()) => {
+                let without = missing
+                    .map(|e| format!(" without ETags or tiers ({e})"))
+                    .unwrap_or_default();
+                self.success_message = Some(format!(
+                    "Exported {} entries to {}{without}",
+                    entries.len(),
+                    path.display()
+                ));
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to write {}: {e}", path.display()));
+            }
+        }
+    }
+
+    /// What `:export` writes: the containers, the entries on screen in their order, or with
+    /// `all` every blob under the open folder. The entries on screen get their ETags and tiers
+    /// from listing the folder again; if that fails they are written without, with the reason.
+    async fn export_entries(
+        &self,
+        all: bool,
+    ) -> color_eyre::Result<(Vec<ListingEntry>, Option<String>)> {
+        let Some(state) = self.browsing() else {
+            let containers = self
+                .containers
+                .iter()
+                .map(|container| {
+                    ListingEntry::container(container.name.clone(), container.last_modified)
+                })
+                .collect();
+            return Ok((containers, None));
+        };
+        if all {
+            let listing = self
+                .rest_client()?
+                .list_blobs(&state.container_name, &state.current_path, None)
+                .await?;
+            return Ok((export::blob_entries(listing), None));
+        }
+
+        let mut entries: Vec<ListingEntry> = state
+            .file_items
+            .iter()
+            .map(|item| {
+                let name = Self::join_blob_path(&state.current_path, &item.actual_name);
+                let folder = item.kind == EntryKind::Folder;
+                ListingEntry {
+                    name: if folder { format!("{name}/") } else { name },
+                    kind: if folder {
+                        export::EntryKind::Folder
+                    } else {
+                        export::EntryKind::Blob
+                    },
+                    size: item.size,
+                    last_modified: item.last_modified,
+                    etag: None,
+                    tier: None,
                 }
+            })
+            .collect();
+        // Live properties would not match the blobs of a past point in time
+        if state.as_of.is_some() {
+            return Ok((entries, None));
+        }
+        let listing = match self.rest_client() {
+            Ok(client) => {
+                client
+                    .list_blobs(
+                        &state.container_name,
+                        &state.current_path,
+                        self.listing_delimiter(),
+                    )
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match listing {
+            Ok(listing) => {
+                export::add_properties(&mut entries, listing);
+                Ok((entries, None))
+            }
+            Err(e) => Ok((entries, Some(e.to_string()))),
+        }
+    }
+
+    /// The selected blob's full path, or an error naming what to select instead.
+    fn selected_blob_path(&self) -> Result<String, BlobrsError> {
+        let select = || BlobrsError::Invalid("Select a blob to compare".to_string());
+        let state = self.browsing().ok_or_else(select)?;
+        self.selected_file_item()
+            .filter(|item| item.kind == EntryKind::File && !item.deleted)
+            .map(|item| Self::join_blob_path(&state.current_path, &item.actual_name))
+            .ok_or_else(select)
+    }
+
+    /// Compare the one marked blob with the selected one, or with nothing marked, ask for a
+    /// local file to compare the selected blob with.
+    async fn compare_with_marked(&mut self) {
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let marked: Vec<&String> = state
+            .marked
+            .iter()
+            .filter(|(_, item)| item.kind == EntryKind::File && !item.deleted)
+            .map(|(path, _)| path)
+            .collect();
+        let marked = match (marked.as_slice(), state.marked.len()) {
+            (_, 0) => {
+                self.open_command_mode_with("diff ");
+                return;
             }
+            ([marked], 1) => (*marked).clone(),
+            _ => {
+                self.error_message =
+                    Some("Mark one blob to compare with the selected one".to_string());
+                return;
+            }
+        };
+        let selected = match self.selected_blob_path() {
+            Ok(selected) if selected == marked => {
+                self.error_message =
+                    Some("Select another blob to compare with the marked one".to_string());
+                return;
+            }
+            Ok(selected) => selected,
             Err(e) => {
-                if let AsyncOp::Downloading(progress) = &mut self.async_op {
-                    progress.error_message = Some(format!("Failed to download {file_name}: {e}"));
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        };
+        let container = state.container_name.clone();
+        let store = state.object_store.clone();
+        let sides = futures::try_join!(
+            CompareSide::blob(store.as_ref(), &container, &marked),
+            CompareSide::blob(store.as_ref(), &container, &selected)
+        );
+        match sides {
+            Ok((left, right)) => self.show_comparison(&left, &right),
+            Err(e) => {
+                self.error_message = Some(format!("Compare failed: {}", BlobrsError::from(e)));
+            }
+        }
+    }
+
+    /// Compare the local file at `path` with the selected blob.
+    async fn compare_with_local(&mut self, path: &str) {
+        if path.is_empty() {
+            self.error_message = Some("Compare with a local file: `:diff <path>`".to_string());
+            return;
+        }
+        let selected = match self.selected_blob_path() {
+            Ok(selected) => selected,
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        };
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let container = state.container_name.clone();
+        let store = state.object_store.clone();
+        let path = blobrs::config::expand_tilde(Path::new(path));
+        let left = match CompareSide::local(&path).await {
+            Ok(left) => left,
+            Err(e) => {
+                self.error_message = Some(format!("Could not read {}: {e}", path.display()));
+                return;
+            }
+        };
+        match CompareSide::blob(store.as_ref(), &container, &selected).await {
+            Ok(right) => self.show_comparison(&left, &right),
+            Err(e) => {
+                self.error_message = Some(format!("Compare failed: {}", BlobrsError::from(e)));
+            }
+        }
+    }
+
+    fn show_comparison(&mut self, left: &CompareSide, right: &CompareSide) {
+        let comparison = Comparison::new(left, right);
+        self.success_message = Some(comparison.summary());
+        self.modal = Modal::Diff {
+            title: format!("{} ⇄ {}", left.name, right.name),
+            comparison,
+            scroll: 0,
+        };
+    }
+
+    /// Handle a key press in the comparison popup: scroll with the list motions, close with
+    /// `q` or Esc.
+    pub fn handle_diff_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::Diff {
+            comparison, scroll, ..
+        } = &mut self.modal
+        else {
+            return;
+        };
+        if matches!(key_event.code, KeyCode::Esc | KeyCode::Char('q')) {
+            self.pending_motion = PendingMotion::default();
+            self.close_modal();
+            return;
+        }
+        if let MotionKey::Move(motion, count) = self.pending_motion.handle(key_event) {
+            let page = usize::from(self.list_layout.borrow().rows.height).max(1);
+            let last = comparison.len().saturating_sub(page) + 1;
+            *scroll = motion.target(count, *scroll, last, page);
+        }
+    }
+
+    /// Handle a key press in the help overlay: scroll with the list motions, close with `?`,
+    /// `q` or Esc.
+    pub fn handle_help_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::Help { scroll } = &mut self.modal else {
+            return;
+        };
+        if matches!(
+            key_event.code,
+            KeyCode::Esc | KeyCode::Char('?' | 'q' | 'h') | KeyCode::Left
+        ) {
+            self.pending_motion = PendingMotion::default();
+            self.close_modal();
+            return;
+        }
+        if let MotionKey::Move(motion, count) = self.pending_motion.handle(key_event) {
+            // Scroll by the list's page, which is about the size of the overlay
+            let page = usize::from(self.list_layout.borrow().rows.height).max(1);
+            let last = help::line_count().saturating_sub(page) + 1;
+            *scroll = motion.target(count, *scroll, last, page);
+        }
+    }
+
+    /// Handle a key press in the jobs panel.
+    ///
+    /// With a job collapsed: `a` cancels the job, `r` requeues its unfinished files, and `x`
+    /// removes it once finished. With a job expanded: `s` skips, `a` aborts, and `r` requeues the
+    /// selected file.
+    pub fn handle_jobs_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::Jobs {
+            job_index,
+            file_index,
+        } = &mut self.modal
+        else {
+            return;
+        };
+        let Some(job) = self.jobs.get(*job_index) else {
+            self.close_modal();
+            return;
+        };
+        let file_count = job.files().len();
+
+        match (key_event.code, file_index.as_mut()) {
+            (KeyCode::Esc | KeyCode::Left | KeyCode::Char('h'), Some(_)) => {
+                *file_index = None;
+            }
+            (KeyCode::Esc | KeyCode::Char('J'), None) => {
+                self.close_modal();
+            }
+            (KeyCode::Up | KeyCode::Char('k'), Some(file)) => {
+                *file = file.saturating_sub(1);
+            }
+            (KeyCode::Down | KeyCode::Char('j'), Some(file)) if *file + 1 < file_count => {
+                *file += 1;
+            }
+            (KeyCode::Char('s'), Some(file)) => job.skip(*file),
+            (KeyCode::Char('a'), Some(file)) => job.abort(*file),
+            (KeyCode::Char('r'), Some(file)) => job.requeue(*file),
+            (KeyCode::Up | KeyCode::Char('k'), None) => {
+                *job_index = job_index.saturating_sub(1);
+            }
+            (KeyCode::Down | KeyCode::Char('j'), None) if *job_index + 1 < self.jobs.len() => {
+                *job_index += 1;
+            }
+            (KeyCode::Enter | KeyCode::Right | KeyCode::Char('l'), None) if file_count > 0 => {
+                *file_index = Some(0);
+            }
+            (KeyCode::Char('a'), None) => job.cancel(),
+            (KeyCode::Char('r'), None) => job.requeue_all(),
+            (KeyCode::Char('x'), None) if job.summary().is_finished() => {
+                self.jobs.remove(*job_index);
+                if self.jobs.is_empty() {
+                    self.close_modal();
+                } else {
+                    *job_index = (*job_index).min(self.jobs.len() - 1);
                 }
-                return Err(color_eyre::eyre::eyre!("Download failed: {}", e));
             }
+            _ => {}
         }
+    }
 
-        Ok(())
+    /// Open the search prompt, looking through `scope` until `Tab` changes it.
+    pub fn open_global_search(&mut self, scope: SearchScope) {
+        self.modal = Modal::GlobalSearch {
+            input: String::new(),
+            results: None,
+            selected: 0,
+            scope,
+            running: None,
+        };
     }
 
-    /// Download all files in a folder.
-    async fn download_folder(
+    /// Handle a key press in the cross-account search popup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if opening a selected result fails.
+    pub async fn handle_global_search_key_event(
         &mut self,
-        folder_name: &str,
-        destination: &Path,
+        key_event: KeyEvent,
     ) -> color_eyre::Result<()> {
-        let browsing = self
-            .browsing()
-            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
-        let object_store = browsing.object_store.clone();
+        let Modal::GlobalSearch {
+            input,
+            results,
+            selected,
+            scope,
+            running,
+        } = &mut self.modal
+        else {
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.close_modal();
+            }
+            KeyCode::Tab => {
+                *scope = scope.next();
+                *results = None;
+                *running = None;
+            }
+            KeyCode::Up => {
+                *selected = selected.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                let hits = results.as_ref().map_or(0, |r| r.hits.len());
+                if *selected + 1 < hits {
+                    *selected += 1;
+                }
+            }
+            KeyCode::Enter if results.is_some() => {
+                let hit = results
+                    .as_ref()
+                    .and_then(|r| r.hits.get(*selected))
+                    .cloned();
+                if let Some(hit) = hit {
+                    self.open_search_hit(&hit).await?;
+                }
+            }
+            KeyCode::Enter if !input.trim().is_empty() => {
+                let query = input.trim().to_string();
+                let mut search = None;
+                let found = match *scope {
+                    SearchScope::Accounts => self.run_global_search(&query).await,
+                    SearchScope::Folder => match self.start_folder_search(&query) {
+                        Ok(started) => {
+                            search = Some(started);
+                            SearchResults::default()
+                        }
+                        Err(e) => {
+                            self.error_message = Some(e.to_string());
+                            return Ok(());
+                        }
+                    },
+                    SearchScope::Tags => match self.run_tag_search(&query).await {
+                        Ok(found) => found,
+                        Err(e) => {
+                            self.error_message = Some(e.to_string());
+                            return Ok(());
+                        }
+                    },
+                };
+                if let Modal::GlobalSearch {
+                    results,
+                    selected,
+                    running,
+                    ..
+                } = &mut self.modal
+                {
+                    *results = Some(found);
+                    *selected = 0;
+                    *running = search;
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+                *results = None;
+                *running = None;
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+                *results = None;
+                *running = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The current account plus every configured profile for a different account.
+    fn search_sources(&self) -> (Vec<SearchSource>, Vec<String>) {
+        let mut sources = vec![SearchSource {
+            label: self.storage_account.clone(),
+            account: self.storage_account.clone(),
+            access_key: self.access_key.clone(),
+            retry: self.config.retry,
+            timeouts: self.config.timeouts,
+        }];
+        let mut errors = Vec::new();
+
+        for (name, profile) in &self.config.profiles {
+            if sources.iter().any(|s| s.account == profile.account) {
+                continue;
+            }
+            match profile.access_key() {
+                Ok(access_key) => sources.push(SearchSource {
+                    label: name.clone(),
+                    account: profile.account.clone(),
+                    access_key,
+                    retry: self.config.retry,
+                    timeouts: self.config.timeouts,
+                }),
+                Err(e) => errors.push(format!("{name}: {e}")),
+            }
+        }
+
+        (sources, errors)
+    }
+
+    /// Search blob names in every account concurrently.
+    async fn run_global_search(&self, query: &str) -> SearchResults {
+        let (sources, errors) = self.search_sources();
+        let mut results = search_accounts(&sources, query).await;
+        results.errors.extend(errors);
+        results
+    }
+
+    /// The container and folder a folder search looks through: the folder being browsed, or
+    /// the highlighted container while selecting one.
+    pub(crate) fn folder_search_target(&self) -> Option<(String, String)> {
+        match &self.session {
+            Session::Browsing(state) => {
+                Some((state.container_name.clone(), state.current_path.clone()))
+            }
+            Session::Selecting => self
+                .containers
+                .get(self.selected_container_index)
+                .map(|container| (container.name.clone(), String::new())),
+        }
+    }
+
+    /// Start searching blob paths under the current folder at any depth.
+    fn start_folder_search(&self, query: &str) -> Result<FolderSearch, BlobrsError> {
+        let (container, prefix) = self
+            .folder_search_target()
+            .ok_or_else(|| BlobrsError::Invalid("Select a container to search it".to_string()))?;
+        let store: Arc<dyn ObjectStore> = match self.browsing() {
+            Some(state) => state.object_store.clone(),
+            None => self.container_store(&container)?,
+        };
+        Ok(FolderSearch::start(
+            store,
+            &self.storage_account,
+            &container,
+            &prefix,
+            query,
+        ))
+    }
+
+    /// Add the matches a folder search found since the last tick to its results.
+    fn take_folder_search_hits(&mut self) {
+        let Modal::GlobalSearch {
+            results: Some(results),
+            running,
+            ..
+        } = &mut self.modal
+        else {
+            return;
+        };
+        let Some(search) = running.clone() else {
+            return;
+        };
+        // Checked first so matches arriving in between are not lost
+        let finished = search.is_finished();
+        results.hits.extend(search.take_hits());
+        if finished {
+            results.errors.extend(search.error());
+            results.truncated = search.is_truncated();
+            *running = None;
+        }
+    }
+
+    /// Find blobs by index tags in the current container, or the highlighted one while
+    /// selecting a container.
+    async fn run_tag_search(&self, query: &str) -> Result<SearchResults, BlobrsError> {
+        let container = match &self.session {
+            Session::Browsing(state) => state.container_name.clone(),
+            Session::Selecting => self
+                .containers
+                .get(self.selected_container_index)
+                .map(|container| container.name.clone())
+                .ok_or_else(|| {
+                    BlobrsError::Invalid("Select a container to search its tags".to_string())
+                })?,
+        };
+        let expression = tag_filter_expression(query)?;
+        let source = SearchSource {
+            label: self.storage_account.clone(),
+            account: self.storage_account.clone(),
+            access_key: self.access_key.clone(),
+            retry: self.config.retry,
+            timeouts: self.config.timeouts,
+        };
+        Ok(search_tags(&source, &container, &expression).await)
+    }
+
+    /// Browse to a search result's folder and select it. Only results in the current account
+    /// can be opened.
+    async fn open_search_hit(&mut self, hit: &SearchHit) -> color_eyre::Result<()> {
+        if hit.account != self.storage_account {
+            self.error_message = Some(format!(
+                "{} is in account {}; start blobrs with that account to open it",
+                hit.path, hit.account
+            ));
+            return Ok(());
+        }
+
+        let azure_client = self.container_store(&hit.container)?;
+        let (folder, name) = hit
+            .path
+            .rsplit_once('/')
+            .map_or(("", hit.path.as_str()), |(folder, name)| (folder, name));
+
+        self.close_modal();
+        self.search = Search::Inactive;
+        self.session = Session::Browsing(BrowsingState {
+            object_store: azure_client.clone(),
+            container_name: hit.container.clone(),
+            current_path: if folder.is_empty() {
+                String::new()
+            } else {
+                format!("{folder}/")
+            },
+            files: Vec::new(),
+            file_items: Vec::new(),
+            selected_index: 0,
+            as_of: None,
+            paging: Paging::new(azure_client),
+            marked: BTreeMap::new(),
+        });
+        self.refresh_files().await?;
+
+        if let Some(state) = self.browsing_mut()
+            && let Some(index) = state.file_items.iter().position(|i| i.actual_name == name)
+        {
+            state.selected_index = index;
+        }
+        Ok(())
+    }
+
+    /// Key of the container or folder a note applies to.
+    ///
+    /// While selecting this is the highlighted container; while browsing it is the highlighted
+    /// folder, or the current folder when a file is highlighted.
+    fn note_key(&self) -> Option<String> {
+        let account = &self.storage_account;
+        match &self.session {
+            Session::Selecting => {
+                let container = self.containers.get(self.selected_container_index)?;
+                Some(format!("{account}/{}", container.name))
+            }
+            Session::Browsing(state) => {
+                let path = match self.selected_file_item() {
+                    Some(item) if item.kind == EntryKind::Folder => {
+                        Self::join_folder_path(&state.current_path, &item.actual_name)
+                    }
+                    _ => state.current_path.clone(),
+                };
+                let path = path.trim_end_matches('/');
+                if path.is_empty() {
+                    Some(format!("{account}/{}", state.container_name))
+                } else {
+                    Some(format!("{account}/{}/{path}", state.container_name))
+                }
+            }
+        }
+    }
+
+    /// The note attached to whatever `note_key` points at.
+    #[must_use]
+    pub fn current_note(&self) -> Option<(String, &str)> {
+        let key = self.note_key()?;
+        let note = self.state.notes.get(&key)?;
+        Some((key, note.as_str()))
+    }
+
+    /// Open the lease actions for the blob shown in the info popup.
+    pub fn open_lease_menu(&mut self) {
+        let Modal::BlobInfo {
+            info: BlobInfo::File { name, .. },
+        } = &self.modal
+        else {
+            return;
+        };
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let blob_path = Self::join_blob_path(&state.current_path, name);
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let menu = Menu::new(vec![
+            MenuItem::new(
+                'a',
+                "Acquire (infinite)",
+                LeaseAction::Acquire { seconds: None },
+            ),
+            MenuItem::new(
+                't',
+                "Acquire for 60 seconds",
+                LeaseAction::Acquire { seconds: Some(60) },
+            ),
+            MenuItem::new('b', "Break now", LeaseAction::Break),
+            MenuItem::new('r', "Release", LeaseAction::Release),
+        ]);
+        self.modal = Modal::LeaseMenu { blob_path, menu };
+    }
+
+    /// Handle key events in the lease menu. After an action the info popup is shown again with
+    /// the new lease state.
+    pub async fn handle_lease_menu_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::LeaseMenu { blob_path, menu } = &mut self.modal else {
+            return;
+        };
+        match menu.handle_key(key_event) {
+            MenuOutcome::Pending => {}
+            MenuOutcome::Cancelled => self.close_modal(),
+            MenuOutcome::Chosen(action) => {
+                let blob_path = blob_path.clone();
+                self.close_modal();
+                let result = self.run_lease_action(&blob_path, action).await;
+                self.audit(
+                    AuditAction::Lease,
+                    self.audit_target(&blob_path),
+                    Outcome::of(&result),
+                );
+                match result {
+                    Ok(message) => self.success_message = Some(message),
+                    Err(e) => {
+                        self.error_message = Some(format!("Lease on {blob_path} failed: {e}"));
+                        return;
+                    }
+                }
+                if let Err(e) = self.show_blob_info().await {
+                    self.error_message =
+                        Some(format!("Failed to get blob info: {}", describe_error(&e)));
+                }
+            }
+        }
+    }
+
+    /// Apply a lease action and describe the outcome.
+    async fn run_lease_action(
+        &mut self,
+        blob_path: &str,
+        action: LeaseAction,
+    ) -> color_eyre::Result<String> {
+        let container = self
+            .browsing()
+            .map(|state| state.container_name.clone())
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let client = self.rest_client()?;
+        let key = format!("{container}/{blob_path}");
+
+        match action {
+            LeaseAction::Acquire { seconds } => {
+                let lease_id = client.acquire_lease(&container, blob_path, seconds).await?;
+                self.leases.insert(key, lease_id.clone());
+                Ok(format!("Acquired lease {lease_id} on {blob_path}"))
+            }
+            LeaseAction::Break => {
+                client.break_lease(&container, blob_path).await?;
+                self.leases.remove(&key);
+                Ok(format!("Broke the lease on {blob_path}"))
+            }
+            LeaseAction::Release => {
+                let Some(lease_id) = self.leases.get(&key).cloned() else {
+                    return Err(color_eyre::eyre::eyre!(
+                        "it was not acquired in this session; break it instead"
+                    ));
+                };
+                client
+                    .release_lease(&container, blob_path, &lease_id)
+                    .await?;
+                self.leases.remove(&key);
+                Ok(format!("Released the lease on {blob_path}"))
+            }
+        }
+    }
+
+    /// Open the snapshots view for the blob shown in the info popup.
+    pub async fn open_snapshots(&mut self) {
+        let Modal::BlobInfo {
+            info: BlobInfo::File { name, .. },
+        } = &self.modal
+        else {
+            return;
+        };
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let blob_path = Self::join_blob_path(&state.current_path, name);
+
+        match self.list_snapshots(&blob_path).await {
+            Ok(snapshots) => {
+                self.modal = Modal::Snapshots {
+                    blob_path,
+                    snapshots,
+                    selected: 0,
+                    confirm_restore: false,
+                };
+            }
+            Err(e) => self.error_message = Some(format!("Failed to list snapshots: {e}")),
+        }
+    }
+
+    /// Snapshots of a blob in the current container, newest first.
+    async fn list_snapshots(&self, blob_path: &str) -> color_eyre::Result<Vec<BlobSnapshot>> {
+        let container = self
+            .browsing()
+            .map(|state| state.container_name.clone())
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        Ok(self
+            .rest_client()?
+            .list_blob_snapshots(&container, blob_path)
+            .await?)
+    }
+
+    /// Handle key events in the snapshots view.
+    pub async fn handle_snapshots_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::Snapshots {
+            snapshots,
+            selected,
+            confirm_restore,
+            ..
+        } = &mut self.modal
+        else {
+            return;
+        };
+
+        if *confirm_restore {
+            *confirm_restore = false;
+            if matches!(key_event.code, KeyCode::Char('y' | 'Y')) {
+                self.restore_selected_snapshot().await;
+            }
+            return;
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Left | KeyCode::Char('h') => self.close_modal(),
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if *selected + 1 < snapshots.len() => {
+                *selected += 1;
+            }
+            KeyCode::Char('c') => self.create_snapshot().await,
+            KeyCode::Char('d') | KeyCode::Enter => {
+                let Some(snapshot) = snapshots.get(*selected).map(|s| s.snapshot.clone()) else {
+                    return;
+                };
+                self.show_download_picker();
+                if let Modal::DownloadPicker {
+                    snapshot: target, ..
+                } = &mut self.modal
+                {
+                    *target = Some(snapshot);
+                }
+            }
+            KeyCode::Char('r') if !snapshots.is_empty() => {
+                if !self.refuse_if_read_only()
+                    && let Modal::Snapshots {
+                        confirm_restore, ..
+                    } = &mut self.modal
+                {
+                    *confirm_restore = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Take a new snapshot of the blob in the snapshots view and list it.
+    async fn create_snapshot(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let Modal::Snapshots { blob_path, .. } = &self.modal else {
+            return;
+        };
+        let blob_path = blob_path.clone();
+        let Some(container) = self.browsing().map(|state| state.container_name.clone()) else {
+            return;
+        };
+
+        let created = match self.rest_client() {
+            Ok(client) => client.create_snapshot(&container, &blob_path).await,
+            Err(e) => Err(e),
+        };
+        self.audit(
+            AuditAction::Snapshot,
+            format!("{container}/{blob_path}"),
+            Outcome::of(&created),
+        );
+        let created = match created {
+            Ok(created) => created,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to create snapshot: {e}"));
+                return;
+            }
+        };
+        match self.list_snapshots(&blob_path).await {
+            Ok(listed) => {
+                if let Modal::Snapshots {
+                    snapshots,
+                    selected,
+                    ..
+                } = &mut self.modal
+                {
+                    *selected = listed
+                        .iter()
+                        .position(|s| s.snapshot == created)
+                        .unwrap_or(0);
+                    *snapshots = listed;
+                }
+                self.success_message = Some(format!("Created snapshot {created}"));
+            }
+            Err(e) => self.error_message = Some(format!("Failed to list snapshots: {e}")),
+        }
+    }
+
+    /// Copy the selected snapshot over its base blob.
+    async fn restore_selected_snapshot(&mut self) {
+        let Modal::Snapshots {
+            blob_path,
+            snapshots,
+            selected,
+            ..
+        } = &self.modal
+        else {
+            return;
+        };
+        let Some(snapshot) = snapshots.get(*selected).map(|s| s.snapshot.clone()) else {
+            return;
+        };
+        let blob_path = blob_path.clone();
+        let Some(container) = self.browsing().map(|state| state.container_name.clone()) else {
+            return;
+        };
+        if self.refuse_if_immutable(&blob_path).await {
+            return;
+        }
+
+        let restored = match self.rest_client() {
+            Ok(client) => {
+                client
+                    .restore_snapshot(&container, &blob_path, &snapshot)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        self.audit(
+            AuditAction::Restore,
+            format!("{container}/{blob_path} from snapshot {snapshot}"),
+            Outcome::of(&restored),
+        );
+        match restored {
+            Ok(status) => {
+                self.close_modal();
+                self.success_message = Some(if status == "pending" {
+                    format!("Restoring {blob_path} from snapshot {snapshot} in the background")
+                } else {
+                    format!("Restored {blob_path} from snapshot {snapshot}")
+                });
+                if let Err(e) = self.refresh_files().await {
+                    self.error_message = Some(format!("Refresh failed: {}", describe_error(&e)));
+                }
+            }
+            Err(e) => self.error_message = Some(format!("Failed to restore snapshot: {e}")),
+        }
+    }
+
+    /// Open the tag editor for the blob shown in the info popup.
+    pub fn open_tag_editor(&mut self) {
+        let Modal::BlobInfo {
+            info: BlobInfo::File { name, tags, .. },
+        } = &self.modal
+        else {
+            return;
+        };
+        let Some(tags) = tags.clone() else {
+            self.error_message = Some("Tags are not available for this blob".to_string());
+            return;
+        };
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let blob_path = Self::join_blob_path(&state.current_path, name);
+        if self.refuse_if_read_only() {
+            return;
+        }
+        self.modal = Modal::EditTags {
+            blob_path,
+            tags,
+            selected: 0,
+            input: None,
+        };
+    }
+
+    /// Open the list of file types in the folder, counting every file even while searching.
+    pub fn open_extension_picker(&mut self) {
+        let items = match (&self.search, self.browsing()) {
+            (Search::Files { all_file_items, .. }, _) => all_file_items,
+            (_, Some(state)) => &state.file_items,
+            (_, None) => return,
+        };
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for item in items.iter().filter(|item| item.kind == EntryKind::File) {
+            if let Some(extension) = extension(&item.actual_name) {
+                *counts.entry(extension).or_default() += 1;
+            }
+        }
+        if counts.is_empty() {
+            self.error_message = Some("No files with an extension here".to_string());
+            return;
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        // Stable, so equally common types stay in alphabetical order
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let shown = self
+            .file_search_query()
+            .map(extensions_in)
+            .unwrap_or_default();
+        self.modal = Modal::Extensions {
+            counts,
+            shown,
+            selected: 0,
+        };
+    }
+
+    /// Handle key events in the file type picker.
+    pub fn handle_extensions_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::Extensions {
+            counts,
+            shown,
+            selected,
+        } = &mut self.modal
+        else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Esc => self.close_modal(),
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if *selected + 1 < counts.len() => *selected += 1,
+            KeyCode::Char(' ') => {
+                if let Some((extension, _)) = counts.get(*selected)
+                    && !shown.remove(extension)
+                {
+                    shown.insert(extension.clone());
+                }
+            }
+            KeyCode::Char('a') => shown.clear(),
+            KeyCode::Enter => {
+                let shown = std::mem::take(shown);
+                self.close_modal();
+                self.show_extensions(&shown);
+            }
+            _ => {}
+        }
+    }
+
+    /// Show only files with `extensions` by putting them in the search, or every file when
+    /// there are none.
+    fn show_extensions(&mut self, extensions: &BTreeSet<String>) {
+        if !self.is_searching_files() {
+            self.enter_search_mode();
+        }
+        let Search::Files { query, .. } = &mut self.search else {
+            return;
+        };
+        *query = with_extensions(query, extensions);
+        let current = query.clone();
+        if current.is_empty() {
+            self.exit_search_mode();
+        } else {
+            self.apply_file_search(&current);
+        }
+    }
+
+    /// Handle key events in the tag editor.
+    pub async fn handle_edit_tags_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::EditTags {
+            tags,
+            selected,
+            input,
+            ..
+        } = &mut self.modal
+        else {
+            return;
+        };
+
+        // Typing a new tag
+        if let Some(text) = input {
+            match key_event.code {
+                KeyCode::Esc => *input = None,
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Enter => {
+                    let Some((key, value)) = text.split_once('=') else {
+                        self.error_message = Some("Enter the tag as key=value".to_string());
+                        return;
+                    };
+                    let (key, value) = (key.trim().to_string(), value.trim().to_string());
+                    if let Err(e) = validate_tag(&key, &value) {
+                        self.error_message = Some(e.to_string());
+                        return;
+                    }
+                    if !tags.contains_key(&key) && tags.len() >= MAX_BLOB_TAGS {
+                        self.error_message =
+                            Some(format!("A blob can have at most {MAX_BLOB_TAGS} tags"));
+                        return;
+                    }
+                    tags.insert(key.clone(), value);
+                    *selected = tags.keys().position(|k| *k == key).unwrap_or(0);
+                    *input = None;
+                    self.error_message = None;
+                }
+                KeyCode::Char(c) => text.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => self.close_modal(),
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if *selected + 1 < tags.len() => *selected += 1,
+            KeyCode::Char('a') => *input = Some(String::new()),
+            KeyCode::Char('x') | KeyCode::Delete => {
+                if let Some(key) = tags.keys().nth(*selected).cloned() {
+                    tags.remove(&key);
+                    *selected = (*selected).min(tags.len().saturating_sub(1));
+                }
+            }
+            KeyCode::Enter => self.save_tags().await,
+            _ => {}
+        }
+    }
+
+    /// Write the edited tags back to the blob, replacing its previous tags.
+    async fn save_tags(&mut self) {
+        let Modal::EditTags {
+            blob_path, tags, ..
+        } = &self.modal
+        else {
+            return;
+        };
+        let (blob_path, tags) = (blob_path.clone(), tags.clone());
+        let Some(container) = self.browsing().map(|state| state.container_name.clone()) else {
+            return;
+        };
+
+        let result = match self.rest_client() {
+            Ok(client) => client
+                .set_blob_tags(&container, &blob_path, &tags)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        self.audit(
+            AuditAction::Tags,
+            format!("{container}/{blob_path}"),
+            Outcome::of(&result),
+        );
+        match result {
+            Ok(()) => {
+                self.close_modal();
+                self.success_message = Some(format!("Saved {} tag(s) on {blob_path}", tags.len()));
+            }
+            Err(e) => self.error_message = Some(format!("Failed to save tags: {e}")),
+        }
+    }
+
+    /// Open the note editor for the highlighted container or folder.
+    pub fn open_note_editor(&mut self) {
+        let Some(key) = self.note_key() else {
+            return;
+        };
+        let input = self.state.notes.get(&key).cloned().unwrap_or_default();
+        self.modal = Modal::EditNote { key, input };
+    }
+
+    /// Handle key events when editing a note.
+    pub fn handle_edit_note_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::EditNote { key, input } = &mut self.modal else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.close_modal();
+            }
+            KeyCode::Enter => {
+                let (key, input) = (key.clone(), input.clone());
+                self.close_modal();
+                self.state.set_note(&key, &input);
+                if let Err(e) = self.state.save() {
+                    self.error_message = Some(format!("Failed to save note: {e}"));
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the time-travel prompt, pre-filled with the active timestamp if any.
+    pub fn open_time_travel_dialog(&mut self) {
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let input = state
+            .as_of
+            .map(|ts| ts.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        self.modal = Modal::TimeTravel { input };
+    }
+
+    /// Handle key events when in the time-travel prompt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if refreshing the listing fails.
+    pub async fn handle_time_travel_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> color_eyre::Result<()> {
+        let Modal::TimeTravel { input } = &mut self.modal else {
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.close_modal();
+            }
+            KeyCode::Enter => {
+                let input = input.trim().to_string();
+                if input.is_empty() {
+                    self.close_modal();
+                    self.set_time_travel(None).await?;
+                } else if let Some(as_of) = parse_as_of_timestamp(&input) {
+                    self.close_modal();
+                    self.set_time_travel(Some(as_of)).await?;
+                } else {
+                    self.error_message = Some(format!(
+                        "Invalid timestamp \"{input}\" (use YYYY-MM-DD, YYYY-MM-DD HH:MM, or RFC 3339)"
+                    ));
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Switch the listing to the state of the container at `as_of`, or back to live with `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if refreshing the listing fails.
+    pub async fn set_time_travel(
+        &mut self,
+        as_of: Option<chrono::DateTime<Utc>>,
+    ) -> color_eyre::Result<()> {
+        let Some(state) = self.browsing_mut() else {
+            return Ok(());
+        };
+        state.as_of = as_of;
+        state.paging.reset();
+        state.marked.clear();
+
+        if self.is_searching_files() {
+            self.search = Search::Inactive;
+        }
+        self.refresh_files().await?;
+
+        if self.error_message.is_none() {
+            self.success_message = Some(match as_of {
+                Some(ts) => format!(
+                    "Viewing container as of {} (read-only)",
+                    ts.format("%Y-%m-%d %H:%M:%S UTC")
+                ),
+                None => "Returned to live view".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Filter files based on search query.
+    pub fn filter_files(&mut self) {
+        let query = match &self.search {
+            Search::Files { query, .. } => query.clone(),
+            _ => return,
+        };
+        self.apply_file_search(&query);
+    }
+
+    fn apply_file_search(&mut self, query: &str) {
+        let filter = Filter::lenient(self.ui.filter_mode, query);
+        let (
+            Search::Files {
+                all_files,
+                all_file_items,
+                ..
+            },
+            Session::Browsing(state),
+        ) = (&self.search, &mut self.session)
+        else {
+            return;
+        };
+
+        if query.is_empty() {
+            state.files.clone_from(all_files);
+            state.file_items.clone_from(all_file_items);
+        } else {
+            let (files, file_items) = all_files
+                .iter()
+                .zip(all_file_items)
+                .filter(|(_, item)| {
+                    filter.is_match(&item.actual_name, item.size, item.last_modified)
+                })
+                .map(|(file, item)| (file.clone(), item.clone()))
+                .unzip();
+            state.files = files;
+            state.file_items = file_items;
+        }
+        state.selected_index = Self::best_match(
+            state
+                .file_items
+                .iter()
+                .map(|item| item.actual_name.as_str()),
+            &filter,
+        );
+    }
+
+    /// Filter the shown entries down to those matching `query`, which extends the query they
+    /// were filtered by. Only the entries already shown need checking, so each key typed into
+    /// the search gets quicker instead of scanning the whole folder again.
+    fn narrow_file_search(&mut self, query: &str) {
+        let filter = Filter::lenient(self.ui.filter_mode, query);
+        let Some(state) = self.browsing_mut() else {
+            return;
+        };
+        let keep: Vec<bool> = state
+            .file_items
+            .iter()
+            .map(|item| filter.is_match(&item.actual_name, item.size, item.last_modified))
+            .collect();
+        let mut kept = keep.iter();
+        state
+            .file_items
+            .retain(|_| kept.next().copied().unwrap_or(false));
+        let mut kept = keep.iter();
+        state
+            .files
+            .retain(|_| kept.next().copied().unwrap_or(false));
+        state.selected_index = Self::best_match(
+            state
+                .file_items
+                .iter()
+                .map(|item| item.actual_name.as_str()),
+            &filter,
+        );
+    }
+
+    /// Position of the name `filter` matches best, the first of equals. Matches stay in the
+    /// list's own order, so sorting by size or date still works while searching, and the
+    /// selection starts on the best one instead.
+    fn best_match<'a>(names: impl Iterator<Item = &'a str>, filter: &Filter) -> usize {
+        names
+            .enumerate()
+            .filter_map(|(index, name)| Some((filter.find(name)?.score, index)))
+            .max_by_key(|&(score, index)| (score, std::cmp::Reverse(index)))
+            .map_or(0, |(_, index)| index)
+    }
+
+    fn apply_container_search(&mut self, query: &str) {
+        let all_containers = match &self.search {
+            Search::Containers { all_containers, .. } => all_containers.clone(),
+            _ => return,
+        };
+
+        let filter = Filter::lenient(self.ui.filter_mode, query);
+        self.containers = all_containers
+            .into_iter()
+            .filter(|container| filter.is_match(&container.name, None, container.last_modified))
+            .collect();
+        self.selected_container_index = Self::best_match(
+            self.containers
+                .iter()
+                .map(|container| container.name.as_str()),
+            &filter,
+        );
+    }
+
+    /// Load the list of containers from Azure Storage.
+    async fn load_containers(&mut self) -> color_eyre::Result<()> {
+        self.async_op = AsyncOp::LoadingContainers;
+        self.error_message = None;
+        self.success_message = None;
+
+        match self.list_containers().await {
+            Ok(containers) => {
+                // Always store the full list
+                self.all_containers.clone_from(&containers);
+
+                let search_query = match &self.search {
+                    Search::Containers { query, .. } => Some(query.clone()),
+                    _ => None,
+                };
+
+                if let Search::Containers { all_containers, .. } = &mut self.search {
+                    all_containers.clone_from(&containers);
+                }
+
+                if let Some(query) = search_query {
+                    if query.is_empty() {
+                        self.containers = containers;
+                        self.selected_container_index = 0;
+                    } else {
+                        self.apply_container_search(&query);
+                    }
+                } else {
+                    self.containers = containers;
+                    self.selected_container_index = 0;
+                }
+                if self.ui.show_container_usage {
+                    self.scan_container_usage();
+                }
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to list containers: {e}"));
+            }
+        }
+
+        self.async_op = AsyncOp::None;
+        Ok(())
+    }
+
+    /// List all containers in the storage account with pagination support.
+    async fn list_containers(&mut self) -> Result<Vec<ContainerInfo>, BlobrsError> {
+        let containers = self
+            .rest_client()?
+            .list_containers()
+            .await?
+            .into_iter()
+            .map(|container| ContainerInfo {
+                name: container.name,
+                last_modified: container.last_modified,
+                recent: false,
+            })
+            .collect();
+        let recent: Vec<&str> = self
+            .state
+            .recent_containers_of(&self.storage_account)
+            .collect();
+        Ok(order_containers(
+            containers,
+            &recent,
+            self.container_sort == SortCriteria::DateModified,
+        ))
+    }
+
+    /// Select a container and initialize the object store.
+    async fn select_container(&mut self) -> color_eyre::Result<()> {
+        if self.containers.is_empty() {
+            return Ok(());
+        }
+
+        let selected_container = &self.containers[self.selected_container_index];
+        self.state
+            .remember_container(&self.storage_account, &selected_container.name);
+        if let Err(e) = self.state.save() {
+            self.error_message = Some(format!("Failed to save recent containers: {e}"));
+        }
+
+        self.sort = self
+            .state
+            .sort_for(&self.storage_account, &selected_container.name);
+        let azure_client = self.container_store(&selected_container.name)?;
+
+        self.session = Session::Browsing(BrowsingState {
+            object_store: azure_client.clone(),
+            container_name: selected_container.name.clone(),
+            current_path: String::new(),
+            files: Vec::new(),
+            file_items: Vec::new(),
+            selected_index: 0,
+            as_of: None,
+            paging: Paging::new(azure_client),
+            marked: BTreeMap::new(),
+        });
+        self.search = Search::Inactive;
+
+        // Load initial file list
+        self.refresh_files().await?;
+        Ok(())
+    }
+
+    /// Show the properties of the selected container and the account's data protection
+    /// settings.
+    pub async fn open_container_info(&mut self) {
+        let Some(name) = self
+            .containers
+            .get(self.selected_container_index)
+            .map(|container| container.name.clone())
+        else {
+            return;
+        };
+        let client = match self.rest_client() {
+            Ok(client) => client,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to get container properties: {e}"));
+                return;
+            }
+        };
+
+        self.usage.scan_tiers(&name, "", client.clone());
+        match client.container_properties(&name).await {
+            Ok(mut properties) => {
+                properties.last_modified = properties.last_modified.map(format_service_time);
+                self.modal = Modal::ContainerInfo {
+                    properties,
+                    soft_delete: client.blob_soft_delete_enabled().await.ok(),
+                    versioning: client.container_versioning(&name).await.ok().flatten(),
+                };
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to get container properties: {e}"));
+            }
+        }
+    }
+
+    /// Show or hide blob counts and sizes on the container list, scanning the containers
+    /// that were not counted yet.
+    pub fn toggle_container_usage(&mut self) {
+        self.ui.show_container_usage = !self.ui.show_container_usage;
+        if self.ui.show_container_usage {
+            self.scan_container_usage();
+        }
+    }
+
+    /// Start counting every listed container that was not counted yet.
+    fn scan_container_usage(&mut self) {
+        let names: Vec<String> = self.containers.iter().map(|c| c.name.clone()).collect();
+        for name in names {
+            match self.container_store(&name) {
+                Ok(store) => self.usage.scan(&name, store),
+                Err(e) => {
+                    self.error_message = Some(format!("Failed to scan {name}: {e}"));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Count the selected container again, e.g. after it changed.
+    pub fn rescan_selected_container(&mut self) {
+        let Some(name) = self
+            .containers
+            .get(self.selected_container_index)
+            .map(|container| container.name.clone())
+        else {
+            return;
+        };
+        match self.container_store(&name) {
+            Ok(store) => {
+                self.ui.show_container_usage = true;
+                self.usage.rescan(&name, store);
+            }
+            Err(e) => self.error_message = Some(format!("Failed to scan {name}: {e}")),
+        }
+    }
+
+    /// Build an object store for a container of the current account.
+    fn container_store(&self, container: &str) -> Result<Arc<MicrosoftAzure>, BlobrsError> {
+        Ok(Arc::new(container_store(
+            &self.storage_account,
+            self.access_key.expose(),
+            container,
+            self.config.retry,
+            self.config.timeouts,
+        )?))
+    }
+
+    /// Prompt for the name of a container to create.
+    pub fn open_new_container_dialog(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        self.modal = Modal::NewContainer {
+            input: String::new(),
+        };
+    }
+
+    /// Handle key events when in the new container prompt.
+    pub async fn handle_new_container_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::NewContainer { input } = &mut self.modal else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Esc => self.close_modal(),
+            KeyCode::Enter => {
+                let name = input.trim().to_string();
+                if let Err(e) = validate_container_name(&name) {
+                    self.error_message = Some(e.to_string());
+                } else {
+                    self.close_modal();
+                    self.create_container(&name).await;
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    /// Create a container, then reload the list and select it.
+    async fn create_container(&mut self, name: &str) {
+        let result = match self.rest_client() {
+            Ok(client) => client.create_container(name).await,
+            Err(e) => Err(e),
+        };
+        self.audit(AuditAction::CreateContainer, name, Outcome::of(&result));
+        if let Err(e) = result {
+            self.error_message = Some(format!("Failed to create container {name}: {e}"));
+            return;
+        }
+
+        if let Err(e) = self.load_containers().await {
+            self.error_message = Some(format!("Refresh failed: {}", describe_error(&e)));
+        }
+        if let Some(index) = self.containers.iter().position(|c| c.name == name) {
+            self.selected_container_index = index;
+        }
+        self.success_message = Some(format!("Created container {name}"));
+    }
+
+    /// Ask to delete the selected container, which must be empty.
+    pub async fn open_delete_container_dialog(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let Some(name) = self
+            .containers
+            .get(self.selected_container_index)
+            .map(|container| container.name.clone())
+        else {
+            return;
+        };
+
+        let empty = match self.rest_client() {
+            Ok(client) => client.container_is_empty(&name).await,
+            Err(e) => Err(e),
+        };
+        match empty {
+            Ok(true) => {
+                self.modal = Modal::DeleteContainer {
+                    name,
+                    input: String::new(),
+                };
+            }
+            Ok(false) => {
+                self.error_message = Some(format!(
+                    "Container {name} is not empty; delete its blobs first"
+                ));
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to list container {name}: {e}"));
+            }
+        }
+    }
+
+    /// Handle key events when confirming a container delete.
+    pub async fn handle_delete_container_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::DeleteContainer { name, input } = &mut self.modal else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Esc => self.close_modal(),
+            // Only allow confirm if the typed name matches the container name
+            KeyCode::Enter if input == name => {
+                let name = name.clone();
+                self.close_modal();
+                self.delete_container(&name).await;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    /// Delete a container and reload the list.
+    async fn delete_container(&mut self, name: &str) {
+        let result = match self.rest_client() {
+            Ok(client) => client.delete_container(name).await,
+            Err(e) => Err(e),
+        };
+        self.audit(AuditAction::DeleteContainer, name, Outcome::of(&result));
+        if let Err(e) = result {
+            self.error_message = Some(format!("Failed to delete container {name}: {e}"));
+            return;
+        }
+
+        if let Err(e) = self.load_containers().await {
+            self.error_message = Some(format!("Refresh failed: {}", describe_error(&e)));
+        }
+        self.success_message = Some(format!("Deleted container {name}"));
+    }
+
+    /// Move container selection up.
+    fn move_container_up(&mut self) {
+        if !self.containers.is_empty() && self.selected_container_index > 0 {
+            self.selected_container_index -= 1;
+        }
+    }
+
+    /// Move container selection down.
+    fn move_container_down(&mut self) {
+        if !self.containers.is_empty() && self.selected_container_index < self.containers.len() - 1
+        {
+            self.selected_container_index += 1;
+        }
+    }
+
+    /// Take the shown tab's location out of the app, leaving the container list.
+    fn take_tab(&mut self) -> Tab {
+        // The filtered container list is shared, so a container search is not kept
+        if self.is_searching_containers() {
+            self.exit_container_search_mode();
+        }
+        Tab {
+            session: std::mem::replace(&mut self.session, Session::Selecting),
+            search: std::mem::replace(&mut self.search, Search::Inactive),
+            sort: self.sort,
+            selected_container_index: std::mem::take(&mut self.selected_container_index),
+        }
+    }
+
+    /// Show `tab`'s location.
+    fn show_tab(&mut self, tab: Tab) {
+        self.session = tab.session;
+        self.search = tab.search;
+        self.sort = tab.sort;
+        self.selected_container_index = tab
+            .selected_container_index
+            .min(self.containers.len().saturating_sub(1));
+        self.pending_motion = PendingMotion::default();
+        self.last_click = None;
+    }
+
+    /// Keep the shown location in a tab and open a new one on the container list.
+    pub fn new_tab(&mut self) {
+        let shown = self.take_tab();
+        self.tabs.open(shown);
+        self.pending_motion = PendingMotion::default();
+        self.last_click = None;
+    }
+
+    /// Show the next tab, if there is more than one.
+    pub fn next_tab(&mut self) {
+        if self.tabs.count() > 1 {
+            let shown = self.take_tab();
+            let next = self.tabs.next(shown);
+            self.show_tab(next);
+        }
+    }
+
+    /// Close the shown tab and show its neighbour. The last tab stays open.
+    pub fn close_tab(&mut self) {
+        if let Some(neighbour) = self.tabs.close() {
+            self.show_tab(neighbour);
+        }
+    }
+
+    /// Show the current tab's neighbour beside it, opening a second tab on the container list
+    /// if there is only one; or go back to one pane.
+    pub fn toggle_two_panes(&mut self) {
+        self.ui.show_two_panes = !self.ui.show_two_panes;
+        if self.ui.show_two_panes && self.tabs.count() == 1 {
+            self.new_tab();
+        }
+    }
+
+    /// Whether two panes are shown.
+    #[must_use]
+    pub fn shows_two_panes(&self) -> bool {
+        self.ui.show_two_panes && self.tabs.count() > 1
+    }
+
+    /// Show tab `index`, keeping the shown one.
+    fn switch_to_tab(&mut self, index: usize) {
+        let shown = self.take_tab();
+        let tab = self.tabs.switch_to(shown, index);
+        self.show_tab(tab);
+    }
+
+    /// The other pane's position among the tabs, with its container and folder, if it is
+    /// showing one.
+    fn other_pane_folder(&mut self) -> Option<(usize, String, String)> {
+        match self.tabs.neighbour() {
+            Some((
+                index,
+                Tab {
+                    session: Session::Browsing(state),
+                    ..
+                },
+            )) => Some((
+                index,
+                state.container_name.clone(),
+                state.current_path.clone(),
+            )),
+            _ => {
+                self.error_message =
+                    Some("Open a container in the other pane first (`Tab` to switch)".to_string());
+                None
+            }
+        }
+    }
+
+    /// Copy the marked entries, or else the selected one, into the other pane's folder,
+    /// server-side.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the copy fails.
+    pub async fn copy_to_other_pane(&mut self) -> color_eyre::Result<()> {
+        let Some((other, _, _)) = self.other_pane_folder() else {
+            return Ok(());
+        };
+        // Copying goes through the yank machinery without losing what was yanked with `Y`
+        let yanked = self.yanked.take();
+        self.yank_selected();
+        if self.yanked.is_none() {
+            self.yanked = yanked;
+            return Ok(());
+        }
+        let here = self.tabs.active();
+        self.switch_to_tab(other);
+        let result = self.paste_yanked().await;
+        self.switch_to_tab(here);
+        self.yanked = yanked;
+        result
+    }
+
+    /// Move the marked entries, or else the selected one, into the other pane's folder. Both
+    /// panes must show the same container.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if refreshing either listing fails.
+    pub async fn move_to_other_pane(&mut self) -> color_eyre::Result<()> {
+        if self.refuse_if_read_only() {
+            return Ok(());
+        }
+        let Some((other, container, destination)) = self.other_pane_folder() else {
+            return Ok(());
+        };
+        let Some(browsing) = self.browsing() else {
+            return Ok(());
+        };
+        if container != browsing.container_name {
+            self.error_message = Some(
+                "Moves between containers are not supported - copy with F5, then delete the originals"
+                    .to_string(),
+            );
+            return Ok(());
+        }
+        if destination == browsing.current_path {
+            self.error_message =
+                Some("Both panes show the same folder - open another one first".to_string());
+            return Ok(());
+        }
+        let sources: Vec<String> = if browsing.marked.is_empty() {
+            match self.selected_file_item() {
+                Some(item) if !item.actual_name.is_empty() => {
+                    vec![Self::item_path(&browsing.current_path, item)]
+                }
+                _ => return Ok(()),
+            }
+        } else {
+            browsing.marked.keys().cloned().collect()
+        };
+        let folder = browsing.current_path.clone();
+        self.error_message = None;
+
+        let mut moves = Vec::new();
+        let mut plan = FolderPlan::default();
+        for source in &sources {
+            let is_folder = source.ends_with('/');
+            let target = format!(
+                "{destination}{}",
+                source.strip_prefix(folder.as_str()).unwrap_or(source)
+            );
+            if is_folder && target.starts_with(source.as_str()) {
+                self.error_message = Some("Cannot move a folder into itself".to_string());
+                return Ok(());
+            }
+            if !is_folder && self.refuse_if_immutable(source).await {
+                return Ok(());
+            }
+            // Nothing is moved unless every entry can be listed
+            let Some(source_plan) = self.plan_move(source, is_folder).await else {
+                return Ok(());
+            };
+            moves.extend(move_pairs(source, &target, &source_plan));
+            plan.blobs.extend(source_plan.blobs);
+        }
+        if let Some(state) = self.browsing_mut() {
+            state.marked.clear();
+        }
+
+        let source = match sources.as_slice() {
+            [source] => source.clone(),
+            _ => format!("{} marked items", sources.len()),
+        };
+        self.start_move_or_warn(source, destination, moves, &plan, Some(other));
+        Ok(())
+    }
+
+    /// Enter container search mode.
+    pub fn enter_container_search_mode(&mut self) {
+        // Always start search from the full container list
+        self.containers = self.all_containers.clone();
+        self.selected_container_index = 0;
+        self.search = Search::Containers {
+            query: String::new(),
+            all_containers: self.all_containers.clone(),
+        };
+        self.error_message = None;
+        self.success_message = None;
+    }
+
+    /// Exit container search mode and restore original container list.
+    pub fn exit_container_search_mode(&mut self) {
+        if let Search::Containers { all_containers, .. } = &self.search {
+            self.containers = all_containers.clone();
+            self.selected_container_index = 0;
+        }
+        self.search = Search::Inactive;
+    }
+
+    /// Handle key events when in container search mode.
+    ///
+    /// # Errors
+    ///
+    /// This function currently does not return errors but uses `Result` for API consistency.
+    pub fn handle_container_search_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> color_eyre::Result<()> {
+        if let Some(motion) = Motion::of_key(key_event) {
+            self.move_selection(motion, None);
+            return Ok(());
+        }
+        let Search::Containers { query, .. } = &mut self.search else {
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.exit_container_search_mode();
+            }
+            KeyCode::Enter => {
+                // Exit search mode but keep the filtered results
+                self.search = Search::Inactive;
+            }
+            KeyCode::Backspace => {
+                query.pop();
+                let current = query.clone();
+                self.apply_container_search(&current);
+            }
+            KeyCode::Up if key_event.modifiers == KeyModifiers::CONTROL => {
+                self.move_container_up();
+            }
+            KeyCode::Down if key_event.modifiers == KeyModifiers::CONTROL => {
+                self.move_container_down();
+            }
+            KeyCode::Tab => {
+                let current = query.clone();
+                self.ui.filter_mode = self.ui.filter_mode.next();
+                self.apply_container_search(&current);
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                let current = query.clone();
+                self.apply_container_search(&current);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Filter containers based on search query.
+    pub fn filter_containers(&mut self) {
+        let query = match &self.search {
+            Search::Containers { query, .. } => query.clone(),
+            _ => return,
+        };
+        self.apply_container_search(&query);
+    }
+
+    /// Show information about the currently selected blob or folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fetching blob/folder metadata fails.
+    pub async fn show_blob_info(&mut self) -> color_eyre::Result<()> {
+        let Some(selected_item) = self.selected_file_item().cloned() else {
+            return Ok(());
+        };
+
+        let info = if selected_item.kind == EntryKind::Folder {
+            self.folder_info(&selected_item.actual_name)?
+        } else {
+            // Get individual blob information
+            self.get_blob_info(
+                &selected_item.actual_name,
+                selected_item.version_id.as_deref(),
+            )
+            .await?
+        };
+
+        self.modal = Modal::BlobInfo { info };
+        Ok(())
+    }
+
+    /// Break down the size of the open folder by what is directly inside it.
+    fn open_usage_breakdown(&mut self) {
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let folder = state.current_path.clone();
+        self.usage
+            .scan_prefix(&state.container_name, &folder, state.object_store.clone());
+        self.modal = Modal::Usage {
+            folder,
+            selected: 0,
+        };
+    }
+
+    /// Handle a key press in the size breakdown: move with the list motions, open a folder
+    /// with Enter or `l`, go up with `h` or Backspace, count again with `r`, and close with
+    /// `q`, `B` or Esc.
+    pub fn handle_usage_key_event(&mut self, key_event: KeyEvent) {
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let container = state.container_name.clone();
+        let store = state.object_store.clone();
+        let Modal::Usage { folder, selected } = &mut self.modal else {
+            return;
+        };
+        let usage = self
+            .usage
+            .get_prefix(&container, folder)
+            .unwrap_or_default();
+        let entries = usage.breakdown();
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q' | 'B') => {
+                self.pending_motion = PendingMotion::default();
+                self.close_modal();
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                if let Some((name, _)) = entries.get(*selected)
+                    && name.ends_with('/')
+                {
+                    *folder = format!("{folder}{name}");
+                    *selected = 0;
+                    self.usage.scan_prefix(&container, folder, store);
+                }
+            }
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') if !folder.is_empty() => {
+                let trimmed = folder.trim_end_matches('/');
+                let parent = trimmed.rfind('/').map_or("", |index| &trimmed[..=index]);
+                let child = format!("{}/", &trimmed[parent.len()..]);
+                *folder = parent.to_string();
+                self.usage.scan_prefix(&container, folder, store);
+                // Land on the folder just left
+                *selected = self
+                    .usage
+                    .get_prefix(&container, folder)
+                    .and_then(|usage| {
+                        usage
+                            .breakdown()
+                            .iter()
+                            .position(|(name, _)| *name == child)
+                    })
+                    .unwrap_or(0);
+            }
+            KeyCode::Char('r') => {
+                self.usage.rescan_prefix(&container, folder, store);
+                *selected = 0;
+            }
+            _ => {
+                if let MotionKey::Move(motion, count) = self.pending_motion.handle(key_event) {
+                    let page = usize::from(self.list_layout.borrow().rows.height).max(1);
+                    *selected = motion.target(count, *selected, entries.len(), page);
+                }
+            }
+        }
+    }
+
+    /// Start counting a folder's blobs, unless they were counted earlier this session.
+    fn folder_info(&self, folder_name: &str) -> color_eyre::Result<BlobInfo> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let path = Self::join_folder_path(&browsing.current_path, folder_name);
+        self.usage.scan_folder(
+            &browsing.container_name,
+            &path,
+            browsing.object_store.clone(),
+        );
+        // Without a usable key there is no tier listing, and so no cost estimate
+        if let Ok(client) = self.rest_client() {
+            self.usage
+                .scan_tiers(&browsing.container_name, &path, client);
+        }
+        Ok(BlobInfo::Folder {
+            name: folder_name.to_string(),
+            path,
+        })
+    }
+
+    /// Count the folder shown in the info popup again, e.g. after it changed.
+    fn recount_folder_info(&self) {
+        if let (
+            Modal::BlobInfo {
+                info: BlobInfo::Folder { path, .. },
+            },
+            Some(browsing),
+        ) = (&self.modal, self.browsing())
+        {
+            self.usage.rescan_folder(
+                &browsing.container_name,
+                path,
+                browsing.object_store.clone(),
+            );
+            if let Ok(client) = self.rest_client() {
+                self.usage
+                    .rescan_tiers(&browsing.container_name, path, client);
+            }
+        }
+    }
+
+    /// Get information about a specific blob (or one of its versions).
+    async fn get_blob_info(
+        &self,
+        blob_name: &str,
+        version: Option<&str>,
+    ) -> color_eyre::Result<BlobInfo> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let object_store = browsing.object_store.clone();
+
+        let blob_path = Self::join_blob_path(&browsing.current_path, blob_name);
+
+        let object_path = ObjectPath::from(blob_path.as_str());
+
+        // The full HEAD response describes the current blob, not older versions
+        let properties = match (version, self.rest_client()) {
+            (None, Ok(client)) => client
+                .blob_properties(&browsing.container_name, &blob_path)
+                .await
+                .ok(),
+            _ => None,
+        }
+        .unwrap_or_default();
+        let page_used = match (properties.blob_type.as_deref(), self.rest_client()) {
+            (Some("PageBlob"), Ok(client)) => client
+                .page_blob_used_bytes(&browsing.container_name, &blob_path)
+                .await
+                .ok(),
+            _ => None,
+        };
+        let immutable_until = properties.immutability_policy_until.clone().map(|until| {
+            let state = match properties.protection(Utc::now()) {
+                Some(_) => properties
+                    .immutability_policy_mode
+                    .clone()
+                    .unwrap_or_else(|| "active".to_string()),
+                None => "expired".to_string(),
+            };
+            format!("{} ({state})", format_service_time(until))
+        });
+        let created = properties
+            .created
+            .as_deref()
+            .and_then(|time| chrono::DateTime::parse_from_rfc2822(time).ok())
+            .map(|time| time.with_timezone(&Utc));
+
+        match fetch::meta(&object_store, &object_path, version).await {
+            Ok(meta) => Ok(BlobInfo::File {
+                name: blob_name.to_string(),
+                size: meta.size,
+                last_modified: meta.last_modified,
+                etag: meta.e_tag.clone(),
+                tags: properties.tags,
+                lease_state: properties.lease_state,
+                lease_duration: properties.lease_duration,
+                created,
+                blob_type: properties.blob_type,
+                content_type: properties.content_type,
+                content_encoding: properties.content_encoding,
+                content_md5: properties.content_md5,
+                page_used,
+                immutable_until,
+                legal_hold: properties.legal_hold,
+            }),
+            Err(e) => Err(color_eyre::eyre::eyre!("Failed to get blob metadata: {e}",)),
+        }
+    }
+
+    /// Copy the full blob path to clipboard.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if clipboard access fails.
+    pub fn copy_blob_path_to_clipboard(&mut self) -> color_eyre::Result<()> {
+        let (selected_item, current_path) = match self.browsing() {
+            Some(state) => {
+                let Some(item) = self.selected_file_item() else {
+                    return Ok(());
+                };
+                (item.clone(), state.current_path.clone())
+            }
+            None => return Ok(()),
+        };
+
+        if selected_item.actual_name.is_empty() {
+            return Ok(());
+        }
+        let is_folder = selected_item.kind == EntryKind::Folder;
+        let full_path = if is_folder {
+            Self::join_folder_path(&current_path, &selected_item.actual_name)
+        } else {
+            Self::join_blob_path(&current_path, &selected_item.actual_name)
+        };
+
+        // Copy to clipboard
+        let mut clipboard = Clipboard::new()?;
+        clipboard.set_text(full_path.clone())?;
+
+        // Set success message
+        let item_type = if is_folder { "folder" } else { "file" };
+        self.success_message = Some(format!("Copied {item_type} path to clipboard: {full_path}"));
+        self.error_message = None; // Clear any existing error message
+
+        Ok(())
+    }
+
+    /// Show the download destination picker.
+    ///
+    /// When a default download directory is configured, Enter downloads there directly.
+    pub fn show_download_picker(&mut self) {
+        if let Some(state) = self.browsing() {
+            if state.files.is_empty() {
+                return;
+            }
+        } else {
+            return;
+        }
+
+        // Recent destinations are picked with 1-9, the file dialog with `b`
+        let items = self
+            .state
+            .recent_download_dirs
+            .iter()
+            .zip('1'..='9')
+            .map(|(dir, key)| MenuItem::new(key, dir.display().to_string(), Some(dir.clone())))
+            .chain(std::iter::once(MenuItem::new('b', "Browse…", None)))
+            .collect();
+        self.modal = Modal::DownloadPicker {
+            destination: None,
+            menu: Menu::new(items),
+            use_default_dir: self.config.download_dir.is_some(),
+            snapshot: None,
+        };
+    }
+
+    /// Handle key events when the download picker is shown.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file dialog or download fails.
+    pub async fn handle_download_picker_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> color_eyre::Result<()> {
+        let Modal::DownloadPicker {
+            menu,
+            use_default_dir,
+            ..
+        } = &mut self.modal
+        else {
+            return Ok(());
+        };
+
+        if *use_default_dir && self.config.download_dir.is_some() {
+            match key_event.code {
+                KeyCode::Enter => {}
+                // Choose another destination instead of the default directory
+                KeyCode::Char('D') => {
+                    *use_default_dir = false;
+                    return Ok(());
+                }
+                KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                    self.close_modal();
+                    return Ok(());
+                }
+                _ => return Ok(()),
+            }
+        } else {
+            match menu.handle_key(key_event) {
+                MenuOutcome::Pending => return Ok(()),
+                MenuOutcome::Cancelled => {
+                    self.close_modal();
+                    return Ok(());
+                }
+                MenuOutcome::Chosen(choice) => {
+                    if let Some(index) = menu.items.iter().position(|item| item.value == choice) {
+                        menu.selected = index;
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = self.confirm_download().await {
+            self.error_message = Some(format!("Download failed: {e}"));
+        }
+        Ok(())
+    }
+
+    /// Ask which tool to copy a download command for the selected file or folder.
+    pub fn open_copy_command_picker(&mut self) {
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let Some(item) = self.selected_file_item() else {
+            return;
+        };
+        if item.actual_name.is_empty() || item.deleted {
+            return;
+        }
+        let path = if item.kind == EntryKind::Folder {
+            Self::join_folder_path(&state.current_path, &item.actual_name)
+        } else {
+            Self::join_blob_path(&state.current_path, &item.actual_name)
+        };
+        let target = CommandTarget {
+            account: self.storage_account.clone(),
+            container: state.container_name.clone(),
+            path,
+            version_id: item.version_id.clone(),
+        };
+        let menu = Menu::new(vec![
+            MenuItem::new('a', "Azure CLI (az storage blob)", CommandStyle::AzCli),
+            MenuItem::new('c', "AzCopy (azcopy copy)", CommandStyle::AzCopy),
+        ]);
+        self.modal = Modal::CopyCommand { target, menu };
+    }
+
+    /// Handle key events when picking the tool for a download command.
+    pub fn handle_copy_command_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::CopyCommand { target, menu } = &mut self.modal else {
+            return;
+        };
+        match menu.handle_key(key_event) {
+            MenuOutcome::Pending => {}
+            MenuOutcome::Cancelled => self.close_modal(),
+            MenuOutcome::Chosen(style) => {
+                let destination = self
+                    .config
+                    .download_dir()
+                    .map_or_else(|| ".".to_string(), |dir| dir.display().to_string());
+                let command = download_command(style, target, &destination);
+                self.close_modal();
+                match Clipboard::new().and_then(|mut clipboard| clipboard.set_text(command.clone()))
+                {
+                    Ok(()) => {
+                        self.success_message = Some(format!("Copied command: {command}"));
+                        self.error_message = None;
+                    }
+                    Err(e) => {
+                        self.error_message = Some(format!("Failed to copy to clipboard: {e}"));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Show the container sort picker, highlighting the current order. It stays marked as
+    /// current when the highlight moves.
+    pub fn open_container_sort_picker(&mut self) {
+        let current = self.container_sort;
+        let items = [
+            ('n', "Sort by Name", SortCriteria::Name),
+            ('m', "Sort by Date Modified", SortCriteria::DateModified),
+        ]
+        .into_iter()
+        .map(|(key, label, criteria)| {
+            if criteria == current {
+                MenuItem::new(key, format!("{label} (current)"), criteria)
+            } else {
+                MenuItem::new(key, label, criteria)
+            }
+        })
+        .collect();
+        let menu = Menu::new(items).with_selected(|criteria| *criteria == current);
+        self.modal = Modal::SortPicker { menu };
+    }
+
+    /// Reorder the container list, keeping the highlighted container selected. Recently
+    /// opened containers stay first.
+    fn apply_container_sort(&mut self, criteria: SortCriteria) {
+        self.container_sort = criteria;
+        let by_modified = criteria == SortCriteria::DateModified;
+        let recent: Vec<String> = self
+            .state
+            .recent_containers_of(&self.storage_account)
+            .map(str::to_string)
+            .collect();
+        let recent: Vec<&str> = recent.iter().map(String::as_str).collect();
+        let selected = self
+            .containers
+            .get(self.selected_container_index)
+            .map(|container| container.name.clone());
+
+        self.all_containers = order_containers(
+            std::mem::take(&mut self.all_containers),
+            &recent,
+            by_modified,
+        );
+        self.containers =
+            order_containers(std::mem::take(&mut self.containers), &recent, by_modified);
+        if let Search::Containers { all_containers, .. } = &mut self.search {
+            *all_containers =
+                order_containers(std::mem::take(all_containers), &recent, by_modified);
+        }
+        self.selected_container_index = selected
+            .and_then(|name| self.containers.iter().position(|c| c.name == name))
+            .unwrap_or(0);
+    }
+
+    /// Show the sort picker, highlighting the current sort order and which way round it is.
+    pub fn open_sort_picker(&mut self) {
+        let current = self.sort;
+        let items = [
+            ('n', "Sort by Name", SortCriteria::Name),
+            ('m', "Sort by Date Modified", SortCriteria::DateModified),
+            ('t', "Sort by Date Created", SortCriteria::DateCreated),
+            ('s', "Sort by Size", SortCriteria::Size),
+        ]
+        .into_iter()
+        .map(|(key, label, criteria)| {
+            if criteria == current.criteria {
+                MenuItem::new(key, format!("{label} ({})", current.direction()), criteria)
+            } else {
+                MenuItem::new(key, label, criteria)
+            }
+        })
+        .collect();
+        let menu = Menu::new(items).with_selected(|criteria| *criteria == current.criteria);
+        self.modal = Modal::SortPicker { menu };
+    }
+
+    /// Sort by `order` and remember it for the open container and as the default for others.
+    async fn choose_sort(&mut self, order: SortOrder) {
+        if order.criteria == SortCriteria::Size {
+            self.load_listed_page_blob_usage().await;
+        }
+        if let Err(e) = self.apply_sort(order) {
+            self.error_message = Some(format!("Failed to sort: {e}"));
+        }
+        let Some(container) = self.browsing().map(|state| state.container_name.clone()) else {
+            return;
+        };
+        self.state
+            .remember_sort(&self.storage_account, &container, order);
+        if let Err(e) = self.state.save() {
+            self.error_message = Some(format!("Failed to save sort order: {e}"));
+        }
+    }
+
+    /// Handle key events when the sort picker is shown.
+    pub async fn handle_sort_picker_key_event(&mut self, key_event: KeyEvent) {
+        let Modal::SortPicker { menu } = &mut self.modal else {
+            return;
+        };
+        match menu.handle_key(key_event) {
+            MenuOutcome::Pending => {}
+            MenuOutcome::Cancelled => self.close_modal(),
+            MenuOutcome::Chosen(criteria) if self.is_selecting() => {
+                self.apply_container_sort(criteria);
+                self.close_modal();
+            }
+            MenuOutcome::Chosen(criteria) => {
+                self.close_modal();
+                self.choose_sort(self.sort.then(criteria)).await;
+            }
+        }
+    }
+
+    /// Start the download process for the marked entries, or else the selected file or folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the download operation fails.
+    pub async fn start_download(&mut self) -> color_eyre::Result<()> {
+        let (destination, snapshot) = match &self.modal {
+            Modal::DownloadPicker {
+                destination,
+                snapshot,
+                ..
+            } => (destination.clone(), snapshot.clone()),
+            _ => (None, None),
+        };
+
+        if let Some(snapshot) = snapshot {
+            let Some(destination) = destination else {
+                return Ok(());
+            };
+            self.close_modal();
+            self.download_snapshot(&snapshot, &destination).await;
+            self.remember_download_dir(&destination);
+            return Ok(());
+        }
+
+        if self.marked_summary().is_some() {
+            let Some(destination) = destination else {
+                return Ok(());
+            };
+            self.close_modal();
+            let plan = self.plan_marked().await?;
+            if self
+                .config
+                .limits
+                .exceeded_by(plan.blobs.len(), plan.total_bytes())
+            {
+                self.modal = Modal::LargeOperationWarning {
+                    operation: PendingOperation::DownloadMarked { destination },
+                    plan,
+                };
+                return Ok(());
+            }
+            return self.start_marked_download(&destination, plan);
+        }
+
+        let Some(selected_item) = self.selected_file_item().cloned() else {
+            return Ok(());
+        };
+
+        let Some(destination) = destination else {
+            return Ok(());
+        };
+        let is_folder = selected_item.kind == EntryKind::Folder;
+        let name = selected_item.actual_name;
+        self.close_modal();
+
+        if is_folder {
+            let folder_path = self
+                .browsing()
+                .map(|state| Self::join_folder_path(&state.current_path, &name))
+                .unwrap_or_default();
+            let plan = self.plan_folder(&folder_path).await?;
+            if self
+                .config
+                .limits
+                .exceeded_by(plan.blobs.len(), plan.total_bytes())
+            {
+                self.modal = Modal::LargeOperationWarning {
+                    operation: PendingOperation::Download {
+                        folder_name: name,
+                        destination,
+                    },
+                    plan,
+                };
+                return Ok(());
+            }
+            return self.start_folder_download(&name, &destination, plan);
+        }
+
+        self.async_op = AsyncOp::Downloading(DownloadProgress {
+            current_file: String::new(),
+            files_completed: 0,
+            total_files: 0,
+            bytes_downloaded: 0,
+            total_bytes: None,
+            error_message: None,
+        });
+        let result = self
+            .download_file(&name, selected_item.version_id.as_deref(), &destination)
+            .await;
+        let blob_path = self
+            .browsing()
+            .map(|state| Self::join_blob_path(&state.current_path, &name))
+            .unwrap_or_default();
+        self.audit(
+            AuditAction::Download,
+            format!(
+                "{} → {}",
+                self.audit_target(&blob_path),
+                destination.display()
+            ),
+            Outcome::of(&result),
+        );
+        let file = result?;
+        self.async_op = AsyncOp::None;
+        self.remember_download_dir(&destination);
+        self.downloaded(file);
+        Ok(())
+    }
+
+    /// Offer to open a file that has just been downloaded, or open it straight away if the
+    /// config says so.
+    fn downloaded(&mut self, file: PathBuf) {
+        let message = format!("Downloaded {}", file.display());
+        self.last_download = Some(file);
+        if self.config.open_downloads {
+            self.open_last_download();
+        } else {
+            self.success_message = Some(format!("{message} • `o` to open it"));
+        }
+    }
+
+    /// Open the file last downloaded with the system's default application.
+    pub fn open_last_download(&mut self) {
+        let Some(file) = &self.last_download else {
+            self.error_message = Some("Nothing has been downloaded yet".to_string());
+            return;
+        };
+        match open::open(file) {
+            Ok(()) => self.success_message = Some(format!("Opened {}", file.display())),
+            Err(e) => {
+                self.error_message = Some(format!("Could not open {}: {e}", file.display()));
+            }
+        }
+    }
+
+    /// Start downloading a planned folder as a background job and remember where it goes.
+    fn start_folder_download(
+        &mut self,
+        folder_name: &str,
+        destination: &Path,
+        plan: FolderPlan,
+    ) -> color_eyre::Result<()> {
+        let folder_path = self
+            .browsing()
+            .map(|state| Self::join_folder_path(&state.current_path, folder_name))
+            .unwrap_or_default();
+        self.start_plan_download(
+            folder_name,
+            &folder_path,
+            &destination.join(folder_name),
+            destination,
+            plan,
+        )
+    }
+
+    /// Start downloading the planned marked entries as a background job and clear the marks.
+    fn start_marked_download(
+        &mut self,
+        destination: &Path,
+        plan: FolderPlan,
+    ) -> color_eyre::Result<()> {
+        let Some(state) = self.browsing_mut() else {
+            return Ok(());
+        };
+        let label = format!("{} marked items", state.marked.len());
+        let current_path = state.current_path.clone();
+        state.marked.clear();
+        self.start_plan_download(&label, &current_path, destination, destination, plan)
+    }
+
+    /// Download a plan as a background job, keeping paths below `prefix` under `root`.
+    fn start_plan_download(
+        &mut self,
+        label: &str,
+        prefix: &str,
+        root: &Path,
+        destination: &Path,
+        plan: FolderPlan,
+    ) -> color_eyre::Result<()> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+
+        let files = plan
+            .blobs
+            .into_iter()
+            .map(|blob| {
+                let file_path = blob.location.as_ref();
+                let relative_path = file_path
+                    .strip_prefix(prefix)
+                    .unwrap_or(file_path)
+                    .to_string();
+                FileTransfer {
+                    destination: root.join(&relative_path),
+                    relative_path,
+                    location: blob.location,
+                    version_id: blob.version_id,
+                    e_tag: blob.e_tag,
+                    size: blob.size,
+                    bytes_done: 0,
+                    status: FileStatus::Queued,
+                }
+            })
+            .collect();
+        let source = TransferSource {
+            object_store: browsing.object_store.clone(),
+            account: self.storage_account.clone(),
+            container: browsing.container_name.clone(),
+            cache: self.cache.clone(),
+            stats: self.transfer_stats.clone(),
+        };
+
+        let job = TransferJob::new(
+            format!("{label} → {}", destination.display()),
+            source,
+            files,
+        );
+        job.start();
+        // Marked items are downloaded straight into `destination`, a folder into a subfolder
+        let folder = self.audit_target(prefix.trim_end_matches('/'));
+        let what = if root == destination {
+            format!("{label} in {folder}")
+        } else {
+            folder
+        };
+        self.audit.record_job(
+            &self.storage_account,
+            AuditAction::Download,
+            format!("{what} → {}", destination.display()),
+            job.clone(),
+        );
+        self.jobs.push(job);
+
+        if plan.errors.is_empty() {
+            self.success_message = Some(format!(
+                "Downloading {label} in the background • `J` to view jobs"
+            ));
+        } else {
+            self.error_message = Some(format!(
+                "Downloading {label}; {} blob(s) could not be listed: {}",
+                plan.errors.len(),
+                plan.errors[0]
+            ));
+        }
+        self.remember_download_dir(destination);
+        Ok(())
+    }
+
+    /// Add a destination to the recent list and save it.
+    fn remember_download_dir(&mut self, destination: &Path) {
+        self.state.remember_download_dir(destination);
+        if let Err(e) = self.state.save() {
+            self.error_message = Some(format!("Failed to save recent destinations: {e}"));
+        }
+    }
+
+    /// Download a single file, optionally a specific version of it, returning where it was
+    /// saved.
+    async fn download_file(
+        &mut self,
+        file_name: &str,
+        version: Option<&str>,
+        destination: &Path,
+    ) -> color_eyre::Result<PathBuf> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let object_store = browsing.object_store.clone();
+
+        let blob_path = Self::join_blob_path(&browsing.current_path, file_name);
+
+        let object_path = ObjectPath::from(blob_path.as_str());
+
+        // Initialize progress
+        self.async_op = AsyncOp::Downloading(DownloadProgress {
+            current_file: file_name.to_string(),
+            files_completed: 0,
+            total_files: 1,
+            bytes_downloaded: 0,
+            total_bytes: None,
+            error_message: None,
+        });
+
+        // Get file metadata for total size
+        let meta = fetch::meta(&object_store, &object_path, version).await.ok();
+        if let Some(meta) = &meta
+            && let AsyncOp::Downloading(progress) = &mut self.async_op
+        {
+            progress.total_bytes = Some(meta.size);
+        }
+        let e_tag = meta.and_then(|meta| meta.e_tag);
+
+        // Create destination file path
+        let file_destination = destination.join(file_name);
+
+        // Ensure parent directory exists
+        if let Some(parent) = file_destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Download the file
+        match self
+            .fetch_bytes_cached(&object_store, &object_path, None, version, e_tag.as_deref())
+            .await
+        {
+            Ok(bytes) => {
+                fs::write(&file_destination, &bytes)?;
+
+                if let AsyncOp::Downloading(progress) = &mut self.async_op {
+                    progress.bytes_downloaded = bytes.len() as u64;
+                    progress.files_completed = 1;
+                }
+            }
+            Err(e) => {
+                if let AsyncOp::Downloading(progress) = &mut self.async_op {
+                    progress.error_message = Some(format!("Failed to download {file_name}: {e}"));
+                }
+                return Err(color_eyre::eyre::eyre!("Download failed: {}", e));
+            }
+        }
+
+        Ok(file_destination)
+    }
+
+    /// Download a snapshot of the selected blob, named after the blob and the snapshot time.
+    async fn download_snapshot(&mut self, snapshot: &str, destination: &Path) {
+        let Some(browsing) = self.browsing() else {
+            return;
+        };
+        let Some(item) = self.selected_file_item() else {
+            return;
+        };
+        let blob_path = Self::join_blob_path(&browsing.current_path, &item.actual_name);
+        let file_destination = destination.join(snapshot_file_name(&item.actual_name, snapshot));
+
+        let container = browsing.container_name.clone();
+
+        let written: color_eyre::Result<()> = async {
+            let client = self.rest_client()?;
+            tokio::fs::create_dir_all(destination).await?;
+            let downloaded = client
+                .download_snapshot(&container, &blob_path, snapshot, &file_destination)
+                .await;
+            if downloaded.is_err() {
+                let _ = tokio::fs::remove_file(&file_destination).await;
+            }
+            downloaded?;
+            Ok(())
+        }
+        .await;
+        self.audit(
+            AuditAction::Download,
+            format!(
+                "{container}/{blob_path} (snapshot {snapshot}) → {}",
+                file_destination.display()
+            ),
+            Outcome::of(&written),
+        );
+        match written {
+            Ok(()) => self.downloaded(file_destination),
+            Err(e) => {
+                self.error_message = Some(format!(
+                    "Failed to download snapshot {snapshot}: {}",
+                    describe_error(&e)
+                ));
+            }
+        }
+    }
+
+    /// List every blob under `folder_path`, as of the time-travel timestamp if one is set.
+    async fn plan_folder(&self, folder_path: &str) -> color_eyre::Result<FolderPlan> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let mut plan = FolderPlan::default();
+
+        if let Some(as_of) = browsing.as_of {
+            let listing = self
+                .rest_client()?
+                .list_blob_versions(&browsing.container_name, folder_path, None)
+                .await?;
+            plan.blobs = versions_as_of(&listing.versions, as_of)
+                .into_iter()
+                .map(|v| PlannedBlob {
+                    location: ObjectPath::from(v.name.as_str()),
+                    version_id: v.version_id,
+                    size: v.size,
+                    e_tag: v.etag,
+                })
+                .collect();
+        } else {
+            let prefix = ObjectPath::from(folder_path);
+            let mut stream = browsing.object_store.list(Some(&prefix));
+            while let Some(result) = stream.next().await {
+                match result {
+                    Ok(meta) => plan.blobs.push(PlannedBlob {
+                        location: meta.location,
+                        version_id: None,
+                        size: meta.size,
+                        e_tag: meta.e_tag,
+                    }),
+                    Err(e) => plan.errors.push(e.to_string()),
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Handle a key press in the large operation warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the confirmed operation fails.
+    pub async fn handle_large_operation_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> color_eyre::Result<()> {
+        match key_event.code {
+            KeyCode::Char('y' | 'Y') => {
+                let Modal::LargeOperationWarning { operation, plan } =
+                    std::mem::replace(&mut self.modal, Modal::None)
+                else {
+                    return Ok(());
+                };
+                match operation {
+                    PendingOperation::Download {
+                        folder_name,
+                        destination,
+                    } => {
+                        if let Err(e) = self.start_folder_download(&folder_name, &destination, plan)
+                        {
+                            self.error_message = Some(format!("Download failed: {e}"));
+                        }
+                    }
+                    PendingOperation::Delete { target_path } => {
+                        if let Err(e) = self.start_delete(&target_path, Some(plan)) {
+                            self.error_message = Some(format!("Delete failed: {e}"));
+                        }
+                    }
+                    PendingOperation::DownloadMarked { destination } => {
+                        if let Err(e) = self.start_marked_download(&destination, plan) {
+                            self.error_message = Some(format!("Download failed: {e}"));
+                        }
+                    }
+                    PendingOperation::DeleteMarked => {
+                        self.start_delete_marked(plan);
+                    }
+                    PendingOperation::Move {
+                        source,
+                        destination,
+                        moves,
+                        other_tab,
+                    } => {
+                        self.start_move(source, destination, moves, other_tab);
+                    }
+                    PendingOperation::Sync {
+                        local_dir,
+                        prefix,
+                        plan,
+                    } => {
+                        if let Err(e) = self.run_sync(&local_dir, &prefix, &plan).await {
+                            self.error_message = Some(format!("Sync failed: {e}"));
+                        }
+                    }
+                }
+            }
+            KeyCode::Char('n' | 'N') | KeyCode::Esc => {
+                self.close_modal();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Handle Enter key when download picker is shown.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file dialog or download fails.
+    pub async fn confirm_download(&mut self) -> color_eyre::Result<()> {
+        let Modal::DownloadPicker {
+            destination,
+            menu,
+            use_default_dir,
+            ..
+        } = &mut self.modal
+        else {
+            return Ok(());
+        };
+
+        let default_dir = self.config.download_dir().filter(|_| *use_default_dir);
+        // Otherwise reuse a recent destination without opening the file dialog
+        let chosen = default_dir.or_else(|| {
+            menu.items
+                .get(menu.selected)
+                .and_then(|item| item.value.clone())
+        });
+        if let Some(dir) = chosen {
+            *destination = Some(dir);
+            self.start_download().await?;
+        } else {
+            // Use the file dialog to pick a destination folder
+            let file_dialog = rfd::FileDialog::new();
+
+            // Run the file dialog in a spawn_blocking since it's blocking
+            let path_result = tokio::task::spawn_blocking(move || file_dialog.pick_folder()).await;
+
+            match path_result {
+                Ok(Some(path)) => {
+                    if let Modal::DownloadPicker { destination, .. } = &mut self.modal {
+                        *destination = Some(path);
+                    }
+                    self.start_download().await?;
+                }
+                Ok(None) => {
+                    // User cancelled the dialog
+                    self.close_modal();
+                }
+                Err(e) => {
+                    self.close_modal();
+                    self.error_message = Some(format!("Failed to open file dialog: {e}"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Ask for a local file and upload it into the current folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file dialog, reading the file, or the upload fails.
+    pub async fn pick_and_upload_file(&mut self) -> color_eyre::Result<()> {
+        if self.refuse_if_read_only() {
+            return Ok(());
+        }
+        if self.browsing().is_none() {
+            return Ok(());
+        }
+
+        let file_dialog = rfd::FileDialog::new();
+
+        // Run the file dialog in a spawn_blocking since it's blocking
+        match tokio::task::spawn_blocking(move || file_dialog.pick_file()).await {
+            Ok(Some(path)) => self.start_upload(&path),
+            // User cancelled the dialog
+            Ok(None) => Ok(()),
+            Err(e) => {
+                self.error_message = Some(format!("Failed to open file dialog: {e}"));
+                Ok(())
+            }
+        }
+    }
+
+    /// Upload the local file whose path is on the clipboard into the current folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clipboard cannot be read or the upload fails.
+    pub async fn upload_from_clipboard(&mut self) -> color_eyre::Result<()> {
+        if self.refuse_if_read_only() {
+            return Ok(());
+        }
+        if self.browsing().is_none() {
+            return Ok(());
+        }
+
+        let text = Clipboard::new()?.get_text()?;
+        let Some(path) = clipboard_path(&text) else {
+            self.error_message = Some("Clipboard does not contain a file path".to_string());
+            return Ok(());
+        };
+        if !path.is_file() {
+            self.error_message = Some(format!("Not a file: {}", path.display()));
+            return Ok(());
+        }
+        self.start_upload(&path)
+    }
+
+    /// Ask for a local directory and preview syncing it with the current folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either side cannot be listed.
+    pub async fn pick_sync_directory(&mut self) -> color_eyre::Result<()> {
+        if self.refuse_if_read_only() {
+            return Ok(());
+        }
+        if self.browsing().is_none() {
+            return Ok(());
+        }
+
+        let file_dialog = rfd::FileDialog::new();
+        match tokio::task::spawn_blocking(move || file_dialog.pick_folder()).await {
+            Ok(Some(path)) => self.open_sync_preview(path).await,
+            // User cancelled the dialog
+            Ok(None) => Ok(()),
+            Err(e) => {
+                self.error_message = Some(format!("Failed to open folder dialog: {e}"));
+                Ok(())
+            }
+        }
+    }
+
+    /// Compare `local_dir` with the current folder and show what a sync would do.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either side cannot be listed.
+    pub async fn open_sync_preview(&mut self, local_dir: PathBuf) -> color_eyre::Result<()> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let prefix = browsing.current_path.clone();
+        let local = sync::scan_local(&local_dir)?;
+        let remote = sync::list_remote(browsing.object_store.as_ref(), &prefix).await?;
+
+        let mode = SyncMode::default();
+        let plan = sync::plan_sync(&local, &remote, mode);
+        self.modal = Modal::SyncPreview {
+            local_dir,
+            prefix,
+            mode,
+            local,
+            remote,
+            plan,
+            scroll: 0,
+        };
+        Ok(())
+    }
+
+    /// Handle key events in the sync preview.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if refreshing the listing after the sync fails.
+    pub async fn handle_sync_preview_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> color_eyre::Result<()> {
+        let Modal::SyncPreview {
+            mode,
+            local,
+            remote,
+            plan,
+            scroll,
+            ..
+        } = &mut self.modal
+        else {
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('n' | 'q') => self.close_modal(),
+            KeyCode::Char('m') | KeyCode::Tab => {
+                *mode = mode.next();
+                *plan = sync::plan_sync(local, remote, *mode);
+                *scroll = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if *scroll + 1 < plan.steps.len() => {
+                *scroll += 1;
+            }
+            KeyCode::Enter | KeyCode::Char('y') => {
+                let Modal::SyncPreview {
+                    local_dir,
+                    prefix,
+                    plan,
+                    ..
+                } = std::mem::replace(&mut self.modal, Modal::None)
+                else {
+                    return Ok(());
+                };
+                let deletes =
+                    plan.count(SyncAction::DeleteRemote) + plan.count(SyncAction::DeleteLocal);
+                if deletes > 0
+                    || self
+                        .config
+                        .limits
+                        .exceeded_by(plan.steps.len(), plan.total_bytes())
+                {
+                    let folder_plan = FolderPlan {
+                        blobs: plan
+                            .steps
+                            .iter()
+                            .map(|step| PlannedBlob {
+                                location: ObjectPath::from(format!(
+                                    "{prefix}{}",
+                                    step.relative_path
+                                )),
+                                version_id: None,
+                                size: step.size,
+                                e_tag: step.e_tag.clone(),
+                            })
+                            .collect(),
+                        errors: Vec::new(),
+                    };
+                    self.modal = Modal::LargeOperationWarning {
+                        operation: PendingOperation::Sync {
+                            local_dir,
+                            prefix,
+                            plan,
+                        },
+                        plan: folder_plan,
+                    };
+                    return Ok(());
+                }
+                self.run_sync(&local_dir, &prefix, &plan).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Apply a sync plan and report the outcome.
+    async fn run_sync(
+        &mut self,
+        local_dir: &Path,
+        prefix: &str,
+        plan: &SyncPlan,
+    ) -> color_eyre::Result<()> {
+        if plan.steps.is_empty() {
+            self.success_message = Some("Already in sync".to_string());
+            return Ok(());
+        }
+        let Some(browsing) = self.browsing() else {
+            return Ok(());
+        };
+        let object_store = browsing.object_store.clone();
+
+        let report = sync::apply(object_store.as_ref(), prefix, local_dir, plan).await;
+        self.audit(
+            AuditAction::Sync,
+            format!(
+                "{} ⇄ {}",
+                local_dir.display(),
+                self.audit_target(prefix.trim_end_matches('/'))
+            ),
+            if report.errors.is_empty() {
+                Outcome::Succeeded
+            } else {
+                Outcome::Failed(report.errors.join("; "))
+            },
+        );
+        self.refresh_files().await?;
+        if report.errors.is_empty() {
+            self.success_message = Some(format!(
+                "Synced {} with {}: {} uploaded, {} downloaded, {} deleted",
+                local_dir.display(),
+                if prefix.is_empty() { "/" } else { prefix },
+                plan.count(SyncAction::Upload),
+                plan.count(SyncAction::Download),
+                plan.count(SyncAction::DeleteRemote) + plan.count(SyncAction::DeleteLocal),
+            ));
+        } else {
+            self.error_message = Some(format!(
+                "Sync finished with {} error(s): {}",
+                report.errors.len(),
+                report.errors.join("; ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Start uploading a local file into the current folder in the background, setting its
+    /// content type from the extension.
+    fn start_upload(&mut self, local_path: &Path) -> color_eyre::Result<()> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let object_store = browsing.object_store.clone();
+
+        let file_name = local_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| color_eyre::eyre::eyre!("Invalid file name"))?
+            .to_string();
+        let blob_path = Self::join_blob_path(&browsing.current_path, &file_name);
+
+        let total_bytes = fs::metadata(local_path)?.len();
+        let progress = UploadProgress {
+            current_file: file_name,
+            files_completed: 0,
+            total_files: 1,
+            bytes_uploaded: 0,
+            total_bytes: Some(total_bytes),
+            error_message: None,
+        };
+        self.async_op = AsyncOp::Uploading(progress.clone());
+        let progress = Arc::new(Mutex::new(progress));
+
+        let handle = {
+            let progress = progress.clone();
+            let location = ObjectPath::from(blob_path.as_str());
+            let local_path = local_path.to_path_buf();
+            tokio::spawn(async move {
+                upload_file_with_progress(object_store.as_ref(), &location, &local_path, |bytes| {
+                    lock_progress(&progress).bytes_uploaded += bytes;
+                })
+                .await?;
+                lock_progress(&progress).files_completed = 1;
+                Ok(())
+            })
+        };
+        self.upload_task = Some(UploadTask {
+            local_path: local_path.to_path_buf(),
+            blob_path,
+            progress,
+            handle,
+        });
+        Ok(())
+    }
+
+    /// Show how far the background upload has got.
+    fn update_upload_progress(&mut self) {
+        if let (Some(task), AsyncOp::Uploading(progress)) = (&self.upload_task, &mut self.async_op)
+        {
+            progress.clone_from(&lock_progress(&task.progress));
+        }
+    }
+
+    /// Once the background upload is done, record it and refresh the listing.
+    async fn finish_upload(&mut self) {
+        if !self
+            .upload_task
+            .as_ref()
+            .is_some_and(|task| task.handle.is_finished())
+        {
+            return;
+        }
+        let Some(task) = self.upload_task.take() else {
+            return;
+        };
+        let result = match task.handle.await {
+            Ok(result) => result.map_err(color_eyre::Report::from),
+            Err(e) => Err(color_eyre::eyre::eyre!("Upload stopped: {e}")),
+        };
+        self.audit(
+            AuditAction::Upload,
+            format!(
+                "{} → {}",
+                task.local_path.display(),
+                self.audit_target(&task.blob_path)
+            ),
+            Outcome::of(&result),
+        );
+        self.async_op = AsyncOp::None;
+
+        let file_name = lock_progress(&task.progress).current_file.clone();
+        match result {
+            Ok(()) => {
+                if let Err(e) = self.refresh_files().await {
+                    self.error_message = Some(format!("Refresh failed after upload: {e}"));
+                }
+                self.success_message = Some(format!("Uploaded {file_name} to {}", task.blob_path));
+            }
+            Err(e) => {
+                self.error_message = Some(format!(
+                    "Failed to upload {file_name}: {}",
+                    describe_error(&e)
+                ));
+            }
+        }
+    }
+
+    /// Prompt for the name of a folder to create in the current folder.
+    pub fn open_new_folder_dialog(&mut self) {
+        if self.browsing().is_none() || self.refuse_if_read_only() {
+            return;
+        }
+        self.modal = Modal::NewFolder {
+            input: String::new(),
+        };
+    }
+
+    /// Handle key events when in the new folder prompt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if refreshing the listing fails.
+    pub async fn handle_new_folder_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> color_eyre::Result<()> {
+        let Modal::NewFolder { input } = &mut self.modal else {
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.close_modal();
+            }
+            KeyCode::Enter => {
+                let name = input.trim().trim_matches('/').to_string();
+                if name.is_empty() {
+                    self.error_message = Some("Folder name cannot be empty".to_string());
+                } else if name.split('/').any(|part| part.is_empty() || part == "..") {
+                    self.error_message = Some(format!("Invalid folder name \"{name}\""));
+                } else {
+                    self.close_modal();
+                    self.create_folder(&name).await?;
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Create a virtual folder by writing a [`FOLDER_PLACEHOLDER`] blob into it, then select it.
+    ///
+    /// `name` may contain `/` to create nested folders at once.
+    async fn create_folder(&mut self, name: &str) -> color_eyre::Result<()> {
+        let browsing = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?;
+        let object_store = browsing.object_store.clone();
+        let folder_path = Self::join_folder_path(&browsing.current_path, name);
+        let placeholder = ObjectPath::from(format!("{folder_path}{FOLDER_PLACEHOLDER}"));
+
+        let options = PutOptions {
+            mode: PutMode::Create,
+            ..PutOptions::default()
+        };
+        let created = object_store
+            .put_opts(&placeholder, Vec::new().into(), options)
+            .await;
+        self.audit(
+            AuditAction::CreateFolder,
+            self.audit_target(folder_path.trim_end_matches('/')),
+            Outcome::of(&created),
+        );
+        match created {
+            Ok(_) => {}
+            Err(object_store::Error::AlreadyExists { .. }) => {
+                self.error_message = Some(format!(
+                    "Folder {} already exists",
+                    folder_path.trim_end_matches('/')
+                ));
+                return Ok(());
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to create folder: {e}"));
+                return Ok(());
+            }
+        }
+
+        self.refresh_files().await?;
+        // Select the new folder, or the top-level folder it was created in
+        let top = name.split('/').next().unwrap_or(name);
+        if let Some(state) = self.browsing_mut()
+            && let Some(index) = state
+                .file_items
+                .iter()
+                .position(|i| i.kind == EntryKind::Folder && i.actual_name == top)
+        {
+            state.selected_index = index;
+        }
+        self.success_message = Some(format!(
+            "Created folder {} • upload, move or paste into it",
+            folder_path.trim_end_matches('/')
+        ));
+        Ok(())
+    }
+
+    // ========================================
+    // Preview Panel Methods
+    // ========================================
+
+    /// Load preview for the currently selected file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fetching or parsing the file fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn load_preview(&mut self) -> color_eyre::Result<()> {
+        let (selected_item, current_path) = match self.browsing() {
+            Some(state) => {
+                let Some(item) = self.selected_file_item() else {
+                    return Ok(());
+                };
+                (item.clone(), state.current_path.clone())
+            }
+            None => return Ok(()),
+        };
+
+        if selected_item.actual_name.is_empty() {
+            return Ok(());
+        }
+
+        // Check if it's a folder
+        if selected_item.kind == EntryKind::Folder {
+            self.preview_error = Some("Cannot preview folders".to_string());
+            self.ui.show_preview = true;
+            return Ok(());
+        }
+
+        let version = selected_item.version_id;
+        let name = selected_item.actual_name;
+        let size = selected_item.size;
+
+        // Files of unknown types are shown as text, or in hex when they are binary. Compressed
+        // files are previewed as the file inside, e.g. `events.json` in `events.json.gz`
+        let compression = Compression::of(&name);
+        let file_type =
+            PreviewFileType::from_extension(compression.map_or(name.as_str(), |(_, inner)| inner));
+        let compression = compression.map(|(compression, _)| compression);
+        self.preview_file_type = Some(file_type.clone());
+        self.ui.is_loading_preview = true;
+        self.ui.show_preview = true;
+        self.preview_error = None;
+        self.preview_data = None;
+        self.preview_scroll = (0, 0);
+        self.preview_selected_row = 0;
+        self.preview_pager = None;
+        self.parquet_preview_mode = None;
+        self.parquet_table_data = None;
+        self.parquet_schema_data = None;
+
+        // Fetch file content (up to MAX_PREVIEW_BYTES)
+        let object_store = self
+            .browsing()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?
+            .object_store
+            .clone();
+
+        let blob_path = Self::join_blob_path(&current_path, &name);
+
+        let object_path = ObjectPath::from(blob_path.as_str());
+
+        if let Some(kind) = ArchiveKind::of(&name) {
+            let listing =
+                fetch::archive_listing(&object_store, &object_path, kind, version.as_deref()).await;
+            self.ui.is_loading_preview = false;
+            match listing {
+                Ok(data) => self.preview_data = Some(data),
+                Err(e) => self.preview_error = Some(e.to_string()),
+            }
+            return Ok(());
+        }
+
+        // For Parquet files, support both table and metadata views. A compressed one is only
+        // readable whole, so it gets the hex dump.
+        if file_type == PreviewFileType::Parquet && compression.is_none() {
+            let footer_result =
+                fetch::parquet_footer(&object_store, &object_path, version.as_deref()).await;
+            let (footer, meta) = match footer_result {
+                Ok(found) => found,
+                Err(e) => {
+                    self.ui.is_loading_preview = false;
+                    self.preview_error = Some(e.to_string());
+                    return Ok(());
+                }
+            };
+            let file_size = meta.size;
+            let fetch = async |range| {
+                self.fetch_bytes_cached(
+                    &object_store,
+                    &object_path,
+                    Some(range),
+                    version.as_deref(),
+                    meta.e_tag.as_deref(),
+                )
+                .await
+                .map_err(|e| format!("Failed to fetch file: {e}"))
+            };
+            let table_result = if file_size <= MAX_PARQUET_TABLE_PREVIEW_BYTES as u64 {
+                // A file shorter than the footer read is already all here
+                let bytes = if footer.len() as u64 == file_size {
+                    Ok(footer.clone())
+                } else {
+                    fetch(0..file_size).await
+                };
+                bytes.and_then(|bytes| parse_parquet_table(&bytes))
+            } else {
+                // Large parquet files: read only the row groups holding the first rows
+                match parquet_rows_range(&footer) {
+                    Ok((row_groups, range)) => fetch(range.clone()).await.and_then(|rows| {
+                        parse_parquet_rows(file_size, footer.clone(), range.start, rows, row_groups)
+                    }),
+                    Err(e) => Err(e),
+                }
+            };
+            let schema_result = parse_parquet_schema(&footer, Some(file_size));
+            self.ui.is_loading_preview = false;
+
+            let mut table_error: Option<String> = None;
+            match table_result {
+                Ok(PreviewData::Table(table)) => {
+                    self.parquet_table_data = Some(table.clone());
+                    self.preview_data = Some(PreviewData::Table(table));
+                    self.parquet_preview_mode = Some(ParquetPreviewMode::Table);
+                }
+                Ok(_) => {
+                    table_error = Some("Unexpected Parquet table preview format".to_string());
+                }
+                Err(e) => {
+                    table_error = Some(e);
+                }
+            }
+
+            match schema_result {
+                Ok(PreviewData::ParquetSchema(mut schema)) => {
+                    if self.preview_data.is_none() && table_error.is_some() {
+                        schema.note = table_error.clone();
+                    }
+                    self.parquet_schema_data = Some(schema);
+                }
+                Ok(_) => {
+                    self.preview_error =
+                        Some("Unexpected Parquet metadata preview format".to_string());
+                }
+                Err(e) => {
+                    self.preview_error = Some(e);
+                }
+            }
+
+            if self.preview_data.is_none() {
+                if let Some(schema) = &self.parquet_schema_data {
+                    self.preview_data = Some(PreviewData::ParquetSchema(schema.clone()));
+                    self.parquet_preview_mode = Some(ParquetPreviewMode::Metadata);
+                    self.preview_error = None;
+                } else if self.preview_error.is_none() {
+                    self.preview_error = table_error;
+                }
+            }
+            return Ok(());
+        }
+
+        // For other file types, fetch from the beginning
+        let get_result = self
+            .fetch_bytes_cached(
+                &object_store,
+                &object_path,
+                Some(0..(Self::preview_fetch_len(compression) as u64)),
+                version.as_deref(),
+                None,
+            )
+            .await;
+
+        self.ui.is_loading_preview = false;
+
+        match get_result {
+            Ok(bytes) => {
+                // Parse the data
+                match parse_compressed_preview(&bytes, compression, &file_type) {
+                    // The rest of a long text blob is fetched as it is scrolled through
+                    Ok(PreviewData::Text(text))
+                        if compression.is_none()
+                            && size.is_some_and(|size| size > bytes.len() as u64)
+                            && PreviewPager::can_page(&bytes) =>
+                    {
+                        let (pager, text) = PreviewPager::new(
+                            object_store.clone(),
+                            object_path.clone(),
+                            version.clone(),
+                            size.unwrap_or_default(),
+                            &bytes,
+                            &text.extension,
+                        );
+                        self.preview_data = Some(PreviewData::Text(text));
+                        self.preview_pager = Some(pager);
+                    }
+                    Ok(data) => {
+                        self.preview_data = Some(data);
+                    }
+                    Err(e) => {
+                        self.preview_error = Some(e);
+                    }
+                }
+            }
+            Err(e) => {
+                self.preview_error = Some(format!("Failed to fetch file: {e}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A preview for the preview pane: the start of the blob, or for Parquet the table when
+    /// it is small enough and the schema otherwise. It does not go through the cache, as most
+    /// blobs passed over while browsing are never looked at again.
+    async fn fetch_pane_preview(
+        object_store: Arc<dyn ObjectStore>,
+        path: String,
+        version: Option<String>,
+    ) -> Result<PreviewData, BlobrsError> {
+        let compression = Compression::of(&path);
+        let file_type =
+            PreviewFileType::from_extension(compression.map_or(path.as_str(), |(_, inner)| inner));
+        let compression = compression.map(|(compression, _)| compression);
+        let object_path = ObjectPath::from(path.as_str());
+        let version = version.as_deref();
+        let fetch = |range| fetch::bytes(&object_store, &object_path, Some(range), version);
+
+        if let Some(kind) = ArchiveKind::of(&path) {
+            return fetch::archive_listing(&object_store, &object_path, kind, version).await;
+        }
+        if file_type == PreviewFileType::Parquet && compression.is_none() {
+            let (footer, meta) =
+                fetch::parquet_footer(&object_store, &object_path, version).await?;
+            let size = meta.size;
+            if size <= MAX_PARQUET_TABLE_PREVIEW_BYTES as u64 {
+                let bytes = if footer.len() as u64 == size {
+                    footer
+                } else {
+                    fetch(0..size).await?
+                };
+                return parse_parquet_table(&bytes)
+                    .or_else(|_| parse_parquet_schema(&bytes, Some(size)))
+                    .map_err(BlobrsError::Format);
+            }
+            // Large parquet files: the row groups holding the first rows, or else the schema
+            let rows = match parquet_rows_range(&footer) {
+                Ok((row_groups, range)) => match fetch(range.clone()).await {
+                    Ok(rows) => {
+                        parse_parquet_rows(size, footer.clone(), range.start, rows, row_groups)
+                    }
+                    Err(e) => Err(e.to_string()),
+                },
+                Err(e) => Err(e),
+            };
+            return rows
+                .or_else(|_| parse_parquet_schema(&footer, Some(size)))
+                .map_err(BlobrsError::Format);
+        }
+
+        let bytes = fetch(0..Self::preview_fetch_len(compression) as u64).await?;
+        parse_compressed_preview(&bytes, compression, &file_type).map_err(BlobrsError::Format)
+    }
+
+    /// Bytes to fetch from the start of a blob to preview it; more for a compressed one.
+    const fn preview_fetch_len(compression: Option<Compression>) -> usize {
+        if compression.is_some() {
+            MAX_COMPRESSED_PREVIEW_BYTES
+        } else {
+            MAX_PREVIEW_BYTES
+        }
+    }
+
+    /// Close the preview panel.
+    pub fn close_preview(&mut self) {
+        self.ui.show_preview = false;
+        self.preview_data = None;
+        self.preview_file_type = None;
+        self.preview_error = None;
+        self.preview_scroll = (0, 0);
+        self.preview_selected_row = 0;
+        self.preview_search = None;
+        self.preview_pager = None;
+        self.ui.is_loading_preview = false;
+        self.parquet_preview_mode = None;
+        self.parquet_table_data = None;
+        self.parquet_schema_data = None;
+    }
+
+    /// Toggle between parquet table and metadata preview modes.
+    pub fn toggle_parquet_preview_mode(&mut self) {
+        if self.preview_file_type != Some(PreviewFileType::Parquet) {
+            return;
+        }
+
+        match self.parquet_preview_mode {
+            Some(ParquetPreviewMode::Table) => {
+                if let Some(schema) = &self.parquet_schema_data {
+                    self.preview_data = Some(PreviewData::ParquetSchema(schema.clone()));
+                    self.parquet_preview_mode = Some(ParquetPreviewMode::Metadata);
+                    self.preview_scroll = (0, 0);
+                    self.preview_selected_row = 0;
+                }
+            }
+            Some(ParquetPreviewMode::Metadata) => {
+                if let Some(table) = &self.parquet_table_data {
+                    self.preview_data = Some(PreviewData::Table(table.clone()));
+                    self.parquet_preview_mode = Some(ParquetPreviewMode::Table);
+                    self.preview_scroll = (0, 0);
+                    self.preview_selected_row = 0;
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Scroll preview up (decrease row offset).
+    pub fn preview_scroll_up(&mut self) {
+        if self.preview_selected_row > 0 {
+            self.preview_selected_row = self.preview_selected_row.saturating_sub(1);
+        }
+        // Also adjust scroll offset if needed
+        if self.preview_selected_row < self.preview_scroll.0 {
+            self.preview_scroll.0 = self.preview_selected_row;
+        }
+    }
+
+    /// Scroll preview down (increase row offset).
+    pub fn preview_scroll_down(&mut self) {
+        if self.preview_selected_row + 1 < self.preview_row_count() {
+            self.preview_selected_row += 1;
+        }
+    }
+
+    /// Rows the preview scrolls through.
+    fn preview_row_count(&self) -> usize {
+        match &self.preview_data {
+            Some(PreviewData::Table(table)) => table.rows.len(),
+            Some(PreviewData::Json(json)) => json.content.lines().count(),
+            Some(PreviewData::Text(text)) => text.content.lines().count(),
+            Some(PreviewData::Hex(hex)) => hex.content.lines().count(),
+            // Metadata lines + schema fields
+            Some(PreviewData::ParquetSchema(schema)) => 7 + schema.fields.len(),
+            None => 0,
+        }
+    }
+
+    /// Handle the keys that page through or search the preview, returning whether `key_event`
+    /// was one of them.
+    fn handle_preview_key_event(&mut self, key_event: KeyEvent) -> bool {
+        if self
+            .preview_search
+            .as_ref()
+            .is_some_and(|search| search.typing)
+        {
+            self.handle_preview_search_key_event(key_event);
+            return true;
+        }
+        if let Some(motion) = Motion::of_key(key_event) {
+            let page = self.preview_viewport.borrow().rows;
+            self.preview_selected_row = motion.target(
+                None,
+                self.preview_selected_row,
+                self.preview_row_count(),
+                page,
+            );
+            return true;
+        }
+        match key_event.code {
+            KeyCode::Char('/') => {
+                self.preview_search = Some(PreviewSearch::new(self.preview_selected_row));
+            }
+            KeyCode::Char('n') if self.preview_search.is_some() => self.next_preview_match(false),
+            KeyCode::Char('N') if self.preview_search.is_some() => self.next_preview_match(true),
+            KeyCode::Char('c') => self.copy_preview(PreviewCopy::Cell),
+            KeyCode::Char('C') => self.copy_preview(PreviewCopy::Row),
+            KeyCode::Char('Y') => self.copy_preview(PreviewCopy::All),
+            // Esc clears the highlights before it closes the preview
+            KeyCode::Esc if self.preview_search.is_some() => self.preview_search = None,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Copy the cell or line the preview is on, its row, or everything loaded to the clipboard.
+    fn copy_preview(&mut self, what: PreviewCopy) {
+        let Some(text) = self.preview_data.as_ref().and_then(|data| {
+            data.copy_text(what, self.preview_selected_row, self.preview_scroll.1)
+        }) else {
+            self.error_message = Some("Nothing to copy here".to_string());
+            return;
+        };
+        match Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text.clone())) {
+            Ok(()) => {
+                let copied = match (what, &self.preview_data) {
+                    (PreviewCopy::Cell, Some(PreviewData::Table(_))) => "the cell".to_string(),
+                    (PreviewCopy::Row, Some(PreviewData::Table(_))) => "the row".to_string(),
+                    (PreviewCopy::Cell | PreviewCopy::Row, _) => "the line".to_string(),
+                    (PreviewCopy::All, _) => format!("{} lines", text.lines().count()),
+                };
+                self.success_message = Some(format!("Copied {copied} to clipboard"));
+                self.error_message = None;
+            }
+            Err(e) => self.error_message = Some(format!("Failed to copy to clipboard: {e}")),
+        }
+    }
+
+    /// Handle a key while a search of the preview is typed, moving to the first match as the
+    /// query changes.
+    fn handle_preview_search_key_event(&mut self, key_event: KeyEvent) {
+        let Some(search) = &mut self.preview_search else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Esc => {
+                self.preview_selected_row = search.origin;
+                self.preview_search = None;
+                return;
+            }
+            KeyCode::Enter => {
+                search.typing = false;
+                if search.query.is_empty() {
+                    self.preview_search = None;
+                } else if self
+                    .preview_data
+                    .as_ref()
+                    .is_none_or(|data| search.rows(data).is_empty())
+                {
+                    self.error_message = Some(format!("No match for `{}`", search.query));
+                }
+                return;
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+            }
+            KeyCode::Char(c) => search.query.push(c),
+            _ => return,
+        }
+        let origin = search.origin;
+        match self
+            .preview_data
+            .as_ref()
+            .and_then(|data| search.next(data, origin))
+        {
+            Some(row) => self.show_preview_match(row),
+            None => self.preview_selected_row = origin,
+        }
+    }
+
+    /// Move to the next row of the preview with a match, or the previous one if `backwards`.
+    fn next_preview_match(&mut self, backwards: bool) {
+        let (Some(search), Some(data)) = (&self.preview_search, &self.preview_data) else {
+            return;
+        };
+        let row = if backwards {
+            search.previous(data, self.preview_selected_row)
+        } else {
+            search.next(data, self.preview_selected_row + 1)
+        };
+        match row {
+            Some(row) => self.show_preview_match(row),
+            None => self.error_message = Some(format!("No match for `{}`", search.query)),
+        }
+    }
+
+    /// Move the preview to a `row` with a match, scrolling a table sideways if the matched
+    /// cell is off screen.
+    fn show_preview_match(&mut self, row: usize) {
+        self.preview_selected_row = row;
+        let column = self
+            .preview_search
+            .as_ref()
+            .zip(self.preview_data.as_ref())
+            .and_then(|(search, data)| search.column(data, row));
+        if let Some(column) = column
+            && !self.preview_viewport.borrow().columns.contains(&column)
+        {
+            self.preview_scroll.1 = column;
+        }
+    }
+
+    /// Scroll preview left (decrease column offset).
+    pub fn preview_scroll_left(&mut self) {
+        self.preview_scroll.1 = self.preview_scroll.1.saturating_sub(1);
+    }
+
+    /// Scroll preview right (increase column offset).
+    pub fn preview_scroll_right(&mut self) {
+        if let Some(PreviewData::Table(table)) = &self.preview_data {
+            let num_cols = table
+                .headers
+                .len()
+                .max(table.rows.first().map_or(0, Vec::len));
+            if num_cols > 0 {
+                self.preview_scroll.1 = (self.preview_scroll.1 + 1).min(num_cols.saturating_sub(1));
+            }
+        } else {
+            self.preview_scroll.1 += 1;
+        }
+    }
+}
+
+/// Put recently opened containers first, in recency order, then the rest by name or by most
+/// recently modified.
+fn order_containers(
+    containers: Vec<ContainerInfo>,
+    recent: &[&str],
+    by_modified: bool,
+) -> Vec<ContainerInfo> {
+    let (mut first, mut rest): (Vec<_>, Vec<_>) = containers
+        .into_iter()
+        .partition(|c| recent.contains(&c.name.as_str()));
+    first.sort_by_key(|c| recent.iter().position(|r| *r == c.name));
+    for container in &mut first {
+        container.recent = true;
+    }
+    if by_modified {
+        rest.sort_by_key(|c| std::cmp::Reverse(c.last_modified));
+    } else {
+        rest.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    first.extend(rest);
+    first
+}
+
+/// An error as shown in the status line. Timeouts are called out so a stalled network is not
+/// mistaken for rejected credentials or a missing blob.
+fn describe_error(e: &color_eyre::Report) -> String {
+    if !e.is::<BlobrsError>() && e.chain().any(blobrs::error::is_timeout) {
+        return BlobrsError::Timeout(e.to_string()).to_string();
+    }
+    e.to_string()
+}
+
+/// Show an RFC 2822 time from a response header as UTC, or unchanged if it does not parse.
+fn format_service_time(time: String) -> String {
+    chrono::DateTime::parse_from_rfc2822(&time).map_or(time, |time| {
+        time.with_timezone(&Utc)
+            .format("%Y-%m-%d %H:%M:%S UTC")
+            .to_string()
+    })
+}
+
+/// Extract a local path from clipboard text.
+///
+/// Uses the first non-empty line, stripping surrounding quotes and a `file://` prefix as left
+/// by file managers and shells.
+fn clipboard_path(text: &str) -> Option<PathBuf> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let line = line
+        .strip_prefix('"')
+        .and_then(|l| l.strip_suffix('"'))
+        .or_else(|| line.strip_prefix('\'').and_then(|l| l.strip_suffix('\'')))
+        .unwrap_or(line);
+    // File managers copy URLs with the path percent-encoded; plain paths are taken as they are
+    let line = match line.strip_prefix("file://") {
+        Some(path) => urlencoding::decode(path).ok()?,
+        None => line.into(),
+    };
+    if line.is_empty() {
+        return None;
+    }
+    Some(PathBuf::from(line.as_ref()))
+}
+
+/// Parse a user-entered time-travel timestamp, interpreted as UTC.
+///
+/// Accepts RFC 3339, `YYYY-MM-DD HH:MM[:SS]` (with a space or `T`), or a bare date meaning the
+/// start of that day.
+fn parse_as_of_timestamp(input: &str) -> Option<chrono::DateTime<Utc>> {
+    let input = input.trim();
+    if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(input) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(ts) = NaiveDateTime::parse_from_str(input, format) {
+            return Some(ts.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|ts| ts.and_utc())
+}
+
+/// Local file name for a downloaded snapshot: the snapshot time goes before the extension,
+/// with `:` replaced so the name is valid everywhere.
+fn snapshot_file_name(name: &str, snapshot: &str) -> String {
+    let stamp = snapshot.replace(':', "-");
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{stem}.{stamp}.{extension}"),
+        _ => format!("{name}.{stamp}"),
+    }
+}
+
+fn lock_progress<T>(progress: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A poisoned lock only means the task panicked mid-update; the progress is still usable
+    progress
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Where each planned blob under `source` goes when it is moved to `destination`.
+fn move_pairs(source: &str, destination: &str, plan: &FolderPlan) -> Vec<(ObjectPath, ObjectPath)> {
+    plan.blobs
+        .iter()
+        .map(|blob| {
+            let relative = blob
+                .location
+                .as_ref()
+                .strip_prefix(source)
+                .unwrap_or_default();
+            let to = ObjectPath::from(format!("{destination}{relative}"));
+            (blob.location.clone(), to)
+        })
+        .collect()
+}
+
+/// Copy every pair, several at a time, then delete the originals.
+///
+/// After the first failed copy no new copies are started, the copies made so far are deleted
+/// again and the originals are left untouched. Originals that cannot be deleted after a
+/// complete copy are reported, since they then exist in both places.
+async fn move_blobs(
+    object_store: Arc<dyn ObjectStore>,
+    client: Option<AzureRestClient>,
+    container: &str,
+    moves: Vec<(ObjectPath, ObjectPath)>,
+    progress: &Mutex<CloneProgress>,
+) -> MoveOutcome {
+    // Copy everything first, refusing to overwrite existing blobs
+    // Owned pairs and flag, so the stream can run on a spawned task
+    let failed = Arc::new(AtomicBool::new(false));
+    let mut copies = futures::stream::iter(moves.clone())
+        .map(|(from, to)| {
+            let object_store = object_store.clone();
+            let failed = failed.clone();
+            async move {
+                if failed.load(Ordering::Relaxed) {
+                    return (from, to, None);
+                }
+                let result = object_store.copy_if_not_exists(&from, &to).await;
+                if result.is_err() {
+                    failed.store(true, Ordering::Relaxed);
+                }
+                (from, to, Some(result))
+            }
+        })
+        .buffer_unordered(MOVE_CONCURRENCY);
+
+    let mut copied = Vec::new();
+    let mut copy_error = None;
+    while let Some((from, to, result)) = copies.next().await {
+        match result {
+            Some(Ok(())) => {
+                copied.push(to);
+                let mut progress = lock_progress(progress);
+                progress.current_file = from.to_string();
+                progress.files_completed += 1;
+            }
+            Some(Err(e)) => {
+                copy_error.get_or_insert_with(|| format!("{from} → {to}: {e}"));
+            }
+            // Skipped after another copy failed
+            None => {}
+        }
+    }
+    drop(copies);
+
+    if let Some(copy_error) = copy_error {
+        lock_progress(progress).current_file = "Rolling back copies".to_string();
+        let rolled_back = copied.len();
+        let leftovers = delete_reporting(&object_store, client, container, copied).await;
+        return MoveOutcome::Aborted(if leftovers.is_empty() {
+            format!(
+                "Move aborted, nothing was moved ({rolled_back} copies rolled back): {copy_error}"
+            )
+        } else {
+            format!(
+                "Move aborted: {copy_error}. Could not roll back {} copies, remove them manually: {}",
+                leftovers.len(),
+                leftovers.join(", ")
+            )
+        });
+    }
+
+    // Everything is copied; remove the originals
+    lock_progress(progress).current_file = format!("Removing {} originals", moves.len());
+    let originals = moves.into_iter().map(|(from, _)| from).collect();
+    let not_deleted = delete_reporting(&object_store, client, container, originals).await;
+    if not_deleted.is_empty() {
+        MoveOutcome::Moved
+    } else {
+        MoveOutcome::OriginalsLeft(format!(
+            "{} originals could not be deleted and now exist twice: {}",
+            not_deleted.len(),
+            not_deleted.join(", ")
+        ))
+    }
+}
+
+/// Delete `paths` in Blob Batch requests where possible, returning the ones that could not be
+/// deleted.
+async fn delete_reporting(
+    object_store: &Arc<dyn ObjectStore>,
+    client: Option<AzureRestClient>,
+    container: &str,
+    paths: Vec<ObjectPath>,
+) -> Vec<String> {
+    let mut failed = Vec::new();
+    delete_batched(object_store, client, container, paths, |path, error| {
+        if let Some(e) = error {
+            failed.push(match immutability_hint(&e) {
+                Some(hint) => format!("{path} ({hint})"),
+                None => path.to_string(),
+            });
+        }
+    })
+    .await;
+    failed
+}
+
+/// Delete a single blob.
+async fn delete_blob(
+    object_store: Arc<dyn ObjectStore>,
+    path: &str,
+    progress: &Mutex<DeleteProgress>,
+) -> color_eyre::Result<()> {
+    {
+        let mut progress = lock_progress(progress);
+        progress.current_file = path.to_string();
+        progress.total_files = 1;
+    }
+    object_store.delete(&ObjectPath::from(path)).await?;
+    lock_progress(progress).files_completed = 1;
+    Ok(())
+}
+
+/// Delete all blobs in a folder plan, batching them as [`delete_batched`] does. Single
+/// failures are reported on the progress rather than stopping the delete.
+async fn delete_folder(
+    object_store: Arc<dyn ObjectStore>,
+    client: Option<AzureRestClient>,
+    container: &str,
+    plan: FolderPlan,
+    progress: &Mutex<DeleteProgress>,
+) {
+    {
+        let mut progress = lock_progress(progress);
+        progress.total_files = plan.blobs.len() + plan.errors.len();
+        if let Some(e) = plan.errors.first() {
+            progress.error_message = Some(format!("Failed to list file: {e}"));
+        }
+    }
+    let paths = plan.blobs.into_iter().map(|blob| blob.location).collect();
+    delete_batched(
+        &object_store,
+        client,
+        container,
+        paths,
+        |location, error| {
+            lock_progress(progress).record(location, error);
+        },
+    )
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        App, AsyncOp, BrowsingState, ContainerInfo, DELETE_MARKED_CONFIRMATION, EntryKind,
+        MESSAGE_TICKS, Modal, Paging, ParquetPreviewMode, PendingOperation, Search, Session,
+        SortCriteria, SortOrder, UiToggles, describe_error,
+    };
+    use crate::event::EventHandler;
+    use crate::motion::PendingMotion;
+    use crate::preview_pane::PreviewPane;
+    use crate::preview_search::PreviewSearch;
+    use crate::state::PersistedState;
+    use crate::tabs::{Tab, Tabs};
+    use crate::terminal_icons::detect_terminal_icons;
+    use blobrs::audit::AuditLog;
+    use blobrs::azure::ContainerProperties;
+    use blobrs::config::Config;
+    use blobrs::diff::DiffKind;
+    use blobrs::error::BlobrsError;
+    use blobrs::filter::FilterMode;
+    use blobrs::fuzzy::fuzzy_match;
+    use blobrs::pick::PickOutput;
+    use blobrs::preview::{
+        ParquetSchemaPreview, PreviewData, PreviewFileType, TablePreview, TextPreview,
+    };
+    use blobrs::search::SearchScope;
+    use blobrs::secret::Secret;
+    use blobrs::sync::{SyncAction, SyncMode};
+    use blobrs::transfer::TransferStats;
+    use blobrs::usage::UsageScanner;
+    use chrono::{TimeZone, Utc};
+    use object_store::ObjectStore;
+    use object_store::memory::InMemory;
+    use proptest::prelude::*;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn arb_entry_kind() -> impl Strategy<Value = super::EntryKind> {
+        prop_oneof![Just(super::EntryKind::File), Just(super::EntryKind::Folder)]
+    }
+
+    fn arb_sort_criteria() -> impl Strategy<Value = SortCriteria> {
+        prop_oneof![
+            Just(SortCriteria::Name),
+            Just(SortCriteria::DateModified),
+            Just(SortCriteria::DateCreated),
+            Just(SortCriteria::Size),
+        ]
+    }
+
+    fn arb_timestamp() -> impl Strategy<Value = Option<chrono::DateTime<chrono::Utc>>> {
+        prop::option::of((-2_208_988_800_i64..4_102_444_800_i64).prop_map(|secs| {
+            Utc.timestamp_opt(secs, 0)
+                .single()
+                .expect("generated timestamp should be valid")
+        }))
+    }
+
+    fn arb_file_item() -> impl Strategy<Value = super::FileItem> {
+        (
+            "[A-Za-z0-9_./-]{1,16}",
+            arb_entry_kind(),
+            prop::option::of(0_u64..10_000),
+            arb_timestamp(),
+            arb_timestamp(),
+        )
+            .prop_map(|(actual_name, kind, size, last_modified, created)| {
+                let icon = match kind {
+                    super::EntryKind::File => "[FILE]",
+                    super::EntryKind::Folder => "[DIR]",
+                };
+                super::FileItem {
+                    display_name: format!("{icon} {actual_name}"),
+                    actual_name,
+                    kind,
+                    size,
+                    last_modified,
+                    created,
+                    version_id: None,
+                    deleted: false,
+                    used_size: None,
+                }
+            })
+    }
+
+    fn test_app() -> App {
+        App {
+            running: true,
+            events: EventHandler::new(),
+            session: Session::Selecting,
+            tabs: Tabs::default(),
+            storage_account: "test-account".to_string(),
+            access_key: Secret::new("test-key"),
+            containers: Vec::new(),
+            all_containers: Vec::new(),
+            selected_container_index: 0,
+            async_op: AsyncOp::None,
+            error_message: None,
+            success_message: None,
+            search: Search::Inactive,
+            icons: detect_terminal_icons(),
+            modal: Modal::None,
+            ui: UiToggles {
+                show_preview: false,
+                is_loading_preview: false,
+                show_deleted: false,
+                show_container_usage: false,
+                show_audit_log: false,
+                show_two_panes: false,
+                flatten: false,
+                preview_pane: false,
+                filter_mode: FilterMode::default(),
+            },
+            sort: SortOrder::default(),
+            container_sort: SortCriteria::Name,
+            preview_data: None,
+            preview_file_type: None,
+            preview_scroll: (0, 0),
+            preview_error: None,
+            preview_selected_row: 0,
+            parquet_preview_mode: None,
+            parquet_table_data: None,
+            parquet_schema_data: None,
+            state: PersistedState::default(),
+            config: Config::default(),
+            cache: None,
+            jobs: Vec::new(),
+            delete_task: None,
+            upload_task: None,
+            move_task: None,
+            transfer_stats: TransferStats::default(),
+            yanked: None,
+            leases: BTreeMap::new(),
+            usage: UsageScanner::default(),
+            audit: AuditLog::default(),
+            ticks: 0,
+            message_shown: None,
+            preview_search: None,
+            preview_viewport: RefCell::default(),
+            preview_pane: PreviewPane::default(),
+            watch: None,
+            preview_pager: None,
+            pending_motion: PendingMotion::default(),
+            list_layout: RefCell::default(),
+            last_click: None,
+            pending_edit: None,
+            pending_pipe: None,
+            last_download: None,
+            pick: None,
+            picked: Vec::new(),
+        }
+    }
+
+    /// Browsing the root of `test-container` in `store`, with nothing listed yet.
+    fn browsing_state(store: Arc<dyn ObjectStore>) -> BrowsingState {
+        BrowsingState {
+            object_store: store,
+            container_name: "test-container".to_string(),
+            current_path: String::new(),
+            files: Vec::new(),
+            file_items: Vec::new(),
+            selected_index: 0,
+            as_of: None,
+            paging: Paging::default(),
+            marked: BTreeMap::new(),
+        }
+    }
+
+    /// Tick until the background move is done, as the main loop would.
+    async fn finish_move(app: &mut App) {
+        for _ in 0..200 {
+            app.tick();
+            app.finish_move().await;
+            if app.move_task.is_none() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("the move did not finish");
+    }
+
+    /// Tick until the background upload is done, as the main loop would.
+    async fn finish_upload(app: &mut App) {
+        for _ in 0..200 {
+            app.tick();
+            app.finish_upload().await;
+            if app.upload_task.is_none() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("the upload did not finish");
+    }
+
+    /// Tick until the background delete is done, as the main loop would.
+    async fn finish_delete(app: &mut App) {
+        for _ in 0..200 {
+            app.tick();
+            app.finish_delete().await;
+            if app.delete_task.is_none() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("the delete did not finish");
+    }
+
+    #[test]
+    fn typing_after_a_quote_jumps_to_the_first_name_starting_with_it() {
+        let mut app = test_app();
+        app.containers = ["alpha", "Beta", "bravo", "charlie"]
+            .into_iter()
+            .map(|name| ContainerInfo {
+                name: name.to_string(),
+                last_modified: None,
+                recent: false,
+            })
+            .collect();
+        app.modal = Modal::Jump {
+            prefix: String::new(),
+        };
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        assert!(app.handle_jump_key_event(key('b')).is_none());
+        assert_eq!(app.selected_container_index, 1);
+        assert!(app.handle_jump_key_event(key('r')).is_none());
+        assert_eq!(app.selected_container_index, 2);
+        // No match leaves the selection where it was
+        assert!(app.handle_jump_key_event(key('z')).is_none());
+        assert_eq!(app.selected_container_index, 2);
+        assert_eq!(app.jump_target("brz"), None);
+
+        // Other keys end the jump and are handled as usual
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.handle_jump_key_event(enter), Some(enter));
+        assert!(matches!(app.modal, Modal::None));
+    }
+
+    #[test]
+    fn messages_expire_after_a_few_seconds_and_new_ones_start_the_clock_again() {
+        let mut app = test_app();
+        app.success_message = Some("Copied".to_string());
+        for _ in 0..MESSAGE_TICKS - 1 {
+            app.tick();
+        }
+        assert_eq!(app.success_message.as_deref(), Some("Copied"));
+        // An error replacing it gets its full time
+        app.error_message = Some("Failed".to_string());
+        for _ in 0..MESSAGE_TICKS {
+            app.tick();
+        }
+        assert_eq!(app.error_message.as_deref(), Some("Failed"));
+        app.tick();
+        assert!(app.error_message.is_none());
+        assert!(app.success_message.is_none());
+    }
+
+    #[test]
+    fn open_clone_dialog_sets_modal_data() {
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            files: vec![format!("{file} file.txt", file = app.icons.file)],
+            file_items: vec![super::FileItem {
+                display_name: format!("{file} file.txt", file = app.icons.file),
+                actual_name: "file.txt".to_string(),
+                kind: super::EntryKind::File,
+                size: None,
+                last_modified: None,
+                created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
+            }],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+
+        app.open_clone_dialog();
+
+        match app.modal {
+            Modal::Clone {
+                input,
+                original_path,
+                is_folder,
+            } => {
+                assert_eq!(input, "file.txt");
+                assert_eq!(original_path, "file.txt");
+                assert!(!is_folder);
+            }
+            _ => panic!("Expected clone dialog modal"),
+        }
+    }
+
+    #[tokio::test]
+    async fn an_edit_is_uploaded_once_its_changes_are_confirmed() {
+        use object_store::ObjectStoreExt;
+
+        let path = object_store::path::Path::from("conf/app.toml");
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(&path, b"port = 80\nhost = \"a\"\n".to_vec().into())
+            .await
+            .unwrap();
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            current_path: "conf/".to_string(),
+            files: vec!["app.toml".to_string()],
+            file_items: vec![super::FileItem {
+                display_name: "app.toml".to_string(),
+                actual_name: "app.toml".to_string(),
+                kind: super::EntryKind::File,
+                size: Some(21),
+                last_modified: None,
+                created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
+            }],
+            ..browsing_state(store.clone())
+        });
+
+        // Unchanged, there is nothing to upload
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('E')))
+            .await
+            .unwrap();
+        let session = app.pending_edit.take().unwrap();
+        app.review_edit(session).await;
+        assert!(matches!(app.modal, Modal::None));
+        assert_eq!(
+            app.success_message.as_deref(),
+            Some("No changes to conf/app.toml")
+        );
+
+        // The editor's changes are shown, then uploaded with `y`
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('E')))
+            .await
+            .unwrap();
+        let session = app.pending_edit.take().unwrap();
+        std::fs::write(&session.local_path, "port = 8080\nhost = \"a\"\n").unwrap();
+        let local_path = session.local_path.clone();
+        app.review_edit(session).await;
+        let Modal::EditUpload { diff, .. } = &app.modal else {
+            panic!("Expected the edit to be confirmed");
+        };
+        let changed: Vec<_> = diff
+            .iter()
+            .filter(|line| matches!(line.kind, DiffKind::Removed | DiffKind::Added))
+            .map(|line| line.text.as_str())
+            .collect();
+        assert_eq!(changed, ["-port = 80", "+port = 8080"]);
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('y')))
+            .await
+            .unwrap();
+        let uploaded = store.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(uploaded.as_ref(), b"port = 8080\nhost = \"a\"\n");
+        assert!(!local_path.exists());
+        assert_eq!(
+            app.success_message.as_deref(),
+            Some("Uploaded the edit of conf/app.toml")
+        );
+    }
+
+    #[tokio::test]
+    async fn a_long_text_preview_loads_more_as_it_is_scrolled() {
+        use blobrs::preview::{MAX_PREVIEW_BYTES, MAX_PREVIEW_ROWS};
+        use object_store::ObjectStoreExt;
+
+        let log: String = (0..30_000).map(|i| format!("line {i}\n")).collect();
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(
+                &object_store::path::Path::from("app.log"),
+                log.clone().into(),
+            )
+            .await
+            .unwrap();
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            files: vec!["app.log".to_string()],
+            file_items: vec![super::FileItem {
+                display_name: "app.log".to_string(),
+                actual_name: "app.log".to_string(),
+                kind: super::EntryKind::File,
+                size: Some(log.len() as u64),
+                last_modified: None,
+                created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
+            }],
+            ..browsing_state(store)
+        });
+
+        app.load_preview().await.unwrap();
+        let lines = |app: &App| match &app.preview_data {
+            Some(PreviewData::Text(text)) => text.total_lines,
+            other => panic!("Expected a text preview, got {other:?}"),
+        };
+        let first_lines = lines(&app);
+        assert!(first_lines > MAX_PREVIEW_ROWS * 2);
+        assert_eq!(
+            app.preview_pager.as_ref().unwrap().loaded,
+            MAX_PREVIEW_BYTES as u64
+        );
+
+        // Going to the end of what was read fetches on, page after page
+        while !app.preview_pager.as_ref().unwrap().is_complete() {
+            app.handle_preview_key_event(KeyEvent::from(KeyCode::End));
+            app.tick();
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(lines(&app), 30_000);
+        app.close_preview();
+        assert!(app.preview_pager.is_none());
+    }
+
+    #[tokio::test]
+    async fn open_delete_dialog_sets_modal_data_for_folder() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for (name, size) in [("logs/a.log", 3), ("logs/2024/b.log", 5), ("other.txt", 7)] {
+            store
+                .put(
+                    &object_store::path::Path::from(name),
+                    vec![0u8; size].into(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            files: vec![format!("{folder} logs", folder = app.icons.folder)],
+            file_items: vec![super::FileItem {
+                display_name: format!("{folder} logs", folder = app.icons.folder),
+                actual_name: "logs".to_string(),
+                kind: super::EntryKind::Folder,
+                size: None,
+                last_modified: None,
+                created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
+            }],
+            ..browsing_state(store)
+        });
+
+        app.open_delete_dialog().await.unwrap();
+
+        match std::mem::replace(&mut app.modal, Modal::None) {
+            Modal::DeleteConfirm {
+                input,
+                target_path,
+                target_name,
+                is_folder,
+                size,
+                plan,
+            } => {
+                assert_eq!(input, "");
+                assert_eq!(target_path, "logs/");
+                assert_eq!(target_name, "logs");
+                assert!(is_folder);
+                assert_eq!(size, None);
+                let plan = plan.expect("folder deletes are planned up front");
+                assert_eq!(plan.blobs.len(), 2);
+                assert_eq!(plan.total_bytes(), 8);
+            }
+            _ => panic!("Expected delete confirm modal"),
+        }
+
+        app.open_delete_dialog().await.unwrap();
+        for c in "logs".chars() {
+            app.handle_delete_dialog_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        app.handle_delete_dialog_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        finish_delete(&mut app).await;
+        let names: Vec<_> = app
+            .browsing()
+            .unwrap()
+            .file_items
+            .iter()
+            .map(|item| item.actual_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["other.txt"]);
+    }
+
+    #[test]
+    fn show_download_picker_sets_modal() {
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            files: vec![format!("{file} report.csv", file = app.icons.file)],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+
+        app.show_download_picker();
+
+        match app.modal {
+            Modal::DownloadPicker {
+                destination,
+                menu,
+                use_default_dir,
+                snapshot,
+            } => {
+                assert!(destination.is_none());
+                assert!(snapshot.is_none());
+                assert_eq!(menu.selected, 0);
+                assert_eq!(menu.items.len(), 1);
+                assert!(!use_default_dir);
+            }
+            _ => panic!("Expected download picker modal"),
+        }
+    }
+
+    #[tokio::test]
+    async fn default_download_dir_skips_picker_unless_overridden() {
+        let mut app = test_app();
+        app.config.download_dir = Some(std::path::PathBuf::from("/tmp/blobs"));
+        app.session = Session::Browsing(BrowsingState {
+            files: vec![format!("{file} report.csv", file = app.icons.file)],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+
+        app.show_download_picker();
+        assert!(matches!(
+            app.modal,
+            Modal::DownloadPicker {
+                use_default_dir: true,
+                ..
+            }
+        ));
+
+        app.close_modal();
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('D'), KeyModifiers::SHIFT))
+            .await
+            .unwrap();
+        assert!(matches!(
+            app.modal,
+            Modal::DownloadPicker {
+                use_default_dir: false,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_downloaded_file_can_be_opened_afterwards() {
+        use object_store::ObjectStoreExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("open");
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(
+                &object_store::path::Path::from("docs/report.pdf"),
+                b"%PDF".to_vec().into(),
+            )
+            .await
+            .unwrap();
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            current_path: "docs/".to_string(),
+            files: vec!["report.pdf".to_string()],
+            file_items: vec![super::FileItem {
+                display_name: "report.pdf".to_string(),
+                actual_name: "report.pdf".to_string(),
+                kind: super::EntryKind::File,
+                size: Some(4),
+                last_modified: None,
+                created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
+            }],
+            ..browsing_state(store)
+        });
+
+        app.show_download_picker();
+        if let Modal::DownloadPicker { destination, .. } = &mut app.modal {
+            *destination = Some(dir.clone());
+        }
+        app.start_download().await.unwrap();
+        let file = dir.join("report.pdf");
+        assert_eq!(std::fs::read(&file).unwrap(), b"%PDF");
+        assert_eq!(app.last_download.as_ref(), Some(&file));
+        assert_eq!(
+            app.success_message,
+            Some(format!("Downloaded {} • `o` to open it", file.display()))
+        );
+    }
+
+    #[tokio::test]
+    async fn download_picker_selection_wraps_over_recent_destinations() {
+        let mut app = test_app();
+        app.state
+            .remember_download_dir(std::path::Path::new("/tmp/older"));
+        app.state
+            .remember_download_dir(std::path::Path::new("/tmp/newer"));
+        app.session = Session::Browsing(BrowsingState {
+            files: vec![format!("{file} report.csv", file = app.icons.file)],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+        app.show_download_picker();
+        let selected = |app: &App| match &app.modal {
+            Modal::DownloadPicker { menu, .. } => Some(menu.selected),
+            _ => None,
+        };
+
+        // Two recent destinations plus the "Browse" entry
+        for _ in 0..2 {
+            app.handle_key_event(KeyEvent::from(KeyCode::Down))
+                .await
+                .unwrap();
+        }
+        assert_eq!(selected(&app), Some(2));
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('j')))
+            .await
+            .unwrap();
+        assert_eq!(selected(&app), Some(0));
+        app.handle_key_event(KeyEvent::from(KeyCode::Up))
+            .await
+            .unwrap();
+        assert_eq!(selected(&app), Some(2));
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+    }
+
+    #[tokio::test]
+    async fn delete_keeps_selection_position_and_respects_read_only() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for name in ["a.csv", "b.csv", "c.csv"] {
+            store
+                .put(&object_store::path::Path::from(name), vec![0u8; 4].into())
+                .await
+                .unwrap();
+        }
+
+        let mut app = test_app();
+        app.session = Session::Browsing(browsing_state(store.clone()));
+        app.refresh_files().await.unwrap();
+        app.move_down();
+
+        app.config.read_only = true;
+        app.open_delete_dialog().await.unwrap();
+        assert!(matches!(app.modal, Modal::None));
+        assert_eq!(app.error_message.as_deref(), Some("Read-only mode is on"));
+
+        app.config.read_only = false;
+        app.open_delete_dialog().await.unwrap();
+        assert!(matches!(
+            app.modal,
+            Modal::DeleteConfirm { size: Some(4), .. }
+        ));
+        for c in "b.csv".chars() {
+            app.handle_delete_dialog_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        app.handle_delete_dialog_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        finish_delete(&mut app).await;
+
+        let state = app.browsing().unwrap();
+        assert_eq!(state.file_items.len(), 2);
+        assert_eq!(state.selected_index, 1);
+        assert_eq!(state.file_items[1].actual_name, "c.csv");
+    }
+
+    #[tokio::test]
+    async fn marked_entries_are_deleted_together() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for name in ["dir/c.csv", "dir/d.csv", "a.csv", "b.csv"] {
+            store
+                .put(&object_store::path::Path::from(name), vec![0u8; 4].into())
+                .await
+                .unwrap();
+        }
+
+        let mut app = test_app();
+        app.session = Session::Browsing(browsing_state(store.clone()));
+        app.refresh_files().await.unwrap();
+
+        // Marking moves down, so this marks the folder and the first blob
+        app.handle_key_event(KeyEvent::from(KeyCode::Char(' ')))
+            .await
+            .unwrap();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char(' ')))
+            .await
+            .unwrap();
+        assert_eq!(app.marked_summary(), Some((2, 4)));
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT))
+            .await
+            .unwrap();
+        assert_eq!(app.marked_summary(), Some((1, 4)));
+        app.invert_marks();
+
+        app.open_delete_dialog().await.unwrap();
+        let Modal::DeleteMarked { plan, .. } = &app.modal else {
+            panic!("expected the marked delete dialog, got {:?}", app.modal);
+        };
+        assert_eq!(plan.blobs.len(), 3);
+        for c in DELETE_MARKED_CONFIRMATION.chars() {
+            app.handle_delete_dialog_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        app.handle_delete_dialog_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        finish_delete(&mut app).await;
+
+        let state = app.browsing().unwrap();
+        assert!(state.marked.is_empty());
+        let names: Vec<_> = state.file_items.iter().map(|i| &i.actual_name).collect();
+        assert_eq!(names, vec!["b.csv"]);
+    }
+
+    #[tokio::test]
+    async fn container_prompts_validate_and_respect_read_only() {
+        let mut app = test_app();
+        app.containers = vec![ContainerInfo {
+            name: "logs".to_string(),
+            last_modified: None,
+            recent: false,
+        }];
+
+        app.config.read_only = true;
+        for key in [KeyCode::Char('n'), KeyCode::Char('x')] {
+            app.handle_key_event(KeyEvent::from(key)).await.unwrap();
+            assert!(matches!(app.modal, Modal::None));
+        }
+        app.config.read_only = false;
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('n')))
+            .await
+            .unwrap();
+        for c in "My_Logs".chars() {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        assert!(matches!(&app.modal, Modal::NewContainer { input } if input == "My_Logs"));
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("Container names cannot contain 'M'")
+        );
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc))
+            .await
+            .unwrap();
+
+        // Typing anything but the name never confirms the delete
+        app.modal = Modal::DeleteContainer {
+            name: "logs".to_string(),
+            input: String::new(),
+        };
+        for c in "log".chars() {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        assert!(matches!(&app.modal, Modal::DeleteContainer { input, .. } if input == "log"));
+    }
+
+    #[tokio::test]
+    async fn containers_sort_by_date_modified_keeping_the_selection() {
+        let mut app = test_app();
+        let day = |d| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).single();
+        app.containers = vec![
+            ContainerInfo {
+                name: "alpha".to_string(),
+                last_modified: day(1),
+                recent: false,
+            },
+            ContainerInfo {
+                name: "beta".to_string(),
+                last_modified: day(3),
+                recent: false,
+            },
+            ContainerInfo {
+                name: "gamma".to_string(),
+                last_modified: day(2),
+                recent: false,
+            },
+        ];
+        app.all_containers.clone_from(&app.containers);
+        app.selected_container_index = 2;
+
+        app.open_container_sort_picker();
+        let Modal::SortPicker { menu } = &app.modal else {
+            panic!("sort picker should be open");
+        };
+        let labels: Vec<_> = menu.items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, ["Sort by Name (current)", "Sort by Date Modified"]);
+        app.close_modal();
+
+        for c in ['o', 'm'] {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        assert!(matches!(app.modal, Modal::None));
+        assert_eq!(app.container_sort, SortCriteria::DateModified);
+        let names: Vec<_> = app.containers.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["beta", "gamma", "alpha"]);
+        assert_eq!(app.all_containers[0].name, "beta");
+        assert_eq!(app.selected_container_index, 1);
+
+        for c in ['o', 'n'] {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(app.containers[1].name, "beta");
+        assert_eq!(app.selected_container_index, 2);
+    }
+
+    #[tokio::test]
+    async fn s_toggles_container_sizes() {
+        let mut app = test_app();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('s')))
+            .await
+            .unwrap();
+        assert!(app.ui.show_container_usage);
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('s')))
+            .await
+            .unwrap();
+        assert!(!app.ui.show_container_usage);
+    }
+
+    #[tokio::test]
+    async fn container_info_reports_failures_and_closes() {
+        let mut app = test_app();
+        app.containers = vec![ContainerInfo {
+            name: "logs".to_string(),
+            last_modified: None,
+            recent: false,
+        }];
+
+        // The test key is not valid base64, so the request cannot be signed
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('i')))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+        assert!(
+            app.error_message
+                .as_deref()
+                .is_some_and(|e| e.starts_with("Failed to get container properties"))
+        );
+
+        app.modal = Modal::ContainerInfo {
+            properties: ContainerProperties::default(),
+            soft_delete: Some(true),
+            versioning: None,
+        };
+        app.handle_key_event(KeyEvent::from(KeyCode::Down))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::ContainerInfo { .. }));
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('i')))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+    }
+
+    #[tokio::test]
+    async fn new_folder_is_created_with_a_placeholder_and_selected() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for name in ["a.csv", "zeta/b.csv"] {
+            store
+                .put(&object_store::path::Path::from(name), vec![0u8; 4].into())
+                .await
+                .unwrap();
+        }
+
+        let mut app = test_app();
+        app.session = Session::Browsing(browsing_state(store.clone()));
+        app.refresh_files().await.unwrap();
+
+        app.config.read_only = true;
+        app.open_new_folder_dialog();
+        assert!(matches!(app.modal, Modal::None));
+        app.config.read_only = false;
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('n')))
+            .await
+            .unwrap();
+        for c in "/staging/2024/".chars() {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+        assert!(app.error_message.is_none(), "{:?}", app.error_message);
+        store
+            .head(&object_store::path::Path::from("staging/2024/.keep"))
+            .await
+            .unwrap();
+        let selected = app.selected_file_item().unwrap();
+        assert_eq!(selected.actual_name, "staging");
+        assert_eq!(selected.kind, EntryKind::Folder);
+
+        app.open_new_folder_dialog();
+        for c in "staging/2024".chars() {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("Folder staging/2024 already exists")
+        );
+    }
+
+    #[tokio::test]
+    async fn u_undeletes_deleted_entries_and_respects_read_only() {
+        let mut app = test_app();
+        let deleted = super::FileItem {
+            display_name: "[DELETED] gone.csv".to_string(),
+            actual_name: "gone.csv".to_string(),
+            kind: EntryKind::File,
+            size: Some(1),
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: true,
+            used_size: None,
+        };
+        app.session = Session::Browsing(BrowsingState {
+            files: vec![deleted.display_name.clone()],
+            file_items: vec![deleted],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+        app.config.read_only = true;
+
+        // `u` restores instead of uploading, so read-only mode refuses it
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('u')))
+            .await
+            .unwrap();
+        assert_eq!(app.error_message.as_deref(), Some("Read-only mode is on"));
+        assert!(matches!(app.async_op, AsyncOp::None));
+    }
+
+    #[tokio::test]
+    async fn folder_info_opens_at_once_and_counts_in_the_background() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for path in ["logs/1.log", "logs/2024/2.log"] {
+            store
+                .put(
+                    &object_store::path::Path::from(path),
+                    b"abc".to_vec().into(),
+                )
+                .await
+                .unwrap();
+        }
+        let folder = super::FileItem {
+            display_name: "logs".to_string(),
+            actual_name: "logs".to_string(),
+            kind: EntryKind::Folder,
+            size: None,
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            files: vec![folder.display_name.clone()],
+            file_items: vec![folder],
+            ..browsing_state(store)
+        });
+
+        app.show_blob_info().await.unwrap();
+        let Modal::BlobInfo {
+            info: super::BlobInfo::Folder { path, .. },
+        } = &app.modal
+        else {
+            panic!("expected folder info, got {:?}", app.modal);
+        };
+        assert_eq!(path, "logs/");
+        let usage = loop {
+            match app.usage.get_folder("test-container", "logs/") {
+                Some(usage) if usage.complete => break usage,
+                _ => tokio::task::yield_now().await,
+            }
+        };
+        assert_eq!((usage.blobs, usage.bytes), (2, 6));
+    }
 
-        let folder_path = Self::join_folder_path(&browsing.current_path, folder_name);
+    #[tokio::test]
+    async fn the_size_breakdown_opens_folders_and_goes_back_up() {
+        use object_store::ObjectStoreExt;
+
+        let store: std::sync::Arc<dyn object_store::ObjectStore> =
+            std::sync::Arc::new(object_store::memory::InMemory::new());
+        for (path, size) in [("a.csv", 1), ("logs/1.log", 5), ("logs/2024/2.log", 9)] {
+            store
+                .put(
+                    &object_store::path::Path::from(path),
+                    vec![0u8; size].into(),
+                )
+                .await
+                .unwrap();
+        }
+        let mut app = test_app();
+        app.session = Session::Browsing(browsing_state(store));
+        let counted = |app: &App, folder: &str| {
+            app.usage
+                .get_prefix("test-container", folder)
+                .is_some_and(|usage| usage.complete)
+        };
 
-        let object_path = ObjectPath::from(folder_path.as_str());
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('B')))
+            .await
+            .unwrap();
+        while !counted(&app, "") {
+            tokio::task::yield_now().await;
+        }
+        // logs/ is the largest, so it comes first
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        assert!(matches!(&app.modal, Modal::Usage { folder, selected: 0 } if folder == "logs/"));
+        while !counted(&app, "logs/") {
+            tokio::task::yield_now().await;
+        }
+        let usage = app.usage.get_folder("test-container", "logs/").unwrap();
+        assert_eq!(usage.breakdown()[0].0, "2024/");
+
+        // A blob has nothing to open
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('j')))
+            .await
+            .unwrap();
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        assert!(matches!(&app.modal, Modal::Usage { folder, selected: 1 } if folder == "logs/"));
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('h')))
+            .await
+            .unwrap();
+        assert!(matches!(&app.modal, Modal::Usage { folder, selected: 0 } if folder.is_empty()));
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+    }
 
-        // Create destination folder
-        let folder_destination = destination.join(folder_name);
-        fs::create_dir_all(&folder_destination)?;
+    #[tokio::test]
+    async fn a_watched_folder_takes_in_new_blobs() {
+        use blobrs::watch::{Change, FolderWatch};
+        use object_store::ObjectStoreExt;
 
-        // List all files in the folder
-        let stream = object_store.list(Some(&object_path));
-        let objects: Vec<_> = stream.collect().await;
+        let store: std::sync::Arc<dyn object_store::ObjectStore> =
+            std::sync::Arc::new(object_store::memory::InMemory::new());
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            current_path: "in/".to_string(),
+            ..browsing_state(store.clone())
+        });
+        app.watch = Some(FolderWatch::new(std::time::Duration::ZERO));
+        store
+            .put(
+                &object_store::path::Path::from("in/landed.csv"),
+                b"a,b".to_vec().into(),
+            )
+            .await
+            .unwrap();
+
+        while app.browsing().unwrap().file_items.is_empty() {
+            app.tick();
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(app.browsing().unwrap().files.len(), 1);
+        assert_eq!(app.success_message.as_deref(), Some("Watch: 1 new"));
+        let highlight = app.watch.as_ref().unwrap().highlight(
+            "raw/in/",
+            "landed.csv",
+            std::time::Instant::now(),
+        );
+        assert_eq!(highlight, Some(Change::New));
+
+        app.toggle_watch();
+        assert!(app.watch.is_none());
+    }
 
-        let total_files = objects.len();
-        let mut files_completed = 0;
-        let mut total_bytes_downloaded = 0u64;
+    #[test]
+    fn size_sort_uses_page_blob_usage() {
+        let file = |name: &str, size: u64| super::FileItem {
+            display_name: name.to_string(),
+            actual_name: name.to_string(),
+            kind: EntryKind::File,
+            size: Some(size),
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        let mut items = vec![file("sparse.vhd", 1 << 30), file("data.csv", 4096)];
+        App::set_used_sizes(
+            &mut items,
+            &BTreeMap::from([("sparse.vhd".to_string(), 512)]),
+        );
+        App::sort_file_items_static(&mut items, SortOrder::new(SortCriteria::Size));
+
+        let names: Vec<_> = items.iter().map(|item| item.actual_name.as_str()).collect();
+        assert_eq!(names, ["data.csv", "sparse.vhd"]);
+        assert_eq!(items[1].size, Some(1 << 30));
+    }
 
-        // Initialize progress
-        self.async_op = AsyncOp::Downloading(DownloadProgress {
-            current_file: String::new(),
-            files_completed: 0,
-            total_files,
-            bytes_downloaded: 0,
-            total_bytes: None,
-            error_message: None,
+    #[tokio::test]
+    async fn picking_the_same_sort_again_reverses_it_and_is_remembered() {
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            ..browsing_state(Arc::new(InMemory::new()))
         });
+        let pick = |key| KeyEvent::from(KeyCode::Char(key));
 
-        for result in objects {
-            match result {
-                Ok(meta) => {
-                    let file_path = meta.location.as_ref();
-                    let relative_path = file_path.strip_prefix(&folder_path).unwrap_or(file_path);
+        app.open_sort_picker();
+        app.handle_sort_picker_key_event(pick('m')).await;
+        assert_eq!(app.sort, SortOrder::new(SortCriteria::DateModified));
 
-                    // Update progress
-                    if let AsyncOp::Downloading(progress) = &mut self.async_op {
-                        progress.current_file = relative_path.to_string();
-                    }
+        app.open_sort_picker();
+        let Modal::SortPicker { menu } = &app.modal else {
+            panic!("sort picker should be open");
+        };
+        assert_eq!(menu.items[1].label, "Sort by Date Modified (newest first)");
+        app.handle_sort_picker_key_event(pick('m')).await;
+        let oldest_first = SortOrder {
+            criteria: SortCriteria::DateModified,
+            reversed: true,
+        };
+        assert_eq!(app.sort, oldest_first);
+        assert_eq!(
+            app.state.sort_for(&app.storage_account, "raw"),
+            oldest_first
+        );
+    }
 
-                    // Create full destination path
-                    let file_destination = folder_destination.join(relative_path);
+    #[tokio::test]
+    async fn motions_move_through_the_filtered_list() {
+        let mut app = test_app();
+        let file_items: Vec<_> = (0..100)
+            .map(|i| super::FileItem {
+                display_name: format!("{i:03}.{}", if i % 2 == 0 { "csv" } else { "json" }),
+                actual_name: format!("{i:03}.{}", if i % 2 == 0 { "csv" } else { "json" }),
+                kind: EntryKind::File,
+                size: None,
+                last_modified: None,
+                created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
+            })
+            .collect();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            files: file_items.iter().map(|i| i.display_name.clone()).collect(),
+            file_items,
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+        app.list_layout.borrow_mut().rows.height = 20;
+        let selected = |app: &App| app.browsing().unwrap().selected_index;
+        async fn press(app: &mut App, keys: &str) {
+            for c in keys.chars() {
+                app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                    .await
+                    .unwrap();
+            }
+        }
 
-                    // Ensure parent directory exists
-                    if let Some(parent) = file_destination.parent() {
-                        fs::create_dir_all(parent)?;
-                    }
+        press(&mut app, "25j").await;
+        assert_eq!(selected(&app), 25);
+        press(&mut app, "G").await;
+        assert_eq!(selected(&app), 99);
+        press(&mut app, "gg").await;
+        assert_eq!(selected(&app), 0);
+        app.handle_key_event(KeyEvent::from(KeyCode::PageDown))
+            .await
+            .unwrap();
+        assert_eq!(selected(&app), 20);
+
+        // Within a search, paging keys move through the matches only
+        app.enter_search_mode();
+        press(&mut app, "csv").await;
+        app.handle_key_event(KeyEvent::from(KeyCode::End))
+            .await
+            .unwrap();
+        assert_eq!(selected(&app), 49);
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        press(&mut app, "10G").await;
+        assert_eq!(app.selected_file_item().unwrap().actual_name, "018.csv");
+    }
 
-                    // Download the file
-                    match object_store.get(&meta.location).await {
-                        Ok(get_result) => {
-                            let bytes = get_result.bytes().await?;
-                            fs::write(&file_destination, &bytes)?;
+    #[tokio::test]
+    async fn clicks_select_open_and_go_back_up() {
+        use object_store::ObjectStoreExt;
+        use ratatui::buffer::Buffer;
+        use ratatui::crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Widget;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for name in ["logs/2024/a.csv", "b.csv"] {
+            store
+                .put(&object_store::path::Path::from(name), vec![0u8; 4].into())
+                .await
+                .unwrap();
+        }
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            ..browsing_state(store)
+        });
+        app.refresh_files().await.unwrap();
+        let draw = |app: &App| {
+            let mut buf = Buffer::empty(Rect::new(0, 0, 120, 30));
+            app.render(buf.area, &mut buf);
+            buf
+        };
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
 
-                            files_completed += 1;
-                            total_bytes_downloaded += bytes.len() as u64;
+        draw(&app);
+        let first_row = app.list_layout.borrow().rows.y;
+        app.handle_mouse_event(click(5, first_row + 1)).await;
+        assert_eq!(app.selected_file_item().unwrap().actual_name, "b.csv");
 
-                            // Update progress
-                            if let AsyncOp::Downloading(progress) = &mut self.async_op {
-                                progress.files_completed = files_completed;
-                                progress.bytes_downloaded = total_bytes_downloaded;
-                            }
-                        }
-                        Err(e) => {
-                            if let AsyncOp::Downloading(progress) = &mut self.async_op {
-                                progress.error_message =
-                                    Some(format!("Failed to download {relative_path}: {e}"));
-                            }
-                            // Continue with other files even if one fails
-                        }
-                    }
-                }
-                Err(e) => {
-                    if let AsyncOp::Downloading(progress) = &mut self.async_op {
-                        progress.error_message = Some(format!("Failed to list file: {e}"));
-                    }
-                }
-            }
+        // Double-clicking the folder opens it, twice over
+        for _ in 0..2 {
+            draw(&app);
+            app.handle_mouse_event(click(5, first_row)).await;
+            app.handle_mouse_event(click(5, first_row)).await;
         }
+        assert_eq!(app.browsing().unwrap().current_path, "logs/2024/");
+
+        // Clicking `logs` in the path goes back to it
+        let buf = draw(&app);
+        let title: Vec<_> = (0..buf.area.width)
+            .map(|x| buf[(x, first_row - 1)].symbol().to_string())
+            .collect();
+        let column = title
+            .windows(5)
+            .position(|w| w.concat() == "/logs")
+            .unwrap()
+            + 2;
+        app.handle_mouse_event(click(u16::try_from(column).unwrap(), first_row - 1))
+            .await;
+        assert_eq!(app.browsing().unwrap().current_path, "logs/");
+    }
 
-        Ok(())
+    #[tokio::test]
+    async fn going_up_selects_the_folder_come_from() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for name in ["a/x.csv", "b/c/y.csv", "b/d/z.csv", "e/w.csv"] {
+            store
+                .put(&object_store::path::Path::from(name), vec![0u8; 4].into())
+                .await
+                .unwrap();
+        }
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            current_path: "b/d/".to_string(),
+            ..browsing_state(store)
+        });
+        app.refresh_files().await.unwrap();
+        let selected = |app: &App| app.selected_file_item().unwrap().actual_name.clone();
+
+        app.go_up_directory().await.unwrap();
+        assert_eq!(selected(&app), "d");
+        app.go_up_directory().await.unwrap();
+        assert_eq!(selected(&app), "b");
+
+        // Jumping up several levels selects the top folder of the path
+        app.open_prefix("b/c/".to_string()).await.unwrap();
+        app.open_prefix(String::new()).await.unwrap();
+        assert_eq!(selected(&app), "b");
     }
 
-    /// Handle Enter key when download picker is shown.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the file dialog or download fails.
-    pub async fn confirm_download(&mut self) -> color_eyre::Result<()> {
-        if self.is_modal_download_picker() {
-            // Use the file dialog to pick a destination folder
-            let file_dialog = rfd::FileDialog::new();
+    #[tokio::test]
+    async fn question_mark_shows_every_key_and_scrolls() {
+        let mut app = test_app();
+        app.list_layout.borrow_mut().rows.height = 20;
+        let press = |c| KeyEvent::from(KeyCode::Char(c));
+
+        app.handle_key_event(press('?')).await.unwrap();
+        assert!(matches!(app.modal, Modal::Help { scroll: 0 }));
+        app.handle_key_event(press('j')).await.unwrap();
+        assert!(matches!(app.modal, Modal::Help { scroll: 1 }));
+        // The end stops with the last page in view
+        app.handle_key_event(press('G')).await.unwrap();
+        let Modal::Help { scroll } = app.modal else {
+            panic!("expected the help, got {:?}", app.modal);
+        };
+        assert_eq!(scroll, crate::help::line_count() - 20);
 
-            // Run the file dialog in a spawn_blocking since it's blocking
-            let path_result = tokio::task::spawn_blocking(move || file_dialog.pick_folder()).await;
+        // `q` closes the help rather than quitting
+        app.handle_key_event(press('q')).await.unwrap();
+        assert!(matches!(app.modal, Modal::None));
+    }
 
-            match path_result {
-                Ok(Some(path)) => {
-                    self.modal = Modal::DownloadPicker {
-                        destination: Some(path),
-                    };
-                    self.start_download().await?;
-                }
-                Ok(None) => {
-                    // User cancelled the dialog
-                    self.close_modal();
-                }
-                Err(e) => {
-                    self.close_modal();
-                    self.error_message = Some(format!("Failed to open file dialog: {e}"));
-                }
+    #[tokio::test]
+    async fn commands_run_by_name() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(
+                &object_store::path::Path::from("raw/2024/a.csv"),
+                b"x".to_vec().into(),
+            )
+            .await
+            .unwrap();
+        let mut app = test_app();
+        app.session = Session::Browsing(browsing_state(store));
+        async fn command(app: &mut App, typed: &str) {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(':')))
+                .await
+                .unwrap();
+            for c in typed.chars() {
+                app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                    .await
+                    .unwrap();
             }
+            app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+                .await
+                .unwrap();
         }
-        Ok(())
-    }
 
-    // ========================================
-    // Preview Panel Methods
-    // ========================================
+        command(&mut app, "goto raw/2024").await;
+        assert_eq!(app.browsing().unwrap().current_path, "raw/2024/");
+        assert_eq!(app.browsing().unwrap().file_items[0].actual_name, "a.csv");
 
-    /// Load preview for the currently selected file.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if fetching or parsing the file fails.
-    #[allow(clippy::too_many_lines)]
-    pub async fn load_preview(&mut self) -> color_eyre::Result<()> {
-        let (selected_item, current_path) = match self.browsing() {
-            Some(state) => {
-                let Some(item) = self.selected_file_item() else {
-                    return Ok(());
-                };
-                (item.clone(), state.current_path.clone())
+        command(&mut app, "sort size reverse").await;
+        assert_eq!(
+            app.sort,
+            SortOrder {
+                criteria: SortCriteria::Size,
+                reversed: true
             }
-            None => return Ok(()),
-        };
+        );
+
+        // Fuzzy names run the best match, which carries on as its key
+        command(&mut app, "nf").await;
+        assert!(matches!(app.modal, Modal::NewFolder { .. }));
+        app.close_modal();
+
+        command(&mut app, "frobnicate").await;
+        assert!(app.error_message.unwrap().contains("Unknown command"));
+        app.error_message = None;
+        command(&mut app, "tier lukewarm").await;
+        assert!(app.error_message.unwrap().contains("hot, cool"));
+
+        app.session = Session::Selecting;
+        app.error_message = None;
+        command(&mut app, "new-folder").await;
+        assert!(app.error_message.unwrap().contains("inside a container"));
+        app.error_message = None;
+
+        // The container list is exported as it is loaded
+        app.containers = vec![ContainerInfo {
+            name: "raw".to_string(),
+            last_modified: None,
+            recent: false,
+        }];
+        let tmp = tempfile::tempdir().unwrap();
+        let exported = tmp.path().join("export.csv");
+        command(&mut app, &format!("export {}", exported.display())).await;
+        assert!(
+            app.success_message
+                .unwrap()
+                .starts_with("Exported 1 entries")
+        );
+        assert_eq!(
+            std::fs::read_to_string(&exported).unwrap(),
+            "name,kind,size,last_modified,etag,tier\nraw,container,,,,\n"
+        );
+        app.success_message = None;
+        command(&mut app, "export listing").await;
+        assert!(app.error_message.unwrap().contains("json, csv or table"));
+    }
 
-        if selected_item.actual_name.is_empty() {
-            return Ok(());
-        }
+    #[tokio::test]
+    async fn bang_pipes_the_selected_blob_into_a_command() {
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            current_path: "logs/".to_string(),
+            file_items: vec![super::FileItem {
+                display_name: "app.json".to_string(),
+                actual_name: "app.json".to_string(),
+                kind: EntryKind::File,
+                size: Some(2),
+                last_modified: None,
+                created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
+            }],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
 
-        // Check if it's a folder
-        if selected_item.kind == EntryKind::Folder {
-            self.preview_error = Some("Cannot preview folders".to_string());
-            self.ui.show_preview = true;
-            return Ok(());
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('!')))
+            .await
+            .unwrap();
+        assert!(matches!(&app.modal, Modal::Command { input, .. } if input == "pipe "));
+        for c in "jq .".chars() {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
         }
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        let request = app.pending_pipe.take().unwrap();
+        assert_eq!(request.path.as_ref(), "logs/app.json");
+        assert_eq!(request.command, "jq .");
+    }
 
-        let name = selected_item.actual_name;
+    #[tokio::test]
+    async fn equals_compares_the_marked_blob_with_the_selected_one() {
+        use blobrs::diff::Comparison;
+        use object_store::{ObjectStoreExt, path::Path as ObjectPath};
 
-        // Check file type
-        let file_type = PreviewFileType::from_extension(&name);
-        if !file_type.is_supported() {
-            self.preview_error = Some(
-                "Unsupported file type. Preview supports: CSV, TSV, JSON, Parquet, and text files"
-                    .to_string(),
-            );
-            self.preview_file_type = Some(file_type);
-            self.ui.show_preview = true;
-            return Ok(());
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for (path, content) in [("conf/a.ini", "x = 1\n"), ("conf/b.ini", "x = 2\n")] {
+            store
+                .put(&ObjectPath::from(path), content.as_bytes().to_vec().into())
+                .await
+                .unwrap();
         }
+        let item = |name: &str| super::FileItem {
+            display_name: name.to_string(),
+            actual_name: name.to_string(),
+            kind: EntryKind::File,
+            size: Some(6),
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            current_path: "conf/".to_string(),
+            file_items: vec![item("a.ini"), item("b.ini")],
+            ..browsing_state(store)
+        });
 
-        self.preview_file_type = Some(file_type.clone());
-        self.ui.is_loading_preview = true;
-        self.ui.show_preview = true;
-        self.preview_error = None;
-        self.preview_data = None;
-        self.preview_scroll = (0, 0);
-        self.preview_selected_row = 0;
-        self.parquet_preview_mode = None;
-        self.parquet_table_data = None;
-        self.parquet_schema_data = None;
-
-        // Fetch file content (up to MAX_PREVIEW_BYTES)
-        let object_store = self
-            .browsing()
-            .ok_or_else(|| color_eyre::eyre::eyre!("No container selected"))?
-            .object_store
-            .clone();
+        // Nothing marked: ask for a local file
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('=')))
+            .await
+            .unwrap();
+        assert!(matches!(&app.modal, Modal::Command { input, .. } if input == "diff "));
+        app.close_modal();
+
+        app.toggle_mark();
+        app.browsing_mut().unwrap().selected_index = 1;
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('=')))
+            .await
+            .unwrap();
+        let Modal::Diff {
+            title, comparison, ..
+        } = &app.modal
+        else {
+            panic!("expected the comparison, got {:?}", app.error_message);
+        };
+        assert_eq!(title, "raw/conf/a.ini ⇄ raw/conf/b.ini");
+        let Comparison::Text(lines) = comparison else {
+            panic!("text is diffed");
+        };
+        let changed: Vec<&str> = lines[3..].iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(changed, ["-x = 1", "+x = 2"]);
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+
+        // Comparing a blob with itself is refused
+        app.browsing_mut().unwrap().selected_index = 0;
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('=')))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+        assert!(app.error_message.take().unwrap().contains("another blob"));
+    }
 
-        let blob_path = Self::join_blob_path(&current_path, &name);
+    #[tokio::test]
+    async fn enter_picks_blobs_in_pick_mode() {
+        let mut app = test_app();
+        let item = |name: &str, kind: EntryKind| super::FileItem {
+            display_name: name.to_string(),
+            actual_name: name.to_string(),
+            kind,
+            size: None,
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            current_path: "2024/".to_string(),
+            file_items: vec![
+                item("q1", EntryKind::Folder),
+                item("a.csv", EntryKind::File),
+                item("b.csv", EntryKind::File),
+            ],
+            selected_index: 1,
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+        app.pick = Some(PickOutput::Path);
+        let enter = KeyEvent::from(KeyCode::Enter);
+
+        app.handle_key_event(enter).await.unwrap();
+        assert_eq!(app.picked, ["raw/2024/a.csv"]);
+        assert!(!app.running);
+
+        // Marked blobs are picked together, leaving out marked folders
+        app.running = true;
+        app.pick = Some(PickOutput::Url);
+        app.browsing_mut().unwrap().selected_index = 0;
+        for path in ["2024/b.csv", "2024/q1/", "2024/a.csv"] {
+            app.browsing_mut()
+                .unwrap()
+                .marked
+                .insert(path.to_string(), item(path, EntryKind::File));
+        }
+        app.handle_key_event(enter).await.unwrap();
+        assert_eq!(
+            app.picked,
+            [
+                "https://test-account.blob.core.windows.net/raw/2024/a.csv",
+                "https://test-account.blob.core.windows.net/raw/2024/b.csv",
+            ]
+        );
+    }
 
-        let object_path = ObjectPath::from(blob_path.as_str());
+    #[tokio::test]
+    async fn the_listing_on_screen_is_exported_in_its_order() {
+        let mut app = test_app();
+        let item = |name: &str, kind: EntryKind, size: Option<u64>| super::FileItem {
+            display_name: name.to_string(),
+            actual_name: name.to_string(),
+            kind,
+            size,
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            current_path: "2024/".to_string(),
+            file_items: vec![
+                item("q1", EntryKind::Folder, None),
+                item("z.csv", EntryKind::File, Some(9)),
+                item("a.csv", EntryKind::File, Some(1)),
+            ],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
 
-        // For Parquet files, support both table and metadata views.
-        if file_type == PreviewFileType::Parquet {
-            let head_result = object_store.head(&object_path).await;
-            match head_result {
-                Ok(meta) => {
-                    let file_size = meta.size;
-                    if file_size <= MAX_PARQUET_TABLE_PREVIEW_BYTES as u64 {
-                        let get_result = object_store.get_range(&object_path, 0..file_size).await;
-                        self.ui.is_loading_preview = false;
-
-                        match get_result {
-                            Ok(bytes) => {
-                                let table_result = parse_parquet_table(&bytes);
-                                let schema_result = parse_parquet_schema(&bytes, Some(file_size));
-                                let mut table_error: Option<String> = None;
-
-                                match table_result {
-                                    Ok(PreviewData::Table(table)) => {
-                                        self.parquet_table_data = Some(table.clone());
-                                        self.preview_data = Some(PreviewData::Table(table));
-                                        self.parquet_preview_mode = Some(ParquetPreviewMode::Table);
-                                    }
-                                    Ok(_) => {
-                                        table_error = Some(
-                                            "Unexpected Parquet table preview format".to_string(),
-                                        );
-                                    }
-                                    Err(e) => {
-                                        table_error = Some(e);
-                                    }
-                                }
-
-                                match schema_result {
-                                    Ok(PreviewData::ParquetSchema(mut schema)) => {
-                                        if self.preview_data.is_none() && table_error.is_some() {
-                                            schema.note = table_error.clone();
-                                        }
-                                        self.parquet_schema_data = Some(schema);
-                                    }
-                                    Ok(_) => {
-                                        self.preview_error = Some(
-                                            "Unexpected Parquet metadata preview format"
-                                                .to_string(),
-                                        );
-                                    }
-                                    Err(e) => {
-                                        self.preview_error = Some(e);
-                                    }
-                                }
-
-                                if self.preview_data.is_none() {
-                                    if let Some(schema) = &self.parquet_schema_data {
-                                        self.preview_data =
-                                            Some(PreviewData::ParquetSchema(schema.clone()));
-                                        self.parquet_preview_mode =
-                                            Some(ParquetPreviewMode::Metadata);
-                                        self.preview_error = None;
-                                    } else if self.preview_error.is_none() {
-                                        self.preview_error = table_error;
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                self.preview_error = Some(format!("Failed to fetch file: {e}"));
-                            }
-                        }
-                    } else {
-                        // Large parquet files: read only footer metadata.
-                        let start = file_size.saturating_sub(MAX_PARQUET_PREVIEW_BYTES as u64);
-                        let get_result =
-                            object_store.get_range(&object_path, start..file_size).await;
-                        self.ui.is_loading_preview = false;
-
-                        match get_result {
-                            Ok(bytes) => match parse_parquet_schema(&bytes, Some(file_size)) {
-                                Ok(PreviewData::ParquetSchema(mut schema)) => {
-                                    schema.note = Some(
-                                        "Table preview unavailable for files larger than 16 MB"
-                                            .to_string(),
-                                    );
-                                    self.parquet_schema_data = Some(schema.clone());
-                                    self.preview_data = Some(PreviewData::ParquetSchema(schema));
-                                    self.parquet_preview_mode = Some(ParquetPreviewMode::Metadata);
-                                }
-                                Ok(_) => {
-                                    self.preview_error = Some(
-                                        "Unexpected Parquet metadata preview format".to_string(),
-                                    );
-                                }
-                                Err(e) => {
-                                    self.preview_error = Some(e);
-                                }
-                            },
-                            Err(e) => {
-                                self.preview_error = Some(format!("Failed to fetch file: {e}"));
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    self.ui.is_loading_preview = false;
-                    self.preview_error = Some(format!("Failed to get file info: {e}"));
-                }
-            }
-            return Ok(());
-        }
+        let tmp = tempfile::tempdir().unwrap();
+        let exported = tmp.path().join("export.json");
+        app.export_listing(&exported.display().to_string()).await;
+        // The test account's key cannot sign a request, so there are no ETags or tiers
+        let message = app.success_message.take().unwrap();
+        assert!(message.contains("3 entries to"));
+        assert!(message.contains("without ETags or tiers"));
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&exported).unwrap()).unwrap();
+        let names: Vec<&str> = (0..3).filter_map(|i| json[i]["name"].as_str()).collect();
+        assert_eq!(names, ["2024/q1/", "2024/z.csv", "2024/a.csv"]);
+        assert_eq!(json[1]["size"], 9);
+
+        // A manifest needs the listing the test account cannot get
+        app.export_listing("all manifest.csv").await;
+        assert!(app.error_message.unwrap().starts_with("Export failed"));
+    }
 
-        // For other file types, fetch from the beginning
-        let get_result = object_store
-            .get_range(&object_path, 0..(MAX_PREVIEW_BYTES as u64))
-            .await;
+    #[tokio::test]
+    async fn tabs_keep_their_own_location_and_sort() {
+        let mut app = test_app();
+        let browsing = |container: &str, path: &str| {
+            Session::Browsing(BrowsingState {
+                container_name: container.to_string(),
+                current_path: path.to_string(),
+                ..browsing_state(Arc::new(InMemory::new()))
+            })
+        };
+        let location = |app: &App| {
+            app.browsing()
+                .map(|state| format!("{}:{}", state.container_name, state.current_path))
+        };
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        app.session = browsing("prod", "logs/");
+        app.sort = SortOrder::new(SortCriteria::Size);
+
+        app.handle_key_event(ctrl('t')).await.unwrap();
+        assert!(app.is_selecting());
+        assert_eq!(app.tabs.count(), 2);
+        app.session = browsing("staging", "");
+        app.sort = SortOrder::default();
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Tab))
+            .await
+            .unwrap();
+        assert_eq!(location(&app).as_deref(), Some("prod:logs/"));
+        assert_eq!(app.sort, SortOrder::new(SortCriteria::Size));
+        app.handle_key_event(KeyEvent::from(KeyCode::Tab))
+            .await
+            .unwrap();
+        assert_eq!(location(&app).as_deref(), Some("staging:"));
+        assert_eq!(app.sort, SortOrder::default());
+
+        app.handle_key_event(ctrl('w')).await.unwrap();
+        assert_eq!(location(&app).as_deref(), Some("prod:logs/"));
+        // The last tab cannot be closed
+        app.handle_key_event(ctrl('w')).await.unwrap();
+        assert_eq!(app.tabs.count(), 1);
+        assert_eq!(location(&app).as_deref(), Some("prod:logs/"));
+    }
 
-        self.ui.is_loading_preview = false;
+    #[tokio::test]
+    async fn a_failed_snapshot_download_is_reported_without_quitting() {
+        let dest = tempfile::tempdir().unwrap();
+        let mut app = test_app();
+        // The test key is not valid base64, so the download fails before any request
+        app.session = Session::Browsing(BrowsingState {
+            files: vec![format!("{file} report.csv", file = app.icons.file)],
+            file_items: vec![super::FileItem {
+                display_name: format!("{file} report.csv", file = app.icons.file),
+                actual_name: "report.csv".to_string(),
+                kind: super::EntryKind::File,
+                size: None,
+                last_modified: None,
+                created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
+            }],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+        app.modal = Modal::DownloadPicker {
+            destination: Some(dest.path().to_path_buf()),
+            menu: crate::menu::Menu::new(Vec::new()),
+            use_default_dir: false,
+            snapshot: Some("2024-06-01T10:00:00.1234567Z".to_string()),
+        };
 
-        match get_result {
-            Ok(bytes) => {
-                // Parse the data
-                match parse_preview(&bytes, &file_type) {
-                    Ok(data) => {
-                        self.preview_data = Some(data);
-                    }
-                    Err(e) => {
-                        self.preview_error = Some(e);
-                    }
-                }
-            }
-            Err(e) => {
-                self.preview_error = Some(format!("Failed to fetch file: {e}"));
-            }
-        }
+        app.start_download().await.unwrap();
 
-        Ok(())
+        assert!(app.running);
+        assert!(
+            app.error_message
+                .as_deref()
+                .is_some_and(|message| message.starts_with("Failed to download snapshot"))
+        );
+        assert!(app.last_download.is_none());
+        assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
     }
 
-    /// Close the preview panel.
-    pub fn close_preview(&mut self) {
-        self.ui.show_preview = false;
-        self.preview_data = None;
-        self.preview_file_type = None;
-        self.preview_error = None;
-        self.preview_scroll = (0, 0);
-        self.preview_selected_row = 0;
-        self.ui.is_loading_preview = false;
-        self.parquet_preview_mode = None;
-        self.parquet_table_data = None;
-        self.parquet_schema_data = None;
+    #[test]
+    fn snapshot_downloads_keep_the_extension() {
+        let stamp = "2024-06-01T10:00:00.1234567Z";
+        assert_eq!(
+            super::snapshot_file_name("report.csv", stamp),
+            "report.2024-06-01T10-00-00.1234567Z.csv"
+        );
+        assert_eq!(
+            super::snapshot_file_name("README", stamp),
+            "README.2024-06-01T10-00-00.1234567Z"
+        );
+        assert_eq!(
+            super::snapshot_file_name(".env", stamp),
+            ".env.2024-06-01T10-00-00.1234567Z"
+        );
     }
 
-    /// Toggle between parquet table and metadata preview modes.
-    pub fn toggle_parquet_preview_mode(&mut self) {
-        if self.preview_file_type != Some(PreviewFileType::Parquet) {
-            return;
-        }
+    #[tokio::test]
+    async fn snapshot_restore_needs_confirmation_and_write_access() {
+        use blobrs::azure::BlobSnapshot;
 
-        match self.parquet_preview_mode {
-            Some(ParquetPreviewMode::Table) => {
-                if let Some(schema) = &self.parquet_schema_data {
-                    self.preview_data = Some(PreviewData::ParquetSchema(schema.clone()));
-                    self.parquet_preview_mode = Some(ParquetPreviewMode::Metadata);
-                    self.preview_scroll = (0, 0);
-                    self.preview_selected_row = 0;
-                }
-            }
-            Some(ParquetPreviewMode::Metadata) => {
-                if let Some(table) = &self.parquet_table_data {
-                    self.preview_data = Some(PreviewData::Table(table.clone()));
-                    self.parquet_preview_mode = Some(ParquetPreviewMode::Table);
-                    self.preview_scroll = (0, 0);
-                    self.preview_selected_row = 0;
+        let mut app = test_app();
+        app.session = Session::Browsing(browsing_state(Arc::new(InMemory::new())));
+        let snapshot = |stamp: &str| BlobSnapshot {
+            snapshot: stamp.to_string(),
+            size: 1,
+        };
+        app.modal = Modal::Snapshots {
+            blob_path: "a.csv".to_string(),
+            snapshots: vec![snapshot("2024-06-02"), snapshot("2024-06-01")],
+            selected: 0,
+            confirm_restore: false,
+        };
+        let confirming = |app: &App| {
+            matches!(
+                app.modal,
+                Modal::Snapshots {
+                    confirm_restore: true,
+                    ..
                 }
+            )
+        };
+
+        app.config.read_only = true;
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('r')))
+            .await
+            .unwrap();
+        assert!(!confirming(&app));
+        assert_eq!(app.error_message.as_deref(), Some("Read-only mode is on"));
+        app.config.read_only = false;
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Down))
+            .await
+            .unwrap();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('r')))
+            .await
+            .unwrap();
+        assert!(confirming(&app));
+        // Anything but `y` backs out and keeps the view open
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('n')))
+            .await
+            .unwrap();
+        assert!(matches!(
+            app.modal,
+            Modal::Snapshots {
+                selected: 1,
+                confirm_restore: false,
+                ..
             }
-            None => {}
-        }
+        ));
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
     }
 
-    /// Scroll preview up (decrease row offset).
-    pub fn preview_scroll_up(&mut self) {
-        if self.preview_selected_row > 0 {
-            self.preview_selected_row = self.preview_selected_row.saturating_sub(1);
+    #[tokio::test]
+    async fn tags_are_added_and_removed_in_the_editor() {
+        use super::BlobInfo;
+
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            current_path: "data/".to_string(),
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+        app.modal = Modal::BlobInfo {
+            info: BlobInfo::File {
+                name: "a.csv".to_string(),
+                size: 4,
+                last_modified: Utc::now(),
+                etag: None,
+                tags: Some(BTreeMap::from([("env".to_string(), "dev".to_string())])),
+                lease_state: None,
+                lease_duration: None,
+                created: None,
+                blob_type: None,
+                content_type: None,
+                content_encoding: None,
+                content_md5: None,
+                page_used: None,
+                immutable_until: None,
+                legal_hold: false,
+            },
+        };
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('t')))
+            .await
+            .unwrap();
+        // Type `text` and press Enter
+        let typed = |text: &str| -> Vec<KeyEvent> {
+            text.chars()
+                .map(|c| KeyEvent::from(KeyCode::Char(c)))
+                .chain([KeyEvent::from(KeyCode::Enter)])
+                .collect()
+        };
+        for key in typed("apii=no") {
+            app.handle_key_event(key).await.unwrap();
         }
-        // Also adjust scroll offset if needed
-        if self.preview_selected_row < self.preview_scroll.0 {
-            self.preview_scroll.0 = self.preview_selected_row;
+        // Invalid characters are refused and the input stays open
+        for key in typed("aowner=<me>") {
+            app.handle_key_event(key).await.unwrap();
+        }
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("Tags cannot contain '<'")
+        );
+        for key in [KeyCode::Esc, KeyCode::Up, KeyCode::Char('x')] {
+            app.handle_key_event(KeyEvent::from(key)).await.unwrap();
         }
-    }
 
-    /// Scroll preview down (increase row offset).
-    pub fn preview_scroll_down(&mut self) {
-        let max_row = match &self.preview_data {
-            Some(PreviewData::Table(table)) => table.rows.len().saturating_sub(1),
-            Some(PreviewData::Json(json)) => json.content.lines().count().saturating_sub(1),
-            Some(PreviewData::Text(text)) => text.content.lines().count().saturating_sub(1),
-            Some(PreviewData::ParquetSchema(schema)) => {
-                // Metadata lines + schema fields + some padding
-                (7 + schema.fields.len()).saturating_sub(1)
-            }
-            None => 0,
+        let Modal::EditTags {
+            blob_path,
+            tags,
+            selected,
+            input,
+        } = &app.modal
+        else {
+            panic!("expected the tag editor, got {:?}", app.modal);
         };
-        if self.preview_selected_row < max_row {
-            self.preview_selected_row += 1;
-        }
+        assert_eq!(blob_path, "data/a.csv");
+        assert_eq!(
+            tags,
+            &BTreeMap::from([("pii".to_string(), "no".to_string())])
+        );
+        assert_eq!(*selected, 0);
+        assert!(input.is_none());
     }
 
-    /// Scroll preview left (decrease column offset).
-    pub fn preview_scroll_left(&mut self) {
-        self.preview_scroll.1 = self.preview_scroll.1.saturating_sub(1);
-    }
+    #[tokio::test]
+    async fn f_follows_the_selected_blob() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(
+                &object_store::path::Path::from("logs/app.log"),
+                "started\nready\n".into(),
+            )
+            .await
+            .unwrap();
+        let log = super::FileItem {
+            display_name: "app.log".to_string(),
+            actual_name: "app.log".to_string(),
+            kind: EntryKind::File,
+            size: Some(14),
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            current_path: "logs/".to_string(),
+            files: vec![log.display_name.clone()],
+            file_items: vec![log],
+            ..browsing_state(store)
+        });
 
-    /// Scroll preview right (increase column offset).
-    pub fn preview_scroll_right(&mut self) {
-        if let Some(PreviewData::Table(table)) = &self.preview_data {
-            let num_cols = table
-                .headers
-                .len()
-                .max(table.rows.first().map_or(0, Vec::len));
-            if num_cols > 0 {
-                self.preview_scroll.1 = (self.preview_scroll.1 + 1).min(num_cols.saturating_sub(1));
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('f')))
+            .await
+            .unwrap();
+        let Modal::Tail { view, .. } = &app.modal else {
+            panic!("expected the tail view, got {:?}", app.modal);
+        };
+        assert_eq!(view.blob_path, "logs/app.log");
+        for _ in 0..100 {
+            if !view.lines().is_empty() {
+                break;
             }
-        } else {
-            self.preview_scroll.1 += 1;
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{
-        App, AsyncOp, BrowsingState, EntryKind, Modal, ParquetPreviewMode, Search, Session,
-        SortCriteria, UiToggles,
-    };
-    use crate::event::EventHandler;
-    use crate::preview::{ParquetSchemaPreview, PreviewData, PreviewFileType, TablePreview};
-    use crate::terminal_icons::detect_terminal_icons;
-    use chrono::{TimeZone, Utc};
-    use proptest::prelude::*;
-    use std::collections::BTreeMap;
+        assert_eq!(view.lines(), ["started", "ready"]);
 
-    fn arb_entry_kind() -> impl Strategy<Value = super::EntryKind> {
-        prop_oneof![Just(super::EntryKind::File), Just(super::EntryKind::Folder)]
+        // Scrolling stops at the first line
+        for key in [KeyCode::Up, KeyCode::Up, KeyCode::Up] {
+            app.handle_key_event(KeyEvent::from(key)).await.unwrap();
+        }
+        assert!(matches!(app.modal, Modal::Tail { back: 2, .. }));
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
     }
 
-    fn arb_sort_criteria() -> impl Strategy<Value = SortCriteria> {
-        prop_oneof![
-            Just(SortCriteria::Name),
-            Just(SortCriteria::DateModified),
-            Just(SortCriteria::DateCreated),
-            Just(SortCriteria::Size),
-        ]
-    }
+    #[tokio::test]
+    async fn copy_command_targets_the_selected_folder() {
+        let mut app = test_app();
+        let folder = super::FileItem {
+            display_name: "logs/".to_string(),
+            actual_name: "logs".to_string(),
+            kind: EntryKind::Folder,
+            size: None,
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        app.session = Session::Browsing(BrowsingState {
+            current_path: "data/".to_string(),
+            files: vec![folder.display_name.clone()],
+            file_items: vec![folder],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
 
-    fn arb_timestamp() -> impl Strategy<Value = Option<chrono::DateTime<chrono::Utc>>> {
-        prop::option::of((-2_208_988_800_i64..4_102_444_800_i64).prop_map(|secs| {
-            Utc.timestamp_opt(secs, 0)
-                .single()
-                .expect("generated timestamp should be valid")
-        }))
-    }
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('C')))
+            .await
+            .unwrap();
+        let Modal::CopyCommand { target, .. } = &app.modal else {
+            panic!("expected the command picker, got {:?}", app.modal);
+        };
+        assert_eq!(target.container, "test-container");
+        assert_eq!(target.path, "data/logs/");
 
-    fn arb_file_item() -> impl Strategy<Value = super::FileItem> {
-        (
-            "[A-Za-z0-9_./-]{1,16}",
-            arb_entry_kind(),
-            prop::option::of(0_u64..10_000),
-            arb_timestamp(),
-            arb_timestamp(),
-        )
-            .prop_map(|(actual_name, kind, size, last_modified, created)| {
-                let icon = match kind {
-                    super::EntryKind::File => "[FILE]",
-                    super::EntryKind::Folder => "[DIR]",
-                };
-                super::FileItem {
-                    display_name: format!("{icon} {actual_name}"),
-                    actual_name,
-                    kind,
-                    size,
-                    last_modified,
-                    created,
-                }
-            })
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
     }
 
-    fn test_app() -> App {
-        App {
-            running: true,
-            events: EventHandler::new(),
-            session: Session::Selecting,
-            storage_account: "test-account".to_string(),
-            access_key: "test-key".to_string(),
-            containers: Vec::new(),
-            all_containers: Vec::new(),
-            selected_container_index: 0,
-            async_op: AsyncOp::None,
-            error_message: None,
-            success_message: None,
-            search: Search::Inactive,
-            icons: detect_terminal_icons(),
-            modal: Modal::None,
-            ui: UiToggles {
-                show_preview: false,
-                is_loading_preview: false,
-            },
-            sort_criteria: SortCriteria::Name,
-            preview_data: None,
-            preview_file_type: None,
-            preview_scroll: (0, 0),
-            preview_error: None,
-            preview_selected_row: 0,
-            parquet_preview_mode: None,
-            parquet_table_data: None,
-            parquet_schema_data: None,
-        }
-    }
+    #[tokio::test]
+    async fn lease_menu_opens_from_blob_info_unless_read_only() {
+        use super::{BlobInfo, LeaseAction};
 
-    #[test]
-    fn open_clone_dialog_sets_modal_data() {
         let mut app = test_app();
         app.session = Session::Browsing(BrowsingState {
-            object_store: std::sync::Arc::new(object_store::memory::InMemory::new()),
-            current_path: String::new(),
-            files: vec![format!("{file} file.txt", file = app.icons.file)],
-            file_items: vec![super::FileItem {
-                display_name: format!("{file} file.txt", file = app.icons.file),
-                actual_name: "file.txt".to_string(),
-                kind: super::EntryKind::File,
-                size: None,
-                last_modified: None,
-                created: None,
-            }],
-            selected_index: 0,
+            current_path: "data/".to_string(),
+            ..browsing_state(Arc::new(InMemory::new()))
         });
+        let info = Modal::BlobInfo {
+            info: BlobInfo::File {
+                name: "a.csv".to_string(),
+                size: 4,
+                last_modified: Utc::now(),
+                etag: None,
+                tags: None,
+                lease_state: Some("available".to_string()),
+                lease_duration: None,
+                created: None,
+                blob_type: None,
+                content_type: None,
+                content_encoding: None,
+                content_md5: None,
+                page_used: None,
+                immutable_until: None,
+                legal_hold: false,
+            },
+        };
 
-        app.open_clone_dialog();
+        app.config.read_only = true;
+        app.modal = info.clone();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('L')))
+            .await
+            .unwrap();
+        assert_eq!(app.error_message.as_deref(), Some("Read-only mode is on"));
+        assert!(matches!(app.modal, Modal::BlobInfo { .. }));
+
+        app.config.read_only = false;
+        app.error_message = None;
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('L')))
+            .await
+            .unwrap();
+        let Modal::LeaseMenu { blob_path, menu } = &app.modal else {
+            panic!("expected the lease menu, got {:?}", app.modal);
+        };
+        assert_eq!(blob_path, "data/a.csv");
+        assert_eq!(menu.items[0].value, LeaseAction::Acquire { seconds: None });
 
-        match app.modal {
-            Modal::Clone {
-                input,
-                original_path,
-                is_folder,
-            } => {
-                assert_eq!(input, "file.txt");
-                assert_eq!(original_path, "file.txt");
-                assert!(!is_folder);
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+    }
+
+    #[tokio::test]
+    async fn oversized_folder_delete_waits_for_confirmation() {
+        use futures::StreamExt;
+        use object_store::{ObjectStore, ObjectStoreExt};
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for name in ["big/a.csv", "big/b.csv", "big/c.csv"] {
+            store
+                .put(&object_store::path::Path::from(name), vec![0u8; 4].into())
+                .await
+                .unwrap();
+        }
+
+        let mut app = test_app();
+        app.config.limits.warn_file_count = 2;
+        app.session = Session::Browsing(browsing_state(store.clone()));
+        app.modal = Modal::DeleteConfirm {
+            input: "big".to_string(),
+            target_path: "big/".to_string(),
+            target_name: "big".to_string(),
+            is_folder: true,
+            size: None,
+            plan: None,
+        };
+
+        app.execute_delete().await.unwrap();
+        match &app.modal {
+            Modal::LargeOperationWarning { operation, plan } => {
+                assert!(
+                    matches!(operation, PendingOperation::Delete { target_path } if target_path == "big/")
+                );
+                assert_eq!(plan.blobs.len(), 3);
+                assert_eq!(plan.total_bytes(), 12);
             }
-            _ => panic!("Expected clone dialog modal"),
+            other => panic!("Expected large operation warning, got {other:?}"),
         }
+
+        // Declining leaves every blob in place
+        app.handle_large_operation_key_event(KeyEvent::from(KeyCode::Char('n')))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+        let remaining: Vec<_> = store.list(None).collect().await;
+        assert_eq!(remaining.len(), 3);
     }
 
-    #[test]
-    fn open_delete_dialog_sets_modal_data_for_folder() {
+    #[tokio::test]
+    async fn oversized_folder_move_runs_after_confirmation() {
+        use futures::StreamExt;
+        use object_store::{ObjectStore, ObjectStoreExt};
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        let names: Vec<String> = (0..40).map(|i| format!("raw/part-{i:02}.csv")).collect();
+        for name in &names {
+            store
+                .put(
+                    &object_store::path::Path::from(name.as_str()),
+                    vec![0u8; 4].into(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut app = test_app();
+        app.config.limits.warn_file_count = 10;
+        app.session = Session::Browsing(browsing_state(store.clone()));
+
+        app.execute_move("raw/", "curated/2024/", true)
+            .await
+            .unwrap();
+        assert!(matches!(
+            &app.modal,
+            Modal::LargeOperationWarning {
+                operation: PendingOperation::Move { .. },
+                plan,
+            } if plan.blobs.len() == 40
+        ));
+        app.handle_large_operation_key_event(KeyEvent::from(KeyCode::Char('y')))
+            .await
+            .unwrap();
+        assert!(matches!(app.async_op, AsyncOp::Moving(_)));
+        finish_move(&mut app).await;
+        assert!(app.error_message.is_none(), "{:?}", app.error_message);
+
+        let mut moved: Vec<String> = store
+            .list(None)
+            .map(|meta| meta.unwrap().location.to_string())
+            .collect()
+            .await;
+        moved.sort();
+        let expected: Vec<String> = names
+            .iter()
+            .map(|name| name.replace("raw/", "curated/2024/"))
+            .collect();
+        assert_eq!(moved, expected);
+    }
+
+    #[tokio::test]
+    async fn upload_file_puts_blob_with_content_type_at_current_prefix() {
+        use object_store::{Attribute, ObjectStoreExt};
+
+        let local_dir = tempfile::tempdir().unwrap();
+        let local_file = local_dir.path().join("report.json");
+        std::fs::write(&local_file, b"{}").unwrap();
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
         let mut app = test_app();
         app.session = Session::Browsing(BrowsingState {
-            object_store: std::sync::Arc::new(object_store::memory::InMemory::new()),
-            current_path: String::new(),
-            files: vec![format!("{folder} logs", folder = app.icons.folder)],
-            file_items: vec![super::FileItem {
-                display_name: format!("{folder} logs", folder = app.icons.folder),
-                actual_name: "logs".to_string(),
-                kind: super::EntryKind::Folder,
-                size: None,
-                last_modified: None,
-                created: None,
-            }],
-            selected_index: 0,
+            current_path: "data/".to_string(),
+            ..browsing_state(store.clone())
         });
 
-        app.open_delete_dialog();
+        app.start_upload(&local_file).unwrap();
+        assert!(matches!(app.async_op, AsyncOp::Uploading(_)));
+        finish_upload(&mut app).await;
+
+        let result = store
+            .get(&object_store::path::Path::from("data/report.json"))
+            .await
+            .unwrap();
+        assert_eq!(
+            result
+                .attributes
+                .get(&Attribute::ContentType)
+                .map(AsRef::as_ref),
+            Some("application/json")
+        );
+        assert!(matches!(app.async_op, AsyncOp::None));
+        assert_eq!(app.browsing().unwrap().file_items.len(), 1);
+        assert_eq!(
+            app.success_message.as_deref(),
+            Some("Uploaded report.json to data/report.json")
+        );
+    }
 
-        match app.modal {
-            Modal::DeleteConfirm {
-                input,
-                target_path,
-                target_name,
-                is_folder,
-            } => {
-                assert_eq!(input, "");
-                assert_eq!(target_path, "logs/");
-                assert_eq!(target_name, "logs");
-                assert!(is_folder);
+    #[tokio::test]
+    async fn cached_fetch_serves_repeat_reads_from_disk() {
+        use object_store::{ObjectStore, ObjectStoreExt};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let cache_dir = tmp.path().join("cache");
+
+        let store: std::sync::Arc<dyn ObjectStore> =
+            std::sync::Arc::new(object_store::memory::InMemory::new());
+        let path = object_store::path::Path::from("data/a.csv");
+        store
+            .put(&path, b"a,b\n1,2\n".to_vec().into())
+            .await
+            .unwrap();
+        let e_tag = store.head(&path).await.unwrap().e_tag;
+
+        let mut app = test_app();
+        app.cache = Some(blobrs::cache::BlobCache::new(cache_dir.clone(), 1024));
+
+        let first = app
+            .fetch_bytes_cached(&store, &path, None, None, None)
+            .await
+            .unwrap();
+
+        // With the blob gone from the store, the same ETag is still served from disk
+        store.delete(&path).await.unwrap();
+        let second = app
+            .fetch_bytes_cached(&store, &path, None, None, e_tag.as_deref())
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn folder_download_runs_as_job_shown_in_jobs_panel() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for name in ["logs/a.txt", "logs/sub/b.txt"] {
+            store
+                .put(
+                    &object_store::path::Path::from(name),
+                    b"hello".to_vec().into(),
+                )
+                .await
+                .unwrap();
+        }
+        let tmp = tempfile::tempdir().unwrap();
+        let destination = tmp.path().join("logs");
+
+        let mut app = test_app();
+        app.session = Session::Browsing(browsing_state(store));
+
+        let plan = app.plan_folder("logs/").await.unwrap();
+        app.start_folder_download("logs", &destination, plan)
+            .unwrap();
+        assert_eq!(app.jobs.len(), 1);
+        for _ in 0..200 {
+            if app.jobs[0].summary().done == 2 {
+                break;
             }
-            _ => panic!("Expected delete confirm modal"),
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         }
+        assert_eq!(
+            std::fs::read_to_string(destination.join("logs").join("sub").join("b.txt")).unwrap(),
+            "hello"
+        );
+
+        app.open_jobs_panel();
+        app.handle_jobs_key_event(KeyEvent::from(KeyCode::Enter));
+        app.handle_jobs_key_event(KeyEvent::from(KeyCode::Down));
+        assert!(matches!(
+            app.modal,
+            Modal::Jobs {
+                job_index: 0,
+                file_index: Some(1)
+            }
+        ));
+        app.handle_jobs_key_event(KeyEvent::from(KeyCode::Esc));
+        assert!(matches!(
+            app.modal,
+            Modal::Jobs {
+                file_index: None,
+                ..
+            }
+        ));
+        app.handle_jobs_key_event(KeyEvent::from(KeyCode::Char('x')));
+        assert!(app.jobs.is_empty());
+        assert!(matches!(app.modal, Modal::None));
     }
 
-    #[test]
-    fn show_download_picker_sets_modal() {
+    #[tokio::test]
+    async fn operations_are_recorded_in_the_history_panel() {
+        use blobrs::audit::{AuditAction, Outcome};
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(
+                &object_store::path::Path::from("a.csv"),
+                b"x".to_vec().into(),
+            )
+            .await
+            .unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let destination = tmp.path().join("downloads");
+
         let mut app = test_app();
         app.session = Session::Browsing(BrowsingState {
-            object_store: std::sync::Arc::new(object_store::memory::InMemory::new()),
-            current_path: String::new(),
-            files: vec![format!("{file} report.csv", file = app.icons.file)],
-            file_items: Vec::new(),
-            selected_index: 0,
+            container_name: "data".to_string(),
+            ..browsing_state(store)
         });
 
-        app.show_download_picker();
-
-        match app.modal {
-            Modal::DownloadPicker { destination } => {
-                assert!(destination.is_none());
+        app.create_folder("logs").await.unwrap();
+        app.start_delete("a.csv", None).unwrap();
+        assert!(app.is_deleting());
+        finish_delete(&mut app).await;
+        let plan = app.plan_folder("logs/").await.unwrap();
+        app.start_folder_download("logs", &destination, plan)
+            .unwrap();
+
+        let entries = app.audit.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].action, AuditAction::CreateFolder);
+        assert_eq!(entries[0].target, "data/logs");
+        assert_eq!(entries[1].action, AuditAction::Delete);
+        assert_eq!(entries[1].outcome, Outcome::Succeeded);
+        assert_eq!(entries[2].action, AuditAction::Download);
+        assert_eq!(entries[2].outcome, Outcome::Running);
+
+        // The job's outcome is filled in on a later tick
+        for _ in 0..200 {
+            app.tick();
+            if app.audit.entries()[2].outcome != Outcome::Running {
+                break;
             }
-            _ => panic!("Expected download picker modal"),
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         }
+        assert_eq!(app.audit.entries()[2].outcome, Outcome::Succeeded);
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT))
+            .await
+            .unwrap();
+        assert!(app.ui.show_audit_log);
     }
 
     #[test]
@@ -2768,9 +11364,13 @@ mod tests {
         app.containers = vec![
             super::ContainerInfo {
                 name: "alpha".to_string(),
+                last_modified: None,
+                recent: false,
             },
             super::ContainerInfo {
                 name: "beta".to_string(),
+                last_modified: None,
+                recent: false,
             },
         ];
         app.all_containers = app.containers.clone();
@@ -2796,13 +11396,7 @@ mod tests {
     #[test]
     fn file_search_filters_and_exit_restores() {
         let mut app = test_app();
-        app.session = Session::Browsing(BrowsingState {
-            object_store: std::sync::Arc::new(object_store::memory::InMemory::new()),
-            current_path: String::new(),
-            files: Vec::new(),
-            file_items: Vec::new(),
-            selected_index: 0,
-        });
+        app.session = Session::Browsing(browsing_state(Arc::new(InMemory::new())));
         let file_items = vec![
             super::FileItem {
                 display_name: "file_a".to_string(),
@@ -2811,6 +11405,9 @@ mod tests {
                 size: None,
                 last_modified: None,
                 created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
             },
             super::FileItem {
                 display_name: "file_b".to_string(),
@@ -2819,6 +11416,9 @@ mod tests {
                 size: None,
                 last_modified: None,
                 created: None,
+                version_id: None,
+                deleted: false,
+                used_size: None,
             },
         ];
         if let Session::Browsing(state) = &mut app.session {
@@ -2847,6 +11447,748 @@ mod tests {
         assert!(matches!(app.search, Search::Inactive));
     }
 
+    #[test]
+    fn tab_switches_the_search_between_fuzzy_glob_and_regex() {
+        let mut app = test_app();
+        app.containers = ["raw-2024", "raw-2023", "curated"]
+            .into_iter()
+            .map(|name| ContainerInfo {
+                name: name.to_string(),
+                last_modified: None,
+                recent: false,
+            })
+            .collect();
+        app.all_containers = app.containers.clone();
+        app.enter_container_search_mode();
+        let press = |app: &mut App, code| {
+            app.handle_container_search_key_event(KeyEvent::from(code))
+                .unwrap();
+        };
+        for c in "r*3".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Tab);
+        let names =
+            |app: &App| -> Vec<String> { app.containers.iter().map(|c| c.name.clone()).collect() };
+        assert_eq!(app.ui.filter_mode, FilterMode::Glob);
+        assert_eq!(names(&app), ["raw-2023"]);
+
+        // An invalid regex shows everything rather than nothing
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('('));
+        assert_eq!(app.ui.filter_mode, FilterMode::Regex);
+        assert_eq!(app.containers.len(), 3);
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.ui.filter_mode, FilterMode::Fuzzy);
+    }
+
+    #[test]
+    fn file_type_picker_filters_through_the_search() {
+        let item = |name: &str| super::FileItem {
+            display_name: name.to_string(),
+            actual_name: name.to_string(),
+            kind: EntryKind::File,
+            size: Some(1),
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        let items = vec![
+            item("a.json"),
+            item("b.parquet"),
+            item("c.JSON"),
+            item("d.csv"),
+            item("README"),
+        ];
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            files: items.iter().map(|i| i.display_name.clone()).collect(),
+            file_items: items,
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+        let press = |app: &mut App, code| app.handle_extensions_key_event(KeyEvent::from(code));
+
+        app.open_extension_picker();
+        let Modal::Extensions { counts, .. } = &app.modal else {
+            panic!("the picker is open");
+        };
+        assert_eq!(
+            counts,
+            &[
+                ("json".to_string(), 2),
+                ("csv".to_string(), 1),
+                ("parquet".to_string(), 1)
+            ]
+        );
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.file_search_query(), Some("ext:json,parquet"));
+        let names = |app: &App| app.browsing().unwrap().files.clone();
+        assert_eq!(names(&app), ["a.json", "b.parquet", "c.JSON"]);
+
+        // Reopened, it counts the whole folder and shows what is picked
+        app.open_extension_picker();
+        let Modal::Extensions { counts, shown, .. } = &app.modal else {
+            panic!("the picker is open");
+        };
+        assert_eq!(counts.len(), 3);
+        assert_eq!(shown.len(), 2);
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.is_searching_files());
+        assert_eq!(names(&app).len(), 5);
+    }
+
+    #[tokio::test]
+    async fn enter_on_a_file_opens_the_preview() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(
+                &object_store::path::Path::from("sales.csv"),
+                b"region,total\nnorth,12\n".to_vec().into(),
+            )
+            .await
+            .unwrap();
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            ..browsing_state(store)
+        });
+        app.refresh_files().await.unwrap();
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        assert!(app.ui.show_preview);
+        let Some(PreviewData::Table(table)) = &app.preview_data else {
+            panic!("expected a table, got {:?}", app.preview_data);
+        };
+        assert_eq!(table.headers, ["region", "total"]);
+    }
+
+    #[tokio::test]
+    async fn preview_pane_follows_the_selection() {
+        use crate::preview_pane::{PaneContent, SETTLE_TICKS};
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for (name, content) in [("a.csv", "x,y\n1,2\n"), ("b.txt", "hello\n")] {
+            store
+                .put(
+                    &object_store::path::Path::from(name),
+                    content.as_bytes().to_vec().into(),
+                )
+                .await
+                .unwrap();
+        }
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            ..browsing_state(store)
+        });
+        app.refresh_files().await.unwrap();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('w')))
+            .await
+            .unwrap();
+        assert!(app.ui.preview_pane);
+
+        let settle = async |app: &mut App| {
+            for _ in 0..SETTLE_TICKS + 100 {
+                app.tick();
+                if !matches!(app.preview_pane.content(), PaneContent::Loading) {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+        };
+        settle(&mut app).await;
+        assert!(matches!(
+            app.preview_pane.content(),
+            PaneContent::Ready(PreviewData::Table(table)) if table.headers == ["x", "y"]
+        ));
+
+        app.move_down();
+        app.tick();
+        assert!(matches!(app.preview_pane.content(), PaneContent::Loading));
+        settle(&mut app).await;
+        assert!(matches!(
+            app.preview_pane.content(),
+            PaneContent::Ready(PreviewData::Text(text)) if text.content.starts_with("hello")
+        ));
+    }
+
+    #[tokio::test]
+    async fn flattened_view_lists_every_blob_under_the_folder() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for name in [
+            "data/x.csv",
+            "data/2024/06/y.csv",
+            "data/2024/z.csv",
+            "other.csv",
+        ] {
+            store
+                .put(&object_store::path::Path::from(name), vec![0u8; 4].into())
+                .await
+                .unwrap();
+        }
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            current_path: "data/".to_string(),
+            ..browsing_state(store)
+        });
+        let names = |app: &App| -> Vec<String> {
+            let state = app.browsing().unwrap();
+            state
+                .file_items
+                .iter()
+                .map(|item| item.actual_name.clone())
+                .collect()
+        };
+
+        app.toggle_flatten().await;
+        assert_eq!(names(&app), ["2024/06/y.csv", "2024/z.csv", "x.csv"]);
+        let state = app.browsing().unwrap();
+        assert!(
+            state
+                .file_items
+                .iter()
+                .all(|item| item.kind == EntryKind::File)
+        );
+        assert_eq!(
+            App::join_blob_path(&state.current_path, &state.file_items[0].actual_name),
+            "data/2024/06/y.csv"
+        );
+
+        app.toggle_flatten().await;
+        assert_eq!(names(&app), ["2024", "x.csv"]);
+    }
+
+    #[test]
+    fn parse_as_of_timestamp_accepts_common_formats() {
+        let expected = Utc.with_ymd_and_hms(2024, 6, 1, 13, 30, 0).unwrap();
+        assert_eq!(
+            super::parse_as_of_timestamp("2024-06-01 13:30"),
+            Some(expected)
+        );
+        assert_eq!(
+            super::parse_as_of_timestamp("2024-06-01T13:30:00Z"),
+            Some(expected)
+        );
+        assert_eq!(
+            super::parse_as_of_timestamp("2024-06-01"),
+            Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).single()
+        );
+        assert_eq!(super::parse_as_of_timestamp("last tuesday"), None);
+    }
+
+    #[tokio::test]
+    async fn time_travel_view_blocks_destructive_dialogs() {
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            files: vec!["file.txt".to_string()],
+            file_items: vec![super::FileItem {
+                display_name: "file.txt".to_string(),
+                actual_name: "file.txt".to_string(),
+                kind: super::EntryKind::File,
+                size: None,
+                last_modified: None,
+                created: None,
+                version_id: Some("2024-06-01T00:00:00.0000000Z".to_string()),
+                deleted: false,
+                used_size: None,
+            }],
+            as_of: Utc.with_ymd_and_hms(2024, 6, 2, 0, 0, 0).single(),
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+
+        app.open_delete_dialog().await.unwrap();
+        assert!(matches!(app.modal, Modal::None));
+        app.open_clone_dialog();
+        assert!(matches!(app.modal, Modal::None));
+        assert!(app.error_message.is_some());
+    }
+
+    #[test]
+    fn notes_attach_to_highlighted_folder_or_current_path() {
+        let mut app = test_app();
+        let folder = |name: &str| super::FileItem {
+            display_name: name.to_string(),
+            actual_name: name.to_string(),
+            kind: super::EntryKind::Folder,
+            size: None,
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        };
+        let file = super::FileItem {
+            kind: super::EntryKind::File,
+            ..folder("a.csv")
+        };
+        app.session = Session::Browsing(BrowsingState {
+            container_name: "raw".to_string(),
+            current_path: "landing/".to_string(),
+            files: vec!["logs".to_string(), "a.csv".to_string()],
+            file_items: vec![folder("logs"), file],
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+
+        app.open_note_editor();
+        for c in "do not delete".chars() {
+            app.handle_edit_note_key_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_edit_note_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(app.modal, Modal::None));
+        assert_eq!(
+            app.current_note(),
+            Some(("test-account/raw/landing/logs".to_string(), "do not delete"))
+        );
+
+        // A highlighted file shows the note of the folder being browsed
+        app.browsing_mut().unwrap().selected_index = 1;
+        assert_eq!(app.current_note(), None);
+        app.state
+            .set_note("test-account/raw/landing", "owned by team X");
+        assert_eq!(
+            app.current_note().map(|(_, note)| note),
+            Some("owned by team X")
+        );
+
+        // Saving an empty note removes it
+        app.browsing_mut().unwrap().selected_index = 0;
+        app.open_note_editor();
+        for _ in 0.."do not delete".len() {
+            app.handle_edit_note_key_event(KeyEvent::from(KeyCode::Backspace));
+        }
+        app.handle_edit_note_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(
+            !app.state
+                .notes
+                .contains_key("test-account/raw/landing/logs")
+        );
+    }
+
+    #[test]
+    fn clipboard_path_strips_quotes_and_file_urls() {
+        assert_eq!(
+            super::clipboard_path("  /tmp/out.csv\n"),
+            Some(PathBuf::from("/tmp/out.csv"))
+        );
+        assert_eq!(
+            super::clipboard_path("\n\"/tmp/my report.pdf\""),
+            Some(PathBuf::from("/tmp/my report.pdf"))
+        );
+        assert_eq!(
+            super::clipboard_path("'/tmp/a.json'"),
+            Some(PathBuf::from("/tmp/a.json"))
+        );
+        assert_eq!(
+            super::clipboard_path("file:///tmp/a.json"),
+            Some(PathBuf::from("/tmp/a.json"))
+        );
+        assert_eq!(
+            super::clipboard_path("file:///tmp/my%20file.csv"),
+            Some(PathBuf::from("/tmp/my file.csv"))
+        );
+        assert_eq!(super::clipboard_path("  \n "), None);
+    }
+
+    #[tokio::test]
+    async fn sync_preview_switches_modes_and_applies() {
+        use futures::StreamExt;
+        use object_store::{ObjectStore, ObjectStoreExt};
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(
+                &object_store::path::Path::from("in/a.txt"),
+                b"a".to_vec().into(),
+            )
+            .await
+            .unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let local_dir = tmp.path().to_path_buf();
+        std::fs::write(local_dir.join("b.txt"), b"b").unwrap();
+
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            current_path: "in/".to_string(),
+            ..browsing_state(store.clone())
+        });
+
+        app.open_sync_preview(local_dir.clone()).await.unwrap();
+        let Modal::SyncPreview { plan, .. } = &app.modal else {
+            panic!("Expected sync preview");
+        };
+        assert_eq!(plan.count(SyncAction::Upload), 1);
+        assert_eq!(plan.count(SyncAction::Download), 1);
+
+        // Push mode deletes the blob that is missing locally
+        app.handle_sync_preview_key_event(KeyEvent::from(KeyCode::Char('m')))
+            .await
+            .unwrap();
+        let Modal::SyncPreview { mode, plan, .. } = &app.modal else {
+            panic!("Expected sync preview");
+        };
+        assert_eq!(*mode, SyncMode::Push);
+        assert_eq!(plan.count(SyncAction::DeleteRemote), 1);
+
+        // A plan that deletes anything is confirmed before it runs
+        app.handle_sync_preview_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        assert!(matches!(
+            app.modal,
+            Modal::LargeOperationWarning {
+                operation: PendingOperation::Sync { .. },
+                ..
+            }
+        ));
+        app.handle_large_operation_key_event(KeyEvent::from(KeyCode::Char('y')))
+            .await
+            .unwrap();
+        assert!(matches!(app.modal, Modal::None));
+        assert!(app.error_message.is_none(), "{:?}", app.error_message);
+        let names: Vec<String> = store
+            .list(None)
+            .map(|meta| meta.unwrap().location.to_string())
+            .collect()
+            .await;
+        assert_eq!(names, vec!["in/b.txt".to_string()]);
+    }
+
+    #[test]
+    fn paging_moves_through_continuation_tokens() {
+        let mut paging = Paging::default();
+        assert!(!paging.is_paged());
+        assert!(!paging.advance());
+        assert!(!paging.go_back());
+
+        paging.next_token = Some("page-2".to_string());
+        assert!(paging.is_paged());
+        assert!(paging.advance());
+        assert_eq!(paging.page_number(), 2);
+        assert_eq!(paging.tokens, vec!["page-2".to_string()]);
+        assert_eq!(paging.next_token, None);
+
+        paging.next_token = Some("page-3".to_string());
+        assert!(paging.advance());
+        assert_eq!(paging.page_number(), 3);
+        assert!(paging.go_back());
+        assert_eq!(paging.tokens.last().map(String::as_str), Some("page-2"));
+
+        // Listing the page again keeps to it
+        paging.next_token = Some("page-3".to_string());
+        paging.stop();
+        assert_eq!(paging.page_number(), 2);
+        assert_eq!(paging.next_token, None);
+
+        paging.reset();
+        assert_eq!(paging.page_number(), 1);
+        assert!(!paging.is_paged());
+    }
+
+    #[test]
+    fn later_pages_are_merged_in_order_keeping_the_selection() {
+        let item = |name: &str, deleted: bool| super::FileItem {
+            display_name: name.to_string(),
+            actual_name: name.to_string(),
+            kind: EntryKind::File,
+            size: Some(1),
+            last_modified: None,
+            created: None,
+            version_id: None,
+            deleted,
+            used_size: None,
+        };
+        let first_page = vec![
+            item("b.csv", false),
+            item("d.csv", false),
+            item("e.csv", true),
+        ];
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            files: first_page.iter().map(|i| i.display_name.clone()).collect(),
+            file_items: first_page,
+            selected_index: 1,
+            ..browsing_state(Arc::new(InMemory::new()))
+        });
+
+        // The live `e.csv` on a later page replaces its deleted entry
+        app.append_file_items(vec![item("a.csv", false), item("e.csv", false)]);
+        let state = app.browsing().unwrap();
+        let names: Vec<_> = state.files.iter().map(String::as_str).collect();
+        assert_eq!(names, ["a.csv", "b.csv", "d.csv", "e.csv"]);
+        assert!(state.file_items.iter().all(|i| !i.deleted));
+        assert_eq!(state.selected_index, 2, "d.csv stays selected");
+
+        // While searching, only matches are shown but every entry is kept for later
+        app.search = Search::Files {
+            query: "C.".to_string(),
+            all_files: names.iter().map(ToString::to_string).collect(),
+            all_file_items: app.browsing().unwrap().file_items.clone(),
+        };
+        app.apply_file_search("C.");
+        app.append_file_items(vec![item("c.csv", false), item("f.csv", false)]);
+        let names: Vec<_> = app.browsing().unwrap().files.clone();
+        assert_eq!(names, ["c.csv"]);
+        let Search::Files { all_files, .. } = &app.search else {
+            unreachable!()
+        };
+        assert_eq!(all_files.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn yanked_folder_is_pasted_into_current_folder() {
+        use futures::StreamExt;
+        use object_store::{ObjectStore, ObjectStoreExt};
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for path in ["src/data/a.txt", "src/data/sub/b.txt"] {
+            store
+                .put(&object_store::path::Path::from(path), b"x".to_vec().into())
+                .await
+                .unwrap();
+        }
+
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            current_path: "src/".to_string(),
+            ..browsing_state(store.clone())
+        });
+        app.refresh_files().await.unwrap();
+        app.yank_selected();
+        assert_eq!(
+            app.yanked.as_ref().map(|y| y.paths.clone()),
+            Some(vec!["src/data/".to_string()])
+        );
+
+        // Pasting back into the same folder would copy the folder onto itself
+        app.paste_yanked().await.unwrap();
+        assert!(app.error_message.is_some());
+
+        if let Some(state) = app.browsing_mut() {
+            state.current_path = "dst/".to_string();
+        }
+        app.paste_yanked().await.unwrap();
+        assert!(app.error_message.is_none(), "{:?}", app.error_message);
+
+        let mut names: Vec<String> = store
+            .list(Some(&object_store::path::Path::from("dst")))
+            .map(|meta| meta.unwrap().location.to_string())
+            .collect()
+            .await;
+        names.sort();
+        assert_eq!(names, vec!["dst/data/a.txt", "dst/data/sub/b.txt"]);
+    }
+
+    #[tokio::test]
+    async fn ctrl_slash_searches_everything_under_the_folder_as_results_stream_in() {
+        use object_store::ObjectStoreExt;
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for path in ["raw/2024/q1/sales.csv", "raw/orders.csv", "other/sales.csv"] {
+            store
+                .put(&object_store::path::Path::from(path), b"x".to_vec().into())
+                .await
+                .unwrap();
+        }
+        let mut app = test_app();
+        app.session = Session::Browsing(BrowsingState {
+            current_path: "raw/".to_string(),
+            ..browsing_state(store)
+        });
+
+        // Ctrl-/ arrives as Ctrl-7 from most terminals
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('7'), KeyModifiers::CONTROL))
+            .await
+            .unwrap();
+        for c in "sales".chars() {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+                .await
+                .unwrap();
+        }
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter))
+            .await
+            .unwrap();
+        while matches!(
+            &app.modal,
+            Modal::GlobalSearch {
+                running: Some(_),
+                ..
+            }
+        ) {
+            tokio::task::yield_now().await;
+            app.tick();
+        }
+
+        let Modal::GlobalSearch {
+            results: Some(results),
+            scope,
+            ..
+        } = &app.modal
+        else {
+            panic!("expected search results");
+        };
+        assert_eq!(*scope, SearchScope::Folder);
+        let paths: Vec<&str> = results.hits.iter().map(|hit| hit.path.as_str()).collect();
+        assert_eq!(paths, ["raw/2024/q1/sales.csv"]);
+        assert_eq!(results.hits[0].container, "test-container");
+    }
+
+    #[tokio::test]
+    async fn f5_and_f6_copy_and_move_into_the_other_pane() {
+        use futures::StreamExt;
+        use object_store::{ObjectStore, ObjectStoreExt};
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for path in ["src/a.txt", "src/b.txt", "dst/keep.txt"] {
+            store
+                .put(&object_store::path::Path::from(path), b"x".to_vec().into())
+                .await
+                .unwrap();
+        }
+        let browsing = |path: &str| {
+            Session::Browsing(BrowsingState {
+                current_path: path.to_string(),
+                ..browsing_state(store.clone())
+            })
+        };
+        let mut app = test_app();
+        app.session = browsing("dst/");
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('|')))
+            .await
+            .unwrap();
+        assert!(app.shows_two_panes());
+        app.session = browsing("src/");
+        app.refresh_files().await.unwrap();
+
+        app.handle_key_event(KeyEvent::from(KeyCode::F(5)))
+            .await
+            .unwrap();
+        assert!(app.error_message.is_none(), "{:?}", app.error_message);
+        app.move_selection(super::Motion::Down, None);
+        app.handle_key_event(KeyEvent::from(KeyCode::F(6)))
+            .await
+            .unwrap();
+        finish_move(&mut app).await;
+        assert!(app.error_message.is_none(), "{:?}", app.error_message);
+
+        let mut names: Vec<String> = store
+            .list(None)
+            .map(|meta| meta.unwrap().location.to_string())
+            .collect()
+            .await;
+        names.sort();
+        assert_eq!(
+            names,
+            ["dst/a.txt", "dst/b.txt", "dst/keep.txt", "src/a.txt"]
+        );
+        // The other pane shows what arrived
+        let Some((_, Tab { session, .. })) = app.tabs.neighbour() else {
+            panic!("expected a second pane");
+        };
+        let Session::Browsing(other) = session else {
+            panic!("expected the other pane to show a folder");
+        };
+        assert_eq!(other.files.len(), 3);
+    }
+
+    #[test]
+    fn recent_containers_come_first() {
+        let container = |name: &str, day: u32| super::ContainerInfo {
+            name: name.to_string(),
+            last_modified: Utc.with_ymd_and_hms(2024, 6, day, 0, 0, 0).single(),
+            recent: false,
+        };
+        let containers = vec![
+            container("alpha", 3),
+            container("beta", 1),
+            container("gamma", 2),
+            container("delta", 4),
+        ];
+        let names = |ordered: &[super::ContainerInfo]| {
+            ordered.iter().map(|c| c.name.clone()).collect::<Vec<_>>()
+        };
+
+        let ordered = super::order_containers(containers.clone(), &["gamma", "beta"], false);
+        assert_eq!(names(&ordered), vec!["gamma", "beta", "alpha", "delta"]);
+        assert!(ordered[0].recent && ordered[1].recent && !ordered[2].recent);
+
+        let ordered = super::order_containers(containers, &["gamma"], true);
+        assert_eq!(names(&ordered), vec!["gamma", "delta", "alpha", "beta"]);
+    }
+
+    #[tokio::test]
+    async fn move_folder_copies_then_deletes_and_rolls_back_on_conflict() {
+        use futures::StreamExt;
+        use object_store::{ObjectStore, ObjectStoreExt};
+
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        for path in ["old/a.txt", "old/b.txt", "taken/b.txt"] {
+            store
+                .put(&object_store::path::Path::from(path), b"x".to_vec().into())
+                .await
+                .unwrap();
+        }
+        let list = |store: std::sync::Arc<object_store::memory::InMemory>| async move {
+            let mut names: Vec<String> = store
+                .list(None)
+                .map(|meta| meta.unwrap().location.to_string())
+                .collect()
+                .await;
+            names.sort();
+            names
+        };
+
+        let mut app = test_app();
+        app.session = Session::Browsing(browsing_state(store.clone()));
+
+        // b.txt already exists at the destination, so nothing moves
+        app.execute_move("old/", "taken/", true).await.unwrap();
+        finish_move(&mut app).await;
+        assert!(
+            app.error_message
+                .as_deref()
+                .unwrap()
+                .contains("rolled back")
+        );
+        assert_eq!(
+            list(store.clone()).await,
+            vec!["old/a.txt", "old/b.txt", "taken/b.txt"]
+        );
+
+        app.error_message = None;
+        app.execute_move("old/", "new/", true).await.unwrap();
+        finish_move(&mut app).await;
+        assert!(app.error_message.is_none(), "{:?}", app.error_message);
+        assert_eq!(
+            list(store.clone()).await,
+            vec!["new/a.txt", "new/b.txt", "taken/b.txt"]
+        );
+
+        app.execute_move("new/a.txt", "new/renamed.txt", false)
+            .await
+            .unwrap();
+        finish_move(&mut app).await;
+        assert_eq!(
+            list(store).await,
+            vec!["new/b.txt", "new/renamed.txt", "taken/b.txt"]
+        );
+    }
+
     #[test]
     fn entry_kind_folder_detection() {
         assert!(matches!(EntryKind::Folder, EntryKind::Folder));
@@ -2868,6 +12210,7 @@ mod tests {
             total_rows: 1,
             truncated: false,
             file_type: PreviewFileType::Parquet,
+            delimiter: None,
         });
         app.parquet_schema_data = Some(ParquetSchemaPreview {
             fields: vec!["a: Int32".to_string()],
@@ -2920,6 +12263,7 @@ mod tests {
             total_rows: 1,
             truncated: false,
             file_type: PreviewFileType::Csv,
+            delimiter: None,
         }));
 
         for _ in 0..10 {
@@ -2929,134 +12273,274 @@ mod tests {
         assert_eq!(app.preview_scroll.1, 2);
     }
 
-    proptest! {
-        #[test]
-        fn apply_sort_preserves_items_and_folders_stay_first(
-            items in prop::collection::vec(arb_file_item(), 0..40),
-            criteria in arb_sort_criteria(),
-        ) {
-            let mut sorted = items.clone();
-            App::sort_file_items_static(&mut sorted, criteria);
-
-            prop_assert_eq!(sorted.len(), items.len());
-
-            type ItemKey = (
-                String,
-                String,
-                bool,
-                Option<u64>,
-                Option<chrono::DateTime<chrono::Utc>>,
-                Option<chrono::DateTime<chrono::Utc>>,
-            );
+    #[tokio::test]
+    async fn preview_pages_and_searches() {
+        let mut app = test_app();
+        let content = (0..100)
+            .map(|line| {
+                if line % 30 == 5 {
+                    format!("{line} ERROR")
+                } else {
+                    format!("{line} ok")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        app.ui.show_preview = true;
+        app.preview_data = Some(PreviewData::Text(TextPreview {
+            content,
+            total_lines: 100,
+            truncated: false,
+            extension: "LOG".to_string(),
+            highlighted: None,
+        }));
+        app.preview_viewport.borrow_mut().rows = 20;
+        let press = async |app: &mut App, code: KeyCode| {
+            app.handle_key_event(KeyEvent::from(code)).await.unwrap();
+        };
 
-            let before = items.iter().fold(BTreeMap::<ItemKey, usize>::new(), |mut acc, item| {
-                *acc.entry((
-                    item.display_name.clone(),
-                    item.actual_name.clone(),
-                    matches!(item.kind, EntryKind::Folder),
-                    item.size,
-                    item.last_modified,
-                    item.created,
-                )).or_default() += 1_usize;
-                acc
-            });
+        press(&mut app, KeyCode::PageDown).await;
+        assert_eq!(app.preview_selected_row, 20);
+        press(&mut app, KeyCode::End).await;
+        assert_eq!(app.preview_selected_row, 99);
+        press(&mut app, KeyCode::PageUp).await;
+        assert_eq!(app.preview_selected_row, 79);
+
+        // Typing moves to the first match at or after where the search started
+        press(&mut app, KeyCode::Char('/')).await;
+        for c in "error".chars() {
+            press(&mut app, KeyCode::Char(c)).await;
+        }
+        assert_eq!(app.preview_selected_row, 95);
+        press(&mut app, KeyCode::Enter).await;
+        press(&mut app, KeyCode::Char('n')).await;
+        assert_eq!(app.preview_selected_row, 5);
+        press(&mut app, KeyCode::Char('N')).await;
+        assert_eq!(app.preview_selected_row, 95);
+        press(&mut app, KeyCode::Char('N')).await;
+        assert_eq!(app.preview_selected_row, 65);
+
+        // Esc clears the search, then closes the preview
+        press(&mut app, KeyCode::Esc).await;
+        assert!(app.preview_search.is_none());
+        assert!(app.ui.show_preview);
+
+        // Cancelling a search goes back to where it started
+        press(&mut app, KeyCode::Char('/')).await;
+        press(&mut app, KeyCode::Char('8')).await;
+        press(&mut app, KeyCode::Char('0')).await;
+        assert_eq!(app.preview_selected_row, 80);
+        press(&mut app, KeyCode::Esc).await;
+        assert_eq!(app.preview_selected_row, 65);
+        assert!(app.preview_search.is_none());
+
+        press(&mut app, KeyCode::Char('/')).await;
+        press(&mut app, KeyCode::Char('z')).await;
+        press(&mut app, KeyCode::Enter).await;
+        assert_eq!(app.error_message.as_deref(), Some("No match for `z`"));
+    }
 
-            let after = sorted
-                .iter()
-                .fold(BTreeMap::<ItemKey, usize>::new(), |mut acc, item| {
-                *acc.entry((
-                    item.display_name.clone(),
-                    item.actual_name.clone(),
-                    matches!(item.kind, EntryKind::Folder),
-                    item.size,
-                    item.last_modified,
-                    item.created,
-                ))
-                .or_default() += 1_usize;
+    #[test]
+    fn a_table_match_off_screen_scrolls_into_view() {
+        let mut app = test_app();
+        let row = |cells: [&str; 4]| cells.map(str::to_string).to_vec();
+        app.ui.show_preview = true;
+        app.preview_data = Some(PreviewData::Table(TablePreview {
+            headers: row(["a", "b", "c", "d"]),
+            column_types: None,
+            rows: vec![row(["1", "2", "3", "4"]), row(["5", "6", "7", "needle"])],
+            total_rows: 2,
+            truncated: false,
+            file_type: PreviewFileType::Csv,
+            delimiter: None,
+        }));
+        app.preview_viewport.borrow_mut().columns = 0..2;
+        app.preview_search = Some(PreviewSearch {
+            query: "needle".to_string(),
+            typing: false,
+            origin: 0,
+        });
+        app.next_preview_match(false);
+        assert_eq!(app.preview_selected_row, 1);
+        assert_eq!(app.preview_scroll.1, 3);
+    }
+
+    #[test]
+    fn timeouts_are_named_in_error_messages() {
+        let timed_out = color_eyre::Report::from(object_store::Error::Generic {
+            store: "MicrosoftAzure",
+            source: Box::new(object_store::client::HttpError::new(
+                object_store::client::HttpErrorKind::Timeout,
+                std::io::Error::from(std::io::ErrorKind::Other),
+            )),
+        });
+        assert!(describe_error(&timed_out).starts_with("Request timed out: "));
+
+        let already_named = color_eyre::Report::from(BlobrsError::Timeout("GET".to_string()));
+        assert_eq!(describe_error(&already_named), "Request timed out: GET");
+
+        let denied = color_eyre::Report::from(BlobrsError::Auth("bad key".to_string()));
+        assert_eq!(describe_error(&denied), "Authentication failed: bad key");
+    }
+
+    proptest! {
+            #[test]
+            fn apply_sort_preserves_items_and_folders_stay_first(
+                items in prop::collection::vec(arb_file_item(), 0..40),
+                criteria in arb_sort_criteria(),
+                reversed in any::<bool>(),
+            ) {
+                let mut sorted = items.clone();
+                App::sort_file_items_static(&mut sorted, SortOrder { criteria, reversed });
+
+                prop_assert_eq!(sorted.len(), items.len());
+
+                type ItemKey = (
+                    String,
+                    String,
+                    bool,
+                    Option<u64>,
+                    Option<chrono::DateTime<chrono::Utc>>,
+                    Option<chrono::DateTime<chrono::Utc>>,
+                );
+
+                let before = items.iter().fold(BTreeMap::<ItemKey, usize>::new(), |mut acc, item| {
+                    *acc.entry((
+                        item.display_name.clone(),
+                        item.actual_name.clone(),
+                        matches!(item.kind, EntryKind::Folder),
+                        item.size,
+                        item.last_modified,
+                        item.created,
+                    )).or_default() += 1_usize;
                     acc
                 });
 
-            prop_assert_eq!(before, after);
-
-            let first_file = sorted.iter().position(|item| item.kind == EntryKind::File);
-            if let Some(first_file) = first_file {
-                prop_assert!(sorted[first_file..].iter().all(|item| item.kind == EntryKind::File));
+                let after = sorted
+                    .iter()
+                    .fold(BTreeMap::<ItemKey, usize>::new(), |mut acc, item| {
+                    *acc.entry((
+                        item.display_name.clone(),
+                        item.actual_name.clone(),
+                        matches!(item.kind, EntryKind::Folder),
+                        item.size,
+                        item.last_modified,
+                        item.created,
+                    ))
+                    .or_default() += 1_usize;
+                        acc
+                    });
+
+                prop_assert_eq!(before, after);
+
+                let first_file = sorted.iter().position(|item| item.kind == EntryKind::File);
+                if let Some(first_file) = first_file {
+                    prop_assert!(sorted[first_file..].iter().all(|item| item.kind == EntryKind::File));
+                }
             }
-        }
 
-        #[test]
-        fn apply_file_search_keeps_subset_and_resets_selection(
-            items in prop::collection::vec(arb_file_item(), 0..30),
-            query in "[A-Za-z0-9_./-]{0,8}",
-        ) {
-            let mut app = test_app();
-            let all_files: Vec<String> = items.iter().map(|item| item.display_name.clone()).collect();
-
-            app.session = Session::Browsing(BrowsingState {
-                object_store: std::sync::Arc::new(object_store::memory::InMemory::new()),
-                current_path: String::new(),
-                files: all_files.clone(),
-                file_items: items.clone(),
-                selected_index: 5,
-            });
-            app.search = Search::Files {
-                query: query.clone(),
-                all_files: all_files.clone(),
-                all_file_items: items.clone(),
-            };
+            #[test]
+            fn apply_file_search_keeps_subset_and_selects_the_best_match(
+                items in prop::collection::vec(arb_file_item(), 0..30),
+                query in "[A-Za-z0-9_./-]{0,8}",
+            ) {
+                let mut app = test_app();
+                let all_files: Vec<String> = items.iter().map(|item| item.display_name.clone()).collect();
+
+                app.session = Session::Browsing(BrowsingState {
+    files: all_files.clone(),
+    file_items: items.clone(),
+    selected_index: 5,
+    ..browsing_state(Arc::new(InMemory::new()))
+    });
+                app.search = Search::Files {
+                    query: query.clone(),
+                    all_files: all_files.clone(),
+                    all_file_items: items.clone(),
+                };
 
-            app.apply_file_search(&query);
+                app.apply_file_search(&query);
 
-            let Session::Browsing(state) = &app.session else {
-                prop_assert!(false, "expected browsing session");
-                return Ok(());
-            };
+                let Session::Browsing(state) = &app.session else {
+                    prop_assert!(false, "expected browsing session");
+                    return Ok(());
+                };
 
-            prop_assert_eq!(state.selected_index, 0);
-            prop_assert_eq!(
-                state.files.clone(),
-                state.file_items.iter().map(|item| item.display_name.clone()).collect::<Vec<_>>()
-            );
+                // The selection starts on the best match
+                let best = state.file_items.get(state.selected_index).map(|item| fuzzy_match(&item.actual_name, &query).unwrap().score);
+                prop_assert!(state.file_items.iter().all(|item| Some(fuzzy_match(&item.actual_name, &query).unwrap().score) <= best));
+                prop_assert_eq!(
+                    state.files.clone(),
+                    state.file_items.iter().map(|item| item.display_name.clone()).collect::<Vec<_>>()
+                );
 
-            if query.is_empty() {
-                prop_assert_eq!(state.file_items.len(), items.len());
-            } else {
-                let lowered = query.to_lowercase();
-                prop_assert!(state.file_items.iter().all(|item| item.actual_name.to_lowercase().contains(&lowered)));
-                prop_assert!(state.file_items.len() <= items.len());
+                if query.is_empty() {
+                    prop_assert_eq!(state.file_items.len(), items.len());
+                } else {
+                    prop_assert!(state.file_items.iter().all(|item| fuzzy_match(&item.actual_name, &query).is_some()));
+                    prop_assert!(state.file_items.len() <= items.len());
+                }
             }
-        }
 
-        #[test]
-        fn preview_horizontal_scroll_stays_within_table_bounds(
-            cols in 0_usize..12,
-            moves in 0_usize..32,
-        ) {
-            let mut app = test_app();
-            app.preview_data = Some(PreviewData::Table(TablePreview {
-                headers: (0..cols).map(|i| format!("col{i}")).collect(),
-                column_types: None,
-                rows: if cols == 0 {
-                    Vec::new()
-                } else {
-                    vec![(0..cols).map(|i| format!("v{i}")).collect()]
-                },
-                total_rows: 1,
-                truncated: false,
-                file_type: PreviewFileType::Csv,
-            }));
+            #[test]
+            fn typing_narrows_to_the_same_entries_as_a_full_search(
+                items in prop::collection::vec(arb_file_item(), 0..30),
+                typed in "[A-Za-z0-9_./-]{0,4}",
+                more in "[A-Za-z0-9_./-]{1,4}",
+            ) {
+                let mut app = test_app();
+                let all_files: Vec<String> = items.iter().map(|item| item.display_name.clone()).collect();
+                app.session = Session::Browsing(BrowsingState {
+    files: all_files.clone(),
+    file_items: items.clone(),
+    ..browsing_state(Arc::new(InMemory::new()))
+    });
+                app.search = Search::Files {
+                    query: String::new(),
+                    all_files,
+                    all_file_items: items,
+                };
+                let query = format!("{typed}{more}");
 
-            for _ in 0..moves {
-                app.preview_scroll_right();
+                app.apply_file_search(&typed);
+                app.narrow_file_search(&query);
+                let narrowed = app.browsing().unwrap().files.clone();
+                app.apply_file_search(&query);
+
+                prop_assert_eq!(narrowed, app.browsing().unwrap().files.clone());
             }
 
-            prop_assert!(app.preview_scroll.1 <= cols.saturating_sub(1));
+            #[test]
+            fn preview_horizontal_scroll_stays_within_table_bounds(
+                cols in 0_usize..12,
+                moves in 0_usize..32,
+            ) {
+                let mut app = test_app();
+                app.preview_data = Some(PreviewData::Table(TablePreview {
+                    headers: (0..cols).map(|i| format!("col{i}")).collect(),
+                    column_types: None,
+                    rows: if cols == 0 {
+                        Vec::new()
+                    } else {
+                        vec![(0..cols).map(|i| format!("v{i}")).collect()]
+                    },
+                    total_rows: 1,
+                    truncated: false,
+                    file_type: PreviewFileType::Csv,
+                    delimiter: None,
+                }));
+
+                for _ in 0..moves {
+                    app.preview_scroll_right();
+                }
 
-            for _ in 0..moves {
-                app.preview_scroll_left();
-            }
+                prop_assert!(app.preview_scroll.1 <= cols.saturating_sub(1));
 
-            prop_assert_eq!(app.preview_scroll.1, 0);
+                for _ in 0..moves {
+                    app.preview_scroll_left();
+                }
+
+                prop_assert_eq!(app.preview_scroll.1, 0);
+            }
         }
-    }
 }
@@ -0,0 +1,683 @@
+//! Listing what is in a `.zip` or `.tar` blob for the preview, reading as little of it as
+//! possible: a zip lists its entries in the central directory at its end, and a tar has a
+//! header before each file, giving the file's size and so where the next header is.
+//!
+//! A compressed tar has no way to skip ahead, so only the headers in the start of it are
+//! listed.
+
+use std::io::Read;
+use std::ops::Range;
+
+use crate::error::BlobrsError;
+use crate::preview::{Compression, MAX_PREVIEW_ROWS, PreviewData, PreviewFileType, TablePreview};
+
+/// Bytes read from the end of a zip for its end of central directory record (64KB), enough
+/// for the record after the longest comment it can have
+pub const ZIP_TAIL_BYTES: usize = 64 * 1024 + 22;
+
+/// Most of a zip's central directory read (1MB), enough for thousands of entries
+pub const ZIP_DIRECTORY_BYTES: u64 = 1024 * 1024;
+
+/// Bytes of a tar read at a time from a header (64KB), taking in the headers of any small
+/// files after it
+pub const TAR_WINDOW_BYTES: u64 = 64 * 1024;
+
+/// Bytes of a compressed tar downloaded to list the headers in its start (4MB)
+pub const COMPRESSED_TAR_BYTES: u64 = 4 * 1024 * 1024;
+
+const TAR_BLOCK: usize = 512;
+
+/// The kinds of archive that can be listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    /// A tar, compressed or not
+    Tar(Option<Compression>),
+}
+
+impl ArchiveKind {
+    /// The kind of archive `name` is, going by its extensions.
+    #[must_use]
+    #[allow(clippy::case_sensitive_file_extension_comparisons)] // the name is lowercased
+    pub fn of(name: &str) -> Option<Self> {
+        let lower = name.to_ascii_lowercase();
+        if lower.ends_with(".zip") {
+            return Some(Self::Zip);
+        }
+        if lower.ends_with(".tgz") {
+            return Some(Self::Tar(Some(Compression::Gzip)));
+        }
+        if lower.ends_with(".tbz2") || lower.ends_with(".tbz") {
+            return Some(Self::Tar(Some(Compression::Bzip2)));
+        }
+        if lower.ends_with(".tzst") {
+            return Some(Self::Tar(Some(Compression::Zstd)));
+        }
+        let (compression, inner) = match Compression::of(&lower) {
+            Some((compression, inner)) => (Some(compression), inner),
+            None => (None, lower.as_str()),
+        };
+        inner.ends_with(".tar").then_some(Self::Tar(compression))
+    }
+}
+
+/// A file or folder in an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path within the archive; folders end in `/`
+    pub name: String,
+    /// Size once extracted
+    pub size: u64,
+    /// Size within the archive, for a zip
+    pub compressed_size: Option<u64>,
+}
+
+/// The entries of an archive as a table of their names and sizes. `total` is how many there
+/// are in all, and `truncated` whether some are missing from `entries`.
+#[must_use]
+pub fn archive_table(entries: Vec<ArchiveEntry>, total: usize, truncated: bool) -> PreviewData {
+    let compressed = entries.iter().any(|entry| entry.compressed_size.is_some());
+    let mut headers = vec!["Name".to_string(), "Size".to_string()];
+    if compressed {
+        headers.push("Compressed".to_string());
+    }
+    let rows = entries
+        .into_iter()
+        .map(|entry| {
+            let mut row = vec![entry.name, entry.size.to_string()];
+            if compressed {
+                row.push(
+                    entry
+                        .compressed_size
+                        .map_or_else(String::new, |size| size.to_string()),
+                );
+            }
+            row
+        })
+        .collect();
+    PreviewData::Table(TablePreview {
+        headers,
+        column_types: None,
+        rows,
+        total_rows: total,
+        truncated,
+        file_type: PreviewFileType::Archive,
+        delimiter: None,
+    })
+}
+
+/// Where a zip's central directory is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipDirectory {
+    /// Byte range of the directory in the archive
+    pub range: Range<u64>,
+    /// How many entries it lists
+    pub entries: u64,
+}
+
+/// Find a zip's central directory from `tail`, the end of an archive of `file_size` bytes,
+/// through the end of central directory record, and for a zip64 the record it points to.
+///
+/// # Errors
+///
+/// Returns [`BlobrsError::Format`] if `tail` holds no end of central directory record, a
+/// zip64's record is not where its locator points, or the directory runs past the end of the
+/// file.
+pub fn zip_directory(tail: &[u8], file_size: u64) -> Result<ZipDirectory, BlobrsError> {
+    const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+    const ZIP64_LOCATOR: u32 = 0x0706_4b50;
+    const ZIP64_END_OF_DIRECTORY: u32 = 0x0606_4b50;
+    let not_a_zip =
+        || BlobrsError::Format("Failed to read zip: no central directory found".to_string());
+
+    // The record is 22 bytes, then a comment of up to 64KB, so it is looked for from the end
+    let end = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&at| {
+            u32_at(tail, at) == Some(END_OF_DIRECTORY)
+                && u16_at(tail, at + 20)
+                    .is_some_and(|comment| at + 22 + usize::from(comment) <= tail.len())
+        })
+        .ok_or_else(not_a_zip)?;
+    let entries = u64::from(u16_at(tail, end + 10).ok_or_else(not_a_zip)?);
+    let size = u64::from(u32_at(tail, end + 12).ok_or_else(not_a_zip)?);
+    let offset = u64::from(u32_at(tail, end + 16).ok_or_else(not_a_zip)?);
+
+    let directory = if entries == 0xFFFF || size == 0xFFFF_FFFF || offset == 0xFFFF_FFFF {
+        // A zip64 keeps the real figures in a larger record, found through a locator
+        let tail_start = file_size.saturating_sub(tail.len() as u64);
+        let record = end
+            .checked_sub(20)
+            .filter(|&at| u32_at(tail, at) == Some(ZIP64_LOCATOR))
+            .and_then(|at| u64_at(tail, at + 8))
+            .and_then(|record| record.checked_sub(tail_start))
+            .and_then(|record| usize::try_from(record).ok())
+            .filter(|&at| u32_at(tail, at) == Some(ZIP64_END_OF_DIRECTORY))
+            .ok_or_else(|| {
+                BlobrsError::Format("Failed to read zip: zip64 record not found".to_string())
+            })?;
+        let read = |at| u64_at(tail, record + at).ok_or_else(not_a_zip);
+        ZipDirectory {
+            range: read(48)?..read(48)?.saturating_add(read(40)?),
+            entries: read(32)?,
+        }
+    } else {
+        ZipDirectory {
+            range: offset..offset + size,
+            entries,
+        }
+    };
+    if directory.range.end > file_size {
+        return Err(BlobrsError::Format(
+            "Failed to read zip: central directory past the end of the file".to_string(),
+        ));
+    }
+    Ok(directory)
+}
+
+/// The entries at the start of a zip's central directory, as many as `data` holds in full, up
+/// to `MAX_PREVIEW_ROWS`.
+///
+/// # Errors
+///
+/// Returns [`BlobrsError::Format`] if `data` does not start with a directory entry.
+pub fn parse_zip_directory(data: &[u8]) -> Result<Vec<ArchiveEntry>, BlobrsError> {
+    const DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+    const ZIP64_EXTRA: u16 = 0x0001;
+
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while entries.len() < MAX_PREVIEW_ROWS && u32_at(data, at) == Some(DIRECTORY_ENTRY) {
+        let (Some(compressed), Some(size), Some(name_len), Some(extra_len), Some(comment_len)) = (
+            u32_at(data, at + 20),
+            u32_at(data, at + 24),
+            u16_at(data, at + 28),
+            u16_at(data, at + 30),
+            u16_at(data, at + 32),
+        ) else {
+            break;
+        };
+        let name_start = at + 46;
+        let extra_start = name_start + usize::from(name_len);
+        let next = extra_start + usize::from(extra_len) + usize::from(comment_len);
+        if next > data.len() {
+            break;
+        }
+        let name = String::from_utf8_lossy(&data[name_start..extra_start]).into_owned();
+
+        // Sizes over 4GB are kept in a zip64 extra field, each there only if it overflowed
+        let mut size = u64::from(size);
+        let mut compressed = u64::from(compressed);
+        let mut extra = &data[extra_start..extra_start + usize::from(extra_len)];
+        while let (Some(id), Some(len)) = (u16_at(extra, 0), u16_at(extra, 2)) {
+            let field = extra.get(4..4 + usize::from(len)).unwrap_or_default();
+            if id == ZIP64_EXTRA {
+                let mut values = field.chunks_exact(8).filter_map(|value| u64_at(value, 0));
+                if size == 0xFFFF_FFFF {
+                    size = values.next().unwrap_or(size);
+                }
+                if compressed == 0xFFFF_FFFF {
+                    compressed = values.next().unwrap_or(compressed);
+                }
+            }
+            extra = extra.get(4 + usize::from(len)..).unwrap_or_default();
+        }
+
+        entries.push(ArchiveEntry {
+            name,
+            size,
+            compressed_size: Some(compressed),
+        });
+        at = next;
+    }
+    if entries.is_empty() && !data.is_empty() && u32_at(data, 0) != Some(DIRECTORY_ENTRY) {
+        return Err(BlobrsError::Format(
+            "Failed to read zip: central directory is damaged".to_string(),
+        ));
+    }
+    Ok(entries)
+}
+
+/// The entries of a tar, read a stretch at a time by following its headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarListing {
+    pub entries: Vec<ArchiveEntry>,
+    /// Offset of the next header to read, or `None` once the end of the archive is reached
+    pub next: Option<u64>,
+    /// A name given ahead of the entry it belongs to, by a GNU long name or pax header
+    long_name: Option<String>,
+}
+
+impl Default for TarListing {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            next: Some(0),
+            long_name: None,
+        }
+    }
+}
+
+impl TarListing {
+    /// Whether there is more to read to fill the preview.
+    #[must_use]
+    pub fn wants_more(&self) -> bool {
+        self.next.is_some() && self.entries.len() < MAX_PREVIEW_ROWS
+    }
+
+    /// Read the headers in `data`, which starts `offset` bytes into the archive, moving `next`
+    /// past each one and the file after it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlobrsError::Format`] if a header's checksum does not match or its size is
+    /// unreadable or too large.
+    pub fn read(&mut self, data: &[u8], offset: u64) -> Result<(), BlobrsError> {
+        while let Some(next) = self.next.filter(|_| self.wants_more()) {
+            let Some(header) = next
+                .checked_sub(offset)
+                .and_then(|at| usize::try_from(at).ok())
+                .and_then(|at| data.get(at..at.checked_add(TAR_BLOCK)?))
+            else {
+                return Ok(());
+            };
+            // Two empty blocks end the archive, though one is enough to know
+            if header.iter().all(|&byte| byte == 0) {
+                self.next = None;
+                return Ok(());
+            }
+            if !tar_checksum_matches(header) {
+                return Err(BlobrsError::Format(format!(
+                    "Failed to read tar: damaged header at byte {next}"
+                )));
+            }
+            let size = tar_number(&header[124..136]).ok_or_else(|| {
+                BlobrsError::Format(format!("Failed to read tar: bad size at byte {next}"))
+            })?;
+            // Sizes come from the archive, so a damaged one must not overflow the offsets
+            let (body_start, after) = next
+                .checked_add(TAR_BLOCK as u64)
+                .and_then(|body_start| {
+                    let padded = size
+                        .div_ceil(TAR_BLOCK as u64)
+                        .checked_mul(TAR_BLOCK as u64)?;
+                    Some((body_start, body_start.checked_add(padded)?))
+                })
+                .ok_or_else(|| {
+                    BlobrsError::Format(format!(
+                        "Failed to read tar: invalid tar entry size at byte {next}"
+                    ))
+                })?;
+            let body = || {
+                let start = usize::try_from(body_start.checked_sub(offset)?).ok()?;
+                data.get(start..start.checked_add(usize::try_from(size).ok()?)?)
+            };
+            match header[156] {
+                // A GNU long name, or a pax header which may hold one, for the next entry
+                b'L' => self.long_name = body().map(nul_terminated),
+                b'x' => self.long_name = body().and_then(pax_path),
+                // Global pax headers and GNU long link names are not entries themselves
+                b'g' | b'K' => {}
+                kind => {
+                    let mut name = self.long_name.take().unwrap_or_else(|| tar_name(header));
+                    if kind == b'5' && !name.ends_with('/') {
+                        name.push('/');
+                    }
+                    self.entries.push(ArchiveEntry {
+                        name,
+                        size,
+                        compressed_size: None,
+                    });
+                }
+            }
+            self.next = Some(after);
+        }
+        Ok(())
+    }
+
+    /// The listing so far as a table.
+    #[must_use]
+    pub fn into_table(self) -> PreviewData {
+        let total = self.entries.len();
+        archive_table(self.entries, total, self.next.is_some())
+    }
+}
+
+/// List the entries in the start of a compressed tar, read from `reader` until it ends, fails
+/// or the preview is full.
+///
+/// # Errors
+///
+/// Returns [`BlobrsError::Format`] if a header in what was read is damaged, as
+/// [`TarListing::read`] does. A read error is not one: it ends the listing.
+pub fn list_tar_stream(mut reader: impl Read) -> Result<TarListing, BlobrsError> {
+    let mut listing = TarListing::default();
+    // What has been read from the next header on
+    let mut buffer = Vec::new();
+    let mut offset = 0;
+    let mut chunk = vec![0; TAR_WINDOW_BYTES as usize];
+    while listing.wants_more() {
+        // The start of a stream is all that is fetched, so an error is where it was cut off
+        let read = match reader.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        buffer.extend_from_slice(&chunk[..read]);
+        listing.read(&buffer, offset)?;
+        let next = listing.next.unwrap_or(u64::MAX);
+        let done = usize::try_from(next.saturating_sub(offset))
+            .unwrap_or(usize::MAX)
+            .min(buffer.len());
+        buffer.drain(..done);
+        offset += done as u64;
+    }
+    Ok(listing)
+}
+
+/// The header's name, with the ustar prefix before it when there is one.
+fn tar_name(header: &[u8]) -> String {
+    let name = nul_terminated(&header[..100]);
+    // GNU tars put other fields where POSIX ones have the prefix
+    if &header[257..263] == b"ustar\0" {
+        let prefix = nul_terminated(&header[345..500]);
+        if !prefix.is_empty() {
+            return format!("{prefix}/{name}");
+        }
+    }
+    name
+}
+
+/// A header's checksum is the sum of its bytes, counting its own field as spaces.
+fn tar_checksum_matches(header: &[u8]) -> bool {
+    let sum: u64 = header
+        .iter()
+        .enumerate()
+        .map(|(at, &byte)| {
+            if (148..156).contains(&at) {
+                u64::from(b' ')
+            } else {
+                u64::from(byte)
+            }
+        })
+        .sum();
+    tar_number(&header[148..156]) == Some(sum)
+}
+
+/// A number in a tar header: octal text, or big-endian binary after a set high bit for one too
+/// large for its field.
+fn tar_number(field: &[u8]) -> Option<u64> {
+    if field.first().is_some_and(|&byte| byte & 0x80 != 0) {
+        return field[1..]
+            .iter()
+            .try_fold(u64::from(field[0] & 0x7F), |number, &byte| {
+                number.checked_mul(256)?.checked_add(u64::from(byte))
+            });
+    }
+    let text = std::str::from_utf8(field).ok()?;
+    let digits = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(digits, 8).ok()
+}
+
+/// The `path` in pax header records, each `<length> <key>=<value>\n`.
+fn pax_path(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data).lines().find_map(|record| {
+        let (_, field) = record.split_once(' ')?;
+        field.strip_prefix("path=").map(ToString::to_string)
+    })
+}
+
+fn nul_terminated(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u64_at(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A zip storing `files` uncompressed.
+    pub(crate) fn zip_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = Vec::new();
+        let mut directory = Vec::new();
+        for (name, content) in files {
+            let offset = u32::try_from(zip.len()).unwrap();
+            let len = u32::try_from(content.len()).unwrap();
+            let name_len = u16::try_from(name.len()).unwrap();
+            zip.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+            zip.extend_from_slice(&[0; 14]);
+            zip.extend_from_slice(&len.to_le_bytes());
+            zip.extend_from_slice(&len.to_le_bytes());
+            zip.extend_from_slice(&name_len.to_le_bytes());
+            zip.extend_from_slice(&0u16.to_le_bytes());
+            zip.extend_from_slice(name.as_bytes());
+            zip.extend_from_slice(content);
+
+            directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            directory.extend_from_slice(&[0; 16]);
+            directory.extend_from_slice(&len.to_le_bytes());
+            directory.extend_from_slice(&len.to_le_bytes());
+            directory.extend_from_slice(&name_len.to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+        let offset = u32::try_from(zip.len()).unwrap();
+        let count = u16::try_from(files.len()).unwrap();
+        zip.extend_from_slice(&directory);
+        zip.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        zip.extend_from_slice(&[0; 4]);
+        zip.extend_from_slice(&count.to_le_bytes());
+        zip.extend_from_slice(&count.to_le_bytes());
+        zip.extend_from_slice(&u32::try_from(directory.len()).unwrap().to_le_bytes());
+        zip.extend_from_slice(&offset.to_le_bytes());
+        zip.extend_from_slice(&5u16.to_le_bytes());
+        zip.extend_from_slice(b"hello");
+        zip
+    }
+
+    fn tar_header(name: &str, size: usize, kind: u8) -> Vec<u8> {
+        let mut header = vec![0; TAR_BLOCK];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].copy_from_slice(b"        ");
+        let sum: u32 = header.iter().map(|&byte| u32::from(byte)).sum();
+        header[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+        header
+    }
+
+    /// A tar of `files`, each a name, content and type flag.
+    pub(crate) fn tar_of(files: &[(&str, &[u8], u8)]) -> Vec<u8> {
+        let mut tar = Vec::new();
+        for (name, content, kind) in files {
+            tar.extend(tar_header(name, content.len(), *kind));
+            tar.extend_from_slice(content);
+            tar.resize(tar.len().next_multiple_of(TAR_BLOCK), 0);
+        }
+        tar.resize(tar.len() + 2 * TAR_BLOCK, 0);
+        tar
+    }
+
+    fn entry(name: &str, size: u64, compressed_size: Option<u64>) -> ArchiveEntry {
+        ArchiveEntry {
+            name: name.to_string(),
+            size,
+            compressed_size,
+        }
+    }
+
+    #[test]
+    fn archives_are_known_by_their_extensions() {
+        assert_eq!(ArchiveKind::of("backup.ZIP"), Some(ArchiveKind::Zip));
+        assert_eq!(ArchiveKind::of("logs.tar"), Some(ArchiveKind::Tar(None)));
+        assert_eq!(
+            ArchiveKind::of("logs.tar.gz"),
+            Some(ArchiveKind::Tar(Some(Compression::Gzip)))
+        );
+        assert_eq!(
+            ArchiveKind::of("logs.tgz"),
+            Some(ArchiveKind::Tar(Some(Compression::Gzip)))
+        );
+        assert_eq!(
+            ArchiveKind::of("logs.tar.zst"),
+            Some(ArchiveKind::Tar(Some(Compression::Zstd)))
+        );
+        assert_eq!(ArchiveKind::of("events.json.gz"), None);
+        assert_eq!(ArchiveKind::of("notes.txt"), None);
+    }
+
+    #[test]
+    fn a_zip_is_listed_from_its_central_directory() {
+        let zip = zip_of(&[("data/", b""), ("data/a.csv", b"id\n1\n"), ("b.txt", b"hi")]);
+        // Only the end of the archive is needed to find the directory
+        let tail = &zip[zip.len() - 40..];
+        let directory = zip_directory(tail, zip.len() as u64).unwrap();
+        assert_eq!(directory.entries, 3);
+        let range = directory.range.start as usize..directory.range.end as usize;
+        assert_eq!(
+            parse_zip_directory(&zip[range.clone()]).unwrap(),
+            vec![
+                entry("data/", 0, Some(0)),
+                entry("data/a.csv", 5, Some(5)),
+                entry("b.txt", 2, Some(2)),
+            ]
+        );
+        // A directory cut short lists the entries it holds in full
+        assert_eq!(
+            parse_zip_directory(&zip[range.start..range.end - 3]).unwrap(),
+            vec![entry("data/", 0, Some(0)), entry("data/a.csv", 5, Some(5))]
+        );
+
+        assert!(zip_directory(b"not a zip file at all", 21).is_err());
+        assert!(parse_zip_directory(b"not a directory").is_err());
+    }
+
+    #[test]
+    fn zip64_sizes_are_read_from_the_extra_field() {
+        let mut directory = 0x0201_4b50u32.to_le_bytes().to_vec();
+        directory.extend_from_slice(&[0; 16]);
+        directory.extend_from_slice(&u32::MAX.to_le_bytes());
+        directory.extend_from_slice(&u32::MAX.to_le_bytes());
+        directory.extend_from_slice(&5u16.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&[0; 14]);
+        directory.extend_from_slice(b"large");
+        directory.extend_from_slice(&1u16.to_le_bytes());
+        directory.extend_from_slice(&16u16.to_le_bytes());
+        directory.extend_from_slice(&(8u64 << 30).to_le_bytes());
+        directory.extend_from_slice(&(3u64 << 30).to_le_bytes());
+        assert_eq!(
+            parse_zip_directory(&directory).unwrap(),
+            vec![entry("large", 8 << 30, Some(3 << 30))]
+        );
+    }
+
+    #[test]
+    fn a_tar_is_listed_by_following_its_headers() {
+        let long_name = format!("{}/deep.txt", "nested".repeat(20));
+        let tar = tar_of(&[
+            ("logs/", b"", b'5'),
+            ("logs/app.log", &[b'x'; 700], b'0'),
+            ("././@LongLink", format!("{long_name}\0").as_bytes(), b'L'),
+            ("nested/deep.txt", b"deep", b'0'),
+        ]);
+        let expected = vec![
+            entry("logs/", 0, None),
+            entry("logs/app.log", 700, None),
+            entry(&long_name, 4, None),
+        ];
+
+        let mut listing = TarListing::default();
+        listing.read(&tar, 0).unwrap();
+        assert_eq!(listing.entries, expected);
+        assert_eq!(listing.next, None);
+
+        // Read a header at a time, as ranged reads past each file would
+        let mut listing = TarListing::default();
+        while let Some(next) = listing.next {
+            listing
+                .read(&tar[next as usize..next as usize + TAR_BLOCK], next)
+                .unwrap();
+        }
+        assert_eq!(listing.entries[..2], expected[..2]);
+
+        let mut damaged = tar.clone();
+        damaged[0] = b'X';
+        assert!(TarListing::default().read(&damaged, 0).is_err());
+    }
+
+    #[test]
+    fn an_oversized_tar_entry_is_an_error() {
+        let mut header = tar_header("huge.bin", 0, b'0');
+        // A base-256 size that overflows the offset of the next header
+        header[124] = 0x80;
+        header[125..136].fill(0);
+        header[128..136].copy_from_slice(&u64::MAX.to_be_bytes());
+        header[148..156].copy_from_slice(b"        ");
+        let sum: u32 = header.iter().map(|&byte| u32::from(byte)).sum();
+        header[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+        assert_eq!(tar_number(&header[124..136]), Some(u64::MAX));
+
+        let error = TarListing::default()
+            .read(&header, 0)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("invalid tar entry size"), "{error}");
+    }
+
+    #[test]
+    fn a_compressed_tar_lists_the_headers_in_its_start() {
+        use std::io::Write;
+
+        let tar = tar_of(&[("a.txt", b"alpha", b'0'), ("b.bin", &[7; 200_000], b'0')]);
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gzip.write_all(&tar).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        let decoder = Compression::Gzip.decoder(&gzip).unwrap();
+        let listing = list_tar_stream(decoder).unwrap();
+        assert_eq!(
+            listing.entries,
+            vec![entry("a.txt", 5, None), entry("b.bin", 200_000, None)]
+        );
+        assert_eq!(listing.next, None);
+
+        // Cut off partway, it lists what it got to
+        let decoder = Compression::Gzip.decoder(&gzip[..gzip.len() / 2]).unwrap();
+        let PreviewData::Table(table) = list_tar_stream(decoder).unwrap().into_table() else {
+            panic!("Expected a table");
+        };
+        assert_eq!(table.headers, vec!["Name", "Size"]);
+        assert!(table.truncated);
+    }
+
+    #[test]
+    fn tar_numbers_are_octal_or_binary() {
+        assert_eq!(tar_number(b"00000001750\0"), Some(1000));
+        assert_eq!(tar_number(b"      \0\0"), Some(0));
+        let mut binary = [0; 12];
+        binary[0] = 0x80;
+        binary[7..].copy_from_slice(&[2, 0, 0, 0, 0]);
+        assert_eq!(tar_number(&binary), Some(8 << 30));
+        assert_eq!(tar_number(b"not octal\0\0\0"), None);
+    }
+}
@@ -0,0 +1,273 @@
+//! History of the operations that changed blobs or transferred data this session.
+//!
+//! Entries are kept in memory for the log panel and, when configured, appended to a JSON-lines
+//! file once their outcome is known, so the history outlives the session.
+
+use crate::error::BlobrsError;
+use crate::secret;
+use crate::transfer::TransferJob;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Kind of operation recorded in the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditAction {
+    Download,
+    Upload,
+    Edit,
+    Delete,
+    Copy,
+    Move,
+    Restore,
+    Snapshot,
+    Tags,
+    Lease,
+    Tier,
+    CreateFolder,
+    CreateContainer,
+    DeleteContainer,
+    Sync,
+}
+
+impl AuditAction {
+    /// Short name shown in the log panel.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Download => "download",
+            Self::Upload => "upload",
+            Self::Edit => "edit",
+            Self::Delete => "delete",
+            Self::Copy => "copy",
+            Self::Move => "move",
+            Self::Restore => "restore",
+            Self::Snapshot => "snapshot",
+            Self::Tags => "tags",
+            Self::Lease => "lease",
+            Self::Tier => "tier",
+            Self::CreateFolder => "new folder",
+            Self::CreateContainer => "new container",
+            Self::DeleteContainer => "delete container",
+            Self::Sync => "sync",
+        }
+    }
+}
+
+/// How an operation ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", content = "error", rename_all = "kebab-case")]
+pub enum Outcome {
+    /// A background job that has not finished yet.
+    Running,
+    Succeeded,
+    Failed(String),
+}
+
+impl Outcome {
+    /// `Succeeded` for `Ok`, the error for `Err`.
+    pub fn of<T, E: std::fmt::Display>(result: &Result<T, E>) -> Self {
+        match result {
+            Ok(_) => Self::Succeeded,
+            Err(e) => Self::Failed(e.to_string()),
+        }
+    }
+}
+
+/// One operation.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    pub account: String,
+    pub action: AuditAction,
+    /// What was acted on, e.g. `container/path/to/blob` or a local file for uploads.
+    pub target: String,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+    /// Job whose outcome is still pending.
+    #[serde(skip)]
+    job: Option<TransferJob>,
+}
+
+/// Every operation recorded this session, oldest first.
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+    /// JSON-lines file finished entries are appended to.
+    file: Option<PathBuf>,
+    /// Why the file could not be written. Nothing more is written once this is set.
+    pub write_error: Option<String>,
+}
+
+impl AuditLog {
+    /// An empty log that appends finished entries to `file`, if given.
+    #[must_use]
+    pub fn new(file: Option<PathBuf>) -> Self {
+        Self {
+            file,
+            ..Self::default()
+        }
+    }
+
+    /// Entries recorded so far, oldest first.
+    #[must_use]
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Record an operation that has finished.
+    pub fn record(
+        &mut self,
+        account: &str,
+        action: AuditAction,
+        target: impl Into<String>,
+        outcome: Outcome,
+    ) {
+        self.push(AuditEntry {
+            time: Utc::now(),
+            account: account.to_string(),
+            action,
+            target: target.into(),
+            outcome,
+            job: None,
+        });
+    }
+
+    /// Record a background job; its outcome is filled in by [`Self::update_jobs`].
+    pub fn record_job(
+        &mut self,
+        account: &str,
+        action: AuditAction,
+        target: impl Into<String>,
+        job: TransferJob,
+    ) {
+        self.push(AuditEntry {
+            time: Utc::now(),
+            account: account.to_string(),
+            action,
+            target: target.into(),
+            outcome: Outcome::Running,
+            job: Some(job),
+        });
+    }
+
+    /// Fill in the outcome of every job that has finished since the last call.
+    pub fn update_jobs(&mut self) {
+        for index in 0..self.entries.len() {
+            let Some(job) = &self.entries[index].job else {
+                continue;
+            };
+            let summary = job.summary();
+            if !summary.is_finished() {
+                continue;
+            }
+            let problems = [
+                (summary.failed, "failed"),
+                (summary.skipped, "skipped"),
+                (summary.aborted, "aborted"),
+            ]
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, what)| format!("{count} {what}"))
+            .collect::<Vec<_>>();
+            let entry = &mut self.entries[index];
+            entry.job = None;
+            entry.outcome = if problems.is_empty() {
+                Outcome::Succeeded
+            } else {
+                Outcome::Failed(problems.join(", "))
+            };
+            self.write(index);
+        }
+    }
+
+    fn push(&mut self, mut entry: AuditEntry) {
+        if let Outcome::Failed(e) = &mut entry.outcome {
+            *e = secret::redact(e);
+        }
+        self.entries.push(entry);
+        if self.entries[self.entries.len() - 1].outcome != Outcome::Running {
+            self.write(self.entries.len() - 1);
+        }
+    }
+
+    /// Append an entry to the file, if there is one and it could be written so far.
+    fn write(&mut self, index: usize) {
+        let Some(file) = self.file.as_deref().filter(|_| self.write_error.is_none()) else {
+            return;
+        };
+        if let Err(e) = append(file, &self.entries[index]) {
+            self.write_error = Some(e.to_string());
+        }
+    }
+}
+
+/// Append `entry` to `file` as one line of JSON.
+fn append(file: &Path, entry: &AuditEntry) -> Result<(), BlobrsError> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| BlobrsError::io(format!("Failed to create {}", dir.display()), e))?;
+    }
+    let line = serde_json::to_string(entry)
+        .map_err(|e| BlobrsError::Parse(format!("audit entry: {e}")))?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)
+        .and_then(|mut f| writeln!(f, "{line}"))
+        .map_err(|e| BlobrsError::io(format!("Failed to write {}", file.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finished_entries_are_appended_as_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::new(Some(file.clone()));
+
+        log.record(
+            "acct",
+            AuditAction::Delete,
+            "data/a.csv",
+            Outcome::Succeeded,
+        );
+        log.record(
+            "acct",
+            AuditAction::Upload,
+            "data/b.csv",
+            Outcome::of(&Err::<(), _>("denied")),
+        );
+
+        assert_eq!(log.entries().len(), 2);
+        let lines: Vec<serde_json::Value> = fs::read_to_string(&file)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["action"], "delete");
+        assert_eq!(lines[0]["target"], "data/a.csv");
+        assert_eq!(lines[0]["outcome"], "succeeded");
+        assert_eq!(lines[1]["outcome"], "failed");
+        assert_eq!(lines[1]["error"], "denied");
+        assert_eq!(lines[1]["account"], "acct");
+        assert!(log.write_error.is_none());
+    }
+
+    #[test]
+    fn unwritable_files_are_reported_once_and_then_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        // A directory where the file should be cannot be appended to
+        let mut log = AuditLog::new(Some(dir.path().to_path_buf()));
+
+        log.record("acct", AuditAction::Sync, "data", Outcome::Succeeded);
+        assert!(log.write_error.is_some());
+        log.record("acct", AuditAction::Sync, "data", Outcome::Succeeded);
+        assert_eq!(log.entries().len(), 2);
+    }
+}
@@ -0,0 +1,2693 @@
+//! Minimal signed REST client for Azure Blob Storage operations that `object_store` does not
+//! expose (container listing, blob versions, snapshots, full blob properties, ...).
+
+use crate::config::{RetryConfig, TimeoutConfig};
+use crate::error::BlobrsError;
+use crate::xml;
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use object_store::azure::{MicrosoftAzure, MicrosoftAzureBuilder};
+use regex::Regex;
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// REST API version sent with every request; 2021-12-02 is the first to accept the Cold tier.
+pub const API_VERSION: &str = "2021-12-02";
+
+/// Most index tags a blob can carry.
+pub const MAX_BLOB_TAGS: usize = 10;
+
+/// Most sub-requests the Blob Batch API accepts in one request.
+pub const MAX_BATCH_SIZE: usize = 256;
+
+/// How often a pending server-side copy is checked on.
+const COPY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Why one sub-request of a Blob Batch did not succeed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchFailure {
+    /// The service answered it with a status outside 2xx.
+    Status {
+        code: u16,
+        /// The `x-ms-error-code`, or else the status line's reason phrase.
+        reason: String,
+    },
+    /// The response had no part for it.
+    Missing,
+}
+
+impl std::fmt::Display for BatchFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status { code, reason } => write!(f, "{code} {reason}"),
+            Self::Missing => f.write_str("missing from the batch response"),
+        }
+    }
+}
+
+/// A response from the Azure Blob REST API.
+#[derive(Debug, Clone)]
+pub struct RestResponse {
+    /// HTTP status code.
+    pub status: reqwest::StatusCode,
+    /// Response headers.
+    pub headers: reqwest::header::HeaderMap,
+    /// Response body as text.
+    pub body: String,
+}
+
+/// Signs and sends requests to the Blob service using Shared Key authorization.
+#[derive(Clone)]
+pub struct AzureRestClient {
+    account: String,
+    key: Vec<u8>,
+    http: reqwest::Client,
+    /// Base URL of the Blob service, without a trailing slash.
+    endpoint: String,
+    /// Path segment that precedes the container in URLs, e.g. `devstoreaccount1/` for Azurite.
+    resource_prefix: String,
+    /// When to retry throttled and failed requests.
+    retry: RetryConfig,
+}
+
+/// An `object_store` client for `container`, retrying and giving up like the REST client.
+///
+/// # Errors
+///
+/// Returns an error if the account or key is malformed.
+pub fn container_store(
+    account: &str,
+    access_key: &str,
+    container: &str,
+    retry: RetryConfig,
+    timeouts: TimeoutConfig,
+) -> Result<MicrosoftAzure, BlobrsError> {
+    Ok(MicrosoftAzureBuilder::new()
+        .with_account(account)
+        .with_container_name(container)
+        .with_access_key(access_key)
+        .with_retry(retry.object_store())
+        .with_client_options(timeouts.object_store())
+        .build()?)
+}
+
+/// An HTTP client that gives up on connecting after `timeouts`, and on responses that stop
+/// sending data for the read limit.
+fn http_client(timeouts: TimeoutConfig) -> Result<reqwest::Client, BlobrsError> {
+    let builder = reqwest::Client::builder().connect_timeout(timeouts.connect());
+    let builder = match timeouts.read() {
+        Some(read) => builder.read_timeout(read),
+        None => builder,
+    };
+    Ok(builder.build()?)
+}
+
+impl std::fmt::Debug for AzureRestClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AzureRestClient")
+            .field("account", &self.account)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AzureRestClient {
+    /// Create a client for the given account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the access key is not valid base64.
+    pub fn new(account: &str, access_key: &str) -> Result<Self, BlobrsError> {
+        let key = general_purpose::STANDARD
+            .decode(access_key)
+            .map_err(|e| BlobrsError::Auth(format!("access key is not valid base64: {e}")))?;
+        Ok(Self {
+            account: account.to_string(),
+            key,
+            http: http_client(TimeoutConfig::default())?,
+            endpoint: format!("https://{account}.blob.core.windows.net"),
+            resource_prefix: String::new(),
+            retry: RetryConfig::default(),
+        })
+    }
+
+    /// Retry throttled and failed requests according to `retry` instead of the defaults.
+    #[must_use]
+    pub const fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Give up on requests after `timeouts` instead of the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be built.
+    pub fn with_timeouts(mut self, timeouts: TimeoutConfig) -> Result<Self, BlobrsError> {
+        self.http = http_client(timeouts)?;
+        Ok(self)
+    }
+
+    /// Create a client for an account in a local storage emulator such as Azurite, which
+    /// serves accounts path-style under `blob_url`, e.g. `http://127.0.0.1:10000`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the access key is not valid base64.
+    pub fn emulator(account: &str, access_key: &str, blob_url: &str) -> Result<Self, BlobrsError> {
+        let mut client = Self::new(account, access_key)?;
+        client.endpoint = format!("{}/{account}", blob_url.trim_end_matches('/'));
+        client.resource_prefix = format!("{account}/");
+        Ok(client)
+    }
+
+    /// Send a signed `GET` request and return the body, failing on non-success statuses.
+    ///
+    /// `path` is the URL path without the leading slash (empty for account-level operations).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the service responds with a non-success status.
+    pub async fn get_text(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<String, BlobrsError> {
+        let response = self
+            .send(reqwest::Method::GET, path, query, &[], Vec::new())
+            .await?;
+        ensure_success(&response)?;
+        Ok(response.body)
+    }
+
+    /// Send a signed `GET` request and return the raw body, failing on non-success statuses.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the service responds with a non-success status.
+    pub async fn get_bytes(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<bytes::Bytes, BlobrsError> {
+        let response = self
+            .request(reqwest::Method::GET, path, query, &[], Vec::new())
+            .await?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(response.bytes().await?)
+        } else {
+            Err(status_error(status, &response.text().await?))
+        }
+    }
+
+    /// Send a signed request.
+    ///
+    /// `headers` may contain `x-ms-*` headers as well as the standard headers that take part in
+    /// the Shared Key signature (`Content-Type`, `If-Match`, `Range`, ...).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if signing, sending, or reading the response fails. Non-success statuses
+    /// are returned as a normal [`RestResponse`].
+    pub async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<RestResponse, BlobrsError> {
+        let response = self.request(method, path, query, headers, body).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.text().await?;
+
+        Ok(RestResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Sign and send a request, leaving the body unread.
+    ///
+    /// Throttled (429) and unavailable (5xx) responses and network failures are retried with
+    /// exponential backoff, waiting as long as a `Retry-After` header asks. Requests that
+    /// [`is_idempotent`] rules out are only retried when throttled, as the service refused
+    /// them without running them; after a failure they may have taken effect already.
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, BlobrsError> {
+        tracing::info!(%method, path, ?query, "request");
+        let idempotent = is_idempotent(&method, query);
+        let mut retry = 0;
+        loop {
+            let result = self
+                .request_once(method.clone(), path, query, headers, body.clone())
+                .await;
+            let retry_after = match &result {
+                Ok(response)
+                    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+                        || idempotent && is_transient_status(response.status()) =>
+                {
+                    retry_after(response.headers(), Utc::now())
+                }
+                Err(BlobrsError::Network(_) | BlobrsError::Timeout(_)) if idempotent => None,
+                Ok(response) if response.status().is_success() => {
+                    tracing::debug!(%method, path, status = %response.status(), "response");
+                    return result;
+                }
+                Ok(response) => {
+                    tracing::warn!(%method, path, status = %response.status(), "request refused");
+                    return result;
+                }
+                Err(e) => {
+                    tracing::warn!(%method, path, error = %e, "request failed");
+                    return result;
+                }
+            };
+            let outcome = match &result {
+                Ok(response) => response.status().to_string(),
+                Err(e) => e.to_string(),
+            };
+            if retry >= self.retry.max_retries {
+                tracing::warn!(%method, path, %outcome, retries = retry, "giving up");
+                return result;
+            }
+            let delay = self.retry.delay(retry, retry_after);
+            tracing::warn!(%method, path, %outcome, ?delay, "retrying");
+            tokio::time::sleep(delay).await;
+            retry += 1;
+        }
+    }
+
+    /// Sign and send a request once.
+    async fn request_once(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, BlobrsError> {
+        let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+
+        let mut url = format!("{}/{path}", self.endpoint);
+        for (i, (name, value)) in query.iter().enumerate() {
+            let sep = if i == 0 { '?' } else { '&' };
+            let _ = write!(url, "{sep}{name}={}", urlencoding::encode(value));
+        }
+
+        let mut all_headers: Vec<(String, String)> = headers
+            .iter()
+            .map(|(k, v)| (k.to_lowercase(), (*v).to_string()))
+            .collect();
+        all_headers.push(("x-ms-date".to_string(), date));
+        all_headers.push(("x-ms-version".to_string(), API_VERSION.to_string()));
+
+        let content_length = if body.is_empty() {
+            String::new()
+        } else {
+            body.len().to_string()
+        };
+        let authorization =
+            self.authorization(method.as_str(), &content_length, &all_headers, path, query)?;
+
+        let mut request = self.http.request(method, &url);
+        for (name, value) in &all_headers {
+            request = request.header(name, value);
+        }
+        Ok(request
+            .header("Authorization", authorization)
+            .body(body)
+            .send()
+            .await?)
+    }
+
+    /// The Shared Key `Authorization` header value for a request.
+    fn authorization(
+        &self,
+        method: &str,
+        content_length: &str,
+        headers: &[(String, String)],
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<String, BlobrsError> {
+        let account = &self.account;
+        let string_to_sign = string_to_sign(
+            method,
+            content_length,
+            headers,
+            &canonicalized_resource(account, &format!("{}{path}", self.resource_prefix), query),
+        );
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key)
+            .map_err(|e| BlobrsError::Auth(format!("invalid access key: {e}")))?;
+        mac.update(string_to_sign.as_bytes());
+        let signature = general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+        Ok(format!("SharedKey {account}:{signature}"))
+    }
+
+    /// Delete up to [`MAX_BATCH_SIZE`] blobs of one container with a single Blob Batch request.
+    ///
+    /// Returns one outcome per name, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the batch as a whole is rejected or its response cannot be parsed.
+    pub async fn delete_blobs_batch(
+        &self,
+        container: &str,
+        names: &[String],
+    ) -> Result<Vec<Result<(), BatchFailure>>, BlobrsError> {
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        if names.len() > MAX_BATCH_SIZE {
+            return Err(BlobrsError::Parse(format!(
+                "a batch holds at most {MAX_BATCH_SIZE} blobs, got {}",
+                names.len()
+            )));
+        }
+
+        let boundary = format!("batch_{}", Utc::now().timestamp_nanos_opt().unwrap_or(0));
+        let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        let mut body = String::new();
+        for (i, name) in names.iter().enumerate() {
+            let path = format!("{container}/{}", encode_blob_path(name));
+            let headers = [("x-ms-date".to_string(), date.clone())];
+            let authorization = self.authorization("DELETE", "", &headers, &path, &[])?;
+            let _ = write!(
+                body,
+                "--{boundary}\r\n\
+                 Content-Type: application/http\r\n\
+                 Content-Transfer-Encoding: binary\r\n\
+                 Content-ID: {i}\r\n\r\n\
+                 DELETE /{}{path} HTTP/1.1\r\n\
+                 x-ms-date: {date}\r\n\
+                 Authorization: {authorization}\r\n\
+                 Content-Length: 0\r\n\r\n",
+                self.resource_prefix
+            );
+        }
+        let _ = write!(body, "--{boundary}--\r\n");
+
+        let content_type = format!("multipart/mixed; boundary={boundary}");
+        let response = self
+            .send(
+                reqwest::Method::POST,
+                container,
+                &[("comp", "batch"), ("restype", "container")],
+                &[("Content-Type", &content_type)],
+                body.into_bytes(),
+            )
+            .await?;
+        ensure_success(&response)?;
+
+        let mut statuses = parse_batch_response(&response.body);
+        Ok((0..names.len())
+            .map(|i| statuses.remove(&i).unwrap_or(Err(BatchFailure::Missing)))
+            .collect())
+    }
+
+    /// List all containers in the account, following continuation markers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any page request fails.
+    pub async fn list_containers(&self) -> Result<Vec<ContainerSummary>, BlobrsError> {
+        let mut containers = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("comp", "list"),
+                ("include", "metadata"),
+                ("maxresults", "5000"),
+            ];
+            if let Some(marker) = &marker {
+                query.push(("marker", marker));
+            }
+
+            let xml = self.get_text("", &query).await?;
+            let (page, next_marker) = parse_containers_xml(&xml)
+                .map_err(|e| BlobrsError::Parse(format!("container listing: {e}")))?;
+            containers.extend(page);
+
+            match next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(containers)
+    }
+
+    /// Properties and metadata of a container.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the container does not exist or the request fails.
+    pub async fn container_properties(
+        &self,
+        container: &str,
+    ) -> Result<ContainerProperties, BlobrsError> {
+        let response = self
+            .send(
+                reqwest::Method::HEAD,
+                container,
+                &[("restype", "container")],
+                &[],
+                Vec::new(),
+            )
+            .await?;
+        ensure_success(&response)?;
+        Ok(ContainerProperties::from_headers(
+            container,
+            &response.headers,
+        ))
+    }
+
+    /// Whether blob versioning appears to be on, judged by the first blob of the container.
+    ///
+    /// Versioning is an account setting that only the management API reports, so this is
+    /// `None` for an empty container. A blob listed without a version id means versioning is
+    /// off (or was off when it was written).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listing request fails.
+    pub async fn container_versioning(&self, container: &str) -> Result<Option<bool>, BlobrsError> {
+        let xml = self
+            .get_text(
+                container,
+                &[
+                    ("comp", "list"),
+                    ("include", "versions"),
+                    ("maxresults", "1"),
+                    ("restype", "container"),
+                ],
+            )
+            .await?;
+        let root = xml::parse(&xml)?;
+        Ok(listed_blobs(&root)
+            .next()
+            .map(|blob| blob.child("VersionId").is_some()))
+    }
+
+    /// Create an empty container with no public access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the container already exists or the request fails.
+    pub async fn create_container(&self, container: &str) -> Result<(), BlobrsError> {
+        let response = self
+            .send(
+                reqwest::Method::PUT,
+                container,
+                &[("restype", "container")],
+                &[],
+                Vec::new(),
+            )
+            .await?;
+        ensure_success(&response)
+    }
+
+    /// Whether the container holds no blobs at all, in any folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listing request fails.
+    pub async fn container_is_empty(&self, container: &str) -> Result<bool, BlobrsError> {
+        let xml = self
+            .get_text(
+                container,
+                &[
+                    ("comp", "list"),
+                    ("maxresults", "1"),
+                    ("restype", "container"),
+                ],
+            )
+            .await?;
+        Ok(listed_blobs(&xml::parse(&xml)?).next().is_none())
+    }
+
+    /// Delete a container and everything in it. The service removes it in the background, so
+    /// the name cannot be reused for a short while.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the container does not exist or the request fails.
+    pub async fn delete_container(&self, container: &str) -> Result<(), BlobrsError> {
+        let response = self
+            .send(
+                reqwest::Method::DELETE,
+                container,
+                &[("restype", "container")],
+                &[],
+                Vec::new(),
+            )
+            .await?;
+        ensure_success(&response)
+    }
+
+    /// List blob versions under `prefix`, one delimiter level deep when `delimiter` is set,
+    /// with deleted blobs so that [`versions_as_of`] can tell when they went.
+    ///
+    /// Follows continuation markers until the listing is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any page request fails.
+    pub async fn list_blob_versions(
+        &self,
+        container: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+    ) -> Result<VersionListing, BlobrsError> {
+        let mut listing = VersionListing::default();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("comp", "list"),
+                ("include", "versions,deleted"),
+                ("restype", "container"),
+            ];
+            if !prefix.is_empty() {
+                query.push(("prefix", prefix));
+            }
+            if let Some(delimiter) = delimiter {
+                query.push(("delimiter", delimiter));
+            }
+            if let Some(marker) = &marker {
+                query.push(("marker", marker));
+            }
+
+            let xml = self.get_text(container, &query).await?;
+            let page = parse_blob_versions_xml(&xml)
+                .map_err(|e| BlobrsError::Parse(format!("blob version listing: {e}")))?;
+            listing.versions.extend(page.versions);
+            listing.prefixes.extend(page.prefixes);
+
+            match page.next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(listing)
+    }
+
+    /// List the blobs under `prefix` with the properties `object_store` leaves out, such as
+    /// their access tier: directly under it with a `/` delimiter, or at any depth without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any page request fails.
+    pub async fn list_blobs(
+        &self,
+        container: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+    ) -> Result<BlobListing, BlobrsError> {
+        let mut listing = BlobListing::default();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let (page, next_marker) = self
+                .list_blobs_page(container, prefix, delimiter, marker.as_deref())
+                .await?;
+            listing.blobs.extend(page.blobs);
+            listing.prefixes.extend(page.prefixes);
+
+            match next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(listing)
+    }
+
+    /// One page of [`Self::list_blobs`], starting at `marker`, with the marker of the next
+    /// page if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn list_blobs_page(
+        &self,
+        container: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+        marker: Option<&str>,
+    ) -> Result<(BlobListing, Option<String>), BlobrsError> {
+        let mut query = vec![("comp", "list"), ("restype", "container")];
+        if !prefix.is_empty() {
+            query.push(("prefix", prefix));
+        }
+        if let Some(delimiter) = delimiter {
+            query.push(("delimiter", delimiter));
+        }
+        if let Some(marker) = marker {
+            query.push(("marker", marker));
+        }
+
+        let xml = self.get_text(container, &query).await?;
+        parse_blob_listing_xml(&xml).map_err(|e| BlobrsError::Parse(format!("blob listing: {e}")))
+    }
+
+    /// Copy a blob to another location in the same account on the server, returning once the
+    /// copy has finished.
+    ///
+    /// Small blobs are copied before the service answers; for larger ones the copy runs in the
+    /// background and its status is polled until it succeeds or fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the copy could not be started, or failed or was aborted.
+    pub async fn copy_blob(
+        &self,
+        source_container: &str,
+        source_name: &str,
+        destination_container: &str,
+        destination_name: &str,
+    ) -> Result<(), BlobrsError> {
+        let source_url = format!(
+            "{}/{source_container}/{}",
+            self.endpoint,
+            encode_blob_path(source_name)
+        );
+        let mut status = self
+            .copy_from_url(&source_url, destination_container, destination_name)
+            .await?;
+        let path = format!(
+            "{destination_container}/{}",
+            encode_blob_path(destination_name)
+        );
+        let mut description = None;
+        while !copy_finished(&status, description.as_deref())? {
+            tokio::time::sleep(COPY_POLL_INTERVAL).await;
+            let response = self
+                .send(reqwest::Method::HEAD, &path, &[], &[], Vec::new())
+                .await?;
+            ensure_success(&response)?;
+            let header = |name: &str| {
+                response
+                    .headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            };
+            status = header("x-ms-copy-status").unwrap_or_else(|| "success".to_string());
+            description = header("x-ms-copy-status-description");
+        }
+        Ok(())
+    }
+
+    /// Copy a blob from `source_url` in this account, returning the copy status.
+    async fn copy_from_url(
+        &self,
+        source_url: &str,
+        destination_container: &str,
+        destination_name: &str,
+    ) -> Result<String, BlobrsError> {
+        let path = format!(
+            "{destination_container}/{}",
+            encode_blob_path(destination_name)
+        );
+        let response = self
+            .send(
+                reqwest::Method::PUT,
+                &path,
+                &[],
+                &[("x-ms-copy-source", source_url)],
+                Vec::new(),
+            )
+            .await?;
+        ensure_success(&response)?;
+        Ok(response
+            .headers
+            .get("x-ms-copy-status")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("success")
+            .to_string())
+    }
+
+    /// Fetch every property of a blob: system properties, content headers, metadata and tags.
+    ///
+    /// Tags are `None` when the account does not support them (e.g. hierarchical namespace).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob does not exist or its properties cannot be read.
+    pub async fn blob_properties(
+        &self,
+        container: &str,
+        name: &str,
+    ) -> Result<BlobProperties, BlobrsError> {
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let response = self
+            .send(reqwest::Method::HEAD, &path, &[], &[], Vec::new())
+            .await?;
+        ensure_success(&response)?;
+        let mut properties = BlobProperties::from_headers(container, name, &response.headers);
+
+        properties.tags = self.blob_tags(container, name).await.ok();
+        Ok(properties)
+    }
+
+    /// Acquire a lease on a blob for 15 to 60 seconds, or forever when `seconds` is `None`,
+    /// and return the lease id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob is already leased or the request fails.
+    pub async fn acquire_lease(
+        &self,
+        container: &str,
+        name: &str,
+        seconds: Option<u32>,
+    ) -> Result<String, BlobrsError> {
+        let duration = seconds.map_or_else(|| "-1".to_string(), |s| s.to_string());
+        let response = self
+            .lease(
+                container,
+                name,
+                &[
+                    ("x-ms-lease-action", "acquire"),
+                    ("x-ms-lease-duration", &duration),
+                ],
+            )
+            .await?;
+        response
+            .headers
+            .get("x-ms-lease-id")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| BlobrsError::Parse("lease response without x-ms-lease-id".into()))
+    }
+
+    /// Break a blob's lease immediately, whoever holds it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob is not leased or the request fails.
+    pub async fn break_lease(&self, container: &str, name: &str) -> Result<(), BlobrsError> {
+        self.lease(
+            container,
+            name,
+            &[
+                ("x-ms-lease-action", "break"),
+                ("x-ms-lease-break-period", "0"),
+            ],
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Release a lease held with `lease_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the id does not match the active lease or the request fails.
+    pub async fn release_lease(
+        &self,
+        container: &str,
+        name: &str,
+        lease_id: &str,
+    ) -> Result<(), BlobrsError> {
+        self.lease(
+            container,
+            name,
+            &[
+                ("x-ms-lease-action", "release"),
+                ("x-ms-lease-id", lease_id),
+            ],
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Send a Lease Blob request with the given lease headers.
+    async fn lease(
+        &self,
+        container: &str,
+        name: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<RestResponse, BlobrsError> {
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let response = self
+            .send(
+                reqwest::Method::PUT,
+                &path,
+                &[("comp", "lease")],
+                headers,
+                Vec::new(),
+            )
+            .await?;
+        ensure_success(&response)?;
+        Ok(response)
+    }
+
+    /// List the snapshots of a blob, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any page request fails.
+    pub async fn list_blob_snapshots(
+        &self,
+        container: &str,
+        name: &str,
+    ) -> Result<Vec<BlobSnapshot>, BlobrsError> {
+        let mut snapshots = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("comp", "list"),
+                ("include", "snapshots"),
+                ("prefix", name),
+                ("restype", "container"),
+            ];
+            if let Some(marker) = &marker {
+                query.push(("marker", marker));
+            }
+
+            let xml = self.get_text(container, &query).await?;
+            let (page, next_marker) = parse_snapshots_xml(&xml, name)
+                .map_err(|e| BlobrsError::Parse(format!("snapshot listing: {e}")))?;
+            snapshots.extend(page);
+
+            match next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        snapshots.sort_by(|a, b| b.snapshot.cmp(&a.snapshot));
+        Ok(snapshots)
+    }
+
+    /// Take a snapshot of a blob and return its timestamp.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot could not be created.
+    pub async fn create_snapshot(
+        &self,
+        container: &str,
+        name: &str,
+    ) -> Result<String, BlobrsError> {
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let response = self
+            .send(
+                reqwest::Method::PUT,
+                &path,
+                &[("comp", "snapshot")],
+                &[],
+                Vec::new(),
+            )
+            .await?;
+        ensure_success(&response)?;
+        response
+            .headers
+            .get("x-ms-snapshot")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| BlobrsError::Parse("snapshot response without x-ms-snapshot".into()))
+    }
+
+    /// Download the contents of a blob snapshot into the file at `destination`, one chunk at a
+    /// time, returning the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot does not exist or cannot be read, or the file cannot be
+    /// written.
+    pub async fn download_snapshot(
+        &self,
+        container: &str,
+        name: &str,
+        snapshot: &str,
+        destination: &std::path::Path,
+    ) -> Result<u64, BlobrsError> {
+        use tokio::io::AsyncWriteExt;
+
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let mut response = self
+            .request(
+                reqwest::Method::GET,
+                &path,
+                &[("snapshot", snapshot)],
+                &[],
+                Vec::new(),
+            )
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(status_error(status, &response.text().await?));
+        }
+
+        let write_error =
+            |e| BlobrsError::io(format!("Failed to write {}", destination.display()), e);
+        let mut output = tokio::fs::File::create(destination)
+            .await
+            .map_err(write_error)?;
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await? {
+            output.write_all(&chunk).await.map_err(write_error)?;
+            written += chunk.len() as u64;
+        }
+        output.flush().await.map_err(write_error)?;
+        Ok(written)
+    }
+
+    /// Copy a snapshot over its base blob, returning the copy status.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the copy could not be started.
+    pub async fn restore_snapshot(
+        &self,
+        container: &str,
+        name: &str,
+        snapshot: &str,
+    ) -> Result<String, BlobrsError> {
+        let source_url = format!(
+            "{}/{container}/{}?snapshot={}",
+            self.endpoint,
+            encode_blob_path(name),
+            urlencoding::encode(snapshot)
+        );
+        self.copy_from_url(&source_url, container, name).await
+    }
+
+    /// Whether soft delete is turned on for blobs in this account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the service properties cannot be read.
+    pub async fn blob_soft_delete_enabled(&self) -> Result<bool, BlobrsError> {
+        let xml = self
+            .get_text("", &[("comp", "properties"), ("restype", "service")])
+            .await?;
+        let root = xml::parse(&xml)?;
+        Ok(root
+            .find(&["DeleteRetentionPolicy", "Enabled"])
+            .is_some_and(|enabled| enabled.text == "true"))
+    }
+
+    /// List the soft-deleted blobs under `prefix`: directly under it with a `/` delimiter, or
+    /// at any depth without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any page request fails.
+    pub async fn list_deleted_blobs(
+        &self,
+        container: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+    ) -> Result<Vec<DeletedBlob>, BlobrsError> {
+        let mut deleted = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("comp", "list"),
+                ("include", "deleted"),
+                ("restype", "container"),
+            ];
+            if !prefix.is_empty() {
+                query.push(("prefix", prefix));
+            }
+            if let Some(delimiter) = delimiter {
+                query.push(("delimiter", delimiter));
+            }
+            if let Some(marker) = &marker {
+                query.push(("marker", marker));
+            }
+
+            let xml = self.get_text(container, &query).await?;
+            let (page, next_marker) = parse_deleted_blobs_xml(&xml)
+                .map_err(|e| BlobrsError::Parse(format!("deleted blob listing: {e}")))?;
+            deleted.extend(page);
+
+            match next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Restore a soft-deleted blob and its soft-deleted snapshots.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob cannot be restored, e.g. because its retention ran out.
+    pub async fn undelete_blob(&self, container: &str, name: &str) -> Result<(), BlobrsError> {
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let response = self
+            .send(
+                reqwest::Method::PUT,
+                &path,
+                &[("comp", "undelete")],
+                &[],
+                Vec::new(),
+            )
+            .await?;
+        ensure_success(&response)
+    }
+
+    /// Set a block blob's access tier (`Hot`, `Cool`, `Cold` or `Archive`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob is not a block blob, the account does not support the tier,
+    /// or the request fails.
+    pub async fn set_blob_tier(
+        &self,
+        container: &str,
+        name: &str,
+        tier: &str,
+    ) -> Result<(), BlobrsError> {
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let response = self
+            .send(
+                reqwest::Method::PUT,
+                &path,
+                &[("comp", "tier")],
+                &[("x-ms-access-tier", tier)],
+                Vec::new(),
+            )
+            .await?;
+        ensure_success(&response)
+    }
+
+    /// Names of the page blobs directly under `prefix`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listing fails or cannot be parsed.
+    pub async fn list_page_blobs(
+        &self,
+        container: &str,
+        prefix: &str,
+    ) -> Result<Vec<String>, BlobrsError> {
+        let mut names = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("comp", "list"),
+                ("delimiter", "/"),
+                ("restype", "container"),
+            ];
+            if !prefix.is_empty() {
+                query.push(("prefix", prefix));
+            }
+            if let Some(marker) = &marker {
+                query.push(("marker", marker));
+            }
+
+            let xml = self.get_text(container, &query).await?;
+            let (page, next_marker) = parse_page_blobs_xml(&xml)?;
+            names.extend(page);
+
+            match next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Bytes actually written to a page blob, i.e. the total length of its valid page ranges.
+    /// Sparse disks use far less than their provisioned size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob is not a page blob or the request fails.
+    pub async fn page_blob_used_bytes(
+        &self,
+        container: &str,
+        name: &str,
+    ) -> Result<u64, BlobrsError> {
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let mut used = 0;
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![("comp", "pagelist")];
+            if let Some(marker) = &marker {
+                query.push(("marker", marker));
+            }
+
+            let xml = self.get_text(&path, &query).await?;
+            let (bytes, next_marker) = parse_page_list_xml(&xml)?;
+            used += bytes;
+
+            match next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(used)
+    }
+
+    /// Fetch the index tags of a blob.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob does not exist or the account does not support tags.
+    pub async fn blob_tags(
+        &self,
+        container: &str,
+        name: &str,
+    ) -> Result<BTreeMap<String, String>, BlobrsError> {
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let xml = self.get_text(&path, &[("comp", "tags")]).await?;
+        Ok(parse_tags_xml(&xml))
+    }
+
+    /// Replace every index tag of a blob with `tags`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tags are rejected or the request fails.
+    pub async fn set_blob_tags(
+        &self,
+        container: &str,
+        name: &str,
+        tags: &BTreeMap<String, String>,
+    ) -> Result<(), BlobrsError> {
+        let path = format!("{container}/{}", encode_blob_path(name));
+        let response = self
+            .send(
+                reqwest::Method::PUT,
+                &path,
+                &[("comp", "tags")],
+                &[("Content-Type", "application/xml; charset=UTF-8")],
+                tags_xml(tags).into_bytes(),
+            )
+            .await?;
+        ensure_success(&response)
+    }
+
+    /// Find every blob in a container whose tags match `expression`, e.g.
+    /// `"env" = 'prod' AND "team" = 'data'`.
+    ///
+    /// Follows continuation markers until the listing is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the expression is rejected or any page request fails.
+    pub async fn find_blobs_by_tags(
+        &self,
+        container: &str,
+        expression: &str,
+    ) -> Result<Vec<String>, BlobrsError> {
+        let mut names = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("comp", "blobs"),
+                ("restype", "container"),
+                ("where", expression),
+            ];
+            if let Some(marker) = &marker {
+                query.push(("marker", marker));
+            }
+
+            let xml = self.get_text(container, &query).await?;
+            let (page, next_marker) = parse_tagged_blobs_xml(&xml)?;
+            names.extend(page);
+
+            match next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(names)
+    }
+}
+
+/// Check a tag against the Blob service rules: keys of 1 to 128 and values of up to 256
+/// letters, digits, spaces and `+ - . / : = _`.
+///
+/// # Errors
+///
+/// Returns [`BlobrsError::Invalid`] describing the first rule the tag breaks.
+pub fn validate_tag(key: &str, value: &str) -> Result<(), BlobrsError> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || " +-./:=_".contains(c);
+    if key.is_empty() || key.len() > 128 {
+        return Err(BlobrsError::Invalid(
+            "Tag keys must be 1 to 128 characters".to_string(),
+        ));
+    }
+    if value.len() > 256 {
+        return Err(BlobrsError::Invalid(
+            "Tag values must be at most 256 characters".to_string(),
+        ));
+    }
+    match key.chars().chain(value.chars()).find(|c| !allowed(*c)) {
+        Some(c) => Err(BlobrsError::Invalid(format!("Tags cannot contain '{c}'"))),
+        None => Ok(()),
+    }
+}
+
+/// Check a container name against the Blob service rules: 3 to 63 lowercase letters, digits
+/// and hyphens, starting with a letter or digit, with no consecutive or trailing hyphens.
+///
+/// # Errors
+///
+/// Returns [`BlobrsError::Invalid`] describing the first rule the name breaks.
+pub fn validate_container_name(name: &str) -> Result<(), BlobrsError> {
+    if !(3..=63).contains(&name.len()) {
+        return Err(BlobrsError::Invalid(
+            "Container names must be 3 to 63 characters".to_string(),
+        ));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-'))
+    {
+        return Err(BlobrsError::Invalid(format!(
+            "Container names cannot contain '{c}'"
+        )));
+    }
+    if name.starts_with('-') || name.ends_with('-') {
+        return Err(BlobrsError::Invalid(
+            "Container names must start and end with a letter or digit".to_string(),
+        ));
+    }
+    if name.contains("--") {
+        return Err(BlobrsError::Invalid(
+            "Container names cannot contain consecutive hyphens".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Turn a tag search into a Find Blobs by Tags expression.
+///
+/// `env=prod, team=data` becomes `"env" = 'prod' AND "team" = 'data'`; input that already
+/// contains quotes is assumed to be a full expression and passed through.
+///
+/// # Errors
+///
+/// Returns [`BlobrsError::Invalid`] when a term is not of the form `key=value`.
+pub fn tag_filter_expression(input: &str) -> Result<String, BlobrsError> {
+    let input = input.trim();
+    if input.contains(['\'', '"']) {
+        return Ok(input.to_string());
+    }
+    input
+        .split(',')
+        .map(|term| {
+            let (key, value) = term.split_once('=').ok_or_else(|| {
+                BlobrsError::Invalid(format!("Expected key=value, got '{}'", term.trim()))
+            })?;
+            Ok(format!("\"{}\" = '{}'", key.trim(), value.trim()))
+        })
+        .collect::<Result<Vec<_>, BlobrsError>>()
+        .map(|terms| terms.join(" AND "))
+}
+
+/// Build a Set Blob Tags request body. Tags are assumed to pass [`validate_tag`], so nothing
+/// needs escaping.
+fn tags_xml(tags: &BTreeMap<String, String>) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?><Tags><TagSet>"#);
+    for (key, value) in tags {
+        let _ = write!(xml, "<Tag><Key>{key}</Key><Value>{value}</Value></Tag>");
+    }
+    xml.push_str("</TagSet></Tags>");
+    xml
+}
+
+/// Every property of a single blob, as reported by Get Blob Properties and Get Blob Tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BlobProperties {
+    /// Container name.
+    pub container: String,
+    /// Full blob name.
+    pub name: String,
+    /// Content length in bytes.
+    pub size: u64,
+    /// ETag of the current content.
+    pub etag: Option<String>,
+    /// Last-Modified time, as returned by the service.
+    pub last_modified: Option<String>,
+    /// Creation time, as returned by the service.
+    pub created: Option<String>,
+    /// `BlockBlob`, `PageBlob` or `AppendBlob`.
+    pub blob_type: Option<String>,
+    /// Access tier (`Hot`, `Cool`, `Archive`, ...).
+    pub access_tier: Option<String>,
+    /// Whether the tier was inferred from the account default.
+    pub access_tier_inferred: Option<bool>,
+    /// Lease state (`available`, `leased`, `breaking`, ...).
+    pub lease_state: Option<String>,
+    /// Lease status (`locked` or `unlocked`).
+    pub lease_status: Option<String>,
+    /// `infinite` or `fixed` while the blob is leased.
+    pub lease_duration: Option<String>,
+    /// Version id of the current version when versioning is enabled.
+    pub version_id: Option<String>,
+    /// `Content-Type` header.
+    pub content_type: Option<String>,
+    /// `Content-Encoding` header.
+    pub content_encoding: Option<String>,
+    /// `Content-Language` header.
+    pub content_language: Option<String>,
+    /// `Content-Disposition` header.
+    pub content_disposition: Option<String>,
+    /// `Cache-Control` header.
+    pub cache_control: Option<String>,
+    /// Base64 `Content-MD5` header.
+    pub content_md5: Option<String>,
+    /// End of the blob's time-based retention, as returned by the service.
+    pub immutability_policy_until: Option<String>,
+    /// `unlocked` or `locked` immutability policy.
+    pub immutability_policy_mode: Option<String>,
+    /// Whether a legal hold stops the blob from being modified or deleted.
+    pub legal_hold: bool,
+    /// User-defined metadata (`x-ms-meta-*`).
+    pub metadata: BTreeMap<String, String>,
+    /// Blob index tags.
+    pub tags: Option<BTreeMap<String, String>>,
+}
+
+impl BlobProperties {
+    /// Build properties from a Get Blob Properties response. Tags are left unset.
+    #[must_use]
+    pub fn from_headers(container: &str, name: &str, headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let metadata = headers
+            .iter()
+            .filter_map(|(key, value)| {
+                let key = key.as_str().strip_prefix("x-ms-meta-")?;
+                Some((key.to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+
+        Self {
+            container: container.to_string(),
+            name: name.to_string(),
+            size: header("content-length")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            etag: header("etag"),
+            last_modified: header("last-modified"),
+            created: header("x-ms-creation-time"),
+            blob_type: header("x-ms-blob-type"),
+            access_tier: header("x-ms-access-tier"),
+            access_tier_inferred: header("x-ms-access-tier-inferred").map(|s| s == "true"),
+            lease_state: header("x-ms-lease-state"),
+            lease_status: header("x-ms-lease-status"),
+            lease_duration: header("x-ms-lease-duration"),
+            version_id: header("x-ms-version-id"),
+            content_type: header("content-type"),
+            content_encoding: header("content-encoding"),
+            content_language: header("content-language"),
+            content_disposition: header("content-disposition"),
+            cache_control: header("cache-control"),
+            content_md5: header("content-md5"),
+            immutability_policy_until: header("x-ms-immutability-policy-until-date"),
+            immutability_policy_mode: header("x-ms-immutability-policy-mode"),
+            legal_hold: header("x-ms-legal-hold").is_some_and(|s| s == "true"),
+            metadata,
+            tags: None,
+        }
+    }
+
+    /// Why the blob cannot be overwritten or deleted at `now`, if it is protected.
+    #[must_use]
+    pub fn protection(&self, now: DateTime<Utc>) -> Option<String> {
+        if self.legal_hold {
+            return Some("under a legal hold".to_string());
+        }
+        let until = self.immutability_policy_until.as_deref()?;
+        let expiry = DateTime::parse_from_rfc2822(until)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok();
+        if expiry.is_some_and(|expiry| expiry <= now) {
+            return None;
+        }
+        let until = expiry.map_or_else(
+            || until.to_string(),
+            |expiry| expiry.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        );
+        Some(match self.immutability_policy_mode.as_deref() {
+            Some(mode) => format!("immutable until {until} ({mode} policy)"),
+            None => format!("immutable until {until}"),
+        })
+    }
+}
+
+/// A clearer explanation for the 409 errors returned when changing an immutable blob.
+#[must_use]
+pub fn immutability_hint(error: &str) -> Option<&'static str> {
+    if error.contains("BlobImmutableDueToLegalHold") {
+        Some("the blob is under a legal hold")
+    } else if error.contains("BlobImmutableDueToPolicy") {
+        Some("the blob is protected by an immutability policy")
+    } else {
+        None
+    }
+}
+
+/// Percent-encode each segment of a blob name for use in a request path.
+fn encode_blob_path(name: &str) -> String {
+    name.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Map a non-success response to an error, classified by status code.
+///
+/// # Errors
+///
+/// Returns an error describing the status when the response is not a success.
+pub fn ensure_success(response: &RestResponse) -> Result<(), BlobrsError> {
+    if response.status.is_success() {
+        return Ok(());
+    }
+    Err(status_error(response.status, &response.body))
+}
+
+/// Whether a copy with this `x-ms-copy-status` is done, or the error it ended with.
+fn copy_finished(status: &str, description: Option<&str>) -> Result<bool, BlobrsError> {
+    match status {
+        "success" => Ok(true),
+        "pending" => Ok(false),
+        _ => Err(BlobrsError::Service(match description {
+            Some(description) => format!("Copy {status}: {description}"),
+            None => format!("Copy {status}"),
+        })),
+    }
+}
+
+/// Whether a status means the request may succeed if sent again later.
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    matches!(
+        status,
+        reqwest::StatusCode::TOO_MANY_REQUESTS
+            | reqwest::StatusCode::INTERNAL_SERVER_ERROR
+            | reqwest::StatusCode::BAD_GATEWAY
+            | reqwest::StatusCode::SERVICE_UNAVAILABLE
+            | reqwest::StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Whether sending a request twice has the same effect as sending it once, so that it is safe
+/// to retry after a failure. Not so for a Blob Batch `POST`, whose sub-requests may have run,
+/// or for creating a snapshot, which would make a second one.
+fn is_idempotent(method: &reqwest::Method, query: &[(&str, &str)]) -> bool {
+    match *method {
+        reqwest::Method::POST => false,
+        reqwest::Method::PUT => !query.contains(&("comp", "snapshot")),
+        _ => true,
+    }
+}
+
+/// The wait requested by a `Retry-After` header, given in seconds or as an HTTP date.
+fn retry_after(headers: &reqwest::header::HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    Some((at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Classify a non-success status and its response body as an error.
+fn status_error(status: reqwest::StatusCode, body: &str) -> BlobrsError {
+    let message = format!(
+        "HTTP {} {} - {body}",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    );
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            BlobrsError::Auth(message)
+        }
+        reqwest::StatusCode::NOT_FOUND => BlobrsError::NotFound(message),
+        _ => BlobrsError::Service(message),
+    }
+}
+
+/// Build the Shared Key string-to-sign.
+fn string_to_sign(
+    method: &str,
+    content_length: &str,
+    headers: &[(String, String)],
+    canonicalized_resource: &str,
+) -> String {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map_or("", |(_, v)| v.as_str())
+    };
+
+    let mut ms_headers: Vec<&(String, String)> = headers
+        .iter()
+        .filter(|(k, _)| k.starts_with("x-ms-"))
+        .collect();
+    ms_headers.sort_by(|a, b| a.0.cmp(&b.0));
+    let canonicalized_headers: String = ms_headers
+        .iter()
+        .map(|(k, v)| format!("{k}:{}\n", v.trim()))
+        .collect();
+
+    format!(
+        "{method}\n{}\n{}\n{content_length}\n{}\n{}\n\n{}\n{}\n{}\n{}\n{}\n{canonicalized_headers}{canonicalized_resource}",
+        header("content-encoding"),
+        header("content-language"),
+        header("content-md5"),
+        header("content-type"),
+        header("if-modified-since"),
+        header("if-match"),
+        header("if-none-match"),
+        header("if-unmodified-since"),
+        header("range"),
+    )
+}
+
+/// Build the Shared Key canonicalized resource for a path and query.
+fn canonicalized_resource(account: &str, path: &str, query: &[(&str, &str)]) -> String {
+    let mut params: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for (name, value) in query {
+        params.entry(name.to_lowercase()).or_default().push(value);
+    }
+
+    let mut resource = format!("/{account}/{path}");
+    for (name, mut values) in params {
+        values.sort_unstable();
+        let _ = write!(resource, "\n{name}:{}", values.join(","));
+    }
+    resource
+}
+
+/// A single blob version returned by a versioned listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobVersion {
+    /// Full blob name.
+    pub name: String,
+    /// Version identifier (an RFC 3339 timestamp).
+    pub version_id: Option<String>,
+    /// Whether this is the current (live) version.
+    pub is_current: bool,
+    /// Content length in bytes.
+    pub size: u64,
+    /// Last-Modified time of this version.
+    pub last_modified: Option<DateTime<Utc>>,
+    /// ETag of this version.
+    pub etag: Option<String>,
+    /// When it was deleted, for a deleted blob or version.
+    pub deleted_time: Option<DateTime<Utc>>,
+}
+
+/// A blob returned by List Blobs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedBlob {
+    /// Full blob name.
+    pub name: String,
+    /// Content length in bytes.
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub etag: Option<String>,
+    /// Access tier (`Hot`, `Cool`, `Archive`, ...), if the account reports one.
+    pub access_tier: Option<String>,
+}
+
+/// The result of listing blobs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlobListing {
+    pub blobs: Vec<ListedBlob>,
+    /// Common prefixes (virtual folders) when listing with a delimiter.
+    pub prefixes: Vec<String>,
+}
+
+/// A read-only, point-in-time copy of a blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobSnapshot {
+    /// Snapshot timestamp, which also identifies the snapshot.
+    pub snapshot: String,
+    /// Content length in bytes.
+    pub size: u64,
+}
+
+/// A soft-deleted blob that can still be restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedBlob {
+    /// Full blob name.
+    pub name: String,
+    /// Content length in bytes.
+    pub size: u64,
+    /// When the blob was deleted.
+    pub deleted_time: Option<DateTime<Utc>>,
+}
+
+/// The result of listing blob versions.
+#[derive(Debug, Clone, Default)]
+pub struct VersionListing {
+    /// Every version found.
+    pub versions: Vec<BlobVersion>,
+    /// Common prefixes (virtual folders) when listing with a delimiter.
+    pub prefixes: Vec<String>,
+}
+
+/// One page of a versioned listing.
+struct VersionPage {
+    versions: Vec<BlobVersion>,
+    prefixes: Vec<String>,
+    next_marker: Option<String>,
+}
+
+/// A container returned by List Containers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerSummary {
+    /// Container name.
+    pub name: String,
+    /// Last time the container or its properties were modified.
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Properties and metadata included in the listing.
+    pub properties: ContainerProperties,
+}
+
+/// Properties of a container, as reported by Get Container Properties.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContainerProperties {
+    /// Container name.
+    pub name: String,
+    /// Last-Modified time, as returned by the service.
+    pub last_modified: Option<String>,
+    /// `container` or `blob` when anonymous reads are allowed; `None` means private.
+    pub public_access: Option<String>,
+    /// Lease state (`available`, `leased`, `breaking`, ...).
+    pub lease_state: Option<String>,
+    /// Lease status (`locked` or `unlocked`).
+    pub lease_status: Option<String>,
+    /// `infinite` or `fixed` while the container is leased.
+    pub lease_duration: Option<String>,
+    /// Whether a container-level immutability policy is set.
+    pub immutability_policy: bool,
+    /// Whether a legal hold is set on the container.
+    pub legal_hold: bool,
+    /// User-defined metadata (`x-ms-meta-*`).
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl ContainerProperties {
+    /// Build properties from a Get Container Properties response.
+    #[must_use]
+    pub fn from_headers(name: &str, headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let metadata = headers
+            .iter()
+            .filter_map(|(key, value)| {
+                let key = key.as_str().strip_prefix("x-ms-meta-")?;
+                Some((key.to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            last_modified: header("last-modified"),
+            public_access: header("x-ms-blob-public-access"),
+            lease_state: header("x-ms-lease-state"),
+            lease_status: header("x-ms-lease-status"),
+            lease_duration: header("x-ms-lease-duration"),
+            immutability_policy: header("x-ms-has-immutability-policy")
+                .is_some_and(|s| s == "true"),
+            legal_hold: header("x-ms-has-legal-hold").is_some_and(|s| s == "true"),
+            metadata,
+        }
+    }
+}
+
+impl ContainerProperties {
+    /// Build properties from a `<Container>` entry of List Containers.
+    fn from_listing(name: &str, container: &xml::Element) -> Self {
+        let property = |key: &str| container.find(&["Properties", key]).map(|p| p.text.clone());
+        let metadata = container
+            .find(&["Metadata"])
+            .into_iter()
+            .flat_map(|metadata| &metadata.children)
+            .map(|entry| (entry.name.clone(), entry.text.clone()))
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            last_modified: property("Last-Modified"),
+            public_access: property("PublicAccess"),
+            lease_state: property("LeaseState"),
+            lease_status: property("LeaseStatus"),
+            lease_duration: property("LeaseDuration"),
+            immutability_policy: property("HasImmutabilityPolicy").is_some_and(|s| s == "true"),
+            legal_hold: property("HasLegalHold").is_some_and(|s| s == "true"),
+            metadata,
+        }
+    }
+}
+
+/// Parse a List Containers response into containers and the next page marker.
+fn parse_containers_xml(xml: &str) -> color_eyre::Result<(Vec<ContainerSummary>, Option<String>)> {
+    let root = xml::parse(xml)?;
+    let containers = root
+        .find(&["Containers"])
+        .into_iter()
+        .flat_map(|containers| containers.children("Container"))
+        .filter_map(|container| {
+            let name = container.non_empty("Name")?;
+            let properties = ContainerProperties::from_listing(&name, container);
+            Some(ContainerSummary {
+                last_modified: rfc2822(properties.last_modified.as_deref()),
+                name,
+                properties,
+            })
+        })
+        .collect();
+    Ok((containers, root.non_empty("NextMarker")))
+}
+
+/// The `<Blob>` entries of a List Blobs or Find Blobs by Tags response.
+fn listed_blobs(root: &xml::Element) -> impl Iterator<Item = &xml::Element> {
+    root.find(&["Blobs"])
+        .into_iter()
+        .flat_map(|blobs| blobs.children("Blob"))
+}
+
+/// A property of a listed blob, from its `<Properties>` element.
+fn blob_property<'a>(blob: &'a xml::Element, name: &str) -> Option<&'a str> {
+    blob.find(&["Properties", name]).map(|p| p.text.as_str())
+}
+
+/// The `Content-Length` of a listed blob, or 0 when it is missing.
+fn blob_size(blob: &xml::Element) -> u64 {
+    blob_property(blob, "Content-Length")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Parse an RFC 2822 time as the service writes them.
+fn rfc2822(time: Option<&str>) -> Option<DateTime<Utc>> {
+    time.and_then(|s| DateTime::parse_from_rfc2822(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Parse a List Blobs response that includes versions.
+fn parse_blob_versions_xml(xml: &str) -> color_eyre::Result<VersionPage> {
+    let root = xml::parse(xml)?;
+    let versions = listed_blobs(&root)
+        .filter_map(|blob| {
+            Some(BlobVersion {
+                name: blob.value("Name")?.to_string(),
+                version_id: blob.non_empty("VersionId"),
+                is_current: blob.value("IsCurrentVersion") == Some("true"),
+                size: blob_size(blob),
+                last_modified: rfc2822(blob_property(blob, "Last-Modified")),
+                etag: blob_property(blob, "Etag").map(str::to_string),
+                deleted_time: rfc2822(blob_property(blob, "DeletedTime"))
+                    .filter(|_| blob.value("Deleted") == Some("true")),
+            })
+        })
+        .collect();
+    let prefixes = root
+        .find(&["Blobs"])
+        .into_iter()
+        .flat_map(|blobs| blobs.children("BlobPrefix"))
+        .filter_map(|prefix| prefix.non_empty("Name"))
+        .collect();
+
+    Ok(VersionPage {
+        versions,
+        prefixes,
+        next_marker: root.non_empty("NextMarker"),
+    })
+}
+
+/// Parse a List Blobs response into its blobs and folders, and the next page marker.
+fn parse_blob_listing_xml(xml: &str) -> color_eyre::Result<(BlobListing, Option<String>)> {
+    let root = xml::parse(xml)?;
+    let blobs = listed_blobs(&root)
+        .filter_map(|blob| {
+            Some(ListedBlob {
+                name: blob.value("Name")?.to_string(),
+                size: blob_size(blob),
+                last_modified: rfc2822(blob_property(blob, "Last-Modified")),
+                etag: blob_property(blob, "Etag").map(str::to_string),
+                access_tier: blob_property(blob, "AccessTier").map(str::to_string),
+            })
+        })
+        .collect();
+    let prefixes = root
+        .find(&["Blobs"])
+        .into_iter()
+        .flat_map(|blobs| blobs.children("BlobPrefix"))
+        .filter_map(|prefix| prefix.non_empty("Name"))
+        .collect();
+    Ok((
+        BlobListing { blobs, prefixes },
+        root.non_empty("NextMarker"),
+    ))
+}
+
+/// Parse a List Blobs response that includes snapshots, keeping those of the blob `name`.
+fn parse_snapshots_xml(
+    xml: &str,
+    name: &str,
+) -> color_eyre::Result<(Vec<BlobSnapshot>, Option<String>)> {
+    let root = xml::parse(xml)?;
+    let snapshots = listed_blobs(&root)
+        .filter(|blob| blob.value("Name") == Some(name))
+        .filter_map(|blob| {
+            Some(BlobSnapshot {
+                snapshot: blob.non_empty("Snapshot")?,
+                size: blob_size(blob),
+            })
+        })
+        .collect();
+    Ok((snapshots, root.non_empty("NextMarker")))
+}
+
+/// Parse a List Blobs response that includes deleted blobs, keeping only the deleted ones.
+fn parse_deleted_blobs_xml(xml: &str) -> color_eyre::Result<(Vec<DeletedBlob>, Option<String>)> {
+    let root = xml::parse(xml)?;
+    let deleted = listed_blobs(&root)
+        .filter(|blob| blob.value("Deleted") == Some("true") && blob.child("Snapshot").is_none())
+        .filter_map(|blob| {
+            Some(DeletedBlob {
+                name: blob.value("Name")?.to_string(),
+                size: blob_size(blob),
+                deleted_time: rfc2822(blob_property(blob, "DeletedTime")),
+            })
+        })
+        .collect();
+    Ok((deleted, root.non_empty("NextMarker")))
+}
+
+/// Parse a Get Blob Tags response into a key/value map. Malformed responses have no tags.
+fn parse_tags_xml(xml: &str) -> BTreeMap<String, String> {
+    xml::parse(xml).map_or_else(|_| BTreeMap::new(), |root| tag_set(&root))
+}
+
+/// The tags of a `<Tags>` element.
+fn tag_set(tags: &xml::Element) -> BTreeMap<String, String> {
+    tags.find(&["TagSet"])
+        .into_iter()
+        .flat_map(|set| set.children("Tag"))
+        .filter_map(|tag| {
+            Some((
+                tag.value("Key")?.to_string(),
+                tag.value("Value")?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Parse a Find Blobs by Tags response into blob names and the next page marker.
+fn parse_tagged_blobs_xml(xml: &str) -> Result<(Vec<String>, Option<String>), BlobrsError> {
+    let root = xml::parse(xml)?;
+    let names = listed_blobs(&root)
+        .filter_map(|blob| blob.non_empty("Name"))
+        .collect();
+    Ok((names, root.non_empty("NextMarker")))
+}
+
+/// `Content-ID`, status line and error code of a Blob Batch response part.
+static BATCH_PART_PATTERNS: LazyLock<(Regex, Regex, Regex)> = LazyLock::new(|| {
+    let compile = |pattern| Regex::new(pattern).expect("batch response pattern is valid");
+    (
+        compile(r"(?i)Content-ID:\s*(\d+)"),
+        compile(r"HTTP/1\.1 (\d{3})([^\r\n]*)"),
+        compile(r"(?i)x-ms-error-code:\s*(\S+)"),
+    )
+});
+
+/// Parse a Blob Batch response into the outcome of each sub-request, by `Content-ID`.
+fn parse_batch_response(body: &str) -> BTreeMap<usize, Result<(), BatchFailure>> {
+    let (id_regex, status_regex, code_regex) = &*BATCH_PART_PATTERNS;
+    // Every part starts with a boundary line beginning with `--batchresponse_`
+    body.split("--batchresponse_")
+        .filter_map(|part| {
+            let id = id_regex.captures(part)?.get(1)?.as_str().parse().ok()?;
+            let status = status_regex.captures(part)?;
+            let code: u16 = status.get(1)?.as_str().parse().ok()?;
+            let outcome = if (200..300).contains(&code) {
+                Ok(())
+            } else {
+                let reason = code_regex.captures(part).map_or_else(
+                    || status.get(2).map_or("", |m| m.as_str()).trim().to_string(),
+                    |c| c[1].to_string(),
+                );
+                Err(BatchFailure::Status { code, reason })
+            };
+            Some((id, outcome))
+        })
+        .collect()
+}
+
+/// Parse a blob listing into the names of its page blobs and the next page marker.
+fn parse_page_blobs_xml(xml: &str) -> Result<(Vec<String>, Option<String>), BlobrsError> {
+    let root = xml::parse(xml)?;
+    let names = listed_blobs(&root)
+        .filter(|blob| blob_property(blob, "BlobType") == Some("PageBlob"))
+        .filter_map(|blob| blob.non_empty("Name"))
+        .collect();
+    Ok((names, root.non_empty("NextMarker")))
+}
+
+/// Parse a Get Page Ranges response into the bytes covered by valid pages and the next marker.
+fn parse_page_list_xml(xml: &str) -> Result<(u64, Option<String>), BlobrsError> {
+    let root = xml::parse(xml)?;
+    let mut used = 0;
+    for range in root.children("PageRange") {
+        let start: u64 = range
+            .value("Start")
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| BlobrsError::Parse("page ranges: a range has no Start".to_string()))?;
+        let end: u64 = range
+            .value("End")
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| BlobrsError::Parse("page ranges: a range has no End".to_string()))?;
+        used += end.saturating_sub(start) + 1;
+    }
+    Ok((used, root.non_empty("NextMarker")))
+}
+
+/// Select, for every blob name, the version that was current at `as_of`: the newest created at
+/// or before it, unless the blob had been deleted by then.
+///
+/// A blob whose earliest version is newer than `as_of` did not exist yet and is omitted. Blobs
+/// listed without a version id (versioning disabled) are treated as having always existed. A
+/// version stayed current until a newer one was written, and the last version of a blob with
+/// no current version until the blob was deleted; a deletion the listing gives no time for
+/// cannot be placed, so that version is kept.
+#[must_use]
+pub fn versions_as_of(versions: &[BlobVersion], as_of: DateTime<Utc>) -> Vec<BlobVersion> {
+    /// What the listing says about one blob name.
+    #[derive(Default)]
+    struct History<'a> {
+        latest: Option<(Option<DateTime<Utc>>, &'a BlobVersion)>,
+        /// Whether a version was written after `as_of`, ending the latest one's time as current.
+        replaced: bool,
+        /// When the blob itself was deleted, from a deleted entry without a version id.
+        deleted: Option<DateTime<Utc>>,
+    }
+    let mut names: BTreeMap<&str, History> = BTreeMap::new();
+
+    for version in versions {
+        let history = names.entry(version.name.as_str()).or_default();
+        let created = match &version.version_id {
+            Some(id) => match DateTime::parse_from_rfc3339(id) {
+                Ok(dt) => Some(dt.with_timezone(&Utc)),
+                Err(_) => continue,
+            },
+            None if version.deleted_time.is_some() => {
+                history.deleted = history.deleted.max(version.deleted_time);
+                continue;
+            }
+            None => None,
+        };
+        if created.is_some_and(|created| created > as_of) {
+            history.replaced = true;
+            continue;
+        }
+        if history
+            .latest
+            .is_none_or(|(existing, _)| created > existing)
+        {
+            history.latest = Some((created, version));
+        }
+    }
+
+    names
+        .into_values()
+        .filter_map(|history| {
+            let (_, version) = history.latest?;
+            let deleted = version.deleted_time.or(history.deleted);
+            let gone = !version.is_current
+                && !history.replaced
+                && deleted.is_some_and(|deleted| deleted <= as_of);
+            (!gone).then(|| version.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn version(name: &str, id: &str) -> BlobVersion {
+        BlobVersion {
+            name: name.to_string(),
+            version_id: Some(id.to_string()),
+            is_current: false,
+            size: 1,
+            last_modified: None,
+            etag: None,
+            deleted_time: None,
+        }
+    }
+
+    #[test]
+    fn canonicalized_resource_sorts_query_params() {
+        let resource = canonicalized_resource(
+            "acct",
+            "container",
+            &[
+                ("restype", "container"),
+                ("comp", "list"),
+                ("include", "versions"),
+            ],
+        );
+        assert_eq!(
+            resource,
+            "/acct/container\ncomp:list\ninclude:versions\nrestype:container"
+        );
+    }
+
+    #[test]
+    fn error_statuses_are_classified() {
+        let response = |status: u16| RestResponse {
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+            headers: reqwest::header::HeaderMap::new(),
+            body: String::new(),
+        };
+        assert!(ensure_success(&response(200)).is_ok());
+        assert!(matches!(
+            ensure_success(&response(403)),
+            Err(BlobrsError::Auth(_))
+        ));
+        assert!(matches!(
+            ensure_success(&response(404)),
+            Err(BlobrsError::NotFound(_))
+        ));
+        assert!(matches!(
+            ensure_success(&response(409)),
+            Err(BlobrsError::Service(message)) if message.starts_with("HTTP 409 Conflict")
+        ));
+    }
+
+    #[test]
+    fn emulator_client_uses_path_style_urls() {
+        let client =
+            AzureRestClient::emulator("devstoreaccount1", "a2V5", "http://127.0.0.1:10000/")
+                .unwrap();
+        assert_eq!(client.endpoint, "http://127.0.0.1:10000/devstoreaccount1");
+        assert_eq!(client.resource_prefix, "devstoreaccount1/");
+
+        let client = AzureRestClient::new("acct", "a2V5").unwrap();
+        assert_eq!(client.endpoint, "https://acct.blob.core.windows.net");
+        assert_eq!(client.resource_prefix, "");
+    }
+
+    #[test]
+    fn parses_container_listing() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://acct.blob.core.windows.net/">
+  <Marker>prev</Marker>
+  <Containers>
+    <Container><Name>raw</Name><Properties /></Container>
+    <Container>
+      <Name>curated</Name>
+      <Properties>
+        <Last-Modified>Sat, 01 Jun 2024 10:00:00 GMT</Last-Modified>
+        <LeaseStatus>locked</LeaseStatus>
+        <LeaseState>leased</LeaseState>
+        <PublicAccess>blob</PublicAccess>
+        <HasLegalHold>true</HasLegalHold>
+      </Properties>
+      <Metadata>
+        <Name>not the container name</Name>
+        <owner>data &amp; ml</owner>
+      </Metadata>
+    </Container>
+  </Containers>
+  <NextMarker>/acct/next</NextMarker>
+</EnumerationResults>"#;
+        let (containers, next_marker) = parse_containers_xml(xml).unwrap();
+        let names: Vec<_> = containers.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["raw", "curated"]);
+        assert_eq!(containers[0].last_modified, None);
+        assert_eq!(containers[0].properties.public_access, None);
+        assert_eq!(
+            containers[1].last_modified,
+            Utc.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).single()
+        );
+
+        let properties = &containers[1].properties;
+        assert_eq!(properties.lease_state.as_deref(), Some("leased"));
+        assert_eq!(properties.public_access.as_deref(), Some("blob"));
+        assert!(properties.legal_hold);
+        assert_eq!(properties.metadata["owner"], "data & ml");
+        assert_eq!(properties.metadata["Name"], "not the container name");
+        assert_eq!(next_marker.as_deref(), Some("/acct/next"));
+
+        assert!(parse_containers_xml("<EnumerationResults><Containers>").is_err());
+    }
+
+    #[test]
+    fn parses_versioned_listing() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ContainerName="c">
+  <Blobs>
+    <BlobPrefix><Name>logs/</Name></BlobPrefix>
+    <Blob>
+      <Name>a.txt</Name>
+      <VersionId>2024-06-01T10:00:00.1234567Z</VersionId>
+      <IsCurrentVersion>true</IsCurrentVersion>
+      <Properties>
+        <Last-Modified>Sat, 01 Jun 2024 10:00:00 GMT</Last-Modified>
+        <Etag>0x1</Etag>
+        <Content-Length>42</Content-Length>
+      </Properties>
+    </Blob>
+  </Blobs>
+  <NextMarker>abc</NextMarker>
+</EnumerationResults>"#;
+        let page = parse_blob_versions_xml(xml).unwrap();
+        assert_eq!(page.prefixes, vec!["logs/".to_string()]);
+        assert_eq!(page.versions.len(), 1);
+        assert_eq!(page.versions[0].name, "a.txt");
+        assert_eq!(page.versions[0].size, 42);
+        assert!(page.versions[0].is_current);
+        assert!(page.versions[0].last_modified.is_some());
+        assert_eq!(page.next_marker.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn copies_finish_on_success_and_fail_on_failed_or_aborted() {
+        assert!(copy_finished("success", None).unwrap());
+        assert!(!copy_finished("pending", None).unwrap());
+        let Err(BlobrsError::Service(message)) = copy_finished("failed", Some("500 InternalError"))
+        else {
+            panic!("Expected a failed copy to be an error");
+        };
+        assert_eq!(message, "Copy failed: 500 InternalError");
+        assert!(copy_finished("aborted", None).is_err());
+    }
+
+    #[test]
+    fn blob_properties_from_headers_and_tags() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in [
+            ("content-length", "42"),
+            ("etag", "\"0x8DC\""),
+            ("content-type", "text/csv"),
+            ("x-ms-access-tier", "Cool"),
+            ("x-ms-access-tier-inferred", "false"),
+            ("x-ms-meta-owner", "team-x"),
+            ("x-ms-lease-state", "leased"),
+            ("x-ms-lease-duration", "infinite"),
+        ] {
+            headers.insert(name, value.parse().unwrap());
+        }
+
+        let properties = BlobProperties::from_headers("raw", "dir/a.csv", &headers);
+        assert_eq!(properties.size, 42);
+        assert_eq!(properties.etag.as_deref(), Some("\"0x8DC\""));
+        assert_eq!(properties.content_type.as_deref(), Some("text/csv"));
+        assert_eq!(properties.access_tier.as_deref(), Some("Cool"));
+        assert_eq!(properties.access_tier_inferred, Some(false));
+        assert_eq!(properties.metadata["owner"], "team-x");
+        assert_eq!(properties.lease_state.as_deref(), Some("leased"));
+        assert_eq!(properties.lease_duration.as_deref(), Some("infinite"));
+        assert_eq!(properties.lease_status, None);
+        assert_eq!(properties.cache_control, None);
+        assert!(!properties.legal_hold);
+        assert_eq!(properties.protection(Utc::now()), None);
+
+        let tags = parse_tags_xml(
+            "<Tags><TagSet><Tag><Key>env</Key><Value>prod</Value></Tag>\
+             <Tag><Key>pii</Key><Value>no</Value></Tag></TagSet></Tags>",
+        );
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags["env"], "prod");
+        assert_eq!(encode_blob_path("my dir/a b.csv"), "my%20dir/a%20b.csv");
+    }
+
+    #[test]
+    fn container_properties_from_headers() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in [
+            ("last-modified", "Mon, 01 Jan 2024 10:00:00 GMT"),
+            ("x-ms-blob-public-access", "blob"),
+            ("x-ms-lease-status", "unlocked"),
+            ("x-ms-lease-state", "available"),
+            ("x-ms-has-legal-hold", "true"),
+            ("x-ms-has-immutability-policy", "false"),
+            ("x-ms-meta-owner", "team-x"),
+        ] {
+            headers.insert(name, value.parse().unwrap());
+        }
+
+        let properties = ContainerProperties::from_headers("raw", &headers);
+        assert_eq!(properties.name, "raw");
+        assert_eq!(properties.public_access.as_deref(), Some("blob"));
+        assert_eq!(properties.lease_state.as_deref(), Some("available"));
+        assert!(properties.legal_hold);
+        assert!(!properties.immutability_policy);
+        assert_eq!(properties.metadata["owner"], "team-x");
+
+        let private = ContainerProperties::from_headers("raw", &reqwest::header::HeaderMap::new());
+        assert_eq!(private.public_access, None);
+        assert!(private.metadata.is_empty());
+    }
+
+    #[test]
+    fn batch_responses_report_each_sub_request() {
+        let body = "--batchresponse_66925647-d0cb-4109-b6d3-28efe3e1e5ed\r\n\
+             Content-Type: application/http\r\n\
+             Content-ID: 0\r\n\r\n\
+             HTTP/1.1 202 Accepted\r\n\
+             x-ms-delete-type-permanent: true\r\n\
+             x-ms-version: 2021-12-02\r\n\r\n\
+             --batchresponse_66925647-d0cb-4109-b6d3-28efe3e1e5ed\r\n\
+             Content-Type: application/http\r\n\
+             Content-ID: 1\r\n\r\n\
+             HTTP/1.1 409 Conflict\r\n\
+             x-ms-error-code: BlobImmutableDueToLegalHold\r\n\r\n\
+             --batchresponse_66925647-d0cb-4109-b6d3-28efe3e1e5ed\r\n\
+             Content-Type: application/http\r\n\
+             Content-ID: 2\r\n\r\n\
+             HTTP/1.1 404 The specified blob does not exist.\r\n\r\n\
+             --batchresponse_66925647-d0cb-4109-b6d3-28efe3e1e5ed--";
+        let statuses = parse_batch_response(body);
+        assert_eq!(statuses.len(), 3);
+        assert_eq!(statuses[&0], Ok(()));
+        assert_eq!(
+            statuses[&1],
+            Err(BatchFailure::Status {
+                code: 409,
+                reason: "BlobImmutableDueToLegalHold".to_string()
+            })
+        );
+        let not_found = statuses[&2].clone().unwrap_err();
+        assert_eq!(
+            not_found.to_string(),
+            "404 The specified blob does not exist."
+        );
+        assert_eq!(
+            BatchFailure::Missing.to_string(),
+            "missing from the batch response"
+        );
+    }
+
+    #[test]
+    fn immutable_blobs_explain_their_protection() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "x-ms-immutability-policy-until-date",
+            "Wed, 01 Jan 2025 00:00:00 GMT".parse().unwrap(),
+        );
+        headers.insert("x-ms-immutability-policy-mode", "locked".parse().unwrap());
+        let mut properties = BlobProperties::from_headers("raw", "a.csv", &headers);
+
+        let before = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            properties.protection(before).as_deref(),
+            Some("immutable until 2025-01-01 00:00:00 UTC (locked policy)")
+        );
+        assert_eq!(properties.protection(after), None);
+
+        properties.legal_hold = true;
+        assert_eq!(
+            properties.protection(after).as_deref(),
+            Some("under a legal hold")
+        );
+        assert_eq!(
+            immutability_hint("409 Conflict: BlobImmutableDueToPolicy"),
+            Some("the blob is protected by an immutability policy")
+        );
+        assert_eq!(immutability_hint("404 Not Found"), None);
+    }
+
+    #[test]
+    fn parses_snapshot_listing_for_one_blob() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ContainerName="c">
+  <Blobs>
+    <Blob>
+      <Name>a.csv</Name>
+      <Snapshot>2024-06-01T10:00:00.1234567Z</Snapshot>
+      <Properties><Content-Length>10</Content-Length></Properties>
+    </Blob>
+    <Blob><Name>a.csv</Name><Properties><Content-Length>12</Content-Length></Properties></Blob>
+    <Blob>
+      <Name>a.csv.bak</Name>
+      <Snapshot>2024-06-02T10:00:00.0000000Z</Snapshot>
+      <Properties><Content-Length>3</Content-Length></Properties>
+    </Blob>
+  </Blobs>
+  <NextMarker />
+</EnumerationResults>"#;
+        let (snapshots, next_marker) = parse_snapshots_xml(xml, "a.csv").unwrap();
+        assert_eq!(
+            snapshots,
+            vec![BlobSnapshot {
+                snapshot: "2024-06-01T10:00:00.1234567Z".to_string(),
+                size: 10,
+            }]
+        );
+        assert_eq!(next_marker, None);
+    }
+
+    #[test]
+    fn parses_blob_listing_with_tiers() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ContainerName="c">
+  <Blobs>
+    <BlobPrefix><Name>2024/q1/</Name></BlobPrefix>
+    <Blob>
+      <Name>2024/a.csv</Name>
+      <Properties>
+        <Last-Modified>Sat, 01 Jun 2024 10:00:00 GMT</Last-Modified>
+        <Etag>0x8DC1</Etag>
+        <Content-Length>7</Content-Length>
+        <AccessTier>Cool</AccessTier>
+      </Properties>
+    </Blob>
+  </Blobs>
+  <NextMarker>page2</NextMarker>
+</EnumerationResults>"#;
+        let (listing, next_marker) = parse_blob_listing_xml(xml).unwrap();
+        assert_eq!(listing.prefixes, ["2024/q1/"]);
+        assert_eq!(
+            listing.blobs,
+            vec![ListedBlob {
+                name: "2024/a.csv".to_string(),
+                size: 7,
+                last_modified: Utc.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).single(),
+                etag: Some("0x8DC1".to_string()),
+                access_tier: Some("Cool".to_string()),
+            }]
+        );
+        assert_eq!(next_marker.as_deref(), Some("page2"));
+    }
+
+    #[test]
+    fn listings_show_folders_then_blobs() {
+        use crate::export::{self, EntryKind, ListingFormat};
+
+        let table = |xml: &str| {
+            let (listing, _) = parse_blob_listing_xml(xml).unwrap();
+            let entries = export::blob_entries(listing);
+            let mut out = Vec::new();
+            export::write_listing(&mut out, &entries, ListingFormat::Table).unwrap();
+            (entries, String::from_utf8(out).unwrap())
+        };
+        let blob = |name: &str| {
+            format!(
+                "<Blob><Name>{name}</Name><Properties><Content-Length>4</Content-Length>\
+                 </Properties></Blob>"
+            )
+        };
+
+        // The service lists blobs and folders interleaved by name
+        let delimited = format!(
+            "<EnumerationResults><Blobs>{}<BlobPrefix><Name>2024/q1/</Name></BlobPrefix>\
+             <BlobPrefix><Name>2024/q2/</Name></BlobPrefix></Blobs></EnumerationResults>",
+            blob("2024/a.csv")
+        );
+        let (entries, lines) = table(&delimited);
+        let kinds: Vec<_> = entries.iter().map(|entry| entry.kind).collect();
+        assert_eq!(
+            kinds,
+            [EntryKind::Folder, EntryKind::Folder, EntryKind::Blob]
+        );
+        let lines: Vec<_> = lines.lines().collect();
+        assert!(lines[0].ends_with("DIR  2024/q1/"));
+        assert!(lines[1].ends_with("DIR  2024/q2/"));
+        assert!(lines[2].ends_with("           4  2024/a.csv"));
+
+        // Without a delimiter, as `ls --recursive` lists, there are no folders
+        let recursive = format!(
+            "<EnumerationResults><Blobs>{}{}{}</Blobs></EnumerationResults>",
+            blob("2024/a.csv"),
+            blob("2024/q1/b.csv"),
+            blob("2024/q2/c.csv")
+        );
+        let (entries, lines) = table(&recursive);
+        assert_eq!(entries.len(), 3);
+        assert!(!lines.contains("DIR"));
+    }
+
+    #[test]
+    fn parses_deleted_blobs_only() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ContainerName="c">
+  <Blobs>
+    <Blob><Name>live.csv</Name><Properties><Content-Length>5</Content-Length></Properties></Blob>
+    <Blob>
+      <Name>gone.csv</Name>
+      <Deleted>true</Deleted>
+      <Properties>
+        <Content-Length>7</Content-Length>
+        <DeletedTime>Sat, 01 Jun 2024 10:00:00 GMT</DeletedTime>
+      </Properties>
+    </Blob>
+    <Blob>
+      <Name>live.csv</Name>
+      <Snapshot>2024-05-01T00:00:00.0000000Z</Snapshot>
+      <Deleted>true</Deleted>
+    </Blob>
+  </Blobs>
+  <NextMarker />
+</EnumerationResults>"#;
+        let (deleted, next_marker) = parse_deleted_blobs_xml(xml).unwrap();
+        assert_eq!(
+            deleted,
+            vec![DeletedBlob {
+                name: "gone.csv".to_string(),
+                size: 7,
+                deleted_time: Utc.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).single(),
+            }]
+        );
+        assert_eq!(next_marker, None);
+    }
+
+    #[test]
+    fn tags_are_validated_and_serialized() {
+        assert!(validate_tag("env", "prod").is_ok());
+        assert!(validate_tag("path", "a/b:c=d_e-f.g+h i").is_ok());
+        assert!(validate_tag("", "x").is_err());
+        assert!(validate_tag("env", "<prod>").is_err());
+        assert!(validate_tag("env", &"x".repeat(257)).is_err());
+
+        let tags = BTreeMap::from([
+            ("pii".to_string(), "no".to_string()),
+            ("env".to_string(), "prod".to_string()),
+        ]);
+        let xml = tags_xml(&tags);
+        assert!(xml.ends_with(
+            "<Tags><TagSet><Tag><Key>env</Key><Value>prod</Value></Tag>\
+             <Tag><Key>pii</Key><Value>no</Value></Tag></TagSet></Tags>"
+        ));
+        assert_eq!(parse_tags_xml(&xml), tags);
+    }
+
+    #[test]
+    fn container_names_follow_service_rules() {
+        assert!(validate_container_name("logs-2024").is_ok());
+        assert!(validate_container_name("abc").is_ok());
+        assert!(validate_container_name("ab").is_err());
+        assert!(validate_container_name(&"a".repeat(64)).is_err());
+        assert!(validate_container_name("Logs").is_err());
+        assert!(validate_container_name("my_logs").is_err());
+        assert!(validate_container_name("-logs").is_err());
+        assert!(validate_container_name("logs-").is_err());
+        assert!(validate_container_name("my--logs").is_err());
+    }
+
+    #[test]
+    fn tag_searches_become_expressions() {
+        assert_eq!(
+            tag_filter_expression(" env=prod, team = data ").unwrap(),
+            r#""env" = 'prod' AND "team" = 'data'"#
+        );
+        let raw = r#""size" > '100'"#;
+        assert_eq!(tag_filter_expression(raw).unwrap(), raw);
+        assert!(tag_filter_expression("env").is_err());
+    }
+
+    #[test]
+    fn parses_tagged_blob_listing() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://acct.blob.core.windows.net/" ContainerName="raw">
+  <Where>"env" = 'prod'</Where>
+  <Blobs>
+    <Blob>
+      <Name>dir/a.csv</Name>
+      <ContainerName>raw</ContainerName>
+      <Tags><TagSet><Tag><Key>env</Key><Value>prod</Value></Tag></TagSet></Tags>
+    </Blob>
+    <Blob><Name>b.txt</Name><ContainerName>raw</ContainerName></Blob>
+  </Blobs>
+  <NextMarker>next</NextMarker>
+</EnumerationResults>"#;
+        let (names, next_marker) = parse_tagged_blobs_xml(xml).unwrap();
+        assert_eq!(names, vec!["dir/a.csv".to_string(), "b.txt".to_string()]);
+        assert_eq!(next_marker.as_deref(), Some("next"));
+    }
+
+    #[test]
+    fn page_blobs_and_their_used_ranges_are_parsed() {
+        let listing = r#"<EnumerationResults ContainerName="vhds"><Blobs>
+    <Blob><Name>disks/os.vhd</Name><Properties><Content-Length>136365212160</Content-Length><BlobType>PageBlob</BlobType></Properties></Blob>
+    <Blob><Name>disks/notes.txt</Name><Properties><BlobType>BlockBlob</BlobType></Properties></Blob>
+  </Blobs><NextMarker /></EnumerationResults>"#;
+        let (names, next_marker) = parse_page_blobs_xml(listing).unwrap();
+        assert_eq!(names, vec!["disks/os.vhd".to_string()]);
+        assert_eq!(next_marker, None);
+
+        let ranges = r#"<?xml version="1.0" encoding="utf-8"?>
+<PageList>
+  <PageRange><Start>0</Start><End>511</End></PageRange>
+  <ClearRange><Start>512</Start><End>1023</End></ClearRange>
+  <PageRange><Start>1048576</Start><End>1049599</End></PageRange>
+  <NextMarker>more</NextMarker>
+</PageList>"#;
+        let (used, next_marker) = parse_page_list_xml(ranges).unwrap();
+        assert_eq!(used, 512 + 1024);
+        assert_eq!(next_marker.as_deref(), Some("more"));
+        assert!(matches!(
+            parse_page_list_xml("<PageList><PageRange><Start>0</Start></PageRange></PageList>"),
+            Err(BlobrsError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn only_idempotent_requests_are_retried_after_failures() {
+        use reqwest::Method;
+
+        assert!(is_idempotent(&Method::GET, &[("comp", "list")]));
+        assert!(is_idempotent(&Method::DELETE, &[]));
+        assert!(is_idempotent(&Method::PUT, &[("comp", "tier")]));
+        assert!(!is_idempotent(&Method::PUT, &[("comp", "snapshot")]));
+        assert!(!is_idempotent(
+            &Method::POST,
+            &[("comp", "batch"), ("restype", "container")]
+        ));
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_dates() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let header = |value: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(reqwest::header::RETRY_AFTER, value.parse().unwrap());
+            headers
+        };
+
+        assert_eq!(retry_after(&header("7"), now), Some(Duration::from_secs(7)));
+        assert_eq!(
+            retry_after(&header("Wed, 01 May 2024 12:00:30 GMT"), now),
+            Some(Duration::from_secs(30))
+        );
+        // A date already passed means retry now
+        assert_eq!(
+            retry_after(&header("Wed, 01 May 2024 11:00:00 GMT"), now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&header("soon"), now), None);
+        assert_eq!(retry_after(&reqwest::header::HeaderMap::new(), now), None);
+        assert!(is_transient_status(
+            reqwest::StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(!is_transient_status(reqwest::StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn versions_as_of_picks_latest_version_before_timestamp() {
+        let versions = vec![
+            version("a.txt", "2024-06-01T10:00:00.0000000Z"),
+            version("a.txt", "2024-06-03T10:00:00.0000000Z"),
+            version("b.txt", "2024-06-05T10:00:00.0000000Z"),
+        ];
+        let as_of = Utc.with_ymd_and_hms(2024, 6, 2, 0, 0, 0).unwrap();
+
+        let result = versions_as_of(&versions, as_of);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].name, "a.txt");
+        assert_eq!(
+            result[0].version_id.as_deref(),
+            Some("2024-06-01T10:00:00.0000000Z")
+        );
+    }
+
+    #[test]
+    fn versions_as_of_skips_blobs_deleted_before_timestamp() {
+        let deleted_at = |day| Utc.with_ymd_and_hms(2024, 6, day, 0, 0, 0).single();
+        let mut gone = version("gone.txt", "2024-06-01T10:00:00.0000000Z");
+        gone.deleted_time = deleted_at(3);
+        let mut later = version("later.txt", "2024-06-01T10:00:00.0000000Z");
+        later.deleted_time = deleted_at(8);
+        // A deleted blob's own entry, without a version id, says when it went
+        let marker = BlobVersion {
+            version_id: None,
+            deleted_time: deleted_at(4),
+            ..version("marked.txt", "")
+        };
+        let versions = vec![
+            gone,
+            later,
+            version("marked.txt", "2024-06-01T10:00:00.0000000Z"),
+            marker,
+            BlobVersion {
+                is_current: true,
+                ..version("live.txt", "2024-06-01T10:00:00.0000000Z")
+            },
+        ];
+        let as_of = Utc.with_ymd_and_hms(2024, 6, 5, 0, 0, 0).unwrap();
+
+        let names: Vec<String> = versions_as_of(&versions, as_of)
+            .into_iter()
+            .map(|v| v.name)
+            .collect();
+        assert_eq!(names, ["later.txt", "live.txt"]);
+        let before = Utc.with_ymd_and_hms(2024, 6, 2, 0, 0, 0).unwrap();
+        assert_eq!(versions_as_of(&versions, before).len(), 4);
+    }
+}
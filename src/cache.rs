@@ -0,0 +1,224 @@
+//! Size-capped on-disk cache of blob contents, keyed by blob path and ETag.
+//!
+//! Entries are evicted least-recently-used first, using file modification times as the
+//! access clock.
+
+use crate::config::CacheConfig;
+use crate::error::BlobrsError;
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// An on-disk blob cache.
+#[derive(Debug, Clone)]
+pub struct BlobCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl BlobCache {
+    /// Create a cache in `dir` holding at most `max_bytes`.
+    #[must_use]
+    pub const fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    /// The cache described by the `[cache]` settings, or `None` when it is turned off.
+    #[must_use]
+    pub fn from_config(config: &CacheConfig) -> Option<Self> {
+        config
+            .dir()
+            .filter(|_| config.enabled)
+            .map(|dir| Self::new(dir, config.max_bytes))
+    }
+
+    /// Build a cache key from the parts that identify a blob's contents.
+    #[must_use]
+    pub fn key(parts: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .fold(String::with_capacity(64), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            })
+    }
+
+    /// Read an entry, marking it as recently used.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<Bytes> {
+        let path = self.dir.join(key);
+        let data = fs::read(&path).ok()?;
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(Bytes::from(data))
+    }
+
+    /// Copy an entry to `destination` without reading it into memory, marking it as recently
+    /// used. Returns the number of bytes copied.
+    #[must_use]
+    pub fn get_file(&self, key: &str, destination: &Path) -> Option<u64> {
+        let path = self.dir.join(key);
+        if !path.is_file() {
+            return None;
+        }
+        let Ok(copied) = fs::copy(&path, destination) else {
+            let _ = fs::remove_file(destination);
+            return None;
+        };
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(copied)
+    }
+
+    /// Store an entry and evict old entries beyond the size cap.
+    ///
+    /// Entries larger than the whole cache are not stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory or entry cannot be written.
+    pub fn put(&self, key: &str, data: &[u8]) -> Result<(), BlobrsError> {
+        if data.len() as u64 > self.max_bytes {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| BlobrsError::io(format!("Failed to create {}", self.dir.display()), e))?;
+
+        // Write to a temporary name first so readers never see a partial entry
+        let tmp = self.dir.join(format!("{key}.tmp"));
+        fs::write(&tmp, data).map_err(|e| BlobrsError::io("Failed to write cache entry", e))?;
+        fs::rename(&tmp, self.dir.join(key))
+            .map_err(|e| BlobrsError::io("Failed to write cache entry", e))?;
+
+        self.evict();
+        Ok(())
+    }
+
+    /// Store a copy of the file at `source` and evict old entries beyond the size cap, without
+    /// reading the file into memory.
+    ///
+    /// Files larger than the whole cache are not stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` cannot be read or the cache entry cannot be written.
+    pub fn put_file(&self, key: &str, source: &Path) -> Result<(), BlobrsError> {
+        let size = fs::metadata(source)
+            .map_err(|e| BlobrsError::io(format!("Failed to read {}", source.display()), e))?
+            .len();
+        if size > self.max_bytes {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| BlobrsError::io(format!("Failed to create {}", self.dir.display()), e))?;
+
+        let tmp = self.dir.join(format!("{key}.tmp"));
+        fs::copy(source, &tmp).map_err(|e| BlobrsError::io("Failed to write cache entry", e))?;
+        // A copy may keep the source's modification time; the entry was just used
+        if let Ok(file) = fs::File::options().append(true).open(&tmp) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        fs::rename(&tmp, self.dir.join(key))
+            .map_err(|e| BlobrsError::io("Failed to write cache entry", e))?;
+
+        self.evict();
+        Ok(())
+    }
+
+    /// Remove least recently used entries until the cache fits in its size cap.
+    fn evict(&self) {
+        let Ok(dir) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut entries: Vec<(SystemTime, u64, PathBuf)> = dir
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok()?;
+                if !meta.is_file() {
+                    return None;
+                }
+                Some((meta.modified().ok()?, meta.len(), entry.path()))
+            })
+            .collect();
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        entries.sort_by_key(|(modified, _, _)| *modified);
+        for (_, size, path) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn keys_depend_on_every_part() {
+        let a = BlobCache::key(&["acct", "container", "a.csv", "0x1"]);
+        assert_eq!(a.len(), 64);
+        assert_eq!(a, BlobCache::key(&["acct", "container", "a.csv", "0x1"]));
+        assert_ne!(a, BlobCache::key(&["acct", "container", "a.csv", "0x2"]));
+        assert_ne!(BlobCache::key(&["ab", "c"]), BlobCache::key(&["a", "bc"]));
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = BlobCache::new(dir.path().join("cache"), 10);
+        cache.put("old", b"1234").unwrap();
+        cache.put("mid", b"5678").unwrap();
+
+        // Make "old" the most recently used entry
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.get("old").as_deref(), Some(&b"1234"[..]));
+
+        cache.put("new", b"90ab").unwrap();
+        assert!(cache.get("mid").is_none());
+        assert!(cache.get("old").is_some());
+        assert!(cache.get("new").is_some());
+
+        // Entries bigger than the cache are skipped
+        cache.put("huge", &[0u8; 11]).unwrap();
+        assert!(cache.get("huge").is_none());
+    }
+
+    #[test]
+    fn files_are_copied_in_and_out_unless_bigger_than_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = BlobCache::new(dir.path().join("cache"), 10);
+        let small = dir.path().join("small.txt");
+        let large = dir.path().join("large.txt");
+        std::fs::write(&small, b"1234").unwrap();
+        std::fs::write(&large, [0u8; 11]).unwrap();
+
+        cache.put_file("small", &small).unwrap();
+        cache.put_file("large", &large).unwrap();
+        assert_eq!(cache.get("small").as_deref(), Some(&b"1234"[..]));
+        assert!(cache.get("large").is_none());
+        // The downloaded file stays where it was
+        assert!(small.exists());
+
+        let copy = dir.path().join("copy.txt");
+        assert_eq!(cache.get_file("small", &copy), Some(4));
+        assert_eq!(std::fs::read(&copy).unwrap(), b"1234");
+        assert_eq!(cache.get_file("large", &dir.path().join("none.txt")), None);
+    }
+}
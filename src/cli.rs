@@ -0,0 +1,854 @@
+//! Command-line arguments and the non-interactive subcommands.
+
+use crate::azure::{AzureRestClient, container_store, immutability_hint};
+use crate::cache::BlobCache;
+use crate::config::Config;
+use crate::error::BlobrsError;
+use crate::export::{self, ListingEntry, ListingFormat};
+use crate::pick::PickOutput;
+use crate::pipe;
+use crate::sync::{self, SyncAction, SyncMode};
+use crate::transfer::{
+    FileStatus, FileTransfer, TransferJob, TransferSource, TransferStats, delete_batched,
+    upload_file,
+};
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::WrapErr;
+use object_store::{ObjectStore, ObjectStoreExt, path::Path as ObjectPath};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// TUI for interacting with blob storage.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Start the TUI without upload, delete, copy, or move.
+    #[arg(long)]
+    pub read_only: bool,
+    /// Use this profile from config.toml instead of asking when several credentials are found.
+    #[arg(long)]
+    pub profile: Option<String>,
+    /// Log requests, retries, and errors to blobrs.log in the state directory; repeat for more
+    /// detail (`-vv` adds responses, `-vvv` everything the HTTP client reports).
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+    /// Pick blobs for a script: Enter on a blob quits and prints it, or the marked blobs, on
+    /// stdout, as `container/path` or with `--pick url` as URLs. The interface is drawn on
+    /// stderr.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "path")]
+    pub pick: Option<PickOutput>,
+    /// Exercise the storage layer against a local Azurite emulator and exit.
+    #[arg(long, hide = true)]
+    pub self_test: bool,
+    /// Run a single command instead of starting the TUI.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Non-interactive subcommands. Blobs and prefixes are given as `container/path`, optionally
+/// written `az://container/path`.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List containers, or the folders and blobs under a prefix.
+    Ls {
+        /// Prefix to list, as `container` or `container/prefix`; containers when left out.
+        target: Option<String>,
+        /// List every blob under the prefix rather than one level.
+        #[arg(short, long)]
+        recursive: bool,
+        /// Print a table, or JSON or CSV with each entry's name, size, last-modified time,
+        /// ETag and tier.
+        #[arg(short, long, value_enum, default_value_t)]
+        output: ListingFormat,
+    },
+    /// Download a blob, or every blob under a prefix.
+    Get {
+        /// Blob, or with `--recursive` a prefix.
+        remote: String,
+        /// Local file or directory.
+        local: PathBuf,
+        /// Download every blob under the prefix, keeping its folders.
+        #[arg(short, long)]
+        recursive: bool,
+    },
+    /// Write a blob's bytes to stdout as they download, e.g. to pipe into `jq` or `zcat`.
+    Cat {
+        /// Blob to print.
+        target: String,
+    },
+    /// Upload a file, or every file in a directory.
+    Put {
+        /// Local file, or with `--recursive` a directory.
+        local: PathBuf,
+        /// Blob, or a prefix ending in `/` to upload into.
+        remote: String,
+        /// Upload every file in the directory, keeping its folders.
+        #[arg(short, long)]
+        recursive: bool,
+    },
+    /// Delete a blob, or every blob under a prefix.
+    Rm {
+        /// Blob, or with `--recursive` a prefix.
+        target: String,
+        /// Delete every blob under the prefix.
+        #[arg(short, long)]
+        recursive: bool,
+        /// Delete a prefix with more blobs or bytes than the configured limits.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Copy a blob, or every blob under a prefix, within the account without downloading it.
+    Cp {
+        /// Blob, or with `--recursive` a prefix.
+        source: String,
+        /// Blob, or a prefix ending in `/` to copy into.
+        destination: String,
+        /// Copy every blob under the prefix, keeping its folders.
+        #[arg(short, long)]
+        recursive: bool,
+    },
+    /// Print a blob's full metadata as JSON.
+    Stat {
+        /// Blob to describe, as `container/path`.
+        target: String,
+    },
+    /// Sync a local directory with a blob prefix, transferring only differences.
+    Sync {
+        /// Local directory.
+        local_dir: PathBuf,
+        /// Blob prefix, as `container` or `container/prefix`.
+        target: String,
+        /// Which side wins and whether extra files are deleted.
+        #[arg(long, value_enum, default_value_t)]
+        mode: SyncMode,
+        /// Only print what would be transferred or deleted.
+        #[arg(long)]
+        dry_run: bool,
+        /// Apply a plan that deletes files or blobs or exceeds the configured limits; without it
+        /// such a plan is only printed.
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+/// The storage account a subcommand runs against, with the settings it shares with the TUI.
+#[derive(Debug, Clone, Copy)]
+pub struct Account<'a> {
+    pub name: &'a str,
+    pub access_key: &'a str,
+    pub config: &'a Config,
+}
+
+impl Account<'_> {
+    /// A client for `container`, retrying and timing out as configured.
+    fn store(&self, container: &str) -> Result<Arc<dyn ObjectStore>, BlobrsError> {
+        Ok(Arc::new(container_store(
+            self.name,
+            self.access_key,
+            container,
+            self.config.retry,
+            self.config.timeouts,
+        )?))
+    }
+
+    /// A REST client for what `object_store` does not cover, retrying and timing out as
+    /// configured.
+    fn rest(&self) -> Result<AzureRestClient, BlobrsError> {
+        AzureRestClient::new(self.name, self.access_key)?
+            .with_retry(self.config.retry)
+            .with_timeouts(self.config.timeouts)
+    }
+
+    /// Refuse a command that changes blobs in read-only mode.
+    fn refuse_if_read_only(&self) -> color_eyre::Result<()> {
+        if self.config.read_only {
+            return Err(color_eyre::eyre::eyre!("Read-only mode is on"));
+        }
+        Ok(())
+    }
+}
+
+/// Run a subcommand against `account`.
+///
+/// # Errors
+///
+/// Returns an error if the command fails.
+pub async fn run(command: Command, account: Account<'_>) -> color_eyre::Result<()> {
+    match command {
+        Command::Ls {
+            target,
+            recursive,
+            output,
+        } => ls(account, target.as_deref(), recursive, output).await,
+        Command::Get {
+            remote,
+            local,
+            recursive,
+        } => get(account, &remote, &local, recursive).await,
+        Command::Cat { target } => cat(account, &target).await,
+        Command::Put {
+            local,
+            remote,
+            recursive,
+        } => put(account, &local, &remote, recursive).await,
+        Command::Rm {
+            target,
+            recursive,
+            yes,
+        } => rm(account, &target, recursive, yes).await,
+        Command::Cp {
+            source,
+            destination,
+            recursive,
+        } => cp(account, &source, &destination, recursive).await,
+        Command::Stat { target } => stat(account, &target).await,
+        Command::Sync {
+            local_dir,
+            target,
+            mode,
+            dry_run,
+            yes,
+        } => run_sync(account, &local_dir, &target, mode, dry_run, yes).await,
+    }
+}
+
+/// Print the containers of the account, or what is under the prefix at `target`, as `output`.
+async fn ls(
+    account: Account<'_>,
+    target: Option<&str>,
+    recursive: bool,
+    output: ListingFormat,
+) -> color_eyre::Result<()> {
+    let rest = account.rest()?;
+    let entries = match target {
+        None => rest
+            .list_containers()
+            .await?
+            .into_iter()
+            .map(|container| ListingEntry::container(container.name, container.last_modified))
+            .collect(),
+        Some(target) => {
+            let (container, prefix) = split_prefix_target(target)?;
+            let delimiter = (!recursive).then_some("/");
+            export::blob_entries(rest.list_blobs(container, &prefix, delimiter).await?)
+        }
+    };
+    export::write_listing(&mut std::io::stdout().lock(), &entries, output)?;
+    Ok(())
+}
+
+/// Download the blob at `remote`, or every blob under it if `recursive`, with the same
+/// transfer jobs as downloads in the TUI.
+async fn get(
+    account: Account<'_>,
+    remote: &str,
+    local: &Path,
+    recursive: bool,
+) -> color_eyre::Result<()> {
+    let (container, prefix) = if recursive {
+        split_prefix_target(remote)?
+    } else {
+        let (container, path) = split_blob_target(remote)?;
+        (container, path.to_string())
+    };
+    let store = account.store(container)?;
+    let files = downloads(store.as_ref(), &prefix, local, recursive).await?;
+    if files.is_empty() {
+        return Err(BlobrsError::NotFound(format!("Nothing to download under {remote}")).into());
+    }
+    let source = TransferSource {
+        object_store: store,
+        account: account.name.to_string(),
+        container: container.to_string(),
+        cache: BlobCache::from_config(&account.config.cache),
+        stats: TransferStats::default(),
+    };
+    let job = TransferJob::new(format!("{remote} → {}", local.display()), source, files);
+    run_job(&job).await
+}
+
+/// The files to download for `get`: the blob at `path` to `local`, or into it when it is a
+/// directory, or if `recursive` every blob under the prefix `path` into the directory `local`.
+async fn downloads(
+    store: &dyn ObjectStore,
+    path: &str,
+    local: &Path,
+    recursive: bool,
+) -> Result<Vec<FileTransfer>, BlobrsError> {
+    let transfer = |location: &str, relative_path: &str, destination, size, e_tag| FileTransfer {
+        location: ObjectPath::from(location),
+        version_id: None,
+        e_tag,
+        relative_path: relative_path.to_string(),
+        destination,
+        size,
+        bytes_done: 0,
+        status: FileStatus::Queued,
+    };
+    if recursive {
+        return Ok(sync::list_remote(store, path)
+            .await?
+            .into_iter()
+            .map(|blob| {
+                transfer(
+                    &format!("{path}{}", blob.relative_path),
+                    &blob.relative_path,
+                    sync::local_path(local, &blob.relative_path),
+                    blob.size,
+                    blob.e_tag,
+                )
+            })
+            .collect());
+    }
+    let meta = store.head(&ObjectPath::from(path)).await?;
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let destination = if local.is_dir() {
+        local.join(name)
+    } else {
+        local.to_path_buf()
+    };
+    Ok(vec![transfer(
+        path,
+        name,
+        destination,
+        meta.size,
+        meta.e_tag,
+    )])
+}
+
+/// Run a download job to the end, then report the files that failed.
+async fn run_job(job: &TransferJob) -> color_eyre::Result<()> {
+    job.start();
+    while !job.summary().is_finished() {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let files = job.files();
+    for file in &files {
+        match &file.status {
+            FileStatus::Done => println!("{}", file.destination.display()),
+            FileStatus::Failed(e) => eprintln!("{}: {e}", file.relative_path),
+            _ => {}
+        }
+    }
+    let summary = job.summary();
+    if summary.failed == 0 {
+        Ok(())
+    } else {
+        Err(color_eyre::eyre::eyre!(
+            "{} of {} downloads failed",
+            summary.failed,
+            files.len()
+        ))
+    }
+}
+
+/// Stream the blob at `target` to stdout.
+async fn cat(account: Account<'_>, target: &str) -> color_eyre::Result<()> {
+    let (container, path) = split_blob_target(target)?;
+    let store = account.store(container)?;
+    pipe::stream_to(
+        store.as_ref(),
+        &ObjectPath::from(path),
+        &mut tokio::io::stdout(),
+    )
+    .await
+    .wrap_err_with(|| target.to_string())?;
+    Ok(())
+}
+
+/// Upload the file at `local` to `remote`, or every file in the directory if `recursive`.
+async fn put(
+    account: Account<'_>,
+    local: &Path,
+    remote: &str,
+    recursive: bool,
+) -> color_eyre::Result<()> {
+    account.refuse_if_read_only()?;
+    let (container, uploads) = uploads(local, remote, recursive)?;
+    let store = account.store(container)?;
+    let mut failed = 0;
+    for (path, blob_path) in &uploads {
+        match upload_file(store.as_ref(), &ObjectPath::from(blob_path.as_str()), path).await {
+            Ok(_) => println!("{container}/{blob_path}"),
+            Err(e) => {
+                eprintln!("{}: {e}", path.display());
+                failed += 1;
+            }
+        }
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(color_eyre::eyre::eyre!(
+            "{failed} of {} uploads failed",
+            uploads.len()
+        ))
+    }
+}
+
+/// The container `put` uploads to, and each local file with the blob path it goes to.
+fn uploads<'a>(
+    local: &Path,
+    remote: &'a str,
+    recursive: bool,
+) -> color_eyre::Result<(&'a str, Vec<(PathBuf, String)>)> {
+    if recursive {
+        let (container, prefix) = split_prefix_target(remote)?;
+        let files = sync::scan_local(local)?
+            .into_iter()
+            .map(|file| {
+                (
+                    sync::local_path(local, &file.relative_path),
+                    format!("{prefix}{}", file.relative_path),
+                )
+            })
+            .collect();
+        return Ok((container, files));
+    }
+    if local.is_dir() {
+        return Err(BlobrsError::Invalid(format!(
+            "{} is a directory; add --recursive to upload what is in it",
+            local.display()
+        ))
+        .into());
+    }
+    let name = local
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| BlobrsError::Invalid(format!("Invalid file name: {}", local.display())))?;
+    let (container, blob_path) = blob_destination(remote, name)?;
+    Ok((container, vec![(local.to_path_buf(), blob_path)]))
+}
+
+/// Delete the blob at `target`, or every blob under it if `recursive`. A prefix over the
+/// configured limits is only deleted with `yes`.
+async fn rm(
+    account: Account<'_>,
+    target: &str,
+    recursive: bool,
+    yes: bool,
+) -> color_eyre::Result<()> {
+    account.refuse_if_read_only()?;
+    let (container, paths) = if recursive {
+        let (container, prefix) = split_prefix_target(target)?;
+        let store = account.store(container)?;
+        let blobs = sync::list_remote(store.as_ref(), &prefix).await?;
+        let total_bytes = blobs.iter().map(|blob| blob.size).sum();
+        if !yes && account.config.limits.exceeded_by(blobs.len(), total_bytes) {
+            return Err(BlobrsError::Invalid(format!(
+                "Not deleting: {} blobs of {total_bytes} bytes under {target} is over the configured limits; add --yes to delete them",
+                blobs.len()
+            ))
+            .into());
+        }
+        let paths: Vec<String> = blobs
+            .into_iter()
+            .map(|blob| format!("{prefix}{}", blob.relative_path))
+            .collect();
+        (container, paths)
+    } else {
+        let (container, path) = split_blob_target(target)?;
+        (container, vec![path.to_string()])
+    };
+    let store = account.store(container)?;
+    // Without a REST client every blob is deleted one request at a time
+    let failed = remove(&store, account.rest().ok(), container, &paths).await?;
+    for path in paths.iter().filter(|path| !failed.contains_key(*path)) {
+        println!("{container}/{path}");
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        for (path, reason) in &failed {
+            eprintln!("{container}/{path}: not deleted: {reason}");
+        }
+        Err(color_eyre::eyre::eyre!(
+            "{} of {} deletes failed",
+            failed.len(),
+            paths.len()
+        ))
+    }
+}
+
+/// Delete `paths` in batches as the TUI does, returning the ones that could not be deleted
+/// with the reason. A single blob that does not exist is an error rather than nothing to do.
+async fn remove(
+    store: &Arc<dyn ObjectStore>,
+    client: Option<AzureRestClient>,
+    container: &str,
+    paths: &[String],
+) -> Result<BTreeMap<String, String>, BlobrsError> {
+    match paths {
+        [] => return Err(BlobrsError::NotFound("nothing to delete".to_string())),
+        [path] => {
+            store.head(&ObjectPath::from(path.as_str())).await?;
+        }
+        _ => {}
+    }
+    let locations = paths
+        .iter()
+        .map(|path| ObjectPath::from(path.as_str()))
+        .collect();
+    let mut failed = BTreeMap::new();
+    delete_batched(store, client, container, locations, |path, error| {
+        if let Some(e) = error {
+            let reason = immutability_hint(&e).map_or(e, str::to_string);
+            failed.insert(path.to_string(), reason);
+        }
+    })
+    .await;
+    Ok(failed)
+}
+
+/// Copy the blob at `source` to `destination`, or every blob under it if `recursive`, on the
+/// service: within a container with `object_store`, between containers with Copy Blob.
+async fn cp(
+    account: Account<'_>,
+    source: &str,
+    destination: &str,
+    recursive: bool,
+) -> color_eyre::Result<()> {
+    account.refuse_if_read_only()?;
+    let (from_container, to_container, pairs) = if recursive {
+        let (from_container, from_prefix) = split_prefix_target(source)?;
+        let (to_container, to_prefix) = split_prefix_target(destination)?;
+        let store = account.store(from_container)?;
+        let pairs: Vec<(String, String)> = sync::list_remote(store.as_ref(), &from_prefix)
+            .await?
+            .into_iter()
+            .map(|blob| {
+                (
+                    format!("{from_prefix}{}", blob.relative_path),
+                    format!("{to_prefix}{}", blob.relative_path),
+                )
+            })
+            .collect();
+        (from_container, to_container, pairs)
+    } else {
+        let (from_container, from_path) = split_blob_target(source)?;
+        let name = from_path.rsplit('/').next().unwrap_or(from_path);
+        let (to_container, to_path) = blob_destination(destination, name)?;
+        (
+            from_container,
+            to_container,
+            vec![(from_path.to_string(), to_path)],
+        )
+    };
+    if pairs.is_empty() {
+        return Err(BlobrsError::NotFound(format!("Nothing to copy under {source}")).into());
+    }
+
+    let store = account.store(from_container)?;
+    let rest = account.rest()?;
+    let mut failed = 0;
+    for (from, to) in &pairs {
+        let copied = if from_container == to_container {
+            store
+                .copy(
+                    &ObjectPath::from(from.as_str()),
+                    &ObjectPath::from(to.as_str()),
+                )
+                .await
+                .map_err(BlobrsError::from)
+        } else {
+            rest.copy_blob(from_container, from, to_container, to).await
+        };
+        match copied {
+            Ok(()) => println!("{from_container}/{from} → {to_container}/{to}"),
+            Err(e) => {
+                eprintln!("{from_container}/{from}: {e}");
+                failed += 1;
+            }
+        }
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(color_eyre::eyre::eyre!(
+            "{failed} of {} copies failed",
+            pairs.len()
+        ))
+    }
+}
+
+/// Print the properties of the blob at `target` as pretty JSON on stdout.
+async fn stat(account: Account<'_>, target: &str) -> color_eyre::Result<()> {
+    let (container, path) = split_blob_target(target)?;
+    let properties = account
+        .rest()?
+        .blob_properties(container, path)
+        .await
+        .wrap_err_with(|| target.to_string())?;
+    println!("{}", serde_json::to_string_pretty(&properties)?);
+    Ok(())
+}
+
+/// Plan a sync, print it, and apply it unless `dry_run` is set. A plan that deletes anything
+/// or exceeds the configured limits is only applied with `yes`.
+async fn run_sync(
+    account: Account<'_>,
+    local_dir: &Path,
+    target: &str,
+    mode: SyncMode,
+    dry_run: bool,
+    yes: bool,
+) -> color_eyre::Result<()> {
+    let (container, prefix) = split_prefix_target(target)?;
+    if !dry_run {
+        account.refuse_if_read_only()?;
+    }
+    let store = account.store(container)?;
+
+    let local = sync::scan_sync_root(local_dir, mode)?;
+    let remote = sync::list_remote(store.as_ref(), &prefix).await?;
+    let plan = sync::plan_sync(&local, &remote, mode);
+
+    for step in &plan.steps {
+        println!("{:<12} {}", step.action.label(), step.relative_path);
+    }
+    println!(
+        "{} to upload, {} to download, {} to delete, {} unchanged",
+        plan.count(SyncAction::Upload),
+        plan.count(SyncAction::Download),
+        plan.count(SyncAction::DeleteRemote) + plan.count(SyncAction::DeleteLocal),
+        plan.unchanged
+    );
+    if dry_run || plan.steps.is_empty() {
+        return Ok(());
+    }
+    let deletes = plan.count(SyncAction::DeleteRemote) + plan.count(SyncAction::DeleteLocal);
+    if !yes && deletes > 0 {
+        return Err(BlobrsError::Invalid(format!(
+            "Not syncing: the plan deletes {deletes} files; check it and add --yes to apply it"
+        ))
+        .into());
+    }
+    if !yes
+        && account
+            .config
+            .limits
+            .exceeded_by(plan.steps.len(), plan.total_bytes())
+    {
+        return Err(BlobrsError::Invalid(format!(
+            "Not syncing: {} steps moving {} bytes is over the configured limits; add --yes to apply it",
+            plan.steps.len(),
+            plan.total_bytes()
+        ))
+        .into());
+    }
+
+    let report = sync::apply(store.as_ref(), &prefix, local_dir, &plan).await;
+    for error in &report.errors {
+        eprintln!("{error}");
+    }
+    if report.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(color_eyre::eyre::eyre!(
+            "{} of {} steps failed",
+            report.errors.len(),
+            plan.steps.len()
+        ))
+    }
+}
+
+/// `target` without an `az://` in front.
+fn strip_scheme(target: &str) -> &str {
+    target.strip_prefix("az://").unwrap_or(target)
+}
+
+/// Split `container[/prefix]` into its container and a prefix ending in `/` (or empty).
+///
+/// # Errors
+///
+/// Returns an error if the container is missing.
+pub fn split_prefix_target(target: &str) -> Result<(&str, String), BlobrsError> {
+    let target = strip_scheme(target).trim_start_matches('/');
+    let (container, prefix) = target.split_once('/').unwrap_or((target, ""));
+    if container.is_empty() {
+        return Err(BlobrsError::Invalid(format!(
+            "Expected container[/prefix], got \"{target}\""
+        )));
+    }
+    let prefix = prefix.trim_end_matches('/');
+    let prefix = if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{prefix}/")
+    };
+    Ok((container, prefix))
+}
+
+/// Split `container/path` into its container and blob path.
+///
+/// # Errors
+///
+/// Returns an error if either part is missing.
+pub fn split_blob_target(target: &str) -> Result<(&str, &str), BlobrsError> {
+    match strip_scheme(target).trim_start_matches('/').split_once('/') {
+        Some((container, path)) if !container.is_empty() && !path.is_empty() => {
+            Ok((container, path))
+        }
+        _ => Err(BlobrsError::Invalid(format!(
+            "Expected container/path, got \"{target}\""
+        ))),
+    }
+}
+
+/// Split where a file named `name` is put or copied to: `container/path` as it is, or
+/// `container` or `container/prefix/` with the name added.
+///
+/// # Errors
+///
+/// Returns an error if the container is missing.
+pub fn blob_destination<'a>(target: &'a str, name: &str) -> Result<(&'a str, String), BlobrsError> {
+    let (container, prefix) = split_prefix_target(target)?;
+    if target.ends_with('/') || prefix.is_empty() {
+        return Ok((container, format!("{prefix}{name}")));
+    }
+    Ok((container, prefix.trim_end_matches('/').to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_targets_need_container_and_path() {
+        assert_eq!(
+            split_blob_target("raw/2024/a.csv").unwrap(),
+            ("raw", "2024/a.csv")
+        );
+        assert_eq!(split_blob_target("/raw/a.csv").unwrap(), ("raw", "a.csv"));
+        assert!(split_blob_target("raw").is_err());
+        assert!(split_blob_target("raw/").is_err());
+    }
+
+    #[test]
+    fn prefix_targets_normalise_trailing_slash() {
+        assert_eq!(split_prefix_target("raw").unwrap(), ("raw", String::new()));
+        assert_eq!(
+            split_prefix_target("raw/2024/q1").unwrap(),
+            ("raw", "2024/q1/".to_string())
+        );
+        assert_eq!(
+            split_prefix_target("raw/2024/").unwrap(),
+            ("raw", "2024/".to_string())
+        );
+        assert!(split_prefix_target("/").is_err());
+    }
+
+    #[test]
+    fn targets_may_be_written_as_urls() {
+        assert_eq!(
+            split_blob_target("az://raw/a.csv").unwrap(),
+            ("raw", "a.csv")
+        );
+        assert_eq!(
+            split_prefix_target("az://raw/2024").unwrap(),
+            ("raw", "2024/".to_string())
+        );
+    }
+
+    #[test]
+    fn destinations_ending_in_a_slash_get_the_file_name() {
+        assert_eq!(
+            blob_destination("raw", "a.csv").unwrap(),
+            ("raw", "a.csv".to_string())
+        );
+        assert_eq!(
+            blob_destination("raw/2024/", "a.csv").unwrap(),
+            ("raw", "2024/a.csv".to_string())
+        );
+        assert_eq!(
+            blob_destination("az://raw/2024/b.csv", "a.csv").unwrap(),
+            ("raw", "2024/b.csv".to_string())
+        );
+    }
+
+    async fn store_with(paths: &[&str]) -> Arc<dyn ObjectStore> {
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        for path in paths {
+            store
+                .put(&ObjectPath::from(*path), b"data".to_vec().into())
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    #[tokio::test]
+    async fn downloads_keep_the_folders_under_the_prefix() {
+        let store = store_with(&["2024/a.csv", "2024/q1/b.csv", "other.csv"]).await;
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+
+        let files = downloads(store.as_ref(), "2024/", dir, true).await.unwrap();
+        let mut destinations: Vec<_> = files.iter().map(|file| file.destination.clone()).collect();
+        destinations.sort();
+        assert_eq!(
+            destinations,
+            [dir.join("a.csv"), dir.join("q1").join("b.csv")]
+        );
+        assert_eq!(files[0].location.as_ref().split('/').next(), Some("2024"));
+
+        // A single blob goes into a directory under its own name
+        let files = downloads(store.as_ref(), "2024/q1/b.csv", dir, false)
+            .await
+            .unwrap();
+        assert_eq!(files[0].destination, dir.join("b.csv"));
+        assert_eq!(files[0].size, 4);
+        assert!(
+            downloads(store.as_ref(), "missing.csv", dir, false)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn uploads_need_recursive_for_a_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("q1")).unwrap();
+        std::fs::write(dir.join("a.csv"), "1").unwrap();
+        std::fs::write(dir.join("q1").join("b.csv"), "2").unwrap();
+
+        assert!(uploads(dir, "raw/2024/", false).is_err());
+        let (container, mut files) = uploads(dir, "raw/2024", true).unwrap();
+        files.sort();
+        assert_eq!(container, "raw");
+        assert_eq!(
+            files,
+            [
+                (dir.join("a.csv"), "2024/a.csv".to_string()),
+                (dir.join("q1").join("b.csv"), "2024/q1/b.csv".to_string()),
+            ]
+        );
+
+        let (_, files) = uploads(&dir.join("a.csv"), "raw/2024/", false).unwrap();
+        assert_eq!(files[0].1, "2024/a.csv");
+    }
+
+    #[tokio::test]
+    async fn removing_a_missing_blob_is_an_error() {
+        let store = store_with(&["a.csv", "b.csv"]).await;
+        assert!(
+            remove(&store, None, "data", &["missing.csv".to_string()])
+                .await
+                .is_err()
+        );
+
+        let failed = remove(
+            &store,
+            None,
+            "data",
+            &["a.csv".to_string(), "b.csv".to_string()],
+        )
+        .await
+        .unwrap();
+        assert!(failed.is_empty());
+        assert!(store.head(&ObjectPath::from("a.csv")).await.is_err());
+    }
+}
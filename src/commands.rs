@@ -0,0 +1,169 @@
+//! Shell commands that reproduce a download with the Azure CLI or `AzCopy`, for teammates
+//! who do not use blobrs.
+//!
+//! Credentials are never written into the command; `az` picks them up from its login or
+//! `AZURE_STORAGE_KEY`, and `azcopy` from `azcopy login` or a SAS token appended to the URL.
+
+/// Tool to build a command for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandStyle {
+    /// `az storage blob download` (or `download-batch` for folders).
+    AzCli,
+    /// `azcopy copy`.
+    AzCopy,
+}
+
+/// The blob or folder to download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandTarget {
+    pub account: String,
+    pub container: String,
+    /// Blob path, or folder path ending in `/`.
+    pub path: String,
+    /// Version to download instead of the current blob.
+    pub version_id: Option<String>,
+}
+
+impl CommandTarget {
+    fn is_folder(&self) -> bool {
+        self.path.ends_with('/')
+    }
+}
+
+/// A ready-to-run command downloading `target` into `destination`.
+#[must_use]
+pub fn download_command(style: CommandStyle, target: &CommandTarget, destination: &str) -> String {
+    let name = target
+        .path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    match style {
+        CommandStyle::AzCli if target.is_folder() => format!(
+            "az storage blob download-batch --account-name {} --source {} --pattern {} --destination {}",
+            quote(&target.account),
+            quote(&target.container),
+            quote(&format!("{}*", target.path)),
+            quote(destination),
+        ),
+        CommandStyle::AzCli => {
+            let file = format!("{}/{name}", destination.trim_end_matches('/'));
+            let mut command = format!(
+                "az storage blob download --account-name {} --container-name {} --name {} --file {}",
+                quote(&target.account),
+                quote(&target.container),
+                quote(&target.path),
+                quote(&file),
+            );
+            if let Some(version_id) = &target.version_id {
+                command.push_str(&format!(" --version-id {}", quote(version_id)));
+            }
+            command
+        }
+        CommandStyle::AzCopy => {
+            let mut url = blob_url(&target.account, &target.container, &target.path);
+            if let Some(version_id) = &target.version_id {
+                url.push_str(&format!("?versionid={}", encode_path(version_id)));
+            }
+            let recursive = if target.is_folder() {
+                " --recursive"
+            } else {
+                ""
+            };
+            format!(
+                "azcopy copy {} {}{recursive}",
+                quote(&url),
+                quote(destination)
+            )
+        }
+    }
+}
+
+/// The URL of the blob, or folder, at `path` in `container`.
+#[must_use]
+pub fn blob_url(account: &str, container: &str, path: &str) -> String {
+    format!(
+        "https://{account}.blob.core.windows.net/{container}/{}",
+        encode_path(path)
+    )
+}
+
+/// Quote a shell word with single quotes when it contains anything but safe characters.
+fn quote(word: &str) -> String {
+    let safe = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if safe {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// Percent-encode a blob path for a URL, keeping the `/` separators.
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(path: &str) -> CommandTarget {
+        CommandTarget {
+            account: "acct".to_string(),
+            container: "data".to_string(),
+            path: path.to_string(),
+            version_id: None,
+        }
+    }
+
+    #[test]
+    fn az_cli_downloads_blobs_and_folders() {
+        assert_eq!(
+            download_command(CommandStyle::AzCli, &target("logs/my file.csv"), "."),
+            "az storage blob download --account-name acct --container-name data \
+             --name 'logs/my file.csv' --file './my file.csv'"
+        );
+        assert_eq!(
+            download_command(CommandStyle::AzCli, &target("logs/"), "/tmp/out"),
+            "az storage blob download-batch --account-name acct --source data \
+             --pattern 'logs/*' --destination /tmp/out"
+        );
+
+        let mut versioned = target("a.csv");
+        versioned.version_id = Some("2024-01-01T00:00:00.0000000Z".to_string());
+        assert!(
+            download_command(CommandStyle::AzCli, &versioned, ".")
+                .ends_with(" --version-id 2024-01-01T00:00:00.0000000Z")
+        );
+    }
+
+    #[test]
+    fn azcopy_encodes_urls_and_recurses_into_folders() {
+        assert_eq!(
+            download_command(CommandStyle::AzCopy, &target("logs/it's #1.txt"), "."),
+            "azcopy copy https://acct.blob.core.windows.net/data/logs/it%27s%20%231.txt ."
+        );
+        assert_eq!(
+            download_command(CommandStyle::AzCopy, &target("logs/"), "."),
+            "azcopy copy https://acct.blob.core.windows.net/data/logs/ . --recursive"
+        );
+    }
+
+    #[test]
+    fn quoting_escapes_single_quotes() {
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote(""), "''");
+    }
+}
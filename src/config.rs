@@ -0,0 +1,634 @@
+//! User configuration loaded from `config.toml`.
+//!
+//! Every setting is optional; anything missing falls back to its default.
+
+use crate::error::BlobrsError;
+use crate::secret::Secret;
+use crate::theme::ThemeConfig;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Top-level configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Download here without showing the picker; `~` expands to the home directory.
+    pub download_dir: Option<PathBuf>,
+    /// Open a downloaded file with the system's default application as soon as it is saved.
+    pub open_downloads: bool,
+    /// Thresholds for warning before large folder operations.
+    pub limits: Limits,
+    /// Named storage accounts, e.g. `[profiles.prod]`.
+    pub profiles: BTreeMap<String, Profile>,
+    /// On-disk cache of previewed and downloaded blobs.
+    pub cache: CacheConfig,
+    /// Folder listing behaviour.
+    pub browse: BrowseConfig,
+    /// Retries of throttled or failed storage requests.
+    pub retry: RetryConfig,
+    /// How long to wait for the service before giving up on a request.
+    pub timeouts: TimeoutConfig,
+    /// History of operations that changed blobs or transferred data.
+    pub audit: AuditConfig,
+    /// Colour theme, and colours overriding the theme's.
+    pub theme: ThemeConfig,
+    /// Refuse every operation that changes blobs.
+    pub read_only: bool,
+    /// Prices the storage cost estimate in folder and container info uses.
+    pub costs: CostConfig,
+}
+
+/// Settings for folder listings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrowseConfig {
+    /// Entries per page; larger folders are browsed one page at a time. The start of a page is
+    /// shown while the rest of it loads.
+    pub page_size: usize,
+    /// List containers most recently modified first instead of alphabetically.
+    pub sort_containers_by_modified: bool,
+    /// How modification times are shown in listings and info popups.
+    pub time_format: TimeFormat,
+    /// Capture the mouse for scrolling and clicking in the lists. Turn off to select text with
+    /// the mouse as usual.
+    pub mouse: bool,
+    /// Seconds between listings of a folder being watched with `W`.
+    pub watch_secs: u64,
+    /// Blobs modified less than this many seconds ago are shown in a colour of their own;
+    /// 0 turns this off.
+    pub recent_secs: u64,
+}
+
+impl Default for BrowseConfig {
+    fn default() -> Self {
+        Self {
+            page_size: 5000,
+            sort_containers_by_modified: false,
+            time_format: TimeFormat::Relative,
+            mouse: true,
+            watch_secs: 5,
+            recent_secs: 0,
+        }
+    }
+}
+
+/// How times are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeFormat {
+    /// `3 h ago`, `yesterday`; the info popup adds the local time.
+    #[default]
+    Relative,
+    /// ISO 8601 in UTC, e.g. `2025-01-31T09:30:00Z`.
+    Iso,
+    /// In the local time zone, with its offset.
+    Local,
+}
+
+/// How often and how patiently requests are retried after throttling (429), a busy or failing
+/// service (5xx), or a network failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    /// Retries after the first attempt; 0 turns retrying off.
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for every further one.
+    pub initial_backoff_ms: u64,
+    /// Longest wait between two attempts.
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Wait before retry number `retry` (0 for the first), unless the service asked for
+    /// another delay with `Retry-After`, which is honoured up to [`Self::max_backoff_ms`].
+    #[must_use]
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let max = Duration::from_millis(self.max_backoff_ms);
+        retry_after
+            .unwrap_or_else(|| {
+                Duration::from_millis(self.initial_backoff_ms)
+                    .saturating_mul(2u32.saturating_pow(retry))
+            })
+            .min(max)
+    }
+
+    /// The same policy for `object_store`, which retries listings, reads and writes itself.
+    #[must_use]
+    pub fn object_store(&self) -> object_store::RetryConfig {
+        object_store::RetryConfig {
+            backoff: object_store::BackoffConfig {
+                init_backoff: Duration::from_millis(self.initial_backoff_ms),
+                max_backoff: Duration::from_millis(self.max_backoff_ms),
+                base: 2.0,
+            },
+            max_retries: self.max_retries as usize,
+            ..object_store::RetryConfig::default()
+        }
+    }
+}
+
+/// How long a request may take before it fails with a timeout instead of hanging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeoutConfig {
+    /// Longest wait for a connection to the service.
+    pub connect_secs: u64,
+    /// Read limit in seconds; 0 waits forever.
+    ///
+    /// The REST client fails once no data has arrived for this long, so long downloads keep
+    /// going as long as they make progress. The `object_store` clients only take a limit on the
+    /// whole response, body included.
+    pub read_secs: u64,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect_secs: 5,
+            read_secs: 30,
+        }
+    }
+}
+
+impl TimeoutConfig {
+    /// Longest wait for a connection.
+    #[must_use]
+    pub const fn connect(&self) -> Duration {
+        Duration::from_secs(self.connect_secs)
+    }
+
+    /// The read limit, if there is one.
+    #[must_use]
+    pub const fn read(&self) -> Option<Duration> {
+        match self.read_secs {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// The same limits for `object_store` clients, where the read limit covers the whole
+    /// response.
+    #[must_use]
+    pub fn object_store(&self) -> object_store::ClientOptions {
+        let options = object_store::ClientOptions::new().with_connect_timeout(self.connect());
+        match self.read() {
+            Some(read) => options.with_timeout(read),
+            None => options.with_timeout_disabled(),
+        }
+    }
+}
+
+/// Settings for the on-disk blob cache.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Whether the cache is used at all.
+    pub enabled: bool,
+    /// Cache location; defaults to a `blobrs` folder in the user cache directory.
+    pub dir: Option<PathBuf>,
+    /// Maximum total size of cached blobs.
+    pub max_bytes: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: None,
+            max_bytes: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// The cache directory, with `~` expanded.
+    #[must_use]
+    pub fn dir(&self) -> Option<PathBuf> {
+        match &self.dir {
+            Some(dir) => Some(expand_tilde(dir)),
+            None => dirs::cache_dir().map(|dir| dir.join("blobrs").join("blobs")),
+        }
+    }
+}
+
+/// Settings for the history of operations shown in the log panel.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    /// Also append every operation to a file, so the history outlives the session.
+    pub write_to_file: bool,
+    /// File to append to; defaults to `audit.jsonl` in the user state directory.
+    pub path: Option<PathBuf>,
+}
+
+impl AuditConfig {
+    /// The file operations are appended to, with `~` expanded, if writing is enabled.
+    #[must_use]
+    pub fn file(&self) -> Option<PathBuf> {
+        if !self.write_to_file {
+            return None;
+        }
+        match &self.path {
+            Some(path) => Some(expand_tilde(path)),
+            None => dirs::state_dir()
+                .or_else(dirs::data_local_dir)
+                .map(|dir| dir.join("blobrs").join("audit.jsonl")),
+        }
+    }
+}
+
+/// Monthly storage prices per GiB by access tier. The defaults are pay-as-you-go prices
+/// for locally redundant storage in East US; set your region's and redundancy's own.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CostConfig {
+    /// Shown before amounts, e.g. `$` or `€`.
+    pub currency: String,
+    pub hot: f64,
+    pub cool: f64,
+    pub cold: f64,
+    pub archive: f64,
+}
+
+impl Default for CostConfig {
+    fn default() -> Self {
+        Self {
+            currency: "$".to_string(),
+            hot: 0.0184,
+            cool: 0.01,
+            cold: 0.0036,
+            archive: 0.00099,
+        }
+    }
+}
+
+impl CostConfig {
+    /// Price per GiB a month of `tier`. Blobs without a standard tier, such as page blobs,
+    /// are priced as hot.
+    #[must_use]
+    pub fn rate(&self, tier: &str) -> f64 {
+        match tier.to_ascii_lowercase().as_str() {
+            "cool" => self.cool,
+            "cold" => self.cold,
+            "archive" => self.archive,
+            _ => self.hot,
+        }
+    }
+
+    /// Monthly cost of storing `bytes` in `tier`.
+    #[must_use]
+    pub fn monthly(&self, tier: &str, bytes: u64) -> f64 {
+        #[allow(clippy::cast_precision_loss)] // an estimate
+        let gib = bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        gib * self.rate(tier)
+    }
+
+    /// An amount in the configured currency, e.g. `$12.34`, with more digits for amounts
+    /// under a cent so they do not read as nothing.
+    #[must_use]
+    pub fn format(&self, amount: f64) -> String {
+        if amount > 0.0 && amount < 0.01 {
+            format!("{}{amount:.4}", self.currency)
+        } else {
+            format!("{}{amount:.2}", self.currency)
+        }
+    }
+}
+
+/// A named storage account.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Storage account name.
+    pub account: String,
+    /// Access key, stored inline.
+    pub access_key: Option<Secret>,
+    /// Environment variable holding the access key; preferred over an inline key.
+    pub access_key_env: Option<String>,
+}
+
+impl Profile {
+    /// Resolve the access key for this profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither `access_key_env` (set, and not empty, in the environment)
+    /// nor `access_key` is available.
+    pub fn access_key(&self) -> Result<Secret, BlobrsError> {
+        if let Some(var) = &self.access_key_env {
+            return std::env::var(var)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(Secret::new)
+                .ok_or_else(|| {
+                    BlobrsError::Credentials(format!(
+                        "{var} is not set for account {}",
+                        self.account
+                    ))
+                });
+        }
+        self.access_key.clone().ok_or_else(|| {
+            BlobrsError::Credentials(format!(
+                "no access key configured for account {}",
+                self.account
+            ))
+        })
+    }
+}
+
+/// Totals above which a folder operation needs an extra confirmation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Warn when an operation touches more than this many blobs.
+    pub warn_file_count: usize,
+    /// Warn when an operation moves more than this many bytes.
+    pub warn_total_bytes: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            warn_file_count: 10_000,
+            warn_total_bytes: 50 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl Limits {
+    /// Whether an operation with these totals should be confirmed first.
+    #[must_use]
+    pub const fn exceeded_by(&self, file_count: usize, total_bytes: u64) -> bool {
+        file_count > self.warn_file_count || total_bytes > self.warn_total_bytes
+    }
+}
+
+impl Config {
+    /// Default location of the config file, e.g. `~/.config/blobrs/config.toml`.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("blobrs").join("config.toml"))
+    }
+
+    /// The configured default download directory, with `~` expanded.
+    #[must_use]
+    pub fn download_dir(&self) -> Option<PathBuf> {
+        self.download_dir.as_deref().map(expand_tilde)
+    }
+
+    /// Load the config from the default location. A missing file yields the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self, BlobrsError> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load the config from `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load_from(path: &Path) -> Result<Self, BlobrsError> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| BlobrsError::io(format!("Failed to read {}", path.display()), e))?;
+        toml::from_str(&text)
+            .map_err(|e| BlobrsError::Parse(format!("config {}: {e}", path.display())))
+    }
+
+    /// Parse config from TOML text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not valid TOML or has unknown or mistyped settings.
+    pub fn parse(text: &str) -> Result<Self, BlobrsError> {
+        toml::from_str(text).map_err(|e| BlobrsError::Parse(format!("config: {e}")))
+    }
+}
+
+/// Expand a leading `~` to the home directory.
+#[must_use]
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_uses_defaults() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.limits, Limits::default());
+    }
+
+    #[test]
+    fn limits_can_be_overridden_and_checked() {
+        let config = Config::parse("[limits]\nwarn_file_count = 5\n").unwrap();
+        assert_eq!(config.limits.warn_file_count, 5);
+        assert_eq!(
+            config.limits.warn_total_bytes,
+            Limits::default().warn_total_bytes
+        );
+        assert!(!config.limits.exceeded_by(5, 0));
+        assert!(config.limits.exceeded_by(6, 0));
+        assert!(config.limits.exceeded_by(1, u64::MAX));
+    }
+
+    #[test]
+    fn themes_are_picked_by_name_with_colours_overridden() {
+        use crate::theme::{Theme, ThemeName};
+        use ratatui::style::Color;
+
+        let config = Config::parse(
+            "[theme]\nname = \"light\"\n\n[theme.colors]\naccent = \"#268bd2\"\nerror = \"light-red\"\n",
+        )
+        .unwrap();
+        let theme = Theme::from(&config.theme);
+        assert_eq!(theme.accent, Color::Rgb(0x26, 0x8b, 0xd2));
+        assert_eq!(theme.error, Color::LightRed);
+        assert_eq!(theme.text, Theme::named(ThemeName::Light).text);
+        assert_eq!(
+            Theme::from(&Config::default().theme),
+            Theme::named(ThemeName::Dark)
+        );
+
+        assert!(Config::parse("[theme.colors]\naccent = \"octarine\"\n").is_err());
+        assert!(Config::parse("[theme]\nname = \"neon\"\n").is_err());
+    }
+
+    #[test]
+    fn download_dir_expands_home() {
+        let config = Config::parse("download_dir = \"~/Downloads/blobs\"\n").unwrap();
+        let expected = dirs::home_dir().unwrap().join("Downloads").join("blobs");
+        assert_eq!(config.download_dir(), Some(expected));
+
+        let config = Config::parse("download_dir = \"/data/blobs\"\n").unwrap();
+        assert_eq!(config.download_dir(), Some(PathBuf::from("/data/blobs")));
+        assert_eq!(Config::default().download_dir(), None);
+    }
+
+    #[test]
+    fn profiles_are_parsed_by_name() {
+        let config = Config::parse(
+            r#"
+[profiles.prod]
+account = "prodacct"
+access_key = "c2VjcmV0"
+
+[profiles.dev]
+account = "devacct"
+access_key_env = "BLOBRS_TEST_UNSET_KEY_VAR"
+"#,
+        )
+        .unwrap();
+
+        let names: Vec<_> = config.profiles.keys().cloned().collect();
+        assert_eq!(names, vec!["dev".to_string(), "prod".to_string()]);
+        assert_eq!(
+            config.profiles["prod"].access_key().unwrap().expose(),
+            "c2VjcmV0"
+        );
+        assert!(config.profiles["dev"].access_key().is_err());
+    }
+
+    #[test]
+    fn cache_is_disabled_by_default() {
+        let config = Config::parse("").unwrap();
+        assert!(!config.cache.enabled);
+
+        let config =
+            Config::parse("[cache]\nenabled = true\ndir = \"/tmp/blobs\"\nmax_bytes = 10\n")
+                .unwrap();
+        assert!(config.cache.enabled);
+        assert_eq!(config.cache.dir(), Some(PathBuf::from("/tmp/blobs")));
+        assert_eq!(config.cache.max_bytes, 10);
+    }
+
+    #[test]
+    fn browse_page_size_can_be_overridden() {
+        assert_eq!(Config::default().browse.page_size, 5000);
+        let config = Config::parse("[browse]\npage_size = 200\n").unwrap();
+        assert_eq!(config.browse.page_size, 200);
+    }
+
+    #[test]
+    fn time_format_defaults_to_relative() {
+        assert_eq!(Config::default().browse.time_format, TimeFormat::Relative);
+        let config = Config::parse("[browse]\ntime_format = \"iso\"\n").unwrap();
+        assert_eq!(config.browse.time_format, TimeFormat::Iso);
+        assert!(Config::parse("[browse]\ntime_format = \"utc\"\n").is_err());
+    }
+
+    #[test]
+    fn recent_blobs_stand_out_only_when_asked() {
+        assert_eq!(Config::default().browse.recent_secs, 0);
+        let config = Config::parse("[browse]\nrecent_secs = 3600\n").unwrap();
+        assert_eq!(config.browse.recent_secs, 3600);
+    }
+
+    #[test]
+    fn mouse_is_captured_unless_turned_off() {
+        assert!(Config::default().browse.mouse);
+        assert!(
+            !Config::parse("[browse]\nmouse = false\n")
+                .unwrap()
+                .browse
+                .mouse
+        );
+    }
+
+    #[test]
+    fn retries_back_off_exponentially_up_to_the_maximum() {
+        let retry = Config::parse("[retry]\nmax_retries = 5\ninitial_backoff_ms = 100\n")
+            .unwrap()
+            .retry;
+        assert_eq!(retry.max_retries, 5);
+        assert_eq!(retry.delay(0, None), Duration::from_millis(100));
+        assert_eq!(retry.delay(3, None), Duration::from_millis(800));
+        assert_eq!(retry.delay(30, None), Duration::from_secs(30));
+        assert_eq!(
+            retry.delay(0, Some(Duration::from_secs(2))),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            retry.delay(0, Some(Duration::from_secs(600))),
+            Duration::from_secs(30)
+        );
+        assert_eq!(retry.object_store().max_retries, 5);
+    }
+
+    #[test]
+    fn timeouts_default_to_object_store_limits_and_zero_disables_reading() {
+        let timeouts = Config::default().timeouts;
+        assert_eq!(timeouts.connect(), Duration::from_secs(5));
+        assert_eq!(timeouts.read(), Some(Duration::from_secs(30)));
+
+        let timeouts = Config::parse("[timeouts]\nconnect_secs = 2\nread_secs = 0\n")
+            .unwrap()
+            .timeouts;
+        assert_eq!(timeouts.connect(), Duration::from_secs(2));
+        assert_eq!(timeouts.read(), None);
+    }
+
+    #[test]
+    fn audit_file_is_only_used_when_enabled() {
+        assert_eq!(Config::default().audit.file(), None);
+        let audit = Config::parse("[audit]\nwrite_to_file = true\npath = \"~/audit.jsonl\"\n")
+            .unwrap()
+            .audit;
+        assert_eq!(
+            audit.file(),
+            Some(dirs::home_dir().unwrap().join("audit.jsonl"))
+        );
+    }
+
+    #[test]
+    fn read_only_is_off_by_default() {
+        assert!(!Config::default().read_only);
+        assert!(Config::parse("read_only = true\n").unwrap().read_only);
+        assert!(!Config::default().open_downloads);
+        assert!(
+            Config::parse("open_downloads = true\n")
+                .unwrap()
+                .open_downloads
+        );
+    }
+
+    #[test]
+    fn costs_are_priced_by_tier() {
+        let costs = Config::parse("[costs]\ncurrency = \"€\"\ncool = 0.5\n")
+            .unwrap()
+            .costs;
+        assert_eq!(costs.rate("Cool"), 0.5);
+        assert_eq!(costs.rate("P10"), CostConfig::default().hot);
+        assert_eq!(costs.monthly("Cool", 4 * 1024 * 1024 * 1024), 2.0);
+        assert_eq!(costs.format(2.0), "€2.00");
+        assert_eq!(costs.format(0.0012), "€0.0012");
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        assert!(Config::parse("[limits]\nwarn_files = 5\n").is_err());
+    }
+}
@@ -0,0 +1,347 @@
+//! Storage account credentials found at startup.
+//!
+//! When more than one is available the user picks one instead of blobrs guessing. Credentials
+//! are account access keys, from the environment or a profile. A SAS token or Azure CLI login
+//! is noticed too, but cannot be used, as every request is signed with the key; they are listed
+//! as unsupported so that it is clear why they were passed over.
+
+use crate::config::Config;
+use crate::error::BlobrsError;
+use crate::secret::Secret;
+use std::path::PathBuf;
+
+/// Environment variable with the storage account name.
+pub const ACCOUNT_VAR: &str = "AZURE_STORAGE_ACCOUNT";
+
+/// Environment variable with the storage account access key.
+pub const ACCESS_KEY_VAR: &str = "AZURE_STORAGE_ACCESS_KEY";
+
+/// Environment variable with a shared access signature, which blobrs cannot use.
+pub const SAS_TOKEN_VAR: &str = "AZURE_STORAGE_SAS_TOKEN";
+
+/// A storage account and the key to use for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credential {
+    /// Where the credential came from, e.g. `environment` or `profile prod`.
+    pub source: String,
+    /// Storage account name.
+    pub account: String,
+    /// Storage account access key.
+    pub access_key: Secret,
+}
+
+impl Credential {
+    /// The account and key from [`ACCOUNT_VAR`] and [`ACCESS_KEY_VAR`], if both are set and
+    /// not empty.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            source: "environment".to_string(),
+            account: env_value(ACCOUNT_VAR)?,
+            access_key: Secret::new(env_value(ACCESS_KEY_VAR)?),
+        })
+    }
+}
+
+/// A credential that was found but cannot be used, as it is not an access key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedCredential {
+    /// What was found, e.g. `SAS token in AZURE_STORAGE_SAS_TOKEN` or `az login`.
+    pub source: String,
+    /// The storage account, or the signed-in user for an Azure CLI login, when known.
+    pub account: Option<String>,
+}
+
+impl std::fmt::Display for UnsupportedCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.account {
+            Some(account) => write!(f, "{} ({account})", self.source),
+            None => f.write_str(&self.source),
+        }
+    }
+}
+
+/// The SAS token from [`SAS_TOKEN_VAR`] and the Azure CLI login, whichever are present.
+#[must_use]
+pub fn detect_unsupported() -> Vec<UnsupportedCredential> {
+    let mut unsupported = Vec::new();
+    if env_value(SAS_TOKEN_VAR).is_some() {
+        unsupported.push(UnsupportedCredential {
+            source: format!("SAS token in {SAS_TOKEN_VAR}"),
+            account: env_value(ACCOUNT_VAR),
+        });
+    }
+    let profile = azure_cli_dir().map(|dir| dir.join("azureProfile.json"));
+    if let Some(login) = profile
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| azure_cli_login(&json))
+    {
+        unsupported.push(login);
+    }
+    unsupported
+}
+
+/// Where the Azure CLI keeps its configuration: `AZURE_CONFIG_DIR`, or `~/.azure`.
+fn azure_cli_dir() -> Option<PathBuf> {
+    env_value("AZURE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".azure")))
+}
+
+/// The login recorded in the Azure CLI's `azureProfile.json`, if it has any subscriptions.
+fn azure_cli_login(json: &str) -> Option<UnsupportedCredential> {
+    // The CLI writes the file with a byte order mark
+    let profile: serde_json::Value =
+        serde_json::from_str(json.trim_start_matches('\u{feff}')).ok()?;
+    let subscriptions = profile.get("subscriptions")?.as_array()?;
+    let subscription = subscriptions
+        .iter()
+        .find(|s| s.get("isDefault").and_then(serde_json::Value::as_bool) == Some(true))
+        .or_else(|| subscriptions.first())?;
+    Some(UnsupportedCredential {
+        source: "az login".to_string(),
+        account: subscription
+            .pointer("/user/name")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+/// The value of the environment variable `var`, unless it is unset or empty.
+fn env_value(var: &str) -> Option<String> {
+    std::env::var(var).ok().filter(|value| !value.is_empty())
+}
+
+/// Every usable credential: the environment first, then each profile in name order.
+///
+/// Profiles that repeat an earlier account and key are left out; profiles whose key cannot be
+/// resolved are returned as errors.
+#[must_use]
+pub fn detect(env: Option<Credential>, config: &Config) -> (Vec<Credential>, Vec<String>) {
+    let mut credentials: Vec<Credential> = env.into_iter().collect();
+    let mut errors = Vec::new();
+
+    for (name, profile) in &config.profiles {
+        match profile.access_key() {
+            Ok(access_key) => {
+                let duplicate = credentials
+                    .iter()
+                    .any(|c| c.account == profile.account && c.access_key == access_key);
+                if !duplicate {
+                    credentials.push(Credential {
+                        source: format!("profile {name}"),
+                        account: profile.account.clone(),
+                        access_key,
+                    });
+                }
+            }
+            Err(e) => errors.push(format!("profile {name}: {e}")),
+        }
+    }
+
+    (credentials, errors)
+}
+
+/// The credentials to start with: those from [`detect`], narrowed to `profile` if one was
+/// asked for, along with the profiles that were skipped.
+///
+/// # Errors
+///
+/// Returns an error explaining how to provide a credential when none is usable, naming the
+/// `unsupported` ones that were found instead, or why `profile` cannot be used.
+pub fn resolve(
+    env: Option<Credential>,
+    unsupported: &[UnsupportedCredential],
+    config: &Config,
+    profile: Option<&str>,
+) -> Result<(Vec<Credential>, Vec<String>), BlobrsError> {
+    let (mut credentials, errors) = detect(env, config);
+    if let Some(name) = profile {
+        let source = format!("profile {name}");
+        credentials.retain(|credential| credential.source == source);
+    }
+    if !credentials.is_empty() {
+        return Ok((credentials, errors));
+    }
+
+    let config_path = Config::default_path().map_or_else(
+        || "config.toml".to_string(),
+        |path| path.display().to_string(),
+    );
+    if let Some(name) = profile {
+        let prefix = format!("profile {name}: ");
+        return Err(BlobrsError::Credentials(
+            match errors.into_iter().find(|e| e.starts_with(&prefix)) {
+                Some(e) => format!("{e}\n\nProfiles are read from {config_path}"),
+                None => format!("profile {name} is not in {config_path}"),
+            },
+        ));
+    }
+    let found = if unsupported.is_empty() {
+        String::new()
+    } else {
+        let names: Vec<String> = unsupported.iter().map(ToString::to_string).collect();
+        format!(
+            "\n\nFound {}, which blobrs cannot use: it signs every request with an access key",
+            names.join(" and ")
+        )
+    };
+    Err(BlobrsError::Credentials(format!(
+        "no storage credentials found{found}\n\n{}\n\nOr add a profile to {config_path}:\n\n  \
+         [profiles.<name>]\n  account = \"<account>\"\n  access_key_env = \"<VARIABLE>\"",
+        env_guidance(&missing_env_vars())
+    )))
+}
+
+/// Which of [`ACCOUNT_VAR`] and [`ACCESS_KEY_VAR`] are unset or empty.
+#[must_use]
+pub fn missing_env_vars() -> Vec<&'static str> {
+    [ACCOUNT_VAR, ACCESS_KEY_VAR]
+        .into_iter()
+        .filter(|var| env_value(var).is_none())
+        .collect()
+}
+
+/// What to set when the variables in `missing` are not, with a way to look up the key.
+#[must_use]
+pub fn env_guidance(missing: &[&str]) -> String {
+    let mut text = String::new();
+    for var in missing {
+        text.push_str(&format!("{var} is not set\n"));
+    }
+    text.push_str(&format!(
+        "Set {ACCOUNT_VAR} to the storage account name and {ACCESS_KEY_VAR} to one of its \
+         access keys, which the Azure CLI can print:\n\n  \
+         az storage account keys list --account-name <account> --query '[0].value' -o tsv"
+    ));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_comes_first_and_duplicates_are_dropped() {
+        let config = Config::parse(
+            r#"
+[profiles.dev]
+account = "devacct"
+access_key = "ZGV2"
+
+[profiles.same]
+account = "envacct"
+access_key = "ZW52"
+
+[profiles.stale]
+account = "envacct"
+access_key = "b2xk"
+
+[profiles.unset]
+account = "other"
+access_key_env = "BLOBRS_TEST_UNSET_KEY_VAR"
+"#,
+        )
+        .unwrap();
+        let env = Credential {
+            source: "environment".to_string(),
+            account: "envacct".to_string(),
+            access_key: Secret::new("ZW52"),
+        };
+
+        let (credentials, errors) = detect(Some(env), &config);
+        let sources: Vec<_> = credentials.iter().map(|c| c.source.as_str()).collect();
+        assert_eq!(sources, vec!["environment", "profile dev", "profile stale"]);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("profile unset: "));
+
+        let (credentials, _) = detect(None, &Config::default());
+        assert!(credentials.is_empty());
+    }
+
+    #[test]
+    fn missing_credentials_explain_what_to_set() {
+        let config = Config::parse(
+            r#"
+[profiles.unset]
+account = "other"
+access_key_env = "BLOBRS_TEST_UNSET_KEY_VAR"
+"#,
+        )
+        .unwrap();
+
+        let e = resolve(None, &[], &config, Some("unset"))
+            .unwrap_err()
+            .to_string();
+        assert!(e.starts_with("profile unset: "));
+        assert!(e.contains("BLOBRS_TEST_UNSET_KEY_VAR is not set"));
+        let e = resolve(None, &[], &config, Some("missing"))
+            .unwrap_err()
+            .to_string();
+        assert!(e.starts_with("profile missing is not in "));
+        let e = resolve(None, &[], &Config::default(), None)
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains(ACCESS_KEY_VAR));
+        assert!(e.contains("[profiles.<name>]"));
+
+        let sas = UnsupportedCredential {
+            source: format!("SAS token in {SAS_TOKEN_VAR}"),
+            account: Some("acct".to_string()),
+        };
+        let e = resolve(None, &[sas], &Config::default(), None)
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains("Found SAS token in AZURE_STORAGE_SAS_TOKEN (acct), which blobrs"));
+
+        let guidance = env_guidance(&[ACCOUNT_VAR]);
+        assert!(guidance.starts_with("AZURE_STORAGE_ACCOUNT is not set\n"));
+        assert!(guidance.contains("az storage account keys list"));
+    }
+
+    #[test]
+    fn an_unusable_profile_is_a_credentials_error() {
+        unsafe {
+            std::env::set_var("BLOBRS_TEST_EMPTY_KEY_VAR", "");
+        }
+        let config = Config::parse(
+            r#"
+[profiles.empty]
+account = "other"
+access_key_env = "BLOBRS_TEST_EMPTY_KEY_VAR"
+"#,
+        )
+        .unwrap();
+
+        let e = resolve(None, &[], &config, Some("empty")).unwrap_err();
+        assert!(matches!(e, BlobrsError::Credentials(_)));
+        assert!(
+            e.to_string()
+                .contains("BLOBRS_TEST_EMPTY_KEY_VAR is not set")
+        );
+        let e = resolve(None, &[], &config, Some("missing")).unwrap_err();
+        assert!(matches!(e, BlobrsError::Credentials(_)));
+        assert_eq!(e.exit_code(), 3);
+        unsafe {
+            std::env::remove_var("BLOBRS_TEST_EMPTY_KEY_VAR");
+        }
+    }
+
+    #[test]
+    fn an_azure_cli_login_is_read_from_its_profile() {
+        let profile = "\u{feff}{\"subscriptions\": [
+            {\"isDefault\": false, \"user\": {\"name\": \"old@example.com\"}},
+            {\"isDefault\": true, \"user\": {\"name\": \"me@example.com\"}}
+        ]}";
+        assert_eq!(
+            azure_cli_login(profile),
+            Some(UnsupportedCredential {
+                source: "az login".to_string(),
+                account: Some("me@example.com".to_string()),
+            })
+        );
+        // Logging out leaves the file with no subscriptions
+        assert_eq!(azure_cli_login("{\"subscriptions\": []}"), None);
+        assert_eq!(azure_cli_login("not json"), None);
+    }
+}
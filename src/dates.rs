@@ -0,0 +1,112 @@
+//! How blob times are shown: relative to now, as ISO 8601, or in the local time zone, and
+//! whether they are recent enough to stand out in a listing.
+
+use crate::config::TimeFormat;
+use chrono::{DateTime, Local, Utc};
+
+/// `time` for a listing row, e.g. `3 h ago`, `2025-01-31T09:30:00Z` or
+/// `2025-01-31 10:30 +01:00`.
+#[must_use]
+pub fn short(time: DateTime<Utc>, format: TimeFormat, now: DateTime<Utc>) -> String {
+    match format {
+        TimeFormat::Relative => relative(time, now),
+        TimeFormat::Iso => iso(time),
+        TimeFormat::Local => time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M %:z")
+            .to_string(),
+    }
+}
+
+/// `time` for the info popup: as [`short`], with the exact time after relative ones.
+#[must_use]
+pub fn detailed(time: DateTime<Utc>, format: TimeFormat, now: DateTime<Utc>) -> String {
+    match format {
+        TimeFormat::Relative => format!("{} ({})", relative(time, now), local(time)),
+        TimeFormat::Iso => iso(time),
+        TimeFormat::Local => local(time),
+    }
+}
+
+fn iso(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn local(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S %:z")
+        .to_string()
+}
+
+/// How long before `now` `time` was, e.g. `5 min ago` or `yesterday`. Times more than a month
+/// back are shown as their local date, which is easier to place than a count of days.
+#[must_use]
+pub fn relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now - time;
+    match (
+        elapsed.num_minutes(),
+        elapsed.num_hours(),
+        elapsed.num_days(),
+    ) {
+        // Clocks drift, so times slightly ahead of ours are treated as now
+        (..1, _, _) => "just now".to_string(),
+        (minutes, 0, _) => format!("{minutes} min ago"),
+        (_, hours, 0) => format!("{hours} h ago"),
+        (_, _, 1) => "yesterday".to_string(),
+        (_, _, days @ ..30) => format!("{days} days ago"),
+        _ => time.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Whether `time` is less than `window_secs` before `now`. A window of 0 turns this off.
+#[must_use]
+pub fn is_recent(time: DateTime<Utc>, window_secs: u64, now: DateTime<Utc>) -> bool {
+    let Ok(window) = i64::try_from(window_secs) else {
+        return window_secs > 0;
+    };
+    window > 0 && (now - time).num_seconds() < window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn recent_times_are_relative() {
+        let now = Utc::now();
+        let ago = |delta: TimeDelta| relative(now - delta, now);
+
+        assert_eq!(ago(TimeDelta::seconds(-30)), "just now");
+        assert_eq!(ago(TimeDelta::seconds(59)), "just now");
+        assert_eq!(ago(TimeDelta::minutes(5)), "5 min ago");
+        assert_eq!(ago(TimeDelta::minutes(180)), "3 h ago");
+        assert_eq!(ago(TimeDelta::hours(30)), "yesterday");
+        assert_eq!(ago(TimeDelta::days(12)), "12 days ago");
+        let old = now - TimeDelta::days(400);
+        assert_eq!(
+            relative(old, now),
+            old.with_timezone(&Local).format("%Y-%m-%d").to_string()
+        );
+    }
+
+    #[test]
+    fn recent_times_are_within_the_window() {
+        let now = Utc::now();
+        let hour = 60 * 60;
+        assert!(is_recent(now - TimeDelta::minutes(59), hour, now));
+        assert!(!is_recent(now - TimeDelta::minutes(61), hour, now));
+        // Clock drift puts some times slightly ahead of ours
+        assert!(is_recent(now + TimeDelta::seconds(5), hour, now));
+        assert!(!is_recent(now, 0, now));
+    }
+
+    #[test]
+    fn iso_times_are_exact_utc() {
+        let time = "2025-01-31T09:30:15Z".parse().unwrap();
+        let now = Utc::now();
+        assert_eq!(short(time, TimeFormat::Iso, now), "2025-01-31T09:30:15Z");
+        assert_eq!(detailed(time, TimeFormat::Iso, now), "2025-01-31T09:30:15Z");
+        assert!(detailed(time, TimeFormat::Relative, now).ends_with(&format!("({})", local(time))));
+    }
+}
@@ -0,0 +1,465 @@
+//! Comparing two blobs, or a blob and a local file: a unified diff when both are text, or else
+//! their sizes, MD5 hashes and properties side by side.
+//!
+//! Lines are matched by their longest common subsequence after trimming what the two share at
+//! the start and end, which keeps the usual small edit to a large file cheap. Past
+//! [`MAX_DIFF_CELLS`] the changed middle is shown as removed and added whole.
+
+use crate::preview::{decode_text, is_likely_binary};
+use base64::{Engine as _, engine::general_purpose};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use md5::{Digest, Md5};
+use object_store::{Attribute, ObjectStore, ObjectStoreExt, path::Path as ObjectPath};
+use std::path::Path;
+
+/// Largest blob or file whose content is fetched to compare.
+pub const MAX_COMPARE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Unchanged lines shown around each change.
+pub const CONTEXT_LINES: usize = 3;
+
+/// Most line pairs weighed to match up the changed middle of two texts.
+pub const MAX_DIFF_CELLS: usize = 16_000_000;
+
+/// What a line of a unified diff is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// `---`/`+++` naming the two sides.
+    Header,
+    /// `@@ -a,b +c,d @@` starting a hunk.
+    Hunk,
+    Context,
+    Removed,
+    Added,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    /// The line as `diff -u` prints it, with its `+`, `-` or space.
+    pub text: String,
+}
+
+/// One side of a comparison.
+#[derive(Debug, Clone, Default)]
+pub struct CompareSide {
+    /// `container/path`, or a local path.
+    pub name: String,
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub e_tag: Option<String>,
+    pub content_type: Option<String>,
+    /// The content, unless it is over [`MAX_COMPARE_BYTES`].
+    pub content: Option<Bytes>,
+}
+
+impl CompareSide {
+    /// The blob at `path` in `container`, with its content if it is small enough.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob cannot be read.
+    pub async fn blob(
+        store: &dyn ObjectStore,
+        container: &str,
+        path: &str,
+    ) -> object_store::Result<Self> {
+        let location = ObjectPath::from(path);
+        let meta = store.head(&location).await?;
+        let mut side = Self {
+            name: format!("{container}/{path}"),
+            size: meta.size,
+            last_modified: Some(meta.last_modified),
+            e_tag: meta.e_tag,
+            ..Self::default()
+        };
+        if side.size <= MAX_COMPARE_BYTES {
+            let got = store.get(&location).await?;
+            side.content_type = got
+                .attributes
+                .get(&Attribute::ContentType)
+                .map(|value| value.as_ref().to_string());
+            side.content = Some(got.bytes().await?);
+        }
+        Ok(side)
+    }
+
+    /// The local file at `path`, with its content if it is small enough.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub async fn local(path: &Path) -> std::io::Result<Self> {
+        let meta = tokio::fs::metadata(path).await?;
+        let content = if meta.len() <= MAX_COMPARE_BYTES {
+            Some(Bytes::from(tokio::fs::read(path).await?))
+        } else {
+            None
+        };
+        Ok(Self {
+            name: path.display().to_string(),
+            size: meta.len(),
+            last_modified: meta.modified().ok().map(DateTime::<Utc>::from),
+            e_tag: None,
+            content_type: mime_guess::from_path(path).first().map(|m| m.to_string()),
+            content,
+        })
+    }
+
+    /// Base64 MD5 of the content, as in `Content-MD5`.
+    fn md5(&self) -> Option<String> {
+        self.content
+            .as_ref()
+            .map(|content| general_purpose::STANDARD.encode(Md5::digest(content)))
+    }
+
+    /// The content as text, unless it looks binary.
+    fn text(&self) -> Option<String> {
+        let content = self.content.as_ref()?;
+        if is_likely_binary(content) && encoding_rs::Encoding::for_bom(content).is_none() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&decode_text(content)).into_owned())
+    }
+}
+
+/// A property of both sides, for comparing binaries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompareRow {
+    pub label: &'static str,
+    pub left: String,
+    pub right: String,
+}
+
+impl CompareRow {
+    #[must_use]
+    pub fn differs(&self) -> bool {
+        self.left != self.right
+    }
+}
+
+/// How two sides compare.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Comparison {
+    /// Both are text: their unified diff, with only the header when they are the same.
+    Text(Vec<DiffLine>),
+    /// Either is binary or too large: their properties side by side.
+    Binary(Vec<CompareRow>),
+}
+
+impl Comparison {
+    #[must_use]
+    pub fn new(left: &CompareSide, right: &CompareSide) -> Self {
+        if let (Some(old), Some(new)) = (left.text(), right.text()) {
+            return Self::Text(unified_diff(&old, &new, &left.name, &right.name));
+        }
+        let text = |value: Option<String>| value.unwrap_or_else(|| "—".to_string());
+        let time = |time: Option<DateTime<Utc>>| {
+            text(time.map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)))
+        };
+        let md5 = |side: &CompareSide| {
+            side.md5()
+                .unwrap_or_else(|| "too large to hash".to_string())
+        };
+        Self::Binary(vec![
+            CompareRow {
+                label: "Size",
+                left: left.size.to_string(),
+                right: right.size.to_string(),
+            },
+            CompareRow {
+                label: "MD5",
+                left: md5(left),
+                right: md5(right),
+            },
+            CompareRow {
+                label: "Content type",
+                left: text(left.content_type.clone()),
+                right: text(right.content_type.clone()),
+            },
+            CompareRow {
+                label: "Modified",
+                left: time(left.last_modified),
+                right: time(right.last_modified),
+            },
+            CompareRow {
+                label: "ETag",
+                left: text(left.e_tag.clone()),
+                right: text(right.e_tag.clone()),
+            },
+        ])
+    }
+
+    /// What the comparison found, e.g. `+3 −1 lines` or `Same content`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        match self {
+            Self::Text(lines) => {
+                let count = |kind| lines.iter().filter(|line| line.kind == kind).count();
+                match (count(DiffKind::Removed), count(DiffKind::Added)) {
+                    (0, 0) => "Same text".to_string(),
+                    (removed, added) => format!("+{added} −{removed} lines"),
+                }
+            }
+            Self::Binary(rows) => match rows.iter().find(|row| row.label == "MD5") {
+                Some(md5) if !md5.differs() && !md5.left.starts_with("too large") => {
+                    "Same content".to_string()
+                }
+                _ if rows.iter().any(|row| row.label == "Size" && row.differs()) => {
+                    "Sizes differ".to_string()
+                }
+                Some(md5) if md5.differs() && !md5.left.starts_with("too large") => {
+                    "Content differs".to_string()
+                }
+                _ => "Too large to compare the content".to_string(),
+            },
+        }
+    }
+
+    /// Lines the popup scrolls through.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Text(lines) => lines.len(),
+            Self::Binary(rows) => rows.len(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// An edit turning one line list into another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// The unified diff from `old` to `new`, as `diff -u` would print it.
+#[must_use]
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> Vec<DiffLine> {
+    let before: Vec<&str> = old.lines().collect();
+    let after: Vec<&str> = new.lines().collect();
+    let edits = edits(&before, &after);
+
+    let mut lines = vec![
+        DiffLine {
+            kind: DiffKind::Header,
+            text: format!("--- {old_name}"),
+        },
+        DiffLine {
+            kind: DiffKind::Header,
+            text: format!("+++ {new_name}"),
+        },
+    ];
+    // Line in each text each edit starts at
+    let mut at = Vec::with_capacity(edits.len());
+    let (mut old_line, mut new_line) = (0, 0);
+    for edit in &edits {
+        at.push((old_line, new_line));
+        match edit {
+            Edit::Keep => (old_line, new_line) = (old_line + 1, new_line + 1),
+            Edit::Remove => old_line += 1,
+            Edit::Add => new_line += 1,
+        }
+    }
+
+    let changed: Vec<usize> = (0..edits.len())
+        .filter(|&index| edits[index] != Edit::Keep)
+        .collect();
+    let mut next = 0;
+    while next < changed.len() {
+        // A hunk runs on while the changes in it are close enough to share context
+        let start = changed[next].saturating_sub(CONTEXT_LINES);
+        let mut end = changed[next];
+        while next < changed.len() && changed[next] <= end + 2 * CONTEXT_LINES {
+            end = changed[next];
+            next += 1;
+        }
+        let end = (end + CONTEXT_LINES + 1).min(edits.len());
+
+        let hunk = &edits[start..end];
+        let old_count = hunk.iter().filter(|edit| **edit != Edit::Add).count();
+        let new_count = hunk.iter().filter(|edit| **edit != Edit::Remove).count();
+        let (old_start, new_start) = at[start];
+        lines.push(DiffLine {
+            kind: DiffKind::Hunk,
+            text: format!(
+                "@@ -{} +{} @@",
+                hunk_range(old_start, old_count),
+                hunk_range(new_start, new_count)
+            ),
+        });
+        for (edit, &(old_line, new_line)) in hunk.iter().zip(&at[start..end]) {
+            lines.push(match edit {
+                Edit::Keep => DiffLine {
+                    kind: DiffKind::Context,
+                    text: format!(" {}", before[old_line]),
+                },
+                Edit::Remove => DiffLine {
+                    kind: DiffKind::Removed,
+                    text: format!("-{}", before[old_line]),
+                },
+                Edit::Add => DiffLine {
+                    kind: DiffKind::Added,
+                    text: format!("+{}", after[new_line]),
+                },
+            });
+        }
+    }
+    lines
+}
+
+/// `start,count` of a hunk, counting lines from 1 as `diff -u` does.
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{count}", start + 1),
+    }
+}
+
+/// The edits turning `before` into `after`, removals before additions where lines change.
+fn edits(before: &[&str], after: &[&str]) -> Vec<Edit> {
+    let prefix = before.iter().zip(after).take_while(|(a, b)| a == b).count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &before[prefix..before.len() - suffix];
+    let new = &after[prefix..after.len() - suffix];
+
+    let mut edits = vec![Edit::Keep; prefix];
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        edits.extend(std::iter::repeat_n(Edit::Remove, old.len()));
+        edits.extend(std::iter::repeat_n(Edit::Add, new.len()));
+    } else {
+        edits.extend(common_subsequence_edits(old, new));
+    }
+    edits.extend(std::iter::repeat_n(Edit::Keep, suffix));
+    edits
+}
+
+/// The edits keeping the longest common subsequence of `old` and `new`.
+fn common_subsequence_edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    // Length of the longest common subsequence of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut longest = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            longest[i * width + j] = if old[i] == new[j] {
+                longest[(i + 1) * width + j + 1] + 1
+            } else {
+                longest[(i + 1) * width + j].max(longest[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            edits.push(Edit::Keep);
+            (i, j) = (i + 1, j + 1);
+        } else if longest[(i + 1) * width + j] >= longest[i * width + j + 1] {
+            edits.push(Edit::Remove);
+            i += 1;
+        } else {
+            edits.push(Edit::Add);
+            j += 1;
+        }
+    }
+    edits.extend(std::iter::repeat_n(Edit::Remove, old.len() - i));
+    edits.extend(std::iter::repeat_n(Edit::Add, new.len() - j));
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn texts(lines: &[DiffLine]) -> Vec<&str> {
+        lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn diffs_read_like_diff_u() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        assert_eq!(
+            texts(&unified_diff(old, new, "raw/old.txt", "raw/new.txt")),
+            [
+                "--- raw/old.txt",
+                "+++ raw/new.txt",
+                "@@ -1,7 +1,7 @@",
+                " a",
+                " b",
+                " c",
+                "-d",
+                "+D",
+                " e",
+                " f",
+                " g",
+                "@@ -10,3 +10,4 @@",
+                " j",
+                " k",
+                " l",
+                "+m",
+            ]
+        );
+        assert_eq!(unified_diff("same\n", "same\n", "a", "b").len(), 2);
+        assert_eq!(
+            texts(&unified_diff("", "new\n", "a", "b"))[2..],
+            ["@@ -0,0 +1 @@", "+new"]
+        );
+    }
+
+    #[test]
+    fn changes_close_together_share_a_hunk() {
+        let old: String = (0..10).map(|i| format!("{i}\n")).collect();
+        let new = old.replace("2\n", "two\n").replace("7\n", "seven\n");
+        let diff = unified_diff(&old, &new, "a", "b");
+        let hunks = diff
+            .iter()
+            .filter(|line| line.kind == DiffKind::Hunk)
+            .count();
+        assert_eq!(hunks, 1);
+        assert_eq!(Comparison::Text(diff).summary(), "+2 −2 lines");
+    }
+
+    #[tokio::test]
+    async fn binaries_are_compared_by_size_and_hash() {
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        for (path, content) in [("a.bin", b"\0\x01\x02"), ("b.bin", b"\0\x01\x02")] {
+            store
+                .put(&ObjectPath::from(path), content.to_vec().into())
+                .await
+                .unwrap();
+        }
+        let left = CompareSide::blob(store.as_ref(), "raw", "a.bin")
+            .await
+            .unwrap();
+        let right = CompareSide::blob(store.as_ref(), "raw", "b.bin")
+            .await
+            .unwrap();
+        let Comparison::Binary(rows) = Comparison::new(&left, &right) else {
+            panic!("binaries compare by their properties");
+        };
+        assert!(!rows[1].differs());
+        assert_eq!(rows[1].left, "uV9n9h67A2GWIteY9F/C0w==");
+        assert_eq!(Comparison::Binary(rows.clone()).summary(), "Same content");
+
+        let mut larger = right.clone();
+        larger.content = Some(Bytes::from_static(b"\0\x01\x02\x03"));
+        larger.size = 4;
+        assert_eq!(Comparison::new(&left, &larger).summary(), "Sizes differ");
+    }
+}
@@ -0,0 +1,241 @@
+//! Editing a blob in place: it is downloaded to a temporary file, opened in `$VISUAL` or
+//! `$EDITOR` with the interface suspended, and once the editor exits the changes are shown and
+//! can be uploaded back.
+//!
+//! The upload only succeeds if the blob still has the ETag it was downloaded with, so an edit
+//! never silently overwrites a change someone else made in the meantime.
+
+use bytes::Bytes;
+use object_store::{
+    Attributes, ObjectStore, ObjectStoreExt, PutMode, PutOptions, PutPayload, PutResult,
+    UpdateVersion, path::Path as ObjectPath,
+};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Largest blob that is opened in an editor.
+pub const MAX_EDIT_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Diff lines shown before uploading an edit.
+pub const MAX_DIFF_LINES: usize = 12;
+
+/// A blob downloaded to be edited.
+#[derive(Debug, Clone)]
+pub struct EditSession {
+    pub object_store: Arc<dyn ObjectStore>,
+    /// Full blob path.
+    pub blob_path: String,
+    /// ETag the blob had when it was downloaded.
+    pub e_tag: Option<String>,
+    /// The blob as downloaded.
+    pub original: Bytes,
+    /// Content type, encoding, cache control and metadata the blob had, kept on upload.
+    pub attributes: Attributes,
+    /// Temporary file the editor opens.
+    pub local_path: PathBuf,
+    /// Directory of this session alone holding `local_path`, removed once the session is
+    /// dropped.
+    _dir: Arc<TempDir>,
+}
+
+impl EditSession {
+    /// Download the blob at `blob_path` into a temporary file named like it, so the editor
+    /// picks its syntax from the extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob cannot be read or the file cannot be written.
+    pub async fn start(
+        object_store: Arc<dyn ObjectStore>,
+        blob_path: &str,
+    ) -> color_eyre::Result<Self> {
+        let got = object_store.get(&ObjectPath::from(blob_path)).await?;
+        let e_tag = got.meta.e_tag.clone();
+        let attributes = got.attributes.clone();
+        let original = got.bytes().await?;
+
+        let file_name = blob_path.rsplit('/').next().unwrap_or(blob_path);
+        let dir = tempfile::Builder::new().prefix("blobrs-edit-").tempdir()?;
+        let local_path = dir.path().join(file_name);
+        tokio::fs::write(&local_path, &original).await?;
+
+        Ok(Self {
+            object_store,
+            blob_path: blob_path.to_string(),
+            e_tag,
+            original,
+            attributes,
+            local_path,
+            _dir: Arc::new(dir),
+        })
+    }
+
+    /// The file as the editor left it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, e.g. because the editor removed it.
+    pub async fn edited(&self) -> std::io::Result<Vec<u8>> {
+        tokio::fs::read(&self.local_path).await
+    }
+
+    /// Upload `edited` over the blob with the properties it had, unless it has changed since
+    /// it was downloaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`object_store::Error::Precondition`] if the blob has changed, or any other
+    /// error from the upload.
+    pub async fn upload(&self, edited: Vec<u8>) -> object_store::Result<PutResult> {
+        let mode = match &self.e_tag {
+            Some(e_tag) => PutMode::Update(UpdateVersion {
+                e_tag: Some(e_tag.clone()),
+                version: None,
+            }),
+            None => PutMode::Overwrite,
+        };
+        let options = PutOptions {
+            mode,
+            attributes: self.attributes.clone(),
+            ..PutOptions::default()
+        };
+        self.object_store
+            .put_opts(
+                &ObjectPath::from(self.blob_path.as_str()),
+                PutPayload::from(edited),
+                options,
+            )
+            .await
+    }
+
+    /// Remove the temporary file. Its directory goes once every copy of the session is
+    /// dropped.
+    pub fn discard(&self) {
+        let _ = std::fs::remove_file(&self.local_path);
+    }
+}
+
+/// The editor to run: `$VISUAL`, then `$EDITOR`, then a platform default.
+#[must_use]
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        })
+}
+
+/// Run `command` on `file`, waiting for it to exit. The command may carry its own arguments,
+/// such as `code --wait`.
+///
+/// # Errors
+///
+/// Returns an error if the command is empty or cannot be started.
+pub fn run_editor(command: &str, file: &Path) -> std::io::Result<ExitStatus> {
+    let mut words = command.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no editor set"))?;
+    Command::new(program).args(words).arg(file).status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::Attribute;
+
+    #[test]
+    fn editor_arguments_are_passed_before_the_file() {
+        let status = run_editor("true --wait", Path::new("config.json"));
+        if cfg!(unix) {
+            assert!(status.unwrap().success());
+        }
+        assert!(run_editor("  ", Path::new("config.json")).is_err());
+    }
+
+    #[tokio::test]
+    async fn an_edit_is_refused_once_the_blob_has_changed() {
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let path = ObjectPath::from("conf/app.json");
+        store.put(&path, b"{}".to_vec().into()).await.unwrap();
+
+        let session = EditSession::start(store.clone(), "conf/app.json")
+            .await
+            .unwrap();
+        assert!(session.local_path.ends_with("app.json"));
+        assert_eq!(session.edited().await.unwrap(), b"{}");
+        // Another blob of the same name gets a file of its own
+        store
+            .put(&ObjectPath::from("other/app.json"), b"[]".to_vec().into())
+            .await
+            .unwrap();
+        let other = EditSession::start(store.clone(), "other/app.json")
+            .await
+            .unwrap();
+        assert_ne!(other.local_path, session.local_path);
+        assert_eq!(session.edited().await.unwrap(), b"{}");
+        let dir = other.local_path.parent().unwrap().to_path_buf();
+        drop(other);
+        assert!(!dir.exists());
+
+        // Someone else writes the blob before the edit is uploaded
+        store
+            .put(&path, b"{\"a\":1}".to_vec().into())
+            .await
+            .unwrap();
+        let refused = session.upload(b"{\"b\":2}".to_vec()).await;
+        assert!(matches!(
+            refused,
+            Err(object_store::Error::Precondition { .. })
+        ));
+        let current = store.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(current.as_ref(), b"{\"a\":1}");
+
+        let session = EditSession::start(store.clone(), "conf/app.json")
+            .await
+            .unwrap();
+        session.upload(b"{\"b\":2}".to_vec()).await.unwrap();
+        let current = store.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(current.as_ref(), b"{\"b\":2}");
+        session.discard();
+        assert!(!session.local_path.exists());
+    }
+
+    #[tokio::test]
+    async fn an_edit_keeps_the_blob_properties() {
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let path = ObjectPath::from("conf/app.json");
+        let mut attributes = Attributes::new();
+        attributes.insert(Attribute::ContentType, "application/json".into());
+        attributes.insert(Attribute::CacheControl, "no-cache".into());
+        attributes.insert(Attribute::Metadata("owner".into()), "data-platform".into());
+        store
+            .put_opts(
+                &path,
+                b"{}".to_vec().into(),
+                PutOptions {
+                    attributes: attributes.clone(),
+                    ..PutOptions::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let session = EditSession::start(store.clone(), "conf/app.json")
+            .await
+            .unwrap();
+        session.upload(b"{\"a\":1}".to_vec()).await.unwrap();
+        session.discard();
+        let got = store.get(&path).await.unwrap();
+        assert_eq!(got.attributes, attributes);
+        assert_eq!(got.bytes().await.unwrap().as_ref(), b"{\"a\":1}");
+    }
+}
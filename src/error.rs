@@ -0,0 +1,189 @@
+//! Crate-wide error type.
+
+use thiserror::Error;
+
+/// Everything that can go wrong talking to blob storage or reading and writing local files.
+#[derive(Debug, Error)]
+pub enum BlobrsError {
+    /// Credentials are malformed or rejected by the service.
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// No credential can be used: none is set, or a profile is missing or incomplete.
+    #[error("{0}")]
+    Credentials(String),
+    /// The request never got a response.
+    #[error("Network error: {0}")]
+    Network(String),
+    /// The service did not answer within the configured timeout.
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+    /// The container or blob does not exist.
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// A response, config file, or state file could not be understood.
+    #[error("Failed to parse {0}")]
+    Parse(String),
+    /// A blob is not in the format it was read as, such as a damaged archive or Parquet file.
+    #[error("{0}")]
+    Format(String),
+    /// A name, tag or filter typed in is not one the service accepts.
+    #[error("{0}")]
+    Invalid(String),
+    /// A local file or directory could not be read or written.
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The operation was stopped before it finished.
+    #[error("Cancelled")]
+    Cancelled,
+    /// The service refused the request for another reason.
+    #[error("{0}")]
+    Service(String),
+}
+
+impl BlobrsError {
+    /// The process exit code for a subcommand that failed with this error, so scripts can tell
+    /// a missing blob from bad credentials or a network outage.
+    #[must_use]
+    pub const fn exit_code(&self) -> u8 {
+        match self {
+            Self::Invalid(_) => 2,
+            Self::Auth(_) | Self::Credentials(_) => 3,
+            Self::NotFound(_) => 4,
+            Self::Network(_) | Self::Timeout(_) => 5,
+            Self::Parse(_) | Self::Format(_) => 6,
+            Self::Io { .. } => 7,
+            Self::Cancelled => 130,
+            Self::Service(_) => 1,
+        }
+    }
+
+    /// An I/O error with a description of what was being done, e.g. `Failed to read /tmp/x`.
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        Self::Io {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+/// The exit code for `report`: that of the first [`BlobrsError`] in its chain, or 1.
+#[must_use]
+pub fn exit_code(report: &color_eyre::Report) -> u8 {
+    report
+        .chain()
+        .find_map(|e| e.downcast_ref::<BlobrsError>())
+        .map_or(1, BlobrsError::exit_code)
+}
+
+/// Whether `e`, or any error that caused it, is a request that ran out of time.
+#[must_use]
+pub fn is_timeout(e: &(dyn std::error::Error + 'static)) -> bool {
+    std::iter::successors(Some(e), |e| e.source()).any(|e| {
+        e.downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_timeout)
+            || e.downcast_ref::<object_store::client::HttpError>()
+                .is_some_and(|e| e.kind() == object_store::client::HttpErrorKind::Timeout)
+            || e.downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::TimedOut)
+    })
+}
+
+impl From<object_store::Error> for BlobrsError {
+    fn from(e: object_store::Error) -> Self {
+        if is_timeout(&e) {
+            return Self::Timeout(e.to_string());
+        }
+        match e {
+            object_store::Error::NotFound { .. } => Self::NotFound(e.to_string()),
+            object_store::Error::PermissionDenied { .. }
+            | object_store::Error::Unauthenticated { .. } => Self::Auth(e.to_string()),
+            _ => Self::Service(e.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for BlobrsError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::Timeout(e.to_string())
+        } else {
+            Self::Network(e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_store_errors_map_to_kinds() {
+        let not_found = object_store::Error::NotFound {
+            path: "a.csv".to_string(),
+            source: "missing".into(),
+        };
+        assert!(matches!(
+            BlobrsError::from(not_found),
+            BlobrsError::NotFound(_)
+        ));
+
+        let denied = object_store::Error::PermissionDenied {
+            path: "a.csv".to_string(),
+            source: "denied".into(),
+        };
+        assert!(matches!(BlobrsError::from(denied), BlobrsError::Auth(_)));
+
+        let io = BlobrsError::io(
+            "Failed to read /tmp/x",
+            std::io::Error::from(std::io::ErrorKind::NotFound),
+        );
+        assert!(io.to_string().starts_with("Failed to read /tmp/x: "));
+    }
+
+    #[test]
+    fn timeouts_are_told_apart_from_other_failures() {
+        let timed_out = object_store::Error::Generic {
+            store: "MicrosoftAzure",
+            source: Box::new(object_store::client::HttpError::new(
+                object_store::client::HttpErrorKind::Timeout,
+                std::io::Error::from(std::io::ErrorKind::Other),
+            )),
+        };
+        let error = BlobrsError::from(timed_out);
+        assert!(matches!(error, BlobrsError::Timeout(_)));
+        assert!(error.to_string().starts_with("Request timed out: "));
+
+        let refused = object_store::Error::Generic {
+            store: "MicrosoftAzure",
+            source: Box::new(object_store::client::HttpError::new(
+                object_store::client::HttpErrorKind::Connect,
+                std::io::Error::from(std::io::ErrorKind::ConnectionRefused),
+            )),
+        };
+        assert!(matches!(
+            BlobrsError::from(refused),
+            BlobrsError::Service(_)
+        ));
+    }
+
+    #[test]
+    fn exit_codes_follow_the_error_kind() {
+        let not_found = color_eyre::Report::new(BlobrsError::NotFound("raw/a.csv".to_string()));
+        assert_eq!(exit_code(&not_found), 4);
+        let wrapped = color_eyre::eyre::WrapErr::wrap_err(
+            Err::<(), _>(BlobrsError::Auth("key rejected".to_string())),
+            "Could not list raw",
+        )
+        .unwrap_err();
+        assert_eq!(exit_code(&wrapped), 3);
+        assert_eq!(
+            exit_code(&color_eyre::eyre::eyre!("Read-only mode is on")),
+            1
+        );
+        assert_eq!(BlobrsError::Cancelled.exit_code(), 130);
+    }
+}
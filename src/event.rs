@@ -1,7 +1,11 @@
 use color_eyre::eyre::WrapErr;
 use ratatui::crossterm::event::{self, Event as CrosstermEvent};
 use std::{
-    sync::mpsc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
     thread,
     time::{Duration, Instant},
 };
@@ -44,6 +48,8 @@ pub struct EventHandler {
     sender: mpsc::Sender<Event>,
     /// Event receiver channel.
     receiver: mpsc::Receiver<Event>,
+    /// Set while another program has the terminal, so its input is left alone.
+    paused: Arc<AtomicBool>,
 }
 
 impl Default for EventHandler {
@@ -57,9 +63,26 @@ impl EventHandler {
     #[must_use]
     pub fn new() -> Self {
         let (sender, receiver) = mpsc::channel();
-        let actor = EventThread::new(sender.clone());
+        let paused = Arc::new(AtomicBool::new(false));
+        let actor = EventThread::new(sender.clone(), paused.clone());
         thread::spawn(|| actor.run());
-        Self { sender, receiver }
+        Self {
+            sender,
+            receiver,
+            paused,
+        }
+    }
+
+    /// Stop reading terminal events, e.g. while an editor runs in the terminal. Returns once
+    /// any read already under way has finished.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        thread::sleep(Duration::from_secs_f64(1.0 / TICK_FPS));
+    }
+
+    /// Read terminal events again after [`Self::pause`].
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
     }
 
     /// Receives an event from the sender.
@@ -90,12 +113,14 @@ impl EventHandler {
 struct EventThread {
     /// Event sender channel.
     sender: mpsc::Sender<Event>,
+    /// Whether terminal events are left unread.
+    paused: Arc<AtomicBool>,
 }
 
 impl EventThread {
     /// Constructs a new instance of [`EventThread`].
-    fn new(sender: mpsc::Sender<Event>) -> Self {
-        Self { sender }
+    fn new(sender: mpsc::Sender<Event>, paused: Arc<AtomicBool>) -> Self {
+        Self { sender, paused }
     }
 
     /// Runs the event thread.
@@ -105,6 +130,11 @@ impl EventThread {
         let tick_interval = Duration::from_secs_f64(1.0 / TICK_FPS);
         let mut last_tick = Instant::now();
         loop {
+            // nothing is sent while paused, so no ticks pile up for when it resumes
+            if self.paused.load(Ordering::SeqCst) {
+                thread::sleep(tick_interval);
+                continue;
+            }
             // emit tick events at a fixed rate
             let timeout = tick_interval.saturating_sub(last_tick.elapsed());
             if timeout == Duration::ZERO {
@@ -0,0 +1,312 @@
+//! Listings written out for other tools: a table to read, JSON for `jq`, or CSV for a
+//! spreadsheet or dataframe. `blobrs ls --output` prints them, and `:export` writes the listing
+//! on screen, filtered and sorted as it is, or a manifest of everything under the open folder,
+//! to a file.
+
+use crate::azure::BlobListing;
+use crate::config::TimeFormat;
+use crate::dates;
+use crate::error::BlobrsError;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+
+/// How a listing is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ListingFormat {
+    /// Aligned columns.
+    #[default]
+    Table,
+    /// An array of objects, one per entry.
+    Json,
+    /// A header row, then one row per entry.
+    Csv,
+}
+
+impl ListingFormat {
+    /// The format named by `arg`, or else the one the extension of `path` suggests.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the formats if neither gives one.
+    pub fn parse(arg: Option<&str>, path: &str) -> Result<Self, BlobrsError> {
+        let extension = path.rsplit_once('.').map(|(_, extension)| extension);
+        match arg.or(extension).map(str::to_ascii_lowercase).as_deref() {
+            Some("json") => Ok(Self::Json),
+            Some("csv") => Ok(Self::Csv),
+            Some("table" | "txt") => Ok(Self::Table),
+            _ => Err(BlobrsError::Invalid(
+                "Export as json, csv or table".to_string(),
+            )),
+        }
+    }
+}
+
+/// What an entry of a listing is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Container,
+    Folder,
+    Blob,
+}
+
+/// One entry of a listing, with the fields every format writes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ListingEntry {
+    /// Full path in the container, or the container's name; folders end in `/`.
+    pub name: String,
+    pub kind: EntryKind,
+    pub size: Option<u64>,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub etag: Option<String>,
+    pub tier: Option<String>,
+}
+
+/// What `:export` was asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportRequest {
+    pub format: ListingFormat,
+    /// Every blob under the open folder rather than the entries on screen.
+    pub all: bool,
+    pub path: String,
+}
+
+impl ExportRequest {
+    /// Parse `[json|csv|table] [all] <path>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error showing the arguments if they do not fit, or naming the formats if
+    /// none is given and the path has no extension to go by.
+    pub fn parse(args: &str) -> Result<Self, BlobrsError> {
+        let usage = || {
+            BlobrsError::Invalid(
+                "Export to a file: `:export [json|csv|table] [all] <path>`".to_string(),
+            )
+        };
+        let mut words: Vec<&str> = args.split_whitespace().collect();
+        let path = words.pop().ok_or_else(usage)?.to_string();
+        let all = words
+            .iter()
+            .position(|word| word.eq_ignore_ascii_case("all"))
+            .map(|index| words.remove(index))
+            .is_some();
+        let format = match words.as_slice() {
+            [] => ListingFormat::parse(None, &path)?,
+            [format] => ListingFormat::parse(Some(format), &path)?,
+            _ => return Err(usage()),
+        };
+        Ok(Self { format, all, path })
+    }
+}
+
+impl ListingEntry {
+    /// A container, which has no more than a name and a last-modified time.
+    #[must_use]
+    pub const fn container(name: String, last_modified: Option<DateTime<Utc>>) -> Self {
+        Self {
+            name,
+            kind: EntryKind::Container,
+            size: None,
+            last_modified,
+            etag: None,
+            tier: None,
+        }
+    }
+}
+
+/// Fill in the ETags and tiers of the blobs in `entries` from `listing`, which has them.
+pub fn add_properties(entries: &mut [ListingEntry], listing: BlobListing) {
+    let mut listed: std::collections::HashMap<String, _> = listing
+        .blobs
+        .into_iter()
+        .map(|blob| (blob.name, (blob.etag, blob.access_tier)))
+        .collect();
+    for entry in entries
+        .iter_mut()
+        .filter(|entry| entry.kind == EntryKind::Blob)
+    {
+        if let Some((etag, tier)) = listed.remove(&entry.name) {
+            entry.etag = etag;
+            entry.tier = tier;
+        }
+    }
+}
+
+/// The folders of `listing`, then its blobs.
+#[must_use]
+pub fn blob_entries(listing: BlobListing) -> Vec<ListingEntry> {
+    let folders = listing.prefixes.into_iter().map(|name| ListingEntry {
+        name,
+        kind: EntryKind::Folder,
+        size: None,
+        last_modified: None,
+        etag: None,
+        tier: None,
+    });
+    let blobs = listing.blobs.into_iter().map(|blob| ListingEntry {
+        name: blob.name,
+        kind: EntryKind::Blob,
+        size: Some(blob.size),
+        last_modified: blob.last_modified,
+        etag: blob.etag,
+        tier: blob.access_tier,
+    });
+    folders.chain(blobs).collect()
+}
+
+/// Write `entries` to `out` as `format`.
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_listing(
+    out: &mut impl Write,
+    entries: &[ListingEntry],
+    format: ListingFormat,
+) -> std::io::Result<()> {
+    match format {
+        ListingFormat::Table => {
+            let now = Utc::now();
+            for entry in entries {
+                let time = entry
+                    .last_modified
+                    .map(|time| dates::short(time, TimeFormat::Iso, now))
+                    .unwrap_or_default();
+                let size = match entry.kind {
+                    EntryKind::Folder => "DIR".to_string(),
+                    _ => entry.size.map(|size| size.to_string()).unwrap_or_default(),
+                };
+                let tier = entry.tier.as_deref().unwrap_or_default();
+                writeln!(out, "{time:20}  {tier:7}  {size:>12}  {}", entry.name)?;
+            }
+        }
+        ListingFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, entries)?;
+            writeln!(out)?;
+        }
+        ListingFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for entry in entries {
+                writer.serialize(entry)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::ListedBlob;
+    use chrono::TimeZone;
+
+    fn entries() -> Vec<ListingEntry> {
+        blob_entries(BlobListing {
+            blobs: vec![ListedBlob {
+                name: "2024/a,b.csv".to_string(),
+                size: 7,
+                last_modified: Utc.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).single(),
+                etag: Some("0x8DC1".to_string()),
+                access_tier: Some("Cool".to_string()),
+            }],
+            prefixes: vec!["2024/q1/".to_string()],
+        })
+    }
+
+    fn written(format: ListingFormat) -> String {
+        let mut out = Vec::new();
+        write_listing(&mut out, &entries(), format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn listings_are_written_in_each_format() {
+        assert_eq!(
+            written(ListingFormat::Table),
+            format!(
+                "{:20}  {:7}  {:>12}  2024/q1/\n2024-06-01T10:00:00Z  Cool     {:>12}  2024/a,b.csv\n",
+                "", "", "DIR", 7
+            )
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&written(ListingFormat::Json)).unwrap();
+        assert_eq!(json[0]["kind"], "folder");
+        assert_eq!(json[1]["etag"], "0x8DC1");
+        assert_eq!(json[1]["tier"], "Cool");
+        assert_eq!(json[1]["size"], 7);
+        assert_eq!(json[1]["last_modified"], "2024-06-01T10:00:00Z");
+
+        assert_eq!(
+            written(ListingFormat::Csv),
+            "name,kind,size,last_modified,etag,tier\n\
+             2024/q1/,folder,,,,\n\
+             \"2024/a,b.csv\",blob,7,2024-06-01T10:00:00Z,0x8DC1,Cool\n"
+        );
+    }
+
+    #[test]
+    fn properties_are_added_to_the_blobs_shown() {
+        let mut shown = vec![ListingEntry {
+            name: "2024/a,b.csv".to_string(),
+            kind: EntryKind::Blob,
+            size: Some(7),
+            last_modified: None,
+            etag: None,
+            tier: None,
+        }];
+        add_properties(
+            &mut shown,
+            BlobListing {
+                blobs: vec![ListedBlob {
+                    name: "2024/a,b.csv".to_string(),
+                    size: 7,
+                    last_modified: None,
+                    etag: Some("0x8DC1".to_string()),
+                    access_tier: Some("Cool".to_string()),
+                }],
+                prefixes: Vec::new(),
+            },
+        );
+        assert_eq!(shown[0].tier.as_deref(), Some("Cool"));
+        assert_eq!(shown[0].etag.as_deref(), Some("0x8DC1"));
+    }
+
+    #[test]
+    fn export_arguments_end_with_the_path() {
+        assert_eq!(
+            ExportRequest::parse("all ~/manifest.json").unwrap(),
+            ExportRequest {
+                format: ListingFormat::Json,
+                all: true,
+                path: "~/manifest.json".to_string(),
+            }
+        );
+        assert_eq!(
+            ExportRequest::parse("csv listing")
+                .map(|request| (request.format, request.all))
+                .unwrap(),
+            (ListingFormat::Csv, false)
+        );
+        assert!(matches!(
+            ExportRequest::parse(""),
+            Err(BlobrsError::Invalid(_))
+        ));
+        assert!(ExportRequest::parse("csv json all listing").is_err());
+    }
+
+    #[test]
+    fn the_format_can_come_from_the_file_name() {
+        assert_eq!(
+            ListingFormat::parse(None, "out/list.CSV").unwrap(),
+            ListingFormat::Csv
+        );
+        assert_eq!(
+            ListingFormat::parse(Some("json"), "list.csv").unwrap(),
+            ListingFormat::Json
+        );
+        assert!(ListingFormat::parse(None, "listing").is_err());
+    }
+}
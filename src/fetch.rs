@@ -0,0 +1,304 @@
+//! Reading blobs, or the parts of them a preview needs, with ranged reads: the start of a
+//! blob, its end for a Parquet footer or zip directory, or a tar's headers one after another.
+//!
+//! Each read can be pinned to a version for the time-travel view.
+
+use crate::archive::{
+    ArchiveKind, COMPRESSED_TAR_BYTES, TAR_WINDOW_BYTES, TarListing, ZIP_DIRECTORY_BYTES,
+    ZIP_TAIL_BYTES, archive_table, list_tar_stream, parse_zip_directory, zip_directory,
+};
+use crate::error::BlobrsError;
+use crate::preview::{PARQUET_FOOTER_BYTES, PreviewData, parquet_footer_len};
+use bytes::Bytes;
+use object_store::{
+    GetOptions, GetRange, ObjectMeta, ObjectStore, ObjectStoreExt, path::Path as ObjectPath,
+};
+use std::sync::Arc;
+
+/// Fetch a blob (or a byte range of it), optionally pinned to a specific version.
+///
+/// # Errors
+///
+/// Returns an error if the blob cannot be read.
+pub async fn bytes(
+    object_store: &Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+    range: Option<std::ops::Range<u64>>,
+    version: Option<&str>,
+) -> object_store::Result<Bytes> {
+    let options = GetOptions {
+        range: range.map(Into::into),
+        version: version.map(ToString::to_string),
+        ..GetOptions::default()
+    };
+    object_store.get_opts(path, options).await?.bytes().await
+}
+
+/// Fetch blob metadata, optionally pinned to a specific version.
+///
+/// # Errors
+///
+/// Returns an error if the blob cannot be found.
+pub async fn meta(
+    object_store: &Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+    version: Option<&str>,
+) -> object_store::Result<ObjectMeta> {
+    match version {
+        Some(version) => {
+            let options = GetOptions {
+                head: true,
+                version: Some(version.to_string()),
+                ..GetOptions::default()
+            };
+            Ok(object_store.get_opts(path, options).await?.meta)
+        }
+        None => object_store.head(path).await,
+    }
+}
+
+/// Fetch the last `len` bytes of a blob with a suffix range, so its size need not be looked
+/// up first. Returns them with the blob's metadata.
+///
+/// # Errors
+///
+/// Returns an error if the blob cannot be read.
+pub async fn tail(
+    object_store: &Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+    len: u64,
+    version: Option<&str>,
+) -> Result<(Bytes, ObjectMeta), BlobrsError> {
+    let options = GetOptions {
+        range: Some(GetRange::Suffix(len)),
+        version: version.map(ToString::to_string),
+        ..GetOptions::default()
+    };
+    match object_store.get_opts(path, options).await {
+        Ok(result) => {
+            let meta = result.meta.clone();
+            Ok((result.bytes().await?, meta))
+        }
+        // Azure takes no suffix ranges, so the size is looked up to read the same bytes
+        Err(object_store::Error::NotSupported { .. }) => {
+            let meta = meta(object_store, path, version).await?;
+            let start = meta.size.saturating_sub(len);
+            let tail = bytes(object_store, path, Some(start..meta.size), version).await?;
+            Ok((tail, meta))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Fetch a Parquet blob's footer: the last [`PARQUET_FOOTER_BYTES`], then the rest of the
+/// footer with a second ranged read if it is larger. Returns the footer, possibly with
+/// bytes before it, and the blob's metadata.
+///
+/// # Errors
+///
+/// Returns an error if the blob cannot be read or is not Parquet.
+pub async fn parquet_footer(
+    object_store: &Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+    version: Option<&str>,
+) -> Result<(Bytes, ObjectMeta), BlobrsError> {
+    let (tail, meta) = tail(object_store, path, PARQUET_FOOTER_BYTES as u64, version).await?;
+
+    let footer_len = parquet_footer_len(&tail).map_err(BlobrsError::Format)?;
+    let fetched = tail.len() as u64;
+    if footer_len <= fetched {
+        return Ok((tail, meta));
+    }
+    let Some(start) = meta.size.checked_sub(footer_len) else {
+        return Err(BlobrsError::Format(
+            "Failed to read Parquet file: footer longer than the file".to_string(),
+        ));
+    };
+    let rest = bytes(
+        object_store,
+        path,
+        Some(start..meta.size - fetched),
+        version,
+    )
+    .await?;
+    Ok(([rest, tail].concat().into(), meta))
+}
+
+/// List an archive's entries for the preview with ranged reads: a zip's from the central
+/// directory its last bytes point to, a tar's by reading from each header past the file
+/// after it, and a compressed tar's from the headers in its start.
+///
+/// # Errors
+///
+/// Returns an error if the blob cannot be read or is not a valid archive.
+pub async fn archive_listing(
+    object_store: &Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+    kind: ArchiveKind,
+    version: Option<&str>,
+) -> Result<PreviewData, BlobrsError> {
+    let fetch = |range| bytes(object_store, path, Some(range), version);
+    match kind {
+        ArchiveKind::Zip => {
+            let (tail, meta) = tail(object_store, path, ZIP_TAIL_BYTES as u64, version).await?;
+            let directory = zip_directory(&tail, meta.size)?;
+            let range = directory.range.start
+                ..directory
+                    .range
+                    .end
+                    .min(directory.range.start + ZIP_DIRECTORY_BYTES);
+            // A small directory came with the tail
+            let tail_start = meta.size - tail.len() as u64;
+            let data = match range.start.checked_sub(tail_start) {
+                Some(start) => tail.slice(start as usize..(range.end - tail_start) as usize),
+                None => fetch(range).await?,
+            };
+            let entries = parse_zip_directory(&data)?;
+            let total = usize::try_from(directory.entries).unwrap_or(usize::MAX);
+            let truncated = entries.len() < total;
+            Ok(archive_table(entries, total, truncated))
+        }
+        ArchiveKind::Tar(None) => {
+            let size = meta(object_store, path, version).await?.size;
+            let mut listing = TarListing::default();
+            while let Some(offset) = listing.next.filter(|&offset| offset < size) {
+                if !listing.wants_more() {
+                    break;
+                }
+                let data = fetch(offset..size.min(offset + TAR_WINDOW_BYTES)).await?;
+                listing.read(&data, offset)?;
+                // A header cut off by the end of the blob
+                if listing.next == Some(offset) {
+                    break;
+                }
+            }
+            Ok(listing.into_table())
+        }
+        ArchiveKind::Tar(Some(compression)) => {
+            let data = fetch(0..COMPRESSED_TAR_BYTES).await?;
+            let decoder = compression.decoder(&data).map_err(BlobrsError::Format)?;
+            Ok(list_tar_stream(decoder)?.into_table())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn archives_are_listed_with_ranged_reads() {
+        use crate::archive::tests::{tar_of, zip_of};
+        use crate::preview::MAX_PREVIEW_ROWS;
+        use std::io::Write;
+
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let list = |name: &'static str, bytes: Vec<u8>| {
+            let store = store.clone();
+            async move {
+                let path = ObjectPath::from(name);
+                store.put(&path, bytes.into()).await.unwrap();
+                let kind = ArchiveKind::of(name).unwrap();
+                match archive_listing(&store, &path, kind, None).await {
+                    Ok(PreviewData::Table(table)) => table,
+                    other => panic!("Expected a table for {name}, got {other:?}"),
+                }
+            }
+        };
+
+        // A directory too big to come with the tail is read on its own, its start only
+        let names: Vec<String> = (0..1000)
+            .map(|i| format!("{i:04}-{}", "x".repeat(80)))
+            .collect();
+        let files: Vec<(&str, &[u8])> =
+            names.iter().map(|name| (name.as_str(), &b""[..])).collect();
+        let table = list("many.zip", zip_of(&files)).await;
+        assert_eq!(table.headers, vec!["Name", "Size", "Compressed"]);
+        assert_eq!(table.rows.len(), MAX_PREVIEW_ROWS);
+        assert_eq!(table.rows[1][0], names[1]);
+        assert_eq!(table.total_rows, 1000);
+        assert!(table.truncated);
+
+        let large = vec![1; 200_000];
+        let tar = tar_of(&[
+            ("a.bin", &large, b'0'),
+            ("b.bin", &large, b'0'),
+            ("c.txt", b"c", b'0'),
+        ]);
+        let table = list("files.tar", tar.clone()).await;
+        assert_eq!(table.headers, vec!["Name", "Size"]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["a.bin", "200000"],
+                vec!["b.bin", "200000"],
+                vec!["c.txt", "1"]
+            ]
+        );
+        assert!(!table.truncated);
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gzip.write_all(&tar).unwrap();
+        let table = list("files.tgz", gzip.finish().unwrap()).await;
+        assert_eq!(table.rows.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn parquet_footers_are_read_from_the_end() {
+        use crate::preview::parse_parquet_schema;
+        use arrow::array::Int32Array;
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::ArrowWriter;
+        use parquet::file::metadata::KeyValue;
+        use parquet::file::properties::WriterProperties;
+
+        let parquet = |note: usize| {
+            let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int32, false)]));
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int32Array::from((0..10_000).collect::<Vec<_>>()))],
+            )
+            .unwrap();
+            let properties = WriterProperties::builder()
+                .set_key_value_metadata(Some(vec![KeyValue::new(
+                    "note".to_string(),
+                    "x".repeat(note),
+                )]))
+                .build();
+            let mut buffer = Vec::new();
+            let mut writer = ArrowWriter::try_new(&mut buffer, schema, Some(properties)).unwrap();
+            writer.write(&batch).unwrap();
+            writer.close().unwrap();
+            buffer
+        };
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        // A footer far bigger than the first read takes a second
+        for (name, note) in [
+            ("small.parquet", 0),
+            ("wide.parquet", 3 * PARQUET_FOOTER_BYTES),
+        ] {
+            let bytes = parquet(note);
+            let path = ObjectPath::from(name);
+            store.put(&path, bytes.clone().into()).await.unwrap();
+
+            let (footer, meta) = parquet_footer(&store, &path, None).await.unwrap();
+            assert_eq!(meta.size, bytes.len() as u64);
+            assert!(bytes.ends_with(&footer));
+            assert!(footer.len() as u64 >= parquet_footer_len(&footer).unwrap());
+            let Ok(PreviewData::ParquetSchema(schema)) =
+                parse_parquet_schema(&footer, Some(meta.size))
+            else {
+                panic!("{name} has no schema");
+            };
+            assert_eq!(schema.num_rows, 10_000);
+        }
+
+        let path = ObjectPath::from("not.parquet");
+        store
+            .put(&path, b"not parquet".to_vec().into())
+            .await
+            .unwrap();
+        assert!(parquet_footer(&store, &path, None).await.is_err());
+    }
+}
@@ -0,0 +1,577 @@
+//! How the search box filters the container and blob lists: fuzzily (the default), by a glob
+//! such as `*.parquet`, or by a regular expression. `Tab` in the search box switches between
+//! them. Every mode ignores case.
+//!
+//! Words such as `>100MB`, `<=1KB`, `modified:2024-06` or `modified:<2023` in any mode are
+//! conditions on the size or last modified date rather than part of the name, so the huge or
+//! stale blobs in a folder can be found alongside a name search. `ext:parquet,json` shows only
+//! those file types; the `e` popup writes it for you.
+
+use crate::error::BlobrsError;
+use crate::fuzzy::{FuzzyMatch, fuzzy_match};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use regex::{Regex, RegexBuilder};
+use std::collections::BTreeSet;
+
+/// How a search query is read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FilterMode {
+    /// The query's letters in order, with the best match selected.
+    #[default]
+    Fuzzy,
+    /// The whole name against `*`, `?` and `[...]` wildcards.
+    Glob,
+    /// A regular expression anywhere in the name.
+    Regex,
+}
+
+impl FilterMode {
+    /// The mode `Tab` switches to.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Fuzzy => Self::Glob,
+            Self::Glob => Self::Regex,
+            Self::Regex => Self::Fuzzy,
+        }
+    }
+
+    /// Whether a name matching a query also matches every shorter query it was typed from, so
+    /// typing more only needs to check the names already shown.
+    #[must_use]
+    pub const fn narrows(self) -> bool {
+        matches!(self, Self::Fuzzy)
+    }
+
+    /// Name shown in the search box.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Fuzzy => "fuzzy",
+            Self::Glob => "glob",
+            Self::Regex => "regex",
+        }
+    }
+}
+
+/// A search query ready to match entries.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    name: NameFilter,
+    conditions: Vec<Condition>,
+}
+
+/// The part of a query matching names.
+#[derive(Debug, Clone, Default)]
+enum NameFilter {
+    /// Matches every name, for an empty or invalid query.
+    #[default]
+    Everything,
+    Fuzzy(String),
+    /// A glob or regex; each group captures letters to highlight.
+    Pattern(Regex),
+}
+
+/// How a size condition compares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    AtMost,
+    Greater,
+    AtLeast,
+}
+
+/// A condition on something other than the name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Size(Comparison, u64),
+    /// The name ends in one of these extensions, lower-cased.
+    Extensions(BTreeSet<String>),
+    /// Last modified on or after `from` and before `until`, by UTC date.
+    Modified {
+        from: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    },
+}
+
+impl Condition {
+    /// The condition `word` stands for, `None` if it is part of the name.
+    fn parse(word: &str) -> Option<Result<Self, BlobrsError>> {
+        if let Some(date) = strip_keyword(word, "modified:") {
+            return Some(parse_modified(date));
+        }
+        if let Some(list) = strip_keyword(word, "ext:") {
+            let extensions = parse_extensions(list);
+            return Some(if extensions.is_empty() {
+                Err(BlobrsError::Invalid(
+                    "invalid extensions: try ext:parquet,json".to_string(),
+                ))
+            } else {
+                Ok(Self::Extensions(extensions))
+            });
+        }
+        let (comparison, size) = if let Some(size) = word.strip_prefix(">=") {
+            (Comparison::AtLeast, size)
+        } else if let Some(size) = word.strip_prefix("<=") {
+            (Comparison::AtMost, size)
+        } else if let Some(size) = word.strip_prefix('>') {
+            (Comparison::Greater, size)
+        } else if let Some(size) = word.strip_prefix('<') {
+            (Comparison::Less, size)
+        } else {
+            return None;
+        };
+        Some(
+            parse_size(size)
+                .map(|bytes| Self::Size(comparison, bytes))
+                .ok_or_else(|| BlobrsError::Invalid(format!("invalid size: {size}, try 100MB"))),
+        )
+    }
+
+    /// Whether an entry meets the condition; an unknown size or date never does.
+    fn is_met(&self, name: &str, size: Option<u64>, modified: Option<DateTime<Utc>>) -> bool {
+        match *self {
+            Self::Size(comparison, bytes) => size.is_some_and(|size| match comparison {
+                Comparison::Less => size < bytes,
+                Comparison::AtMost => size <= bytes,
+                Comparison::Greater => size > bytes,
+                Comparison::AtLeast => size >= bytes,
+            }),
+            Self::Modified { from, until } => modified.is_some_and(|modified| {
+                let date = modified.date_naive();
+                from.is_none_or(|from| date >= from) && until.is_none_or(|until| date < until)
+            }),
+            Self::Extensions(ref extensions) => {
+                extension(name).is_some_and(|extension| extensions.contains(&extension))
+            }
+        }
+    }
+}
+
+/// `word` after `keyword`, such as `modified:`, in any case.
+fn strip_keyword<'a>(word: &'a str, keyword: &str) -> Option<&'a str> {
+    word.get(..keyword.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(keyword))
+        .map(|_| &word[keyword.len()..])
+}
+
+/// The extensions in a list such as `parquet,.JSON`, lower-cased and without dots.
+fn parse_extensions(list: &str) -> BTreeSet<String> {
+    list.split(',')
+        .map(|extension| extension.trim_start_matches('.').to_lowercase())
+        .filter(|extension| !extension.is_empty())
+        .collect()
+}
+
+/// The extension of `name`, lower-cased: what follows its last `.`, unless the dot starts the
+/// name as in `.env`.
+#[must_use]
+pub fn extension(name: &str) -> Option<String> {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && !extension.is_empty() => {
+            Some(extension.to_lowercase())
+        }
+        _ => None,
+    }
+}
+
+/// The extensions the `ext:` word in `query` shows, empty when it has none.
+#[must_use]
+pub fn extensions_in(query: &str) -> BTreeSet<String> {
+    query
+        .split(' ')
+        .filter_map(|word| strip_keyword(word, "ext:"))
+        .flat_map(parse_extensions)
+        .collect()
+}
+
+/// `query` showing only `extensions`: its `ext:` word replaced, or removed when there are none.
+#[must_use]
+pub fn with_extensions(query: &str, extensions: &BTreeSet<String>) -> String {
+    let mut words: Vec<String> = query
+        .split(' ')
+        .filter(|word| !word.is_empty() && strip_keyword(word, "ext:").is_none())
+        .map(str::to_string)
+        .collect();
+    if !extensions.is_empty() {
+        let list: Vec<&str> = extensions.iter().map(String::as_str).collect();
+        words.push(format!("ext:{}", list.join(",")));
+    }
+    words.join(" ")
+}
+
+/// Bytes in a size such as `100MB`, `1.5g` or `512`, counting in 1024s as the list shows sizes.
+fn parse_size(size: &str) -> Option<u64> {
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let power = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 1,
+        "m" | "mb" | "mib" => 2,
+        "g" | "gb" | "gib" => 3,
+        "t" | "tb" | "tib" => 4,
+        _ => return None,
+    };
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    // the number has no sign, and sizes past u64::MAX saturate
+    Some((number * 1024_f64.powi(power)).round() as u64)
+}
+
+/// The dates a `modified:` condition covers: the year, month or day given, or with `<` or `>`
+/// before it, everything before or after that.
+fn parse_modified(date: &str) -> Result<Condition, BlobrsError> {
+    let invalid = || BlobrsError::Invalid(format!("invalid date: {date}, try 2024-06"));
+    let (before, after, period) = if let Some(period) = date.strip_prefix('<') {
+        (true, false, period)
+    } else if let Some(period) = date.strip_prefix('>') {
+        (false, true, period)
+    } else {
+        (false, false, date)
+    };
+    let parts: Vec<u32> = period
+        .split('-')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()
+        .ok_or_else(invalid)?;
+    let year = |year: u32| i32::try_from(year).ok();
+    let (start, end) = match parts[..] {
+        [y] => {
+            let start = NaiveDate::from_ymd_opt(year(y).ok_or_else(invalid)?, 1, 1);
+            (
+                start,
+                start.and_then(|start| start.with_year(start.year() + 1)),
+            )
+        }
+        [y, m] => {
+            let start = NaiveDate::from_ymd_opt(year(y).ok_or_else(invalid)?, m, 1);
+            let end = start.and_then(|start| {
+                if start.month() == 12 {
+                    NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+                } else {
+                    start.with_month(start.month() + 1)
+                }
+            });
+            (start, end)
+        }
+        [y, m, d] => {
+            let start = NaiveDate::from_ymd_opt(year(y).ok_or_else(invalid)?, m, d);
+            (start, start.and_then(|start| start.succ_opt()))
+        }
+        _ => return Err(invalid()),
+    };
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(invalid());
+    };
+    Ok(Condition::Modified {
+        from: (!before).then_some(if after { end } else { start }),
+        until: (!after).then_some(if before { start } else { end }),
+    })
+}
+
+impl Filter {
+    /// Read `query` as `mode` says, with any size and date conditions in it.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the pattern or a condition is invalid, for showing beside the search box.
+    pub fn new(mode: FilterMode, query: &str) -> Result<Self, BlobrsError> {
+        let mut conditions = Vec::new();
+        let mut words = Vec::new();
+        for word in query.split(' ') {
+            match Condition::parse(word) {
+                Some(condition) => conditions.push(condition?),
+                None => words.push(word),
+            }
+        }
+        let name = if conditions.is_empty() {
+            NameFilter::new(mode, query)?
+        } else {
+            NameFilter::new(mode, words.join(" ").trim())?
+        };
+        Ok(Self { name, conditions })
+    }
+
+    /// Read `query` as `mode` says, matching everything when it is invalid.
+    #[must_use]
+    pub fn lenient(mode: FilterMode, query: &str) -> Self {
+        Self::new(mode, query).unwrap_or_default()
+    }
+
+    /// Whether it has size or date conditions. Editing the number in one can widen the
+    /// matches, as `<1` becomes `<10`, so typing never only narrows them.
+    #[must_use]
+    pub fn has_conditions(&self) -> bool {
+        !self.conditions.is_empty()
+    }
+
+    /// Whether `name` matches, with the matched letters; glob and regex matches all score 0.
+    /// Conditions are not checked.
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<FuzzyMatch> {
+        self.name.find(name)
+    }
+
+    /// Whether an entry matches by name and meets every condition.
+    #[must_use]
+    pub fn is_match(&self, name: &str, size: Option<u64>, modified: Option<DateTime<Utc>>) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.is_met(name, size, modified))
+            && self.name.is_match(name)
+    }
+}
+
+impl NameFilter {
+    fn new(mode: FilterMode, query: &str) -> Result<Self, BlobrsError> {
+        if query.is_empty() {
+            return Ok(Self::Everything);
+        }
+        let pattern = match mode {
+            FilterMode::Fuzzy => return Ok(Self::Fuzzy(query.to_string())),
+            FilterMode::Glob => glob_to_regex(query)?,
+            // The whole match is highlighted
+            FilterMode::Regex => format!("({query})"),
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .map(Self::Pattern)
+            .map_err(|e| {
+                BlobrsError::Invalid(match e {
+                    regex::Error::Syntax(message) => {
+                        // The last line says what is wrong; the rest points at it in the pattern
+                        let reason = message.lines().last().unwrap_or_default();
+                        format!("invalid regex: {}", reason.trim_start_matches("error: "))
+                    }
+                    other => other.to_string(),
+                })
+            })
+    }
+
+    fn find(&self, name: &str) -> Option<FuzzyMatch> {
+        match self {
+            Self::Everything => fuzzy_match(name, ""),
+            Self::Fuzzy(query) => fuzzy_match(name, query),
+            Self::Pattern(regex) => {
+                let captures = regex.captures(name)?;
+                let mut positions = Vec::new();
+                for group in captures.iter().skip(1).flatten() {
+                    let start = name[..group.start()].chars().count();
+                    positions.extend(start..start + group.as_str().chars().count());
+                }
+                Some(FuzzyMatch {
+                    score: 0,
+                    positions,
+                })
+            }
+        }
+    }
+
+    fn is_match(&self, name: &str) -> bool {
+        match self {
+            Self::Everything => true,
+            Self::Fuzzy(query) => fuzzy_match(name, query).is_some(),
+            Self::Pattern(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// An anchored regex for a glob, with each run of literal letters in a group so it can be
+/// highlighted.
+fn glob_to_regex(glob: &str) -> Result<String, BlobrsError> {
+    let mut pattern = String::from("^");
+    let mut literal = String::new();
+    let mut chars = glob.chars();
+    let flush = |pattern: &mut String, literal: &mut String| {
+        if !literal.is_empty() {
+            pattern.push('(');
+            pattern.push_str(&regex::escape(literal));
+            pattern.push(')');
+            literal.clear();
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                flush(&mut pattern, &mut literal);
+                pattern.push_str(".*");
+            }
+            '?' => {
+                flush(&mut pattern, &mut literal);
+                pattern.push('.');
+            }
+            '[' => {
+                flush(&mut pattern, &mut literal);
+                let mut class = String::from("[");
+                let mut closed = false;
+                for (index, c) in chars.by_ref().enumerate() {
+                    match c {
+                        ']' if index > 0 => {
+                            closed = true;
+                            break;
+                        }
+                        '!' if index == 0 => class.push('^'),
+                        // Escapes and set operators in a regex class are literal in a glob
+                        '\\' | '[' | '&' | '~' => {
+                            class.push('\\');
+                            class.push(c);
+                        }
+                        c => class.push(c),
+                    }
+                }
+                if !closed {
+                    return Err(BlobrsError::Invalid("invalid glob: unclosed [".to_string()));
+                }
+                class.push(']');
+                pattern.push_str(&class);
+            }
+            c => literal.push(c),
+        }
+    }
+    flush(&mut pattern, &mut literal);
+    pattern.push('$');
+    Ok(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(mode: FilterMode, query: &str) -> Vec<&'static str> {
+        let filter = Filter::new(mode, query).unwrap();
+        [
+            "sales_2024.parquet",
+            "sales_2023.csv",
+            "Orders.PARQUET",
+            "readme.md",
+        ]
+        .into_iter()
+        .filter(|name| filter.is_match(name, None, None))
+        .collect()
+    }
+
+    #[test]
+    fn globs_match_whole_names_and_regexes_match_anywhere() {
+        assert_eq!(
+            names(FilterMode::Glob, "*.parquet"),
+            ["sales_2024.parquet", "Orders.PARQUET"]
+        );
+        assert_eq!(
+            names(FilterMode::Glob, "sales_202?.*"),
+            ["sales_2024.parquet", "sales_2023.csv"]
+        );
+        assert_eq!(
+            names(FilterMode::Glob, "sales_202[!4]*"),
+            ["sales_2023.csv"]
+        );
+        assert!(names(FilterMode::Glob, "sales").is_empty());
+        assert_eq!(
+            names(FilterMode::Regex, r"20\d{2}\.csv$"),
+            ["sales_2023.csv"]
+        );
+        assert_eq!(names(FilterMode::Fuzzy, "s24p"), ["sales_2024.parquet"]);
+        assert_eq!(names(FilterMode::Regex, "").len(), 4);
+    }
+
+    #[test]
+    fn literal_letters_are_highlighted() {
+        let glob = Filter::new(FilterMode::Glob, "*.csv").unwrap();
+        assert_eq!(glob.find("a.csv").unwrap().positions, [1, 2, 3, 4]);
+        let regex = Filter::new(FilterMode::Regex, "le.").unwrap();
+        assert_eq!(regex.find("sales").unwrap().positions, [2, 3, 4]);
+    }
+
+    #[test]
+    fn invalid_patterns_say_why_and_match_everything_leniently() {
+        let error = Filter::new(FilterMode::Regex, "(sales")
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("invalid regex: "), "{error}");
+        assert!(error.contains("unclosed group"), "{error}");
+        assert_eq!(
+            Filter::new(FilterMode::Glob, "[abc")
+                .unwrap_err()
+                .to_string(),
+            "invalid glob: unclosed ["
+        );
+        assert!(Filter::lenient(FilterMode::Regex, "(sales").is_match("anything", None, None));
+        // Regex characters in a glob are literal
+        assert!(
+            Filter::new(FilterMode::Glob, "a+b(1).txt")
+                .unwrap()
+                .is_match("a+b(1).txt", None, None)
+        );
+    }
+
+    #[test]
+    fn sizes_and_dates_are_conditions_beside_the_name() {
+        let june = "2024-06-15T12:00:00Z".parse::<DateTime<Utc>>().ok();
+        let filter = Filter::new(FilterMode::Glob, ">100MB *.parquet").unwrap();
+        assert!(filter.has_conditions());
+        assert!(filter.is_match("big.parquet", Some(200 << 20), june));
+        assert!(!filter.is_match("big.csv", Some(200 << 20), june));
+        assert!(!filter.is_match("small.parquet", Some(100 << 20), june));
+        // Folders have no size, so a size condition leaves them out
+        assert!(!filter.is_match("folder.parquet", None, june));
+        // The name is highlighted without the conditions
+        assert_eq!(filter.find("a.parquet").unwrap().positions.len(), 8);
+
+        let at_most = Filter::new(FilterMode::Fuzzy, "<=1.5kb").unwrap();
+        assert!(at_most.is_match("x", Some(1536), None));
+        assert!(!at_most.is_match("x", Some(1537), None));
+
+        let month = Filter::new(FilterMode::Fuzzy, "modified:2024-06").unwrap();
+        assert!(month.is_match("x", None, june));
+        assert!(!month.is_match("x", None, "2024-07-01T00:00:00Z".parse().ok()));
+        assert!(!month.is_match("x", None, None));
+        let before = Filter::new(FilterMode::Fuzzy, "Modified:<2024").unwrap();
+        assert!(before.is_match("x", None, "2023-12-31T23:59:59Z".parse().ok()));
+        assert!(!before.is_match("x", None, june));
+        let after = Filter::new(FilterMode::Fuzzy, "modified:>2024-06-14").unwrap();
+        assert!(after.is_match("x", None, june));
+        assert!(!after.is_match("x", None, "2024-06-14T23:00:00Z".parse().ok()));
+
+        assert_eq!(
+            Filter::new(FilterMode::Fuzzy, ">10XB")
+                .unwrap_err()
+                .to_string(),
+            "invalid size: 10XB, try 100MB"
+        );
+        assert_eq!(
+            Filter::new(FilterMode::Fuzzy, "modified:2024-13")
+                .unwrap_err()
+                .to_string(),
+            "invalid date: 2024-13, try 2024-06"
+        );
+        assert!(
+            !Filter::new(FilterMode::Fuzzy, "sales")
+                .unwrap()
+                .has_conditions()
+        );
+    }
+
+    #[test]
+    fn extension_words_show_only_those_file_types() {
+        let filter = Filter::new(FilterMode::Fuzzy, "sales ext:parquet,.JSON").unwrap();
+        assert!(filter.is_match("sales_2024.parquet", None, None));
+        assert!(filter.is_match("Sales.Json", None, None));
+        assert!(!filter.is_match("sales.csv", None, None));
+        assert!(!filter.is_match("orders.json", None, None));
+        assert!(Filter::new(FilterMode::Fuzzy, "ext:").is_err());
+
+        assert_eq!(extension("a.tar.GZ").as_deref(), Some("gz"));
+        assert_eq!(extension(".env"), None);
+        assert_eq!(extension("README"), None);
+
+        let shown: BTreeSet<String> = ["json".to_string(), "csv".to_string()].into();
+        assert_eq!(
+            with_extensions("sales ext:md", &shown),
+            "sales ext:csv,json"
+        );
+        assert_eq!(extensions_in("sales ext:csv,json"), shown);
+        assert_eq!(with_extensions("ext:md sales", &BTreeSet::new()), "sales");
+    }
+}
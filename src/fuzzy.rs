@@ -0,0 +1,144 @@
+//! Fuzzy matching of names, in the style of fzf: the letters typed must appear in order, and a
+//! match scores higher when they are close together, follow one another, or start words.
+//!
+//! Used by the container and blob searches, where it picks the best match and highlights the
+//! matched letters, and by command mode to rank command names.
+
+/// Points for each matched letter.
+const SCORE_MATCH: i32 = 16;
+/// Penalty for the first letter skipped between two matched ones.
+const GAP_START: i32 = -3;
+/// Penalty for each further letter skipped.
+const GAP_EXTENSION: i32 = -1;
+/// Bonus for a letter at the start of a word: after `/`, `-`, `_`, `.` or a space, or at the
+/// start of the name.
+const BONUS_BOUNDARY: i32 = 8;
+/// Bonus for an upper-case letter after a lower-case one, as in `camelCase`.
+const BONUS_CAMEL: i32 = 7;
+/// Least bonus for a letter straight after the one matched before it; a run of letters that
+/// starts a word gets the word's bonus for each of them.
+const BONUS_CONSECUTIVE: i32 = 4;
+
+/// Where a query matched a name, and how well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better.
+    pub score: i32,
+    /// Character positions in the name of each matched letter, in order.
+    pub positions: Vec<usize>,
+}
+
+/// Match `query` against `name`, ignoring case. `None` when its letters do not all appear in
+/// `name` in order; an empty query matches everything with a score of 0.
+#[must_use]
+pub fn fuzzy_match(name: &str, query: &str) -> Option<FuzzyMatch> {
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    // Find where the earliest match ends without allocating, as most names will not match
+    let mut wanted = query.chars().peekable();
+    let mut end = None;
+    for (index, c) in name.chars().enumerate() {
+        if wanted.next_if(|w| same_letter(*w, c)).is_some() && wanted.peek().is_none() {
+            end = Some(index);
+            break;
+        }
+    }
+    let end = end?;
+
+    // Then walk back from there for the shortest stretch holding every letter
+    let chars: Vec<char> = name.chars().take(end + 1).collect();
+    let mut positions = Vec::with_capacity(query.chars().count());
+    let mut wanted = query.chars().rev().peekable();
+    for (index, c) in chars.iter().enumerate().rev() {
+        if wanted.next_if(|w| same_letter(*w, *c)).is_some() {
+            positions.push(index);
+            if wanted.peek().is_none() {
+                break;
+            }
+        }
+    }
+    positions.reverse();
+
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    // Bonus of the letter starting the current run of consecutive letters
+    let mut run_bonus = 0;
+    for &position in &positions {
+        let mut bonus = bonus_at(&chars, position);
+        match previous {
+            Some(previous) if previous + 1 == position => {
+                bonus = bonus.max(run_bonus).max(BONUS_CONSECUTIVE);
+            }
+            Some(previous) => {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+                // names are far shorter than i32::MAX
+                let skipped = (position - previous - 1) as i32;
+                score += GAP_START + GAP_EXTENSION * (skipped - 1);
+                run_bonus = bonus;
+            }
+            None => run_bonus = bonus,
+        }
+        // The first letter counts double, so matching the start of a word wins
+        if previous.is_none() {
+            bonus *= 2;
+        }
+        score += SCORE_MATCH + bonus;
+        previous = Some(position);
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+fn same_letter(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Bonus for matching the letter at `index`, by what comes before it.
+fn bonus_at(chars: &[char], index: usize) -> i32 {
+    let Some(&before) = index.checked_sub(1).and_then(|i| chars.get(i)) else {
+        return BONUS_BOUNDARY;
+    };
+    let c = chars[index];
+    if matches!(before, '/' | '-' | '_' | '.' | ' ') {
+        BONUS_BOUNDARY
+    } else if before.is_lowercase() && c.is_uppercase() {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(name: &str, query: &str) -> Option<Vec<usize>> {
+        fuzzy_match(name, query).map(|found| found.positions)
+    }
+
+    fn score(name: &str, query: &str) -> i32 {
+        fuzzy_match(name, query).map_or(i32::MIN, |found| found.score)
+    }
+
+    #[test]
+    fn letters_match_in_order_ignoring_case() {
+        assert_eq!(positions("sales_2024.csv", "s24c"), Some(vec![4, 8, 9, 11]));
+        assert_eq!(positions("Report.PDF", "rpdf"), Some(vec![4, 7, 8, 9]));
+        assert_eq!(positions("sales.csv", "vc"), None);
+        assert_eq!(positions("abc", ""), Some(Vec::new()));
+        // The shortest stretch holding the letters is the one highlighted
+        assert_eq!(positions("a_xa_b", "ab"), Some(vec![3, 5]));
+    }
+
+    #[test]
+    fn close_letters_and_word_starts_score_higher() {
+        assert!(score("sales.csv", "sales") > score("s_a_l_e_s.csv", "sales"));
+        assert!(score("new-folder", "nf") > score("info", "nf"));
+        assert!(score("userEvents.json", "ue") > score("queue.json", "ue"));
+        assert!(score("logs/app.log", "app") > score("snapper.log", "app"));
+    }
+}
@@ -0,0 +1,200 @@
+//! The keymap, by where each key works, for the `?` help overlay.
+//!
+//! The footer only has room for the most common keys; everything the app responds to is
+//! listed here, so keep this in step with `App::handle_key_event` when adding a binding.
+
+/// Keys that do one thing, with what they do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub keys: &'static str,
+    pub action: &'static str,
+}
+
+/// Bindings that work in the same place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section {
+    pub title: &'static str,
+    pub bindings: &'static [Binding],
+}
+
+const fn bind(keys: &'static str, action: &'static str) -> Binding {
+    Binding { keys, action }
+}
+
+/// Every binding, in the order shown.
+pub const KEYMAP: &[Section] = &[
+    Section {
+        title: "Everywhere",
+        bindings: &[
+            bind("?", "Show or hide this help"),
+            bind(
+                ":  Ctrl-p",
+                "Run a command by name, e.g. :goto raw/2024, :sort size, :tier cool",
+            ),
+            bind("q  Ctrl-c", "Quit"),
+            bind("F", "Search every container by name, or one by index tags"),
+            bind(
+                "Ctrl-/",
+                "Search everything under this folder, at any depth",
+            ),
+            bind("J", "Background jobs"),
+            bind("H", "Show or hide the activity log"),
+            bind("Ctrl-t  Ctrl-w", "Open or close a tab"),
+            bind("Tab", "Show the next tab"),
+            bind("|", "Two panes: the shown tab and the one next to it"),
+            bind("F5  F6", "Copy or move into the other pane's folder"),
+        ],
+    },
+    Section {
+        title: "Moving through lists",
+        bindings: &[
+            bind("↑ k  ↓ j", "Previous or next entry"),
+            bind("PgUp  PgDn", "A page up or down"),
+            bind("Ctrl-u  Ctrl-d", "Half a page up or down"),
+            bind("gg Home  G End", "First or last entry"),
+            bind(
+                "<count>",
+                "Repeat the next motion, e.g. 25j; with gg/G, go to that row",
+            ),
+            bind(
+                "'<name>",
+                "Jump to the first entry starting with what is typed",
+            ),
+            bind("Wheel", "Scroll"),
+            bind("Click", "Select; double click to open"),
+            bind("Click the path", "Go up to that folder"),
+        ],
+    },
+    Section {
+        title: "Containers",
+        bindings: &[
+            bind("Enter → l", "Open the container"),
+            bind("/", "Search; Tab switches between fuzzy, glob and regex"),
+            bind("o", "Sort"),
+            bind("s  S", "Show sizes, or rescan the selected container"),
+            bind("i", "Properties"),
+            bind("N", "Edit the note"),
+            bind("n", "New container"),
+            bind("x  Del", "Delete an empty container"),
+            bind("r  F5", "Refresh"),
+            bind("Esc", "Quit"),
+        ],
+    },
+    Section {
+        title: "Blobs and folders",
+        bindings: &[
+            bind("Enter → l", "Open the folder; Enter previews a file"),
+            bind("← h  Esc", "Up a folder"),
+            bind("Backspace", "Back to the containers"),
+            bind("/", "Search; Tab switches between fuzzy, glob and regex"),
+            bind(
+                "/>100MB",
+                "Search by size or date too, e.g. <1KB or modified:2024-06",
+            ),
+            bind("e", "Show only some file types, e.g. .parquet and .json"),
+            bind("s", "Sort"),
+            bind("r  F5", "Refresh"),
+            bind("i", "Info"),
+            bind("p", "Preview"),
+            bind("w", "Preview pane beside the list, following the selection"),
+            bind("f", "Follow the blob as it grows"),
+            bind("E", "Edit in $EDITOR, then upload the changes"),
+            bind("!", "Pipe into a shell command, e.g. `jq . | less`"),
+            bind("=", "Compare with the marked blob, or a local file"),
+            bind("d  D", "Download; D always asks where to"),
+            bind("o", "Open the file last downloaded"),
+            bind("u", "Upload a file, or undelete a deleted blob"),
+            bind("U", "Upload from the clipboard"),
+            bind("S", "Sync with a local folder"),
+            bind("n", "New folder"),
+            bind("c", "Clone"),
+            bind("R  F2", "Move or rename"),
+            bind("x  Del", "Delete"),
+            bind("Space  a  A", "Mark, mark all, or invert the marks"),
+            bind("y", "Copy the path to the clipboard"),
+            bind("Y  P", "Yank, then paste into the shown folder"),
+            bind("C", "Copy an az or azcopy download command"),
+            bind(".", "Show or hide deleted blobs"),
+            bind("v", "List every blob under this folder, without folders"),
+            bind(
+                "[  ]",
+                "Previous or next page of a folder too large to list at once",
+            ),
+            bind("B", "Break down the folder's size by what is inside"),
+            bind("W", "Watch the folder, highlighting new and changed blobs"),
+            bind("T", "Browse as of a point in time"),
+            bind("N", "Edit the container's note"),
+        ],
+    },
+    Section {
+        title: "Blob info",
+        bindings: &[
+            bind("s", "Snapshots"),
+            bind("t", "Index tags"),
+            bind("L", "Lease"),
+            bind("r", "Count a folder's contents again"),
+            bind("Esc ← h", "Close"),
+        ],
+    },
+    Section {
+        title: "Preview",
+        bindings: &[
+            bind("↑ ↓ ← →", "Scroll"),
+            bind("PgUp  PgDn", "A page up or down"),
+            bind("/", "Search the text or table"),
+            bind("n  N", "Next or previous match"),
+            bind(
+                "c  C  Y",
+                "Copy the cell or line, the row, or all that is loaded",
+            ),
+            bind("Tab", "Parquet schema or data"),
+            bind("p  Esc", "Close; Esc first clears a search"),
+        ],
+    },
+];
+
+/// Lines the help takes: each section's title and bindings, with a blank line between
+/// sections.
+#[must_use]
+pub fn line_count() -> usize {
+    KEYMAP
+        .iter()
+        .map(|section| section.bindings.len() + 1)
+        .sum::<usize>()
+        + KEYMAP.len().saturating_sub(1)
+}
+
+/// Width of the widest keys, for lining up the actions.
+#[must_use]
+pub fn keys_width() -> usize {
+    KEYMAP
+        .iter()
+        .flat_map(|section| section.bindings)
+        .map(|binding| binding.keys.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn keys_are_listed_once_per_section() {
+        for section in KEYMAP {
+            assert!(!section.bindings.is_empty(), "{} is empty", section.title);
+            let mut seen = BTreeSet::new();
+            for binding in section.bindings {
+                assert!(
+                    seen.insert(binding.keys),
+                    "{} is listed twice in {}",
+                    binding.keys,
+                    section.title
+                );
+            }
+        }
+        assert_eq!(line_count(), 6 + 73 + 9);
+        assert_eq!(keys_width(), "gg Home  G End".chars().count());
+    }
+}
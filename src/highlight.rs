@@ -0,0 +1,94 @@
+//! Syntax highlighting for text previews, with syntect's bundled syntaxes picked by the file's
+//! extension and its styles turned into ratatui's.
+//!
+//! Only the foreground colour and font style are kept, so highlighted code sits on the
+//! preview's own background whatever the theme.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::sync::LazyLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+/// The bundled syntaxes, loaded the first time a preview is highlighted.
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    ThemeSet::load_defaults()
+        .themes
+        .remove("base16-ocean.dark")
+        .unwrap_or_default()
+});
+
+/// `content` highlighted a line at a time as the language of files ending `.extension`, or of
+/// its first line such as a `#!` line. `None` when neither names a language.
+#[must_use]
+pub fn highlight(content: &str, extension: &str) -> Option<Vec<Line<'static>>> {
+    let syntax = SYNTAXES
+        .find_syntax_by_extension(&extension.to_ascii_lowercase())
+        .or_else(|| SYNTAXES.find_syntax_by_first_line(content.lines().next()?))?;
+    if syntax.name == "Plain Text" {
+        return None;
+    }
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    content
+        .lines()
+        .map(|line| {
+            // The syntaxes match each line with its newline
+            let line = format!("{line}\n");
+            let ranges = highlighter.highlight_line(&line, &SYNTAXES).ok()?;
+            let spans: Vec<Span<'static>> = ranges
+                .into_iter()
+                .map(|(style, text)| (style, text.trim_end_matches('\n')))
+                .filter(|(_, text)| !text.is_empty())
+                .map(|(style, text)| Span::styled(text.to_string(), ratatui_style(style)))
+                .collect();
+            Some(Line::from(spans))
+        })
+        .collect()
+}
+
+fn ratatui_style(style: syntect::highlighting::Style) -> Style {
+    let mut modifiers = Modifier::empty();
+    if style.font_style.contains(FontStyle::BOLD) {
+        modifiers |= Modifier::BOLD;
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        modifiers |= Modifier::ITALIC;
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        modifiers |= Modifier::UNDERLINED;
+    }
+    let fg = style.foreground;
+    Style::default()
+        .fg(Color::Rgb(fg.r, fg.g, fg.b))
+        .add_modifier(modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_is_highlighted_by_extension_or_first_line() {
+        let source = "def greet(name):\n    return f\"hi {name}\"\n";
+        let lines = highlight(source, "PY").unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].to_string(), "def greet(name):");
+        // The keyword is coloured apart from the name it defines
+        let colour = |text: &str| {
+            lines[0]
+                .spans
+                .iter()
+                .find(|span| span.content == text)
+                .and_then(|span| span.style.fg)
+        };
+        assert!(colour("def").is_some());
+        assert_ne!(colour("def"), colour("greet"));
+
+        assert!(highlight("#!/bin/bash\necho hi\n", "").is_some());
+        assert!(highlight("just some notes\n", "TXT").is_none());
+        assert!(highlight("just some notes\n", "NOPE").is_none());
+    }
+}
@@ -0,0 +1,46 @@
+//! blobrs: browse Azure Blob Storage from the terminal.
+//!
+//! The library holds what does not need a terminal: talking to storage ([`azure`],
+//! [`listing`], [`fetch`], [`transfer`], [`sync`]), reading blobs ([`preview`],
+//! [`preview_pager`], [`archive`], [`diff`]) and the subcommands in [`cli`]. The interface
+//! itself, its state and how it is drawn, lives in the binary on top, so other tools can reuse
+//! the rest and its tests run without one.
+//!
+//! Styled text is the exception: previews are highlighted by [`highlight`] and colours come
+//! from [`theme`], both as ratatui types, so the library depends on ratatui for them though it
+//! never draws anything itself.
+
+pub mod archive;
+pub mod audit;
+pub mod azure;
+pub mod cache;
+pub mod cli;
+pub mod commands;
+pub mod config;
+pub mod credentials;
+pub mod dates;
+pub mod diff;
+pub mod edit;
+pub mod error;
+pub mod export;
+pub mod fetch;
+pub mod filter;
+pub mod fuzzy;
+pub mod highlight;
+pub mod listing;
+pub mod logging;
+pub mod open;
+pub mod pick;
+pub mod pipe;
+pub mod preview;
+pub mod preview_pager;
+pub mod search;
+pub mod secret;
+pub mod selftest;
+pub mod sync;
+pub mod tail;
+pub mod theme;
+pub mod transfer;
+pub mod usage;
+pub mod watch;
+pub mod xml;
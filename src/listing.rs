@@ -0,0 +1,315 @@
+//! The rest of a page of a large folder's listing, loaded in the background.
+//!
+//! The first request of a page is listed up front so it can be shown straight away. When the
+//! service says there is more, the rest of the page is fetched one request after another here
+//! and picked up by the app on each tick, so large folders never hold up the UI. Listing stops
+//! once the page is full, keeping the continuation token of the next page, so a folder with
+//! millions of blobs is never held in memory at once.
+
+use object_store::ListResult;
+use object_store::list::{PaginatedListOptions, PaginatedListStore};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::task::AbortHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Folder,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileItem {
+    pub display_name: String, // What to show in the UI (with icon)
+    pub actual_name: String,  // The actual file/folder name
+    pub kind: EntryKind,
+    pub size: Option<u64>,
+    pub last_modified: Option<chrono::DateTime<chrono::Utc>>,
+    pub created: Option<chrono::DateTime<chrono::Utc>>,
+    pub version_id: Option<String>, // Set for historical versions in time-travel view
+    /// Soft-deleted blob, listed while deleted blobs are shown.
+    pub deleted: bool,
+    /// Bytes actually written to a sparse page blob, loaded when sorting by size.
+    pub used_size: Option<u64>,
+}
+
+/// Pages fetched but not yet taken, and how the listing ended.
+#[derive(Debug, Default)]
+struct Progress {
+    pages: Vec<ListResult>,
+    /// Entries fetched so far, including pages already taken.
+    entries: usize,
+    complete: bool,
+    error: Option<String>,
+    /// Where the folder carries on once this page is full.
+    next_token: Option<String>,
+}
+
+/// Stops a background task once the last handle to it is gone.
+#[derive(Debug)]
+pub struct StopOnDrop(pub AbortHandle);
+
+impl Drop for StopOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// A folder listing that is still loading.
+///
+/// Dropping every clone stops the listing, so leaving the folder does not keep fetching it.
+#[derive(Debug, Clone)]
+pub struct FolderListing {
+    progress: Arc<Mutex<Progress>>,
+    _task: Arc<StopOnDrop>,
+}
+
+impl FolderListing {
+    /// List up to `limit` entries of `prefix` that follow `page_token`, at most `request_size`
+    /// per request. With no `delimiter`, every blob under `prefix` is listed rather than one
+    /// folder level.
+    pub fn start(
+        store: Arc<dyn PaginatedListStore>,
+        prefix: String,
+        delimiter: Option<&'static str>,
+        request_size: usize,
+        limit: usize,
+        page_token: String,
+    ) -> Self {
+        let progress = Arc::new(Mutex::new(Progress::default()));
+        let task = tokio::spawn({
+            let progress = progress.clone();
+            async move {
+                load(
+                    store.as_ref(),
+                    &prefix,
+                    delimiter,
+                    request_size,
+                    limit,
+                    page_token,
+                    &progress,
+                )
+                .await;
+            }
+        });
+        Self {
+            progress,
+            _task: Arc::new(StopOnDrop(task.abort_handle())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Progress> {
+        self.progress
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Pages fetched since the last call, oldest first.
+    #[must_use]
+    pub fn take_pages(&self) -> Vec<ListResult> {
+        std::mem::take(&mut self.lock().pages)
+    }
+
+    /// Entries fetched so far.
+    #[must_use]
+    pub fn entries(&self) -> usize {
+        self.lock().entries
+    }
+
+    /// Whether every page has been fetched, or the listing stopped with an error.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        let progress = self.lock();
+        progress.complete || progress.error.is_some()
+    }
+
+    /// Why the listing stopped early, if it did.
+    #[must_use]
+    pub fn error(&self) -> Option<String> {
+        self.lock().error.clone()
+    }
+
+    /// Continuation token of the next page, once this one is full and the folder has more.
+    #[must_use]
+    pub fn next_token(&self) -> Option<String> {
+        self.lock().next_token.clone()
+    }
+}
+
+/// Fetch requests until `limit` entries are listed or the service has no more, handing each
+/// to `progress` as it arrives.
+async fn load(
+    store: &dyn PaginatedListStore,
+    prefix: &str,
+    delimiter: Option<&'static str>,
+    request_size: usize,
+    limit: usize,
+    page_token: String,
+    progress: &Mutex<Progress>,
+) {
+    let mut page_token = Some(page_token);
+    let mut listed = 0;
+    while listed < limit
+        && let Some(token) = page_token.take()
+    {
+        let options = PaginatedListOptions {
+            delimiter: delimiter.map(Into::into),
+            max_keys: Some(request_size.min(limit - listed)),
+            page_token: Some(token),
+            ..PaginatedListOptions::default()
+        };
+        let result = store
+            .list_paginated((!prefix.is_empty()).then_some(prefix), options)
+            .await;
+        let mut progress = progress
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match result {
+            Ok(page) => {
+                let entries = page.result.common_prefixes.len() + page.result.objects.len();
+                listed += entries;
+                progress.entries += entries;
+                progress.pages.push(page.result);
+                page_token = page.page_token;
+            }
+            Err(e) => {
+                progress.error = Some(e.to_string());
+                return;
+            }
+        }
+    }
+    let mut progress = progress
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    progress.next_token = page_token;
+    progress.complete = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use object_store::list::PaginatedListResult;
+    use object_store::path::Path as ObjectPath;
+    use object_store::{ObjectMeta, Result};
+    use std::future::Future;
+    use std::pin::Pin;
+
+    /// Serves `pages` blobs per page, with the page number as the continuation token, and
+    /// fails on page `fail_at` if set.
+    #[derive(Debug)]
+    struct Pages {
+        pages: usize,
+        fail_at: Option<usize>,
+    }
+
+    impl PaginatedListStore for Pages {
+        fn list_paginated<'a, 'b, 'f>(
+            &'a self,
+            _prefix: Option<&'b str>,
+            opts: PaginatedListOptions,
+        ) -> Pin<Box<dyn Future<Output = Result<PaginatedListResult>> + Send + 'f>>
+        where
+            'a: 'f,
+            'b: 'f,
+            Self: 'f,
+        {
+            Box::pin(async move {
+                let page: usize = opts.page_token.as_deref().unwrap_or("0").parse().unwrap();
+                if self.fail_at == Some(page) {
+                    return Err(object_store::Error::Generic {
+                        store: "test",
+                        source: "throttled".into(),
+                    });
+                }
+                let objects = (0..opts.max_keys.unwrap_or(1))
+                    .map(|i| ObjectMeta {
+                        location: ObjectPath::from(format!("big/{page}-{i}.csv")),
+                        last_modified: Utc::now(),
+                        size: 1,
+                        e_tag: None,
+                        version: None,
+                    })
+                    .collect();
+                Ok(PaginatedListResult {
+                    result: ListResult {
+                        common_prefixes: Vec::new(),
+                        objects,
+                    },
+                    page_token: (page + 1 < self.pages).then(|| (page + 1).to_string()),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn pages_after_the_first_are_handed_over_as_they_arrive() {
+        let listing = FolderListing::start(
+            Arc::new(Pages {
+                pages: 4,
+                fail_at: None,
+            }),
+            "big/".to_string(),
+            Some("/"),
+            2,
+            100,
+            "1".to_string(),
+        );
+        while !listing.is_finished() {
+            tokio::task::yield_now().await;
+        }
+
+        let pages = listing.take_pages();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].objects[0].location.as_ref(), "big/1-0.csv");
+        assert_eq!(listing.entries(), 6);
+        assert!(listing.take_pages().is_empty());
+        assert_eq!(listing.error(), None);
+        assert_eq!(listing.next_token(), None);
+    }
+
+    #[tokio::test]
+    async fn listing_stops_once_the_page_is_full() {
+        let listing = FolderListing::start(
+            Arc::new(Pages {
+                pages: 10,
+                fail_at: None,
+            }),
+            "big/".to_string(),
+            Some("/"),
+            2,
+            3,
+            "1".to_string(),
+        );
+        while !listing.is_finished() {
+            tokio::task::yield_now().await;
+        }
+
+        // One full request, then one cut down to what is left of the page
+        let pages = listing.take_pages();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].objects.len(), 1);
+        assert_eq!(listing.entries(), 3);
+        assert_eq!(listing.next_token().as_deref(), Some("3"));
+    }
+
+    #[tokio::test]
+    async fn a_failed_page_keeps_what_was_listed() {
+        let listing = FolderListing::start(
+            Arc::new(Pages {
+                pages: 4,
+                fail_at: Some(2),
+            }),
+            String::new(),
+            Some("/"),
+            2,
+            100,
+            "1".to_string(),
+        );
+        while !listing.is_finished() {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(listing.take_pages().len(), 1);
+        assert!(listing.error().unwrap().contains("throttled"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Diagnostic log written to a file with `--verbose`.
+//!
+//! Requests, retries and errors go to `blobrs.log` in the state directory rather than the
+//! terminal, which the TUI owns. The log is rolled over to `blobrs.log.1` at startup once it
+//! grows past `MAX_LOG_BYTES`, so at most two files are kept.
+
+use crate::error::BlobrsError;
+use crate::secret;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+
+/// Size above which the log is rolled over when blobrs starts.
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default location of the log, e.g. `~/.local/state/blobrs/blobrs.log`.
+#[must_use]
+pub fn default_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("blobrs").join("blobrs.log"))
+}
+
+/// What is logged for `-v` repeated `verbose` times: nothing, then errors, retries and
+/// requests (`-v`), responses (`-vv`), and everything the HTTP stack reports (`-vvv`).
+#[must_use]
+pub const fn level(verbose: u8) -> LevelFilter {
+    match verbose {
+        0 => LevelFilter::OFF,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Start logging to the default location, unless `verbose` is 0. Returns the log's path.
+///
+/// # Errors
+///
+/// Returns an error if there is no state directory or the log cannot be opened.
+pub fn init(verbose: u8) -> Result<Option<PathBuf>, BlobrsError> {
+    let level = level(verbose);
+    if level == LevelFilter::OFF {
+        return Ok(None);
+    }
+    let path = default_path().ok_or_else(|| {
+        BlobrsError::Service("no state directory to write blobrs.log to".to_string())
+    })?;
+    let file = open(&path)?;
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(Redacting(file)))
+        .with_max_level(level)
+        .try_init()
+        .map_err(|e| BlobrsError::Service(format!("Failed to start logging: {e}")))?;
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "blobrs started");
+    Ok(Some(path))
+}
+
+/// Scrubs secrets from each formatted event before it reaches the file, including events
+/// from the HTTP stack that blobrs does not format itself.
+struct Redacting<W>(W);
+
+impl<W: Write> Write for Redacting<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // The formatter writes each event in one call, so secrets are not split across calls
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(secret::redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Open `path` for appending, rolling it over first if it is too large.
+fn open(path: &Path) -> Result<File, BlobrsError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| BlobrsError::io(format!("Failed to create {}", dir.display()), e))?;
+    }
+    roll_over(path, MAX_LOG_BYTES)?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| BlobrsError::io(format!("Failed to open {}", path.display()), e))
+}
+
+/// Move `path` to `<path>.1`, replacing an older one, if it is larger than `max_bytes`.
+fn roll_over(path: &Path, max_bytes: u64) -> Result<(), BlobrsError> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+    if metadata.len() <= max_bytes {
+        return Ok(());
+    }
+    let mut previous = path.as_os_str().to_owned();
+    previous.push(".1");
+    fs::rename(path, &previous)
+        .map_err(|e| BlobrsError::io(format!("Failed to roll over {}", path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity_raises_the_level() {
+        assert_eq!(level(0), LevelFilter::OFF);
+        assert_eq!(level(1), LevelFilter::INFO);
+        assert_eq!(level(2), LevelFilter::DEBUG);
+        assert_eq!(level(9), LevelFilter::TRACE);
+    }
+
+    #[test]
+    fn written_events_are_redacted() {
+        let mut writer = Redacting(Vec::new());
+        writer
+            .write_all(b"GET /c/a.csv?sig=abc&sv=1 failed\n")
+            .unwrap();
+        assert_eq!(writer.0, b"GET /c/a.csv?sig=[redacted]&sv=1 failed\n");
+    }
+
+    #[test]
+    fn large_logs_are_rolled_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blobrs.log");
+        let rolled = dir.path().join("blobrs.log.1");
+
+        fs::write(&path, "small").unwrap();
+        roll_over(&path, 10).unwrap();
+        assert!(path.exists());
+        assert!(!rolled.exists());
+
+        fs::write(&path, "more than ten bytes").unwrap();
+        roll_over(&path, 10).unwrap();
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(&rolled).unwrap(), "more than ten bytes");
+
+        // A missing log is not an error
+        roll_over(&path, 10).unwrap();
+    }
+}
@@ -1,30 +1,195 @@
 use crate::app::App;
+use blobrs::cli::Cli;
+use blobrs::config::Config;
+use blobrs::credentials::{Credential, UnsupportedCredential};
+use blobrs::error::BlobrsError;
+use blobrs::{cli, credentials, error, secret};
+use clap::Parser;
+use color_eyre::eyre::WrapErr;
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::cursor::Show;
+use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use std::io::Write;
+use std::process::ExitCode;
+use tokio::runtime::Runtime;
 
 pub mod app;
 pub mod event;
-pub mod preview;
+pub mod help;
+pub mod menu;
+pub mod motion;
+pub mod mouse;
+pub mod palette;
+pub mod preview_pane;
+pub mod preview_search;
+pub mod state;
+pub mod tabs;
 pub mod terminal_icons;
 pub mod ui;
 
-fn main() -> color_eyre::Result<()> {
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // The report is for people fixing their setup, not a backtrace. Errors from the
+            // HTTP stack can quote signed URLs.
+            eprintln!("blobrs: {}", secret::redact(&format!("{e:#}")));
+            ExitCode::from(error::exit_code(&e))
+        }
+    }
+}
+
+fn run() -> color_eyre::Result<()> {
     color_eyre::install()?;
+    let cli = Cli::parse();
+    blobrs::logging::init(cli.verbose)
+        .wrap_err("Could not start the --verbose log; run without it to skip logging")?;
+
+    if cli.self_test {
+        return runtime()?.block_on(blobrs::selftest::run());
+    }
+
+    if let Some(command) = cli.command {
+        // Subcommands are non-interactive, so there is no picker: they use --profile when given
+        // and the environment otherwise, and a broken config is an error rather than a warning
+        let mut config = Config::load()?;
+        let credential = match cli.profile.as_deref() {
+            Some(profile) => credentials::resolve(None, &[], &config, Some(profile))?
+                .0
+                .into_iter()
+                .next()
+                .ok_or_else(|| BlobrsError::Credentials(format!("profile {profile} not found")))?,
+            None => Credential::from_env().ok_or_else(|| {
+                BlobrsError::Credentials(
+                    credentials::env_guidance(&credentials::missing_env_vars()),
+                )
+            })?,
+        };
+        config.read_only |= cli.read_only;
+        let account = cli::Account {
+            name: &credential.account,
+            access_key: credential.access_key.expose(),
+            config: &config,
+        };
+        return runtime()?.block_on(cli::run(command, account));
+    }
+
+    // Everything that can stop blobrs from starting is checked before the terminal is taken
+    // over, so it is reported as plain text
+    let (config, config_error) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+    let unsupported = credentials::detect_unsupported();
+    let (credentials, credential_errors) = credentials::resolve(
+        Credential::from_env(),
+        &unsupported,
+        &config,
+        cli.profile.as_deref(),
+    )?;
+    let runtime = runtime()?;
+    let start = Start {
+        config,
+        config_error,
+        credentials,
+        unsupported,
+        credential_errors,
+    };
+
+    let picked = if cli.pick.is_some() {
+        // Drawn on stderr so that stdout carries only the pick, as with fzf
+        let _screen = StderrScreen::enter()?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stderr()))?;
+        run_app(&mut terminal, &runtime, &cli, start)
+    } else {
+        ratatui::run(|terminal| run_app(terminal, &runtime, &cli, start))
+    }?;
+    for line in picked {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Raw mode and the alternate screen on stderr, for `--pick`. Both are left again when this
+/// is dropped, or before a panic is reported, as [`ratatui::run`] does for stdout.
+struct StderrScreen;
+
+impl StderrScreen {
+    fn enter() -> color_eyre::Result<Self> {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_stderr();
+            hook(info);
+        }));
+        enable_raw_mode()?;
+        let screen = Self;
+        execute!(std::io::stderr(), EnterAlternateScreen)?;
+        Ok(screen)
+    }
+}
+
+impl Drop for StderrScreen {
+    fn drop(&mut self) {
+        restore_stderr();
+    }
+}
+
+/// Leave raw mode and the alternate screen on stderr and show the cursor, ignoring failures
+/// as there is nothing left to do about them.
+fn restore_stderr() {
+    let _ = disable_raw_mode();
+    let _ = execute!(std::io::stderr(), LeaveAlternateScreen, Show);
+}
+
+/// What the interface starts with.
+struct Start {
+    config: Config,
+    config_error: Option<BlobrsError>,
+    credentials: Vec<Credential>,
+    unsupported: Vec<UnsupportedCredential>,
+    credential_errors: Vec<String>,
+}
+
+/// Run the interface in `terminal` until it quits, capturing the mouse meanwhile if
+/// configured. Returns what was picked in `--pick` mode.
+fn run_app<W: Write>(
+    terminal: &mut Terminal<CrosstermBackend<W>>,
+    runtime: &Runtime,
+    cli: &Cli,
+    start: Start,
+) -> color_eyre::Result<Vec<String>> {
+    let mouse = start.config.browse.mouse;
+    if mouse {
+        execute!(terminal.backend_mut(), EnableMouseCapture)?;
+    }
+    let result = runtime.block_on(async {
+        let mut app = App::new(
+            start.config,
+            start.config_error,
+            start.credentials,
+            start.unsupported,
+            start.credential_errors,
+        )
+        .await?;
+        app.config.read_only |= cli.read_only;
+        app.pick = cli.pick;
+        app.run(terminal).await
+    });
+    if mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
+    result
+}
 
-    // Initialize Azure Storage Account credentials
-    let storage_account = std::env::var("AZURE_STORAGE_ACCOUNT")
-        .expect("AZURE_STORAGE_ACCOUNT environment variable not set");
-    let access_key = std::env::var("AZURE_STORAGE_ACCESS_KEY")
-        .expect("AZURE_STORAGE_ACCESS_KEY environment variable not set");
-
-    ratatui::run(|terminal| {
-        let runtime = tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()?;
-
-        runtime.block_on(async {
-            App::new(storage_account, access_key)
-                .await?
-                .run(terminal)
-                .await
-        })
-    })
+/// The runtime that network requests run on.
+fn runtime() -> color_eyre::Result<Runtime> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .wrap_err("Could not start the async runtime")
 }
@@ -0,0 +1,142 @@
+//! Keyboard-driven menus shown in popups.
+//!
+//! Every entry has a visible key hint that picks it directly; the arrows (or `j`/`k` when
+//! they are not hints) move the highlight and Enter picks the highlighted entry.
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+/// One choice in a [`Menu`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItem<T> {
+    /// Key that picks this entry directly.
+    pub key: char,
+    pub label: String,
+    pub value: T,
+}
+
+impl<T> MenuItem<T> {
+    pub fn new(key: char, label: impl Into<String>, value: T) -> Self {
+        Self {
+            key,
+            label: label.into(),
+            value,
+        }
+    }
+}
+
+/// A list of choices with a highlighted entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Menu<T> {
+    pub items: Vec<MenuItem<T>>,
+    pub selected: usize,
+}
+
+/// What a key press did to a [`Menu`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuOutcome<T> {
+    /// The menu is still open.
+    Pending,
+    /// An entry was picked.
+    Chosen(T),
+    /// The menu was dismissed.
+    Cancelled,
+}
+
+impl<T: Clone> Menu<T> {
+    #[must_use]
+    pub const fn new(items: Vec<MenuItem<T>>) -> Self {
+        Self { items, selected: 0 }
+    }
+
+    /// Highlight the first entry matching `predicate`, if any.
+    #[must_use]
+    pub fn with_selected(mut self, predicate: impl Fn(&T) -> bool) -> Self {
+        if let Some(index) = self.items.iter().position(|item| predicate(&item.value)) {
+            self.selected = index;
+        }
+        self
+    }
+
+    /// Apply a key press. Key hints win over the `h`/`j`/`k`/`l` navigation keys.
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> MenuOutcome<T> {
+        if let KeyCode::Char(c) = key_event.code
+            && let Some(item) = self.items.iter().find(|item| item.key == c)
+        {
+            return MenuOutcome::Chosen(item.value.clone());
+        }
+
+        let count = self.items.len();
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                MenuOutcome::Cancelled
+            }
+            KeyCode::Up | KeyCode::Char('k') if count > 0 => {
+                self.selected = (self.selected + count - 1) % count;
+                MenuOutcome::Pending
+            }
+            KeyCode::Down | KeyCode::Char('j') if count > 0 => {
+                self.selected = (self.selected + 1) % count;
+                MenuOutcome::Pending
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => self
+                .items
+                .get(self.selected)
+                .map_or(MenuOutcome::Pending, |item| {
+                    MenuOutcome::Chosen(item.value.clone())
+                }),
+            _ => MenuOutcome::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> Menu<u8> {
+        Menu::new(vec![
+            MenuItem::new('n', "Name", 1),
+            MenuItem::new('s', "Size", 2),
+            MenuItem::new('j', "Jump", 3),
+        ])
+    }
+
+    #[test]
+    fn hints_pick_directly_and_arrows_wrap() {
+        let mut menu = menu();
+        assert_eq!(
+            menu.handle_key(KeyEvent::from(KeyCode::Char('s'))),
+            MenuOutcome::Chosen(2)
+        );
+        // `j` is a hint here, so it picks instead of moving down
+        assert_eq!(
+            menu.handle_key(KeyEvent::from(KeyCode::Char('j'))),
+            MenuOutcome::Chosen(3)
+        );
+
+        assert_eq!(
+            menu.handle_key(KeyEvent::from(KeyCode::Up)),
+            MenuOutcome::Pending
+        );
+        assert_eq!(menu.selected, 2);
+        menu.handle_key(KeyEvent::from(KeyCode::Down));
+        assert_eq!(
+            menu.handle_key(KeyEvent::from(KeyCode::Enter)),
+            MenuOutcome::Chosen(1)
+        );
+        assert_eq!(
+            menu.handle_key(KeyEvent::from(KeyCode::Esc)),
+            MenuOutcome::Cancelled
+        );
+        assert_eq!(
+            menu.handle_key(KeyEvent::from(KeyCode::Char('x'))),
+            MenuOutcome::Pending
+        );
+    }
+
+    #[test]
+    fn with_selected_highlights_matching_entry() {
+        assert_eq!(menu().with_selected(|value| *value == 2).selected, 1);
+        assert_eq!(menu().with_selected(|value| *value == 9).selected, 0);
+    }
+}
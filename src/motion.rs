@@ -0,0 +1,193 @@
+//! Moving through the container and blob lists more than a row at a time.
+//!
+//! Besides the arrow keys and `j`/`k`, lists take `PageUp`/`PageDown`, `Ctrl-d`/`Ctrl-u` for
+//! half a page, `Home`/`End` and vim's `gg`/`G`, each optionally preceded by a count such as
+//! `25j` or `100G`. Motions move through the rows on screen, so they stay within a search
+//! filter.
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A way of moving the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    /// The first row, or row `count` when given.
+    First,
+    /// The last row, or row `count` when given.
+    Last,
+}
+
+impl Motion {
+    /// The motion of a key that cannot be typed into a search: paging, `Home` and `End`.
+    #[must_use]
+    pub fn of_key(key_event: KeyEvent) -> Option<Self> {
+        match key_event.code {
+            KeyCode::PageUp => Some(Self::PageUp),
+            KeyCode::PageDown => Some(Self::PageDown),
+            KeyCode::Home => Some(Self::First),
+            KeyCode::End => Some(Self::Last),
+            KeyCode::Char('u') if key_event.modifiers == KeyModifiers::CONTROL => {
+                Some(Self::HalfPageUp)
+            }
+            KeyCode::Char('d') if key_event.modifiers == KeyModifiers::CONTROL => {
+                Some(Self::HalfPageDown)
+            }
+            _ => None,
+        }
+    }
+
+    /// Where the selection ends up after moving `count` times (once if not given) from
+    /// `selected`, in a list `len` rows long of which `page` are on screen.
+    #[must_use]
+    pub fn target(self, count: Option<usize>, selected: usize, len: usize, page: usize) -> usize {
+        let Some(last) = len.checked_sub(1) else {
+            return 0;
+        };
+        let times = count.unwrap_or(1);
+        let page = page.max(1);
+        let half_page = (page / 2).max(1);
+        let row = match self {
+            Self::Up => selected.saturating_sub(times),
+            Self::Down => selected.saturating_add(times),
+            Self::PageUp => selected.saturating_sub(times.saturating_mul(page)),
+            Self::PageDown => selected.saturating_add(times.saturating_mul(page)),
+            Self::HalfPageUp => selected.saturating_sub(times.saturating_mul(half_page)),
+            Self::HalfPageDown => selected.saturating_add(times.saturating_mul(half_page)),
+            // Rows are counted from 1, as in vim
+            Self::First => count.map_or(0, |row| row.saturating_sub(1)),
+            Self::Last => count.map_or(last, |row| row.saturating_sub(1)),
+        };
+        row.min(last)
+    }
+}
+
+/// What a key did to the motion being typed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionKey {
+    /// Part of a count or `gg`; nothing moves yet.
+    Pending,
+    Move(Motion, Option<usize>),
+    /// Not a motion; any count typed before it is dropped.
+    Other,
+}
+
+/// A count or `g` typed ahead of a motion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PendingMotion {
+    count: Option<usize>,
+    g: bool,
+}
+
+impl PendingMotion {
+    /// The count typed so far, for showing while the motion is incomplete.
+    #[must_use]
+    pub const fn count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// Take a key press in a list.
+    pub fn handle(&mut self, key_event: KeyEvent) -> MotionKey {
+        let pending = std::mem::take(self);
+        let typed = key_event
+            .modifiers
+            .difference(KeyModifiers::SHIFT)
+            .is_empty();
+        let motion = match key_event.code {
+            KeyCode::Char(digit @ '0'..='9')
+                if typed && (digit != '0' || pending.count.is_some()) =>
+            {
+                let digit = usize::from(digit as u8 - b'0');
+                self.count = Some(
+                    pending
+                        .count
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(digit),
+                );
+                return MotionKey::Pending;
+            }
+            KeyCode::Char('g') if typed && !pending.g => {
+                *self = Self { g: true, ..pending };
+                return MotionKey::Pending;
+            }
+            KeyCode::Char('g') if typed => Motion::First,
+            KeyCode::Char('G') if typed => Motion::Last,
+            KeyCode::Up | KeyCode::Char('k') if typed => Motion::Up,
+            KeyCode::Down | KeyCode::Char('j') if typed => Motion::Down,
+            // Esc drops a half-typed motion rather than leaving the list
+            KeyCode::Esc if pending != Self::default() => return MotionKey::Pending,
+            _ => match Motion::of_key(key_event) {
+                Some(motion) => motion,
+                None => return MotionKey::Other,
+            },
+        };
+        MotionKey::Move(motion, pending.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(pending: &mut PendingMotion, typed: &str) -> MotionKey {
+        typed
+            .chars()
+            .map(|c| pending.handle(KeyEvent::from(KeyCode::Char(c))))
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn counts_and_gg_are_typed_ahead() {
+        let mut pending = PendingMotion::default();
+        assert_eq!(
+            keys(&mut pending, "25j"),
+            MotionKey::Move(Motion::Down, Some(25))
+        );
+        assert_eq!(keys(&mut pending, "g"), MotionKey::Pending);
+        assert_eq!(
+            keys(&mut pending, "g"),
+            MotionKey::Move(Motion::First, None)
+        );
+        assert_eq!(
+            keys(&mut pending, "10G"),
+            MotionKey::Move(Motion::Last, Some(10))
+        );
+        assert_eq!(keys(&mut pending, "3"), MotionKey::Pending);
+        assert_eq!(pending.count(), Some(3));
+        assert_eq!(
+            pending.handle(KeyEvent::from(KeyCode::Esc)),
+            MotionKey::Pending
+        );
+        assert_eq!(pending.count(), None);
+        // A leading zero is not a count, and other keys drop what was typed
+        assert_eq!(keys(&mut pending, "0"), MotionKey::Other);
+        assert_eq!(keys(&mut pending, "4x"), MotionKey::Other);
+        assert_eq!(keys(&mut pending, "j"), MotionKey::Move(Motion::Down, None));
+        assert_eq!(
+            pending.handle(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            MotionKey::Move(Motion::HalfPageDown, None)
+        );
+    }
+
+    #[test]
+    fn targets_stay_within_the_list() {
+        assert_eq!(Motion::Down.target(Some(25), 0, 100, 20), 25);
+        assert_eq!(Motion::Down.target(Some(250), 0, 100, 20), 99);
+        assert_eq!(Motion::Up.target(None, 0, 100, 20), 0);
+        assert_eq!(Motion::PageDown.target(None, 5, 100, 20), 25);
+        assert_eq!(Motion::PageUp.target(Some(2), 50, 100, 20), 10);
+        assert_eq!(Motion::HalfPageDown.target(None, 5, 100, 20), 15);
+        assert_eq!(Motion::HalfPageUp.target(None, 5, 100, 1), 4);
+        assert_eq!(Motion::First.target(None, 50, 100, 20), 0);
+        assert_eq!(Motion::First.target(Some(10), 50, 100, 20), 9);
+        assert_eq!(Motion::Last.target(None, 0, 100, 20), 99);
+        assert_eq!(Motion::Last.target(Some(1000), 0, 100, 20), 99);
+        assert_eq!(Motion::Last.target(None, 0, 0, 20), 0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Mouse support for the container and blob lists.
+//!
+//! The wheel scrolls the list, a click selects an entry, a double click opens it, and clicking
+//! a folder in the path in the list's title goes back up to it. Where the list was last drawn
+//! is kept in a [`ListLayout`] so clicks can be matched to what was under them.
+
+use ratatui::layout::{Position, Rect};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// Rows the wheel moves the selection by.
+pub const SCROLL_ROWS: usize = 3;
+
+/// Longest gap between the clicks of a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Where the container or blob list was last drawn.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListLayout {
+    /// The list's rows, inside its border.
+    pub rows: Rect,
+    /// Entry on the first row.
+    pub first: usize,
+    /// Entries in the list.
+    pub len: usize,
+    /// Each folder of the path in the title: the columns it covers and the prefix it opens.
+    pub crumbs: Vec<(Range<u16>, String)>,
+}
+
+impl ListLayout {
+    /// Entry drawn at `position`, if any.
+    #[must_use]
+    pub fn entry_at(&self, position: Position) -> Option<usize> {
+        if !self.rows.contains(position) {
+            return None;
+        }
+        let index = self.first + usize::from(position.y - self.rows.y);
+        (index < self.len).then_some(index)
+    }
+
+    /// Prefix of the folder in the title at `position`, if any.
+    #[must_use]
+    pub fn crumb_at(&self, position: Position) -> Option<&str> {
+        if position.y + 1 != self.rows.y {
+            return None;
+        }
+        self.crumbs
+            .iter()
+            .find(|(columns, _)| columns.contains(&position.x))
+            .map(|(_, prefix)| prefix.as_str())
+    }
+}
+
+/// Clickable folders of `path` (e.g. `logs/2024/`) shown as `/logs/2024` from column `x`:
+/// the leading `/` opens the container root, and each name opens that folder.
+#[must_use]
+pub fn crumbs(path: &str, x: u16) -> Vec<(Range<u16>, String)> {
+    let mut crumbs = vec![(x..x + 1, String::new())];
+    let mut start = x + 1;
+    let mut prefix = String::new();
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        #[allow(clippy::cast_possible_truncation)] // terminals are narrower than u16::MAX
+        let end = start.saturating_add(name.chars().count() as u16);
+        prefix.push_str(name);
+        prefix.push('/');
+        crumbs.push((start..end, prefix.clone()));
+        // Skip the `/` before the next name
+        start = end.saturating_add(1);
+    }
+    crumbs
+}
+
+/// The last click, for telling double clicks apart.
+#[derive(Debug, Clone, Copy)]
+pub struct LastClick {
+    at: Instant,
+    entry: usize,
+}
+
+impl LastClick {
+    #[must_use]
+    pub fn new(entry: usize) -> Self {
+        Self {
+            at: Instant::now(),
+            entry,
+        }
+    }
+
+    /// Whether a click on `entry` now completes a double click.
+    #[must_use]
+    pub fn is_double(&self, entry: usize) -> bool {
+        self.entry == entry && self.at.elapsed() <= DOUBLE_CLICK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_map_to_entries_and_folders() {
+        let layout = ListLayout {
+            rows: Rect::new(1, 1, 30, 10),
+            first: 40,
+            len: 45,
+            crumbs: crumbs("logs/2024/", 12),
+        };
+
+        assert_eq!(layout.entry_at(Position::new(5, 1)), Some(40));
+        assert_eq!(layout.entry_at(Position::new(5, 5)), Some(44));
+        // Past the last entry, or outside the list
+        assert_eq!(layout.entry_at(Position::new(5, 6)), None);
+        assert_eq!(layout.entry_at(Position::new(0, 1)), None);
+
+        // `/logs/2024` drawn from column 12
+        assert_eq!(layout.crumb_at(Position::new(12, 0)), Some(""));
+        assert_eq!(layout.crumb_at(Position::new(13, 0)), Some("logs/"));
+        assert_eq!(layout.crumb_at(Position::new(16, 0)), Some("logs/"));
+        assert_eq!(layout.crumb_at(Position::new(17, 0)), None);
+        assert_eq!(layout.crumb_at(Position::new(18, 0)), Some("logs/2024/"));
+        assert_eq!(layout.crumb_at(Position::new(18, 1)), None);
+    }
+
+    #[test]
+    fn only_quick_clicks_on_the_same_entry_are_double() {
+        let click = LastClick::new(3);
+        assert!(click.is_double(3));
+        assert!(!click.is_double(4));
+        let stale = LastClick {
+            at: Instant::now() - DOUBLE_CLICK * 2,
+            entry: 3,
+        };
+        assert!(!stale.is_double(3));
+    }
+}
@@ -0,0 +1,55 @@
+//! Opening a downloaded file with the system's default application: `xdg-open` on Linux and
+//! the BSDs, `open` on macOS and `explorer` on Windows.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The command that opens `path` with its default application.
+#[must_use]
+pub fn open_command(path: &Path) -> Command {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        // Not `cmd /C start`, as the shell would act on `&`, `|` or `^` in the file name
+        Command::new("explorer")
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(path);
+    command
+}
+
+/// Open `path` with its default application without waiting for it, keeping its output off
+/// the screen.
+///
+/// # Errors
+///
+/// Returns an error if the opener cannot be started.
+pub fn open(path: &Path) -> std::io::Result<()> {
+    let mut child = open_command(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // Reaped in the background, as the opener may wait for the application to exit
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_file_is_the_last_argument() {
+        let command = open_command(Path::new("/tmp/report.pdf"));
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args.last().copied(), Some("/tmp/report.pdf".as_ref()));
+        if cfg!(target_os = "linux") {
+            assert_eq!(command.get_program(), "xdg-open");
+        } else if cfg!(windows) {
+            assert_eq!(command.get_program(), "explorer");
+        }
+        assert_eq!(args.len(), 1);
+    }
+}
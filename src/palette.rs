@@ -0,0 +1,488 @@
+//! Command mode: every action by name, typed after `:` or picked from the `Ctrl-p` palette.
+//!
+//! Most commands stand for the key that already does the same thing, so `:download` behaves
+//! exactly like `d`; a few take arguments that no single key could, such as `:sort size
+//! reverse`, `:goto raw/2024/`, `:tier cool` or `:pipe jq .`. Names are matched fuzzily, so
+//! `:nf` finds `new-folder` and `:cp` finds `copy-path`.
+
+use crate::app::{SortCriteria, SortOrder};
+use blobrs::error::BlobrsError;
+use blobrs::fuzzy::fuzzy_match;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Where a command can be run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Anywhere,
+    /// On the container list.
+    Containers,
+    /// Inside a container.
+    Blobs,
+}
+
+/// What running a command does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// The same as pressing this key.
+    Key(KeyCode, KeyModifiers),
+    /// Sort by the criteria given, optionally reversed.
+    Sort,
+    /// Open a folder, or a container and folder from the container list.
+    Goto,
+    /// Set the access tier of the marked or selected blobs.
+    Tier,
+    /// Write the listing to a file.
+    Export,
+    /// Pipe the selected blob into a shell command.
+    Pipe,
+    /// Compare the selected blob with a local file.
+    Diff,
+}
+
+/// A named command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+    pub name: &'static str,
+    /// Arguments it takes, for the palette, e.g. `<path>`.
+    pub args: &'static str,
+    pub description: &'static str,
+    pub scope: Scope,
+    pub action: Action,
+}
+
+const fn key(
+    name: &'static str,
+    description: &'static str,
+    scope: Scope,
+    code: KeyCode,
+) -> Command {
+    Command {
+        name,
+        args: "",
+        description,
+        scope,
+        action: Action::Key(code, KeyModifiers::NONE),
+    }
+}
+
+const fn ctrl(name: &'static str, description: &'static str, c: char) -> Command {
+    Command {
+        name,
+        args: "",
+        description,
+        scope: Scope::Anywhere,
+        action: Action::Key(KeyCode::Char(c), KeyModifiers::CONTROL),
+    }
+}
+
+/// Every command, in the order the palette lists them before anything is typed.
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "goto",
+        args: "<path>",
+        description: "Open a folder (`container/path` from the container list)",
+        scope: Scope::Anywhere,
+        action: Action::Goto,
+    },
+    Command {
+        name: "sort",
+        args: "<name|modified|created|size> [reverse]",
+        description: "Sort the list",
+        scope: Scope::Anywhere,
+        action: Action::Sort,
+    },
+    Command {
+        name: "tier",
+        args: "<hot|cool|cold|archive>",
+        description: "Set the access tier of the marked or selected blobs",
+        scope: Scope::Blobs,
+        action: Action::Tier,
+    },
+    Command {
+        name: "export",
+        args: "[json|csv|table] [all] <path>",
+        description: "Write the listing shown, or with `all` every blob below, to a file",
+        scope: Scope::Anywhere,
+        action: Action::Export,
+    },
+    key("download", "Download", Scope::Blobs, KeyCode::Char('d')),
+    key(
+        "download-to",
+        "Download, choosing where to",
+        Scope::Blobs,
+        KeyCode::Char('D'),
+    ),
+    key(
+        "open-download",
+        "Open the file last downloaded with its default application",
+        Scope::Blobs,
+        KeyCode::Char('o'),
+    ),
+    key(
+        "upload",
+        "Upload a file, or undelete a deleted blob",
+        Scope::Blobs,
+        KeyCode::Char('u'),
+    ),
+    key(
+        "upload-clipboard",
+        "Upload from a path on the clipboard",
+        Scope::Blobs,
+        KeyCode::Char('U'),
+    ),
+    key(
+        "refresh",
+        "Refresh the list",
+        Scope::Anywhere,
+        KeyCode::Char('r'),
+    ),
+    key(
+        "search",
+        "Search the list",
+        Scope::Anywhere,
+        KeyCode::Char('/'),
+    ),
+    key(
+        "file-types",
+        "Show only some file types",
+        Scope::Blobs,
+        KeyCode::Char('e'),
+    ),
+    key(
+        "jump",
+        "Jump to a name as it is typed",
+        Scope::Anywhere,
+        KeyCode::Char('\''),
+    ),
+    key(
+        "info",
+        "Info or properties",
+        Scope::Anywhere,
+        KeyCode::Char('i'),
+    ),
+    key(
+        "preview",
+        "Preview the blob",
+        Scope::Blobs,
+        KeyCode::Char('p'),
+    ),
+    key(
+        "preview-pane",
+        "Show or hide the preview pane beside the list",
+        Scope::Blobs,
+        KeyCode::Char('w'),
+    ),
+    key(
+        "breakdown",
+        "Break down the folder's size by what is inside, largest first",
+        Scope::Blobs,
+        KeyCode::Char('B'),
+    ),
+    key(
+        "watch",
+        "Watch the folder: list it every few seconds and highlight new and changed blobs",
+        Scope::Blobs,
+        KeyCode::Char('W'),
+    ),
+    key(
+        "next-page",
+        "Next page of a folder too large to list at once",
+        Scope::Blobs,
+        KeyCode::Char(']'),
+    ),
+    key(
+        "previous-page",
+        "Previous page of a folder too large to list at once",
+        Scope::Blobs,
+        KeyCode::Char('['),
+    ),
+    key(
+        "follow",
+        "Follow the blob as it grows",
+        Scope::Blobs,
+        KeyCode::Char('f'),
+    ),
+    Command {
+        name: "pipe",
+        args: "[command]",
+        description: "Pipe the blob into a shell command, or $PAGER",
+        scope: Scope::Blobs,
+        action: Action::Pipe,
+    },
+    Command {
+        name: "diff",
+        args: "<local path>",
+        description: "Compare the blob with a local file (`=` compares it with the marked blob)",
+        scope: Scope::Blobs,
+        action: Action::Diff,
+    },
+    key(
+        "edit",
+        "Edit the blob in $EDITOR and upload the changes",
+        Scope::Blobs,
+        KeyCode::Char('E'),
+    ),
+    key("clone", "Clone", Scope::Blobs, KeyCode::Char('c')),
+    key("move", "Move or rename", Scope::Blobs, KeyCode::Char('R')),
+    key("delete", "Delete", Scope::Anywhere, KeyCode::Char('x')),
+    key("new-folder", "New folder", Scope::Blobs, KeyCode::Char('n')),
+    key(
+        "new-container",
+        "New container",
+        Scope::Containers,
+        KeyCode::Char('n'),
+    ),
+    key("mark", "Mark or unmark", Scope::Blobs, KeyCode::Char(' ')),
+    key(
+        "mark-all",
+        "Mark everything",
+        Scope::Blobs,
+        KeyCode::Char('a'),
+    ),
+    key(
+        "invert-marks",
+        "Invert the marks",
+        Scope::Blobs,
+        KeyCode::Char('A'),
+    ),
+    key(
+        "yank",
+        "Yank to paste elsewhere",
+        Scope::Blobs,
+        KeyCode::Char('Y'),
+    ),
+    key(
+        "paste",
+        "Paste what was yanked",
+        Scope::Blobs,
+        KeyCode::Char('P'),
+    ),
+    key(
+        "copy-path",
+        "Copy the path to the clipboard",
+        Scope::Blobs,
+        KeyCode::Char('y'),
+    ),
+    key(
+        "copy-command",
+        "Copy an az or azcopy download command",
+        Scope::Blobs,
+        KeyCode::Char('C'),
+    ),
+    key(
+        "deleted",
+        "Show or hide deleted blobs",
+        Scope::Blobs,
+        KeyCode::Char('.'),
+    ),
+    key(
+        "flatten",
+        "List every blob under this folder, without folders",
+        Scope::Blobs,
+        KeyCode::Char('v'),
+    ),
+    key(
+        "time-travel",
+        "Browse as of a point in time",
+        Scope::Blobs,
+        KeyCode::Char('T'),
+    ),
+    key(
+        "sync",
+        "Sync with a local folder",
+        Scope::Blobs,
+        KeyCode::Char('S'),
+    ),
+    key("note", "Edit the note", Scope::Anywhere, KeyCode::Char('N')),
+    key(
+        "usage",
+        "Show container sizes",
+        Scope::Containers,
+        KeyCode::Char('s'),
+    ),
+    key(
+        "rescan",
+        "Count the selected container again",
+        Scope::Containers,
+        KeyCode::Char('S'),
+    ),
+    key("up", "Up a folder", Scope::Blobs, KeyCode::Left),
+    key(
+        "containers",
+        "Back to the containers",
+        Scope::Blobs,
+        KeyCode::Backspace,
+    ),
+    key(
+        "search-all",
+        "Search every container",
+        Scope::Anywhere,
+        KeyCode::Char('F'),
+    ),
+    key(
+        "jobs",
+        "Background jobs",
+        Scope::Anywhere,
+        KeyCode::Char('J'),
+    ),
+    key(
+        "history",
+        "Activity log",
+        Scope::Anywhere,
+        KeyCode::Char('H'),
+    ),
+    ctrl(
+        "search-deep",
+        "Search everything under this folder, at any depth",
+        '/',
+    ),
+    ctrl("tab-new", "Open a tab", 't'),
+    ctrl("tab-close", "Close the tab", 'w'),
+    key(
+        "tab-next",
+        "Show the next tab",
+        Scope::Anywhere,
+        KeyCode::Tab,
+    ),
+    key(
+        "panes",
+        "One or two panes",
+        Scope::Anywhere,
+        KeyCode::Char('|'),
+    ),
+    key("help", "Every key", Scope::Anywhere, KeyCode::Char('?')),
+    key("quit", "Quit", Scope::Anywhere, KeyCode::Char('q')),
+];
+
+impl Command {
+    /// The key press this command stands for, if it stands for one.
+    #[must_use]
+    pub const fn key_event(&self) -> Option<KeyEvent> {
+        match self.action {
+            Action::Key(code, modifiers) => Some(KeyEvent::new(code, modifiers)),
+            _ => None,
+        }
+    }
+
+    /// Whether it can be run on the container list (`selecting`) or inside a container.
+    #[must_use]
+    pub fn runs_in(&self, selecting: bool) -> bool {
+        match self.scope {
+            Scope::Anywhere => true,
+            Scope::Containers => selecting,
+            Scope::Blobs => !selecting,
+        }
+    }
+}
+
+/// Commands that can run here whose names match the first word of `input`, best first.
+#[must_use]
+pub fn matching(input: &str, selecting: bool) -> Vec<&'static Command> {
+    let word = input.split_whitespace().next().unwrap_or("");
+    let mut matches: Vec<(i32, &Command)> = COMMANDS
+        .iter()
+        .filter(|command| command.runs_in(selecting))
+        .filter_map(|command| Some((fuzzy_match(command.name, word)?.score, command)))
+        .collect();
+    // Stable, so ties keep the order of `COMMANDS`
+    matches.sort_by_key(|(score, command)| (command.name != word, std::cmp::Reverse(*score)));
+    matches.into_iter().map(|(_, command)| command).collect()
+}
+
+/// The sort order in `args`, e.g. `size reverse` or `modified`.
+///
+/// # Errors
+///
+/// Returns an error naming the valid orders if `args` is not one of them.
+pub fn sort_order(args: &[&str]) -> Result<SortOrder, BlobrsError> {
+    let criteria = match args.first().map(|arg| arg.to_ascii_lowercase()).as_deref() {
+        Some("name") => SortCriteria::Name,
+        Some("modified" | "date") => SortCriteria::DateModified,
+        Some("created") => SortCriteria::DateCreated,
+        Some("size") => SortCriteria::Size,
+        _ => {
+            return Err(BlobrsError::Invalid(
+                "Sort by name, modified, created or size".to_string(),
+            ));
+        }
+    };
+    let reversed = match args.get(1).map(|arg| arg.to_ascii_lowercase()).as_deref() {
+        None => false,
+        Some("reverse" | "desc" | "rev") => true,
+        Some(other) => {
+            return Err(BlobrsError::Invalid(format!(
+                "Unknown sort option `{other}`, try `reverse`"
+            )));
+        }
+    };
+    Ok(SortOrder { criteria, reversed })
+}
+
+/// The access tier named by `arg`, as the service spells it.
+///
+/// # Errors
+///
+/// Returns an error naming the valid tiers if `arg` is not one of them.
+pub fn access_tier(arg: Option<&str>) -> Result<&'static str, BlobrsError> {
+    match arg.map(str::to_ascii_lowercase).as_deref() {
+        Some("hot") => Ok("Hot"),
+        Some("cool") => Ok("Cool"),
+        Some("cold") => Ok("Cold"),
+        Some("archive") => Ok("Archive"),
+        _ => Err(BlobrsError::Invalid(
+            "Set the tier to hot, cool, cold or archive".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(input: &str, selecting: bool) -> Vec<&'static str> {
+        matching(input, selecting)
+            .into_iter()
+            .map(|command| command.name)
+            .collect()
+    }
+
+    #[test]
+    fn names_match_fuzzily_and_exact_names_come_first() {
+        assert_eq!(names("dow", false)[0], "download");
+        assert_eq!(names("nf", false)[0], "new-folder");
+        assert_eq!(names("cp", false)[0], "copy-path");
+        assert_eq!(names("sort size", false)[0], "sort");
+        assert_eq!(names("download", false)[..2], ["download", "download-to"]);
+        // Commands that only work inside a container are not offered on the container list
+        assert!(!names("", true).contains(&"download"));
+        assert!(names("", true).contains(&"new-container"));
+        assert!(names("zzz", false).is_empty());
+        // Every name is unique
+        for command in COMMANDS {
+            assert_eq!(
+                COMMANDS.iter().filter(|c| c.name == command.name).count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn arguments_are_checked() {
+        assert_eq!(
+            sort_order(&["size", "reverse"]).unwrap(),
+            SortOrder {
+                criteria: SortCriteria::Size,
+                reversed: true
+            }
+        );
+        assert_eq!(
+            sort_order(&["Modified"]).unwrap(),
+            SortOrder::new(SortCriteria::DateModified)
+        );
+        assert!(sort_order(&[]).is_err());
+        assert!(sort_order(&["size", "sideways"]).is_err());
+        assert_eq!(access_tier(Some("COOL")).unwrap(), "Cool");
+        assert!(access_tier(Some("lukewarm")).is_err());
+        assert!(access_tier(None).is_err());
+    }
+}
@@ -0,0 +1,43 @@
+//! `--pick`: blobrs as an interactive picker for shell scripts, the way fzf is. Enter on a blob
+//! quits and prints it, or every marked blob, on stdout. The interface is drawn on stderr
+//! meanwhile, so `$(blobrs --pick)` captures only what was picked.
+
+use crate::commands::blob_url;
+
+/// How a picked blob is printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum PickOutput {
+    /// `container/path`, as the subcommands take it.
+    #[default]
+    Path,
+    /// `https://account.blob.core.windows.net/container/path`.
+    Url,
+}
+
+impl PickOutput {
+    /// The line printed for the blob at `path` in `container`.
+    #[must_use]
+    pub fn line(self, account: &str, container: &str, path: &str) -> String {
+        match self {
+            Self::Path => format!("{container}/{path}"),
+            Self::Url => blob_url(account, container, path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_print_as_paths_or_urls() {
+        assert_eq!(
+            PickOutput::Path.line("acct", "raw", "2024/a b.csv"),
+            "raw/2024/a b.csv"
+        );
+        assert_eq!(
+            PickOutput::Url.line("acct", "raw", "2024/a b.csv"),
+            "https://acct.blob.core.windows.net/raw/2024/a%20b.csv"
+        );
+    }
+}
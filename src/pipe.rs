@@ -0,0 +1,148 @@
+//! Streaming a blob's bytes out without a temporary file: to stdout for `blobrs cat`, or from
+//! the interface into a shell command such as `less` or `jq . | less`, with the interface
+//! suspended while it runs.
+//!
+//! A reader that stops early, as `head` does, is not an error: the stream just ends there.
+
+use crate::error::BlobrsError;
+use futures::TryStreamExt;
+use object_store::{ObjectStore, ObjectStoreExt, path::Path as ObjectPath};
+use std::io::ErrorKind;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A blob to pipe into a command once the terminal is free.
+#[derive(Debug, Clone)]
+pub struct PipeRequest {
+    pub object_store: Arc<dyn ObjectStore>,
+    pub path: ObjectPath,
+    pub command: String,
+}
+
+/// The command blobs are piped to when none is given: `$PAGER`, or else `less`.
+#[must_use]
+pub fn default_command() -> String {
+    std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string())
+}
+
+/// Stream the blob at `path` to `out` as it downloads. Returns the bytes written, fewer than
+/// the blob's size if `out` was closed early.
+///
+/// # Errors
+///
+/// Returns an error if the blob cannot be read or writing fails for another reason.
+pub async fn stream_to(
+    store: &dyn ObjectStore,
+    path: &ObjectPath,
+    out: &mut (impl AsyncWrite + Unpin),
+) -> Result<u64, BlobrsError> {
+    let mut chunks = store.get(path).await?.into_stream();
+    let mut written = 0;
+    while let Some(chunk) = chunks.try_next().await? {
+        match out.write_all(&chunk).await {
+            Ok(()) => written += chunk.len() as u64,
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(written),
+            Err(e) => return Err(BlobrsError::io("Failed to write the blob", e)),
+        }
+    }
+    match out.flush().await {
+        Err(e) if e.kind() != ErrorKind::BrokenPipe => {
+            Err(BlobrsError::io("Failed to write the blob", e))
+        }
+        _ => Ok(written),
+    }
+}
+
+/// Run `command` in the shell with the blob at `path` on its stdin, waiting for it to exit.
+///
+/// # Errors
+///
+/// Returns an error if the command cannot be started or the blob cannot be read.
+pub async fn pipe_to(
+    store: &dyn ObjectStore,
+    path: &ObjectPath,
+    command: &str,
+) -> Result<ExitStatus, BlobrsError> {
+    let started = |e| BlobrsError::io(format!("Could not run `{command}`"), e);
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(started)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let streamed = stream_to(store, path, &mut stdin).await;
+        // Closing stdin lets the command see the end of the blob
+        drop(stdin);
+        if let Err(e) = streamed {
+            let _ = child.kill().await;
+            return Err(e);
+        }
+    }
+    child.wait().await.map_err(started)
+}
+
+/// `command` run by the shell, so it can hold pipes and arguments.
+fn shell(command: &str) -> tokio::process::Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn store_with(path: &str, content: &[u8]) -> Arc<dyn ObjectStore> {
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(&ObjectPath::from(path), content.to_vec().into())
+            .await
+            .unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn blobs_stream_into_a_writer() {
+        let store = store_with("logs/app.log", b"one\ntwo\n").await;
+        let mut out = Vec::new();
+        let written = stream_to(store.as_ref(), &ObjectPath::from("logs/app.log"), &mut out)
+            .await
+            .unwrap();
+        assert_eq!(written, 8);
+        assert_eq!(out, b"one\ntwo\n");
+
+        let missing = stream_to(store.as_ref(), &ObjectPath::from("nope"), &mut out).await;
+        assert!(matches!(missing, Err(BlobrsError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn blobs_are_piped_to_shell_commands() {
+        if !cfg!(unix) {
+            return;
+        }
+        let store = store_with("logs/app.log", b"one\ntwo\n").await;
+        let path = ObjectPath::from("logs/app.log");
+        let status = pipe_to(store.as_ref(), &path, "grep -q two").await.unwrap();
+        assert!(status.success());
+        let status = pipe_to(store.as_ref(), &path, "grep -q three")
+            .await
+            .unwrap();
+        assert!(!status.success());
+        // A command that stops reading early is not an error
+        let status = pipe_to(store.as_ref(), &path, "head -c 1 > /dev/null")
+            .await
+            .unwrap();
+        assert!(status.success());
+    }
+}
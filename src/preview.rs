@@ -1,16 +1,25 @@
 //! Preview module for displaying CSV, TSV, JSON, Parquet, and text file contents.
 
-use std::io::Cursor;
+use std::borrow::Cow;
+use std::io::{Cursor, Read};
+use std::ops::Range;
 
-use bytes::Bytes;
+use bytes::{Buf, Bytes};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
-use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::errors::ParquetError;
+use parquet::file::reader::{ChunkReader, FileReader, Length, SerializedFileReader};
+use ratatui::text::Line;
+
+use crate::archive::ArchiveKind;
+use crate::highlight::highlight;
 
 /// Maximum bytes to download for preview (50KB)
 pub const MAX_PREVIEW_BYTES: usize = 50 * 1024;
 
-/// Maximum bytes to download for Parquet preview (1MB - parquet is compressed)
-pub const MAX_PARQUET_PREVIEW_BYTES: usize = 1024 * 1024;
+/// Bytes read from the end of a Parquet file for its footer (64KB); a larger footer takes a
+/// second read for the rest
+pub const PARQUET_FOOTER_BYTES: usize = 64 * 1024;
 
 /// Maximum bytes to download for full Parquet table preview (16MB)
 pub const MAX_PARQUET_TABLE_PREVIEW_BYTES: usize = 16 * 1024 * 1024;
@@ -18,13 +27,27 @@ pub const MAX_PARQUET_TABLE_PREVIEW_BYTES: usize = 16 * 1024 * 1024;
 /// Maximum rows to display in table preview
 pub const MAX_PREVIEW_ROWS: usize = 100;
 
+/// Lines shown of each pretty-printed JSON Lines record before the rest is left out
+pub const MAX_RECORD_LINES: usize = 20;
+
+/// Bytes of a compressed file to download for preview (256KB), which most data decompresses
+/// to well past `MAX_PREVIEW_BYTES`
+pub const MAX_COMPRESSED_PREVIEW_BYTES: usize = 256 * 1024;
+
+/// Bytes shown in a hex dump of a binary file (4KB), enough for its magic number and header
+pub const HEX_PREVIEW_BYTES: usize = 4 * 1024;
+
 /// Supported file types for preview
 #[derive(Debug, Clone, PartialEq)]
 pub enum PreviewFileType {
     Csv,
     Tsv,
     Json,
+    /// JSON Lines: one JSON value per line
+    JsonLines,
     Parquet,
+    /// A zip or tar, listed by its entries
+    Archive,
     /// Generic text file with extension name (e.g., "MD", "PY", "TXT")
     Text(String),
     Unsupported,
@@ -133,10 +156,14 @@ impl PreviewFileType {
             Self::Csv
         } else if lower.ends_with(".tsv") || lower.ends_with(".tab") {
             Self::Tsv
-        } else if lower.ends_with(".json") || lower.ends_with(".jsonl") {
+        } else if lower.ends_with(".json") {
             Self::Json
+        } else if lower.ends_with(".jsonl") || lower.ends_with(".ndjson") {
+            Self::JsonLines
         } else if lower.ends_with(".parquet") || lower.ends_with(".pq") {
             Self::Parquet
+        } else if ArchiveKind::of(&lower).is_some() {
+            Self::Archive
         } else if let Some(ext) = Self::extract_extension(&lower) {
             if TEXT_EXTENSIONS.contains(&ext.as_str()) {
                 Self::Text(ext.to_uppercase())
@@ -189,12 +216,6 @@ impl PreviewFileType {
         None
     }
 
-    /// Check if this file type is supported for preview
-    #[must_use]
-    pub fn is_supported(&self) -> bool {
-        !matches!(self, Self::Unsupported)
-    }
-
     /// Get the display name for this file type
     #[must_use]
     pub fn display_name(&self) -> String {
@@ -202,9 +223,11 @@ impl PreviewFileType {
             Self::Csv => "CSV".to_string(),
             Self::Tsv => "TSV".to_string(),
             Self::Json => "JSON".to_string(),
+            Self::JsonLines => "JSONL".to_string(),
             Self::Parquet => "Parquet".to_string(),
+            Self::Archive => "Archive".to_string(),
             Self::Text(ext) => ext.clone(),
-            Self::Unsupported => "Unsupported".to_string(),
+            Self::Unsupported => "File".to_string(),
         }
     }
 }
@@ -220,6 +243,54 @@ pub enum PreviewData {
     Text(TextPreview),
     /// Parquet schema view
     ParquetSchema(ParquetSchemaPreview),
+    /// Hex dump of a binary file
+    Hex(HexPreview),
+}
+
+/// What of the preview to copy to the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewCopy {
+    /// The table cell in the leftmost column shown, or the line
+    Cell,
+    /// The table row, or the line
+    Row,
+    /// Everything loaded
+    All,
+}
+
+impl PreviewData {
+    /// The text to copy for `what`, with the preview on `row` and a table scrolled sideways
+    /// to `column`. Table rows are copied as tab-separated values, to paste into a
+    /// spreadsheet. `None` when there is nothing there.
+    #[must_use]
+    pub fn copy_text(&self, what: PreviewCopy, row: usize, column: usize) -> Option<String> {
+        let content = match self {
+            Self::Table(table) => {
+                let tsv = |cells: &[String]| cells.join("\t");
+                return match what {
+                    PreviewCopy::Cell => table.rows.get(row)?.get(column).cloned(),
+                    PreviewCopy::Row => table.rows.get(row).map(|cells| tsv(cells)),
+                    PreviewCopy::All => Some(
+                        std::iter::once(&table.headers)
+                            .filter(|headers| !headers.is_empty())
+                            .chain(&table.rows)
+                            .map(|cells| tsv(cells))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ),
+                };
+            }
+            // The schema's lines are drawn around its fields, so it is copied whole
+            Self::ParquetSchema(schema) => return Some(schema.fields.join("\n")),
+            Self::Json(json) => &json.content,
+            Self::Text(text) => &text.content,
+            Self::Hex(hex) => &hex.content,
+        };
+        match what {
+            PreviewCopy::Cell | PreviewCopy::Row => content.lines().nth(row).map(str::to_string),
+            PreviewCopy::All => Some(content.clone()),
+        }
+    }
 }
 
 /// Tabular preview data
@@ -237,6 +308,27 @@ pub struct TablePreview {
     pub truncated: bool,
     /// File type that produced this table
     pub file_type: PreviewFileType,
+    /// Delimiter found in a CSV or TSV file when it is not the one its extension suggests
+    pub delimiter: Option<char>,
+}
+
+impl TablePreview {
+    /// Which columns hold only numbers, ignoring empty cells, to be right-aligned.
+    #[must_use]
+    pub fn numeric_columns(&self) -> Vec<bool> {
+        let width = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        (0..width)
+            .map(|column| {
+                let mut cells = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .filter(|cell| !cell.trim().is_empty())
+                    .peekable();
+                cells.peek().is_some() && cells.all(|cell| is_number(cell))
+            })
+            .collect()
+    }
 }
 
 /// JSON preview data (for non-tabular JSON)
@@ -250,6 +342,19 @@ pub struct JsonPreview {
     pub total_lines: usize,
     /// Whether this is raw content (parsing failed, likely due to truncation)
     pub is_raw: bool,
+    /// Records of a JSON Lines file, each pretty-printed in turn
+    pub records: Option<usize>,
+}
+
+/// Hex dump preview data (for binary files)
+#[derive(Debug, Clone)]
+pub struct HexPreview {
+    /// One line per 16 bytes: the offset, the bytes in hex, then as ASCII
+    pub content: String,
+    /// Bytes dumped
+    pub len: usize,
+    /// Whether the file goes on past the bytes dumped
+    pub truncated: bool,
 }
 
 /// Text preview data (for generic text files)
@@ -263,6 +368,8 @@ pub struct TextPreview {
     pub total_lines: usize,
     /// File extension (for display purposes)
     pub extension: String,
+    /// The content's lines with syntax highlighting, when its extension names a language
+    pub highlighted: Option<Vec<Line<'static>>>,
 }
 
 /// Parquet schema preview data
@@ -294,35 +401,29 @@ pub fn parse_tsv(data: &[u8]) -> Result<PreviewData, String> {
     parse_delimited(data, b'\t', PreviewFileType::Tsv)
 }
 
-/// Parse delimited data (CSV or TSV)
+/// Parse delimited data (CSV or TSV), with the delimiter and whether the first row is a
+/// header sniffed from the data rather than taken from the extension.
 fn parse_delimited(
     data: &[u8],
-    delimiter: u8,
+    default_delimiter: u8,
     file_type: PreviewFileType,
 ) -> Result<PreviewData, String> {
+    let delimiter = sniff_delimiter(data, default_delimiter);
     let cursor = Cursor::new(data);
     let mut reader = csv::ReaderBuilder::new()
         .delimiter(delimiter)
         .flexible(true) // Allow varying number of fields
-        .has_headers(true)
+        .has_headers(false)
         .from_reader(cursor);
 
-    // Get headers
-    let headers: Vec<String> = reader
-        .headers()
-        .map_err(|e| format!("Failed to read headers: {e}"))?
-        .iter()
-        .map(String::from)
-        .collect();
-
-    // Read rows
+    // Read rows, one more than shown as the first may be the header
     let mut rows = Vec::new();
     let mut total_rows = 0;
     let mut truncated = false;
 
     for result in reader.records() {
         total_rows += 1;
-        if rows.len() >= MAX_PREVIEW_ROWS {
+        if rows.len() > MAX_PREVIEW_ROWS {
             truncated = true;
             continue; // Count remaining rows but don't store them
         }
@@ -343,6 +444,22 @@ fn parse_delimited(
         }
     }
 
+    let headers = if rows.is_empty() {
+        Vec::new()
+    } else if looks_like_header(&rows) {
+        total_rows -= 1;
+        rows.remove(0)
+    } else {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        (1..=width)
+            .map(|column| format!("Column {column}"))
+            .collect()
+    };
+    if rows.len() > MAX_PREVIEW_ROWS {
+        rows.truncate(MAX_PREVIEW_ROWS);
+        truncated = true;
+    }
+
     Ok(PreviewData::Table(TablePreview {
         headers,
         column_types: None,
@@ -350,9 +467,86 @@ fn parse_delimited(
         total_rows,
         truncated,
         file_type,
+        delimiter: (delimiter != default_delimiter).then_some(char::from(delimiter)),
     }))
 }
 
+/// The delimiter of delimited data: of comma, semicolon, pipe and tab, the one splitting its
+/// first lines into the same number of fields, the most fields if several do. `default`
+/// wins a tie, and is kept when none splits the lines evenly.
+fn sniff_delimiter(data: &[u8], default: u8) -> u8 {
+    const SNIFF_LINES: usize = 20;
+    let mut candidates = vec![default];
+    candidates.extend(
+        [b',', b';', b'|', b'\t']
+            .into_iter()
+            .filter(|&c| c != default),
+    );
+
+    // Only whole lines, as the fetched data likely ends partway through one
+    let mut lines: Vec<&[u8]> = data.split(|&byte| byte == b'\n').collect();
+    if !data.ends_with(b"\n") && lines.len() > 1 {
+        lines.pop();
+    }
+    let lines: Vec<&[u8]> = lines
+        .into_iter()
+        .filter(|line| !line.trim_ascii().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+
+    candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let mut counts = lines.iter().map(|line| {
+                // Delimiters inside quotes are part of a field
+                let mut quoted = false;
+                line.iter()
+                    .filter(|&&byte| {
+                        if byte == b'"' {
+                            quoted = !quoted;
+                        }
+                        byte == candidate && !quoted
+                    })
+                    .count()
+            });
+            let first = counts.next()?;
+            (first > 0 && counts.all(|count| count == first)).then_some((candidate, first))
+        })
+        .fold(
+            None,
+            |best: Option<(u8, usize)>, (candidate, count)| match best {
+                Some((_, best_count)) if best_count >= count => best,
+                _ => Some((candidate, count)),
+            },
+        )
+        .map_or(default, |(candidate, _)| candidate)
+}
+
+/// Whether the first of `rows` is a header: it is unless it holds numbers in the columns
+/// that are otherwise all numbers, as a header names them instead.
+fn looks_like_header(rows: &[Vec<String>]) -> bool {
+    let Some((first, rest)) = rows.split_first() else {
+        return false;
+    };
+    let mut votes = 0;
+    for (column, cell) in first.iter().enumerate() {
+        let mut below = rest
+            .iter()
+            .filter_map(|row| row.get(column))
+            .filter(|cell| !cell.trim().is_empty())
+            .peekable();
+        if below.peek().is_some() && below.all(|cell| is_number(cell)) {
+            votes += if is_number(cell) { -1 } else { 1 };
+        }
+    }
+    // With nothing to go on, the first row is taken as the header as most files have one
+    votes >= 0
+}
+
+fn is_number(cell: &str) -> bool {
+    cell.trim().parse::<f64>().is_ok()
+}
+
 /// Parse JSON data from bytes
 #[allow(clippy::missing_errors_doc)]
 pub fn parse_json(data: &[u8]) -> Result<PreviewData, String> {
@@ -400,6 +594,7 @@ pub fn parse_json(data: &[u8]) -> Result<PreviewData, String> {
             truncated,
             total_lines,
             is_raw: false,
+            records: None,
         }))
     } else {
         // JSON parsing failed (likely truncated) - show raw content
@@ -421,10 +616,89 @@ pub fn parse_json(data: &[u8]) -> Result<PreviewData, String> {
             truncated,
             total_lines,
             is_raw: true,
+            records: None,
         }))
     }
 }
 
+/// Parse JSON Lines, one value per line: a table when every line is an object with the same
+/// keys, and otherwise each value pretty-printed in turn, long ones cut short.
+#[allow(clippy::missing_errors_doc)]
+pub fn parse_json_lines(data: &[u8]) -> Result<PreviewData, String> {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text.to_string(),
+        Err(_) => make_valid_utf8(data),
+    };
+    let mut lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    // Only the start of the file is fetched, so its last line is likely cut off
+    let cut_off = data.len() >= MAX_PREVIEW_BYTES;
+    if cut_off && !text.ends_with('\n') {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return parse_json(data);
+    }
+    let values: Vec<Option<serde_json::Value>> = lines
+        .iter()
+        .map(|line| serde_json::from_str(line).ok())
+        .collect();
+
+    let keys = |value: &Option<serde_json::Value>| -> Option<Vec<String>> {
+        let mut keys: Vec<String> = value.as_ref()?.as_object()?.keys().cloned().collect();
+        keys.sort();
+        Some(keys)
+    };
+    let first_keys = keys(&values[0]);
+    let uniform = first_keys.as_ref().is_some_and(|first| !first.is_empty())
+        && values.iter().all(|value| keys(value) == first_keys);
+    if uniform {
+        let objects: Vec<serde_json::Value> = values.iter().flatten().cloned().collect();
+        if let Some(mut table) = try_json_array_as_table(&objects) {
+            table.truncated |= cut_off;
+            table.file_type = PreviewFileType::JsonLines;
+            return Ok(PreviewData::Table(table));
+        }
+    }
+
+    // Lines that are not JSON are shown as they are
+    let mut content: Vec<String> = Vec::new();
+    let mut truncated = cut_off;
+    for (line, value) in lines.iter().zip(&values) {
+        if content.len() >= MAX_PREVIEW_ROWS * 2 {
+            truncated = true;
+            break;
+        }
+        let record = value
+            .as_ref()
+            .and_then(|value| serde_json::to_string_pretty(value).ok())
+            .unwrap_or_else(|| (*line).to_string());
+        let record_lines = record.lines().count();
+        content.extend(
+            record
+                .lines()
+                .take(MAX_RECORD_LINES)
+                .map(ToString::to_string),
+        );
+        if record_lines > MAX_RECORD_LINES {
+            content.push(format!(
+                "  … {} more lines",
+                record_lines - MAX_RECORD_LINES
+            ));
+        }
+    }
+
+    Ok(PreviewData::Json(JsonPreview {
+        total_lines: content.len(),
+        content: content.join("\n"),
+        truncated,
+        is_raw: false,
+        records: Some(lines.len()),
+    }))
+}
+
 /// Convert bytes to valid UTF-8 by trimming invalid bytes from the end
 fn make_valid_utf8(data: &[u8]) -> String {
     // Try progressively shorter slices until we get valid UTF-8
@@ -442,7 +716,7 @@ fn make_valid_utf8(data: &[u8]) -> String {
 }
 
 /// Check if data is likely a binary file (not text)
-fn is_likely_binary(data: &[u8]) -> bool {
+pub(crate) fn is_likely_binary(data: &[u8]) -> bool {
     // Check first few KB for binary indicators
     let check_len = data.len().min(8192);
     let sample = &data[..check_len];
@@ -518,6 +792,7 @@ fn try_json_array_as_table(arr: &[serde_json::Value]) -> Option<TablePreview> {
         total_rows,
         truncated,
         file_type: PreviewFileType::Json,
+        delimiter: None,
     })
 }
 
@@ -559,27 +834,149 @@ fn value_to_string(value: &serde_json::Value) -> String {
     }
 }
 
+/// How a file is compressed, going by its name: `events.json.gz` is gzipped JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// The compression `name` ends in, and the name of the file inside it.
+    #[must_use]
+    pub fn of(name: &str) -> Option<(Self, &str)> {
+        let (inner, extension) = name.rsplit_once('.')?;
+        let compression = match extension.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Self::Gzip,
+            "zst" | "zstd" => Self::Zstd,
+            "bz2" => Self::Bzip2,
+            _ => return None,
+        };
+        Some((compression, inner))
+    }
+
+    /// A reader decompressing `data`.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn decoder(self, data: &[u8]) -> Result<Box<dyn Read + '_>, String> {
+        Ok(match self {
+            Self::Gzip => Box::new(flate2::read::MultiGzDecoder::new(data)),
+            Self::Zstd => Box::new(
+                zstd::stream::read::Decoder::new(data)
+                    .map_err(|e| format!("Failed to decompress {self} data: {e}"))?,
+            ),
+            Self::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(data)),
+        })
+    }
+
+    /// Decompress the start of a file, up to `MAX_PREVIEW_BYTES`. Only the start is usually
+    /// fetched, so a stream cut off partway keeps what decompressed before the cut.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut decompressed = Vec::new();
+        match self
+            .decoder(data)?
+            .take(MAX_PREVIEW_BYTES as u64)
+            .read_to_end(&mut decompressed)
+        {
+            Err(e) if decompressed.is_empty() => {
+                Err(format!("Failed to decompress {self} data: {e}"))
+            }
+            _ => Ok(decompressed),
+        }
+    }
+}
+
+impl std::fmt::Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Bzip2 => "bzip2",
+        })
+    }
+}
+
+/// Parse the start of a file as `file_type`, decompressing it first if it is compressed.
+#[allow(clippy::missing_errors_doc)]
+pub fn parse_compressed_preview(
+    data: &[u8],
+    compression: Option<Compression>,
+    file_type: &PreviewFileType,
+) -> Result<PreviewData, String> {
+    match compression {
+        Some(compression) => parse_preview(&compression.decompress(data)?, file_type),
+        None => parse_preview(data, file_type),
+    }
+}
+
 /// Parse data based on file type
 #[allow(clippy::missing_errors_doc)]
 pub fn parse_preview(data: &[u8], file_type: &PreviewFileType) -> Result<PreviewData, String> {
+    // Text in another encoding is decoded to UTF-8 first; binary data is left as it is for a
+    // hex dump
+    let text = || {
+        if Encoding::for_bom(data).is_some() || !is_likely_binary(data) {
+            decode_text(data)
+        } else {
+            Cow::Borrowed(data)
+        }
+    };
     match file_type {
-        PreviewFileType::Csv => parse_csv(data),
-        PreviewFileType::Tsv => parse_tsv(data),
-        PreviewFileType::Json => parse_json(data),
+        PreviewFileType::Csv => parse_csv(&text()),
+        PreviewFileType::Tsv => parse_tsv(&text()),
+        PreviewFileType::Json => parse_json(&text()),
+        PreviewFileType::JsonLines => parse_json_lines(&text()),
         PreviewFileType::Parquet => parse_parquet_schema(data, None),
-        PreviewFileType::Text(ext) => parse_text(data, ext),
+        // Archives are listed from their directory or headers, so their bytes are all there is
+        PreviewFileType::Archive => Ok(parse_hex(data)),
+        PreviewFileType::Text(ext) => parse_text(&text(), ext),
         PreviewFileType::Unsupported => {
-            // Last resort: try to detect if it's valid UTF-8 text
-            let detected = PreviewFileType::detect_from_content(data);
+            // Last resort: try to detect if it's text
+            let text = text();
+            let detected = PreviewFileType::detect_from_content(&text);
             if let PreviewFileType::Text(ext) = detected {
-                parse_text(data, &ext)
+                parse_text(&text, &ext)
             } else {
-                Err("Cannot preview binary file".to_string())
+                Ok(parse_hex(data))
             }
         }
     }
 }
 
+/// The encoding text is written in, and the length of its byte order mark: UTF-8 or UTF-16
+/// going by the mark, UTF-8 when it is valid as such, and otherwise Windows-1252, the
+/// superset of Latin-1 most legacy exports are in.
+#[must_use]
+pub fn text_encoding(data: &[u8]) -> (&'static Encoding, usize) {
+    if let Some(found) = Encoding::for_bom(data) {
+        return found;
+    }
+    match std::str::from_utf8(data) {
+        // Cut off partway through a character is still UTF-8
+        Ok(_) => (UTF_8, 0),
+        Err(e) if e.error_len().is_none() => (UTF_8, 0),
+        Err(_) => (WINDOWS_1252, 0),
+    }
+}
+
+/// Text as UTF-8, decoded from the encoding it is written in, without its byte order mark.
+/// A character cut off at the end of the fetched bytes is left out.
+#[must_use]
+pub fn decode_text(data: &[u8]) -> Cow<'_, [u8]> {
+    let (encoding, bom) = text_encoding(data);
+    let data = &data[bom..];
+    if encoding == UTF_8 {
+        return match std::str::from_utf8(data) {
+            Ok(_) => Cow::Borrowed(data),
+            Err(e) if e.error_len().is_none() => Cow::Borrowed(&data[..e.valid_up_to()]),
+            Err(_) => Cow::Owned(String::from_utf8_lossy(data).into_owned().into_bytes()),
+        };
+    }
+    let (text, _) = encoding.decode_without_bom_handling(data);
+    Cow::Owned(text.trim_end_matches('\u{FFFD}').as_bytes().to_vec())
+}
+
 /// Parse text data from bytes
 #[allow(clippy::missing_errors_doc)]
 pub fn parse_text(data: &[u8], extension: &str) -> Result<PreviewData, String> {
@@ -590,7 +987,7 @@ pub fn parse_text(data: &[u8], extension: &str) -> Result<PreviewData, String> {
         // Try to salvage valid UTF-8 by trimming from the end
         let valid_text = make_valid_utf8(data);
         if valid_text.is_empty() {
-            return Err("Cannot preview binary file".to_string());
+            return Ok(parse_hex(data));
         }
         valid_text
     };
@@ -610,6 +1007,7 @@ pub fn parse_text(data: &[u8], extension: &str) -> Result<PreviewData, String> {
     };
 
     Ok(PreviewData::Text(TextPreview {
+        highlighted: highlight(&content, extension),
         content,
         truncated,
         total_lines,
@@ -617,6 +1015,58 @@ pub fn parse_text(data: &[u8], extension: &str) -> Result<PreviewData, String> {
     }))
 }
 
+/// Dump the start of a binary file in hex, as `hexdump -C` does: each line the offset, 16
+/// bytes in hex in two groups of eight, then those bytes as ASCII with `.` for the rest.
+#[must_use]
+pub fn parse_hex(data: &[u8]) -> PreviewData {
+    // Offset, a space, then each group of eight with a space before it and after each byte
+    const HEX_WIDTH: usize = 8 + 1 + 2 * (1 + 8 * 3);
+    let shown = &data[..data.len().min(HEX_PREVIEW_BYTES)];
+    let content = shown
+        .chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let mut line = format!("{:08x} ", row * 16);
+            for (index, byte) in chunk.iter().enumerate() {
+                if index % 8 == 0 {
+                    line.push(' ');
+                }
+                line.push_str(&format!("{byte:02x} "));
+            }
+            line.push_str(&" ".repeat(HEX_WIDTH - line.len()));
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        char::from(byte)
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{line} |{ascii}|")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    PreviewData::Hex(HexPreview {
+        content,
+        len: shown.len(),
+        truncated: data.len() > shown.len(),
+    })
+}
+
+/// Length of the footer ending `tail`, the last bytes of a Parquet file: its metadata, then
+/// the metadata's length and the `PAR1` magic in 8 bytes.
+#[allow(clippy::missing_errors_doc)]
+pub fn parquet_footer_len(tail: &[u8]) -> Result<u64, String> {
+    match tail.len().checked_sub(8).map(|start| &tail[start..]) {
+        Some([a, b, c, d, b'P', b'A', b'R', b'1']) => {
+            Ok(u64::from(u32::from_le_bytes([*a, *b, *c, *d])) + 8)
+        }
+        _ => Err("Failed to read Parquet file: no Parquet footer at the end".to_string()),
+    }
+}
+
 /// Parse Parquet schema from footer bytes
 ///
 /// This reads only the Parquet footer to extract schema and metadata.
@@ -665,12 +1115,131 @@ pub fn parse_parquet_schema(data: &[u8], file_size: Option<u64>) -> Result<Previ
 /// Parse Parquet data as a table preview.
 #[allow(clippy::missing_errors_doc)]
 pub fn parse_parquet_table(data: &[u8]) -> Result<PreviewData, String> {
-    let bytes = Bytes::copy_from_slice(data);
-    let mut builder = ParquetRecordBatchReaderBuilder::try_new(bytes)
+    parquet_table(Bytes::copy_from_slice(data), None)
+}
+
+/// The leading row groups of a Parquet file holding its first [`MAX_PREVIEW_ROWS`] rows, from
+/// the file's `footer`: how many there are and the bytes they take, to read them for a table
+/// preview without the rest of the file.
+///
+/// # Errors
+///
+/// Returns an error if the footer cannot be read, or the rows take more than
+/// [`MAX_PARQUET_TABLE_PREVIEW_BYTES`].
+pub fn parquet_rows_range(footer: &[u8]) -> Result<(usize, Range<u64>), String> {
+    let reader = SerializedFileReader::new(Bytes::copy_from_slice(footer))
+        .map_err(|e| format!("Failed to read Parquet file: {e}"))?;
+    let mut row_groups = 0;
+    let mut rows = 0;
+    let mut range: Option<Range<u64>> = None;
+    for row_group in reader.metadata().row_groups() {
+        for column in row_group.columns() {
+            let start = column
+                .dictionary_page_offset()
+                .unwrap_or_else(|| column.data_page_offset());
+            let (Ok(start), Ok(len)) = (
+                u64::try_from(start),
+                u64::try_from(column.compressed_size()),
+            ) else {
+                return Err("Failed to read Parquet file: negative column offset".to_string());
+            };
+            range = Some(match range {
+                Some(range) => range.start.min(start)..range.end.max(start + len),
+                None => start..start + len,
+            });
+        }
+        row_groups += 1;
+        rows += row_group.num_rows();
+        if rows >= MAX_PREVIEW_ROWS as i64 {
+            break;
+        }
+    }
+    let range = range.ok_or_else(|| "No rows to preview".to_string())?;
+    if range.end - range.start > MAX_PARQUET_TABLE_PREVIEW_BYTES as u64 {
+        return Err("Table preview unavailable: the first rows take more than 16 MB".to_string());
+    }
+    Ok((row_groups, range))
+}
+
+/// Parse the first rows of a Parquet file `file_size` bytes long as a table preview, from its
+/// `footer` and the `rows` read at `rows_start` from [`parquet_rows_range`], which found them
+/// in the first `row_groups`.
+#[allow(clippy::missing_errors_doc)]
+pub fn parse_parquet_rows(
+    file_size: u64,
+    footer: Bytes,
+    rows_start: u64,
+    rows: Bytes,
+    row_groups: usize,
+) -> Result<PreviewData, String> {
+    let footer_start = file_size
+        .checked_sub(footer.len() as u64)
+        .ok_or_else(|| "Failed to read Parquet file: footer longer than the file".to_string())?;
+    let parts = ParquetParts {
+        len: file_size,
+        parts: vec![(rows_start, rows), (footer_start, footer)],
+    };
+    parquet_table(parts, Some((0..row_groups).collect()))
+}
+
+/// Parts of a Parquet file read with ranged requests, to decode it without the rest.
+struct ParquetParts {
+    len: u64,
+    /// Where each part starts in the file, and its bytes.
+    parts: Vec<(u64, Bytes)>,
+}
+
+impl ParquetParts {
+    /// The part holding `start`, from `start` on.
+    fn part_from(&self, start: u64) -> parquet::errors::Result<Bytes> {
+        self.parts
+            .iter()
+            .find_map(|(at, bytes)| {
+                let offset = usize::try_from(start.checked_sub(*at)?).ok()?;
+                (offset < bytes.len()).then(|| bytes.slice(offset..))
+            })
+            .ok_or_else(|| ParquetError::General(format!("byte {start} was not fetched")))
+    }
+}
+
+impl Length for ParquetParts {
+    fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+impl ChunkReader for ParquetParts {
+    type T = bytes::buf::Reader<Bytes>;
+
+    fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
+        Ok(self.part_from(start)?.reader())
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        let bytes = self.part_from(start)?;
+        if length > bytes.len() {
+            return Err(ParquetError::General(format!(
+                "bytes {start}..{} were not fetched",
+                start + length as u64
+            )));
+        }
+        Ok(bytes.slice(..length))
+    }
+}
+
+/// The first rows of the Parquet file read from `source`, from only `row_groups` if given.
+fn parquet_table(
+    source: impl ChunkReader + 'static,
+    row_groups: Option<Vec<usize>>,
+) -> Result<PreviewData, String> {
+    let mut builder = ParquetRecordBatchReaderBuilder::try_new(source)
         .map_err(|e| format!("Failed to read Parquet file: {e}"))?;
 
     let total_rows = builder.metadata().file_metadata().num_rows().max(0) as usize;
     builder = builder.with_batch_size(MAX_PREVIEW_ROWS);
+    if let Some(row_groups) = row_groups {
+        builder = builder.with_row_groups(row_groups);
+    }
 
     let schema = builder.schema().clone();
     let headers: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
@@ -718,6 +1287,7 @@ pub fn parse_parquet_table(data: &[u8]) -> Result<PreviewData, String> {
         total_rows,
         truncated: total_rows > MAX_PREVIEW_ROWS,
         file_type: PreviewFileType::Parquet,
+        delimiter: None,
     }))
 }
 
@@ -875,6 +1445,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_csv_delimiter_is_sniffed() {
+        let table = |data: &[u8]| match parse_csv(data).unwrap() {
+            PreviewData::Table(table) => table,
+            other => panic!("Expected table preview, got {other:?}"),
+        };
+        let semicolons = table(b"name;price;note\nTea;1,50;\"a;b\"\nCake;3,20;x\n");
+        assert_eq!(semicolons.headers, vec!["name", "price", "note"]);
+        assert_eq!(semicolons.rows[0], vec!["Tea", "1,50", "a;b"]);
+        assert_eq!(semicolons.delimiter, Some(';'));
+
+        let pipes = table(b"a|b\n1|2\n3|4");
+        assert_eq!(pipes.rows, vec![vec!["1", "2"], vec!["3", "4"]]);
+        assert_eq!(pipes.delimiter, Some('|'));
+
+        // Commas inside quotes do not count, and the extension's delimiter wins a tie
+        let commas = table(b"name,note\nAlice,\"x, y, z\"\nBob,w\n");
+        assert_eq!(commas.rows[0], vec!["Alice", "x, y, z"]);
+        assert_eq!(commas.delimiter, None);
+        let PreviewData::Table(tabs) = parse_tsv(b"a,b\tc\n1,2\t3\n").unwrap() else {
+            panic!("Expected table preview");
+        };
+        assert_eq!(tabs.headers, vec!["a,b", "c"]);
+        assert_eq!(tabs.delimiter, None);
+    }
+
+    #[test]
+    fn test_csv_header_is_sniffed() {
+        let PreviewData::Table(table) = parse_csv(b"1,Alice,30.5\n2,Bob,41\n").unwrap() else {
+            panic!("Expected table preview");
+        };
+        assert_eq!(table.headers, vec!["Column 1", "Column 2", "Column 3"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.total_rows, 2);
+        assert_eq!(table.numeric_columns(), vec![true, false, true]);
+
+        let PreviewData::Table(table) = parse_csv(b"id,name\n1,Alice\n,Bob\n").unwrap() else {
+            panic!("Expected table preview");
+        };
+        assert_eq!(table.headers, vec!["id", "name"]);
+        assert_eq!(table.total_rows, 2);
+        // Empty cells leave a column of numbers numeric
+        assert_eq!(table.numeric_columns(), vec![true, false]);
+    }
+
     #[test]
     fn test_json_array_as_table() {
         let json_data = br#"[{"name": "Alice", "age": 30}, {"name": "Bob", "age": 25}]"#;
@@ -900,6 +1515,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_json_lines_of_uniform_objects_as_table() {
+        assert_eq!(
+            PreviewFileType::from_extension("events.ndjson"),
+            PreviewFileType::JsonLines
+        );
+        let data = b"{\"id\": 1, \"name\": \"Alice\"}\n\n{\"name\": \"Bob\", \"id\": 2}\n";
+        let Ok(PreviewData::Table(table)) = parse_json_lines(data) else {
+            panic!("Expected table preview for uniform JSON Lines");
+        };
+        assert_eq!(table.headers, vec!["id", "name"]);
+        assert_eq!(table.rows, vec![vec!["1", "Alice"], vec!["2", "Bob"]]);
+        assert_eq!(table.file_type, PreviewFileType::JsonLines);
+        assert!(!table.truncated);
+    }
+
+    #[test]
+    fn test_mixed_json_lines_pretty_printed_per_line() {
+        let long: Vec<u32> = (0..30).collect();
+        let data = format!(
+            "{{\"id\": 1}}\n{{\"id\": 2, \"extra\": true}}\nnot json\n{}\n",
+            serde_json::to_string(&long).unwrap()
+        );
+        let Ok(PreviewData::Json(json)) = parse_json_lines(data.as_bytes()) else {
+            panic!("Expected JSON preview for mixed JSON Lines");
+        };
+        assert_eq!(json.records, Some(4));
+        assert!(!json.truncated);
+        let lines: Vec<&str> = json.content.lines().collect();
+        assert_eq!(lines[..3], ["{", "  \"id\": 1", "}"]);
+        assert!(lines.contains(&"not json"));
+        // The long array is cut short on its own, leaving the rest of the file
+        assert_eq!(lines.last(), Some(&"  … 12 more lines"));
+        assert_eq!(lines.len(), 3 + 4 + 1 + MAX_RECORD_LINES + 1);
+    }
+
+    #[test]
+    fn test_cut_off_json_lines_drop_the_partial_line() {
+        let mut data = Vec::new();
+        while data.len() < MAX_PREVIEW_BYTES {
+            data.extend_from_slice(b"{\"id\": 1, \"name\": \"Alice\"}\n");
+        }
+        data.truncate(MAX_PREVIEW_BYTES);
+        let records = data.iter().filter(|&&byte| byte == b'\n').count();
+        let Ok(PreviewData::Table(table)) = parse_json_lines(&data) else {
+            panic!("Expected table preview for cut off JSON Lines");
+        };
+        assert_eq!(table.total_rows, records);
+        assert!(table.truncated);
+    }
+
     #[test]
     fn test_truncated_json_falls_back_to_raw() {
         // Simulate truncated JSON (incomplete array)
@@ -933,6 +1599,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_text_in_other_encodings_is_decoded() {
+        let text = |data: &[u8], file_type: PreviewFileType| match parse_preview(data, &file_type) {
+            Ok(PreviewData::Text(text)) => text.content,
+            other => panic!("Expected text preview, got {other:?}"),
+        };
+        let utf16 = |bom: [u8; 2], unit: fn(u16) -> [u8; 2]| {
+            let mut data = bom.to_vec();
+            data.extend("héllo\nwörld".encode_utf16().flat_map(unit));
+            data
+        };
+        let unknown = || PreviewFileType::Unsupported;
+        assert_eq!(
+            text(&utf16([0xFF, 0xFE], u16::to_le_bytes), unknown()),
+            "héllo\nwörld"
+        );
+        assert_eq!(
+            text(&utf16([0xFE, 0xFF], u16::to_be_bytes), unknown()),
+            "héllo\nwörld"
+        );
+        // A UTF-16 unit cut in half at the end of the fetched bytes is left out
+        let mut cut = utf16([0xFF, 0xFE], u16::to_le_bytes);
+        cut.push(b'!');
+        assert_eq!(text(&cut, unknown()), "héllo\nwörld");
+
+        // Invalid as UTF-8, so Windows-1252
+        let latin = b"na\xefve caf\xe9 \x80 5";
+        assert_eq!(
+            text(latin, PreviewFileType::Text("TXT".to_string())),
+            "naïve café € 5"
+        );
+        let PreviewData::Table(table) = parse_preview(
+            b"\xef\xbb\xbfname;city\nJos\xc3\xa9;Z\xc3\xbcrich\n",
+            &PreviewFileType::Csv,
+        )
+        .unwrap() else {
+            panic!("Expected table preview");
+        };
+        // The UTF-8 byte order mark is not part of the first header
+        assert_eq!(table.headers, vec!["name", "city"]);
+        assert_eq!(table.rows[0], vec!["José", "Zürich"]);
+        let PreviewData::Table(table) =
+            parse_preview(b"name,city\nJos\xe9,Z\xfcrich\n", &PreviewFileType::Csv).unwrap()
+        else {
+            panic!("Expected table preview");
+        };
+        assert_eq!(table.rows[0], vec!["José", "Zürich"]);
+    }
+
     #[test]
     fn test_text_file_parsing() {
         let text_data = b"Hello, World!\nThis is a test file.\nLine 3.";
@@ -978,9 +1693,73 @@ mod tests {
         let binary_data: &[u8] = &[0x00, 0x01, 0x02, 0xFF, 0xFE];
         let result = parse_preview(binary_data, &PreviewFileType::Unsupported);
 
-        // Should fail with binary error
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("binary"));
+        // Should fall back to a hex dump
+        let Ok(PreviewData::Hex(hex)) = result else {
+            panic!("Expected a hex dump for binary content");
+        };
+        assert_eq!(hex.len, 5);
+        assert!(!hex.truncated);
+    }
+
+    #[test]
+    fn test_hex_dump_lines() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(b"\0\0\x01");
+        let Ok(PreviewData::Hex(hex)) = parse_preview(&png, &PreviewFileType::Unsupported) else {
+            panic!("Expected a hex dump");
+        };
+        let lines: Vec<&str> = hex.content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|",
+                "00000010  00 00 01                                          |...|",
+            ]
+        );
+
+        // Only the start of a large file is dumped
+        let Ok(PreviewData::Hex(hex)) =
+            parse_preview(&[0u8; 50 * 1024], &PreviewFileType::Unsupported)
+        else {
+            panic!("Expected a hex dump");
+        };
+        assert_eq!(hex.len, HEX_PREVIEW_BYTES);
+        assert_eq!(hex.content.lines().count(), HEX_PREVIEW_BYTES / 16);
+        assert!(hex.truncated);
+    }
+
+    #[test]
+    fn test_copying_a_cell_row_line_or_everything() {
+        let table = parse_csv(b"id,city\n1,Paris\n2,Oslo\n").unwrap();
+        assert_eq!(
+            table.copy_text(PreviewCopy::Cell, 1, 1),
+            Some("Oslo".to_string())
+        );
+        assert_eq!(
+            table.copy_text(PreviewCopy::Row, 0, 1),
+            Some("1\tParis".to_string())
+        );
+        assert_eq!(
+            table.copy_text(PreviewCopy::All, 0, 0),
+            Some("id\tcity\n1\tParis\n2\tOslo".to_string())
+        );
+        assert_eq!(table.copy_text(PreviewCopy::Cell, 0, 2), None);
+        assert_eq!(table.copy_text(PreviewCopy::Row, 2, 0), None);
+
+        // Outside a table, the cell and the row are both the line
+        let text = parse_text(b"one\ntwo\nthree", "LOG").unwrap();
+        assert_eq!(
+            text.copy_text(PreviewCopy::Cell, 1, 4),
+            Some("two".to_string())
+        );
+        assert_eq!(
+            text.copy_text(PreviewCopy::Row, 2, 0),
+            Some("three".to_string())
+        );
+        assert_eq!(
+            text.copy_text(PreviewCopy::All, 2, 0),
+            Some("one\ntwo\nthree".to_string())
+        );
     }
 
     #[test]
@@ -1075,6 +1854,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parquet_rows_read_from_leading_row_groups() {
+        use arrow::array::Int32Array;
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::ArrowWriter;
+        use parquet::file::properties::WriterProperties;
+        use std::sync::Arc;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from((0..1000).collect::<Vec<_>>()))],
+        )
+        .unwrap();
+        let properties = WriterProperties::builder()
+            .set_max_row_group_row_count(Some(60))
+            .build();
+        let mut buffer: Vec<u8> = Vec::new();
+        {
+            let mut writer = ArrowWriter::try_new(&mut buffer, schema, Some(properties)).unwrap();
+            writer.write(&batch).unwrap();
+            writer.close().unwrap();
+        }
+        let file = Bytes::from(buffer);
+        let size = file.len() as u64;
+        let footer_len = parquet_footer_len(&file).unwrap() as usize;
+        let footer = file.slice(file.len() - footer_len..);
+
+        // Two row groups of 60 hold the first 100 rows
+        let (row_groups, range) = parquet_rows_range(&footer).unwrap();
+        assert_eq!(row_groups, 2);
+        assert!(range.end < size - footer_len as u64);
+        let rows = file.slice(range.start as usize..range.end as usize);
+        let Ok(PreviewData::Table(table)) =
+            parse_parquet_rows(size, footer, range.start, rows, row_groups)
+        else {
+            panic!("Expected table preview");
+        };
+        assert_eq!(table.rows.len(), MAX_PREVIEW_ROWS);
+        assert_eq!(table.rows[99], vec!["99"]);
+        assert_eq!(table.total_rows, 1000);
+        assert!(table.truncated);
+    }
+
+    #[test]
+    fn test_parquet_footer_len() {
+        let mut tail = b"metadata".to_vec();
+        tail.extend_from_slice(&8u32.to_le_bytes());
+        tail.extend_from_slice(b"PAR1");
+        assert_eq!(parquet_footer_len(&tail), Ok(16));
+        // Only the length and magic are read, so a footer longer than the tail is reported
+        assert_eq!(parquet_footer_len(&tail[4..]), Ok(16));
+        assert!(parquet_footer_len(b"PAR1").is_err());
+        assert!(parquet_footer_len(b"not a parquet file").is_err());
+    }
+
     #[test]
     fn test_parquet_invalid_data() {
         // Invalid data should return an error
@@ -1085,6 +1921,67 @@ mod tests {
         assert!(result.unwrap_err().contains("Failed to read Parquet"));
     }
 
+    #[test]
+    fn test_compression_from_name() {
+        assert_eq!(
+            Compression::of("events.json.gz"),
+            Some((Compression::Gzip, "events.json"))
+        );
+        assert_eq!(
+            Compression::of("logs/app.LOG.ZST"),
+            Some((Compression::Zstd, "logs/app.LOG"))
+        );
+        assert_eq!(
+            Compression::of("dump.csv.bz2"),
+            Some((Compression::Bzip2, "dump.csv"))
+        );
+        assert_eq!(Compression::of("data.csv"), None);
+        assert_eq!(Compression::of("gz"), None);
+    }
+
+    #[test]
+    fn test_compressed_files_preview_as_the_file_inside() {
+        use std::io::Write;
+
+        let csv = "id,name\n1,Alice\n2,Bob\n";
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(csv.as_bytes()).unwrap();
+        let mut bzip2 = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip2.write_all(csv.as_bytes()).unwrap();
+        let compressed = [
+            (Compression::Gzip, gzip.finish().unwrap()),
+            (
+                Compression::Zstd,
+                zstd::encode_all(csv.as_bytes(), 0).unwrap(),
+            ),
+            (Compression::Bzip2, bzip2.finish().unwrap()),
+        ];
+        for (compression, data) in compressed {
+            let result = parse_compressed_preview(&data, Some(compression), &PreviewFileType::Csv);
+            let Ok(PreviewData::Table(table)) = result else {
+                panic!("Expected table preview of {compression} data, got {result:?}");
+            };
+            assert_eq!(table.rows, vec![vec!["1", "Alice"], vec!["2", "Bob"]]);
+        }
+
+        assert!(Compression::Gzip.decompress(b"not gzip").is_err());
+    }
+
+    #[test]
+    fn test_decompressing_a_cut_off_stream_keeps_its_start() {
+        let lines: String = (0..200_000).map(|i| format!("line {i}\n")).collect();
+        let compressed = zstd::encode_all(lines.as_bytes(), 0).unwrap();
+        let decompressed = Compression::Zstd
+            .decompress(&compressed[..compressed.len() / 2])
+            .unwrap();
+        assert!(!decompressed.is_empty());
+        assert!(lines.as_bytes().starts_with(&decompressed));
+
+        // Only the start of a large file is decompressed
+        let decompressed = Compression::Zstd.decompress(&compressed).unwrap();
+        assert_eq!(decompressed.len(), MAX_PREVIEW_BYTES);
+    }
+
     proptest! {
         #[test]
         fn make_valid_utf8_always_returns_valid_utf8(
@@ -1118,7 +2015,7 @@ mod tests {
         }
 
         #[test]
-        fn parse_preview_for_unknown_extension_only_yields_text_or_error(
+        fn parse_preview_for_unknown_extension_only_yields_text_or_hex(
             data in prop::collection::vec(any::<u8>(), 0..2048)
         ) {
             let result = parse_preview(&data, &PreviewFileType::Unsupported);
@@ -1127,6 +2024,7 @@ mod tests {
                     prop_assert_eq!(text.extension, "TEXT");
                     prop_assert!(std::str::from_utf8(text.content.as_bytes()).is_ok());
                 }
+                Ok(PreviewData::Hex(hex)) => prop_assert_eq!(hex.len, data.len()),
                 Ok(other) => prop_assert!(false, "unexpected preview variant: {other:?}"),
                 Err(_) => {}
             }
@@ -0,0 +1,270 @@
+//! Reading on through a long text preview: once it is scrolled near the end of what has been
+//! fetched, the next stretch of the blob is fetched in the background and added on, so a large
+//! log can be read in place without downloading it.
+//!
+//! Pages end at the last newline in them, the rest being held back for the next, so a line or
+//! a character is never split between two. That takes an encoding in which a newline is one
+//! byte, so a UTF-16 blob is not paged.
+
+use crate::error::BlobrsError;
+use crate::highlight::highlight;
+use crate::listing::StopOnDrop;
+use crate::preview::{MAX_PREVIEW_BYTES, TextPreview, text_encoding};
+use bytes::Bytes;
+use encoding_rs::Encoding;
+use object_store::{GetOptions, ObjectStore, path::Path as ObjectPath};
+use ratatui::text::Line;
+use std::sync::{Arc, Mutex};
+
+/// Bytes fetched at a time, as many as the first page.
+pub const PAGE_BYTES: u64 = MAX_PREVIEW_BYTES as u64;
+
+/// Rows short of the end of the fetched text at which the next page is fetched.
+pub const FETCH_AHEAD_ROWS: usize = 100;
+
+type PageResult = Arc<Mutex<Option<Result<Bytes, BlobrsError>>>>;
+
+/// A page still being fetched; dropping it stops the fetch.
+#[derive(Debug)]
+struct Fetch {
+    result: PageResult,
+    _task: StopOnDrop,
+}
+
+/// A text blob read into the preview a page at a time.
+#[derive(Debug)]
+pub struct PreviewPager {
+    object_store: Arc<dyn ObjectStore>,
+    path: ObjectPath,
+    version: Option<String>,
+    /// Encoding of the text, found from the first page.
+    encoding: &'static Encoding,
+    /// Size of the blob.
+    pub size: u64,
+    /// Bytes of the blob read so far, from its start.
+    pub loaded: u64,
+    /// Bytes after the last newline read, held back for the next page.
+    partial: Vec<u8>,
+    fetch: Option<Fetch>,
+}
+
+impl PreviewPager {
+    /// Whether a text blob starting with `first_page` can be paged through.
+    #[must_use]
+    pub fn can_page(first_page: &[u8]) -> bool {
+        text_encoding(first_page).0.is_ascii_compatible()
+    }
+
+    /// Page through the text blob at `path`, of `size` bytes, starting with `first_page`, the
+    /// start of it. Returns the pager and the preview of the first page's lines.
+    #[must_use]
+    pub fn new(
+        object_store: Arc<dyn ObjectStore>,
+        path: ObjectPath,
+        version: Option<String>,
+        size: u64,
+        first_page: &[u8],
+        extension: &str,
+    ) -> (Self, TextPreview) {
+        let (encoding, bom) = text_encoding(first_page);
+        let mut pager = Self {
+            object_store,
+            path,
+            version,
+            encoding,
+            size,
+            loaded: bom as u64,
+            partial: Vec::new(),
+            fetch: None,
+        };
+        let mut text = TextPreview {
+            content: String::new(),
+            total_lines: 0,
+            truncated: true,
+            extension: extension.to_string(),
+            highlighted: None,
+        };
+        pager.add_page(&first_page[bom..], &mut text);
+        (pager, text)
+    }
+
+    /// Whether the whole blob has been read.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.loaded >= self.size
+    }
+
+    #[must_use]
+    pub const fn is_fetching(&self) -> bool {
+        self.fetch.is_some()
+    }
+
+    /// Add a page read from where the last one ended to `text`: its whole lines, and the last
+    /// line too once the end of the blob is reached.
+    pub fn add_page(&mut self, page: &[u8], text: &mut TextPreview) {
+        self.loaded += page.len() as u64;
+        // A blob that shrank since it was listed ends early
+        if page.is_empty() {
+            self.size = self.loaded;
+        }
+        self.partial.extend_from_slice(page);
+        let end = if self.is_complete() {
+            self.partial.len()
+        } else {
+            match self.partial.iter().rposition(|&byte| byte == b'\n') {
+                Some(newline) => newline + 1,
+                None => return,
+            }
+        };
+        let lines: Vec<u8> = self.partial.drain(..end).collect();
+        let (lines, _) = self.encoding.decode_without_bom_handling(&lines);
+        let lines = lines.strip_suffix('\n').unwrap_or(&lines);
+
+        if text.content.is_empty() {
+            text.highlighted = highlight(lines, &text.extension);
+            text.content = lines.to_string();
+        } else {
+            // Highlighting starts afresh on each page, as it costs too much to redo it all
+            if let Some(highlighted) = &mut text.highlighted {
+                highlighted.extend(highlight(lines, &text.extension).unwrap_or_else(|| {
+                    lines
+                        .lines()
+                        .map(|line| Line::from(line.to_string()))
+                        .collect()
+                }));
+            }
+            text.content.push('\n');
+            text.content.push_str(lines);
+        }
+        text.total_lines = text.content.lines().count();
+        text.truncated = !self.is_complete();
+    }
+
+    /// Fetch the next page in the background once `row`, with `rows` more on screen below
+    /// it, comes near the end of the `total` rows read.
+    pub fn fetch_near(&mut self, row: usize, rows: usize, total: usize) {
+        if self.is_fetching() || self.is_complete() || row + rows + FETCH_AHEAD_ROWS < total {
+            return;
+        }
+        let options = GetOptions {
+            range: Some((self.loaded..self.size.min(self.loaded + PAGE_BYTES)).into()),
+            version: self.version.clone(),
+            ..GetOptions::default()
+        };
+        let (object_store, path) = (self.object_store.clone(), self.path.clone());
+        let result: PageResult = Arc::default();
+        let task = tokio::spawn({
+            let result = result.clone();
+            async move {
+                let page = match object_store.get_opts(&path, options).await {
+                    Ok(got) => got.bytes().await,
+                    Err(e) => Err(e),
+                };
+                *result
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner) =
+                    Some(page.map_err(BlobrsError::from));
+            }
+        });
+        self.fetch = Some(Fetch {
+            result,
+            _task: StopOnDrop(task.abort_handle()),
+        });
+    }
+
+    /// The page being fetched, once it has arrived.
+    pub fn poll(&mut self) -> Option<Result<Bytes, BlobrsError>> {
+        let page = self
+            .fetch
+            .as_ref()?
+            .result
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()?;
+        self.fetch = None;
+        Some(page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::ObjectStoreExt;
+
+    fn pager(size: u64, first_page: &[u8]) -> (PreviewPager, TextPreview) {
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        PreviewPager::new(
+            store,
+            ObjectPath::from("app.log"),
+            None,
+            size,
+            first_page,
+            "LOG",
+        )
+    }
+
+    #[test]
+    fn lines_are_held_back_until_they_are_whole() {
+        let log = "one\ntwo\nthree ünïcode\nfour";
+        let (mut pager, mut text) = pager(log.len() as u64, &log.as_bytes()[..10]);
+        assert_eq!(text.content, "one\ntwo");
+        assert!(text.truncated);
+
+        // A page in the middle of a line, and of a character, adds nothing yet
+        pager.add_page(&log.as_bytes()[10..15], &mut text);
+        assert_eq!(text.total_lines, 2);
+        pager.add_page(&log.as_bytes()[15..24], &mut text);
+        assert_eq!(text.content, "one\ntwo\nthree ünïcode");
+
+        // The last line has no newline, and comes with the end of the blob
+        pager.add_page(&log.as_bytes()[24..], &mut text);
+        assert_eq!(text.content, log);
+        assert_eq!(text.total_lines, 4);
+        assert!(!text.truncated);
+        assert!(pager.is_complete());
+    }
+
+    #[test]
+    fn pages_are_decoded_from_the_encoding_of_the_first() {
+        // Windows-1252, as a legacy export would be
+        let log = b"caf\xe9\nna\xefve\n";
+        assert!(PreviewPager::can_page(log));
+        let (mut pager, mut text) = pager(log.len() as u64 + 3, log);
+        pager.add_page(b"\x80 5", &mut text);
+        assert_eq!(text.content, "café\nnaïve\n€ 5");
+
+        assert!(!PreviewPager::can_page(b"\xff\xfea\x00\n\x00"));
+    }
+
+    #[tokio::test]
+    async fn pages_are_fetched_near_the_end_of_the_text() {
+        let log: String = (0..20_000).map(|i| format!("line {i}\n")).collect();
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let path = ObjectPath::from("app.log");
+        store.put(&path, log.clone().into()).await.unwrap();
+        let first_page = &log.as_bytes()[..PAGE_BYTES as usize];
+        let (mut pager, mut text) =
+            PreviewPager::new(store, path, None, log.len() as u64, first_page, "LOG");
+        let first_lines = text.total_lines;
+
+        // Far from the end, nothing is fetched
+        pager.fetch_near(0, 40, text.total_lines);
+        assert!(!pager.is_fetching());
+
+        while !pager.is_complete() {
+            pager.fetch_near(text.total_lines - 40, 40, text.total_lines);
+            assert!(pager.is_fetching());
+            let page = loop {
+                if let Some(page) = pager.poll() {
+                    break page.unwrap();
+                }
+                tokio::task::yield_now().await;
+            };
+            pager.add_page(&page, &mut text);
+        }
+        assert!(text.total_lines > first_lines);
+        assert_eq!(text.total_lines, 20_000);
+        assert_eq!(text.content.lines().last(), Some("line 19999"));
+        assert!(!text.truncated);
+    }
+}
@@ -0,0 +1,188 @@
+//! The preview pane beside the blob list, showing whichever blob is selected, as ranger and
+//! yazi do.
+//!
+//! Moving through a folder changes the selection many times a second, so a blob is only
+//! fetched once the selection has rested on it for [`SETTLE_TICKS`], in the background, and
+//! moving on stops the fetch for the blob left behind.
+
+use blobrs::error::BlobrsError;
+use blobrs::listing::StopOnDrop;
+use blobrs::preview::PreviewData;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Ticks the selection must rest on a blob before it is fetched, about a fifth of a second.
+pub const SETTLE_TICKS: usize = 6;
+
+/// What the pane shows.
+#[derive(Debug, Clone, Default)]
+pub enum PaneContent {
+    /// A folder is selected, or nothing is.
+    #[default]
+    Nothing,
+    /// Waiting for the selection to rest, or for the fetch.
+    Loading,
+    Ready(PreviewData),
+    Failed(String),
+}
+
+type FetchResult = Arc<Mutex<Option<Result<PreviewData, BlobrsError>>>>;
+
+/// A preview still being fetched; dropping it stops the fetch.
+#[derive(Debug)]
+struct Fetch {
+    result: FetchResult,
+    _task: StopOnDrop,
+}
+
+/// The pane's content and the fetch of the next.
+#[derive(Debug, Default)]
+pub struct PreviewPane {
+    /// The blob the pane is for, by a key naming it and its version.
+    target: Option<String>,
+    /// Ticks the selection has rested on `target`.
+    rested: usize,
+    content: PaneContent,
+    fetch: Option<Fetch>,
+}
+
+impl PreviewPane {
+    /// Follow the selection, called on every tick with a key naming the selected blob, or
+    /// `None` for a folder or nothing. Returns `true` once, when the selection has rested on a
+    /// blob long enough for it to be fetched.
+    pub fn follow(&mut self, target: Option<String>) -> bool {
+        if self.target != target {
+            self.content = if target.is_some() {
+                PaneContent::Loading
+            } else {
+                PaneContent::Nothing
+            };
+            self.target = target;
+            self.rested = 0;
+            self.fetch = None;
+            return false;
+        }
+        if self.target.is_none() || self.rested >= SETTLE_TICKS {
+            return false;
+        }
+        self.rested += 1;
+        self.rested == SETTLE_TICKS
+    }
+
+    /// Fetch the selected blob's preview in the background.
+    pub fn start(
+        &mut self,
+        fetch: impl Future<Output = Result<PreviewData, BlobrsError>> + Send + 'static,
+    ) {
+        let result: FetchResult = Arc::default();
+        let task = tokio::spawn({
+            let result = result.clone();
+            async move {
+                let preview = fetch.await;
+                *result
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(preview);
+            }
+        });
+        self.fetch = Some(Fetch {
+            result,
+            _task: StopOnDrop(task.abort_handle()),
+        });
+    }
+
+    /// Show the fetched preview once it has arrived.
+    pub fn poll(&mut self) {
+        let Some(fetch) = &self.fetch else {
+            return;
+        };
+        let Some(result) = fetch
+            .result
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()
+        else {
+            return;
+        };
+        self.content = match result {
+            Ok(preview) => PaneContent::Ready(preview),
+            Err(e) => PaneContent::Failed(e.to_string()),
+        };
+        self.fetch = None;
+    }
+
+    #[must_use]
+    pub const fn content(&self) -> &PaneContent {
+        &self.content
+    }
+
+    /// Forget the shown blob, stopping any fetch, so it is fetched again when next selected.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blobrs::preview::TextPreview;
+
+    fn text(content: &str) -> PreviewData {
+        PreviewData::Text(TextPreview {
+            content: content.to_string(),
+            total_lines: 1,
+            truncated: false,
+            extension: "TXT".to_string(),
+            highlighted: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn only_a_selection_that_rests_is_fetched() {
+        let mut pane = PreviewPane::default();
+        let a = || Some("raw/a.txt".to_string());
+
+        // Moving on before the selection rests fetches nothing
+        assert!(!pane.follow(a()));
+        for _ in 1..SETTLE_TICKS {
+            assert!(!pane.follow(a()));
+        }
+        assert!(!pane.follow(Some("raw/b.txt".to_string())));
+        assert!(matches!(pane.content(), PaneContent::Loading));
+
+        assert!(!pane.follow(a()));
+        let due = (0..SETTLE_TICKS).filter(|_| pane.follow(a())).count();
+        assert_eq!(due, 1);
+        pane.start(async { Ok(text("hello")) });
+        while matches!(pane.content(), PaneContent::Loading) {
+            tokio::task::yield_now().await;
+            pane.poll();
+        }
+        assert!(
+            matches!(pane.content(), PaneContent::Ready(PreviewData::Text(t)) if t.content == "hello")
+        );
+
+        // A folder shows nothing
+        pane.follow(None);
+        assert!(matches!(pane.content(), PaneContent::Nothing));
+    }
+
+    #[tokio::test]
+    async fn a_fetch_for_a_blob_left_behind_is_dropped() {
+        let mut pane = PreviewPane::default();
+        pane.follow(Some("raw/a.txt".to_string()));
+        pane.start(std::future::pending());
+        let result = pane.fetch.as_ref().map(|fetch| fetch.result.clone());
+
+        pane.follow(Some("raw/b.txt".to_string()));
+        assert!(pane.fetch.is_none());
+        // The stopped task lets go of its handle, leaving only this one
+        let result = result.unwrap();
+        for _ in 0..100 {
+            if Arc::strong_count(&result) == 1 {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(Arc::strong_count(&result), 1);
+    }
+}
@@ -0,0 +1,209 @@
+//! Searching the preview: `/` finds text in the lines of a text, JSON or hex preview or in a
+//! table's cells, every match is highlighted, and `n`/`N` step through the rows holding one.
+//!
+//! Matching is plain text ignoring ASCII case rather than fuzzy: a preview is long, and what is
+//! searched for is usually a word or value already seen in it.
+
+use blobrs::preview::PreviewData;
+use std::ops::Range;
+
+/// A search within the preview.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreviewSearch {
+    pub query: String,
+    /// Whether the query is still being typed.
+    pub typing: bool,
+    /// Row the preview was on when the search started, to go back to if it is cancelled.
+    pub origin: usize,
+}
+
+/// Where the preview was last drawn, for paging and for bringing a match into view.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreviewViewport {
+    /// Rows of content on screen.
+    pub rows: usize,
+    /// Table columns on screen.
+    pub columns: Range<usize>,
+}
+
+impl PreviewSearch {
+    /// Start typing a search from the preview's `origin` row.
+    #[must_use]
+    pub const fn new(origin: usize) -> Self {
+        Self {
+            query: String::new(),
+            typing: true,
+            origin,
+        }
+    }
+
+    /// Rows holding a match: lines of a text, JSON or hex preview, or rows of a table. A parquet
+    /// schema has no rows to search.
+    #[must_use]
+    pub fn rows(&self, data: &PreviewData) -> Vec<usize> {
+        if self.query.is_empty() {
+            return Vec::new();
+        }
+        let found = |text: &str| !match_ranges(text, &self.query).is_empty();
+        match data {
+            PreviewData::Text(text) => lines_matching(&text.content, found),
+            PreviewData::Json(json) => lines_matching(&json.content, found),
+            PreviewData::Hex(hex) => lines_matching(&hex.content, found),
+            PreviewData::Table(table) => table
+                .rows
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| row.iter().any(|cell| found(cell)))
+                .map(|(index, _)| index)
+                .collect(),
+            PreviewData::ParquetSchema(_) => Vec::new(),
+        }
+    }
+
+    /// The first row with a match at or after `row`, wrapping around to the top.
+    #[must_use]
+    pub fn next(&self, data: &PreviewData, row: usize) -> Option<usize> {
+        let rows = self.rows(data);
+        rows.iter()
+            .find(|&&found| found >= row)
+            .or_else(|| rows.first())
+            .copied()
+    }
+
+    /// The last row with a match before `row`, wrapping around to the bottom.
+    #[must_use]
+    pub fn previous(&self, data: &PreviewData, row: usize) -> Option<usize> {
+        let rows = self.rows(data);
+        rows.iter()
+            .rev()
+            .find(|&&found| found < row)
+            .or_else(|| rows.last())
+            .copied()
+    }
+
+    /// The first column of a table's `row` with a match, to scroll it into view.
+    #[must_use]
+    pub fn column(&self, data: &PreviewData, row: usize) -> Option<usize> {
+        let PreviewData::Table(table) = data else {
+            return None;
+        };
+        table
+            .rows
+            .get(row)?
+            .iter()
+            .position(|cell| !match_ranges(cell, &self.query).is_empty())
+    }
+
+    /// What the search has found for showing under the preview, e.g. `/error  2 of 5`, with
+    /// the preview on `row`.
+    #[must_use]
+    pub fn status(&self, data: Option<&PreviewData>, row: usize) -> String {
+        if self.typing {
+            return format!("/{}▏", self.query);
+        }
+        let rows = data.map(|data| self.rows(data)).unwrap_or_default();
+        let found = match (rows.len(), rows.iter().position(|&found| found == row)) {
+            (0, _) => "no matches".to_string(),
+            (count, Some(index)) => format!("{} of {count}", index + 1),
+            (1, None) => "1 match".to_string(),
+            (count, None) => format!("{count} matches"),
+        };
+        format!("/{}  {found}", self.query)
+    }
+}
+
+fn lines_matching(content: &str, found: impl Fn(&str) -> bool) -> Vec<usize> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| found(line))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Byte ranges in `text` of each `query`, ignoring ASCII case, none overlapping.
+#[must_use]
+pub fn match_ranges(text: &str, query: &str) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    // Lowering only ASCII keeps every character at the same byte offset
+    let query = query.to_ascii_lowercase();
+    text.to_ascii_lowercase()
+        .match_indices(&query)
+        .map(|(start, found)| start..start + found.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blobrs::preview::{PreviewFileType, TablePreview, TextPreview};
+
+    fn search(query: &str) -> PreviewSearch {
+        PreviewSearch {
+            query: query.to_string(),
+            typing: false,
+            origin: 0,
+        }
+    }
+
+    fn text(content: &str) -> PreviewData {
+        PreviewData::Text(TextPreview {
+            content: content.to_string(),
+            total_lines: content.lines().count(),
+            truncated: false,
+            extension: "LOG".to_string(),
+            highlighted: None,
+        })
+    }
+
+    #[test]
+    fn matches_ignore_case_and_do_not_overlap() {
+        assert_eq!(match_ranges("Error: an error", "ERROR"), vec![0..5, 10..15]);
+        assert_eq!(match_ranges("aaaa", "aa"), vec![0..2, 2..4]);
+        assert_eq!(match_ranges("naïve Naïve", "naï"), vec![0..4, 7..11]);
+        assert!(match_ranges("anything", "").is_empty());
+    }
+
+    #[test]
+    fn next_and_previous_wrap_around() {
+        let data = text("ok\nerror one\nok\nerror two\nok");
+        let errors = search("error");
+        assert_eq!(errors.rows(&data), vec![1, 3]);
+        assert_eq!(errors.next(&data, 0), Some(1));
+        assert_eq!(errors.next(&data, 2), Some(3));
+        assert_eq!(errors.next(&data, 4), Some(1));
+        assert_eq!(errors.previous(&data, 3), Some(1));
+        assert_eq!(errors.previous(&data, 1), Some(3));
+        assert_eq!(errors.status(Some(&data), 3), "/error  2 of 2");
+        assert_eq!(errors.status(Some(&data), 0), "/error  2 matches");
+        assert_eq!(PreviewSearch::new(0).status(Some(&data), 0), "/▏");
+        assert_eq!(search("warn").next(&data, 0), None);
+        assert_eq!(search("warn").status(Some(&data), 0), "/warn  no matches");
+    }
+
+    #[test]
+    fn tables_are_searched_by_cell() {
+        let data = PreviewData::Table(TablePreview {
+            headers: vec!["id".to_string(), "city".to_string(), "note".to_string()],
+            rows: vec![
+                vec!["1".to_string(), "Paris".to_string(), String::new()],
+                vec![
+                    "2".to_string(),
+                    "Oslo".to_string(),
+                    "near paris".to_string(),
+                ],
+            ],
+            total_rows: 2,
+            truncated: false,
+            column_types: None,
+            file_type: PreviewFileType::Csv,
+            delimiter: None,
+        });
+        let paris = search("paris");
+        assert_eq!(paris.rows(&data), vec![0, 1]);
+        assert_eq!(paris.column(&data, 0), Some(1));
+        assert_eq!(paris.column(&data, 1), Some(2));
+    }
+}
@@ -0,0 +1,383 @@
+//! Name search across several storage accounts and their containers at once, name search
+//! through everything under one folder, and blob index tag search within one container.
+
+use crate::azure::{AzureRestClient, container_store};
+use crate::config::{RetryConfig, TimeoutConfig};
+use crate::error::BlobrsError;
+use crate::listing::StopOnDrop;
+use crate::secret::Secret;
+use futures::stream::{self, StreamExt};
+use object_store::ObjectStore;
+use object_store::path::Path as ObjectPath;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Stop collecting once this many matches have been found.
+pub const MAX_SEARCH_HITS: usize = 500;
+
+/// How many containers are listed at the same time per account.
+const CONTAINER_CONCURRENCY: usize = 8;
+
+/// What the search popup looks through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Blob paths in every container of this account and the configured profiles.
+    #[default]
+    Accounts,
+    /// Blob paths under the current folder, or in the highlighted container, at any depth.
+    Folder,
+    /// Blob index tags in one container.
+    Tags,
+}
+
+impl SearchScope {
+    /// The scope `Tab` switches to.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Accounts => Self::Folder,
+            Self::Folder => Self::Tags,
+            Self::Tags => Self::Accounts,
+        }
+    }
+}
+
+/// A storage account to search.
+#[derive(Debug, Clone)]
+pub struct SearchSource {
+    /// Label shown next to results, e.g. the profile name.
+    pub label: String,
+    /// Storage account name.
+    pub account: String,
+    /// Storage account access key.
+    pub access_key: Secret,
+    /// Retry policy for the account's requests.
+    pub retry: RetryConfig,
+    /// Timeouts for the account's requests.
+    pub timeouts: TimeoutConfig,
+}
+
+impl SearchSource {
+    /// A REST client for the account, with its retry policy and timeouts.
+    fn rest_client(&self) -> Result<AzureRestClient, BlobrsError> {
+        AzureRestClient::new(&self.account, self.access_key.expose())?
+            .with_retry(self.retry)
+            .with_timeouts(self.timeouts)
+    }
+}
+
+/// A blob whose path matched the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    /// Label of the source the blob was found in.
+    pub source: String,
+    /// Storage account name.
+    pub account: String,
+    /// Container name.
+    pub container: String,
+    /// Full blob path within the container.
+    pub path: String,
+    /// Blob size in bytes; unknown for tag matches.
+    pub size: Option<u64>,
+}
+
+/// Aggregated results of a search.
+#[derive(Debug, Clone, Default)]
+pub struct SearchResults {
+    /// Matches, ordered by source, container, then path.
+    pub hits: Vec<SearchHit>,
+    /// Accounts or containers that could not be searched.
+    pub errors: Vec<String>,
+    /// Whether matches were dropped because [`MAX_SEARCH_HITS`] was reached.
+    pub truncated: bool,
+}
+
+/// Case-insensitive substring match of `query` against a blob path.
+#[must_use]
+pub fn matches_query(path: &str, query: &str) -> bool {
+    path.to_lowercase().contains(&query.to_lowercase())
+}
+
+/// Search every container of every source concurrently for blob paths containing `query`.
+pub async fn search_accounts(sources: &[SearchSource], query: &str) -> SearchResults {
+    let per_source =
+        futures::future::join_all(sources.iter().map(|source| search_account(source, query))).await;
+
+    let mut results = SearchResults::default();
+    for (hits, errors) in per_source {
+        results.hits.extend(hits);
+        results.errors.extend(errors);
+    }
+
+    results.hits.sort_by(|a, b| {
+        (&a.source, &a.container, &a.path).cmp(&(&b.source, &b.container, &b.path))
+    });
+    if results.hits.len() > MAX_SEARCH_HITS {
+        results.hits.truncate(MAX_SEARCH_HITS);
+        results.truncated = true;
+    }
+    results
+}
+
+/// Search all containers of one account.
+async fn search_account(source: &SearchSource, query: &str) -> (Vec<SearchHit>, Vec<String>) {
+    let containers = match source.rest_client() {
+        Ok(client) => client.list_containers().await,
+        Err(e) => Err(e),
+    };
+    let containers = match containers {
+        Ok(containers) => containers,
+        Err(e) => return (Vec::new(), vec![format!("{}: {e}", source.label)]),
+    };
+
+    let per_container: Vec<(String, Result<Vec<SearchHit>, BlobrsError>)> =
+        stream::iter(containers)
+            .map(|container| async move {
+                let found = search_container(source, &container.name, query).await;
+                (container.name, found)
+            })
+            .buffer_unordered(CONTAINER_CONCURRENCY)
+            .collect()
+            .await;
+
+    let mut hits = Vec::new();
+    let mut errors = Vec::new();
+    for (container, result) in per_container {
+        match result {
+            Ok(found) => hits.extend(found),
+            Err(e) => errors.push(format!("{}/{container}: {e}", source.label)),
+        }
+    }
+    (hits, errors)
+}
+
+/// List one container and keep the blobs that match.
+async fn search_container(
+    source: &SearchSource,
+    container: &str,
+    query: &str,
+) -> Result<Vec<SearchHit>, BlobrsError> {
+    let store = container_store(
+        &source.account,
+        source.access_key.expose(),
+        container,
+        source.retry,
+        source.timeouts,
+    )?;
+
+    let mut hits = Vec::new();
+    let mut listing = store.list(None);
+    while let Some(result) = listing.next().await {
+        let meta = result?;
+        if matches_query(meta.location.as_ref(), query) {
+            hits.push(SearchHit {
+                source: source.label.clone(),
+                account: source.account.clone(),
+                container: container.to_string(),
+                path: meta.location.to_string(),
+                size: Some(meta.size),
+            });
+            if hits.len() > MAX_SEARCH_HITS {
+                break;
+            }
+        }
+    }
+    Ok(hits)
+}
+
+/// Matches found so far by a [`FolderSearch`], and how it ended.
+#[derive(Debug, Default)]
+struct FolderProgress {
+    hits: Vec<SearchHit>,
+    /// Blobs looked at so far.
+    scanned: usize,
+    complete: bool,
+    truncated: bool,
+    error: Option<String>,
+}
+
+/// A search through every blob under a folder, listed in the background so matches show up as
+/// they are found.
+///
+/// Dropping every clone stops the listing, so changing the query does not keep searching.
+#[derive(Debug, Clone)]
+pub struct FolderSearch {
+    progress: Arc<Mutex<FolderProgress>>,
+    _task: Arc<StopOnDrop>,
+}
+
+impl FolderSearch {
+    /// Search the blobs under `prefix` (the whole container when empty) of `container` in
+    /// `account` for paths containing `query`.
+    pub fn start(
+        store: Arc<dyn ObjectStore>,
+        account: &str,
+        container: &str,
+        prefix: &str,
+        query: &str,
+    ) -> Self {
+        let progress = Arc::new(Mutex::new(FolderProgress::default()));
+        let task = tokio::spawn({
+            let progress = progress.clone();
+            let hit = SearchHit {
+                source: account.to_string(),
+                account: account.to_string(),
+                container: container.to_string(),
+                path: String::new(),
+                size: None,
+            };
+            let prefix = (!prefix.is_empty()).then(|| ObjectPath::from(prefix));
+            let query = query.to_string();
+            async move {
+                let mut listing = store.list(prefix.as_ref());
+                while let Some(result) = listing.next().await {
+                    let mut progress = progress
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner);
+                    let meta = match result {
+                        Ok(meta) => meta,
+                        Err(e) => {
+                            progress.error = Some(format!("{}: {e}", hit.container));
+                            return;
+                        }
+                    };
+                    progress.scanned += 1;
+                    if matches_query(meta.location.as_ref(), &query) {
+                        if progress.hits.len() >= MAX_SEARCH_HITS {
+                            progress.truncated = true;
+                            break;
+                        }
+                        progress.hits.push(SearchHit {
+                            path: meta.location.to_string(),
+                            size: Some(meta.size),
+                            ..hit.clone()
+                        });
+                    }
+                }
+                progress
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .complete = true;
+            }
+        });
+        Self {
+            progress,
+            _task: Arc::new(StopOnDrop(task.abort_handle())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FolderProgress> {
+        self.progress
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Matches found since the last call, in path order.
+    #[must_use]
+    pub fn take_hits(&self) -> Vec<SearchHit> {
+        std::mem::take(&mut self.lock().hits)
+    }
+
+    /// Blobs looked at so far.
+    #[must_use]
+    pub fn scanned(&self) -> usize {
+        self.lock().scanned
+    }
+
+    /// Whether every blob has been looked at, or the search stopped early.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        let progress = self.lock();
+        progress.complete || progress.error.is_some()
+    }
+
+    /// Whether the search stopped at [`MAX_SEARCH_HITS`] matches.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.lock().truncated
+    }
+
+    /// Why the search stopped early, if it did.
+    #[must_use]
+    pub fn error(&self) -> Option<String> {
+        self.lock().error.clone()
+    }
+}
+
+/// Find the blobs in `container` whose index tags match `expression`, using the service-side
+/// Find Blobs by Tags operation.
+pub async fn search_tags(
+    source: &SearchSource,
+    container: &str,
+    expression: &str,
+) -> SearchResults {
+    let found = match source.rest_client() {
+        Ok(client) => client.find_blobs_by_tags(container, expression).await,
+        Err(e) => Err(e),
+    };
+
+    let mut results = SearchResults::default();
+    match found {
+        Ok(mut names) => {
+            names.sort();
+            results.truncated = names.len() > MAX_SEARCH_HITS;
+            names.truncate(MAX_SEARCH_HITS);
+            results.hits = names
+                .into_iter()
+                .map(|path| SearchHit {
+                    source: source.label.clone(),
+                    account: source.account.clone(),
+                    container: container.to_string(),
+                    path,
+                    size: None,
+                })
+                .collect();
+        }
+        Err(e) => results
+            .errors
+            .push(format!("{}/{container}: {e}", source.label)),
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_matching_ignores_case() {
+        assert!(matches_query("raw/2024/Sales_Q1.parquet", "sales_q1"));
+        assert!(matches_query("raw/2024/sales.csv", "2024/SAL"));
+        assert!(!matches_query("raw/2024/sales.csv", "orders"));
+    }
+
+    #[tokio::test]
+    async fn folder_search_finds_matches_at_any_depth_under_the_folder() {
+        use object_store::{ObjectStoreExt, PutPayload, memory::InMemory};
+
+        let store = Arc::new(InMemory::new());
+        for path in [
+            "raw/sales.csv",
+            "raw/2024/q1/sales.csv",
+            "raw/2024/orders.csv",
+            "curated/sales.csv",
+        ] {
+            store
+                .put(&ObjectPath::from(path), PutPayload::from_static(b"x"))
+                .await
+                .unwrap();
+        }
+
+        let search = FolderSearch::start(store, "acct", "data", "raw/", "SALES");
+        while !search.is_finished() {
+            tokio::task::yield_now().await;
+        }
+
+        let paths: Vec<String> = search.take_hits().into_iter().map(|hit| hit.path).collect();
+        assert_eq!(paths, ["raw/2024/q1/sales.csv", "raw/sales.csv"]);
+        assert_eq!(search.scanned(), 3);
+        assert!(!search.is_truncated());
+        assert!(search.take_hits().is_empty());
+        assert_eq!(search.error(), None);
+        assert_eq!(SearchScope::Tags.next(), SearchScope::Accounts);
+    }
+}
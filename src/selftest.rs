@@ -0,0 +1,247 @@
+//! End-to-end check of the storage layer against a local Azurite emulator.
+//!
+//! Run with `blobrs --self-test` while Azurite is listening (e.g.
+//! `docker run -p 10000:10000 mcr.microsoft.com/azure-storage/azurite azurite-blob --blobHost 0.0.0.0`).
+//! A scratch container is created, exercised and removed again.
+
+use crate::azure::AzureRestClient;
+use crate::preview::{PreviewData, PreviewFileType, parse_preview};
+use crate::sync::{self, SyncMode};
+use color_eyre::eyre::eyre;
+use object_store::azure::{MicrosoftAzure, MicrosoftAzureBuilder};
+use object_store::list::{PaginatedListOptions, PaginatedListStore};
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, ObjectStoreExt};
+use std::path::Path;
+
+/// Well-known Azurite development account.
+pub const EMULATOR_ACCOUNT: &str = "devstoreaccount1";
+
+/// Well-known Azurite development account key.
+pub const EMULATOR_ACCESS_KEY: &str =
+    "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==";
+
+/// Environment variable with the emulator's blob URL, shared with `object_store`.
+pub const EMULATOR_URL_VAR: &str = "AZURITE_BLOB_STORAGE_URL";
+
+/// Blob URL used when [`EMULATOR_URL_VAR`] is not set.
+const DEFAULT_EMULATOR_URL: &str = "http://127.0.0.1:10000";
+
+/// Files uploaded by the self-test, relative to the scratch container.
+const FIXTURES: [(&str, &str); 3] = [
+    ("data/table.csv", "id,name\n1,alpha\n2,beta\n"),
+    ("data/readme.txt", "blobrs self-test\n"),
+    ("config.json", "{\"enabled\": true}\n"),
+];
+
+/// Run every check, printing one line per step.
+///
+/// # Errors
+///
+/// Returns an error describing the first failed step.
+pub async fn run() -> color_eyre::Result<()> {
+    let blob_url =
+        std::env::var(EMULATOR_URL_VAR).unwrap_or_else(|_| DEFAULT_EMULATOR_URL.to_string());
+    let container = format!("blobrs-selftest-{}", chrono::Utc::now().timestamp_millis());
+    println!("Self-test against {blob_url}, container {container}");
+
+    let rest = AzureRestClient::emulator(EMULATOR_ACCOUNT, EMULATOR_ACCESS_KEY, &blob_url)?;
+    // object_store reads the emulator URL from the same variable
+    let store = MicrosoftAzureBuilder::new()
+        .with_use_emulator(true)
+        .with_account(EMULATOR_ACCOUNT)
+        .with_access_key(EMULATOR_ACCESS_KEY)
+        .with_container_name(&container)
+        .build()?;
+    let scratch = std::env::temp_dir().join(&container);
+
+    step(
+        "create container",
+        rest.create_container(&container).await.map_err(Into::into),
+    )?;
+    let result = run_steps(&rest, &store, &container, &scratch).await;
+
+    // Clean up even when a step failed
+    let _ = std::fs::remove_dir_all(&scratch);
+    let cleanup = rest.delete_container(&container).await.map_err(Into::into);
+    result?;
+    step("delete container", cleanup)?;
+    println!("Self-test passed");
+    Ok(())
+}
+
+/// The checks that need the scratch container to exist.
+async fn run_steps(
+    rest: &AzureRestClient,
+    store: &MicrosoftAzure,
+    container: &str,
+    scratch: &Path,
+) -> color_eyre::Result<()> {
+    step("list containers", list_containers(rest, container).await)?;
+    step("upload", upload(store, &scratch.join("upload")).await)?;
+    step("list folders", list_folders(store).await)?;
+    step("page through listing", page_listing(store).await)?;
+    step("preview", preview(store).await)?;
+    step("blob properties", properties(rest, container).await)?;
+    step("server-side copy", copy(rest, store, container).await)?;
+    step("download", download(store, &scratch.join("download")).await)?;
+    step("delete", delete(store).await)?;
+    Ok(())
+}
+
+/// Print the outcome of a step and turn a failure into an error.
+fn step(name: &str, result: color_eyre::Result<()>) -> color_eyre::Result<()> {
+    match result {
+        Ok(()) => {
+            println!("  ok      {name}");
+            Ok(())
+        }
+        Err(e) => {
+            println!("  FAILED  {name}: {e}");
+            Err(e.wrap_err(format!("{name} failed")))
+        }
+    }
+}
+
+async fn list_containers(rest: &AzureRestClient, container: &str) -> color_eyre::Result<()> {
+    let containers = rest.list_containers().await?;
+    if containers.iter().any(|summary| summary.name == container) {
+        Ok(())
+    } else {
+        Err(eyre!("{container} missing from the container list"))
+    }
+}
+
+/// Upload the fixtures through the same path as sync pushes.
+async fn upload(store: &MicrosoftAzure, local_dir: &Path) -> color_eyre::Result<()> {
+    for (name, contents) in FIXTURES {
+        let path = sync::local_path(local_dir, name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, contents)?;
+    }
+    let local = sync::scan_local(local_dir)?;
+    let plan = sync::plan_sync(&local, &[], SyncMode::Push);
+    let report = sync::apply(store, "", local_dir, &plan).await;
+    if !report.errors.is_empty() {
+        return Err(eyre!(report.errors.join("; ")));
+    }
+    expect_eq("uploaded blobs", report.completed, FIXTURES.len())
+}
+
+async fn list_folders(store: &MicrosoftAzure) -> color_eyre::Result<()> {
+    let root = store.list_with_delimiter(None).await?;
+    let folders: Vec<String> = root
+        .common_prefixes
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    expect_eq("root folders", folders, vec!["data".to_string()])?;
+    expect_eq("root blobs", root.objects.len(), 1)?;
+
+    let data = store
+        .list_with_delimiter(Some(&ObjectPath::from("data")))
+        .await?;
+    expect_eq("blobs in data/", data.objects.len(), 2)
+}
+
+/// List one entry per page, following continuation tokens like large folders do.
+async fn page_listing(store: &MicrosoftAzure) -> color_eyre::Result<()> {
+    let mut token = None;
+    let mut seen = 0;
+    loop {
+        let options = PaginatedListOptions {
+            max_keys: Some(1),
+            page_token: token,
+            ..PaginatedListOptions::default()
+        };
+        let page = store.list_paginated(None, options).await?;
+        seen += page.result.objects.len();
+        token = page.page_token;
+        if token.is_none() {
+            break;
+        }
+    }
+    expect_eq("blobs across pages", seen, FIXTURES.len())
+}
+
+async fn preview(store: &MicrosoftAzure) -> color_eyre::Result<()> {
+    let name = "data/table.csv";
+    let data = store.get(&ObjectPath::from(name)).await?.bytes().await?;
+    match parse_preview(&data, &PreviewFileType::from_extension(name)).map_err(|e| eyre!(e))? {
+        PreviewData::Table(table) => {
+            expect_eq(
+                "csv headers",
+                table.headers,
+                vec!["id".into(), "name".into()],
+            )?;
+            expect_eq("csv rows", table.rows.len(), 2)
+        }
+        other => Err(eyre!("expected a table preview, got {other:?}")),
+    }
+}
+
+async fn properties(rest: &AzureRestClient, container: &str) -> color_eyre::Result<()> {
+    let properties = rest.blob_properties(container, "data/table.csv").await?;
+    expect_eq("size", properties.size, FIXTURES[0].1.len() as u64)?;
+    expect_eq(
+        "content type",
+        properties.content_type.as_deref(),
+        Some("text/csv"),
+    )
+}
+
+async fn copy(
+    rest: &AzureRestClient,
+    store: &MicrosoftAzure,
+    container: &str,
+) -> color_eyre::Result<()> {
+    rest.copy_blob(container, "config.json", container, "copies/config.json")
+        .await?;
+    let copied = store
+        .get(&ObjectPath::from("copies/config.json"))
+        .await?
+        .bytes()
+        .await?;
+    expect_eq("copied contents", &copied[..], FIXTURES[2].1.as_bytes())?;
+    store
+        .delete(&ObjectPath::from("copies/config.json"))
+        .await?;
+    Ok(())
+}
+
+/// Download everything through the same path as sync pulls.
+async fn download(store: &MicrosoftAzure, local_dir: &Path) -> color_eyre::Result<()> {
+    let remote = sync::list_remote(store, "").await?;
+    let plan = sync::plan_sync(&[], &remote, SyncMode::Pull);
+    let report = sync::apply(store, "", local_dir, &plan).await;
+    if !report.errors.is_empty() {
+        return Err(eyre!(report.errors.join("; ")));
+    }
+    for (name, contents) in FIXTURES {
+        let downloaded = std::fs::read_to_string(sync::local_path(local_dir, name))?;
+        expect_eq(name, downloaded.as_str(), contents)?;
+    }
+    Ok(())
+}
+
+async fn delete(store: &MicrosoftAzure) -> color_eyre::Result<()> {
+    for (name, _) in FIXTURES {
+        store.delete(&ObjectPath::from(name)).await?;
+    }
+    let remaining = sync::list_remote(store, "").await?;
+    expect_eq("blobs left after delete", remaining.len(), 0)
+}
+
+fn expect_eq<T: PartialEq + std::fmt::Debug>(
+    what: &str,
+    actual: T,
+    expected: T,
+) -> color_eyre::Result<()> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(eyre!("{what}: expected {expected:?}, got {actual:?}"))
+    }
+}
@@ -0,0 +1,227 @@
+//! Small amount of state persisted between runs (recently used download folders, notes, ...).
+
+use crate::app::SortOrder;
+use blobrs::error::BlobrsError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of recent download destinations to remember.
+pub const MAX_RECENT_DOWNLOAD_DIRS: usize = 5;
+
+/// Maximum number of recently opened containers to remember.
+pub const MAX_RECENT_CONTAINERS: usize = 5;
+
+/// State that is saved to disk as JSON.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistedState {
+    /// Recently used download destinations, most recent first.
+    pub recent_download_dirs: Vec<PathBuf>,
+    /// Recently opened containers as `account/container`, most recent first.
+    pub recent_containers: Vec<String>,
+    /// Notes attached to containers and folders, keyed by `account/container[/prefix]`.
+    pub notes: BTreeMap<String, String>,
+    /// Sort order last picked, used in containers without one of their own.
+    pub sort: SortOrder,
+    /// Sort order picked in each container, keyed by `account/container`.
+    pub container_sorts: BTreeMap<String, SortOrder>,
+    /// Where this state is saved. `None` keeps the state in memory only.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl PersistedState {
+    /// Default location of the state file, e.g. `~/.local/state/blobrs/state.json`.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("blobrs").join("state.json"))
+    }
+
+    /// Load the state from the default location.
+    ///
+    /// A missing or unreadable file yields an empty state that will still be saved there.
+    #[must_use]
+    pub fn load() -> Self {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load the state from `path`, falling back to an empty state.
+    #[must_use]
+    pub fn load_from(path: &Path) -> Self {
+        let mut state: Self = fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        state.path = Some(path.to_path_buf());
+        state
+    }
+
+    /// Write the state back to where it was loaded from.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state directory or file cannot be written.
+    pub fn save(&self) -> Result<(), BlobrsError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                BlobrsError::io(format!("Failed to create {}", parent.display()), e)
+            })?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| BlobrsError::Parse(format!("state: {e}")))?;
+        fs::write(path, json)
+            .map_err(|e| BlobrsError::io(format!("Failed to write {}", path.display()), e))
+    }
+
+    /// Move `dir` to the front of the recent download destinations.
+    pub fn remember_download_dir(&mut self, dir: &Path) {
+        self.recent_download_dirs.retain(|d| d != dir);
+        self.recent_download_dirs.insert(0, dir.to_path_buf());
+        self.recent_download_dirs.truncate(MAX_RECENT_DOWNLOAD_DIRS);
+    }
+
+    /// Move `account/container` to the front of the recently opened containers.
+    pub fn remember_container(&mut self, account: &str, container: &str) {
+        let key = format!("{account}/{container}");
+        self.recent_containers.retain(|c| *c != key);
+        self.recent_containers.insert(0, key);
+        self.recent_containers.truncate(MAX_RECENT_CONTAINERS);
+    }
+
+    /// Recently opened containers of `account`, most recent first.
+    pub fn recent_containers_of<'a>(&'a self, account: &'a str) -> impl Iterator<Item = &'a str> {
+        self.recent_containers.iter().filter_map(move |key| {
+            key.strip_prefix(account)
+                .and_then(|rest| rest.strip_prefix('/'))
+        })
+    }
+
+    /// Remember `order` for `account/container`, and as the order for containers that have
+    /// not had one picked.
+    pub fn remember_sort(&mut self, account: &str, container: &str, order: SortOrder) {
+        self.sort = order;
+        self.container_sorts
+            .insert(format!("{account}/{container}"), order);
+    }
+
+    /// Sort order to open `account/container` with.
+    #[must_use]
+    pub fn sort_for(&self, account: &str, container: &str) -> SortOrder {
+        self.container_sorts
+            .get(&format!("{account}/{container}"))
+            .copied()
+            .unwrap_or(self.sort)
+    }
+
+    /// Set the note for `key`; blank text removes it.
+    pub fn set_note(&mut self, key: &str, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            self.notes.remove(key);
+        } else {
+            self.notes.insert(key.to_string(), text.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::SortCriteria;
+
+    #[test]
+    fn remember_download_dir_dedups_and_caps() {
+        let mut state = PersistedState::default();
+        for i in 0..MAX_RECENT_DOWNLOAD_DIRS + 2 {
+            state.remember_download_dir(Path::new(&format!("/tmp/dir{i}")));
+        }
+        state.remember_download_dir(Path::new("/tmp/dir3"));
+
+        assert_eq!(state.recent_download_dirs.len(), MAX_RECENT_DOWNLOAD_DIRS);
+        assert_eq!(state.recent_download_dirs[0], PathBuf::from("/tmp/dir3"));
+        assert_eq!(
+            state
+                .recent_download_dirs
+                .iter()
+                .filter(|d| d.as_path() == Path::new("/tmp/dir3"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn recent_containers_are_per_account() {
+        let mut state = PersistedState::default();
+        state.remember_container("acct", "raw");
+        state.remember_container("other", "logs");
+        state.remember_container("acct", "curated");
+        state.remember_container("acct", "raw");
+
+        let recent: Vec<_> = state.recent_containers_of("acct").collect();
+        assert_eq!(recent, vec!["raw", "curated"]);
+        assert_eq!(state.recent_containers_of("acc").count(), 0);
+    }
+
+    #[test]
+    fn blank_note_removes_it() {
+        let mut state = PersistedState::default();
+        state.set_note("acct/raw/logs", "  do not delete ");
+        assert_eq!(state.notes["acct/raw/logs"], "do not delete");
+
+        state.set_note("acct/raw/logs", "   ");
+        assert!(state.notes.is_empty());
+    }
+
+    #[test]
+    fn containers_keep_their_own_sort_order() {
+        let mut state = PersistedState::default();
+        let by_size = SortOrder {
+            criteria: SortCriteria::Size,
+            reversed: true,
+        };
+        state.remember_sort("acct", "raw", SortOrder::new(SortCriteria::DateModified));
+        state.remember_sort("acct", "curated", by_size);
+
+        assert_eq!(
+            state.sort_for("acct", "raw"),
+            SortOrder::new(SortCriteria::DateModified)
+        );
+        assert_eq!(state.sort_for("acct", "curated"), by_size);
+        // Containers without one use the order picked last
+        assert_eq!(state.sort_for("other", "raw"), by_size);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("state.json");
+        let mut state = PersistedState::load_from(&path);
+        assert!(state.recent_download_dirs.is_empty());
+
+        state.remember_download_dir(Path::new("/tmp/downloads"));
+        state.set_note("acct/raw", "owned by team X");
+        state.remember_sort("acct", "raw", SortOrder::new(SortCriteria::DateModified));
+        state.save().unwrap();
+
+        let reloaded = PersistedState::load_from(&path);
+        assert_eq!(
+            reloaded.recent_download_dirs,
+            vec![PathBuf::from("/tmp/downloads")]
+        );
+        assert_eq!(reloaded.notes["acct/raw"], "owned by team X");
+        assert_eq!(
+            reloaded.sort_for("acct", "raw"),
+            SortOrder::new(SortCriteria::DateModified)
+        );
+    }
+}
@@ -0,0 +1,539 @@
+//! Synchronisation between a blob prefix and a local directory.
+//!
+//! Files are compared by size and modification time. After every transfer the local file's
+//! modification time is set to the blob's `Last-Modified`, so an unchanged pair compares equal
+//! on the next run and only files edited since then are transferred again.
+
+use crate::error::BlobrsError;
+use crate::transfer::{download_file, upload_file};
+use chrono::{DateTime, Utc};
+use futures::stream::StreamExt;
+use object_store::{GetOptions, ObjectStore, ObjectStoreExt, path::Path as ObjectPath};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Modification times closer than this are considered equal.
+const MTIME_TOLERANCE_SECS: i64 = 1;
+
+/// Which side wins, and whether extra files are deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SyncMode {
+    /// Copy new and changed files both ways; the newer copy wins. Nothing is deleted.
+    #[default]
+    TwoWay,
+    /// Make the prefix match the local directory, deleting blobs missing locally.
+    Push,
+    /// Make the local directory match the prefix, deleting local files missing remotely.
+    Pull,
+}
+
+impl SyncMode {
+    /// Human-readable name.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::TwoWay => "two-way",
+            Self::Push => "push (local → blob)",
+            Self::Pull => "pull (blob → local)",
+        }
+    }
+
+    /// The next mode, for cycling through them in the UI.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::TwoWay => Self::Push,
+            Self::Push => Self::Pull,
+            Self::Pull => Self::TwoWay,
+        }
+    }
+}
+
+/// A local file, relative to the synced directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalEntry {
+    /// Path relative to the directory, with `/` separators.
+    pub relative_path: String,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+}
+
+/// A blob, relative to the synced prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    /// Path relative to the prefix.
+    pub relative_path: String,
+    pub size: u64,
+    pub last_modified: DateTime<Utc>,
+    pub e_tag: Option<String>,
+}
+
+/// What to do with one file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    Upload,
+    Download,
+    DeleteRemote,
+    DeleteLocal,
+}
+
+impl SyncAction {
+    /// Short label used in previews.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Upload => "upload",
+            Self::Download => "download",
+            Self::DeleteRemote => "delete blob",
+            Self::DeleteLocal => "delete local",
+        }
+    }
+}
+
+/// One planned action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncStep {
+    pub action: SyncAction,
+    pub relative_path: String,
+    /// Bytes moved (0 for deletes).
+    pub size: u64,
+    /// ETag the blob is expected to have when downloading it.
+    pub e_tag: Option<String>,
+    /// `Last-Modified` of the blob when downloading it.
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Everything a sync would do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncPlan {
+    /// Actions ordered by path.
+    pub steps: Vec<SyncStep>,
+    /// Files already identical on both sides.
+    pub unchanged: usize,
+}
+
+impl SyncPlan {
+    /// Number of steps with the given action.
+    #[must_use]
+    pub fn count(&self, action: SyncAction) -> usize {
+        self.steps.iter().filter(|s| s.action == action).count()
+    }
+
+    /// Total bytes uploaded and downloaded.
+    #[must_use]
+    pub fn total_bytes(&self) -> u64 {
+        self.steps.iter().map(|s| s.size).sum()
+    }
+}
+
+/// Outcome of applying a plan.
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    /// Steps that completed.
+    pub completed: usize,
+    /// Steps that failed, with the reason.
+    pub errors: Vec<String>,
+}
+
+/// Compare the two sides and decide what to transfer or delete.
+#[must_use]
+pub fn plan_sync(local: &[LocalEntry], remote: &[RemoteEntry], mode: SyncMode) -> SyncPlan {
+    let local: BTreeMap<&str, &LocalEntry> = local
+        .iter()
+        .map(|e| (e.relative_path.as_str(), e))
+        .collect();
+    let remote: BTreeMap<&str, &RemoteEntry> = remote
+        .iter()
+        .map(|e| (e.relative_path.as_str(), e))
+        .collect();
+
+    let mut paths: Vec<&str> = local.keys().chain(remote.keys()).copied().collect();
+    paths.sort_unstable();
+    paths.dedup();
+
+    let mut plan = SyncPlan::default();
+    for path in paths {
+        let action = match (local.get(path), remote.get(path)) {
+            (Some(_), None) if mode == SyncMode::Pull => Some(SyncAction::DeleteLocal),
+            (Some(_), None) => Some(SyncAction::Upload),
+            (None, Some(_)) if mode == SyncMode::Push => Some(SyncAction::DeleteRemote),
+            (None, Some(_)) => Some(SyncAction::Download),
+            (Some(l), Some(r)) => {
+                let time_diff = (l.modified - r.last_modified).num_seconds();
+                if l.size == r.size && time_diff.abs() <= MTIME_TOLERANCE_SECS {
+                    None
+                } else {
+                    match mode {
+                        SyncMode::Push => Some(SyncAction::Upload),
+                        SyncMode::Pull => Some(SyncAction::Download),
+                        SyncMode::TwoWay if time_diff > 0 => Some(SyncAction::Upload),
+                        SyncMode::TwoWay => Some(SyncAction::Download),
+                    }
+                }
+            }
+            (None, None) => None,
+        };
+
+        let Some(action) = action else {
+            plan.unchanged += 1;
+            continue;
+        };
+        let remote_entry = remote.get(path);
+        plan.steps.push(SyncStep {
+            action,
+            relative_path: path.to_string(),
+            size: match action {
+                SyncAction::Upload => local.get(path).map_or(0, |e| e.size),
+                SyncAction::Download => remote_entry.map_or(0, |e| e.size),
+                SyncAction::DeleteRemote | SyncAction::DeleteLocal => 0,
+            },
+            e_tag: remote_entry.and_then(|e| e.e_tag.clone()),
+            last_modified: remote_entry.map(|e| e.last_modified),
+        });
+    }
+    plan
+}
+
+/// The files of the directory a sync in `mode` runs against. Only a pull may name a directory
+/// that does not exist yet, which it creates.
+///
+/// # Errors
+///
+/// Returns an error if the directory is missing and `mode` is not a pull, or cannot be read.
+pub fn scan_sync_root(root: &Path, mode: SyncMode) -> Result<Vec<LocalEntry>, BlobrsError> {
+    if mode == SyncMode::Pull && !root.exists() {
+        return Ok(Vec::new());
+    }
+    scan_local(root)
+}
+
+/// List every file under `root`, recursively.
+///
+/// # Errors
+///
+/// Returns an error if `root` does not exist or a directory cannot be read.
+pub fn scan_local(root: &Path) -> Result<Vec<LocalEntry>, BlobrsError> {
+    if !root.exists() {
+        return Err(BlobrsError::NotFound(format!(
+            "{} does not exist",
+            root.display()
+        )));
+    }
+    let mut entries = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let read_dir = std::fs::read_dir(&dir)
+            .map_err(|e| BlobrsError::io(format!("Failed to read {}", dir.display()), e))?;
+        for entry in read_dir {
+            let entry = entry
+                .map_err(|e| BlobrsError::io(format!("Failed to read {}", dir.display()), e))?;
+            let path = entry.path();
+            let meta = entry
+                .metadata()
+                .map_err(|e| BlobrsError::io(format!("Failed to read {}", path.display()), e))?;
+            if meta.is_dir() {
+                pending.push(path);
+            } else if meta.is_file() {
+                let Ok(relative) = path.strip_prefix(root) else {
+                    continue;
+                };
+                let relative_path = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                entries.push(LocalEntry {
+                    relative_path,
+                    size: meta.len(),
+                    modified: meta.modified().map_or_else(|_| Utc::now(), DateTime::from),
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// List every blob under `prefix`.
+///
+/// # Errors
+///
+/// Returns an error if the listing fails.
+pub async fn list_remote(
+    store: &dyn ObjectStore,
+    prefix: &str,
+) -> Result<Vec<RemoteEntry>, BlobrsError> {
+    let prefix_path = (!prefix.is_empty()).then(|| ObjectPath::from(prefix));
+    let mut listing = store.list(prefix_path.as_ref());
+    let mut entries = Vec::new();
+    while let Some(result) = listing.next().await {
+        let meta = result?;
+        let location = meta.location.as_ref();
+        let relative = location
+            .strip_prefix(prefix)
+            .unwrap_or(location)
+            .trim_start_matches('/');
+        if relative.is_empty() {
+            continue;
+        }
+        entries.push(RemoteEntry {
+            relative_path: relative.to_string(),
+            size: meta.size,
+            last_modified: meta.last_modified,
+            e_tag: meta.e_tag,
+        });
+    }
+    Ok(entries)
+}
+
+/// Carry out a plan, continuing past individual failures.
+pub async fn apply(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    root: &Path,
+    plan: &SyncPlan,
+) -> SyncReport {
+    let mut report = SyncReport::default();
+    for step in &plan.steps {
+        let location = ObjectPath::from(format!("{prefix}{}", step.relative_path));
+        let local_path = local_path(root, &step.relative_path);
+        let result = match step.action {
+            SyncAction::Upload => upload(store, &location, &local_path).await,
+            SyncAction::Download => download(store, &location, &local_path, step).await,
+            SyncAction::DeleteRemote => store.delete(&location).await.map_err(BlobrsError::from),
+            SyncAction::DeleteLocal => tokio::fs::remove_file(&local_path).await.map_err(|e| {
+                BlobrsError::io(format!("Failed to delete {}", local_path.display()), e)
+            }),
+        };
+        match result {
+            Ok(()) => report.completed += 1,
+            Err(e) => report.errors.push(format!(
+                "{} {}: {e}",
+                step.action.label(),
+                step.relative_path
+            )),
+        }
+    }
+    report
+}
+
+/// Local path of a file given its `/`-separated relative path.
+pub(crate) fn local_path(root: &Path, relative_path: &str) -> PathBuf {
+    relative_path
+        .split('/')
+        .fold(root.to_path_buf(), |path, part| path.join(part))
+}
+
+async fn upload(
+    store: &dyn ObjectStore,
+    location: &ObjectPath,
+    path: &Path,
+) -> Result<(), BlobrsError> {
+    upload_file(store, location, path).await?;
+
+    // Match the local time to the blob so the pair compares equal next time
+    let meta = store.head(location).await?;
+    set_modified(path, meta.last_modified)
+}
+
+async fn download(
+    store: &dyn ObjectStore,
+    location: &ObjectPath,
+    path: &Path,
+    step: &SyncStep,
+) -> Result<(), BlobrsError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| BlobrsError::io(format!("Failed to create {}", parent.display()), e))?;
+    }
+    let options = GetOptions {
+        if_match: step.e_tag.clone(),
+        ..GetOptions::default()
+    };
+    download_file(store, location, options, path).await?;
+
+    match step.last_modified {
+        Some(last_modified) => set_modified(path, last_modified),
+        None => Ok(()),
+    }
+}
+
+fn set_modified(path: &Path, modified: DateTime<Utc>) -> Result<(), BlobrsError> {
+    std::fs::File::options()
+        .append(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::from(modified)))
+        .map_err(|e| {
+            BlobrsError::io(
+                format!("Failed to set modification time of {}", path.display()),
+                e,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn local(path: &str, size: u64, secs: i64) -> LocalEntry {
+        LocalEntry {
+            relative_path: path.to_string(),
+            size,
+            modified: Utc.timestamp_opt(secs, 0).unwrap(),
+        }
+    }
+
+    fn remote(path: &str, size: u64, secs: i64) -> RemoteEntry {
+        RemoteEntry {
+            relative_path: path.to_string(),
+            size,
+            last_modified: Utc.timestamp_opt(secs, 0).unwrap(),
+            e_tag: Some(format!("etag-{path}")),
+        }
+    }
+
+    fn actions(plan: &SyncPlan) -> Vec<(SyncAction, &str)> {
+        plan.steps
+            .iter()
+            .map(|s| (s.action, s.relative_path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn two_way_copies_missing_and_newer_files() {
+        let local = [
+            local("same.txt", 3, 1000),
+            local("local-only.txt", 1, 1000),
+            local("edited-locally.txt", 4, 2000),
+            local("edited-remotely.txt", 4, 1000),
+        ];
+        let remote = [
+            remote("same.txt", 3, 1000),
+            remote("remote-only.txt", 2, 1000),
+            remote("edited-locally.txt", 4, 1000),
+            remote("edited-remotely.txt", 5, 2000),
+        ];
+
+        let plan = plan_sync(&local, &remote, SyncMode::TwoWay);
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(
+            actions(&plan),
+            vec![
+                (SyncAction::Upload, "edited-locally.txt"),
+                (SyncAction::Download, "edited-remotely.txt"),
+                (SyncAction::Upload, "local-only.txt"),
+                (SyncAction::Download, "remote-only.txt"),
+            ]
+        );
+        assert_eq!(plan.total_bytes(), 4 + 5 + 1 + 2);
+    }
+
+    #[test]
+    fn push_and_pull_delete_extra_files() {
+        let local = [local("a.txt", 1, 1000), local("b.txt", 1, 1000)];
+        let remote = [remote("b.txt", 2, 2000), remote("c.txt", 1, 1000)];
+
+        let push = plan_sync(&local, &remote, SyncMode::Push);
+        assert_eq!(
+            actions(&push),
+            vec![
+                (SyncAction::Upload, "a.txt"),
+                (SyncAction::Upload, "b.txt"),
+                (SyncAction::DeleteRemote, "c.txt"),
+            ]
+        );
+
+        let pull = plan_sync(&local, &remote, SyncMode::Pull);
+        assert_eq!(
+            actions(&pull),
+            vec![
+                (SyncAction::DeleteLocal, "a.txt"),
+                (SyncAction::Download, "b.txt"),
+                (SyncAction::Download, "c.txt"),
+            ]
+        );
+        assert_eq!(pull.count(SyncAction::Download), 2);
+    }
+
+    #[tokio::test]
+    async fn applied_sync_is_stable() {
+        let store = object_store::memory::InMemory::new();
+        store
+            .put(
+                &ObjectPath::from("data/remote.txt"),
+                b"remote".to_vec().into(),
+            )
+            .await
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(root.join("nested").join("local.txt"), b"local").unwrap();
+
+        let plan = plan_sync(
+            &scan_local(root).unwrap(),
+            &list_remote(&store, "data/").await.unwrap(),
+            SyncMode::TwoWay,
+        );
+        assert_eq!(plan.steps.len(), 2);
+        let report = apply(&store, "data/", root, &plan).await;
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(report.completed, 2);
+
+        assert_eq!(std::fs::read(root.join("remote.txt")).unwrap(), b"remote");
+        let uploaded = store
+            .get(&ObjectPath::from("data/nested/local.txt"))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(&uploaded[..], b"local");
+
+        // Nothing left to do on a second pass
+        let again = plan_sync(
+            &scan_local(root).unwrap(),
+            &list_remote(&store, "data/").await.unwrap(),
+            SyncMode::TwoWay,
+        );
+        assert!(again.steps.is_empty());
+        assert_eq!(again.unchanged, 2);
+    }
+
+    #[tokio::test]
+    async fn a_blob_changed_since_planning_is_not_pulled() {
+        let store = object_store::memory::InMemory::new();
+        let location = ObjectPath::from("data/report.csv");
+        store.put(&location, b"v1".to_vec().into()).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let plan = plan_sync(
+            &[],
+            &list_remote(&store, "data/").await.unwrap(),
+            SyncMode::Pull,
+        );
+        store.put(&location, b"v2".to_vec().into()).await.unwrap();
+
+        let report = apply(&store, "data/", dir.path(), &plan).await;
+        assert_eq!(report.completed, 0);
+        assert_eq!(report.errors.len(), 1);
+        assert!(!dir.path().join("report.csv").exists());
+    }
+
+    #[test]
+    fn only_a_pull_may_target_a_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("missing");
+        assert!(matches!(
+            scan_sync_root(&root, SyncMode::TwoWay),
+            Err(BlobrsError::NotFound(_))
+        ));
+        assert!(matches!(
+            scan_sync_root(&root, SyncMode::Push),
+            Err(BlobrsError::NotFound(_))
+        ));
+        assert!(scan_sync_root(&root, SyncMode::Pull).unwrap().is_empty());
+    }
+}
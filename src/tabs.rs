@@ -0,0 +1,183 @@
+//! Several containers or folders open at once, one shown at a time.
+//!
+//! The shown tab lives in the app's own session, search and sort fields, as it did before there
+//! were tabs; the others wait here with everything needed to show them again, including their
+//! store, path, listing and sort order.
+
+use crate::app::{Search, Session, SortOrder};
+
+/// A location kept open while another tab is shown.
+#[derive(Debug, Clone)]
+pub struct Tab {
+    pub session: Session,
+    pub search: Search,
+    pub sort: SortOrder,
+    pub selected_container_index: usize,
+}
+
+impl Tab {
+    /// Short name for the tab bar, e.g. `raw:/logs` or `containers`.
+    #[must_use]
+    pub fn label(session: &Session) -> String {
+        match session {
+            Session::Selecting => "containers".to_string(),
+            Session::Browsing(state) => format!(
+                "{}:/{}",
+                state.container_name,
+                state.current_path.trim_end_matches('/')
+            ),
+        }
+    }
+}
+
+/// The tabs that are not shown, and where the shown one sits among them.
+#[derive(Debug, Default)]
+pub struct Tabs {
+    /// Every tab but the shown one, in order.
+    hidden: Vec<Tab>,
+    /// Position of the shown tab.
+    active: usize,
+}
+
+impl Tabs {
+    /// Number of tabs, counting the shown one.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.hidden.len() + 1
+    }
+
+    /// Position of the shown tab.
+    #[must_use]
+    pub const fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Keep `shown` and show a new tab after it.
+    pub fn open(&mut self, shown: Tab) {
+        self.hidden.insert(self.active, shown);
+        self.active += 1;
+    }
+
+    /// Keep `shown` and take the next tab to show, wrapping round to the first.
+    pub fn next(&mut self, shown: Tab) -> Tab {
+        let count = self.count();
+        self.hidden.insert(self.active, shown);
+        self.active = (self.active + 1) % count;
+        self.hidden.remove(self.active)
+    }
+
+    /// Keep `shown` and take tab `index` to show.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no tab `index`.
+    pub fn switch_to(&mut self, shown: Tab, index: usize) -> Tab {
+        self.hidden.insert(self.active, shown);
+        self.active = index;
+        self.hidden.remove(index)
+    }
+
+    /// The tab beside the shown one, with its position: the next one, or the previous one
+    /// for the last tab. This is the other pane in the two-pane view.
+    #[must_use]
+    pub fn neighbour(&self) -> Option<(usize, &Tab)> {
+        if self.active < self.hidden.len() {
+            Some((self.active + 1, &self.hidden[self.active]))
+        } else {
+            let index = self.active.checked_sub(1)?;
+            Some((index, &self.hidden[index]))
+        }
+    }
+
+    /// Drop the shown tab and take its neighbour to show instead: the next one, or the
+    /// previous one for the last tab. `None` when it is the only tab.
+    pub fn close(&mut self) -> Option<Tab> {
+        if self.hidden.is_empty() {
+            return None;
+        }
+        if self.active == self.hidden.len() {
+            self.active -= 1;
+        }
+        Some(self.hidden.remove(self.active))
+    }
+
+    /// Labels of every tab in order, with `shown` for the shown one.
+    #[must_use]
+    pub fn labels(&self, shown: String) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .hidden
+            .iter()
+            .map(|tab| Tab::label(&tab.session))
+            .collect();
+        labels.insert(self.active, shown);
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tab(index: usize) -> Tab {
+        Tab {
+            session: Session::Selecting,
+            search: Search::Inactive,
+            sort: SortOrder::default(),
+            selected_container_index: index,
+        }
+    }
+
+    #[test]
+    fn tabs_open_after_the_shown_one_and_cycle_in_order() {
+        let mut tabs = Tabs::default();
+        assert!(tabs.close().is_none());
+
+        // Tabs 0, 1 and 2, with 2 shown
+        tabs.open(tab(0));
+        tabs.open(tab(1));
+        assert_eq!((tabs.count(), tabs.active()), (3, 2));
+
+        let shown = tabs.next(tab(2));
+        assert_eq!((shown.selected_container_index, tabs.active()), (0, 0));
+        let shown = tabs.next(shown);
+        assert_eq!(shown.selected_container_index, 1);
+
+        // Closing tab 1 shows the one after it, and closing the last shows the one before
+        assert_eq!(tabs.close().unwrap().selected_container_index, 2);
+        assert_eq!(tabs.close().unwrap().selected_container_index, 0);
+        assert_eq!(tabs.count(), 1);
+    }
+
+    #[test]
+    fn the_neighbour_is_the_next_tab_or_the_one_before_the_last() {
+        let mut tabs = Tabs::default();
+        assert!(tabs.neighbour().is_none());
+        tabs.open(tab(0));
+        assert_eq!(tabs.neighbour().unwrap().0, 0);
+        tabs.open(tab(1));
+        let shown = tabs.switch_to(tab(2), 0);
+        assert_eq!(shown.selected_container_index, 0);
+        let (index, neighbour) = tabs.neighbour().unwrap();
+        assert_eq!((index, neighbour.selected_container_index), (1, 1));
+        // Switching there and back restores the order
+        let shown = tabs.switch_to(shown, 1);
+        let shown = tabs.switch_to(shown, 0);
+        assert_eq!(shown.selected_container_index, 0);
+        assert_eq!(
+            tabs.labels("shown".to_string()),
+            ["shown", "containers", "containers"]
+        );
+    }
+
+    #[test]
+    fn labels_put_the_shown_tab_in_its_place() {
+        let mut tabs = Tabs::default();
+        tabs.open(tab(0));
+        tabs.open(tab(1));
+        let shown = tabs.next(tab(2));
+        assert_eq!(
+            tabs.labels(format!("shown {}", shown.selected_container_index)),
+            ["shown 0", "containers", "containers"]
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Follow a growing blob like `tail -f`.
+//!
+//! A [`TailView`] polls the blob on a tokio task and fetches only the bytes past the last-read
+//! offset. The task stops by itself once the view is dropped.
+
+use crate::error::BlobrsError;
+use object_store::{GetOptions, GetRange, ObjectStore, ObjectStoreExt, path::Path as ObjectPath};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+
+/// How often the blob is checked for new bytes.
+pub const TAIL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How much of the end of the blob is shown when following starts.
+const INITIAL_BYTES: u64 = 16 * 1024;
+
+/// Older lines are dropped beyond this many.
+const MAX_LINES: usize = 5000;
+
+#[derive(Debug, Default)]
+struct TailState {
+    lines: VecDeque<String>,
+    /// Bytes after the last newline.
+    partial: Vec<u8>,
+    /// Next byte to read.
+    offset: u64,
+    /// Drop everything up to the first newline, because reading started mid-line.
+    skip_to_newline: bool,
+    /// Set when the last poll failed, cleared by the next successful one.
+    error: Option<String>,
+}
+
+impl TailState {
+    fn ingest(&mut self, bytes: &[u8]) {
+        let mut bytes = bytes;
+        if self.skip_to_newline {
+            match bytes.iter().position(|&b| b == b'\n') {
+                Some(end) => {
+                    bytes = &bytes[end + 1..];
+                    self.skip_to_newline = false;
+                }
+                None => return,
+            }
+        }
+        self.partial.extend_from_slice(bytes);
+        let Some(last_newline) = self.partial.iter().rposition(|&b| b == b'\n') else {
+            return;
+        };
+        let rest = self.partial.split_off(last_newline + 1);
+        for line in self.partial.split(|&b| b == b'\n') {
+            let line = String::from_utf8_lossy(line);
+            self.lines
+                .push_back(line.strip_suffix('\r').unwrap_or(&line).to_string());
+        }
+        // `split` yields an empty piece after the final newline
+        self.lines.pop_back();
+        self.partial = rest;
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+}
+
+/// A blob being followed.
+#[derive(Debug, Clone)]
+pub struct TailView {
+    /// Full blob path.
+    pub blob_path: String,
+    state: Arc<Mutex<TailState>>,
+}
+
+impl TailView {
+    /// Start following `blob_path`, beginning with the last few KiB.
+    #[must_use]
+    pub fn start(object_store: Arc<dyn ObjectStore>, blob_path: String) -> Self {
+        let view = Self {
+            blob_path: blob_path.clone(),
+            state: Arc::new(Mutex::new(TailState::default())),
+        };
+        let state = Arc::downgrade(&view.state);
+        tokio::spawn(async move {
+            follow(object_store.as_ref(), &ObjectPath::from(blob_path), state).await;
+        });
+        view
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TailState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Every line read so far, including an unfinished last line.
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        let state = self.lock();
+        let mut lines: Vec<String> = state.lines.iter().cloned().collect();
+        if !state.partial.is_empty() {
+            lines.push(String::from_utf8_lossy(&state.partial).into_owned());
+        }
+        lines
+    }
+
+    /// Bytes of the blob read so far, counting from its start.
+    #[must_use]
+    pub fn offset(&self) -> u64 {
+        self.lock().offset
+    }
+
+    /// Why the last poll failed, if it did.
+    #[must_use]
+    pub fn error(&self) -> Option<String> {
+        self.lock().error.clone()
+    }
+}
+
+/// Poll until the view owning `state` is dropped.
+async fn follow(store: &dyn ObjectStore, path: &ObjectPath, state: Weak<Mutex<TailState>>) {
+    let mut first = true;
+    loop {
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+        let result = poll(store, path, &state, first).await;
+        // Keep starting near the end until the blob could be read once
+        first &= result.is_err();
+        state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .error = result.err().map(|e| e.to_string());
+        drop(state);
+        tokio::time::sleep(TAIL_POLL_INTERVAL).await;
+    }
+}
+
+/// Read whatever was appended since the last poll. The first poll starts near the end.
+async fn poll(
+    store: &dyn ObjectStore,
+    path: &ObjectPath,
+    state: &Mutex<TailState>,
+    first: bool,
+) -> Result<(), BlobrsError> {
+    let size = store.head(path).await?.size;
+    let offset = {
+        let mut state = state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if first {
+            state.offset = size.saturating_sub(INITIAL_BYTES);
+            state.skip_to_newline = state.offset > 0;
+        } else if size < state.offset {
+            // Replaced by a shorter blob: start over
+            state
+                .lines
+                .push_back("--- blob was truncated ---".to_string());
+            state.partial.clear();
+            state.offset = 0;
+        }
+        state.offset
+    };
+    if size == offset {
+        return Ok(());
+    }
+
+    let options = GetOptions {
+        range: Some(GetRange::Bounded(offset..size)),
+        ..GetOptions::default()
+    };
+    let bytes = store.get_opts(path, options).await?.bytes().await?;
+    let mut state = state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    state.ingest(&bytes);
+    state.offset = offset + bytes.len() as u64;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+
+    #[test]
+    fn ingest_splits_lines_across_chunks() {
+        let mut state = TailState {
+            skip_to_newline: true,
+            ..TailState::default()
+        };
+        state.ingest(b"tail of a cut line\nfirst\r\nsec");
+        state.ingest(b"ond\n\nthi");
+        assert_eq!(state.lines, ["first", "second", ""]);
+        assert_eq!(state.partial, b"thi");
+    }
+
+    #[tokio::test]
+    async fn polls_fetch_only_appended_bytes() {
+        let store = InMemory::new();
+        let path = ObjectPath::from("logs/app.log");
+        let state = Mutex::new(TailState::default());
+
+        store.put(&path, "one\ntwo\n".into()).await.unwrap();
+        poll(&store, &path, &state, true).await.unwrap();
+        store
+            .put(&path, "one\ntwo\nthree\nfo".into())
+            .await
+            .unwrap();
+        poll(&store, &path, &state, false).await.unwrap();
+        {
+            let state = state.lock().unwrap();
+            assert_eq!(state.lines, ["one", "two", "three"]);
+            assert_eq!(state.offset, 16);
+        }
+
+        // A shorter blob starts over from the beginning
+        store.put(&path, "new\n".into()).await.unwrap();
+        poll(&store, &path, &state, false).await.unwrap();
+        let state = state.lock().unwrap();
+        assert_eq!(state.lines.back().map(String::as_str), Some("new"));
+        assert!(state.partial.is_empty());
+    }
+}
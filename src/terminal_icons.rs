@@ -1,54 +1,158 @@
+use ratatui::style::Color;
 use std::env;
 
+/// Kinds of blob told apart by their extension, each with its own icon and colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    Parquet,
+    Csv,
+    Json,
+    Image,
+    Archive,
+    Code,
+    Text,
+}
+
+impl FileType {
+    /// The type of the blob `name`, from its extension; `None` for anything else.
+    #[must_use]
+    pub fn of(name: &str) -> Option<Self> {
+        let (_, extension) = name.rsplit_once('.')?;
+        let kind = match extension.to_ascii_lowercase().as_str() {
+            "parquet" | "avro" | "orc" | "arrow" | "feather" => Self::Parquet,
+            "csv" | "tsv" | "xlsx" | "xls" => Self::Csv,
+            "json" | "jsonl" | "ndjson" | "geojson" => Self::Json,
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "tif" | "tiff" | "ico" => {
+                Self::Image
+            }
+            "zip" | "gz" | "tgz" | "tar" | "bz2" | "xz" | "zst" | "7z" | "rar" => Self::Archive,
+            "py" | "rs" | "js" | "ts" | "sh" | "sql" | "go" | "java" | "c" | "cpp" | "h" | "cs"
+            | "rb" | "ipynb" | "yaml" | "yml" | "toml" | "xml" | "html" => Self::Code,
+            "txt" | "log" | "md" | "rst" => Self::Text,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Colour of the type's icon in listings. These are theme roles, so themes recolour them.
+    #[must_use]
+    pub const fn color(self) -> Color {
+        match self {
+            Self::Parquet => Color::Cyan,
+            Self::Csv => Color::Green,
+            Self::Json => Color::Yellow,
+            Self::Image => Color::Magenta,
+            Self::Archive => Color::Red,
+            Self::Code => Color::Blue,
+            Self::Text => Color::Gray,
+        }
+    }
+}
+
 /// Icon set for different terminal capabilities
 #[derive(Debug, Clone)]
 pub struct IconSet {
     pub folder: &'static str,
+    /// Blobs of a type without an icon of its own in [`Self::file_types`].
     pub file: &'static str,
-    pub loading: &'static str,
+    /// Icons for particular file types.
+    pub file_types: &'static [(FileType, &'static str)],
+    /// Frames of the activity spinner, shown in turn while something is in progress.
+    pub spinner: &'static [&'static str],
     pub error: &'static str,
     pub success: &'static str,
     pub empty: &'static str,
     pub search: &'static str,
     pub refresh: &'static str,
+    pub trash: &'static str,
 }
 
 impl IconSet {
+    /// Nerd Font glyphs, for terminals set up with a patched font. Never detected, only chosen
+    /// with `BLOBRS_ICONS=nerd`.
+    pub const NERD: IconSet = IconSet {
+        folder: "\u{f07b}",
+        file: "\u{f15b}",
+        file_types: &[
+            (FileType::Parquet, "\u{f1c0}"),
+            (FileType::Csv, "\u{f1c3}"),
+            (FileType::Json, "\u{e60b}"),
+            (FileType::Image, "\u{f1c5}"),
+            (FileType::Archive, "\u{f1c6}"),
+            (FileType::Code, "\u{f1c9}"),
+            (FileType::Text, "\u{f15c}"),
+        ],
+        spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+        error: "\u{f057}",
+        success: "\u{f058}",
+        empty: "\u{f115}",
+        search: "\u{f002}",
+        refresh: "\u{f021}",
+        trash: "\u{f1f8}",
+    };
+
     /// Fancy Unicode/Emoji icons for modern terminals
     pub const UNICODE: IconSet = IconSet {
         folder: "📁",
         file: "📄",
-        loading: "🔄",
+        file_types: &[
+            (FileType::Parquet, "📊"),
+            (FileType::Csv, "📊"),
+            (FileType::Json, "📋"),
+            (FileType::Image, "📷"),
+            (FileType::Archive, "📦"),
+            (FileType::Code, "📜"),
+            (FileType::Text, "📝"),
+        ],
+        spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
         error: "❌",
         success: "✅",
         empty: "📭",
         search: "🔍",
         refresh: "🔄",
+        trash: "🗑",
     };
 
     /// ASCII fallback icons for basic terminals
     pub const ASCII: IconSet = IconSet {
         folder: "[DIR]",
         file: "[FILE]",
-        loading: "[LOADING]",
+        file_types: &[],
+        spinner: &["[|]", "[/]", "[-]", "[\\]"],
         error: "[ERROR]",
         success: "[OK]",
         empty: "[EMPTY]",
         search: "[SEARCH]",
         refresh: "[REFRESH]",
+        trash: "[DELETED]",
     };
 
     /// Minimal symbols for very basic terminals
     pub const MINIMAL: IconSet = IconSet {
         folder: "D",
         file: "F",
-        loading: "*",
+        file_types: &[],
+        spinner: &["|", "/", "-", "\\"],
         error: "!",
         success: "+",
         empty: "-",
         search: "?",
         refresh: "~",
+        trash: "x",
     };
+
+    /// Icon for the blob `name`: its file type's, or the generic file icon.
+    #[must_use]
+    pub fn file_icon(&self, name: &str) -> &'static str {
+        FileType::of(name)
+            .and_then(|kind| {
+                self.file_types
+                    .iter()
+                    .find(|(of, _)| *of == kind)
+                    .map(|(_, icon)| *icon)
+            })
+            .unwrap_or(self.file)
+    }
 }
 
 /// Detect terminal capabilities and return appropriate icon set
@@ -57,6 +161,7 @@ pub fn detect_terminal_icons() -> IconSet {
     // Check for explicit override first
     if let Ok(val) = env::var("BLOBRS_ICONS") {
         match val.to_lowercase().as_str() {
+            "nerd" | "nerdfont" | "nerd-font" => return IconSet::NERD,
             "unicode" | "emoji" | "fancy" => return IconSet::UNICODE,
             "ascii" => return IconSet::ASCII,
             "minimal" | "basic" => return IconSet::MINIMAL,
@@ -162,6 +267,20 @@ mod tests {
         assert_eq!(minimal.folder, "D");
     }
 
+    #[test]
+    fn blobs_get_their_file_types_icon_or_the_generic_one() {
+        assert_eq!(FileType::of("data/part-0.PARQUET"), Some(FileType::Parquet));
+        assert_eq!(FileType::of("archive.tar.gz"), Some(FileType::Archive));
+        assert_eq!(FileType::of("Makefile"), None);
+        assert_eq!(FileType::of("notes.unknown"), None);
+
+        assert_eq!(IconSet::NERD.file_icon("report.csv"), "\u{f1c3}");
+        assert_eq!(IconSet::NERD.file_icon("Makefile"), IconSet::NERD.file);
+        assert_eq!(IconSet::UNICODE.file_icon("photo.jpg"), "📷");
+        // Sets without type icons fall back to the generic one
+        assert_eq!(IconSet::ASCII.file_icon("report.csv"), "[FILE]");
+    }
+
     #[test]
     fn test_detection_with_override() {
         unsafe {
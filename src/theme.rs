@@ -0,0 +1,233 @@
+//! Colour themes.
+//!
+//! The UI draws with ten named terminal colours, each standing for a role: black for popup
+//! backgrounds, white for text, cyan for the selection and accents, and so on. A theme maps
+//! each role to the colour it is shown in, after the frame is drawn, so a light terminal can
+//! get dark text on light popups without every widget knowing about themes.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+
+/// A built-in theme.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// The terminal's own colours, for dark backgrounds.
+    #[default]
+    Dark,
+    /// Dark text on light popups, for light backgrounds.
+    Light,
+    /// Solarized dark.
+    Solarized,
+    /// Bright colours on black.
+    HighContrast,
+}
+
+/// A colour in the config: a name such as `blue` or `light-red`, `#rrggbb`, or a 256-colour
+/// index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigColor(pub Color);
+
+impl<'de> Deserialize<'de> for ConfigColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse()
+            .map(Self)
+            .map_err(|_| serde::de::Error::custom(format!("`{text}` is not a colour")))
+    }
+}
+
+/// Colours set in the config, each replacing the chosen theme's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorOverrides {
+    pub background: Option<ConfigColor>,
+    pub text: Option<ConfigColor>,
+    pub accent: Option<ConfigColor>,
+    pub highlight: Option<ConfigColor>,
+    pub muted: Option<ConfigColor>,
+    pub subtle: Option<ConfigColor>,
+    pub success: Option<ConfigColor>,
+    pub error: Option<ConfigColor>,
+    pub special: Option<ConfigColor>,
+    pub info: Option<ConfigColor>,
+}
+
+/// The `[theme]` section of the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub name: ThemeName,
+    pub colors: ColorOverrides,
+}
+
+/// The colour of each role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Popup backgrounds, and text on the selection.
+    pub background: Color,
+    pub text: Color,
+    /// The selection, borders of focused popups, key hints and prompts.
+    pub accent: Color,
+    /// Titles, warnings and folders.
+    pub highlight: Color,
+    /// Borders, hints and the list selection bar.
+    pub muted: Color,
+    /// Secondary text.
+    pub subtle: Color,
+    pub success: Color,
+    pub error: Color,
+    /// Recently modified blobs, aborted jobs and other rare states.
+    pub special: Color,
+    pub info: Color,
+}
+
+impl Theme {
+    /// A built-in theme.
+    #[must_use]
+    pub const fn named(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self {
+                background: Color::Black,
+                text: Color::White,
+                accent: Color::Cyan,
+                highlight: Color::Yellow,
+                muted: Color::DarkGray,
+                subtle: Color::Gray,
+                success: Color::Green,
+                error: Color::Red,
+                special: Color::Magenta,
+                info: Color::Blue,
+            },
+            ThemeName::Light => Self {
+                background: Color::White,
+                text: Color::Black,
+                accent: Color::Blue,
+                highlight: Color::Indexed(130),
+                muted: Color::Gray,
+                subtle: Color::DarkGray,
+                success: Color::Indexed(28),
+                error: Color::Red,
+                special: Color::Magenta,
+                info: Color::Indexed(25),
+            },
+            ThemeName::Solarized => Self {
+                background: Color::Rgb(0x00, 0x2b, 0x36),
+                text: Color::Rgb(0x93, 0xa1, 0xa1),
+                accent: Color::Rgb(0x2a, 0xa1, 0x98),
+                highlight: Color::Rgb(0xb5, 0x89, 0x00),
+                muted: Color::Rgb(0x58, 0x6e, 0x75),
+                subtle: Color::Rgb(0x83, 0x94, 0x96),
+                success: Color::Rgb(0x85, 0x99, 0x00),
+                error: Color::Rgb(0xdc, 0x32, 0x2f),
+                special: Color::Rgb(0xd3, 0x36, 0x82),
+                info: Color::Rgb(0x26, 0x8b, 0xd2),
+            },
+            ThemeName::HighContrast => Self {
+                background: Color::Black,
+                text: Color::White,
+                accent: Color::LightCyan,
+                highlight: Color::LightYellow,
+                muted: Color::Gray,
+                subtle: Color::White,
+                success: Color::LightGreen,
+                error: Color::LightRed,
+                special: Color::LightMagenta,
+                info: Color::LightBlue,
+            },
+        }
+    }
+
+    /// Each role as the UI draws it, with the colour this theme shows it in.
+    fn roles(&self) -> [(Color, Color); 10] {
+        [
+            (Color::Black, self.background),
+            (Color::White, self.text),
+            (Color::Cyan, self.accent),
+            (Color::Yellow, self.highlight),
+            (Color::DarkGray, self.muted),
+            (Color::Gray, self.subtle),
+            (Color::Green, self.success),
+            (Color::Red, self.error),
+            (Color::Magenta, self.special),
+            (Color::Blue, self.info),
+        ]
+    }
+
+    /// The colour to show for `color` as drawn by the UI.
+    #[must_use]
+    pub fn map(&self, color: Color) -> Color {
+        self.roles()
+            .into_iter()
+            .find(|(role, _)| *role == color)
+            .map_or(color, |(_, shown)| shown)
+    }
+
+    /// Recolour the drawn frame in `area`.
+    pub fn apply(&self, area: Rect, buf: &mut Buffer) {
+        if *self == Self::named(ThemeName::Dark) {
+            return;
+        }
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = &mut buf[(x, y)];
+                cell.fg = self.map(cell.fg);
+                cell.bg = self.map(cell.bg);
+            }
+        }
+    }
+}
+
+impl From<&ThemeConfig> for Theme {
+    fn from(config: &ThemeConfig) -> Self {
+        let theme = Self::named(config.name);
+        let colors = &config.colors;
+        let pick = |color: Option<ConfigColor>, default| color.map_or(default, |c| c.0);
+        Self {
+            background: pick(colors.background, theme.background),
+            text: pick(colors.text, theme.text),
+            accent: pick(colors.accent, theme.accent),
+            highlight: pick(colors.highlight, theme.highlight),
+            muted: pick(colors.muted, theme.muted),
+            subtle: pick(colors.subtle, theme.subtle),
+            success: pick(colors.success, theme.success),
+            error: pick(colors.error, theme.error),
+            special: pick(colors.special, theme.special),
+            info: pick(colors.info, theme.info),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Style;
+
+    #[test]
+    fn themes_recolour_each_role() {
+        let light = Theme::named(ThemeName::Light);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buf[(0, 0)].set_style(Style::default().fg(Color::White).bg(Color::Black));
+        buf[(1, 0)].set_style(Style::default().fg(Color::Yellow).bg(Color::Cyan));
+        light.apply(buf.area, &mut buf);
+
+        assert_eq!(
+            (buf[(0, 0)].fg, buf[(0, 0)].bg),
+            (Color::Black, Color::White)
+        );
+        assert_eq!(
+            (buf[(1, 0)].fg, buf[(1, 0)].bg),
+            (Color::Indexed(130), Color::Blue)
+        );
+        // The terminal's own colours are left alone
+        assert_eq!(
+            (buf[(2, 0)].fg, buf[(2, 0)].bg),
+            (Color::Reset, Color::Reset)
+        );
+        // The dark theme draws the roles as they are
+        let dark = Theme::named(ThemeName::Dark);
+        assert_eq!(dark.map(Color::Cyan), Color::Cyan);
+    }
+}
@@ -0,0 +1,867 @@
+//! Background folder transfers with per-file status, and uploads of single files.
+//!
+//! A [`TransferJob`] downloads its files one at a time on a tokio task. Individual files can be
+//! skipped, aborted while running, or requeued without cancelling the rest of the job. Every job
+//! reports into the session's [`TransferStats`] for the live throughput readout.
+
+use crate::azure::{AzureRestClient, MAX_BATCH_SIZE};
+use crate::cache::BlobCache;
+use crate::error::BlobrsError;
+use futures::stream::StreamExt;
+use object_store::{
+    Attribute, Attributes, GetOptions, ObjectStore, ObjectStoreExt, PutMultipartOptions,
+    PutOptions, PutResult, WriteMultipart, path::Path as ObjectPath,
+};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Blobs deleted at once by [`delete_batched`] when they cannot be batched.
+const DELETE_CONCURRENCY: usize = 16;
+
+/// How many Blob Batch delete requests are in flight at the same time.
+const DELETE_BATCH_CONCURRENCY: usize = 4;
+
+/// Size of each block of a multipart upload; smaller files are uploaded in one request.
+const UPLOAD_PART_SIZE: usize = 10 * 1024 * 1024;
+
+/// Blocks of one upload in flight at the same time.
+const UPLOAD_CONCURRENCY: usize = 8;
+
+/// Status of a single file within a job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Skipped,
+    Aborted,
+}
+
+impl FileStatus {
+    /// Whether the file has stopped without completing and can be requeued.
+    #[must_use]
+    pub const fn can_requeue(&self) -> bool {
+        matches!(self, Self::Failed(_) | Self::Skipped | Self::Aborted)
+    }
+}
+
+/// One file of a folder transfer.
+#[derive(Debug, Clone)]
+pub struct FileTransfer {
+    /// Blob to read.
+    pub location: ObjectPath,
+    /// Version to read, for time-travel downloads.
+    pub version_id: Option<String>,
+    /// ETag from the listing, used as the cache key.
+    pub e_tag: Option<String>,
+    /// Path relative to the job's folder, for display.
+    pub relative_path: String,
+    /// Local file to write.
+    pub destination: PathBuf,
+    /// Blob size in bytes.
+    pub size: u64,
+    /// Bytes written so far.
+    pub bytes_done: u64,
+    pub status: FileStatus,
+}
+
+/// Where a job reads from.
+#[derive(Debug, Clone)]
+pub struct TransferSource {
+    pub object_store: Arc<dyn ObjectStore>,
+    pub account: String,
+    pub container: String,
+    pub cache: Option<BlobCache>,
+    pub stats: TransferStats,
+}
+
+/// How far back [`TransferStats::snapshot`] looks when computing throughput.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(3);
+
+/// Bytes received and requests in flight across every job of a session.
+#[derive(Debug, Clone, Default)]
+pub struct TransferStats {
+    state: Arc<Mutex<StatsState>>,
+}
+
+#[derive(Debug, Default)]
+struct StatsState {
+    active_requests: usize,
+    total_bytes: u64,
+    /// Chunks received within the last [`THROUGHPUT_WINDOW`].
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl StatsState {
+    fn prune(&mut self, now: Instant) {
+        while let Some((at, _)) = self.samples.front()
+            && now.duration_since(*at) > THROUGHPUT_WINDOW
+        {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Throughput and activity at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    /// Average over the last few seconds.
+    pub bytes_per_second: u64,
+    pub active_requests: usize,
+    /// Everything received this session.
+    pub total_bytes: u64,
+}
+
+impl StatsSnapshot {
+    /// Whether anything is being transferred right now.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.active_requests > 0 || self.bytes_per_second > 0
+    }
+}
+
+/// A request counted as in flight until dropped.
+#[derive(Debug)]
+pub struct ActiveRequest {
+    stats: TransferStats,
+}
+
+impl Drop for ActiveRequest {
+    fn drop(&mut self) {
+        let mut state = self.stats.lock();
+        state.active_requests = state.active_requests.saturating_sub(1);
+    }
+}
+
+impl TransferStats {
+    fn lock(&self) -> MutexGuard<'_, StatsState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Count a request as in flight until the returned guard is dropped.
+    #[must_use]
+    pub fn begin_request(&self) -> ActiveRequest {
+        self.lock().active_requests += 1;
+        ActiveRequest {
+            stats: self.clone(),
+        }
+    }
+
+    /// Count `bytes` as received now.
+    pub fn record(&self, bytes: u64) {
+        self.record_at(Instant::now(), bytes);
+    }
+
+    fn record_at(&self, now: Instant, bytes: u64) {
+        let mut state = self.lock();
+        state.total_bytes += bytes;
+        state.samples.push_back((now, bytes));
+        state.prune(now);
+    }
+
+    /// Current throughput and number of requests in flight.
+    #[must_use]
+    pub fn snapshot(&self) -> StatsSnapshot {
+        self.snapshot_at(Instant::now())
+    }
+
+    fn snapshot_at(&self, now: Instant) -> StatsSnapshot {
+        let mut state = self.lock();
+        state.prune(now);
+        let recent: u64 = state.samples.iter().map(|(_, bytes)| bytes).sum();
+        #[allow(clippy::cast_possible_truncation)] // the window is a few seconds
+        let window_ms = THROUGHPUT_WINDOW.as_millis() as u64;
+        StatsSnapshot {
+            bytes_per_second: recent * 1000 / window_ms,
+            active_requests: state.active_requests,
+            total_bytes: state.total_bytes,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct JobState {
+    files: Vec<FileTransfer>,
+    worker_running: bool,
+}
+
+/// Per-status file counts of a job.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JobSummary {
+    pub queued: usize,
+    pub running: usize,
+    pub done: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub aborted: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+impl JobSummary {
+    /// Whether no file is waiting or in progress.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.queued == 0 && self.running == 0
+    }
+}
+
+/// A folder download running in the background.
+#[derive(Debug, Clone)]
+pub struct TransferJob {
+    /// Short description, e.g. the folder name and destination.
+    pub label: String,
+    source: TransferSource,
+    state: Arc<Mutex<JobState>>,
+}
+
+impl TransferJob {
+    /// Create a job; nothing is transferred until [`TransferJob::start`] is called.
+    #[must_use]
+    pub fn new(label: String, source: TransferSource, files: Vec<FileTransfer>) -> Self {
+        Self {
+            label,
+            source,
+            state: Arc::new(Mutex::new(JobState {
+                files,
+                worker_running: false,
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, JobState> {
+        // A poisoned lock only means a worker panicked mid-update; the state is still usable
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Start the background worker unless one is already running.
+    pub fn start(&self) {
+        {
+            let mut state = self.lock();
+            if state.worker_running {
+                return;
+            }
+            state.worker_running = true;
+        }
+        let job = self.clone();
+        tokio::spawn(async move { job.run_worker().await });
+    }
+
+    /// Snapshot of every file in the job.
+    #[must_use]
+    pub fn files(&self) -> Vec<FileTransfer> {
+        self.lock().files.clone()
+    }
+
+    /// Per-status counts and byte totals.
+    #[must_use]
+    pub fn summary(&self) -> JobSummary {
+        let state = self.lock();
+        let mut summary = JobSummary::default();
+        for file in &state.files {
+            match file.status {
+                FileStatus::Queued => summary.queued += 1,
+                FileStatus::Running => summary.running += 1,
+                FileStatus::Done => summary.done += 1,
+                FileStatus::Failed(_) => summary.failed += 1,
+                FileStatus::Skipped => summary.skipped += 1,
+                FileStatus::Aborted => summary.aborted += 1,
+            }
+            summary.bytes_done += file.bytes_done;
+            summary.bytes_total += file.size;
+        }
+        summary
+    }
+
+    /// Skip a queued file.
+    pub fn skip(&self, index: usize) {
+        if let Some(file) = self.lock().files.get_mut(index)
+            && file.status == FileStatus::Queued
+        {
+            file.status = FileStatus::Skipped;
+        }
+    }
+
+    /// Abort a running file (the worker moves on to the next one), or skip it if still queued.
+    pub fn abort(&self, index: usize) {
+        if let Some(file) = self.lock().files.get_mut(index) {
+            match file.status {
+                FileStatus::Running => file.status = FileStatus::Aborted,
+                FileStatus::Queued => file.status = FileStatus::Skipped,
+                _ => {}
+            }
+        }
+    }
+
+    /// Put a failed, skipped, or aborted file back in the queue, restarting the worker if needed.
+    pub fn requeue(&self, index: usize) {
+        let requeued = {
+            let mut state = self.lock();
+            match state.files.get_mut(index) {
+                Some(file) if file.status.can_requeue() => {
+                    file.status = FileStatus::Queued;
+                    file.bytes_done = 0;
+                    true
+                }
+                _ => false,
+            }
+        };
+        if requeued {
+            self.start();
+        }
+    }
+
+    /// Requeue every failed, skipped, or aborted file.
+    pub fn requeue_all(&self) {
+        let count = self.lock().files.len();
+        for index in 0..count {
+            self.requeue(index);
+        }
+    }
+
+    /// Stop the whole job: abort the running file and skip everything queued.
+    pub fn cancel(&self) {
+        let count = self.lock().files.len();
+        for index in 0..count {
+            self.abort(index);
+        }
+    }
+
+    /// Claim the next queued file, or mark the worker as stopped when none remain.
+    fn claim_next(&self) -> Option<(usize, FileTransfer)> {
+        let mut state = self.lock();
+        let next = state
+            .files
+            .iter()
+            .position(|file| file.status == FileStatus::Queued);
+        match next {
+            Some(index) => {
+                let file = &mut state.files[index];
+                file.status = FileStatus::Running;
+                file.bytes_done = 0;
+                Some((index, file.clone()))
+            }
+            None => {
+                state.worker_running = false;
+                None
+            }
+        }
+    }
+
+    fn is_aborted(&self, index: usize) -> bool {
+        self.lock().files[index].status == FileStatus::Aborted
+    }
+
+    fn add_progress(&self, index: usize, bytes: u64) {
+        self.lock().files[index].bytes_done += bytes;
+    }
+
+    async fn run_worker(self) {
+        while let Some((index, file)) = self.claim_next() {
+            let result = self.download(index, &file).await;
+
+            let mut state = self.lock();
+            let entry = &mut state.files[index];
+            // An abort requested mid-transfer wins over the result
+            if entry.status == FileStatus::Running {
+                entry.status = match result {
+                    Ok(()) => FileStatus::Done,
+                    Err(e) => FileStatus::Failed(e.to_string()),
+                };
+            }
+        }
+    }
+
+    /// Download one file, stopping early if it gets aborted.
+    async fn download(&self, index: usize, file: &FileTransfer) -> Result<(), BlobrsError> {
+        if let Some(parent) = file.destination.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                BlobrsError::io(format!("Failed to create {}", parent.display()), e)
+            })?;
+        }
+
+        let cache_key = self.cache_key(file);
+        if let (Some(cache), Some(key)) = (self.source.cache.clone(), cache_key.clone()) {
+            let destination = file.destination.clone();
+            let copied =
+                tokio::task::spawn_blocking(move || cache.get_file(&key, &destination)).await;
+            if let Ok(Some(bytes)) = copied {
+                self.add_progress(index, bytes);
+                return Ok(());
+            }
+        }
+
+        let _request = self.source.stats.begin_request();
+        let options = GetOptions {
+            version: file.version_id.clone(),
+            ..GetOptions::default()
+        };
+        download_with(
+            self.source.object_store.as_ref(),
+            &file.location,
+            options,
+            &file.destination,
+            |bytes| {
+                if self.is_aborted(index) {
+                    return Err(BlobrsError::Cancelled);
+                }
+                self.add_progress(index, bytes);
+                self.source.stats.record(bytes);
+                Ok(())
+            },
+        )
+        .await?;
+
+        if let (Some(cache), Some(key)) = (self.source.cache.clone(), cache_key) {
+            let destination = file.destination.clone();
+            let _ = tokio::task::spawn_blocking(move || cache.put_file(&key, &destination)).await;
+        }
+        Ok(())
+    }
+
+    fn cache_key(&self, file: &FileTransfer) -> Option<String> {
+        file.e_tag.as_deref().map(|e_tag| {
+            BlobCache::key(&[
+                &self.source.account,
+                &self.source.container,
+                file.location.as_ref(),
+                e_tag,
+                "",
+            ])
+        })
+    }
+}
+
+/// Download `location` into the file at `path` one chunk at a time, removing the partial file
+/// if the download fails.
+///
+/// # Errors
+///
+/// Returns an error if the blob cannot be read (including when `options` preconditions are not
+/// met) or the file cannot be written.
+pub async fn download_file(
+    store: &dyn ObjectStore,
+    location: &ObjectPath,
+    options: GetOptions,
+    path: &Path,
+) -> Result<(), BlobrsError> {
+    download_with(store, location, options, path, |_| Ok(())).await
+}
+
+/// [`download_file`], calling `on_chunk` with the size of each chunk once it is written; an
+/// error from it stops the download.
+async fn download_with(
+    store: &dyn ObjectStore,
+    location: &ObjectPath,
+    options: GetOptions,
+    path: &Path,
+    mut on_chunk: impl FnMut(u64) -> Result<(), BlobrsError>,
+) -> Result<(), BlobrsError> {
+    let mut stream = store.get_opts(location, options).await?.into_stream();
+    let write_error = |e| BlobrsError::io(format!("Failed to write {}", path.display()), e);
+    let mut output = tokio::fs::File::create(path).await.map_err(write_error)?;
+
+    let written = async {
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            output.write_all(&chunk).await.map_err(write_error)?;
+            on_chunk(chunk.len() as u64)?;
+        }
+        output.flush().await.map_err(write_error)
+    }
+    .await;
+    if written.is_err() {
+        drop(output);
+        let _ = tokio::fs::remove_file(path).await;
+    }
+    written
+}
+
+/// Upload the local file at `path` to `location`, with a content type guessed from its name.
+///
+/// Files larger than one block are streamed as a multipart upload, so they are
+/// never held in memory whole and are not bound by the single Put Blob size limit.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the upload fails.
+pub async fn upload_file(
+    store: &dyn ObjectStore,
+    location: &ObjectPath,
+    path: &Path,
+) -> Result<PutResult, BlobrsError> {
+    upload_in_parts(store, location, path, UPLOAD_PART_SIZE, |_| {}).await
+}
+
+/// [`upload_file`], calling `on_progress` with the size of each block as it is handed to the
+/// upload.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the upload fails.
+pub async fn upload_file_with_progress(
+    store: &dyn ObjectStore,
+    location: &ObjectPath,
+    path: &Path,
+    on_progress: impl FnMut(u64),
+) -> Result<PutResult, BlobrsError> {
+    upload_in_parts(store, location, path, UPLOAD_PART_SIZE, on_progress).await
+}
+
+async fn upload_in_parts(
+    store: &dyn ObjectStore,
+    location: &ObjectPath,
+    path: &Path,
+    part_size: usize,
+    mut on_progress: impl FnMut(u64),
+) -> Result<PutResult, BlobrsError> {
+    let read_error = |e| BlobrsError::io(format!("Failed to read {}", path.display()), e);
+    let mut file = tokio::fs::File::open(path).await.map_err(read_error)?;
+
+    let mut attributes = Attributes::new();
+    attributes.insert(Attribute::ContentType, content_type_for(path).into());
+
+    let mut part = read_part(&mut file, part_size).await.map_err(read_error)?;
+    if part.len() < part_size {
+        // The whole file fits in one request
+        let options = PutOptions {
+            attributes,
+            ..PutOptions::default()
+        };
+        let size = part.len() as u64;
+        let result = store.put_opts(location, part.into(), options).await?;
+        on_progress(size);
+        return Ok(result);
+    }
+
+    let options = PutMultipartOptions {
+        attributes,
+        ..PutMultipartOptions::default()
+    };
+    let upload = store.put_multipart_opts(location, options).await?;
+    let mut writer = WriteMultipart::new_with_chunk_size(upload, part_size);
+    let streamed = async {
+        while !part.is_empty() {
+            writer.wait_for_capacity(UPLOAD_CONCURRENCY).await?;
+            on_progress(part.len() as u64);
+            writer.put(part.into());
+            part = read_part(&mut file, part_size).await.map_err(read_error)?;
+        }
+        Ok::<_, BlobrsError>(())
+    }
+    .await;
+    match streamed {
+        Ok(()) => Ok(writer.finish().await?),
+        Err(e) => {
+            let _ = writer.abort().await;
+            Err(e)
+        }
+    }
+}
+
+/// Read up to `part_size` bytes, fewer only at the end of the file.
+async fn read_part(file: &mut tokio::fs::File, part_size: usize) -> std::io::Result<Vec<u8>> {
+    let mut part = Vec::new();
+    (&mut *file)
+        .take(part_size as u64)
+        .read_to_end(&mut part)
+        .await?;
+    Ok(part)
+}
+
+/// Delete `paths` in `container`, up to [`MAX_BATCH_SIZE`] per Blob Batch request, calling
+/// `record` with each path and why it was not deleted, if it was not.
+///
+/// Blobs of a batch the service rejects as a whole, or all of them when there is no REST
+/// client, are deleted one request at a time instead.
+pub async fn delete_batched(
+    object_store: &Arc<dyn ObjectStore>,
+    client: Option<AzureRestClient>,
+    container: &str,
+    paths: Vec<ObjectPath>,
+    mut record: impl FnMut(&str, Option<String>),
+) {
+    let mut unbatched = Vec::new();
+    match client {
+        Some(client) => {
+            // Owned chunks, so the delete can run in a spawned task
+            let chunks: Vec<Vec<ObjectPath>> =
+                paths.chunks(MAX_BATCH_SIZE).map(<[_]>::to_vec).collect();
+            let mut batches = futures::stream::iter(chunks)
+                .map(|chunk| {
+                    let client = client.clone();
+                    let container = container.to_string();
+                    async move {
+                        let names: Vec<String> = chunk.iter().map(ToString::to_string).collect();
+                        let result = client.delete_blobs_batch(&container, &names).await;
+                        (chunk, names, result)
+                    }
+                })
+                .buffer_unordered(DELETE_BATCH_CONCURRENCY);
+            while let Some((chunk, names, result)) = batches.next().await {
+                match result {
+                    Ok(outcomes) => {
+                        for (name, outcome) in names.iter().zip(outcomes) {
+                            record(name, outcome.err().map(|e| e.to_string()));
+                        }
+                    }
+                    Err(_) => unbatched.extend(chunk),
+                }
+            }
+        }
+        None => unbatched = paths,
+    }
+
+    let mut deletions = futures::stream::iter(unbatched)
+        .map(|location| {
+            let object_store = object_store.clone();
+            async move {
+                let result = object_store.delete(&location).await;
+                (location, result)
+            }
+        })
+        .buffer_unordered(DELETE_CONCURRENCY);
+    while let Some((location, result)) = deletions.next().await {
+        record(location.as_ref(), result.err().map(|e| e.to_string()));
+    }
+}
+
+/// Guess a blob content type from a file extension.
+#[must_use]
+pub fn content_type_for(path: &Path) -> String {
+    mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::ObjectStoreExt;
+    use std::time::Duration;
+
+    async fn job_with_files(name: &str, count: usize) -> (TransferJob, tempfile::TempDir) {
+        let store = Arc::new(object_store::memory::InMemory::new());
+        let dest = tempfile::tempdir().unwrap();
+
+        let mut files = Vec::new();
+        for i in 0..count {
+            let location = ObjectPath::from(format!("folder/{i}.txt"));
+            store
+                .put(&location, format!("file {i}").into_bytes().into())
+                .await
+                .unwrap();
+            files.push(FileTransfer {
+                location,
+                version_id: None,
+                e_tag: None,
+                relative_path: format!("{i}.txt"),
+                destination: dest.path().join(format!("{i}.txt")),
+                size: 6,
+                bytes_done: 0,
+                status: FileStatus::Queued,
+            });
+        }
+
+        let source = TransferSource {
+            object_store: store,
+            account: "acct".to_string(),
+            container: "container".to_string(),
+            cache: None,
+            stats: TransferStats::default(),
+        };
+        (TransferJob::new(name.to_string(), source, files), dest)
+    }
+
+    async fn wait_until_finished(job: &TransferJob) {
+        for _ in 0..200 {
+            if job.summary().is_finished() && !job.lock().worker_running {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job did not finish");
+    }
+
+    #[tokio::test]
+    async fn skipped_files_are_left_out_and_can_be_requeued() {
+        let (job, dest) = job_with_files("skip", 3).await;
+        job.skip(1);
+        job.start();
+        wait_until_finished(&job).await;
+
+        let summary = job.summary();
+        assert_eq!(summary.done, 2);
+        assert_eq!(summary.skipped, 1);
+        assert!(!dest.path().join("1.txt").exists());
+
+        job.requeue(1);
+        wait_until_finished(&job).await;
+        assert_eq!(job.summary().done, 3);
+        assert_eq!(
+            std::fs::read_to_string(dest.path().join("1.txt")).unwrap(),
+            "file 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_file_does_not_stop_the_rest() {
+        let (job, _dest) = job_with_files("fail", 3).await;
+        job.lock().files[0].location = ObjectPath::from("folder/missing.txt");
+        job.start();
+        wait_until_finished(&job).await;
+
+        let files = job.files();
+        assert!(matches!(files[0].status, FileStatus::Failed(_)));
+        assert_eq!(files[1].status, FileStatus::Done);
+        assert_eq!(files[2].status, FileStatus::Done);
+
+        // Cancelling a finished job changes nothing
+        job.cancel();
+        assert_eq!(job.summary().done, 2);
+    }
+
+    #[tokio::test]
+    async fn cached_files_are_copied_without_a_request() {
+        let (mut job, dest) = job_with_files("cached", 1).await;
+        let cache = BlobCache::new(dest.path().join("cache"), 1024);
+        job.source.cache = Some(cache.clone());
+        job.source.object_store = Arc::new(object_store::memory::InMemory::new());
+        let key = {
+            let mut state = job.lock();
+            state.files[0].e_tag = Some("0x1".to_string());
+            job.cache_key(&state.files[0]).unwrap()
+        };
+        cache.put(&key, b"cached").unwrap();
+
+        job.start();
+        wait_until_finished(&job).await;
+
+        assert_eq!(job.files()[0].status, FileStatus::Done);
+        assert_eq!(job.summary().bytes_done, 6);
+        assert_eq!(
+            std::fs::read_to_string(dest.path().join("0.txt")).unwrap(),
+            "cached"
+        );
+    }
+
+    #[tokio::test]
+    async fn jobs_report_into_session_stats() {
+        let (mut job, _dest) = job_with_files("stats", 3).await;
+        let stats = TransferStats::default();
+        job.source.stats = stats.clone();
+        job.start();
+        wait_until_finished(&job).await;
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_bytes, 18);
+        assert_eq!(snapshot.active_requests, 0);
+    }
+
+    #[test]
+    fn throughput_only_counts_the_recent_window() {
+        let stats = TransferStats::default();
+        let start = Instant::now();
+        stats.record_at(start, 3_000_000);
+        stats.record_at(start + Duration::from_secs(2), 3_000_000);
+
+        let request = stats.begin_request();
+        let snapshot = stats.snapshot_at(start + Duration::from_secs(2));
+        assert_eq!(snapshot.bytes_per_second, 2_000_000);
+        assert_eq!(snapshot.active_requests, 1);
+        assert!(snapshot.is_active());
+
+        drop(request);
+        let snapshot = stats.snapshot_at(start + Duration::from_secs(10));
+        assert_eq!(snapshot.bytes_per_second, 0);
+        assert_eq!(snapshot.total_bytes, 6_000_000);
+        assert!(!snapshot.is_active());
+    }
+
+    #[test]
+    fn abort_only_affects_queued_or_running_files() {
+        let source = TransferSource {
+            object_store: Arc::new(object_store::memory::InMemory::new()),
+            account: String::new(),
+            container: String::new(),
+            cache: None,
+            stats: TransferStats::default(),
+        };
+        let file = |status| FileTransfer {
+            location: ObjectPath::from("a"),
+            version_id: None,
+            e_tag: None,
+            relative_path: "a".to_string(),
+            destination: PathBuf::from("a"),
+            size: 0,
+            bytes_done: 0,
+            status,
+        };
+        let job = TransferJob::new(
+            "job".to_string(),
+            source,
+            vec![
+                file(FileStatus::Running),
+                file(FileStatus::Queued),
+                file(FileStatus::Done),
+            ],
+        );
+
+        job.cancel();
+        let statuses: Vec<_> = job.files().into_iter().map(|f| f.status).collect();
+        assert_eq!(
+            statuses,
+            vec![FileStatus::Aborted, FileStatus::Skipped, FileStatus::Done]
+        );
+    }
+
+    #[tokio::test]
+    async fn large_files_are_uploaded_in_parts_with_their_content_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rows.csv");
+        std::fs::write(&path, "a,b\n1,2\n3,4\n5,6\n").unwrap();
+        let store = object_store::memory::InMemory::new();
+        let location = ObjectPath::from("raw/rows.csv");
+
+        // 16 bytes in 4-byte parts
+        let mut parts = Vec::new();
+        upload_in_parts(&store, &location, &path, 4, |size| parts.push(size))
+            .await
+            .unwrap();
+        assert_eq!(parts, [4, 4, 4, 4]);
+
+        let result = store.get(&location).await.unwrap();
+        assert_eq!(
+            result
+                .attributes
+                .get(&Attribute::ContentType)
+                .map(AsRef::as_ref),
+            Some("text/csv")
+        );
+        assert_eq!(
+            result.bytes().await.unwrap().as_ref(),
+            b"a,b\n1,2\n3,4\n5,6\n"
+        );
+    }
+
+    #[test]
+    fn content_types_come_from_the_extension() {
+        assert_eq!(
+            content_type_for(Path::new("raw/a.json")),
+            "application/json"
+        );
+        assert_eq!(
+            content_type_for(Path::new("raw/part-0")),
+            "application/octet-stream"
+        );
+    }
+}
@@ -1,24 +1,207 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span, Text},
     widgets::{
-        Block, BorderType, Cell, List, ListItem, ListState, Paragraph, Row, Table, TableState,
-        Widget, Wrap,
+        Block, BorderType, Cell, List, ListItem, ListState, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Table, TableState, Widget, Wrap,
     },
 };
 
-use crate::app::{App, AsyncOp, Modal, ParquetPreviewMode, Session};
-use crate::preview::PreviewData;
+use crate::app::{
+    App, AsyncOp, DELETE_MARKED_CONFIRMATION, FOLDER_PLACEHOLDER, FolderPlan, LeaseAction, Modal,
+    ParquetPreviewMode, PendingOperation, Session, SortCriteria,
+};
+use crate::help;
+use crate::menu::Menu;
+use crate::mouse::{self, ListLayout};
+use crate::palette;
+use crate::preview_pane::PaneContent;
+use crate::preview_search::match_ranges;
+use crate::tabs::Tab;
+use crate::terminal_icons::FileType;
+use blobrs::audit::Outcome;
+use blobrs::azure::{BlobSnapshot, ContainerProperties};
+use blobrs::commands::CommandStyle;
+use blobrs::credentials::{Credential, UnsupportedCredential};
+use blobrs::dates;
+use blobrs::diff::{Comparison, DiffKind, DiffLine};
+use blobrs::edit::MAX_DIFF_LINES;
+use blobrs::filter::Filter;
+use blobrs::listing::EntryKind;
+use blobrs::preview::{PreviewData, PreviewFileType};
+use blobrs::search::SearchScope;
+use blobrs::sync::{SyncAction, SyncMode, SyncPlan};
+use blobrs::tail::TailView;
+use blobrs::theme::Theme;
+use blobrs::transfer::FileStatus;
+use blobrs::watch::Change;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Draw a scrollbar over the right border of the list in `area` when its entries do not all
+/// fit, with the thumb showing which of them are on screen.
+fn render_list_scrollbar(area: Rect, buf: &mut Buffer, layout: &ListLayout) {
+    let height = usize::from(layout.rows.height);
+    if layout.len <= height {
+        return;
+    }
+    // One position per first row the list can scroll to, so the thumb reaches the bottom
+    // with the last entry
+    let mut state = ScrollbarState::new(layout.len - height + 1)
+        .viewport_content_length(height)
+        .position(layout.first);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_style(Style::default().fg(Color::DarkGray))
+        .thumb_style(Style::default().fg(Color::Cyan));
+    ratatui::widgets::StatefulWidget::render(
+        scrollbar,
+        area.inner(Margin::new(0, 1)),
+        buf,
+        &mut state,
+    );
+}
+
+/// `text` in spans, with the letters a search `filter` matched in bold and underlined.
+fn highlight_matches(text: &str, filter: Option<&Filter>) -> Vec<Span<'static>> {
+    let Some(found) = filter.and_then(|filter| filter.find(text)) else {
+        return vec![Span::raw(text.to_string())];
+    };
+    let matched = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut positions = found.positions.iter().peekable();
+    for (index, c) in text.chars().enumerate() {
+        let style = if positions.next_if(|&&at| at == index).is_some() {
+            matched
+        } else {
+            Style::default()
+        };
+        // Runs of letters with the same style share a span
+        match spans.last_mut() {
+            Some(last) if last.style == style => last.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    spans
+}
+
+/// `text` in spans, with each piece a search of the preview found for `query` picked out.
+fn highlight_found(text: &str, query: Option<&str>) -> Vec<Span<'static>> {
+    let found_style = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for found in query
+        .map(|query| match_ranges(text, query))
+        .unwrap_or_default()
+    {
+        if found.start > end {
+            spans.push(Span::raw(text[end..found.start].to_string()));
+        }
+        spans.push(Span::styled(text[found.clone()].to_string(), found_style));
+        end = found.end;
+    }
+    if end < text.len() || spans.is_empty() {
+        spans.push(Span::raw(text[end..].to_string()));
+    }
+    spans
+}
+
+/// Rows for the status bar: one, plus lines of its own for a message too wide to share the
+/// row with the counts, up to six.
+#[allow(clippy::cast_possible_truncation)] // capped well below u16::MAX
+fn status_bar_height(message_width: usize, stats_width: usize, width: u16) -> u16 {
+    let width = usize::from(width.saturating_sub(2)).max(1);
+    if message_width + stats_width + 2 <= width {
+        1
+    } else {
+        1 + message_width.div_ceil(width).min(6) as u16
+    }
+}
+
+/// Ticks per spinner frame: ten frames a second at the event loop's 30 ticks a second.
+const SPINNER_TICKS: usize = 3;
 
 impl Widget for &App {
     /// Renders the user interface widgets.
     fn render(self, area: Rect, buf: &mut Buffer) {
         match &self.session {
-            Session::Selecting => self.render_container_selection(area, buf),
+            Session::Selecting => {
+                self.render_container_selection(area, buf);
+                if self.ui.show_audit_log {
+                    self.render_audit_panel(area, buf);
+                }
+
+                match &self.modal {
+                    Modal::GlobalSearch { .. } => self.render_global_search_popup(area, buf),
+                    Modal::Jobs {
+                        job_index,
+                        file_index,
+                    } => {
+                        self.render_jobs_popup(area, buf, *job_index, *file_index);
+                    }
+                    Modal::Help { scroll } => App::render_help_popup(area, buf, *scroll),
+                    Modal::Command { input, selected } => {
+                        self.render_command_popup(area, buf, input, *selected);
+                    }
+                    Modal::EditNote { key, input } => {
+                        App::render_edit_note_popup(area, buf, key, input);
+                    }
+                    Modal::CredentialPicker {
+                        credentials,
+                        unsupported,
+                        selected,
+                    } => {
+                        App::render_credential_picker_popup(
+                            area,
+                            buf,
+                            credentials,
+                            unsupported,
+                            *selected,
+                        );
+                    }
+                    Modal::NewContainer { input } => {
+                        App::render_new_container_popup(area, buf, input);
+                    }
+                    Modal::SortPicker { menu } => {
+                        App::render_sort_popup(area, buf, "Sort Containers", menu, false);
+                    }
+                    Modal::ContainerInfo {
+                        properties,
+                        soft_delete,
+                        versioning,
+                    } => {
+                        App::render_container_info_popup(
+                            area,
+                            buf,
+                            properties,
+                            *soft_delete,
+                            *versioning,
+                            &self.cost_lines(&properties.name, ""),
+                        );
+                    }
+                    Modal::DeleteContainer { name, input } => {
+                        App::render_delete_dialog_popup(
+                            area,
+                            buf,
+                            input,
+                            &format!("Delete container: {name}"),
+                            name,
+                            false,
+                            None,
+                        );
+                    }
+                    _ => {}
+                }
+            }
             Session::Browsing(_) => {
                 self.render_blob_browsing(area, buf);
+                if self.ui.show_audit_log {
+                    self.render_audit_panel(area, buf);
+                }
 
                 // Render popup over the blob browsing view if needed
                 match &self.modal {
@@ -26,16 +209,67 @@ impl Widget for &App {
                         input,
                         target_name,
                         is_folder,
+                        size,
+                        plan,
                         ..
                     } => {
-                        App::render_delete_dialog_popup(area, buf, input, target_name, *is_folder);
+                        let item_type = if *is_folder { "folder" } else { "blob" };
+                        let heading = match size {
+                            Some(size) => format!(
+                                "Delete {item_type}: {target_name} ({})",
+                                format_bytes(*size)
+                            ),
+                            None => format!("Delete {item_type}: {target_name}"),
+                        };
+                        App::render_delete_dialog_popup(
+                            area,
+                            buf,
+                            input,
+                            &heading,
+                            target_name,
+                            *is_folder,
+                            plan.as_ref(),
+                        );
+                    }
+                    Modal::DeleteMarked { input, plan } => {
+                        let count = self.marked_summary().map_or(0, |(count, _)| count);
+                        App::render_delete_dialog_popup(
+                            area,
+                            buf,
+                            input,
+                            &format!("Delete {count} marked items"),
+                            DELETE_MARKED_CONFIRMATION,
+                            true,
+                            Some(plan),
+                        );
                     }
                     Modal::Clone {
                         input,
                         original_path,
                         is_folder,
                     } => {
-                        App::render_clone_dialog_popup(area, buf, input, original_path, *is_folder);
+                        App::render_clone_dialog_popup(
+                            area,
+                            buf,
+                            "Clone",
+                            input,
+                            original_path,
+                            *is_folder,
+                        );
+                    }
+                    Modal::Move {
+                        input,
+                        original_path,
+                        is_folder,
+                    } => {
+                        App::render_clone_dialog_popup(
+                            area,
+                            buf,
+                            "Move",
+                            input,
+                            original_path,
+                            *is_folder,
+                        );
                     }
                     Modal::BlobInfo { info } => {
                         self.render_blob_info_popup(area, buf, info);
@@ -43,28 +277,142 @@ impl Widget for &App {
                     Modal::DownloadPicker { .. } => {
                         self.render_download_picker_popup(area, buf);
                     }
-                    Modal::SortPicker => {
-                        App::render_sort_popup(area, buf);
+                    Modal::SortPicker { menu } => {
+                        App::render_sort_popup(area, buf, "Sort Files", menu, true);
+                    }
+                    Modal::CopyCommand { target, menu } => {
+                        App::render_copy_command_popup(area, buf, &target.path, menu);
+                    }
+                    Modal::TimeTravel { input } => {
+                        App::render_time_travel_popup(area, buf, input);
+                    }
+                    Modal::NewFolder { input } => {
+                        self.render_new_folder_popup(area, buf, input);
+                    }
+                    Modal::SyncPreview {
+                        local_dir,
+                        prefix,
+                        mode,
+                        plan,
+                        scroll,
+                        ..
+                    } => {
+                        App::render_sync_preview_popup(
+                            area, buf, local_dir, prefix, *mode, plan, *scroll,
+                        );
+                    }
+                    Modal::EditNote { key, input } => {
+                        App::render_edit_note_popup(area, buf, key, input);
+                    }
+                    Modal::LeaseMenu { blob_path, menu } => {
+                        App::render_lease_menu_popup(area, buf, blob_path, menu);
+                    }
+                    Modal::Snapshots {
+                        blob_path,
+                        snapshots,
+                        selected,
+                        confirm_restore,
+                    } => {
+                        App::render_snapshots_popup(
+                            area,
+                            buf,
+                            blob_path,
+                            snapshots,
+                            *selected,
+                            *confirm_restore,
+                        );
+                    }
+                    Modal::EditTags {
+                        blob_path,
+                        tags,
+                        selected,
+                        input,
+                    } => {
+                        App::render_edit_tags_popup(
+                            area,
+                            buf,
+                            blob_path,
+                            tags,
+                            *selected,
+                            input.as_deref(),
+                        );
+                    }
+                    Modal::Extensions {
+                        counts,
+                        shown,
+                        selected,
+                    } => {
+                        App::render_extensions_popup(area, buf, counts, shown, *selected);
+                    }
+                    Modal::LargeOperationWarning { operation, plan } => {
+                        self.render_large_operation_popup(area, buf, operation, plan);
+                    }
+                    Modal::EditUpload { session, diff } => {
+                        App::render_edit_upload_popup(area, buf, &session.blob_path, diff);
+                    }
+                    Modal::Usage { folder, selected } => {
+                        self.render_usage_popup(area, buf, folder, *selected);
+                    }
+                    Modal::Diff {
+                        title,
+                        comparison,
+                        scroll,
+                    } => {
+                        App::render_diff_popup(area, buf, title, comparison, *scroll);
+                    }
+                    Modal::GlobalSearch { .. } => self.render_global_search_popup(area, buf),
+                    Modal::Tail { view, back } => {
+                        App::render_tail_popup(area, buf, view, *back);
+                    }
+                    Modal::Jobs {
+                        job_index,
+                        file_index,
+                    } => {
+                        self.render_jobs_popup(area, buf, *job_index, *file_index);
+                    }
+                    Modal::Help { scroll } => App::render_help_popup(area, buf, *scroll),
+                    Modal::Command { input, selected } => {
+                        self.render_command_popup(area, buf, input, *selected);
                     }
+                    // Shown in the status bar
+                    Modal::Jump { .. } => {}
+                    // Only shown at startup or on the container list
+                    Modal::CredentialPicker { .. }
+                    | Modal::NewContainer { .. }
+                    | Modal::ContainerInfo { .. }
+                    | Modal::DeleteContainer { .. } => {}
                     Modal::None => match &self.async_op {
                         AsyncOp::Deleting(progress) => {
-                            App::render_delete_progress_popup(area, buf, progress);
+                            self.render_delete_progress_popup(area, buf, progress);
                         }
                         AsyncOp::Cloning(progress) => {
-                            App::render_clone_progress_popup(area, buf, progress);
+                            self.render_clone_progress_popup(area, buf, "Clone", progress);
+                        }
+                        AsyncOp::Moving(progress) => {
+                            self.render_clone_progress_popup(area, buf, "Move", progress);
                         }
                         AsyncOp::Downloading(progress) => {
-                            App::render_download_progress_popup(area, buf, progress);
+                            self.render_download_progress_popup(area, buf, progress);
+                        }
+                        AsyncOp::Uploading(progress) => {
+                            self.render_upload_progress_popup(area, buf, progress);
                         }
                         _ => {}
                     },
                 }
             }
         }
+        Theme::from(&self.config.theme).apply(area, buf);
     }
 }
 
 impl App {
+    /// The spinner frame to show now. It advances every [`SPINNER_TICKS`] ticks, so it only
+    /// keeps turning while the app is responsive.
+    fn spinner(&self) -> &'static str {
+        spinner_frame(self.icons.spinner, self.ticks)
+    }
+
     /// Calculate the height needed for footer text with wrapping
     #[allow(clippy::cast_possible_truncation)] // text length is always small for UI
     fn calculate_footer_height(text: &str, available_width: u16) -> u16 {
@@ -86,13 +434,222 @@ impl App {
         lines_needed + 2
     }
 
+    /// Draw the other pane in its half of `area`, if two panes are shown, and return the area
+    /// left for the shown tab: the left half when it comes first, else the right.
+    fn render_other_pane(&self, area: Rect, buf: &mut Buffer) -> Rect {
+        let Some((index, tab)) = self.tabs.neighbour().filter(|_| self.shows_two_panes()) else {
+            return area;
+        };
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        let (shown_area, other_area) = if index > self.tabs.active() {
+            (halves[0], halves[1])
+        } else {
+            (halves[1], halves[0])
+        };
+
+        let (names, selected): (Vec<&str>, usize) = match &tab.session {
+            Session::Selecting => (
+                self.containers.iter().map(|c| c.name.as_str()).collect(),
+                tab.selected_container_index,
+            ),
+            Session::Browsing(state) => (
+                state.files.iter().map(String::as_str).collect(),
+                state.selected_index,
+            ),
+        };
+        let rows = visible_rows(
+            selected,
+            names.len(),
+            usize::from(other_area.height.saturating_sub(2)),
+        );
+        let items: Vec<ListItem> = names[rows.clone()]
+            .iter()
+            .map(|name| ListItem::new(name.to_string()))
+            .collect();
+        let mut list_state = ListState::default();
+        if selected < names.len() {
+            list_state.select(Some(selected - rows.start));
+        }
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(format!(" {} ", Tab::label(&tab.session)))
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded)
+                    .fg(Color::DarkGray),
+            )
+            .fg(Color::Gray)
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol("  ");
+        ratatui::widgets::StatefulWidget::render(list, other_area, buf, &mut list_state);
+        shown_area
+    }
+
+    /// Numbered tabs with the shown one highlighted, or `None` while only one is open.
+    fn tab_bar(&self) -> Option<Line<'static>> {
+        if self.tabs.count() < 2 {
+            return None;
+        }
+        let spans: Vec<Span> = self
+            .tabs
+            .labels(Tab::label(&self.session))
+            .into_iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let text = format!(" {} {label} ", i + 1);
+                if i == self.tabs.active() {
+                    Span::styled(text, Style::default().fg(Color::Black).bg(Color::Cyan))
+                } else {
+                    Span::styled(text, Style::default().fg(Color::DarkGray))
+                }
+            })
+            .collect();
+        Some(Line::from(spans).left_aligned())
+    }
+
+    /// Draw the search box with its mode, and why the pattern is invalid when it is; an invalid
+    /// pattern leaves every entry shown.
+    fn render_search_input(&self, area: Rect, buf: &mut Buffer, title: &str, query: &str) {
+        let mode = self.ui.filter_mode;
+        let mut line = Line::from(format!("Search: {query}"));
+        if let Err(e) = Filter::new(mode, query) {
+            line.push_span(Span::styled(
+                format!("  {} {e}", self.icons.error),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        Paragraph::new(line)
+            .block(
+                Block::bordered()
+                    .title(format!(
+                        " {title} [{}] (Tab to change mode, Enter to confirm, Esc to cancel) ",
+                        mode.label()
+                    ))
+                    .border_type(BorderType::Rounded),
+            )
+            .fg(Color::Cyan)
+            .alignment(Alignment::Left)
+            .render(area, buf);
+    }
+
+    /// The error, success or loading message for the status bar, if there is one.
+    fn status_message(&self, loading: Option<&str>) -> Option<Line<'static>> {
+        if let Modal::Jump { prefix } = &self.modal {
+            let line = Line::from(format!("Jump to: {prefix}▏"));
+            Some(if self.jump_target(prefix).is_some() {
+                line.cyan()
+            } else {
+                line.red()
+            })
+        } else if let Some(error) = &self.error_message {
+            Some(Line::from(format!("{} {error}", self.icons.error)).red())
+        } else if let Some(success) = &self.success_message {
+            Some(Line::from(format!("{} {success}", self.icons.success)).green())
+        } else {
+            loading.map(|text| Line::from(format!("{} {text}", self.spinner())).yellow())
+        }
+    }
+
+    /// Counts for the status bar: entries listed and their total size, what is marked,
+    /// running jobs and live throughput for the storage account.
+    fn status_stats(&self) -> Line<'static> {
+        let dim = Style::default().fg(Color::DarkGray);
+        let mut spans = Vec::new();
+        match self.browsing() {
+            Some(browsing) => {
+                let bytes: u64 = browsing
+                    .file_items
+                    .iter()
+                    .filter_map(|item| item.size)
+                    .sum();
+                let more = if browsing.paging.more.is_some() {
+                    "+"
+                } else {
+                    ""
+                };
+                spans.push(Span::raw(format!(
+                    "{count}{more} items • {size}{more}",
+                    count = browsing.files.len(),
+                    size = format_bytes(bytes)
+                )));
+                if let Some((count, bytes)) = self.marked_summary() {
+                    spans.push(Span::styled(" • ", dim));
+                    spans.push(Span::styled(
+                        format!("{count} marked ({})", format_bytes(bytes)),
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+            }
+            None => spans.push(Span::raw(format!("{} containers", self.containers.len()))),
+        }
+        let running = self
+            .jobs
+            .iter()
+            .filter(|job| !job.summary().is_finished())
+            .count();
+        if running > 0 {
+            spans.push(Span::styled(" • ", dim));
+            spans.push(Span::raw(format!("{running} job(s) running")));
+        }
+        let stats = self.transfer_stats.snapshot();
+        if stats.is_active() {
+            spans.push(Span::styled(" • ", dim));
+            spans.push(Span::styled(
+                format!(
+                    "{spinner} ↓ {rate}/s, {active} request(s)",
+                    spinner = self.spinner(),
+                    rate = format_bytes(stats.bytes_per_second),
+                    active = stats.active_requests
+                ),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        spans.push(Span::raw(" "));
+        Line::from(spans).fg(Color::Gray)
+    }
+
+    /// Draw the status bar: the message on the left, or on lines of its own when too long
+    /// to share one with the counts, and the counts on the right of the last line.
+    fn render_status_bar(
+        area: Rect,
+        buf: &mut Buffer,
+        message: Option<Line<'static>>,
+        stats: Line<'static>,
+    ) {
+        if area.height == 0 {
+            return;
+        }
+        let last = Rect {
+            y: area.bottom() - 1,
+            height: 1,
+            ..area
+        };
+        if let Some(message) = message {
+            let message_area = if area.height == 1 {
+                last
+            } else {
+                Rect {
+                    height: area.height - 1,
+                    ..area
+                }
+            };
+            Paragraph::new(message)
+                .wrap(Wrap { trim: true })
+                .render(message_area.inner(Margin::new(1, 0)), buf);
+        }
+        stats.right_aligned().render(last, buf);
+    }
+
     #[allow(clippy::too_many_lines)]
     fn render_container_selection(&self, area: Rect, buf: &mut Buffer) {
         // Calculate footer height based on instruction text
         let instructions = if self.is_searching_containers() {
             "Search Mode: Type to filter containers • `Enter` to confirm • `Esc` to cancel • `Ctrl+↑`/`Ctrl+↓` to navigate"
         } else {
-            "Press `Ctrl-C` or `q` or `Esc` to quit • `r`/`F5` to refresh • `↑`/`↓` or `k`/`j` to navigate • `→`/`l`/`Enter` to select container • `/` to search"
+            "Press `Ctrl-C` or `q` or `Esc` to quit • `?` for all keys • `:` for commands • `r`/`F5` to refresh • `↑`/`↓` or `k`/`j` to navigate (`PgUp`/`PgDn`, `gg`/`G`, counts like `25j`) • `→`/`l`/`Enter` to select container • `/` to search • `i` for container info • `s`/`S` to show/rescan sizes • `o` to sort • `Ctrl-t`/`Ctrl-w` to open/close a tab (`Tab` to switch) • `|` for two panes • `n` new container • `x` to delete an empty container • `N` to edit note • `F` to search all accounts • `J` for jobs • `H` for history"
         };
         let footer_height = Self::calculate_footer_height(instructions, area.width);
 
@@ -106,41 +663,17 @@ impl App {
             constraints.push(Constraint::Length(3)); // Search input area
         }
 
-        // Add space for error or success message if present
-        if self.error_message.is_some()
-            || self.success_message.is_some()
-            || self.is_loading_containers()
-        {
-            // Calculate height based on message length and terminal width
-            #[allow(clippy::cast_possible_truncation)] // UI text lengths are always small
-            let message_height = if let Some(error) = &self.error_message {
-                // Estimate lines needed: error length / (width - padding), min 3, max 8
-                let available_width = area.width.saturating_sub(4); // Account for borders and padding
-                if available_width > 0 {
-                    ((error.len() + format!("{error_icon} ", error_icon = self.icons.error).len())
-                        as u16)
-                        .div_ceil(available_width)
-                        .clamp(3, 8)
-                } else {
-                    3
-                }
-            } else if let Some(success) = &self.success_message {
-                // Estimate lines needed: success length / (width - padding), min 3, max 8
-                let available_width = area.width.saturating_sub(4); // Account for borders and padding
-                if available_width > 0 {
-                    ((success.len()
-                        + format!("{success_icon} ", success_icon = self.icons.success).len())
-                        as u16)
-                        .div_ceil(available_width)
-                        .clamp(3, 8)
-                } else {
-                    3
-                }
-            } else {
-                3
-            };
-            constraints.push(Constraint::Length(message_height)); // Message/loading area
-        }
+        // Status bar, taller only while a long message needs the room
+        let status_message = self.status_message(
+            self.is_loading_containers()
+                .then_some("Loading containers..."),
+        );
+        let status_stats = self.status_stats();
+        constraints.push(Constraint::Length(status_bar_height(
+            status_message.as_ref().map_or(0, Line::width),
+            status_stats.width(),
+            area.width,
+        )));
 
         constraints.push(Constraint::Length(footer_height)); // Footer for instructions
 
@@ -153,7 +686,7 @@ impl App {
         let container_items: Vec<ListItem> = if self.is_loading_containers() {
             vec![ListItem::new(format!(
                 "{loading} Loading containers...",
-                loading = self.icons.loading
+                loading = self.spinner()
             ))]
         } else if self.containers.is_empty() {
             let has_query = self.container_search_query().is_some_and(|q| !q.is_empty());
@@ -169,11 +702,48 @@ impl App {
                 ))]
             }
         } else {
+            // Line the usage column up after the longest name
+            let name_width = self
+                .containers
+                .iter()
+                .map(|container| container.name.chars().count())
+                .max()
+                .unwrap_or(0)
+                .min(40);
+            let now = chrono::Utc::now();
+            let filter = self
+                .container_search_query()
+                .map(|query| Filter::lenient(self.ui.filter_mode, query));
             self.containers
                 .iter()
                 .map(|container| {
                     let name = &container.name;
-                    ListItem::new(format!("{folder} {name}", folder = self.icons.folder))
+                    let mut line = Line::from(format!("{folder} ", folder = self.icons.folder));
+                    line.spans.extend(highlight_matches(name, filter.as_ref()));
+                    if self.ui.show_container_usage {
+                        let padding = name_width.saturating_sub(name.chars().count());
+                        line.push_span(Span::styled(
+                            format!("{:padding$}  {}", "", self.container_usage_text(name)),
+                            Style::default().fg(Color::Cyan),
+                        ));
+                    }
+                    if container.recent {
+                        line.push_span(Span::styled(
+                            "  (recent)",
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    } else if self.container_sort == SortCriteria::DateModified
+                        && let Some(modified) = container.last_modified
+                    {
+                        line.push_span(Span::styled(
+                            format!(
+                                "  {}",
+                                dates::short(modified, self.config.browse.time_format, now)
+                            ),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                    ListItem::new(line)
                 })
                 .collect()
         };
@@ -197,71 +767,63 @@ impl App {
             )
         };
 
+        let mut list_block = Block::bordered()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded);
+        if let Some(tabs) = self.tab_bar() {
+            list_block = list_block.title(tabs);
+        }
+        if let Some(count) = self.pending_motion.count() {
+            list_block = list_block.title_bottom(Line::from(format!(" {count} ")).right_aligned());
+        }
+
         let main_block = List::new(container_items)
-            .block(
-                Block::bordered()
-                    .title(title)
-                    .title_alignment(Alignment::Center)
-                    .border_type(BorderType::Rounded),
-            )
+            .block(list_block)
             .fg(Color::Green)
             .highlight_style(Style::default().bg(Color::DarkGray).fg(Color::Yellow))
             .highlight_symbol("▶ ");
 
-        ratatui::widgets::StatefulWidget::render(main_block, chunks[0], buf, &mut list_state);
+        // Show the highlighted container's note beside the list
+        let main_area = self.render_other_pane(chunks[0], buf);
+        let list_area = if let Some((_, note)) = self.current_note() {
+            let horizontal_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+                .split(main_area);
+            Self::render_note_panel(horizontal_chunks[1], buf, note);
+            horizontal_chunks[0]
+        } else {
+            main_area
+        };
+
+        let rows = list_area.inner(Margin::new(1, 1));
+        *self.list_layout.borrow_mut() = ListLayout {
+            rows,
+            // A fresh list state scrolls the same way as a virtualized list
+            first: visible_rows(
+                self.selected_container_index,
+                self.containers.len(),
+                usize::from(rows.height),
+            )
+            .start,
+            len: self.containers.len(),
+            crumbs: Vec::new(),
+        };
+        ratatui::widgets::StatefulWidget::render(main_block, list_area, buf, &mut list_state);
+        render_list_scrollbar(list_area, buf, &self.list_layout.borrow());
 
         let mut chunk_index = 1;
 
         // Search input if in container search mode
         if self.is_searching_containers() {
             let query = self.container_search_query().unwrap_or("");
-            let search_text = format!("Search: {query}");
-            let search_widget = Paragraph::new(search_text)
-                .block(
-                    Block::bordered()
-                        .title(" Search Containers (Press Enter to confirm, Esc to cancel) ")
-                        .border_type(BorderType::Rounded),
-                )
-                .fg(Color::Cyan)
-                .alignment(Alignment::Left);
-            search_widget.render(chunks[chunk_index], buf);
+            self.render_search_input(chunks[chunk_index], buf, "Search Containers", query);
             chunk_index += 1;
         }
 
-        // Error/Success/Loading message if present
-        if let Some(error) = &self.error_message {
-            let error_widget = Paragraph::new(format!(
-                "{error_icon} {error}",
-                error_icon = self.icons.error
-            ))
-            .block(Block::bordered().border_type(BorderType::Rounded))
-            .fg(Color::Red)
-            .wrap(ratatui::widgets::Wrap { trim: true })
-            .alignment(Alignment::Left);
-            error_widget.render(chunks[chunk_index], buf);
-            chunk_index += 1;
-        } else if let Some(success) = &self.success_message {
-            let success_widget = Paragraph::new(format!(
-                "{success_icon} {success}",
-                success_icon = self.icons.success
-            ))
-            .block(Block::bordered().border_type(BorderType::Rounded))
-            .fg(Color::Green)
-            .wrap(ratatui::widgets::Wrap { trim: true })
-            .alignment(Alignment::Left);
-            success_widget.render(chunks[chunk_index], buf);
-            chunk_index += 1;
-        } else if self.is_loading_containers() {
-            let loading_widget = Paragraph::new(format!(
-                "{loading} Loading containers...",
-                loading = self.icons.loading
-            ))
-            .block(Block::bordered().border_type(BorderType::Rounded))
-            .fg(Color::Yellow)
-            .alignment(Alignment::Center);
-            loading_widget.render(chunks[chunk_index], buf);
-            chunk_index += 1;
-        }
+        Self::render_status_bar(chunks[chunk_index], buf, status_message, status_stats);
+        chunk_index += 1;
 
         // Footer with instructions (using pre-calculated text)
         let footer = Paragraph::new(instructions)
@@ -283,13 +845,19 @@ impl App {
         let instructions = if self.is_searching_files() {
             "Search Mode: Type to filter • `Enter` to confirm • `Esc` to cancel • `Ctrl+↑`/`Ctrl+↓` to navigate"
         } else if self.ui.show_preview {
-            if self.preview_file_type == Some(super::preview::PreviewFileType::Parquet) {
-                "Preview: `↑`/`↓`/`k`/`j` to scroll rows • `←`/`→`/`h`/`l` to scroll columns • `Tab` to switch table/metadata • `p` or `Esc` to close preview"
+            if self
+                .preview_search
+                .as_ref()
+                .is_some_and(|search| search.typing)
+            {
+                "Preview search: Type to find • `Enter` to confirm • `Esc` to cancel"
+            } else if self.preview_file_type == Some(PreviewFileType::Parquet) {
+                "Preview: `↑`/`↓`/`k`/`j` to scroll rows (`PgUp`/`PgDn` a page) • `←`/`→`/`h`/`l` to scroll columns • `/` to search (`n`/`N` next/previous) • `c`/`C`/`Y` to copy the cell or line, row or all • `Tab` to switch table/metadata • `p` or `Esc` to close preview"
             } else {
-                "Preview: `↑`/`↓`/`k`/`j` to scroll rows • `←`/`→`/`h`/`l` to scroll columns • `p` or `Esc` to close preview"
+                "Preview: `↑`/`↓`/`k`/`j` to scroll rows (`PgUp`/`PgDn` a page) • `←`/`→`/`h`/`l` to scroll columns • `/` to search (`n`/`N` next/previous) • `c`/`C`/`Y` to copy the cell or line, row or all • `p` or `Esc` to close preview"
             }
         } else {
-            "Press `Ctrl-C` or `q` to quit • `Esc`/`←`/`h` to go back • `r`/`F5` to refresh • `↑`/`↓` or `k`/`j` to navigate • `→`/`l`/`Enter` to enter folder • `/` to search • `s` to sort • `i` for info • `p` to preview • `y` to copy path • `c` to clone • `x` to delete • `d` to download"
+            "Press `Ctrl-C` or `q` to quit • `?` for all keys • `:` for commands (`:goto`, `:sort size`, `:tier cool`) • `Esc`/`←`/`h` to go back • `r`/`F5` to refresh • `↑`/`↓` or `k`/`j` to navigate (`PgUp`/`PgDn`, `gg`/`G`, counts like `25j`) • `→`/`l`/`Enter` to enter folder • `/` to search • `s` to sort • `Ctrl-t`/`Ctrl-w` to open/close a tab (`Tab` to switch) • `|` for two panes (`F5`/`F6` to copy/move to the other) • `i` for info • `p` to preview (`w` for a pane following the selection) • `y` to copy path (`C` as an az/azcopy command) • `c` to clone • `F2`/`R` to move or rename • `Space` to mark (`a` all, `A` invert) • `Y`/`P` to yank/paste (server-side copy) • `x` to delete • `d` to download (`D` to pick folder, `o` to open it) • `u` to upload (`U` from clipboard path) • `.` to show deleted (`u` to undelete) • `f` to follow a log blob • `E` to edit in $EDITOR • `!` to pipe into a command • `=` to compare with the marked blob • `n` for new folder • `S` to sync with a local folder • `[`/`]` for previous/next page • `T` to time travel • `B` for a size breakdown • `W` to watch for new blobs • `N` to edit note • `F` to search all accounts • `J` for jobs • `H` for history"
         };
         let footer_height = Self::calculate_footer_height(instructions, area.width);
 
@@ -303,39 +871,17 @@ impl App {
             constraints.push(Constraint::Length(3)); // Search input area
         }
 
-        // Add space for error or success message if present
-        if self.error_message.is_some() || self.success_message.is_some() || self.is_loading_files()
-        {
-            // Calculate height based on message length and terminal width
-            #[allow(clippy::cast_possible_truncation)] // UI text lengths are always small
-            let message_height = if let Some(error) = &self.error_message {
-                // Estimate lines needed: error length / (width - padding), min 3, max 8
-                let available_width = area.width.saturating_sub(4); // Account for borders and padding
-                if available_width > 0 {
-                    ((error.len() + format!("{error_icon} ", error_icon = self.icons.error).len())
-                        as u16)
-                        .div_ceil(available_width)
-                        .clamp(3, 8)
-                } else {
-                    3
-                }
-            } else if let Some(success) = &self.success_message {
-                // Estimate lines needed: success length / (width - padding), min 3, max 8
-                let available_width = area.width.saturating_sub(4); // Account for borders and padding
-                if available_width > 0 {
-                    ((success.len()
-                        + format!("{success_icon} ", success_icon = self.icons.success).len())
-                        as u16)
-                        .div_ceil(available_width)
-                        .clamp(3, 8)
-                } else {
-                    3
-                }
-            } else {
-                3
-            };
-            constraints.push(Constraint::Length(message_height)); // Message/loading area
-        }
+        // Status bar, taller only while a long message needs the room
+        let status_message = self.status_message(
+            self.is_loading_files()
+                .then_some("Loading Azure Blob Storage..."),
+        );
+        let status_stats = self.status_stats();
+        constraints.push(Constraint::Length(status_bar_height(
+            status_message.as_ref().map_or(0, Line::width),
+            status_stats.width(),
+            area.width,
+        )));
 
         constraints.push(Constraint::Length(footer_height)); // Footer for instructions
 
@@ -345,7 +891,7 @@ impl App {
             .split(area);
 
         // Main content area - split horizontally if preview is shown
-        let main_area = chunks[0];
+        let main_area = self.render_other_pane(chunks[0], buf);
         let (file_list_area, preview_area) = if self.ui.show_preview {
             let horizontal_chunks = Layout::default()
                 .direction(Direction::Horizontal)
@@ -355,15 +901,37 @@ impl App {
                 ])
                 .split(main_area);
             (horizontal_chunks[0], Some(horizontal_chunks[1]))
+        } else if self.ui.preview_pane {
+            let horizontal_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(main_area);
+            self.render_preview_pane(horizontal_chunks[1], buf);
+            (horizontal_chunks[0], None)
+        } else if let Some((_, note)) = self.current_note() {
+            // Show the note for the highlighted folder (or this folder) beside the list
+            let horizontal_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+                .split(main_area);
+            Self::render_note_panel(horizontal_chunks[1], buf, note);
+            (horizontal_chunks[0], None)
         } else {
             (main_area, None)
         };
 
-        // Main block with file list
+        // Main block with file list. Only the rows that fit are built, so folders with
+        // hundreds of thousands of entries draw as quickly as small ones.
+        let list_rows = file_list_area.inner(Margin::new(1, 1));
+        let rows = visible_rows(
+            browsing.selected_index,
+            browsing.files.len(),
+            usize::from(list_rows.height),
+        );
         let file_items: Vec<ListItem> = if self.is_loading_files() {
             vec![ListItem::new(format!(
                 "{loading} Loading...",
-                loading = self.icons.loading
+                loading = self.spinner()
             ))]
         } else if browsing.files.is_empty() {
             let has_query = self.file_search_query().is_some_and(|q| !q.is_empty());
@@ -379,16 +947,95 @@ impl App {
                 ))]
             }
         } else {
-            browsing
-                .files
+            let any_marked = !browsing.marked.is_empty();
+            let now = chrono::Utc::now();
+            let watched = format!("{}/{}", browsing.container_name, browsing.current_path);
+            let instant = std::time::Instant::now();
+            let filter = self
+                .file_search_query()
+                .map(|query| Filter::lenient(self.ui.filter_mode, query));
+            browsing.files[rows.clone()]
                 .iter()
-                .map(|file| ListItem::new(file.as_str()))
+                .zip(&browsing.file_items[rows.clone()])
+                .map(|(file, item)| {
+                    let mut line = if !any_marked {
+                        Line::default()
+                    } else if self.is_marked(item) {
+                        Line::from("● ")
+                    } else {
+                        Line::from("  ")
+                    };
+                    // Blobs get their file type's colour on the icon
+                    match file.split_once(' ') {
+                        Some((icon, name)) => {
+                            let style = FileType::of(&item.actual_name)
+                                .filter(|_| item.kind == EntryKind::File && !item.deleted)
+                                .map_or_else(Style::default, |kind| {
+                                    Style::default().fg(kind.color())
+                                });
+                            line.push_span(Span::styled(icon, style));
+                            line.push_span(Span::raw(" "));
+                            line.spans.extend(highlight_matches(name, filter.as_ref()));
+                        }
+                        None => line.spans.extend(highlight_matches(file, filter.as_ref())),
+                    }
+                    // Fresh data in an ingestion folder stands out in the special colour
+                    let recent = item.kind == EntryKind::File
+                        && !item.deleted
+                        && item.last_modified.is_some_and(|modified| {
+                            dates::is_recent(modified, self.config.browse.recent_secs, now)
+                        });
+                    if let Some(modified) = item.last_modified {
+                        line.push_span(Span::styled(
+                            format!(
+                                "  {}",
+                                dates::short(modified, self.config.browse.time_format, now)
+                            ),
+                            Style::default().fg(if recent {
+                                Color::Magenta
+                            } else {
+                                Color::DarkGray
+                            }),
+                        ));
+                    }
+                    let change = self
+                        .watch
+                        .as_ref()
+                        .and_then(|watch| watch.highlight(&watched, &item.actual_name, instant));
+                    let change_style = match change {
+                        Some(Change::New) => {
+                            line.push_span(Span::raw("  ✚ new"));
+                            Style::default()
+                                .fg(Color::LightGreen)
+                                .add_modifier(Modifier::BOLD)
+                        }
+                        Some(Change::Changed) => {
+                            line.push_span(Span::raw("  ✎ changed"));
+                            Style::default()
+                                .fg(Color::LightCyan)
+                                .add_modifier(Modifier::BOLD)
+                        }
+                        None if recent => Style::default().fg(Color::Magenta),
+                        None => Style::default(),
+                    };
+                    let row = if any_marked && self.is_marked(item) {
+                        ListItem::new(line).style(Style::default().fg(Color::Yellow))
+                    } else {
+                        ListItem::new(line).style(change_style)
+                    };
+                    // Soft-deleted blobs are dimmed
+                    if item.deleted {
+                        row.style(Style::default().fg(Color::DarkGray))
+                    } else {
+                        row
+                    }
+                })
                 .collect()
         };
 
         let mut list_state = ListState::default();
         if !self.is_loading_files() && !browsing.files.is_empty() {
-            list_state.select(Some(browsing.selected_index));
+            list_state.select(Some(browsing.selected_index - rows.start));
         }
 
         let current_path_display = if browsing.current_path.is_empty() {
@@ -400,41 +1047,102 @@ impl App {
             )
         };
 
-        let container_name =
-            if let Some(container) = self.containers.get(self.selected_container_index) {
-                &container.name
-            } else {
-                "Unknown"
-            };
+        let container_name = &browsing.container_name;
+
+        let as_of_display = browsing
+            .as_of
+            .map(|ts| format!(" [AS OF {}]", ts.format("%Y-%m-%d %H:%M UTC")))
+            .unwrap_or_default();
+        let deleted_display = match (self.ui.show_deleted, self.ui.flatten) {
+            (true, true) => " [FLAT] [+DELETED]",
+            (true, false) => " [+DELETED]",
+            (false, true) => " [FLAT]",
+            (false, false) => "",
+        };
+        let page_display = if browsing.paging.is_paged() {
+            format!(
+                " [PAGE {}{}]",
+                browsing.paging.page_number(),
+                if browsing.paging.next_token.is_some() {
+                    ", more with ]"
+                } else {
+                    ""
+                }
+            )
+        } else {
+            String::new()
+        };
+        let more_display = if browsing.paging.more.is_some() {
+            format!(" [{} LOADING MORE…]", self.spinner())
+        } else if self.watch.is_some() {
+            " [WATCHING]".to_string()
+        } else {
+            String::new()
+        };
 
         let title = if self.is_searching_files() {
             format!(
-                " Container: {container} - {path} [SEARCH] ({count} shown) ",
+                " Container: {container} - {path}{as_of}{deleted}{page}{more} [SEARCH] ({count} shown) ",
                 container = container_name,
                 path = current_path_display,
+                as_of = as_of_display,
+                deleted = deleted_display,
+                page = page_display,
+                more = more_display,
                 count = browsing.files.len()
             )
         } else {
             format!(
-                " Container: {container} - {path} ({count} items) ",
+                " Container: {container} - {path}{as_of}{deleted}{page}{more} ({count} items) ",
                 container = container_name,
                 path = current_path_display,
+                as_of = as_of_display,
+                deleted = deleted_display,
+                page = page_display,
+                more = more_display,
                 count = browsing.files.len()
             )
         };
 
-        let main_block = List::new(file_items)
-            .block(
-                Block::bordered()
-                    .title(title)
-                    .title_alignment(Alignment::Center)
-                    .border_type(BorderType::Rounded),
+        // The title is centred, so the path's columns follow from its width
+        let title_width = Line::raw(&title).width();
+        let inner_width = usize::from(list_rows.width);
+        let crumbs = if title_width <= inner_width {
+            let path_offset = Line::raw(format!(" Container: {container_name} - ")).width();
+            let x = (inner_width - title_width) / 2 + path_offset;
+            u16::try_from(x).map_or_else(
+                |_| Vec::new(),
+                |x| mouse::crumbs(&browsing.current_path, list_rows.x + x),
             )
+        } else {
+            Vec::new()
+        };
+        *self.list_layout.borrow_mut() = ListLayout {
+            rows: list_rows,
+            first: rows.start,
+            len: browsing.files.len(),
+            crumbs,
+        };
+
+        let mut list_block = Block::bordered()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded);
+        if let Some(tabs) = self.tab_bar() {
+            list_block = list_block.title(tabs);
+        }
+        if let Some(count) = self.pending_motion.count() {
+            list_block = list_block.title_bottom(Line::from(format!(" {count} ")).right_aligned());
+        }
+
+        let main_block = List::new(file_items)
+            .block(list_block)
             .fg(Color::Green)
             .highlight_style(Style::default().bg(Color::DarkGray).fg(Color::Yellow))
             .highlight_symbol("▶ ");
 
         ratatui::widgets::StatefulWidget::render(main_block, file_list_area, buf, &mut list_state);
+        render_list_scrollbar(file_list_area, buf, &self.list_layout.borrow());
 
         // Render preview panel if active
         if let Some(preview_rect) = preview_area {
@@ -446,56 +1154,15 @@ impl App {
         // Search input if in search mode
         if self.is_searching_files() {
             let query = self.file_search_query().unwrap_or("");
-            let search_text = format!("Search: {query}");
-            let search_widget = Paragraph::new(search_text)
-                .block(
-                    Block::bordered()
-                        .title(" Search (Press Enter to confirm, Esc to cancel) ")
-                        .border_type(BorderType::Rounded),
-                )
-                .fg(Color::Cyan)
-                .alignment(Alignment::Left);
-            search_widget.render(chunks[chunk_index], buf);
+            self.render_search_input(chunks[chunk_index], buf, "Search", query);
             chunk_index += 1;
         }
 
-        // Error/Success/Loading message if present
-        if let Some(error) = &self.error_message {
-            let error_widget = Paragraph::new(format!(
-                "{error_icon} {error}",
-                error_icon = self.icons.error
-            ))
-            .block(Block::bordered().border_type(BorderType::Rounded))
-            .fg(Color::Red)
-            .wrap(ratatui::widgets::Wrap { trim: true })
-            .alignment(Alignment::Left);
-            error_widget.render(chunks[chunk_index], buf);
-            chunk_index += 1;
-        } else if let Some(success) = &self.success_message {
-            let success_widget = Paragraph::new(format!(
-                "{success_icon} {success}",
-                success_icon = self.icons.success
-            ))
-            .block(Block::bordered().border_type(BorderType::Rounded))
-            .fg(Color::Green)
-            .wrap(ratatui::widgets::Wrap { trim: true })
-            .alignment(Alignment::Left);
-            success_widget.render(chunks[chunk_index], buf);
-            chunk_index += 1;
-        } else if self.is_loading_files() {
-            let loading_widget = Paragraph::new(format!(
-                "{loading} Loading Azure Blob Storage...",
-                loading = self.icons.loading
-            ))
-            .block(Block::bordered().border_type(BorderType::Rounded))
-            .fg(Color::Yellow)
-            .alignment(Alignment::Center);
-            loading_widget.render(chunks[chunk_index], buf);
-            chunk_index += 1;
-        }
-
-        // Footer with instructions (using pre-calculated text)
-        let footer = Paragraph::new(instructions)
+        Self::render_status_bar(chunks[chunk_index], buf, status_message, status_stats);
+        chunk_index += 1;
+
+        // Footer with instructions (using pre-calculated text)
+        let footer = Paragraph::new(instructions)
             .block(Block::bordered().border_type(BorderType::Rounded))
             .fg(Color::Cyan)
             .alignment(Alignment::Center)
@@ -512,7 +1179,7 @@ impl App {
     ) {
         // Calculate popup size and position
         let popup_width = area.width.clamp(40, 60); // Between 40 and 60 characters wide
-        let popup_height = area.height.clamp(10, 20); // Between 10 and 20 lines tall
+        let popup_height = area.height.clamp(10, 28); // Between 10 and 28 lines tall
 
         // Center the popup
         let popup_x = (area.width.saturating_sub(popup_width)) / 2;
@@ -550,11 +1217,7 @@ impl App {
         let mut info_lines = Vec::new();
 
         match blob_info {
-            crate::app::BlobInfo::Folder {
-                name,
-                blob_count,
-                total_size,
-            } => {
+            crate::app::BlobInfo::Folder { name, path } => {
                 info_lines.push(format!("{} Folder Information", self.icons.folder));
                 info_lines.push(String::new());
 
@@ -563,14 +1226,41 @@ impl App {
                 info_lines.push(format!("Name: {name_display}"));
                 info_lines.push(String::new());
 
-                info_lines.push(format!("Blobs: {blob_count}"));
-                info_lines.push(format!("Total size: {}", format_bytes(*total_size)));
+                let usage = self
+                    .browsing()
+                    .and_then(|state| self.usage.get_folder(&state.container_name, path))
+                    .unwrap_or_default();
+                // Counts grow as the folder is listed in the background
+                let counting = if usage.complete || usage.error.is_some() {
+                    String::new()
+                } else {
+                    format!("  {} counting…", self.spinner())
+                };
+                info_lines.push(format!("Blobs: {}{counting}", usage.blobs));
+                info_lines.push(format!("Total size: {}", format_bytes(usage.bytes)));
+                if let Some(error) = usage.error {
+                    info_lines.push(format!("Counting stopped early: {error}"));
+                }
+                if let Some(state) = self.browsing() {
+                    info_lines.extend(self.cost_lines(&state.container_name, path));
+                }
             }
             crate::app::BlobInfo::File {
                 name,
                 size,
                 last_modified,
                 etag,
+                tags,
+                lease_state,
+                lease_duration,
+                created,
+                blob_type,
+                content_type,
+                content_encoding,
+                content_md5,
+                page_used,
+                immutable_until,
+                legal_hold,
             } => {
                 info_lines.push(format!("{} Blob Information", self.icons.file));
                 info_lines.push(String::new());
@@ -580,14 +1270,84 @@ impl App {
                 info_lines.push(format!("Name: {name_display}"));
                 info_lines.push(String::new());
 
-                info_lines.push(format!("Size: {}", format_bytes(*size)));
-                info_lines.push(format!("Modified: {last_modified}"));
+                match page_used {
+                    Some(used) => {
+                        // Sparse page blobs (VHDs) only use part of their provisioned size
+                        #[allow(clippy::cast_precision_loss)]
+                        let percentage = if *size > 0 {
+                            *used as f64 / *size as f64 * 100.0
+                        } else {
+                            0.0
+                        };
+                        info_lines.push(format!("Provisioned: {}", format_bytes(*size)));
+                        info_lines
+                            .push(format!("Used: {} ({percentage:.1}%)", format_bytes(*used)));
+                    }
+                    None => info_lines.push(format!("Size: {}", format_bytes(*size))),
+                }
+                let now = chrono::Utc::now();
+                let time_format = self.config.browse.time_format;
+                info_lines.push(format!(
+                    "Modified: {}",
+                    dates::detailed(*last_modified, time_format, now)
+                ));
+                if let Some(created) = created {
+                    info_lines.push(format!(
+                        "Created: {}",
+                        dates::detailed(*created, time_format, now)
+                    ));
+                }
+                if let Some(blob_type) = blob_type {
+                    info_lines.push(format!("Blob type: {blob_type}"));
+                }
+                let value_width = (popup_width as usize).saturating_sub(20);
+                if let Some(content_type) = content_type {
+                    let display = truncate_with_ellipsis(content_type, value_width);
+                    info_lines.push(format!("Content-Type: {display}"));
+                }
+                if let Some(content_encoding) = content_encoding {
+                    let display = truncate_with_ellipsis(content_encoding, value_width);
+                    info_lines.push(format!("Content-Encoding: {display}"));
+                }
+                if let Some(md5) = content_md5 {
+                    info_lines.push(format!("Content-MD5: {md5}"));
+                    if let Some(hex) = md5_hex(md5) {
+                        info_lines.push(format!("  (hex {hex})"));
+                    }
+                }
 
                 if let Some(etag) = etag {
                     let etag_display =
                         truncate_with_ellipsis(etag, (popup_width as usize).saturating_sub(11));
                     info_lines.push(format!("ETag: {etag_display}"));
                 }
+
+                if let Some(until) = immutable_until {
+                    info_lines.push(format!("Immutable until: {until}"));
+                }
+                if *legal_hold {
+                    info_lines.push("Legal hold: on (cannot be changed or deleted)".to_string());
+                }
+
+                match (lease_state, lease_duration) {
+                    (Some(state), Some(duration)) => {
+                        info_lines.push(format!("Lease: {state} ({duration})"));
+                    }
+                    (Some(state), None) => info_lines.push(format!("Lease: {state}")),
+                    (None, _) => {}
+                }
+
+                info_lines.push(String::new());
+                match tags {
+                    Some(tags) if tags.is_empty() => info_lines.push("Tags: none".to_string()),
+                    Some(tags) => {
+                        info_lines.push("Tags:".to_string());
+                        for (key, value) in tags {
+                            info_lines.push(format!("  {key} = {value}"));
+                        }
+                    }
+                    None => info_lines.push("Tags: unavailable".to_string()),
+                }
             }
         }
 
@@ -612,7 +1372,11 @@ impl App {
             height: 1,
         };
 
-        let instructions = "Press Esc, ← or h to close";
+        let instructions = if matches!(blob_info, crate::app::BlobInfo::File { .. }) {
+            "`t` tags • `s` snapshots • `L` lease • Esc, ← or h to close"
+        } else {
+            "`r` to recount • Esc, ← or h to close"
+        };
         let footer_text = Paragraph::new(instructions)
             .style(Style::default().fg(Color::Yellow).bg(Color::Black))
             .alignment(Alignment::Center);
@@ -620,15 +1384,242 @@ impl App {
         footer_text.render(footer_area, buf);
     }
 
+    /// The estimated monthly cost of `folder` of `container` (the whole container when it is
+    /// empty) and its bytes in each tier, as far as they have been counted.
+    fn cost_lines(&self, container: &str, folder: &str) -> Vec<String> {
+        let Some(usage) = self.usage.get_tiers(container, folder) else {
+            return Vec::new();
+        };
+        let costs = &self.config.costs;
+        let status = if let Some(error) = &usage.error {
+            format!("  (stopped early: {error})")
+        } else if usage.complete {
+            String::new()
+        } else {
+            format!("  {} counting…", self.spinner())
+        };
+        let mut lines = vec![
+            String::new(),
+            format!(
+                "Estimated cost: {}/month{status}",
+                costs.format(usage.monthly_cost(costs))
+            ),
+        ];
+        for (tier, bytes) in &usage.tiers {
+            lines.push(format!(
+                "  {tier}: {} at {}{}/GiB",
+                format_bytes(*bytes),
+                costs.currency,
+                costs.rate(tier)
+            ));
+        }
+        lines
+    }
+
+    /// Blob count and total size of a container for the list, as far as it has been counted.
+    fn container_usage_text(&self, container: &str) -> String {
+        match self.usage.get(container) {
+            None => String::new(),
+            Some(usage) => {
+                let counted = format!("{:>8} blobs {:>10}", usage.blobs, format_bytes(usage.bytes));
+                if usage.error.is_some() {
+                    format!("{counted}  (scan failed)")
+                } else if usage.complete {
+                    counted
+                } else {
+                    format!("{counted}  {}", self.spinner())
+                }
+            }
+        }
+    }
+
+    /// Render the properties of a container.
+    fn render_container_info_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        properties: &ContainerProperties,
+        soft_delete: Option<bool>,
+        versioning: Option<bool>,
+        cost_lines: &[String],
+    ) {
+        let popup_width = area.width.clamp(40, 60);
+        let popup_height = area.height.clamp(10, 24);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+        let value_width = (popup_width as usize).saturating_sub(16);
+        let on_off = |setting: Option<bool>, unknown: &str| match setting {
+            Some(true) => "on".to_string(),
+            Some(false) => "off".to_string(),
+            None => unknown.to_string(),
+        };
+
+        let mut info_lines = vec![
+            format!(
+                "Name: {}",
+                truncate_with_ellipsis(&properties.name, value_width)
+            ),
+            String::new(),
+            format!(
+                "Public access: {}",
+                properties.public_access.as_deref().unwrap_or("private")
+            ),
+        ];
+        if let Some(last_modified) = &properties.last_modified {
+            info_lines.push(format!("Modified: {last_modified}"));
+        }
+        match (&properties.lease_state, &properties.lease_duration) {
+            (Some(state), Some(duration)) => {
+                info_lines.push(format!("Lease: {state} ({duration})"));
+            }
+            (Some(state), None) => info_lines.push(format!("Lease: {state}")),
+            (None, _) => {}
+        }
+        if properties.immutability_policy {
+            info_lines.push("Immutability policy: set".to_string());
+        }
+        if properties.legal_hold {
+            info_lines.push("Legal hold: on".to_string());
+        }
+
+        info_lines.push(String::new());
+        info_lines.push(format!(
+            "Soft delete (account): {}",
+            on_off(soft_delete, "unknown")
+        ));
+        info_lines.push(format!(
+            "Versioning: {}",
+            on_off(versioning, "unknown (no blobs)")
+        ));
+        info_lines.extend(cost_lines.iter().cloned());
+
+        info_lines.push(String::new());
+        if properties.metadata.is_empty() {
+            info_lines.push("Metadata: none".to_string());
+        } else {
+            info_lines.push("Metadata:".to_string());
+            for (key, value) in &properties.metadata {
+                info_lines.push(truncate_with_ellipsis(
+                    &format!("  {key} = {value}"),
+                    (popup_width as usize).saturating_sub(4),
+                ));
+            }
+        }
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].reset();
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+        Paragraph::new(info_lines.join("\n"))
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Container ")
+                    .title_bottom(Line::from(" Esc, ← or h to close ").centered().yellow())
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render the history of this session's operations docked at the bottom, newest last.
+    fn render_audit_panel(&self, area: Rect, buf: &mut Buffer) {
+        let panel_height = (area.height / 3).max(5).min(area.height);
+        let panel_area = Rect {
+            x: area.x,
+            y: area.y + area.height - panel_height,
+            width: area.width,
+            height: panel_height,
+        };
+        let max_width = (panel_area.width as usize).saturating_sub(4);
+        let visible = (panel_height as usize).saturating_sub(2);
+
+        let entries = self.audit.entries();
+        let lines: Vec<Line> = if entries.is_empty() {
+            vec![Line::from(Span::styled(
+                "Nothing changed or transferred yet this session",
+                Style::default().fg(Color::Gray),
+            ))]
+        } else {
+            entries
+                .iter()
+                .skip(entries.len().saturating_sub(visible))
+                .map(|entry| {
+                    let (status, color) = match &entry.outcome {
+                        Outcome::Running => ("running".to_string(), Color::Cyan),
+                        Outcome::Succeeded => ("ok".to_string(), Color::Green),
+                        Outcome::Failed(e) => (format!("failed: {e}"), Color::Red),
+                    };
+                    let text = format!(
+                        "{}  {:<16} {}  [{status}]",
+                        entry.time.format("%H:%M:%S"),
+                        entry.action.label(),
+                        entry.target
+                    );
+                    Line::from(Span::styled(
+                        truncate_with_ellipsis(&text, max_width),
+                        Style::default().fg(color),
+                    ))
+                })
+                .collect()
+        };
+        let footer = match &self.audit.write_error {
+            Some(e) => Line::from(format!(" Not saved to file: {e} "))
+                .centered()
+                .red(),
+            None => Line::from(" H to hide ").centered().yellow(),
+        };
+
+        // Clear the panel area with a background
+        for y in panel_area.y..panel_area.y + panel_area.height {
+            for x in panel_area.x..panel_area.x + panel_area.width {
+                buf[(x, y)].reset();
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(format!(" History ({}, times in UTC) ", entries.len()))
+                    .title_bottom(footer)
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .render(panel_area, buf);
+    }
+
     /// Render the download destination picker popup.
     fn render_download_picker_popup(&self, area: Rect, buf: &mut Buffer) {
         let Some(browsing) = self.browsing() else {
             return;
         };
 
+        let Modal::DownloadPicker {
+            menu,
+            use_default_dir,
+            snapshot,
+            ..
+        } = &self.modal
+        else {
+            return;
+        };
+        let default_dir = self.config.download_dir().filter(|_| *use_default_dir);
+
         // Calculate popup size
         let popup_width = (area.width * 3 / 4).min(60);
-        let popup_height = 8;
+        #[allow(clippy::cast_possible_truncation)] // recent list is capped at a handful of entries
+        let popup_height = if default_dir.is_some() {
+            9
+        } else {
+            8 + menu.items.len() as u16
+        };
 
         // Center the popup
         let popup_area = Rect {
@@ -645,36 +1636,40 @@ impl App {
             }
         }
 
-        let selected_file = browsing
-            .files
+        // The name without the icon shown in the list
+        let name = browsing
+            .file_items
             .get(browsing.selected_index)
-            .map(std::string::String::as_str)
-            .unwrap_or("No file selected");
-
-        // Extract the name without the icon prefix
-        let folder_prefix = format!("{} ", self.icons.folder);
-        let file_prefix = format!("{} ", self.icons.file);
-        let name = if selected_file.starts_with(&folder_prefix) {
-            selected_file
-                .strip_prefix(&folder_prefix)
-                .unwrap_or(selected_file)
-        } else if selected_file.starts_with(&file_prefix) {
-            selected_file
-                .strip_prefix(&file_prefix)
-                .unwrap_or(selected_file)
-        } else {
-            selected_file
-        };
+            .map_or("No file selected", |item| item.actual_name.as_str());
 
-        let download_text = [
-            format!("Ready to download: {name}"),
-            String::new(),
-            "Press Enter to select download destination".to_string(),
-            "Press Esc to cancel".to_string(),
+        let target = match (snapshot, self.marked_summary()) {
+            (Some(snapshot), _) => format!("{name} @ {snapshot}"),
+            (None, Some((count, _))) => format!("{count} marked items"),
+            (None, None) => name.to_string(),
+        };
+        let mut lines = vec![
+            Line::from(format!("Ready to download: {target}")),
+            Line::from(""),
         ];
+        if let Some(dir) = default_dir {
+            let max_width = (popup_width as usize).saturating_sub(8);
+            lines.push(Line::from(Span::styled(
+                truncate_with_ellipsis(&dir.display().to_string(), max_width),
+                Style::default().fg(Color::White),
+            )));
+            lines.push(Line::from(""));
+            lines.push(Line::from(
+                "Enter to download here • D to choose another folder",
+            ));
+        } else {
+            let max_width = (popup_width as usize).saturating_sub(8);
+            lines.extend(menu_lines(menu, max_width, Color::Green));
+            lines.push(Line::from(""));
+            lines.push(Line::from("Key or ↑/↓ + Enter to download"));
+        }
+        lines.push(Line::from("Press Esc to cancel"));
 
-        let info_text = download_text.join("\n");
-        let info_paragraph = Paragraph::new(info_text)
+        let info_paragraph = Paragraph::new(lines)
             .block(
                 Block::bordered()
                     .border_type(BorderType::Rounded)
@@ -689,6 +1684,7 @@ impl App {
 
     /// Render the download progress popup.
     fn render_download_progress_popup(
+        &self,
         area: Rect,
         buf: &mut Buffer,
         progress: &crate::app::DownloadProgress,
@@ -757,7 +1753,7 @@ impl App {
             .block(
                 Block::bordered()
                     .border_type(BorderType::Rounded)
-                    .title(" Download Progress ")
+                    .title(format!(" {} Download Progress ", self.spinner()))
                     .style(Style::default().fg(Color::Yellow).bg(Color::Black)),
             )
             .style(Style::default().bg(Color::Black));
@@ -765,11 +1761,16 @@ impl App {
         info_paragraph.render(popup_area, buf);
     }
 
-    /// Render the sort selection popup.
-    fn render_sort_popup(area: Rect, buf: &mut Buffer) {
+    /// Render the upload progress popup.
+    fn render_upload_progress_popup(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        progress: &crate::app::UploadProgress,
+    ) {
         // Calculate popup size
-        let popup_width = 50;
-        let popup_height = 10;
+        let popup_width = (area.width * 3 / 4).min(70);
+        let popup_height = 12;
 
         // Center the popup
         let popup_area = Rect {
@@ -786,68 +1787,123 @@ impl App {
             }
         }
 
-        let sort_text = [
-            "Select sorting criteria:".to_string(),
-            String::new(),
-            "n - Sort by Name".to_string(),
-            "m - Sort by Date Modified".to_string(),
-            "t - Sort by Date Created".to_string(),
-            "s - Sort by Size".to_string(),
+        let mut progress_lines = vec![
+            format!("Uploading: {}", progress.current_file),
             String::new(),
-            "Press Esc to cancel".to_string(),
+            format!(
+                "Files: {} / {}",
+                progress.files_completed, progress.total_files
+            ),
         ];
 
-        let info_text = sort_text.join("\n");
+        // Add bytes uploaded if available
+        if let Some(total_bytes) = progress.total_bytes {
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                clippy::cast_precision_loss
+            )]
+            let percentage = if total_bytes > 0 {
+                (progress.bytes_uploaded as f64 / total_bytes as f64 * 100.0) as u8
+            } else {
+                100
+            };
+            progress_lines.push(format!(
+                "Size: {} / {} ({}%)",
+                format_bytes(progress.bytes_uploaded),
+                format_bytes(total_bytes),
+                percentage
+            ));
+        } else {
+            progress_lines.push(format!(
+                "Uploaded: {}",
+                format_bytes(progress.bytes_uploaded)
+            ));
+        }
+
+        // Add error message if present
+        if let Some(error) = &progress.error_message {
+            progress_lines.push(String::new());
+            progress_lines.push(format!("Error: {error}"));
+        }
+
+        let info_text = progress_lines.join("\n");
         let info_paragraph = Paragraph::new(info_text)
             .block(
                 Block::bordered()
                     .border_type(BorderType::Rounded)
-                    .title(" Sort Files ")
-                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+                    .title(format!(" {} Upload Progress ", self.spinner()))
+                    .style(Style::default().fg(Color::Yellow).bg(Color::Black)),
             )
-            .style(Style::default().bg(Color::Black))
-            .alignment(Alignment::Left);
+            .style(Style::default().bg(Color::Black));
 
         info_paragraph.render(popup_area, buf);
     }
 
-    /// Render the clone dialog popup.
-    fn render_clone_dialog_popup(
+    /// Render the sort selection popup. `reversible` orders are reversed by picking them again.
+    fn render_sort_popup(
         area: Rect,
         buf: &mut Buffer,
-        input: &str,
-        original_path: &str,
-        is_folder: bool,
+        title: &str,
+        menu: &Menu<SortCriteria>,
+        reversible: bool,
     ) {
         // Calculate popup size
-        let popup_width = (area.width * 3 / 4).min(70);
-
-        let item_type = if is_folder { "folder" } else { "blob" };
-        let can_confirm = input != original_path && !input.is_empty();
+        let popup_width = 50;
+        #[allow(clippy::cast_possible_truncation)] // at most four sort orders
+        let popup_height = menu.items.len() as u16 + 6 + u16::from(reversible);
 
-        let enter_hint = if can_confirm {
-            "Enter to confirm"
-        } else {
-            "Enter to confirm (change name first)"
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
         };
 
-        // Calculate wrapped line counts for dynamic height
-        // Account for borders (2 chars)
-        let content_width = popup_width.saturating_sub(2) as usize;
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
 
-        let original_line = format!("Original: {original_path}");
+        let mut lines = vec![Line::from("Select sorting criteria:"), Line::from("")];
+        lines.extend(menu_lines(
+            menu,
+            (popup_width as usize).saturating_sub(4),
+            Color::Cyan,
+        ));
+        lines.push(Line::from(""));
+        if reversible {
+            lines.push(Line::from("Pick the current order again to reverse it"));
+        }
+        lines.push(Line::from("Key or ↑/↓ + Enter to sort • Esc to cancel"));
 
-        // Calculate how many lines the original path will take when wrapped
-        #[allow(clippy::cast_possible_truncation)]
-        let original_lines = if content_width > 0 {
-            original_line.len().div_ceil(content_width).max(1) as u16
-        } else {
-            1
-        };
+        let info_paragraph = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(format!(" {title} "))
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .alignment(Alignment::Left);
 
-        // New path input is always 1 line (scrolling)
-        // Calculate total height: title line (1) + blank (1) + original lines + blank (1) + new path (1) + blank (1) + hint line (1) + borders (2)
-        let popup_height = (1 + 1 + original_lines + 1 + 1 + 1 + 1 + 2).max(10);
+        info_paragraph.render(popup_area, buf);
+    }
+
+    /// Render the tool choice for copying a download command.
+    fn render_copy_command_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        path: &str,
+        menu: &Menu<CommandStyle>,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(60);
+        #[allow(clippy::cast_possible_truncation)] // two tools
+        let popup_height = menu.items.len() as u16 + 6;
 
         // Center the popup
         let popup_area = Rect {
@@ -864,76 +1920,1686 @@ impl App {
             }
         }
 
-        // For the new path input, show a scrolling view that keeps cursor visible
-        let new_path_prefix = "New path: ";
-        let available_input_width = content_width.saturating_sub(new_path_prefix.len());
-
-        // Calculate visible portion of input - scroll to keep cursor at end visible
-        let visible_input = if input.len() > available_input_width {
-            let start = input.len() - available_input_width;
-            format!("…{}", &input[start + 1..])
-        } else {
-            input.to_string()
-        };
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let mut lines = vec![
+            Line::from(truncate_with_ellipsis(
+                &format!("Download {path} with:"),
+                max_width,
+            )),
+            Line::from(""),
+        ];
+        lines.extend(menu_lines(menu, max_width, Color::Cyan));
+        lines.push(Line::from(""));
+        lines.push(Line::from("Key or ↑/↓ + Enter to copy • Esc to cancel"));
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Copy Command ")
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render the lease actions for a blob.
+    fn render_lease_menu_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        blob_path: &str,
+        menu: &Menu<LeaseAction>,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(60);
+        #[allow(clippy::cast_possible_truncation)] // four lease actions
+        let popup_height = menu.items.len() as u16 + 6;
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let mut lines = vec![
+            Line::from(truncate_with_ellipsis(
+                &format!("Lease on {blob_path}"),
+                max_width,
+            )),
+            Line::from(""),
+        ];
+        lines.extend(menu_lines(menu, max_width, Color::Cyan));
+        lines.push(Line::from(""));
+        lines.push(Line::from("Key or ↑/↓ + Enter to apply • Esc to cancel"));
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Lease ")
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render the clone dialog popup.
+    fn render_clone_dialog_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        action: &str,
+        input: &str,
+        original_path: &str,
+        is_folder: bool,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(70);
+
+        let item_type = if is_folder { "folder" } else { "blob" };
+        let can_confirm = input != original_path && !input.is_empty();
+
+        let enter_hint = if can_confirm {
+            "Enter to confirm"
+        } else {
+            "Enter to confirm (change name first)"
+        };
+
+        // Calculate wrapped line counts for dynamic height
+        // Account for borders (2 chars)
+        let content_width = popup_width.saturating_sub(2) as usize;
+
+        let original_line = format!("Original: {original_path}");
+
+        // Calculate how many lines the original path will take when wrapped
+        #[allow(clippy::cast_possible_truncation)]
+        let original_lines = if content_width > 0 {
+            original_line.len().div_ceil(content_width).max(1) as u16
+        } else {
+            1
+        };
+
+        // New path input is always 1 line (scrolling)
+        // Calculate total height: title line (1) + blank (1) + original lines + blank (1) + new path (1) + blank (1) + hint line (1) + borders (2)
+        let popup_height = (1 + 1 + original_lines + 1 + 1 + 1 + 1 + 2).max(10);
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        // For the new path input, show a scrolling view that keeps cursor visible
+        let new_path_prefix = "New path: ";
+        let available_input_width = content_width.saturating_sub(new_path_prefix.len());
+
+        // Calculate visible portion of input - scroll to keep cursor at end visible
+        let visible_input = if input.len() > available_input_width {
+            let start = input.len() - available_input_width;
+            format!("…{}", &input[start + 1..])
+        } else {
+            input.to_string()
+        };
 
         let new_path_display = format!("{new_path_prefix}{visible_input}");
 
-        let clone_text = [
-            format!("Clone {item_type} to new path:"),
+        let clone_text = [
+            format!("{action} {item_type} to new path:"),
+            String::new(),
+            original_line,
+            String::new(),
+            new_path_display.clone(),
+            String::new(),
+            format!("{enter_hint} • Esc to cancel"),
+        ];
+
+        let info_text = clone_text.join("\n");
+
+        // Highlight the input line differently
+        let title_style = if can_confirm {
+            Style::default().fg(Color::Green).bg(Color::Black)
+        } else {
+            Style::default().fg(Color::Yellow).bg(Color::Black)
+        };
+
+        let info_paragraph = Paragraph::new(info_text)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(format!(" {action} "))
+                    .style(title_style),
+            )
+            .style(Style::default().bg(Color::Black))
+            .wrap(Wrap { trim: false });
+
+        info_paragraph.render(popup_area, buf);
+
+        // Render cursor at end of input (always on the new path line)
+        // Y position: border (1) + title line (1) + blank (1) + original lines + blank (1) = new path line
+        let input_y = popup_area.y + 1 + 1 + original_lines + 1;
+        // X position: border (1) + prefix + visible input length
+        #[allow(clippy::cast_possible_truncation)]
+        let cursor_x = popup_area.x + 1 + new_path_display.len() as u16;
+
+        // Show cursor (blinking effect via underscore)
+        if cursor_x < popup_area.x + popup_area.width - 1
+            && input_y < popup_area.y + popup_area.height - 1
+        {
+            buf[(cursor_x, input_y)].set_char('▏');
+            buf[(cursor_x, input_y)].set_style(Style::default().fg(Color::White).bg(Color::Black));
+        }
+    }
+
+    /// Render the clone progress popup.
+    fn render_clone_progress_popup(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        action: &str,
+        progress: &crate::app::CloneProgress,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(70);
+        let popup_height = 10;
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let mut progress_lines = vec![format!("{action} in progress..."), String::new()];
+
+        if !progress.current_file.is_empty() {
+            progress_lines.push(format!("Current: {}", progress.current_file));
+        }
+
+        progress_lines.push(format!(
+            "Files: {} / {}",
+            progress.files_completed, progress.total_files
+        ));
+
+        // Add error message if present
+        if let Some(error) = &progress.error_message {
+            progress_lines.push(String::new());
+            progress_lines.push(format!("Error: {error}"));
+        }
+
+        let info_text = progress_lines.join("\n");
+        let info_paragraph = Paragraph::new(info_text)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(format!(" {} {action} Progress ", self.spinner()))
+                    .style(Style::default().fg(Color::Yellow).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black));
+
+        info_paragraph.render(popup_area, buf);
+    }
+
+    /// Render the delete confirmation dialog popup.
+    fn render_delete_dialog_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        input: &str,
+        heading: &str,
+        confirmation: &str,
+        is_folder: bool,
+        plan: Option<&crate::app::FolderPlan>,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(70);
+        let popup_height = 12;
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let can_confirm = input == confirmation;
+
+        let enter_hint = if can_confirm {
+            "Enter to confirm"
+        } else {
+            "Type name to confirm"
+        };
+
+        let warning = match plan {
+            Some(plan) => format!(
+                "⚠ This will delete {} blobs totaling {}!",
+                plan.blobs.len(),
+                format_bytes(plan.total_bytes())
+            ),
+            None if is_folder => "⚠ This will delete all blobs in this folder!".to_string(),
+            None => "⚠ This action cannot be undone!".to_string(),
+        };
+
+        let delete_text = [
+            heading.to_string(),
+            String::new(),
+            warning,
+            String::new(),
+            format!("Type \"{confirmation}\" to confirm:"),
+            input.to_string(),
+            String::new(),
+            format!("{enter_hint} • Esc to cancel"),
+        ];
+
+        let info_text = delete_text.join("\n");
+
+        let title_style = if can_confirm {
+            Style::default().fg(Color::Red).bg(Color::Black)
+        } else {
+            Style::default().fg(Color::Yellow).bg(Color::Black)
+        };
+
+        let info_paragraph = Paragraph::new(info_text)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Delete ")
+                    .style(title_style),
+            )
+            .style(Style::default().bg(Color::Black));
+
+        info_paragraph.render(popup_area, buf);
+
+        // Render the input field with cursor
+        let input_y = popup_area.y + 6;
+        let input_x = popup_area.x + 1; // After left border
+        #[allow(clippy::cast_possible_truncation)]
+        let cursor_x = input_x + input.len() as u16;
+
+        // Show cursor
+        if cursor_x < popup_area.x + popup_area.width - 1 {
+            buf[(cursor_x, input_y)].set_char('▏');
+            buf[(cursor_x, input_y)].set_style(Style::default().fg(Color::White).bg(Color::Black));
+        }
+    }
+
+    /// Render the warning shown before a folder operation that exceeds the configured limits.
+    #[allow(clippy::too_many_arguments)]
+    fn render_sync_preview_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        local_dir: &std::path::Path,
+        prefix: &str,
+        mode: SyncMode,
+        plan: &SyncPlan,
+        scroll: usize,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 4 / 5).min(90);
+        let popup_height = (area.height * 4 / 5).max(12).min(area.height);
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let target = if prefix.is_empty() { "/" } else { prefix };
+        let mut lines = vec![
+            Line::from(truncate_with_ellipsis(
+                &format!("{} ⇄ {target}", local_dir.display()),
+                max_width,
+            )),
+            Line::from(Span::styled(
+                format!("Mode: {}", mode.label()),
+                Style::default().fg(Color::Cyan),
+            )),
+            Line::from(format!(
+                "{} to upload • {} to download • {} to delete • {} unchanged • {}",
+                plan.count(SyncAction::Upload),
+                plan.count(SyncAction::Download),
+                plan.count(SyncAction::DeleteRemote) + plan.count(SyncAction::DeleteLocal),
+                plan.unchanged,
+                format_bytes(plan.total_bytes())
+            )),
+            Line::from(""),
+        ];
+
+        if plan.steps.is_empty() {
+            lines.push(Line::from("Nothing to do: both sides are in sync"));
+        }
+        let visible = (popup_height as usize).saturating_sub(lines.len() + 2);
+        for step in plan.steps.iter().skip(scroll).take(visible) {
+            let color = match step.action {
+                SyncAction::Upload => Color::Green,
+                SyncAction::Download => Color::Blue,
+                SyncAction::DeleteRemote | SyncAction::DeleteLocal => Color::Red,
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:<13}", step.action.label()),
+                    Style::default().fg(color),
+                ),
+                Span::raw(truncate_with_ellipsis(
+                    &step.relative_path,
+                    max_width.saturating_sub(13),
+                )),
+            ]));
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Sync Preview ")
+                    .title_bottom(
+                        " Enter/y to sync • m to change mode • ↑/↓ to scroll • Esc to cancel ",
+                    )
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+
+        paragraph.render(popup_area, buf);
+    }
+
+    fn render_large_operation_popup(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        operation: &PendingOperation,
+        plan: &FolderPlan,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(70);
+        let popup_height = 12;
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(16);
+        let (action, target) = match operation {
+            PendingOperation::Download {
+                folder_name,
+                destination,
+            } => (
+                "Download",
+                format!(
+                    "{folder_name} → {}",
+                    truncate_with_ellipsis(&destination.display().to_string(), max_width)
+                ),
+            ),
+            PendingOperation::Delete { target_path } => {
+                ("Delete", target_path.trim_end_matches('/').to_string())
+            }
+            PendingOperation::DownloadMarked { destination } => (
+                "Download",
+                format!(
+                    "marked items → {}",
+                    truncate_with_ellipsis(&destination.display().to_string(), max_width)
+                ),
+            ),
+            PendingOperation::DeleteMarked => ("Delete", "marked items".to_string()),
+            PendingOperation::Move {
+                source,
+                destination,
+                ..
+            } => (
+                "Move",
+                format!(
+                    "{} → {}",
+                    source.trim_end_matches('/'),
+                    destination.trim_end_matches('/')
+                ),
+            ),
+            PendingOperation::Sync {
+                local_dir, prefix, ..
+            } => (
+                "Sync",
+                format!(
+                    "{} ⇄ {}",
+                    truncate_with_ellipsis(&local_dir.display().to_string(), max_width),
+                    if prefix.is_empty() { "/" } else { prefix }
+                ),
+            ),
+        };
+        let limits = &self.config.limits;
+        // A sync is also confirmed when it deletes anything, however small it is
+        let heading = if limits.exceeded_by(plan.blobs.len(), plan.total_bytes()) {
+            "⚠ This is a very large operation"
+        } else {
+            "⚠ This sync deletes files"
+        };
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                heading,
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(format!(
+                "{action}: {}",
+                truncate_with_ellipsis(&target, max_width)
+            )),
+            Line::from(format!(
+                "Blobs: {} (warn above {})",
+                plan.blobs.len(),
+                limits.warn_file_count
+            )),
+            Line::from(format!(
+                "Total size: {} (warn above {})",
+                format_bytes(plan.total_bytes()),
+                format_bytes(limits.warn_total_bytes)
+            )),
+        ];
+        if let PendingOperation::Sync { plan, .. } = operation {
+            lines.push(Line::from(format!(
+                "Deletes: {}",
+                plan.count(SyncAction::DeleteRemote) + plan.count(SyncAction::DeleteLocal)
+            )));
+        }
+        lines.extend([
+            Line::from(""),
+            Line::from(Span::styled(
+                "Press y to continue anyway • n or Esc to cancel",
+                Style::default().fg(Color::Yellow),
+            )),
+        ]);
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Double)
+                    .title(" Large Operation ")
+                    .style(Style::default().fg(Color::Red).bg(Color::Black)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .alignment(Alignment::Center);
+
+        paragraph.render(popup_area, buf);
+    }
+
+    /// Render the diff of an edit, asking whether to upload it.
+    fn render_edit_upload_popup(area: Rect, buf: &mut Buffer, blob_path: &str, diff: &[DiffLine]) {
+        // The title already names the blob, so the `---`/`+++` lines are left out
+        let hunks: Vec<&DiffLine> = diff
+            .iter()
+            .filter(|line| line.kind != DiffKind::Header)
+            .collect();
+        let shown = hunks.len();
+        let popup_width = (area.width * 4 / 5).min(100);
+        #[allow(clippy::cast_possible_truncation)] // at most MAX_DIFF_LINES
+        let popup_height = (shown.min(MAX_DIFF_LINES) as u16 + 8).min(area.height);
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                truncate_with_ellipsis(blob_path, max_width),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(edit_summary(diff)),
+            Line::from(""),
+        ];
+        lines.extend(hunks.iter().take(MAX_DIFF_LINES).map(|line| {
+            Line::from(Span::styled(
+                truncate_with_ellipsis(&line.text, max_width),
+                diff_line_style(line.kind),
+            ))
+        }));
+        if shown > MAX_DIFF_LINES {
+            lines.push(Line::from(Span::styled(
+                format!("… {} more lines", shown - MAX_DIFF_LINES),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Press y to upload • e to edit again • n or Esc to discard",
+            Style::default().fg(Color::Yellow),
+        )));
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Upload Edit? ")
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render the size breakdown of `folder`: what is directly inside it, largest first, with
+    /// each entry's share of the total as a bar.
+    fn render_usage_popup(&self, area: Rect, buf: &mut Buffer, folder: &str, selected: usize) {
+        const BAR_WIDTH: usize = 20;
+
+        let popup_width = (area.width * 4 / 5).min(100);
+        let popup_height = (area.height * 4 / 5).max(12).min(area.height);
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let Some(state) = self.browsing() else {
+            return;
+        };
+        let usage = self
+            .usage
+            .get_prefix(&state.container_name, folder)
+            .unwrap_or_default();
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let counting = if usage.error.is_some() {
+            "  (counting stopped early)".to_string()
+        } else if usage.complete {
+            String::new()
+        } else {
+            format!("  {} counting…", self.spinner())
+        };
+        let mut lines = vec![
+            Line::from(Span::styled(
+                truncate_with_ellipsis(&format!("{}/{folder}", state.container_name), max_width),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(format!(
+                "{} in {} blobs{counting}",
+                format_bytes(usage.bytes),
+                usage.blobs
+            )),
+            Line::from(""),
+        ];
+
+        let entries = usage.breakdown();
+        if entries.is_empty() && usage.complete {
+            lines.push(Line::from("Nothing here"));
+        }
+        let visible = (popup_height as usize)
+            .saturating_sub(lines.len() + 2)
+            .max(1);
+        let offset = (selected + 1).saturating_sub(visible);
+        let name_width = max_width.saturating_sub(BAR_WIDTH + 22);
+        for (index, (name, child)) in entries.iter().enumerate().skip(offset).take(visible) {
+            #[allow(clippy::cast_precision_loss)] // only for display
+            let share = if usage.bytes == 0 {
+                0.0
+            } else {
+                child.bytes as f64 / usage.bytes as f64
+            };
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // 0..=BAR_WIDTH
+            let filled = (share * BAR_WIDTH as f64).round() as usize;
+            let style = if index == selected {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:>10} {:>5.1}% ", format_bytes(child.bytes), share * 100.0),
+                    style,
+                ),
+                Span::styled(
+                    format!(
+                        "{}{}",
+                        "█".repeat(filled),
+                        "░".repeat(BAR_WIDTH.saturating_sub(filled))
+                    ),
+                    style.fg(Color::Cyan),
+                ),
+                Span::styled(
+                    format!(" {}", truncate_with_ellipsis(name, name_width)),
+                    if name.ends_with('/') {
+                        style.fg(Color::Blue)
+                    } else {
+                        style
+                    },
+                ),
+            ]));
+        }
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Size Breakdown ")
+                    .title_bottom(
+                        " Enter to open a folder • h to go up • r to count again • Esc to close ",
+                    )
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render a comparison: the unified diff of two texts, or the properties of two binaries
+    /// side by side with those that differ highlighted.
+    fn render_diff_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        title: &str,
+        comparison: &Comparison,
+        scroll: usize,
+    ) {
+        let popup_width = (area.width * 9 / 10).min(140);
+        let popup_height = (area.height * 4 / 5).max(12).min(area.height);
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                truncate_with_ellipsis(title, max_width),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(comparison.summary()),
+            Line::from(""),
+        ];
+        let visible = (popup_height as usize).saturating_sub(lines.len() + 2);
+        match comparison {
+            Comparison::Text(diff) => {
+                lines.extend(diff.iter().skip(scroll).take(visible).map(|line| {
+                    Line::from(Span::styled(
+                        truncate_with_ellipsis(&line.text, max_width),
+                        diff_line_style(line.kind),
+                    ))
+                }));
+            }
+            Comparison::Binary(rows) => {
+                let column = max_width.saturating_sub(14) / 2;
+                lines.extend(rows.iter().skip(scroll).take(visible).map(|row| {
+                    let style = if row.differs() {
+                        Style::default().fg(Color::Yellow)
+                    } else {
+                        Style::default()
+                    };
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{:<14}", row.label),
+                            Style::default().fg(Color::Cyan),
+                        ),
+                        Span::styled(
+                            format!("{:<column$}", truncate_with_ellipsis(&row.left, column)),
+                            style,
+                        ),
+                        Span::styled(truncate_with_ellipsis(&row.right, column), style),
+                    ])
+                }));
+            }
+        }
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Compare ")
+                    .title_bottom(" ↑/↓ or k/j to scroll • q or Esc to close ")
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render the command being typed, with the commands it could be below it.
+    fn render_command_popup(&self, area: Rect, buf: &mut Buffer, input: &str, selected: usize) {
+        let suggestions = palette::matching(input, matches!(self.session, Session::Selecting));
+        let popup_width = (area.width * 4 / 5).min(90);
+        #[allow(clippy::cast_possible_truncation)] // at most a screenful of rows
+        let popup_height =
+            (suggestions.len().min(12) as u16 + 4).min(area.height - area.height / 5);
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: area.height / 5,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].reset();
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = usize::from(popup_width).saturating_sub(4);
+        let visible = usize::from(popup_height.saturating_sub(4)).max(1);
+        let first = selected.saturating_sub(visible - 1);
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled(":", Style::default().fg(Color::Cyan)),
+                Span::styled(format!("{input}▏"), Style::default().fg(Color::White)),
+            ]),
+            Line::from(""),
+        ];
+        for (i, command) in suggestions.iter().enumerate().skip(first).take(visible) {
+            let text = truncate_with_ellipsis(
+                &format!(
+                    "{:<28}{}",
+                    format!("{} {}", command.name, command.args),
+                    command.description
+                ),
+                max_width,
+            );
+            let style = if i == selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            lines.push(Line::from(Span::styled(text, style)));
+        }
+        if suggestions.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No such command",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(" Command ")
+                    .title_alignment(Alignment::Center)
+                    .title_bottom(
+                        Line::from(
+                            " Enter to run • Tab to complete • ↑/↓ to pick • Esc to cancel ",
+                        )
+                        .centered(),
+                    )
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render every keybinding by where it works, scrolled down `scroll` lines.
+    fn render_help_popup(area: Rect, buf: &mut Buffer, scroll: usize) {
+        let popup_width = (area.width * 4 / 5).min(90);
+        let popup_height = (area.height * 4 / 5).max(8).min(area.height);
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].reset();
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let keys_width = help::keys_width();
+        let mut lines = Vec::with_capacity(help::line_count());
+        for (i, section) in help::KEYMAP.iter().enumerate() {
+            if i > 0 {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(Span::styled(
+                section.title,
+                Style::default().fg(Color::Yellow).bold(),
+            )));
+            for binding in section.bindings {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {:<keys_width$}  ", binding.keys),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::raw(binding.action),
+                ]));
+            }
+        }
+
+        // Stop scrolling once the last line is in view
+        let visible = usize::from(popup_height.saturating_sub(2));
+        let scroll = scroll.min(lines.len().saturating_sub(visible));
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(" Keys ")
+                    .title_alignment(Alignment::Center)
+                    .title_bottom(
+                        Line::from(" j/k PgUp/PgDn gg/G scroll • ? or Esc to close ").centered(),
+                    )
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .scroll((u16::try_from(scroll).unwrap_or(u16::MAX), 0));
+        paragraph.render(popup_area, buf);
+    }
+
+    /// Render the background jobs panel, or one job's files when expanded.
+    fn render_jobs_popup(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        job_index: usize,
+        file_index: Option<usize>,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 4 / 5).min(110);
+        let popup_height = (area.height * 4 / 5).max(8).min(area.height);
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let visible = (popup_height as usize).saturating_sub(4).max(1);
+        let selected_style = Style::default().fg(Color::Black).bg(Color::Cyan);
+        let mut lines = Vec::new();
+
+        let (title, footer) = if let (Some(file_index), Some(job)) =
+            (file_index, self.jobs.get(job_index))
+        {
+            lines.push(Line::from(Span::styled(
+                truncate_with_ellipsis(&job.label, max_width),
+                Style::default().fg(Color::Yellow),
+            )));
+            let files = job.files();
+            let first = file_index.saturating_sub(visible.saturating_sub(2));
+            for (i, file) in files.iter().enumerate().skip(first).take(visible - 1) {
+                let (status, color) = match &file.status {
+                    FileStatus::Queued => ("queued".to_string(), Color::Gray),
+                    FileStatus::Running => (
+                        format!(
+                            "{} / {}",
+                            format_bytes(file.bytes_done),
+                            format_bytes(file.size)
+                        ),
+                        Color::Cyan,
+                    ),
+                    FileStatus::Done => ("done".to_string(), Color::Green),
+                    FileStatus::Failed(e) => (format!("failed: {e}"), Color::Red),
+                    FileStatus::Skipped => ("skipped".to_string(), Color::DarkGray),
+                    FileStatus::Aborted => ("aborted".to_string(), Color::Magenta),
+                };
+                let text = truncate_with_ellipsis(
+                    &format!("{}  [{status}]", file.relative_path),
+                    max_width,
+                );
+                let style = if i == file_index {
+                    selected_style
+                } else {
+                    Style::default().fg(color)
+                };
+                lines.push(Line::from(Span::styled(text, style)));
+            }
+            (" Job Files ", "s skip • a abort • r requeue • Esc/← back")
+        } else {
+            let first = job_index.saturating_sub(visible.saturating_sub(1));
+            for (i, job) in self.jobs.iter().enumerate().skip(first).take(visible) {
+                let summary = job.summary();
+                let state = if summary.is_finished() {
+                    "finished"
+                } else {
+                    "running"
+                };
+                let mut text = format!(
+                    "{}  {}/{} files, {} / {}  {state}",
+                    job.label,
+                    summary.done,
+                    summary.done
+                        + summary.queued
+                        + summary.running
+                        + summary.failed
+                        + summary.skipped
+                        + summary.aborted,
+                    format_bytes(summary.bytes_done),
+                    format_bytes(summary.bytes_total),
+                );
+                let problems = summary.failed + summary.skipped + summary.aborted;
+                if problems > 0 {
+                    text.push_str(&format!(" • {problems} not downloaded"));
+                }
+                let style = if i == job_index {
+                    selected_style
+                } else if summary.failed > 0 {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                lines.push(Line::from(Span::styled(
+                    truncate_with_ellipsis(&text, max_width),
+                    style,
+                )));
+            }
+            (
+                " Jobs ",
+                "Enter to show files • a cancel job • r requeue • x remove finished • Esc to close",
+            )
+        };
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(title)
+                    .title_bottom(Line::from(format!(" {footer} ")).centered())
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black));
+
+        paragraph.render(popup_area, buf);
+    }
+
+    /// Render the lines of a followed blob, newest at the bottom.
+    fn render_tail_popup(area: Rect, buf: &mut Buffer, view: &TailView, back: usize) {
+        // Calculate popup size
+        let popup_width = (area.width * 9 / 10).max(20).min(area.width);
+        let popup_height = (area.height * 4 / 5).max(8).min(area.height);
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let lines = view.lines();
+        let visible = (popup_height as usize).saturating_sub(2).max(1);
+        let back = back.min(lines.len());
+        let end = lines.len() - back;
+        let start = end.saturating_sub(visible);
+        let mut text: Vec<Line> = lines[start..end]
+            .iter()
+            .map(|line| Line::from(line.as_str()).fg(Color::White))
+            .collect();
+        if let Some(error) = view.error() {
+            text.push(Line::from(format!("Polling failed: {error}")).fg(Color::Red));
+        }
+
+        let state = if back == 0 {
+            "following".to_string()
+        } else {
+            format!("paused, {back} lines up")
+        };
+        let title = format!(
+            " Tail: {} ({}, {state}) ",
+            truncate_with_ellipsis(&view.blob_path, (popup_width as usize / 2).max(10)),
+            format_bytes(view.offset())
+        );
+        Paragraph::new(text)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(title)
+                    .title_bottom(
+                        Line::from(" ↑/↓ scroll • g/G top/follow • Esc or f to close ").centered(),
+                    )
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render the startup chooser between the credentials that were found.
+    fn render_credential_picker_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        credentials: &[Credential],
+        unsupported: &[UnsupportedCredential],
+        selected: usize,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(80);
+        #[allow(clippy::cast_possible_truncation)] // a handful of profiles at most
+        let popup_height = ((credentials.len() + unsupported.len()) as u16 + 6).min(area.height);
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let mut lines = vec![
+            Line::from("Several credentials were found. Which one should be used?"),
+            Line::from(""),
+        ];
+        for (i, credential) in credentials.iter().enumerate() {
+            // The end of the key tells apart two keys for the same account
+            let key = credential.access_key.expose().trim_end_matches('=');
+            let key_hint = key.get(key.len().saturating_sub(4)..).unwrap_or_default();
+            let text = format!(
+                "{}  ({}, key …{key_hint})",
+                credential.account, credential.source
+            );
+            let style = if i == selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            lines.push(Line::from(Span::styled(
+                truncate_with_ellipsis(&text, max_width),
+                style,
+            )));
+        }
+        // Found, but every request is signed with an access key
+        for credential in unsupported {
+            let text = format!(
+                "{}  ({}, unsupported)",
+                credential.account.as_deref().unwrap_or("-"),
+                credential.source
+            );
+            lines.push(Line::from(Span::styled(
+                truncate_with_ellipsis(&text, max_width),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Choose Credentials ")
+                    .title_bottom(
+                        Line::from(" ↑/↓ to move • Enter to use • Esc to quit ").centered(),
+                    )
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black));
+
+        paragraph.render(popup_area, buf);
+    }
+
+    /// Render the search prompt and its results.
+    fn render_global_search_popup(&self, area: Rect, buf: &mut Buffer) {
+        let Modal::GlobalSearch {
+            input,
+            results,
+            selected,
+            scope,
+            running,
+        } = &self.modal
+        else {
+            return;
+        };
+        let selected = *selected;
+        let folder = self
+            .folder_search_target()
+            .map(|(container, prefix)| format!("{container}:/{}", prefix.trim_end_matches('/')))
+            .unwrap_or_default();
+        // Calculate popup size
+        let popup_width = (area.width * 4 / 5).min(110);
+        let popup_height = (area.height * 4 / 5).max(8).min(area.height);
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("Find: ", Style::default().fg(Color::Cyan)),
+                Span::styled(format!("{input}▏"), Style::default().fg(Color::White)),
+            ]),
+            Line::from(""),
+        ];
+
+        let footer = match results {
+            None => {
+                let (about, footer) = match scope {
+                    SearchScope::Accounts => (
+                        "Searches blob paths in every container of this account and configured profiles".to_string(),
+                        "Enter to search • Tab to search this folder • Esc to cancel",
+                    ),
+                    SearchScope::Folder => (
+                        format!("Searches blob paths under {folder} at any depth"),
+                        "Enter to search • Tab to search tags • Esc to cancel",
+                    ),
+                    SearchScope::Tags => (
+                        "Finds blobs in this container by index tags, e.g. env=prod, team=data".to_string(),
+                        "Enter to search • Tab to search all accounts • Esc to cancel",
+                    ),
+                };
+                lines.push(Line::from(about));
+                footer
+            }
+            Some(results) => {
+                let summary = format!(
+                    "{}{} match(es){}{}",
+                    results.hits.len(),
+                    if results.truncated { "+" } else { "" },
+                    running
+                        .as_ref()
+                        .map(|search| format!(
+                            " • {} searching, {} blobs looked at",
+                            self.spinner(),
+                            search.scanned()
+                        ))
+                        .unwrap_or_default(),
+                    if results.errors.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" • {} source(s) failed", results.errors.len())
+                    }
+                );
+                lines.push(Line::from(Span::styled(
+                    summary,
+                    Style::default().fg(Color::Yellow),
+                )));
+
+                // Keep the selected hit visible
+                let visible = (popup_height as usize).saturating_sub(7).max(1);
+                let first = selected.saturating_sub(visible - 1);
+                let max_width = (popup_width as usize).saturating_sub(4);
+                for (i, hit) in results.hits.iter().enumerate().skip(first).take(visible) {
+                    let text = format!(
+                        "[{}] {}/{}  {}",
+                        hit.source,
+                        hit.container,
+                        hit.path,
+                        hit.size.map(format_bytes).unwrap_or_default()
+                    );
+                    let style = if i == selected {
+                        Style::default().fg(Color::Black).bg(Color::Cyan)
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    lines.push(Line::from(Span::styled(
+                        truncate_with_ellipsis(&text, max_width),
+                        style,
+                    )));
+                }
+                let remaining = visible.saturating_sub(results.hits.len().min(visible));
+                for error in results.errors.iter().take(remaining) {
+                    lines.push(Line::from(Span::styled(
+                        truncate_with_ellipsis(&format!("⚠ {error}"), max_width),
+                        Style::default().fg(Color::Red),
+                    )));
+                }
+                "↑/↓ to choose • Enter to open • type to refine • Esc to close"
+            }
+        };
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(match scope {
+                        SearchScope::Accounts => " Search All Accounts ".to_string(),
+                        SearchScope::Folder => format!(" Search {folder} "),
+                        SearchScope::Tags => " Find Blobs by Tags ".to_string(),
+                    })
+                    .title_bottom(Line::from(format!(" {footer} ")).centered())
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black));
+
+        paragraph.render(popup_area, buf);
+    }
+
+    /// Render the time-travel timestamp prompt.
+    fn render_note_panel(area: Rect, buf: &mut Buffer, note: &str) {
+        Paragraph::new(note.to_string())
+            .block(
+                Block::bordered()
+                    .title(" Note ")
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded),
+            )
+            .fg(Color::Yellow)
+            .wrap(ratatui::widgets::Wrap { trim: true })
+            .render(area, buf);
+    }
+
+    fn render_edit_note_popup(area: Rect, buf: &mut Buffer, key: &str, input: &str) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(70);
+        let popup_height = 8;
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let note_text = [
+            format!("Note for {key}:"),
+            String::new(),
+            input.to_string(),
+            String::new(),
+            "Enter to save • empty to remove • Esc to cancel".to_string(),
+        ];
+
+        let note_paragraph = Paragraph::new(note_text.join("\n"))
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Edit Note ")
+                    .style(Style::default().fg(Color::Yellow).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black));
+
+        note_paragraph.render(popup_area, buf);
+
+        // Render the input field with cursor
+        let input_y = popup_area.y + 3;
+        #[allow(clippy::cast_possible_truncation)]
+        let cursor_x = popup_area.x + 1 + input.chars().count() as u16;
+
+        if cursor_x < popup_area.x + popup_area.width - 1 {
+            buf[(cursor_x, input_y)].set_char('▏');
+            buf[(cursor_x, input_y)].set_style(Style::default().fg(Color::White).bg(Color::Black));
+        }
+    }
+
+    /// Render the snapshots of a blob.
+    fn render_snapshots_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        blob_path: &str,
+        snapshots: &[BlobSnapshot],
+        selected: usize,
+        confirm_restore: bool,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(80);
+        let popup_height = (area.height * 3 / 4).max(8).min(area.height);
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let mut lines = vec![
+            Line::from(truncate_with_ellipsis(
+                &format!("Snapshots of {blob_path}"),
+                max_width,
+            )),
+            Line::from(""),
+        ];
+        if snapshots.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No snapshots yet",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        // Keep the selected snapshot visible
+        let visible = (popup_height as usize).saturating_sub(5).max(1);
+        let first = selected.saturating_sub(visible - 1);
+        for (i, snapshot) in snapshots.iter().enumerate().skip(first).take(visible) {
+            let style = if i == selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            lines.push(Line::from(Span::styled(
+                format!("{}  {}", snapshot.snapshot, format_bytes(snapshot.size)),
+                style,
+            )));
+        }
+
+        let footer = if confirm_restore {
+            "Overwrite the blob with this snapshot? y to restore • any other key to cancel"
+        } else {
+            "`c` create • `d` download • `r` restore • Esc to close"
+        };
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Snapshots ")
+                    .title_bottom(Line::from(format!(" {footer} ")).centered())
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render the tag editor for a blob.
+    fn render_edit_tags_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        blob_path: &str,
+        tags: &BTreeMap<String, String>,
+        selected: usize,
+        input: Option<&str>,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(70);
+        #[allow(clippy::cast_possible_truncation)] // a blob has at most ten tags
+        let popup_height = 7 + tags.len().max(1) as u16;
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let mut lines = vec![
+            Line::from(truncate_with_ellipsis(
+                &format!("Tags of {blob_path}"),
+                max_width,
+            )),
+            Line::from(""),
+        ];
+        if tags.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No tags",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        for (i, (key, value)) in tags.iter().enumerate() {
+            let style = if i == selected && input.is_none() {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            lines.push(Line::from(Span::styled(
+                truncate_with_ellipsis(&format!("{key} = {value}"), max_width),
+                style,
+            )));
+        }
+        lines.push(Line::from(""));
+        let footer = match input {
+            Some(input) => {
+                lines.push(Line::from(vec![
+                    Span::styled("New tag: ", Style::default().fg(Color::Cyan)),
+                    Span::styled(format!("{input}▏"), Style::default().fg(Color::White)),
+                ]));
+                "key=value • Enter to add • Esc to cancel"
+            }
+            None => "`a` to add • `x` to remove • Enter to save • Esc to cancel",
+        };
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" Edit Tags ")
+                    .title_bottom(Line::from(format!(" {footer} ")).centered())
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    /// Render the file types in the folder, with a check by each one shown.
+    fn render_extensions_popup(
+        area: Rect,
+        buf: &mut Buffer,
+        counts: &[(String, usize)],
+        shown: &BTreeSet<String>,
+        selected: usize,
+    ) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(50);
+        let popup_height = (area.height * 3 / 4).max(8).min(area.height);
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let mut lines = vec![
+            Line::from(if shown.is_empty() {
+                "Showing every file type".to_string()
+            } else {
+                format!("Showing {} of {} file types", shown.len(), counts.len())
+            }),
+            Line::from(""),
+        ];
+
+        // Keep the selected type visible
+        let visible = (popup_height as usize).saturating_sub(5).max(1);
+        let first = selected.saturating_sub(visible - 1);
+        for (i, (extension, count)) in counts.iter().enumerate().skip(first).take(visible) {
+            let style = if i == selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let check = if shown.contains(extension) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            lines.push(Line::from(Span::styled(
+                truncate_with_ellipsis(&format!("{check} .{extension}  {count}"), max_width),
+                style,
+            )));
+        }
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(" File Types ")
+                    .title_bottom(
+                        Line::from(" Space to toggle • `a` for all • Enter to show • Esc ")
+                            .centered(),
+                    )
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
+            )
+            .style(Style::default().bg(Color::Black))
+            .render(popup_area, buf);
+    }
+
+    fn render_time_travel_popup(area: Rect, buf: &mut Buffer, input: &str) {
+        // Calculate popup size
+        let popup_width = (area.width * 3 / 4).min(70);
+        let popup_height = 10;
+
+        // Center the popup
+        let popup_area = Rect {
+            x: (area.width.saturating_sub(popup_width)) / 2,
+            y: (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the popup area with a background
+        for y in popup_area.y..popup_area.y + popup_area.height {
+            for x in popup_area.x..popup_area.x + popup_area.width {
+                buf[(x, y)].set_style(Style::default().bg(Color::Black));
+            }
+        }
+
+        let time_travel_text = [
+            "Browse the container as it existed at (UTC):".to_string(),
             String::new(),
-            original_line,
+            "e.g. 2024-06-01, 2024-06-01 13:30, or RFC 3339".to_string(),
             String::new(),
-            new_path_display.clone(),
+            input.to_string(),
             String::new(),
-            format!("{enter_hint} • Esc to cancel"),
+            "Enter to apply • empty for live view • Esc to cancel".to_string(),
         ];
 
-        let info_text = clone_text.join("\n");
-
-        // Highlight the input line differently
-        let title_style = if can_confirm {
-            Style::default().fg(Color::Green).bg(Color::Black)
-        } else {
-            Style::default().fg(Color::Yellow).bg(Color::Black)
-        };
-
-        let info_paragraph = Paragraph::new(info_text)
+        let info_paragraph = Paragraph::new(time_travel_text.join("\n"))
             .block(
                 Block::bordered()
                     .border_type(BorderType::Rounded)
-                    .title(" Clone ")
-                    .style(title_style),
+                    .title(" Time Travel ")
+                    .style(Style::default().fg(Color::Magenta).bg(Color::Black)),
             )
-            .style(Style::default().bg(Color::Black))
-            .wrap(Wrap { trim: false });
+            .style(Style::default().bg(Color::Black));
 
         info_paragraph.render(popup_area, buf);
 
-        // Render cursor at end of input (always on the new path line)
-        // Y position: border (1) + title line (1) + blank (1) + original lines + blank (1) = new path line
-        let input_y = popup_area.y + 1 + 1 + original_lines + 1;
-        // X position: border (1) + prefix + visible input length
+        // Render the input field with cursor
+        let input_y = popup_area.y + 5;
         #[allow(clippy::cast_possible_truncation)]
-        let cursor_x = popup_area.x + 1 + new_path_display.len() as u16;
+        let cursor_x = popup_area.x + 1 + input.len() as u16;
 
-        // Show cursor (blinking effect via underscore)
-        if cursor_x < popup_area.x + popup_area.width - 1
-            && input_y < popup_area.y + popup_area.height - 1
-        {
+        if cursor_x < popup_area.x + popup_area.width - 1 {
             buf[(cursor_x, input_y)].set_char('▏');
             buf[(cursor_x, input_y)].set_style(Style::default().fg(Color::White).bg(Color::Black));
         }
     }
 
-    /// Render the clone progress popup.
-    fn render_clone_progress_popup(
-        area: Rect,
-        buf: &mut Buffer,
-        progress: &crate::app::CloneProgress,
-    ) {
+    /// Render the prompt for the name of a new folder.
+    fn render_new_folder_popup(&self, area: Rect, buf: &mut Buffer, input: &str) {
+        let Some(browsing) = self.browsing() else {
+            return;
+        };
+
         // Calculate popup size
         let popup_width = (area.width * 3 / 4).min(70);
-        let popup_height = 10;
+        let popup_height = 9;
 
         // Center the popup
         let popup_area = Rect {
@@ -950,47 +3616,46 @@ impl App {
             }
         }
 
-        let mut progress_lines = vec!["Cloning in progress...".to_string(), String::new()];
-
-        if !progress.current_file.is_empty() {
-            progress_lines.push(format!("Current: {}", progress.current_file));
-        }
-
-        progress_lines.push(format!(
-            "Files: {} / {}",
-            progress.files_completed, progress.total_files
-        ));
-
-        // Add error message if present
-        if let Some(error) = &progress.error_message {
-            progress_lines.push(String::new());
-            progress_lines.push(format!("Error: {error}"));
-        }
+        let max_width = (popup_width as usize).saturating_sub(4);
+        let new_folder_text = [
+            truncate_with_ellipsis(
+                &format!("New folder in /{}", browsing.current_path),
+                max_width,
+            ),
+            format!("(keeps an empty {FOLDER_PLACEHOLDER} blob; use a/b for nested folders)"),
+            String::new(),
+            input.to_string(),
+            String::new(),
+            "Enter to create • Esc to cancel".to_string(),
+        ];
 
-        let info_text = progress_lines.join("\n");
-        let info_paragraph = Paragraph::new(info_text)
+        let info_paragraph = Paragraph::new(new_folder_text.join("\n"))
             .block(
                 Block::bordered()
                     .border_type(BorderType::Rounded)
-                    .title(" Clone Progress ")
-                    .style(Style::default().fg(Color::Yellow).bg(Color::Black)),
+                    .title(" New Folder ")
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
             )
             .style(Style::default().bg(Color::Black));
 
         info_paragraph.render(popup_area, buf);
+
+        // Render the input field with cursor
+        let input_y = popup_area.y + 4;
+        #[allow(clippy::cast_possible_truncation)]
+        let cursor_x = popup_area.x + 1 + input.len() as u16;
+
+        if cursor_x < popup_area.x + popup_area.width - 1 {
+            buf[(cursor_x, input_y)].set_char('▏');
+            buf[(cursor_x, input_y)].set_style(Style::default().fg(Color::White).bg(Color::Black));
+        }
     }
 
-    /// Render the delete confirmation dialog popup.
-    fn render_delete_dialog_popup(
-        area: Rect,
-        buf: &mut Buffer,
-        input: &str,
-        target_name: &str,
-        is_folder: bool,
-    ) {
+    /// Render the prompt for a new container name.
+    fn render_new_container_popup(area: Rect, buf: &mut Buffer, input: &str) {
         // Calculate popup size
         let popup_width = (area.width * 3 / 4).min(70);
-        let popup_height = 12;
+        let popup_height = 9;
 
         // Center the popup
         let popup_area = Rect {
@@ -1007,58 +3672,31 @@ impl App {
             }
         }
 
-        let item_type = if is_folder { "folder" } else { "blob" };
-        let can_confirm = input == target_name;
-
-        let enter_hint = if can_confirm {
-            "Enter to confirm"
-        } else {
-            "Type name to confirm"
-        };
-
-        let warning = if is_folder {
-            "⚠ This will delete all blobs in this folder!"
-        } else {
-            "⚠ This action cannot be undone!"
-        };
-
-        let delete_text = [
-            format!("Delete {item_type}: {target_name}"),
-            String::new(),
-            warning.to_string(),
+        let new_container_text = [
+            "New container".to_string(),
+            "(3-63 lowercase letters, digits and single hyphens)".to_string(),
             String::new(),
-            format!("Type \"{target_name}\" to confirm:"),
             input.to_string(),
             String::new(),
-            format!("{enter_hint} • Esc to cancel"),
+            "Enter to create • Esc to cancel".to_string(),
         ];
 
-        let info_text = delete_text.join("\n");
-
-        let title_style = if can_confirm {
-            Style::default().fg(Color::Red).bg(Color::Black)
-        } else {
-            Style::default().fg(Color::Yellow).bg(Color::Black)
-        };
-
-        let info_paragraph = Paragraph::new(info_text)
+        let info_paragraph = Paragraph::new(new_container_text.join("\n"))
             .block(
                 Block::bordered()
                     .border_type(BorderType::Rounded)
-                    .title(" Delete ")
-                    .style(title_style),
+                    .title(" New Container ")
+                    .style(Style::default().fg(Color::Cyan).bg(Color::Black)),
             )
             .style(Style::default().bg(Color::Black));
 
         info_paragraph.render(popup_area, buf);
 
         // Render the input field with cursor
-        let input_y = popup_area.y + 6;
-        let input_x = popup_area.x + 1; // After left border
+        let input_y = popup_area.y + 4;
         #[allow(clippy::cast_possible_truncation)]
-        let cursor_x = input_x + input.len() as u16;
+        let cursor_x = popup_area.x + 1 + input.len() as u16;
 
-        // Show cursor
         if cursor_x < popup_area.x + popup_area.width - 1 {
             buf[(cursor_x, input_y)].set_char('▏');
             buf[(cursor_x, input_y)].set_style(Style::default().fg(Color::White).bg(Color::Black));
@@ -1067,6 +3705,7 @@ impl App {
 
     /// Render the delete progress popup.
     fn render_delete_progress_popup(
+        &self,
         area: Rect,
         buf: &mut Buffer,
         progress: &crate::app::DeleteProgress,
@@ -1112,7 +3751,7 @@ impl App {
             .block(
                 Block::bordered()
                     .border_type(BorderType::Rounded)
-                    .title(" Delete Progress ")
+                    .title(format!(" {} Delete Progress ", self.spinner()))
                     .style(Style::default().fg(Color::Red).bg(Color::Black)),
             )
             .style(Style::default().bg(Color::Black));
@@ -1123,24 +3762,23 @@ impl App {
     /// Render the preview panel for CSV, TSV, or JSON files.
     fn render_preview_panel(&self, area: Rect, buf: &mut Buffer) {
         // Get the file type name for the title
-        let file_type_name = self.preview_file_type.as_ref().map_or_else(
-            || "Preview".to_string(),
-            super::preview::PreviewFileType::display_name,
-        );
-        let preview_title_name =
-            if self.preview_file_type == Some(super::preview::PreviewFileType::Parquet) {
-                match self.parquet_preview_mode {
-                    Some(ParquetPreviewMode::Table) => "Parquet Table".to_string(),
-                    Some(ParquetPreviewMode::Metadata) => "Parquet Metadata".to_string(),
-                    None => file_type_name.clone(),
-                }
-            } else {
-                file_type_name.clone()
-            };
+        let file_type_name = self
+            .preview_file_type
+            .as_ref()
+            .map_or_else(|| "Preview".to_string(), PreviewFileType::display_name);
+        let preview_title_name = if self.preview_file_type == Some(PreviewFileType::Parquet) {
+            match self.parquet_preview_mode {
+                Some(ParquetPreviewMode::Table) => "Parquet Table".to_string(),
+                Some(ParquetPreviewMode::Metadata) => "Parquet Metadata".to_string(),
+                None => file_type_name.clone(),
+            }
+        } else {
+            file_type_name.clone()
+        };
 
         // Handle loading state
         if self.ui.is_loading_preview {
-            let loading = Paragraph::new(format!("{} Loading preview...", self.icons.loading))
+            let loading = Paragraph::new(format!("{} Loading preview...", self.spinner()))
                 .block(
                     Block::bordered()
                         .title(format!(" {file_type_name} Preview "))
@@ -1169,6 +3807,9 @@ impl App {
             return;
         }
 
+        // Rows on screen inside the border, less a table's header, for paging
+        self.preview_viewport.borrow_mut().rows = usize::from(area.height.saturating_sub(3));
+
         // Render based on preview data type
         match &self.preview_data {
             Some(PreviewData::Table(table)) => {
@@ -1183,6 +3824,9 @@ impl App {
             Some(PreviewData::ParquetSchema(schema)) => {
                 self.render_parquet_schema_preview(area, buf, schema);
             }
+            Some(PreviewData::Hex(hex)) => {
+                self.render_hex_preview(area, buf, hex);
+            }
             None => {
                 let empty = Paragraph::new("No preview data available")
                     .block(
@@ -1198,13 +3842,85 @@ impl App {
         }
     }
 
+    /// The query of a search of the open preview, to pick out what it found.
+    fn preview_query(&self) -> Option<&str> {
+        self.preview_search
+            .as_ref()
+            .filter(|_| self.ui.show_preview)
+            .map(|search| search.query.as_str())
+            .filter(|query| !query.is_empty())
+    }
+
+    /// What a search of the open preview found, for the bottom of its border.
+    fn preview_search_status(&self) -> Line<'static> {
+        match &self.preview_search {
+            Some(search) if self.ui.show_preview => Line::from(format!(
+                " {} ",
+                search.status(self.preview_data.as_ref(), self.preview_selected_row)
+            ))
+            .fg(Color::Yellow),
+            _ => Line::default(),
+        }
+    }
+
+    /// Render the preview pane for whichever blob is selected.
+    fn render_preview_pane(&self, area: Rect, buf: &mut Buffer) {
+        let message = |text: String, color: Color| {
+            Paragraph::new(text)
+                .block(
+                    Block::bordered()
+                        .title(" Preview ")
+                        .title_alignment(Alignment::Center)
+                        .border_type(BorderType::Rounded),
+                )
+                .fg(color)
+                .wrap(Wrap { trim: true })
+                .alignment(Alignment::Center)
+        };
+        match self.preview_pane.content() {
+            PaneContent::Nothing => {
+                message("Select a file to preview it".to_string(), Color::DarkGray)
+                    .render(area, buf);
+            }
+            PaneContent::Loading => {
+                message(
+                    format!("{} Loading preview...", self.spinner()),
+                    Color::Yellow,
+                )
+                .render(area, buf);
+            }
+            PaneContent::Failed(error) => {
+                message(format!("{} {error}", self.icons.error), Color::Red).render(area, buf);
+            }
+            PaneContent::Ready(PreviewData::Table(table)) => {
+                let file_type = self.selected_file_item().map_or_else(
+                    || "Table".to_string(),
+                    |item| PreviewFileType::from_extension(&item.actual_name).display_name(),
+                );
+                self.render_table_preview(area, buf, table, &file_type);
+            }
+            PaneContent::Ready(PreviewData::Json(json)) => {
+                self.render_json_preview(area, buf, json);
+            }
+            PaneContent::Ready(PreviewData::Text(text)) => {
+                self.render_text_preview(area, buf, text);
+            }
+            PaneContent::Ready(PreviewData::ParquetSchema(schema)) => {
+                self.render_parquet_schema_preview(area, buf, schema);
+            }
+            PaneContent::Ready(PreviewData::Hex(hex)) => {
+                self.render_hex_preview(area, buf, hex);
+            }
+        }
+    }
+
     /// Render a table preview (for CSV, TSV, or JSON array of objects).
     #[allow(clippy::cast_possible_truncation, clippy::too_many_lines)]
     fn render_table_preview(
         &self,
         area: Rect,
         buf: &mut Buffer,
-        table_data: &crate::preview::TablePreview,
+        table_data: &blobrs::preview::TablePreview,
         file_type_name: &str,
     ) {
         // Calculate column widths based on content
@@ -1213,16 +3929,22 @@ impl App {
             .len()
             .max(table_data.rows.first().map_or(0, Vec::len));
 
+        // A CSV that turned out to be split by something else says so
+        let delimited = match table_data.delimiter {
+            Some('\t') => ", tab delimited".to_string(),
+            Some(delimiter) => format!(", '{delimiter}' delimited"),
+            None => String::new(),
+        };
         let row_info = if table_data.truncated {
             format!(
-                "{} Preview ({}/{} rows, truncated)",
+                "{} Preview ({}/{} rows{delimited}, truncated)",
                 file_type_name,
                 table_data.rows.len(),
                 table_data.total_rows
             )
         } else {
             format!(
-                "{} Preview ({} rows)",
+                "{} Preview ({} rows{delimited})",
                 file_type_name,
                 table_data.rows.len()
             )
@@ -1274,6 +3996,12 @@ impl App {
         let col_offset = self.preview_scroll.1;
         let inner_width = area.width.saturating_sub(2) as usize;
         let viewport = compute_table_column_viewport(&col_widths, col_offset, inner_width);
+        if let (Some(first), Some(last)) = (
+            viewport.visible_indices.first(),
+            viewport.visible_indices.last(),
+        ) {
+            self.preview_viewport.borrow_mut().columns = *first..last + 1;
+        }
 
         // Build constraints for visible columns
         let constraints: Vec<Constraint> = viewport
@@ -1309,12 +4037,23 @@ impl App {
             row_info
         );
 
+        // Columns of numbers are right-aligned, headers and all, so their digits line up
+        let numeric = table_data.numeric_columns();
+        let alignment = |idx: usize| {
+            if numeric.get(idx).copied().unwrap_or(false) {
+                Alignment::Right
+            } else {
+                Alignment::Left
+            }
+        };
+
         // Build header row
         let header_cells: Vec<Cell> = viewport
             .visible_indices
             .iter()
             .map(|idx| {
-                Cell::from(table_data.headers.get(*idx).cloned().unwrap_or_default()).style(
+                let header = table_data.headers.get(*idx).cloned().unwrap_or_default();
+                Cell::from(Line::from(header).alignment(alignment(*idx))).style(
                     Style::default()
                         .fg(Color::Cyan)
                         .add_modifier(Modifier::BOLD),
@@ -1339,6 +4078,7 @@ impl App {
         });
 
         // Build data rows with scroll offset
+        let query = self.preview_query();
         let data_rows: Vec<Row> = table_data
             .rows
             .iter()
@@ -1347,7 +4087,12 @@ impl App {
                 let cells: Vec<Cell> = viewport
                     .visible_indices
                     .iter()
-                    .map(|idx| Cell::from(row.get(*idx).cloned().unwrap_or_default()))
+                    .map(|idx| {
+                        let cell = row.get(*idx).map_or("", String::as_str);
+                        Cell::from(
+                            Line::from(highlight_found(cell, query)).alignment(alignment(*idx)),
+                        )
+                    })
                     .collect();
 
                 let style = if row_idx == self.preview_selected_row {
@@ -1372,6 +4117,7 @@ impl App {
             .block(
                 Block::bordered()
                     .title(title)
+                    .title_bottom(self.preview_search_status())
                     .title_alignment(Alignment::Center)
                     .border_type(BorderType::Rounded),
             )
@@ -1391,11 +4137,18 @@ impl App {
         &self,
         area: Rect,
         buf: &mut Buffer,
-        json_data: &crate::preview::JsonPreview,
+        json_data: &blobrs::preview::JsonPreview,
     ) {
         // Build title based on mode and truncation state
         let title = if json_data.is_raw {
             " JSON Preview (raw, truncated at 50KB) ".to_string()
+        } else if let Some(records) = json_data.records {
+            let truncated = if json_data.truncated {
+                ", truncated"
+            } else {
+                ""
+            };
+            format!(" JSONL Preview ({records} records{truncated}) ")
         } else if json_data.truncated {
             format!(
                 " JSON Preview ({}/{} lines, truncated) ",
@@ -1406,37 +4159,27 @@ impl App {
             format!(" JSON Preview ({} lines) ", json_data.total_lines)
         };
 
-        // Apply vertical scroll offset
-        let mut visible_lines: Vec<&str> = json_data
+        // Apply vertical scroll offset, picking out what a search found
+        let query = self.preview_query();
+        let mut visible_lines: Vec<Line> = json_data
             .content
             .lines()
             .skip(self.preview_selected_row)
+            .map(|line| Line::from(highlight_found(line, query)))
             .collect();
 
-        // Add truncation indicator at the bottom if truncated
-        let truncation_indicator = if json_data.truncated {
-            Some("... [truncated at 50KB]")
-        } else {
-            None
-        };
-
-        // Build the visible content
-        let visible_content = if let Some(indicator) = truncation_indicator {
-            // Only show indicator if we're near the bottom
-            let content_height = area.height.saturating_sub(2) as usize; // Account for borders
-            if visible_lines.len() <= content_height {
-                visible_lines.push("");
-                visible_lines.push(indicator);
-            }
-            visible_lines.join("\n")
-        } else {
-            visible_lines.join("\n")
-        };
+        // Add truncation indicator at the bottom if truncated, only if we're near the bottom
+        let content_height = area.height.saturating_sub(2) as usize; // Account for borders
+        if json_data.truncated && visible_lines.len() <= content_height {
+            visible_lines.push(Line::default());
+            visible_lines.push(Line::from("... [truncated at 50KB]"));
+        }
 
-        let json_widget = Paragraph::new(visible_content)
+        let json_widget = Paragraph::new(visible_lines)
             .block(
                 Block::bordered()
                     .title(title)
+                    .title_bottom(self.preview_search_status())
                     .title_alignment(Alignment::Center)
                     .border_type(BorderType::Rounded),
             )
@@ -1451,10 +4194,18 @@ impl App {
         &self,
         area: Rect,
         buf: &mut Buffer,
-        text_data: &crate::preview::TextPreview,
+        text_data: &blobrs::preview::TextPreview,
     ) {
         // Build title with extension and line count
-        let title = if text_data.truncated {
+        let title = if let Some(pager) = &self.preview_pager {
+            format!(
+                " {} Preview ({} lines, loaded {} of {}) ",
+                text_data.extension,
+                text_data.total_lines,
+                format_bytes(pager.loaded),
+                format_bytes(pager.size)
+            )
+        } else if text_data.truncated {
             format!(
                 " {} Preview ({} lines, truncated at 50KB) ",
                 text_data.extension, text_data.total_lines
@@ -1466,37 +4217,42 @@ impl App {
             )
         };
 
-        // Apply vertical scroll offset
-        let mut visible_lines: Vec<&str> = text_data
+        // Apply vertical scroll offset, picking out what a search found over the syntax
+        let query = self.preview_query();
+        let found = |line: &str| query.is_some_and(|query| !match_ranges(line, query).is_empty());
+        let mut visible_lines: Vec<Line> = text_data
             .content
             .lines()
+            .enumerate()
             .skip(self.preview_selected_row)
+            .map(|(index, line)| {
+                match text_data
+                    .highlighted
+                    .as_ref()
+                    .and_then(|lines| lines.get(index))
+                {
+                    Some(highlighted) if !found(line) => highlighted.clone(),
+                    _ => Line::from(highlight_found(line, query)),
+                }
+            })
             .collect();
 
-        // Add truncation indicator at the bottom if truncated
-        let truncation_indicator = if text_data.truncated {
-            Some("... [truncated at 50KB]")
-        } else {
-            None
-        };
-
-        // Build the visible content
-        let visible_content = if let Some(indicator) = truncation_indicator {
-            // Only show indicator if we're near the bottom
-            let content_height = area.height.saturating_sub(2) as usize; // Account for borders
-            if visible_lines.len() <= content_height {
-                visible_lines.push("");
-                visible_lines.push(indicator);
-            }
-            visible_lines.join("\n")
-        } else {
-            visible_lines.join("\n")
-        };
+        // Add truncation indicator at the bottom if truncated, only if we're near the bottom
+        let content_height = area.height.saturating_sub(2) as usize; // Account for borders
+        if text_data.truncated && visible_lines.len() <= content_height {
+            visible_lines.push(Line::default());
+            visible_lines.push(Line::from(if self.preview_pager.is_some() {
+                "... loading more"
+            } else {
+                "... [truncated at 50KB]"
+            }));
+        }
 
-        let text_widget = Paragraph::new(visible_content)
+        let text_widget = Paragraph::new(visible_lines)
             .block(
                 Block::bordered()
                     .title(title)
+                    .title_bottom(self.preview_search_status())
                     .title_alignment(Alignment::Center)
                     .border_type(BorderType::Rounded),
             )
@@ -1506,13 +4262,51 @@ impl App {
         text_widget.render(area, buf);
     }
 
+    /// Render a hex dump of a binary file, with the offsets dimmed and the ASCII in cyan.
+    fn render_hex_preview(&self, area: Rect, buf: &mut Buffer, hex: &blobrs::preview::HexPreview) {
+        let title = if hex.truncated {
+            format!(" Hex Preview (first {} bytes) ", hex.len)
+        } else {
+            format!(" Hex Preview ({} bytes) ", hex.len)
+        };
+
+        let query = self.preview_query();
+        let lines: Vec<Line> = hex
+            .content
+            .lines()
+            .skip(self.preview_selected_row)
+            .map(|line| {
+                let found = query.is_some_and(|query| !match_ranges(line, query).is_empty());
+                match (line.split_at_checked(8), line.find(" |")) {
+                    (Some((offset, _)), Some(gutter)) if !found => Line::from(vec![
+                        Span::styled(offset.to_string(), Style::default().fg(Color::DarkGray)),
+                        Span::raw(line[8..gutter].to_string()),
+                        Span::styled(line[gutter..].to_string(), Style::default().fg(Color::Cyan)),
+                    ]),
+                    _ => Line::from(highlight_found(line, query)),
+                }
+            })
+            .collect();
+
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(self.preview_search_status())
+                    .title_alignment(Alignment::Center)
+                    .border_type(BorderType::Rounded),
+            )
+            .fg(Color::White)
+            .render(area, buf);
+    }
+
     /// Render a Parquet schema preview.
     #[allow(clippy::vec_init_then_push)]
     fn render_parquet_schema_preview(
         &self,
         area: Rect,
         buf: &mut Buffer,
-        schema_data: &crate::preview::ParquetSchemaPreview,
+        schema_data: &blobrs::preview::ParquetSchemaPreview,
     ) {
         let title = format!(" Parquet Metadata ({} columns) ", schema_data.fields.len());
 
@@ -1707,6 +4501,37 @@ fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// One line per menu entry: its key hint, then its label, with the highlighted entry inverted.
+fn menu_lines<T>(menu: &Menu<T>, max_width: usize, highlight: Color) -> Vec<Line<'static>> {
+    menu.items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let (key_style, label_style) = if i == menu.selected {
+                let style = Style::default().fg(Color::Black).bg(highlight);
+                (style.add_modifier(Modifier::BOLD), style)
+            } else {
+                (
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                    Style::default().fg(Color::White),
+                )
+            };
+            Line::from(vec![
+                Span::styled(format!(" {} ", item.key), key_style),
+                Span::styled(
+                    format!(
+                        " {} ",
+                        truncate_with_ellipsis(&item.label, max_width.saturating_sub(5))
+                    ),
+                    label_style,
+                ),
+            ])
+        })
+        .collect()
+}
+
 fn truncate_with_ellipsis(input: &str, max_chars: usize) -> String {
     if input.chars().count() <= max_chars {
         return input.to_string();
@@ -1718,6 +4543,34 @@ fn truncate_with_ellipsis(input: &str, max_chars: usize) -> String {
     format!("{prefix}...")
 }
 
+/// How a line of a unified diff is coloured.
+fn diff_line_style(kind: DiffKind) -> Style {
+    match kind {
+        DiffKind::Header => Style::default().add_modifier(Modifier::BOLD),
+        DiffKind::Hunk => Style::default().fg(Color::Cyan),
+        DiffKind::Context => Style::default(),
+        DiffKind::Removed => Style::default().fg(Color::Red),
+        DiffKind::Added => Style::default().fg(Color::Green),
+    }
+}
+
+/// What an edit changed, e.g. `+2 −1 lines`.
+fn edit_summary(diff: &[DiffLine]) -> String {
+    let count = |kind| diff.iter().filter(|line| line.kind == kind).count();
+    match (count(DiffKind::Removed), count(DiffKind::Added)) {
+        // Only line endings or a final newline changed
+        (0, 0) => "Whitespace at the end changed".to_string(),
+        (removed, added) => format!("+{added} −{removed} lines"),
+    }
+}
+
+/// Hex form of a base64 `Content-MD5`, as printed by `md5sum`.
+fn md5_hex(base64_md5: &str) -> Option<String> {
+    use base64::{Engine as _, engine::general_purpose};
+    let digest = general_purpose::STANDARD.decode(base64_md5).ok()?;
+    Some(digest.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
 /// Format a number with thousand separators
 fn format_number(n: u64) -> String {
     let s = n.to_string();
@@ -1731,10 +4584,146 @@ fn format_number(n: u64) -> String {
     result.chars().rev().collect()
 }
 
+/// The frame of `frames` to show after `ticks` ticks.
+fn spinner_frame(frames: &[&'static str], ticks: usize) -> &'static str {
+    frames
+        .get((ticks / SPINNER_TICKS) % frames.len().max(1))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Rows of a list `len` long that fit in `height` rows with `selected` visible: the rows a
+/// fresh [`ListState`] would scroll to, with the selection on the last row once it is
+/// further down than the first screen.
+fn visible_rows(selected: usize, len: usize, height: usize) -> std::ops::Range<usize> {
+    let start = selected.saturating_sub(height.saturating_sub(1)).min(len);
+    start..(start + height).min(len)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{App, compute_table_column_viewport, truncate_with_ellipsis};
+    use super::{
+        App, SPINNER_TICKS, compute_table_column_viewport, edit_summary, highlight_found,
+        highlight_matches, md5_hex, render_list_scrollbar, spinner_frame, status_bar_height,
+        truncate_with_ellipsis, visible_rows,
+    };
+    use crate::mouse::ListLayout;
+    use crate::terminal_icons::IconSet;
+    use blobrs::diff::unified_diff;
+    use blobrs::filter::{Filter, FilterMode};
     use proptest::prelude::*;
+    use ratatui::{buffer::Buffer, layout::Rect};
+
+    #[test]
+    fn only_the_rows_around_the_selection_are_drawn() {
+        assert_eq!(visible_rows(0, 200_000, 20), 0..20);
+        assert_eq!(visible_rows(19, 200_000, 20), 0..20);
+        assert_eq!(visible_rows(20, 200_000, 20), 1..21);
+        assert_eq!(visible_rows(199_999, 200_000, 20), 199_980..200_000);
+        assert_eq!(visible_rows(2, 5, 20), 0..5);
+        assert_eq!(visible_rows(0, 0, 20), 0..0);
+        assert_eq!(visible_rows(3, 5, 0), 3..3);
+    }
+
+    #[test]
+    fn an_edit_is_summarised_by_its_changed_lines() {
+        let diff = unified_diff(
+            "a = 1\nb = 2\nc = 3\nd = 4\n",
+            "a = 1\nb = 20\nb2 = 21\nd = 4\n",
+            "app.toml",
+            "app.toml",
+        );
+        assert_eq!(edit_summary(&diff), "+2 −2 lines");
+        let newline_only = unified_diff("one\ntwo", "one\ntwo\n", "a", "a");
+        assert_eq!(edit_summary(&newline_only), "Whitespace at the end changed");
+    }
+
+    #[test]
+    fn matched_letters_are_highlighted_in_runs() {
+        let filter = Filter::new(FilterMode::Fuzzy, "sal24").unwrap();
+        let spans = highlight_matches("sales_2024.csv", Some(&filter));
+        let text: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style.fg.is_some()))
+            .collect();
+        assert_eq!(
+            text,
+            [
+                ("sal", true),
+                ("es_20", false),
+                ("24", true),
+                (".csv", false)
+            ]
+        );
+        assert_eq!(highlight_matches("sales.csv", None).len(), 1);
+        let filter = Filter::new(FilterMode::Fuzzy, "xyz").unwrap();
+        assert_eq!(highlight_matches("sales.csv", Some(&filter)).len(), 1);
+    }
+
+    #[test]
+    fn what_a_preview_search_found_is_picked_out() {
+        let spans = highlight_found("Error: an error", Some("error"));
+        let text: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style.bg.is_some()))
+            .collect();
+        assert_eq!(text, [("Error", true), (": an ", false), ("error", true)]);
+        assert_eq!(highlight_found("", Some("error")).len(), 1);
+        assert_eq!(highlight_found("ok", None).len(), 1);
+    }
+
+    #[test]
+    fn the_scrollbar_thumb_follows_the_visible_rows() {
+        // A list of 10 rows between borders, drawn in 12 lines
+        let thumb = |first, len| {
+            let mut buf = Buffer::empty(Rect::new(0, 0, 20, 12));
+            let layout = ListLayout {
+                rows: Rect::new(1, 1, 18, 10),
+                first,
+                len,
+                crumbs: Vec::new(),
+            };
+            render_list_scrollbar(buf.area, &mut buf, &layout);
+            (0..12)
+                .filter(|&y| buf[(19, y)].symbol() == "█")
+                .collect::<Vec<u16>>()
+        };
+        // Everything fits, so there is no scrollbar
+        assert!(thumb(0, 10).is_empty());
+        assert_eq!(thumb(0, 20), (1..6).collect::<Vec<_>>());
+        assert_eq!(thumb(10, 20), (6..11).collect::<Vec<_>>());
+        // A long listing still gets a thumb, at the bottom at the end
+        assert_eq!(thumb(9_990, 10_000), [10]);
+    }
+
+    #[test]
+    fn long_messages_get_lines_of_their_own_in_the_status_bar() {
+        assert_eq!(status_bar_height(0, 30, 80), 1);
+        assert_eq!(status_bar_height(40, 30, 80), 1);
+        assert_eq!(status_bar_height(60, 30, 80), 2);
+        assert_eq!(status_bar_height(200, 30, 80), 4);
+        assert_eq!(status_bar_height(100_000, 30, 80), 7);
+        assert_eq!(status_bar_height(10, 10, 0), 7);
+    }
+
+    #[test]
+    fn spinner_turns_with_ticks_and_wraps_around() {
+        let frames = IconSet::UNICODE.spinner;
+        assert_eq!(spinner_frame(frames, 0), "⠋");
+        assert_eq!(spinner_frame(frames, SPINNER_TICKS - 1), "⠋");
+        assert_eq!(spinner_frame(frames, SPINNER_TICKS), "⠙");
+        assert_eq!(spinner_frame(frames, SPINNER_TICKS * frames.len()), "⠋");
+        assert_eq!(spinner_frame(&[], 7), "");
+    }
+
+    #[test]
+    fn content_md5_is_shown_as_hex() {
+        assert_eq!(
+            md5_hex("1B2M2Y8AsgTpgAmY7PhCfg==").as_deref(),
+            Some("d41d8cd98f00b204e9800998ecf8427e")
+        );
+        assert_eq!(md5_hex("not base64!"), None);
+    }
 
     #[test]
     fn viewport_marks_left_and_right_overflow() {
@@ -0,0 +1,497 @@
+//! Blob counts and total sizes of whole containers and folders, computed in the background,
+//! with a breakdown by the folders and blobs directly inside for the `B` view.
+//!
+//! Results are kept for the rest of the session, so hiding and showing the sizes again,
+//! reloading the container list or reopening a folder's info does not list every blob a
+//! second time.
+//!
+//! Folder and container info also count bytes by access tier, through the Blob service's own
+//! listing as `object_store` does not report tiers, to estimate what storing them costs.
+
+use crate::azure::{AzureRestClient, BlobListing};
+use crate::config::CostConfig;
+use futures::StreamExt;
+use object_store::ObjectStore;
+use object_store::path::Path as ObjectPath;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Semaphore;
+
+/// How many containers are listed at the same time.
+const SCAN_CONCURRENCY: usize = 4;
+
+/// What is known about a container's or folder's contents so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContainerUsage {
+    /// Blobs counted so far.
+    pub blobs: u64,
+    /// Bytes counted so far.
+    pub bytes: u64,
+    /// Every blob has been counted.
+    pub complete: bool,
+    /// Why the listing stopped early, if it did.
+    pub error: Option<String>,
+    /// Counts of the entries directly inside, by name; folders end in `/`.
+    pub children: BTreeMap<String, ChildUsage>,
+    /// Bytes by access tier, for scans that list tiers.
+    pub tiers: BTreeMap<String, u64>,
+}
+
+/// What is counted so far under one entry of a container or folder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChildUsage {
+    pub blobs: u64,
+    pub bytes: u64,
+}
+
+impl ContainerUsage {
+    /// The entries directly inside, largest first.
+    #[must_use]
+    pub fn breakdown(&self) -> Vec<(&str, ChildUsage)> {
+        let mut entries: Vec<(&str, ChildUsage)> = self
+            .children
+            .iter()
+            .map(|(name, usage)| (name.as_str(), *usage))
+            .collect();
+        entries.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(b.0)));
+        entries
+    }
+
+    /// Estimated monthly cost of storing the bytes counted by tier so far.
+    #[must_use]
+    pub fn monthly_cost(&self, costs: &CostConfig) -> f64 {
+        self.tiers
+            .iter()
+            .map(|(tier, bytes)| costs.monthly(tier, *bytes))
+            .sum()
+    }
+
+    /// Count a page of a listing with tiers. Blobs the service gives no tier, such as page
+    /// blobs, count as hot.
+    fn add_tiered_page(&mut self, page: &BlobListing) {
+        for blob in &page.blobs {
+            self.blobs += 1;
+            self.bytes += blob.size;
+            let tier = blob.access_tier.as_deref().unwrap_or("Hot");
+            *self.tiers.entry(tier.to_string()).or_default() += blob.size;
+        }
+    }
+}
+
+type Scans = Arc<Mutex<BTreeMap<String, Arc<Mutex<ContainerUsage>>>>>;
+
+/// Scans, keyed by container name or `folder_key`.
+#[derive(Debug, Clone)]
+pub struct UsageScanner {
+    usage: Scans,
+    /// Scans by tier, keyed by [`folder_key`] with an empty folder for a whole container.
+    tiered: Scans,
+    permits: Arc<Semaphore>,
+}
+
+impl Default for UsageScanner {
+    fn default() -> Self {
+        Self {
+            usage: Arc::default(),
+            tiered: Arc::default(),
+            permits: Arc::new(Semaphore::new(SCAN_CONCURRENCY)),
+        }
+    }
+}
+
+fn lock(scans: &Scans) -> MutexGuard<'_, BTreeMap<String, Arc<Mutex<ContainerUsage>>>> {
+    scans
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+impl UsageScanner {
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, Arc<Mutex<ContainerUsage>>>> {
+        lock(&self.usage)
+    }
+
+    /// Start counting `container` unless it was counted (or is being counted) already.
+    pub fn scan(&self, container: &str, store: Arc<dyn ObjectStore>) {
+        if !self.lock().contains_key(container) {
+            self.rescan(container, store);
+        }
+    }
+
+    /// Count `container` from scratch. A scan still running for it is left to finish
+    /// unseen.
+    pub fn rescan(&self, container: &str, store: Arc<dyn ObjectStore>) {
+        self.start(container.to_string(), store, None);
+    }
+
+    /// Start counting `folder` (ending in `/`) of `container` unless it was counted already.
+    pub fn scan_folder(&self, container: &str, folder: &str, store: Arc<dyn ObjectStore>) {
+        let key = folder_key(container, folder);
+        if !self.lock().contains_key(&key) {
+            self.start(key, store, Some(ObjectPath::from(folder)));
+        }
+    }
+
+    /// Count `folder` of `container` from scratch.
+    pub fn rescan_folder(&self, container: &str, folder: &str, store: Arc<dyn ObjectStore>) {
+        self.start(
+            folder_key(container, folder),
+            store,
+            Some(ObjectPath::from(folder)),
+        );
+    }
+
+    /// Start counting `folder` of `container`, or the whole container when it is empty,
+    /// unless it was counted already.
+    pub fn scan_prefix(&self, container: &str, folder: &str, store: Arc<dyn ObjectStore>) {
+        if folder.is_empty() {
+            self.scan(container, store);
+        } else {
+            self.scan_folder(container, folder, store);
+        }
+    }
+
+    /// Count `folder` of `container`, or the whole container when it is empty, from scratch.
+    pub fn rescan_prefix(&self, container: &str, folder: &str, store: Arc<dyn ObjectStore>) {
+        if folder.is_empty() {
+            self.rescan(container, store);
+        } else {
+            self.rescan_folder(container, folder, store);
+        }
+    }
+
+    fn start(&self, key: String, store: Arc<dyn ObjectStore>, prefix: Option<ObjectPath>) {
+        let usage = Arc::new(Mutex::new(ContainerUsage::default()));
+        self.lock().insert(key, usage.clone());
+        let permits = self.permits.clone();
+        tokio::spawn(async move {
+            let Ok(_permit) = permits.acquire().await else {
+                return;
+            };
+            count(store.as_ref(), prefix.as_ref(), &usage).await;
+        });
+    }
+
+    /// Start counting `folder` of `container` (the whole container when it is empty) by
+    /// tier, unless it was counted already.
+    pub fn scan_tiers(&self, container: &str, folder: &str, client: AzureRestClient) {
+        if !lock(&self.tiered).contains_key(&folder_key(container, folder)) {
+            self.rescan_tiers(container, folder, client);
+        }
+    }
+
+    /// Count `folder` of `container` by tier from scratch.
+    pub fn rescan_tiers(&self, container: &str, folder: &str, client: AzureRestClient) {
+        let usage = Arc::new(Mutex::new(ContainerUsage::default()));
+        lock(&self.tiered).insert(folder_key(container, folder), usage.clone());
+        let permits = self.permits.clone();
+        let (container, folder) = (container.to_string(), folder.to_string());
+        tokio::spawn(async move {
+            let Ok(_permit) = permits.acquire().await else {
+                return;
+            };
+            count_tiers(&client, &container, &folder, &usage).await;
+        });
+    }
+
+    /// Usage by tier of `folder` of `container`, if a scan was started.
+    #[must_use]
+    pub fn get_tiers(&self, container: &str, folder: &str) -> Option<ContainerUsage> {
+        let usage = lock(&self.tiered)
+            .get(&folder_key(container, folder))?
+            .clone();
+        let usage = usage
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        Some(usage)
+    }
+
+    /// Usage of `container`, if a scan was started.
+    #[must_use]
+    pub fn get(&self, container: &str) -> Option<ContainerUsage> {
+        self.get_key(container)
+    }
+
+    /// Usage of `folder` of `container`, if a scan was started.
+    #[must_use]
+    pub fn get_folder(&self, container: &str, folder: &str) -> Option<ContainerUsage> {
+        self.get_key(&folder_key(container, folder))
+    }
+
+    /// Usage of `folder` of `container`, or of the whole container when it is empty.
+    #[must_use]
+    pub fn get_prefix(&self, container: &str, folder: &str) -> Option<ContainerUsage> {
+        if folder.is_empty() {
+            self.get(container)
+        } else {
+            self.get_folder(container, folder)
+        }
+    }
+
+    fn get_key(&self, key: &str) -> Option<ContainerUsage> {
+        let usage = self.lock().get(key)?.clone();
+        let usage = usage
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        Some(usage)
+    }
+}
+
+/// Key of a folder's scan. Container names cannot contain `/`, so it never matches a
+/// container's.
+fn folder_key(container: &str, folder: &str) -> String {
+    format!("{container}/{folder}")
+}
+
+/// Name of the entry directly under `prefix` that holds the blob at `location`: the blob
+/// itself, or a folder ending in `/`.
+fn child_name(prefix: Option<&ObjectPath>, location: &str) -> String {
+    let rest = match prefix.map(AsRef::<str>::as_ref) {
+        Some(prefix) if !prefix.is_empty() => location
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(location),
+        _ => location,
+    };
+    match rest.split_once('/') {
+        Some((folder, _)) => format!("{folder}/"),
+        None => rest.to_string(),
+    }
+}
+
+/// List every blob of the store under `prefix`, adding each to `usage` as it arrives.
+async fn count(
+    store: &dyn ObjectStore,
+    prefix: Option<&ObjectPath>,
+    usage: &Mutex<ContainerUsage>,
+) {
+    let mut listing = store.list(prefix);
+    let error = loop {
+        match listing.next().await {
+            Some(Ok(meta)) => {
+                let child = child_name(prefix, meta.location.as_ref());
+                let mut usage = usage
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                usage.blobs += 1;
+                usage.bytes += meta.size;
+                let child = usage.children.entry(child).or_default();
+                child.blobs += 1;
+                child.bytes += meta.size;
+            }
+            Some(Err(e)) => break Some(e.to_string()),
+            None => break None,
+        }
+    };
+    let mut usage = usage
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    usage.complete = error.is_none();
+    usage.error = error;
+}
+
+/// List every blob under `folder` of `container` with its tier, a page at a time, adding each
+/// page to `usage` as it arrives.
+async fn count_tiers(
+    client: &AzureRestClient,
+    container: &str,
+    folder: &str,
+    usage: &Mutex<ContainerUsage>,
+) {
+    let mut marker: Option<String> = None;
+    let error = loop {
+        match client
+            .list_blobs_page(container, folder, None, marker.as_deref())
+            .await
+        {
+            Ok((page, next)) => {
+                usage
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .add_tiered_page(&page);
+                match next {
+                    Some(next) => marker = Some(next),
+                    None => break None,
+                }
+            }
+            Err(e) => break Some(e.to_string()),
+        }
+    };
+    let mut usage = usage
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    usage.complete = error.is_none();
+    usage.error = error;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+    use object_store::{ObjectStoreExt, path::Path as ObjectPath};
+
+    #[tokio::test]
+    async fn counts_every_blob_in_every_folder() {
+        let store = InMemory::new();
+        for (name, size) in [("a.csv", 3), ("logs/1.log", 10), ("logs/2024/2.log", 7)] {
+            store
+                .put(&ObjectPath::from(name), vec![0u8; size].into())
+                .await
+                .unwrap();
+        }
+
+        let usage = Mutex::new(ContainerUsage::default());
+        count(&store, None, &usage).await;
+        assert_eq!(
+            usage.into_inner().unwrap(),
+            ContainerUsage {
+                blobs: 3,
+                bytes: 20,
+                complete: true,
+                error: None,
+                children: BTreeMap::from([
+                    ("a.csv".to_string(), ChildUsage { blobs: 1, bytes: 3 }),
+                    (
+                        "logs/".to_string(),
+                        ChildUsage {
+                            blobs: 2,
+                            bytes: 17
+                        }
+                    ),
+                ]),
+                tiers: BTreeMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn tiers_are_priced_by_the_bytes_in_each() {
+        use crate::azure::ListedBlob;
+
+        const GIB: u64 = 1024 * 1024 * 1024;
+        let blob = |size: u64, tier: Option<&str>| ListedBlob {
+            name: String::new(),
+            size,
+            last_modified: None,
+            etag: None,
+            access_tier: tier.map(ToString::to_string),
+        };
+        let mut usage = ContainerUsage::default();
+        usage.add_tiered_page(&BlobListing {
+            blobs: vec![
+                blob(10 * GIB, Some("Hot")),
+                blob(90 * GIB, Some("Archive")),
+                // Page blobs have no tier
+                blob(GIB, None),
+            ],
+            prefixes: Vec::new(),
+        });
+        assert_eq!((usage.blobs, usage.bytes), (3, 101 * GIB));
+        assert_eq!(usage.tiers["Hot"], 11 * GIB);
+
+        let costs = CostConfig {
+            hot: 1.0,
+            archive: 0.1,
+            ..CostConfig::default()
+        };
+        assert!((usage.monthly_cost(&costs) - 20.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn folders_break_down_by_what_is_directly_inside() {
+        let store = InMemory::new();
+        for (name, size) in [
+            ("logs/1.log", 10),
+            ("logs/2024/2.log", 7),
+            ("logs/2024/06/3.log", 20),
+            ("logs/a.log", 10),
+        ] {
+            store
+                .put(&ObjectPath::from(name), vec![0u8; size].into())
+                .await
+                .unwrap();
+        }
+
+        let usage = Mutex::new(ContainerUsage::default());
+        count(&store, Some(&ObjectPath::from("logs/")), &usage).await;
+        let usage = usage.into_inner().unwrap();
+        assert_eq!(
+            usage.breakdown(),
+            [
+                (
+                    "2024/",
+                    ChildUsage {
+                        blobs: 2,
+                        bytes: 27
+                    }
+                ),
+                (
+                    "1.log",
+                    ChildUsage {
+                        blobs: 1,
+                        bytes: 10
+                    }
+                ),
+                (
+                    "a.log",
+                    ChildUsage {
+                        blobs: 1,
+                        bytes: 10
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn scans_run_once_until_rescanned() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        store
+            .put(&ObjectPath::from("a.csv"), vec![0u8; 4].into())
+            .await
+            .unwrap();
+        let scanner = UsageScanner::default();
+        assert_eq!(scanner.get("data"), None);
+
+        scanner.scan("data", store.clone());
+        while !scanner.get("data").unwrap().complete {
+            tokio::task::yield_now().await;
+        }
+        store
+            .put(&ObjectPath::from("b.csv"), vec![0u8; 4].into())
+            .await
+            .unwrap();
+
+        // Already counted, so the new blob is not seen
+        scanner.scan("data", store.clone());
+        assert_eq!(scanner.get("data").unwrap().blobs, 1);
+
+        scanner.rescan("data", store);
+        while !scanner.get("data").unwrap().complete {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(scanner.get("data").unwrap().bytes, 8);
+    }
+
+    #[tokio::test]
+    async fn folders_are_counted_separately_from_their_container() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        for (name, size) in [("a.csv", 3), ("logs/1.log", 10), ("logs/2024/2.log", 7)] {
+            store
+                .put(&ObjectPath::from(name), vec![0u8; size].into())
+                .await
+                .unwrap();
+        }
+        let scanner = UsageScanner::default();
+
+        scanner.scan_folder("data", "logs/", store.clone());
+        while !scanner.get_folder("data", "logs/").unwrap().complete {
+            tokio::task::yield_now().await;
+        }
+        let usage = scanner.get_folder("data", "logs/").unwrap();
+        assert_eq!((usage.blobs, usage.bytes), (2, 17));
+        assert_eq!(scanner.get("data"), None);
+        assert_eq!(scanner.get_folder("data", "logs/2024/"), None);
+    }
+}
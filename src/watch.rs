@@ -0,0 +1,296 @@
+//! Watching the open folder: it is listed again every few seconds in the background and
+//! compared with the entries on screen, so blobs a pipeline drops into a landing zone show up
+//! on their own, highlighted for a while as new or changed.
+//!
+//! Only what changed is taken into the list; the selection, sort order and search stay put.
+
+use crate::error::BlobrsError;
+use crate::listing::{EntryKind, FileItem};
+use futures::FutureExt;
+use futures::TryStreamExt;
+use object_store::{ListResult, ObjectStore, path::Path as ObjectPath};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// How long a new or changed entry stays highlighted.
+pub const HIGHLIGHT_FOR: Duration = Duration::from_secs(30);
+
+/// How an entry differs from when the folder was last listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    New,
+    Changed,
+}
+
+/// What a new listing changed, for the status bar.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchDiff {
+    /// Names of the entries that appeared or changed.
+    pub changes: Vec<(String, Change)>,
+    /// Entries no longer listed.
+    pub gone: usize,
+}
+
+impl WatchDiff {
+    /// Compare the live entries `before` with the listing `after`. Entries match by kind and
+    /// name, and a blob has changed when its size or last-modified time has.
+    #[must_use]
+    pub fn new(before: &[FileItem], after: &[FileItem]) -> Self {
+        let key = |item: &FileItem| (item.kind == EntryKind::Folder, item.actual_name.clone());
+        let known: HashMap<_, _> = before
+            .iter()
+            .filter(|item| !item.deleted)
+            .map(|item| (key(item), (item.size, item.last_modified)))
+            .collect();
+        let changes: Vec<(String, Change)> = after
+            .iter()
+            .filter_map(|item| match known.get(&key(item)) {
+                None => Some((item.actual_name.clone(), Change::New)),
+                Some(&(size, last_modified))
+                    if item.kind == EntryKind::File
+                        && (size, last_modified) != (item.size, item.last_modified) =>
+                {
+                    Some((item.actual_name.clone(), Change::Changed))
+                }
+                Some(_) => None,
+            })
+            .collect();
+        let new = changes
+            .iter()
+            .filter(|(_, change)| *change == Change::New)
+            .count();
+        Self {
+            gone: (known.len() + new).saturating_sub(after.len()),
+            changes,
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.gone == 0
+    }
+
+    /// What changed, e.g. `2 new, 1 changed`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let count = |change| {
+            self.changes
+                .iter()
+                .filter(|(_, found)| *found == change)
+                .count()
+        };
+        [
+            (count(Change::New), "new"),
+            (count(Change::Changed), "changed"),
+            (self.gone, "gone"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, what)| format!("{count} {what}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// The open folder being watched.
+#[derive(Debug)]
+pub struct FolderWatch {
+    pub interval: Duration,
+    next: Instant,
+    /// The folder being listed, and the listing.
+    listing: Option<(String, JoinHandle<Result<ListResult, BlobrsError>>)>,
+    /// The folder the highlights are in.
+    folder: String,
+    highlights: HashMap<String, (Change, Instant)>,
+}
+
+impl FolderWatch {
+    /// Watch whichever folder is open, listing it first after `interval`.
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Instant::now() + interval,
+            listing: None,
+            folder: String::new(),
+            highlights: HashMap::new(),
+        }
+    }
+
+    /// Whether the folder is due to be listed again.
+    #[must_use]
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.listing.is_none() && now >= self.next
+    }
+
+    /// List `prefix` of `store` in the background: one level, or every blob below when
+    /// `flatten` is set. `folder` names it, to check it is still open when the listing ends.
+    pub fn start(
+        &mut self,
+        folder: String,
+        store: Arc<dyn ObjectStore>,
+        prefix: &str,
+        flatten: bool,
+    ) {
+        let prefix = (!prefix.is_empty()).then(|| ObjectPath::from(prefix));
+        let listing = tokio::spawn(async move {
+            let listed = if flatten {
+                store
+                    .list(prefix.as_ref())
+                    .try_collect()
+                    .await
+                    .map(|objects| ListResult {
+                        common_prefixes: Vec::new(),
+                        objects,
+                    })
+            } else {
+                store.list_with_delimiter(prefix.as_ref()).await
+            };
+            listed.map_err(BlobrsError::from)
+        });
+        self.listing = Some((folder, listing));
+    }
+
+    /// The listing, once it has finished, with the folder it is of. The next one is due an
+    /// interval later.
+    pub fn poll(&mut self) -> Option<(String, Result<ListResult, BlobrsError>)> {
+        if !self
+            .listing
+            .as_ref()
+            .is_some_and(|(_, listing)| listing.is_finished())
+        {
+            return None;
+        }
+        let (folder, listing) = self.listing.take()?;
+        self.next = Instant::now() + self.interval;
+        let listed = listing.now_or_never()?.unwrap_or_else(|e| {
+            Err(if e.is_cancelled() {
+                BlobrsError::Cancelled
+            } else {
+                BlobrsError::Service(e.to_string())
+            })
+        });
+        Some((folder, listed))
+    }
+
+    /// Highlight the entries `diff` found in `folder` from `now`, dropping highlights left in
+    /// another folder.
+    pub fn record(&mut self, folder: &str, diff: &WatchDiff, now: Instant) {
+        if self.folder != folder {
+            self.folder = folder.to_string();
+            self.highlights.clear();
+        }
+        for (name, change) in &diff.changes {
+            self.highlights.insert(name.clone(), (*change, now));
+        }
+    }
+
+    /// How the entry `name` of `folder` changed, if it did within [`HIGHLIGHT_FOR`] of `now`.
+    #[must_use]
+    pub fn highlight(&self, folder: &str, name: &str, now: Instant) -> Option<Change> {
+        if self.folder != folder {
+            return None;
+        }
+        self.highlights
+            .get(name)
+            .filter(|(_, since)| now.duration_since(*since) < HIGHLIGHT_FOR)
+            .map(|(change, _)| *change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use object_store::ObjectStoreExt;
+
+    fn item(name: &str, kind: EntryKind, size: u64, minute: u32) -> FileItem {
+        FileItem {
+            display_name: name.to_string(),
+            actual_name: name.to_string(),
+            kind,
+            size: Some(size),
+            last_modified: Utc.with_ymd_and_hms(2024, 6, 1, 10, minute, 0).single(),
+            created: None,
+            version_id: None,
+            deleted: false,
+            used_size: None,
+        }
+    }
+
+    #[test]
+    fn new_changed_and_gone_entries_are_found() {
+        let before = [
+            item("in", EntryKind::Folder, 0, 0),
+            item("a.csv", EntryKind::File, 1, 0),
+            item("b.csv", EntryKind::File, 1, 0),
+            item("c.csv", EntryKind::File, 1, 0),
+        ];
+        let after = [
+            item("in", EntryKind::Folder, 0, 0),
+            item("a.csv", EntryKind::File, 1, 0),
+            item("b.csv", EntryKind::File, 1, 5),
+            item("d.csv", EntryKind::File, 1, 5),
+            item("out", EntryKind::Folder, 0, 0),
+        ];
+        let diff = WatchDiff::new(&before, &after);
+        assert_eq!(
+            diff.changes,
+            [
+                ("b.csv".to_string(), Change::Changed),
+                ("d.csv".to_string(), Change::New),
+                ("out".to_string(), Change::New),
+            ]
+        );
+        assert_eq!(diff.gone, 1);
+        assert_eq!(diff.summary(), "2 new, 1 changed, 1 gone");
+        assert!(WatchDiff::new(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn highlights_fade_and_stay_in_their_folder() {
+        let mut watch = FolderWatch::new(Duration::from_secs(5));
+        let now = Instant::now();
+        let diff = WatchDiff {
+            changes: vec![("d.csv".to_string(), Change::New)],
+            gone: 0,
+        };
+        watch.record("raw/in/", &diff, now);
+        assert_eq!(watch.highlight("raw/in/", "d.csv", now), Some(Change::New));
+        assert_eq!(watch.highlight("raw/out/", "d.csv", now), None);
+        assert_eq!(
+            watch.highlight("raw/in/", "d.csv", now + HIGHLIGHT_FOR),
+            None
+        );
+
+        watch.record("raw/out/", &WatchDiff::default(), now);
+        assert_eq!(watch.highlight("raw/in/", "d.csv", now), None);
+    }
+
+    #[tokio::test]
+    async fn folders_are_listed_in_the_background() {
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        for path in ["in/a.csv", "in/2024/b.csv"] {
+            store
+                .put(&ObjectPath::from(path), b"x".to_vec().into())
+                .await
+                .unwrap();
+        }
+        let mut watch = FolderWatch::new(Duration::ZERO);
+        assert!(watch.is_due(Instant::now()));
+        watch.start("raw/in/".to_string(), store, "in/", false);
+        assert!(!watch.is_due(Instant::now()));
+        let (folder, listed) = loop {
+            match watch.poll() {
+                Some(polled) => break polled,
+                None => tokio::task::yield_now().await,
+            }
+        };
+        assert_eq!(folder, "raw/in/");
+        let listed = listed.unwrap();
+        assert_eq!(listed.objects.len(), 1);
+        assert_eq!(listed.common_prefixes.len(), 1);
+    }
+}
@@ -0,0 +1,152 @@
+//! A small element tree for the Blob service's XML responses, read with `quick-xml`.
+//!
+//! Responses are a few hundred KiB at most, so building the whole tree is cheaper to read
+//! and maintain than streaming each response type by hand.
+
+use crate::error::BlobrsError;
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+
+/// An XML element with its text and child elements. Attributes are not kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Element {
+    pub name: String,
+    /// Text directly inside the element, with entities and CDATA resolved.
+    pub text: String,
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    fn new(start: &BytesStart<'_>) -> Self {
+        Self {
+            name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+            ..Self::default()
+        }
+    }
+
+    /// The first child element called `name`.
+    #[must_use]
+    pub fn child(&self, name: &str) -> Option<&Self> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Every child element called `name`, in document order.
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Self> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// The element reached by following `path` from this one.
+    #[must_use]
+    pub fn find(&self, path: &[&str]) -> Option<&Self> {
+        path.iter()
+            .try_fold(self, |element, name| element.child(name))
+    }
+
+    /// Text of the first child called `name`.
+    #[must_use]
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.as_str())
+    }
+
+    /// Text of the first child called `name`, unless it is missing or empty.
+    #[must_use]
+    pub fn non_empty(&self, name: &str) -> Option<String> {
+        self.value(name)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    }
+}
+
+/// Parse a document into its root element.
+///
+/// # Errors
+///
+/// Returns [`BlobrsError::Parse`] for malformed XML or a document without a root element.
+pub fn parse(xml: &str) -> Result<Element, BlobrsError> {
+    let parse_error = |e: &dyn std::fmt::Display| BlobrsError::Parse(format!("invalid XML: {e}"));
+    let mut reader = Reader::from_str(xml);
+    // The document itself, holding the root element once it is closed
+    let mut stack = vec![Element::default()];
+
+    loop {
+        let event = reader.read_event().map_err(|e| parse_error(&e))?;
+        let Some(current) = stack.last_mut() else {
+            return Err(parse_error(&"unbalanced end tag"));
+        };
+        match event {
+            Event::Start(start) => stack.push(Element::new(&start)),
+            Event::Empty(start) => current.children.push(Element::new(&start)),
+            Event::End(_) => {
+                let element = stack.pop().filter(|_| !stack.is_empty());
+                match (element, stack.last_mut()) {
+                    (Some(element), Some(parent)) => parent.children.push(element),
+                    _ => return Err(parse_error(&"unbalanced end tag")),
+                }
+            }
+            Event::Text(text) => current
+                .text
+                .push_str(&text.decode().map_err(|e| parse_error(&e))?),
+            Event::CData(data) => current
+                .text
+                .push_str(&data.decode().map_err(|e| parse_error(&e))?),
+            Event::GeneralRef(reference) => {
+                if let Some(c) = reference.resolve_char_ref().map_err(|e| parse_error(&e))? {
+                    current.text.push(c);
+                } else {
+                    let name = reference.decode().map_err(|e| parse_error(&e))?;
+                    let resolved = resolve_predefined_entity(&name)
+                        .ok_or_else(|| parse_error(&format!("unknown entity &{name};")))?;
+                    current.text.push_str(resolved);
+                }
+            }
+            Event::Eof => break,
+            Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+        }
+    }
+
+    match stack.pop() {
+        Some(document) if stack.is_empty() => document
+            .children
+            .into_iter()
+            .next()
+            .ok_or_else(|| parse_error(&"no root element")),
+        _ => Err(parse_error(&"unclosed element")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_elements_text_and_entities() {
+        let root = parse(
+            "\u{feff}<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <Results a=\"1\">\n  <Item>\n    <Name>a &amp; b&#33;</Name>\n    <Empty />\n  </Item>\n\
+             <Item><Name><![CDATA[<raw>]]></Name></Item>\n  <Marker/>\n</Results>",
+        )
+        .unwrap();
+
+        assert_eq!(root.name, "Results");
+        let names: Vec<_> = root
+            .children("Item")
+            .filter_map(|item| item.value("Name"))
+            .collect();
+        assert_eq!(names, ["a & b!", "<raw>"]);
+        assert_eq!(
+            root.find(&["Item", "Empty"]).map(|e| e.text.as_str()),
+            Some("")
+        );
+        assert_eq!(root.non_empty("Marker"), None);
+        assert_eq!(root.find(&["Item", "Missing"]), None);
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(parse("<a><b></a>").is_err());
+        assert!(parse("<a>").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("<a>&bogus;</a>").is_err());
+    }
+}
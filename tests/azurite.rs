@@ -0,0 +1,22 @@
+//! End-to-end test against a local Azurite emulator.
+//!
+//! Start Azurite, then run `cargo test --features azurite --test azurite`. Set
+//! `AZURITE_BLOB_STORAGE_URL` if it is not listening on `http://127.0.0.1:10000`.
+#![cfg(feature = "azurite")]
+
+use std::process::Command;
+
+#[test]
+fn self_test_passes_against_azurite() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blobrs"))
+        .arg("--self-test")
+        .output()
+        .expect("failed to run blobrs");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "self-test failed\n{stdout}\n{stderr}"
+    );
+    assert!(stdout.contains("Self-test passed"), "{stdout}");
+}